@@ -0,0 +1,167 @@
+//! compares reading a large evtx file using [evtx::EvtxParser] with reading
+//! it using [dfir_toolkit::evtx::EvtxFile]. Every variant runs in a process
+//! of its own, so that its peak memory usage can be measured.
+//!
+//! The fixture is created from the chunk of `tests/data/evtx/security.evtx`,
+//! which is repeated `EVTX_BENCH_CHUNKS` times (default: 2048, which results
+//! in a file of 128 MiB). Run it using
+//!
+//! ```shell
+//! EVTX_BENCH_CHUNKS=32768 cargo bench --bench evtx_file
+//! ```
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use dfir_toolkit::evtx::EvtxFile;
+use evtx::{EvtxParser, ParserSettings};
+
+const FILE_HEADER_SIZE: usize = 4096;
+const VARIANT_VAR: &str = "EVTX_BENCH_VARIANT";
+const FIXTURE_VAR: &str = "EVTX_BENCH_FIXTURE";
+
+const VARIANTS: &[(&str, &str)] = &[
+    ("parser", "EvtxParser::from_path (before)"),
+    (
+        "parser-buffer",
+        "EvtxParser::from_buffer (before, full copy)",
+    ),
+    ("file", "EvtxFile::open (after)"),
+    ("file-headers", "EvtxFile::record_headers (after)"),
+];
+
+fn main() {
+    match (std::env::var(VARIANT_VAR), std::env::var(FIXTURE_VAR)) {
+        (Ok(variant), Ok(fixture)) => run_variant(&variant, Path::new(&fixture)),
+        _ => run_all(),
+    }
+}
+
+fn run_all() {
+    let chunks: usize = std::env::var("EVTX_BENCH_CHUNKS")
+        .ok()
+        .and_then(|chunks| chunks.parse().ok())
+        .unwrap_or(2048);
+    let dir = tempfile::tempdir().expect("unable to create temporary directory");
+    let fixture = create_fixture(dir.path(), chunks);
+    let size = std::fs::metadata(&fixture).unwrap().len();
+    println!("fixture: {chunks} chunks, {} MiB", size / 1024 / 1024);
+    println!(
+        "{:<45} {:>10} {:>10} {:>10} {:>10}",
+        "variant", "records", "time (ms)", "RSS (MiB)", "anon (MiB)"
+    );
+
+    let exe = std::env::current_exe().unwrap();
+    for (variant, description) in VARIANTS {
+        let output = Command::new(&exe)
+            .env(VARIANT_VAR, variant)
+            .env(FIXTURE_VAR, &fixture)
+            .output()
+            .expect("unable to run benchmark");
+        assert!(output.status.success(), "variant '{variant}' failed");
+
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        let fields: Vec<_> = stdout.split_whitespace().collect();
+        let mib = |kb: &str| {
+            kb.parse::<u64>()
+                .map(|kb| format!("{:.1}", kb as f64 / 1024.0))
+                .unwrap_or_else(|_| "n/a".to_owned())
+        };
+        println!(
+            "{description:<45} {:>10} {:>10} {:>10} {:>10}",
+            fields[0],
+            fields[1],
+            mib(fields[2]),
+            mib(fields[3])
+        );
+    }
+}
+
+fn create_fixture(dir: &Path, chunks: usize) -> PathBuf {
+    let sample = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("data")
+        .join("evtx")
+        .join("security.evtx");
+    let data = std::fs::read(sample).unwrap();
+    let (header, chunk) = data.split_at(FILE_HEADER_SIZE);
+
+    let path = dir.join("large.evtx");
+    let mut file = std::io::BufWriter::new(std::fs::File::create(&path).unwrap());
+    file.write_all(header).unwrap();
+    for _ in 0..chunks {
+        file.write_all(chunk).unwrap();
+    }
+    file.flush().unwrap();
+    path
+}
+
+/// reads the fixture and prints the number of records, the elapsed time in
+/// milliseconds, the peak resident set size and the peak size of anonymous
+/// memory in KiB. Pages of a mapped file count to the resident set size, but
+/// in contrast to anonymous memory, the kernel can drop them at any time.
+fn run_variant(variant: &str, fixture: &Path) {
+    let peak_anon = Arc::new(AtomicU64::new(0));
+    let done = Arc::new(AtomicBool::new(false));
+    let sampler = {
+        let peak_anon = Arc::clone(&peak_anon);
+        let done = Arc::clone(&done);
+        std::thread::spawn(move || {
+            while !done.load(Ordering::Relaxed) {
+                if let Some(kb) = status_value("RssAnon:") {
+                    peak_anon.fetch_max(kb, Ordering::Relaxed);
+                }
+                std::thread::sleep(Duration::from_millis(2));
+            }
+        })
+    };
+
+    let settings = ParserSettings::default().num_threads(0);
+    let start = Instant::now();
+    let records = match variant {
+        "parser" => EvtxParser::from_path(fixture)
+            .unwrap()
+            .with_configuration(settings)
+            .records_json_value()
+            .filter(Result::is_ok)
+            .count(),
+        "parser-buffer" => EvtxParser::from_buffer(std::fs::read(fixture).unwrap())
+            .unwrap()
+            .with_configuration(settings)
+            .records_json_value()
+            .filter(Result::is_ok)
+            .count(),
+        "file" => EvtxFile::open(fixture)
+            .unwrap()
+            .with_configuration(settings)
+            .records_json_value()
+            .filter(Result::is_ok)
+            .count(),
+        "file-headers" => EvtxFile::open(fixture).unwrap().record_headers().count(),
+        _ => panic!("unknown variant '{variant}'"),
+    };
+    let elapsed = start.elapsed().as_millis();
+    done.store(true, Ordering::Relaxed);
+    sampler.join().unwrap();
+
+    let peak_rss = status_value("VmHWM:").map_or_else(|| "n/a".to_owned(), |kb| kb.to_string());
+    let peak_anon = match peak_anon.load(Ordering::Relaxed) {
+        0 => "n/a".to_owned(),
+        kb => kb.to_string(),
+    };
+    println!("{records} {elapsed} {peak_rss} {peak_anon}");
+}
+
+/// reads a value in KiB from the status of this process, which is only
+/// available on Linux
+fn status_value(name: &str) -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix(name))
+        .and_then(|value| value.trim().trim_end_matches("kB").trim().parse().ok())
+}
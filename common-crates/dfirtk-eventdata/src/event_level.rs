@@ -5,13 +5,33 @@ use evtx::SerializedEvtxRecord;
 use serde::Serialize;
 use serde_json::Value;
 
+/// Level of an event. The levels are ordered from the most to the least
+/// severe level, so that `LogAlways` events pass every minimum level.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
 pub enum EventLevel {
+    #[cfg_attr(feature = "clap", value(skip))]
     LogAlways,
+
+    /// Level 1
     Critical,
+
+    /// Level 2
     Error,
+
+    /// Level 3
     Warning,
+
+    /// Level 4
     Information,
+
+    /// Level 5
+    Verbose,
+
+    #[cfg_attr(feature = "clap", value(skip))]
     AuditSuccess,
+
+    #[cfg_attr(feature = "clap", value(skip))]
     AuditFailure,
 }
 
@@ -21,7 +41,11 @@ impl TryFrom<&SerializedEvtxRecord<Value>> for EventLevel {
     fn try_from(
         value: &SerializedEvtxRecord<Value>,
     ) -> Result<Self, <EventLevel as TryFrom<&SerializedEvtxRecord<Value>>>::Error> {
-        match value.data["Event"]["System"]["Level"].as_u64() {
+        let level = &value.data["Event"]["System"]["Level"];
+        match level
+            .as_u64()
+            .or_else(|| level.as_str().and_then(|l| l.parse().ok()))
+        {
             Some(level_id) => Self::try_from(level_id),
             None => Err(anyhow!(
                 "missing event level in '{data}'",
@@ -41,7 +65,7 @@ impl TryFrom<u64> for EventLevel {
             2 => EventLevel::Error,
             3 => EventLevel::Warning,
             4 => EventLevel::Information,
-            5 => EventLevel::LogAlways,
+            5 => EventLevel::Verbose,
             _ => return Err(anyhow!("unknown log level identifier: {value}")),
         })
     }
@@ -55,6 +79,7 @@ impl Display for EventLevel {
             EventLevel::Error => f.write_char('🔥'),
             EventLevel::Warning => f.write_str("⚠️"),
             EventLevel::Information => f.write_str("ℹ️"),
+            EventLevel::Verbose => f.write_char('💬'),
             EventLevel::AuditSuccess => f.write_char('🙂'),
             EventLevel::AuditFailure => f.write_char('😡'),
         }
@@ -72,6 +97,7 @@ impl Serialize for EventLevel {
             EventLevel::Error => serializer.serialize_char('🔥'),
             EventLevel::Warning => serializer.serialize_str("⚠️"),
             EventLevel::Information => serializer.serialize_str("ℹ️"),
+            EventLevel::Verbose => serializer.serialize_char('💬'),
             EventLevel::AuditSuccess => serializer.serialize_char('🙂'),
             EventLevel::AuditFailure => serializer.serialize_char('😡'),
         }
@@ -0,0 +1,219 @@
+use std::io::Read;
+
+use anyhow::{bail, Result};
+
+/// size of the part of a base block which is read, up to and including the
+/// checksum
+pub(crate) const BASE_BLOCK_HEADER_SIZE: usize = 512;
+
+/// offset of the checksum, which covers all preceding bytes
+const CHECKSUM_OFFSET: usize = 0x1fc;
+
+/// offset and size of the partial path of the primary file
+const FILE_NAME_OFFSET: usize = 0x30;
+const FILE_NAME_SIZE: usize = 64;
+
+/// file type of primary files
+pub(crate) const FILE_TYPE_PRIMARY: u32 = 0;
+
+/// file type of transaction logs in the new format, which is used since
+/// Windows 8.1
+pub(crate) const FILE_TYPE_TRANSACTION_LOG: u32 = 6;
+
+/// the fields of a base block which are needed to decide whether a hive is
+/// dirty and which transaction logs belong to it. The base block is parsed
+/// without validating it, so that its errors can be reported
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct BaseBlockHeader {
+    pub(crate) has_signature: bool,
+    pub(crate) primary_sequence_number: u32,
+    pub(crate) secondary_sequence_number: u32,
+    pub(crate) timestamp: u64,
+    pub(crate) file_type: u32,
+    pub(crate) file_name: String,
+    pub(crate) stored_checksum: u32,
+    pub(crate) calculated_checksum: u32,
+}
+
+impl BaseBlockHeader {
+    /// reads the header of the base block at the beginning of a hive file or
+    /// a transaction log file
+    pub(crate) fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let mut data = [0; BASE_BLOCK_HEADER_SIZE];
+        if let Err(why) = reader.read_exact(&mut data) {
+            bail!("unable to read the base block: {why}");
+        }
+        Self::parse(&data)
+    }
+
+    pub(crate) fn parse(data: &[u8]) -> Result<Self> {
+        if data.len() < BASE_BLOCK_HEADER_SIZE {
+            bail!(
+                "the base block is truncated to {} bytes, but it needs {BASE_BLOCK_HEADER_SIZE} bytes",
+                data.len()
+            );
+        }
+        let file_name: Vec<u16> = data[FILE_NAME_OFFSET..FILE_NAME_OFFSET + FILE_NAME_SIZE]
+            .chunks_exact(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]))
+            .collect();
+        Ok(Self {
+            has_signature: &data[0..4] == b"regf",
+            primary_sequence_number: u32_at(data, 0x04),
+            secondary_sequence_number: u32_at(data, 0x08),
+            timestamp: u64::from(u32_at(data, 0x0c)) | (u64::from(u32_at(data, 0x10)) << 32),
+            file_type: u32_at(data, 0x1c),
+            file_name: file_name_from(&file_name),
+            stored_checksum: u32_at(data, CHECKSUM_OFFSET),
+            calculated_checksum: checksum(&data[..CHECKSUM_OFFSET]),
+        })
+    }
+
+    pub(crate) fn is_dirty(&self) -> bool {
+        self.primary_sequence_number != self.secondary_sequence_number
+    }
+
+    pub(crate) fn has_valid_checksum(&self) -> bool {
+        self.stored_checksum == self.calculated_checksum
+    }
+
+    /// a base block is valid if it has a signature and a correct checksum
+    pub(crate) fn is_valid(&self) -> bool {
+        self.has_signature && self.has_valid_checksum()
+    }
+
+    /// returns the reason why the base block of a transaction log does not
+    /// seem to belong to the hive with the base block `hive`, or `None` if
+    /// their file names and timestamps match
+    pub(crate) fn mismatch(&self, hive: &Self) -> Option<String> {
+        if !self.is_valid() {
+            return Some("it has no valid base block".to_owned());
+        }
+        if !self.file_name.is_empty()
+            && !hive.file_name.is_empty()
+            && self.file_name != hive.file_name
+        {
+            return Some(format!(
+                "it belongs to the hive '{}', but the name of the hive is '{}'",
+                self.file_name, hive.file_name
+            ));
+        }
+        if self.timestamp != hive.timestamp {
+            return Some(format!(
+                "its timestamp {} differs from the timestamp {} of the hive",
+                self.timestamp, hive.timestamp
+            ));
+        }
+        None
+    }
+}
+
+/// decodes the partial path of the primary file, which is stored in a base
+/// block
+pub(crate) fn file_name_from(name: &[u16]) -> String {
+    let len = name.iter().position(|c| *c == 0).unwrap_or(name.len());
+    String::from_utf16_lossy(&name[..len])
+}
+
+/// the XOR-32 checksum of a base block. The values 0 and 0xffffffff are
+/// never used as checksums
+fn checksum(data: &[u8]) -> u32 {
+    let checksum = data
+        .chunks_exact(4)
+        .map(|dword| u32::from_le_bytes([dword[0], dword[1], dword[2], dword[3]]))
+        .fold(0, |checksum, dword| checksum ^ dword);
+    match checksum {
+        0 => 1,
+        0xffff_ffff => 0xffff_fffe,
+        checksum => checksum,
+    }
+}
+
+fn u32_at(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{checksum, BaseBlockHeader, BASE_BLOCK_HEADER_SIZE, CHECKSUM_OFFSET};
+
+    fn base_block(primary: u32, secondary: u32, name: &str) -> Vec<u8> {
+        let mut data = vec![0; BASE_BLOCK_HEADER_SIZE];
+        data[0..4].copy_from_slice(b"regf");
+        data[4..8].copy_from_slice(&primary.to_le_bytes());
+        data[8..12].copy_from_slice(&secondary.to_le_bytes());
+        data[12..20].copy_from_slice(&0x01d3_1f4a_1234_5678u64.to_le_bytes());
+        data[0x14..0x18].copy_from_slice(&1u32.to_le_bytes());
+        data[0x18..0x1c].copy_from_slice(&5u32.to_le_bytes());
+        for (i, c) in name.encode_utf16().enumerate() {
+            data[0x30 + 2 * i..0x32 + 2 * i].copy_from_slice(&c.to_le_bytes());
+        }
+        let checksum = checksum(&data[..CHECKSUM_OFFSET]);
+        data[CHECKSUM_OFFSET..CHECKSUM_OFFSET + 4].copy_from_slice(&checksum.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn parse_base_block() {
+        let header =
+            BaseBlockHeader::parse(&base_block(3, 2, "\\??\\C:\\Users\\NTUSER.DAT")).unwrap();
+        assert!(header.has_signature);
+        assert!(header.is_valid());
+        assert!(header.is_dirty());
+        assert_eq!(header.primary_sequence_number, 3);
+        assert_eq!(header.secondary_sequence_number, 2);
+        assert_eq!(header.timestamp, 0x01d3_1f4a_1234_5678);
+        assert_eq!(header.file_type, 0);
+        assert_eq!(header.file_name, "\\??\\C:\\Users\\NTUSER.DAT");
+
+        let header = BaseBlockHeader::parse(&base_block(7, 7, "")).unwrap();
+        assert!(!header.is_dirty());
+        assert_eq!(header.file_name, "");
+    }
+
+    #[test]
+    fn detect_invalid_base_blocks() {
+        let mut data = base_block(3, 2, "SYSTEM");
+        data[4] = 4;
+        let header = BaseBlockHeader::parse(&data).unwrap();
+        assert!(header.has_signature);
+        assert!(!header.has_valid_checksum());
+        assert!(!header.is_valid());
+
+        let mut data = base_block(3, 2, "SYSTEM");
+        data[0] = b'x';
+        assert!(!BaseBlockHeader::parse(&data).unwrap().has_signature);
+
+        assert!(BaseBlockHeader::parse(&data[..100]).is_err());
+    }
+
+    #[test]
+    fn avoid_reserved_checksums() {
+        assert_eq!(checksum(&[0; 8]), 1);
+        assert_eq!(checksum(&[0xff, 0xff, 0xff, 0xff, 0, 0, 0, 0]), 0xffff_fffe);
+        assert_eq!(checksum(&[1, 0, 0, 0, 2, 0, 0, 0]), 3);
+    }
+
+    #[test]
+    fn compare_base_blocks() {
+        let hive = BaseBlockHeader::parse(&base_block(3, 2, "NewDirtyHive")).unwrap();
+        let log = BaseBlockHeader::parse(&base_block(2, 2, "NewDirtyHive")).unwrap();
+        assert_eq!(log.mismatch(&hive), None);
+
+        let log = BaseBlockHeader::parse(&base_block(2, 2, "")).unwrap();
+        assert_eq!(log.mismatch(&hive), None);
+
+        let log = BaseBlockHeader::parse(&base_block(2, 2, "OtherHive")).unwrap();
+        assert!(log.mismatch(&hive).unwrap().contains("'OtherHive'"));
+
+        let mut log = BaseBlockHeader::parse(&base_block(2, 2, "NewDirtyHive")).unwrap();
+        log.timestamp += 1;
+        assert!(log.mismatch(&hive).unwrap().contains("timestamp"));
+
+        log.stored_checksum += 1;
+        assert_eq!(
+            log.mismatch(&hive).as_deref(),
+            Some("it has no valid base block")
+        );
+    }
+}
@@ -0,0 +1,198 @@
+use std::{io::Read, path::PathBuf};
+
+use anyhow::Result;
+use dfir_toolkit::common::FormattableDatetime;
+use winstructs::timestamp::WinTimestamp;
+
+use crate::{
+    base_block::{BaseBlockHeader, FILE_TYPE_PRIMARY},
+    transaction_logs::{read_logs, replay_order, LogFile},
+};
+
+/// exit code of `--check` if the hive is dirty, but can be recovered with the
+/// transaction logs. The exit codes 1 and 2 are used for errors and invalid
+/// arguments
+pub(crate) const EXIT_DIRTY: i32 = 3;
+
+/// exit code of `--check` if the hive is dirty and cannot be recovered, or
+/// if its base block is invalid
+pub(crate) const EXIT_CORRUPTED: i32 = 4;
+
+/// the result of checking a hive and its transaction logs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum HiveState {
+    Clean,
+    Recoverable,
+    Corrupted,
+}
+
+impl HiveState {
+    pub(crate) fn exit_code(&self) -> i32 {
+        match self {
+            HiveState::Clean => 0,
+            HiveState::Recoverable => EXIT_DIRTY,
+            HiveState::Corrupted => EXIT_CORRUPTED,
+        }
+    }
+}
+
+/// reports the state of the hive and its transaction logs, and what
+/// applying the logs would do, without changing anything
+pub(crate) fn check_hive<R: Read>(hive: &mut R, logfiles: &[PathBuf]) -> Result<HiveState> {
+    let base_block = match BaseBlockHeader::read(hive) {
+        Ok(base_block) => base_block,
+        Err(why) => {
+            println!("hive: {why}");
+            return Ok(HiveState::Corrupted);
+        }
+    };
+
+    println!("hive:");
+    print_base_block(&base_block);
+    if base_block.file_type != FILE_TYPE_PRIMARY {
+        println!(
+            "  file type:        {} (expected {FILE_TYPE_PRIMARY})",
+            base_block.file_type
+        );
+    }
+
+    let mut logs = read_logs(logfiles)?;
+    for log in logs.iter() {
+        println!("transaction log '{}':", log.path.display());
+        match &log.base_block {
+            Some(log_base_block) => print_base_block(log_base_block),
+            None => println!("  base block:       unreadable"),
+        }
+        print_sequence_range(log);
+        match log.mismatch(&base_block) {
+            Some(reason) => println!("  belongs to hive:  no, {reason}"),
+            None => println!("  belongs to hive:  yes"),
+        }
+    }
+
+    let state = recovery(&base_block, &mut logs);
+    println!(
+        "state: {}",
+        match state {
+            HiveState::Clean => "clean",
+            HiveState::Recoverable => "dirty, but recoverable",
+            HiveState::Corrupted => "corrupted",
+        }
+    );
+    Ok(state)
+}
+
+/// describes what a recovery would do, and decides whether the hive can be
+/// recovered
+fn recovery(base_block: &BaseBlockHeader, logs: &mut [LogFile]) -> HiveState {
+    if !base_block.is_valid() {
+        println!("recovery: impossible, because the base block of the hive is invalid");
+        return HiveState::Corrupted;
+    }
+    if !base_block.is_dirty() {
+        println!("recovery: not needed");
+        return HiveState::Clean;
+    }
+    if logs.is_empty() {
+        println!("recovery: impossible, because no transaction logs have been found");
+        return HiveState::Corrupted;
+    }
+
+    let foreign: Vec<_> = logs
+        .iter()
+        .filter(|log| log.mismatch(base_block).is_some())
+        .map(|log| format!("'{}'", log.path.display()))
+        .collect();
+    if !foreign.is_empty() {
+        println!(
+            "recovery: refused without '--force', because {} {} to belong to a different hive",
+            foreign.join(" and "),
+            if foreign.len() == 1 { "seems" } else { "seem" }
+        );
+        return HiveState::Corrupted;
+    }
+
+    let replay = replay_order(logs, base_block.secondary_sequence_number);
+    for (index, entry) in replay.entries.iter() {
+        logs[*index].applied_entries += 1;
+        logs[*index].dirty_pages += entry.dirty_pages().len();
+    }
+    if let Some((expected, found)) = replay.gap {
+        println!(
+            "recovery: the log entry with sequence number {expected} is missing, so the \
+             entries beginning with sequence number {found} would not be applied"
+        );
+    }
+    if replay.entries.is_empty() {
+        println!(
+            "recovery: impossible, because the transaction logs contain no entry with \
+             sequence number {}",
+            base_block.secondary_sequence_number.wrapping_add(1)
+        );
+        return HiveState::Corrupted;
+    }
+    for log in logs.iter().filter(|log| log.applied_entries > 0) {
+        println!(
+            "recovery: apply {} log entries ({} dirty pages) from '{}'",
+            log.applied_entries,
+            log.dirty_pages,
+            log.path.display()
+        );
+    }
+    HiveState::Recoverable
+}
+
+fn print_base_block(base_block: &BaseBlockHeader) {
+    println!(
+        "  signature:        {}",
+        if base_block.has_signature {
+            "valid"
+        } else {
+            "invalid"
+        }
+    );
+    if base_block.has_valid_checksum() {
+        println!(
+            "  checksum:         valid (0x{:08x})",
+            base_block.stored_checksum
+        );
+    } else {
+        println!(
+            "  checksum:         invalid (stored 0x{:08x}, calculated 0x{:08x})",
+            base_block.stored_checksum, base_block.calculated_checksum
+        );
+    }
+    println!(
+        "  sequence numbers: {} and {} ({})",
+        base_block.primary_sequence_number,
+        base_block.secondary_sequence_number,
+        if base_block.is_dirty() {
+            "different"
+        } else {
+            "equal"
+        }
+    );
+    match WinTimestamp::new(&base_block.timestamp.to_le_bytes()) {
+        Ok(timestamp) => println!(
+            "  last written:     {}",
+            FormattableDatetime::from(timestamp.to_datetime())
+        ),
+        Err(_) => println!("  last written:     0x{:016x}", base_block.timestamp),
+    }
+    println!("  file name:        {}", base_block.file_name);
+}
+
+fn print_sequence_range(log: &LogFile) {
+    let sequence_numbers: Vec<_> = log
+        .entries
+        .iter()
+        .map(|entry| *entry.sequence_number())
+        .collect();
+    match (sequence_numbers.iter().min(), sequence_numbers.iter().max()) {
+        (Some(first), Some(last)) => println!(
+            "  log entries:      {} (sequence numbers {first} to {last})",
+            sequence_numbers.len()
+        ),
+        _ => println!("  log entries:      none"),
+    }
+}
@@ -1,6 +1,9 @@
+use std::path::PathBuf;
+
 use clap::{Parser, ValueHint};
 use clio::*;
-use dfir_toolkit::common::HasVerboseFlag;
+use dfir_toolkit::common::{HasVerboseFlag, Verbosity};
+use dfir_toolkit::registry::find_transaction_logs;
 use log::LevelFilter;
 
 /// merges logfiles into a hive file
@@ -12,17 +15,59 @@ pub(crate) struct Cli {
     pub(crate) hive_file: Input,
 
     /// transaction LOG file(s). This argument can be specified one or two times.
+    /// By default, the files with the extensions '.LOG1' and '.LOG2' next to
+    /// the hive file are used, if the hive is dirty
     #[clap(short('L'), long("log"), value_parser, value_hint=ValueHint::FilePath)]
     pub(crate) logfiles: Vec<InputPath>,
 
+    /// don't apply any transaction logs, but treat the hive as if it was clean
+    #[clap(long("no-logs"), conflicts_with = "logfiles")]
+    pub(crate) no_logs: bool,
+
+    /// apply the transaction logs even if they seem to belong to a different
+    /// hive, because their file names or timestamps differ from those of the hive
+    #[clap(long("force"))]
+    pub(crate) force: bool,
+
+    /// don't write anything, but report the state of the hive and its
+    /// transaction logs, and what applying the logs would do. The exit code
+    /// is 0 if the hive is clean, 3 if it is dirty but can be recovered, and
+    /// 4 if it is corrupted or cannot be recovered
+    #[clap(long("check"), conflicts_with_all = ["no_logs", "dst_hive"])]
+    pub(crate) check: bool,
+
     #[clap(flatten)]
-    verbose: clap_verbosity_flag::Verbosity,
+    verbose: Verbosity,
 
     /// name of the file to which the cleaned hive will be written.
     #[clap(short('O'), long("output"), default_value="-", value_hint=ValueHint::FilePath, value_parser)]
     pub(crate) dst_hive: Output,
 }
 
+impl Cli {
+    /// returns the transaction logs which have been specified with '--log',
+    /// or the logs which have been found next to the hive file
+    pub(crate) fn transaction_logs(&self) -> anyhow::Result<Vec<PathBuf>> {
+        if self.logfiles.is_empty() {
+            if self.hive_file.is_std() {
+                Ok(Vec::new())
+            } else {
+                Ok(find_transaction_logs(self.hive_file.path()))
+            }
+        } else {
+            self.logfiles
+                .iter()
+                .map(|logfile| {
+                    if logfile.is_std() {
+                        anyhow::bail!("transaction logs cannot be read from stdin")
+                    }
+                    Ok(logfile.path().to_path_buf())
+                })
+                .collect()
+        }
+    }
+}
+
 impl HasVerboseFlag for Cli {
     fn log_level_filter(&self) -> LevelFilter {
         self.verbose.log_level_filter()
@@ -0,0 +1,249 @@
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::Seek,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+use binread::BinReaderExt;
+use nt_hive2::{
+    transactionlog::{ApplicationResult, TransactionLog, TransactionLogsEntry},
+    BaseBlock, CleanHive, DirtyHive, Hive,
+};
+
+use crate::base_block::{
+    file_name_from, BaseBlockHeader, FILE_TYPE_PRIMARY, FILE_TYPE_TRANSACTION_LOG,
+};
+
+/// a transaction log file, together with the number of log entries and dirty
+/// pages which have been applied from it
+pub(crate) struct LogFile {
+    pub(crate) path: PathBuf,
+
+    /// `None` if the base block could not be read at all
+    pub(crate) base_block: Option<BaseBlockHeader>,
+    pub(crate) entries: Vec<TransactionLogsEntry>,
+    pub(crate) applied_entries: usize,
+    pub(crate) dirty_pages: usize,
+}
+
+impl LogFile {
+    pub(crate) fn read(path: &Path) -> Result<Self> {
+        let mut file =
+            File::open(path).with_context(|| format!("unable to open '{}'", path.display()))?;
+        let base_block = match BaseBlockHeader::read(&mut file) {
+            Ok(base_block) => Some(base_block),
+            Err(why) => {
+                log::warn!("{}: {why}", path.display());
+                None
+            }
+        };
+        file.rewind()?;
+        let log = TransactionLog::try_from(file)
+            .with_context(|| format!("unable to read the transaction log '{}'", path.display()))?;
+        Ok(Self {
+            path: path.to_owned(),
+            base_block,
+            entries: log.into(),
+            applied_entries: 0,
+            dirty_pages: 0,
+        })
+    }
+
+    /// the primary sequence number of the base block of the log, which is
+    /// the sequence number of the last write to this log
+    fn sequence_number(&self) -> u32 {
+        self.base_block
+            .as_ref()
+            .map(|base_block| base_block.primary_sequence_number)
+            .unwrap_or_default()
+    }
+
+    /// returns the reason why this log does not seem to belong to the hive,
+    /// or `None` if the file name and the timestamp in its base block match
+    /// those of the hive
+    pub(crate) fn mismatch(&self, hive: &BaseBlockHeader) -> Option<String> {
+        match &self.base_block {
+            None => Some("it has no valid base block".to_owned()),
+            Some(base_block) if base_block.file_type != FILE_TYPE_TRANSACTION_LOG => Some(format!(
+                "it is no transaction log in the new format (file type {})",
+                base_block.file_type
+            )),
+            Some(base_block) => base_block.mismatch(hive),
+        }
+    }
+}
+
+/// the log entries which can be replayed into a hive, in the order of their
+/// sequence numbers
+pub(crate) struct Replay {
+    /// the log entries, together with the indices of the logs which contain them
+    pub(crate) entries: Vec<(usize, TransactionLogsEntry)>,
+
+    /// the expected and the found sequence number, if the sequence numbers
+    /// of the log entries have a gap
+    pub(crate) gap: Option<(u32, u32)>,
+}
+
+/// reads the transaction log files. At most two logs are supported
+pub(crate) fn read_logs(logfiles: &[PathBuf]) -> Result<Vec<LogFile>> {
+    if logfiles.len() > 2 {
+        bail!("more than two transaction log files are not supported")
+    }
+    logfiles
+        .iter()
+        .map(|logfile| LogFile::read(logfile))
+        .collect()
+}
+
+/// determines the order in which the log entries are replayed, beginning with
+/// the entry which follows the secondary sequence number `secondary` of the
+/// hive. The logs are sorted by the sequence numbers of their base blocks,
+/// and if both logs contain an entry with the same sequence number, the entry
+/// of the log which has been written last is used. The log entries are moved
+/// out of the logs
+pub(crate) fn replay_order(logs: &mut [LogFile], secondary: u32) -> Replay {
+    logs.sort_by_key(LogFile::sequence_number);
+    let mut entries: BTreeMap<u32, (usize, TransactionLogsEntry)> = BTreeMap::new();
+    for (index, log) in logs.iter_mut().enumerate() {
+        for entry in std::mem::take(&mut log.entries) {
+            if *entry.sequence_number() > secondary {
+                entries.insert(*entry.sequence_number(), (index, entry));
+            }
+        }
+    }
+
+    let mut expected = secondary.wrapping_add(1);
+    let mut replay = Replay {
+        entries: Vec::with_capacity(entries.len()),
+        gap: None,
+    };
+    for (sequence_number, entry) in entries {
+        if sequence_number != expected {
+            replay.gap = Some((expected, sequence_number));
+            break;
+        }
+        replay.entries.push(entry);
+        expected = expected.wrapping_add(1);
+    }
+    replay
+}
+
+/// replays the log entries of the transaction logs into the hive, in the
+/// order which is determined by [replay_order]. A gap between the sequence
+/// numbers stops the replay, and a warning is printed.
+///
+/// Logs whose base blocks do not match the base block of the hive are
+/// refused, unless `force` is set
+pub(crate) fn apply_transaction_logs<B>(
+    mut hive: Hive<B, DirtyHive>,
+    logfiles: &[PathBuf],
+    force: bool,
+) -> Result<Hive<B, CleanHive>>
+where
+    B: BinReaderExt,
+{
+    let Some(base_block) = hive.base_block().map(|base_block| BaseBlockHeader {
+        has_signature: true,
+        primary_sequence_number: *base_block.primary_sequence_number(),
+        secondary_sequence_number: *base_block.secondary_sequence_number(),
+        timestamp: *base_block.timestamp(),
+        file_type: FILE_TYPE_PRIMARY,
+        file_name: file_name_from(base_block.file_name()),
+        stored_checksum: base_block.checksum,
+        calculated_checksum: base_block.checksum,
+    }) else {
+        log::warn!("the hive has no base block, treating it as if it was clean");
+        return Ok(hive.treat_hive_as_clean());
+    };
+    let primary = base_block.primary_sequence_number;
+    let secondary = base_block.secondary_sequence_number;
+
+    if primary == secondary {
+        log::info!("the hive is clean (sequence number {primary}), no transaction log is needed");
+        return Ok(hive.treat_hive_as_clean());
+    }
+
+    if logfiles.is_empty() {
+        log::warn!(
+            "the hive is dirty (sequence numbers {primary} and {secondary}), but no \
+             transaction logs have been found. Its contents may be stale or inconsistent"
+        );
+        return Ok(hive.treat_hive_as_clean());
+    }
+
+    if primary != secondary.wrapping_add(1) {
+        log::warn!(
+            "the sequence numbers of the hive differ by more than one ({primary} and \
+             {secondary}). Its contents may be inconsistent, even after applying the \
+             transaction logs"
+        );
+    }
+
+    let mut logs = read_logs(logfiles)?;
+    for log in logs.iter() {
+        if let Some(reason) = log.mismatch(&base_block) {
+            if force {
+                log::warn!(
+                    "the transaction log '{}' seems to belong to a different hive, \
+                     because {reason}",
+                    log.path.display()
+                );
+            } else {
+                bail!(
+                    "the transaction log '{}' seems to belong to a different hive, because \
+                     {reason}. Use '--force' to apply it anyway",
+                    log.path.display()
+                );
+            }
+        }
+    }
+
+    let replay = replay_order(&mut logs, secondary);
+    let mut stopped = false;
+    for (index, entry) in replay.entries {
+        let sequence_number = *entry.sequence_number();
+        let pages = entry.dirty_pages().len();
+        if hive.apply_transaction_log(entry) != ApplicationResult::Applied {
+            log::warn!(
+                "unable to apply the log entry with sequence number {sequence_number}"
+            );
+            stopped = true;
+            break;
+        }
+        logs[index].applied_entries += 1;
+        logs[index].dirty_pages += pages;
+    }
+    if let (false, Some((expected, found))) = (stopped, replay.gap) {
+        log::warn!(
+            "expected a log entry with sequence number {expected}, but found \
+             {found}. The remaining log entries are not applied, so the contents \
+             of the hive may be stale"
+        );
+    }
+
+    if logs.iter().all(|log| log.applied_entries == 0) {
+        log::warn!(
+            "the hive is dirty (sequence numbers {primary} and {secondary}), but the \
+             transaction logs contain no entry which could be applied. Its contents may be stale"
+        );
+    }
+    // shown by default, to report which logs have been used
+    for log in logs {
+        if log.applied_entries == 0 {
+            log::warn!(
+                "the transaction log '{}' contains no entries which are newer than the hive",
+                log.path.display()
+            );
+        } else {
+            log::warn!(
+                "applied {} log entries ({} dirty pages) from '{}'",
+                log.applied_entries,
+                log.dirty_pages,
+                log.path.display()
+            );
+        }
+    }
+    Ok(hive.treat_hive_as_clean())
+}
@@ -0,0 +1,82 @@
+use std::{fmt::Debug, str::FromStr};
+
+use anyhow::{anyhow, bail};
+use base64::{engine::general_purpose, Engine};
+
+const INVALID_API_KEY: &str =
+    "invalid API key: expected the format '<id>:<key>' or its base64 encoding";
+
+/// an elasticsearch API key, consisting of its id and the key itself. The key
+/// can either be specified as `<id>:<key>`, or in the base64 encoded form
+/// which is displayed by Kibana
+#[derive(Clone)]
+pub(crate) struct ApiKey {
+    pub(crate) id: String,
+    pub(crate) key: String,
+}
+
+impl FromStr for ApiKey {
+    type Err = anyhow::Error;
+
+    // the error messages must never contain the key itself
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let value = s.trim();
+        let decoded = if value.contains(':') {
+            value.to_owned()
+        } else {
+            general_purpose::STANDARD
+                .decode(value)
+                .ok()
+                .and_then(|bytes| String::from_utf8(bytes).ok())
+                .ok_or_else(|| anyhow!(INVALID_API_KEY))?
+        };
+
+        match decoded.split_once(':') {
+            Some((id, key)) if !id.is_empty() && !key.is_empty() => Ok(Self {
+                id: id.to_owned(),
+                key: key.to_owned(),
+            }),
+            _ => bail!(INVALID_API_KEY),
+        }
+    }
+}
+
+impl Debug for ApiKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ApiKey")
+            .field("id", &self.id)
+            .field("key", &"<redacted>")
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ApiKey;
+
+    #[test]
+    fn parse_api_keys() {
+        let key: ApiKey = "VuaCfGcBCdbkQm-e5aOx:ui2lp2axTNmsyakw9tvNnw"
+            .parse()
+            .unwrap();
+        assert_eq!(key.id, "VuaCfGcBCdbkQm-e5aOx");
+        assert_eq!(key.key, "ui2lp2axTNmsyakw9tvNnw");
+
+        let key: ApiKey = "VnVhQ2ZHY0JDZGJrUW0tZTVhT3g6dWkybHAyYXhUTm1zeWFrdzl0dk5udw=="
+            .parse()
+            .unwrap();
+        assert_eq!(key.id, "VuaCfGcBCdbkQm-e5aOx");
+        assert_eq!(key.key, "ui2lp2axTNmsyakw9tvNnw");
+
+        assert!(!format!("{key:?}").contains("ui2lp2axTNmsyakw9tvNnw"));
+    }
+
+    #[test]
+    fn errors_do_not_contain_the_key() {
+        for value in ["secretsecret", "id:", ":secretsecret", "c2VjcmV0c2VjcmV0"] {
+            let error = value.parse::<ApiKey>().unwrap_err().to_string();
+            assert!(!error.contains("secret"), "{error}");
+            assert!(!error.contains(value), "{error}");
+        }
+    }
+}
@@ -0,0 +1,182 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use dfir_toolkit::es4forensics::Index;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// the progress of a single input file
+#[derive(Serialize, Deserialize, Default, Clone, Copy, PartialEq, Debug)]
+struct FileProgress {
+    /// number of documents which have been acknowledged by elasticsearch,
+    /// either by indexing or by rejecting them
+    documents: u64,
+
+    /// all documents of the file have been acknowledged
+    completed: bool,
+}
+
+/// records which documents of which input files have already been imported,
+/// so that an aborted import can be continued without sending them again.
+/// The checkpoint is stored as pretty-printed JSON, e.g.
+///
+/// ```json
+/// {
+///   "files": {
+///     "/cases/2023-042/Security.evtx": {
+///       "documents": 120000,
+///       "completed": false
+///     }
+///   }
+/// }
+/// ```
+#[derive(Serialize, Deserialize, Default)]
+pub(crate) struct Checkpoint {
+    #[serde(skip)]
+    path: PathBuf,
+    files: BTreeMap<String, FileProgress>,
+}
+
+impl Checkpoint {
+    /// reads the checkpoint file, or creates an empty checkpoint if it does not exist yet
+    pub(crate) fn open(path: &Path) -> Result<Self> {
+        let mut checkpoint: Self = if path.exists() {
+            let content = std::fs::read_to_string(path)?;
+            serde_json::from_str(&content)
+                .with_context(|| format!("invalid checkpoint file '{}'", path.display()))?
+        } else {
+            Self::default()
+        };
+        checkpoint.path = path.to_owned();
+        Ok(checkpoint)
+    }
+
+    fn update(&mut self, source: &str, progress: FileProgress) -> Result<()> {
+        self.files.insert(source.to_owned(), progress);
+
+        // the checkpoint must not be corrupted if the import is aborted while
+        // writing, so we write a temporary file and replace the checkpoint with it
+        let mut tmp_path = self.path.clone().into_os_string();
+        tmp_path.push(".tmp");
+        std::fs::write(&tmp_path, serde_json::to_string_pretty(self)?)?;
+        std::fs::rename(&tmp_path, &self.path).with_context(|| {
+            format!("unable to write checkpoint file '{}'", self.path.display())
+        })?;
+        Ok(())
+    }
+}
+
+/// keeps track of the documents of one input file, and skips those documents
+/// which have already been acknowledged in an earlier run
+pub(crate) struct SourceProgress<'c> {
+    checkpoint: Option<&'c mut Checkpoint>,
+    source: String,
+    progress: FileProgress,
+
+    /// number of documents of this file which have been seen in this run
+    documents: u64,
+}
+
+impl<'c> SourceProgress<'c> {
+    pub(crate) fn new(checkpoint: Option<&'c mut Checkpoint>, source: &Path) -> Result<Self> {
+        let (source, progress) = match checkpoint.as_ref() {
+            None => (
+                source.to_string_lossy().into_owned(),
+                FileProgress::default(),
+            ),
+            Some(checkpoint) => {
+                // the same file might be passed using a different relative path
+                let source = source
+                    .canonicalize()
+                    .with_context(|| format!("unable to open '{}'", source.display()))?
+                    .to_string_lossy()
+                    .into_owned();
+                let progress = checkpoint.files.get(&source).copied().unwrap_or_default();
+                (source, progress)
+            }
+        };
+        Ok(Self {
+            checkpoint,
+            source,
+            progress,
+            documents: 0,
+        })
+    }
+
+    /// all documents of this file have been imported in an earlier run
+    pub(crate) fn is_completed(&self) -> bool {
+        self.progress.completed
+    }
+
+    /// adds the document to the index, unless it has already been imported
+    /// in an earlier run. If `key` is given, the id of the document is
+    /// derived from it. Returns `false` if the document has been skipped
+    pub(crate) async fn add_document(
+        &mut self,
+        index: &mut Index,
+        document: Value,
+        key: Option<&str>,
+    ) -> Result<bool> {
+        self.documents += 1;
+        if self.documents <= self.progress.documents {
+            return Ok(false);
+        }
+
+        match key {
+            Some(key) => index.add_bulk_document_with_key(document, key).await?,
+            None => index.add_bulk_document(document).await?,
+        }
+
+        // all documents seen so far have been sent to elasticsearch
+        if index.pending_documents() == 0 {
+            self.save(false)?;
+        }
+        Ok(true)
+    }
+
+    /// sends all remaining documents and marks the file as being completed
+    pub(crate) async fn finish(mut self, index: &mut Index) -> Result<()> {
+        index.flush().await?;
+        self.save(true)
+    }
+
+    fn save(&mut self, completed: bool) -> Result<()> {
+        self.progress = FileProgress {
+            documents: self.documents.max(self.progress.documents),
+            completed,
+        };
+        match self.checkpoint.as_mut() {
+            Some(checkpoint) => checkpoint.update(&self.source, self.progress),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Checkpoint, FileProgress, SourceProgress};
+
+    #[test]
+    fn resume_from_checkpoint() {
+        let dir = tempfile::tempdir().unwrap();
+        let checkpoint_file = dir.path().join("checkpoint.json");
+        let source = dir.path().join("Security.evtx");
+        std::fs::write(&source, b"").unwrap();
+
+        let mut checkpoint = Checkpoint::open(&checkpoint_file).unwrap();
+        let mut progress = SourceProgress::new(Some(&mut checkpoint), &source).unwrap();
+        progress.documents = 42;
+        progress.save(false).unwrap();
+
+        let checkpoint = Checkpoint::open(&checkpoint_file).unwrap();
+        let source = source.canonicalize().unwrap();
+        assert_eq!(
+            checkpoint.files.get(source.to_str().unwrap()),
+            Some(&FileProgress {
+                documents: 42,
+                completed: false
+            })
+        );
+    }
+}
@@ -1,14 +1,18 @@
-use crate::Protocol;
+use crate::api_key::ApiKey;
+use crate::evtx_import::EvtxFilter;
+use crate::input_format::{DocumentLabels, InputFormat};
+use crate::tls::read_client_certificate;
+use crate::{Flavor, Protocol};
+use anyhow::{bail, Result};
 use clap::{Parser, ValueHint};
 use clio::Input;
-use dfir_toolkit::common::HasVerboseFlag;
+use dfir_toolkit::common::{HasVerboseFlag, HumanDuration, Verbosity};
+use elasticsearch::auth::Credentials;
 use log::LevelFilter;
+use std::path::PathBuf;
 
-#[cfg(feature = "gzip")]
 const INPUTFILE_HELP: &str =
     "path to input file or '-' for stdin (files ending with .gz will be treated as being gzipped)";
-#[cfg(not(feature = "gzip"))]
-const INPUTFILE_HELP: &str = "path to input file or '-' for stdin";
 
 #[derive(clap::Subcommand, Clone)]
 pub(crate) enum Action {
@@ -20,12 +24,65 @@ pub(crate) enum Action {
         #[clap(default_value="-", help=INPUTFILE_HELP, value_hint=ValueHint::FilePath)]
         input_file: Input,
 
-        /// number of timeline entries to combine in one bulk operation
-        #[clap(long("bulk-size"), default_value_t = 1000)]
-        bulk_size: usize,
+        /// format of the input file
+        #[clap(short('F'), long("format"), value_enum, default_value_t = InputFormat::Json)]
+        format: InputFormat,
+
+        #[clap(flatten)]
+        labels: DocumentLabels,
+
+        #[clap(flatten)]
+        bulk_options: BulkOptions,
+
+        /// record the progress of the import in this file, and continue an aborted import from there
+        #[clap(long("checkpoint"), value_hint=ValueHint::FilePath)]
+        checkpoint: Option<PathBuf>,
+    },
+
+    /// import the records of evtx files
+    ImportEvtx {
+        /// evtx files to import. Directories are replaced by the evtx files they contain
+        #[clap(required = true, value_hint=ValueHint::AnyPath)]
+        evtx_files: Vec<PathBuf>,
+
+        #[clap(flatten)]
+        filter: EvtxFilter,
+
+        #[clap(flatten)]
+        labels: DocumentLabels,
+
+        #[clap(flatten)]
+        bulk_options: BulkOptions,
+
+        /// record the progress of the import in this file, and continue an aborted import from there
+        #[clap(long("checkpoint"), value_hint=ValueHint::FilePath)]
+        checkpoint: Option<PathBuf>,
     },
 }
 
+#[derive(clap::Args, Clone)]
+pub(crate) struct BulkOptions {
+    /// maximum number of timeline entries to combine in one bulk operation
+    #[clap(long("batch-size"), alias("bulk-size"), default_value_t = 1000)]
+    pub(crate) batch_size: usize,
+
+    /// maximum size of one bulk operation in bytes
+    #[clap(long("batch-bytes"), default_value_t = 10 * 1024 * 1024)]
+    pub(crate) batch_bytes: usize,
+
+    /// number of times a rejected bulk operation or document is sent again
+    #[clap(long("max-retries"), default_value_t = 5)]
+    pub(crate) max_retries: u32,
+
+    /// time to wait before the first retry, which is doubled for every further retry
+    #[clap(long("retry-delay"), default_value = "1s")]
+    pub(crate) retry_delay: HumanDuration,
+
+    /// write documents which could not be indexed to this file
+    #[clap(long("dead-letter-file"), value_hint=ValueHint::FilePath)]
+    pub(crate) dead_letter_file: Option<PathBuf>,
+}
+
 /// This crates provides structs and functions to insert timeline data into an elasticsearch index.
 #[derive(Parser)]
 #[clap(name=env!("CARGO_BIN_NAME"), author, version, long_about = None)]
@@ -58,6 +115,10 @@ pub struct Cli {
     #[clap(long("proto"), display_order=830, default_value_t=Protocol::Https)]
     pub(crate) protocol: Protocol,
 
+    /// search engine running on the server; if omitted, it is detected from the response to 'GET /'
+    #[clap(long("flavor"), display_order = 835)]
+    pub(crate) flavor: Option<Flavor>,
+
     /// omit certificate validation
     #[clap(
         short('k'),
@@ -67,16 +128,87 @@ pub struct Cli {
     )]
     pub(crate) omit_certificate_validation: bool,
 
+    /// PEM file with the certificate(s) of the CA which signed the certificate of the elasticsearch server
+    #[clap(
+        long("ca-cert"),
+        display_order = 842,
+        conflicts_with = "omit_certificate_validation",
+        value_hint = ValueHint::FilePath
+    )]
+    pub(crate) ca_cert: Option<PathBuf>,
+
+    /// PEM file with a client certificate for authenticating at elasticsearch
+    #[clap(
+        long("client-cert"),
+        display_order = 844,
+        requires = "client_key",
+        conflicts_with_all = ["password", "api_key", "bearer_token"],
+        value_hint = ValueHint::FilePath
+    )]
+    pub(crate) client_cert: Option<PathBuf>,
+
+    /// PEM file with the private key of the client certificate
+    #[clap(
+        long("client-key"),
+        display_order = 846,
+        requires = "client_cert",
+        value_hint = ValueHint::FilePath
+    )]
+    pub(crate) client_key: Option<PathBuf>,
+
     /// username for elasticsearch server
     #[clap(short('U'), long("username"), display_order=850, default_value=Some("elastic"))]
     pub(crate) username: String,
 
     /// password for authenticating at elasticsearch
     #[clap(short('W'), long("password"), display_order = 860)]
-    pub(crate) password: String,
+    pub(crate) password: Option<String>,
+
+    /// API key for authenticating at elasticsearch, either as '<id>:<key>' or base64 encoded
+    #[clap(
+        long("api-key"),
+        env("ES4FORENSICS_API_KEY"),
+        hide_env_values = true,
+        conflicts_with_all = ["password", "bearer_token"],
+        display_order = 870
+    )]
+    pub(crate) api_key: Option<String>,
+
+    /// bearer token for authenticating at elasticsearch, e.g. if it is accessed via a proxy
+    #[clap(
+        long("bearer-token"),
+        env("ES4FORENSICS_BEARER_TOKEN"),
+        hide_env_values = true,
+        conflicts_with = "password",
+        display_order = 880
+    )]
+    pub(crate) bearer_token: Option<String>,
+
+    /// do not create or update the index template, which contains the field mappings
+    #[clap(long("no-template"), display_order = 890)]
+    pub(crate) no_template: bool,
 
     #[clap(flatten)]
-    pub(crate) verbose: clap_verbosity_flag::Verbosity,
+    pub(crate) verbose: Verbosity,
+}
+
+impl Cli {
+    pub(crate) fn credentials(&self) -> Result<Credentials> {
+        // the API key is not parsed by clap, because clap would include the
+        // invalid value in its error message
+        if let Some(api_key) = &self.api_key {
+            let api_key: ApiKey = api_key.parse()?;
+            Ok(Credentials::ApiKey(api_key.id, api_key.key))
+        } else if let Some(bearer_token) = &self.bearer_token {
+            Ok(Credentials::Bearer(bearer_token.clone()))
+        } else if let (Some(cert_file), Some(key_file)) = (&self.client_cert, &self.client_key) {
+            Ok(read_client_certificate(cert_file, key_file)?.into())
+        } else if let Some(password) = &self.password {
+            Ok(Credentials::Basic(self.username.clone(), password.clone()))
+        } else {
+            bail!("missing credentials: use one of --password, --api-key, --bearer-token or --client-cert")
+        }
+    }
 }
 
 impl HasVerboseFlag for Cli {
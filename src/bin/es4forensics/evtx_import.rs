@@ -0,0 +1,292 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+use dfir_toolkit::common::TimeBound;
+use dfir_toolkit::es4forensics::{objects::WindowsEvent, Index, TimelineObject};
+use dfir_toolkit::evtx::EvtxFile;
+use evtx::{ParserSettings, SerializedEvtxRecord};
+use serde_json::Value;
+
+use crate::checkpoint::{Checkpoint, SourceProgress};
+use crate::input_format::DocumentLabels;
+
+/// selects the records which are imported, with the same semantics as the
+/// corresponding options of `evtxls`
+#[derive(clap::Args, Clone)]
+pub(crate) struct EvtxFilter {
+    /// import only events with the specified event ids, separated by ','
+    #[clap(short('i'), long("include"), value_delimiter = ',')]
+    included_event_ids: Vec<u16>,
+
+    /// do not import events with the specified event ids, separated by ','
+    #[clap(short('x'), long("exclude"), value_delimiter = ',')]
+    excluded_event_ids: Vec<u16>,
+
+    /// do not import events older than the specified date (hint: use RFC 3339 syntax).
+    /// Relative values like '-24h' are relative to the newest record of all files; valid
+    /// units are 's', 'm', 'h', 'd' and 'w'
+    #[clap(short('f'), long("from"), allow_hyphen_values = true)]
+    not_before: Option<TimeBound>,
+
+    /// do not import events newer than the specified date (hint: use RFC 3339 syntax).
+    /// Relative values are supported as with '--from'
+    #[clap(short('t'), long("to"), allow_hyphen_values = true)]
+    not_after: Option<TimeBound>,
+}
+
+/// the number of records of a single file, by what happened to them
+#[derive(Default)]
+struct FileStatistics {
+    imported: usize,
+    skipped: usize,
+    errors: usize,
+}
+
+pub(crate) struct EvtxImport {
+    included_event_ids: Vec<u16>,
+    excluded_event_ids: Vec<u16>,
+    not_before: Option<DateTime<Utc>>,
+    not_after: Option<DateTime<Utc>>,
+    labels: DocumentLabels,
+    strict_mode: bool,
+}
+
+impl EvtxImport {
+    pub(crate) fn new(
+        filter: EvtxFilter,
+        evtx_files: &[PathBuf],
+        labels: DocumentLabels,
+        strict_mode: bool,
+    ) -> Result<Self> {
+        let bounds = [filter.not_before.as_ref(), filter.not_after.as_ref()];
+        let newest = if bounds.iter().flatten().any(|b| b.is_relative()) {
+            newest_timestamp(evtx_files)
+        } else {
+            None
+        };
+
+        let resolve = |bound: Option<TimeBound>| match bound {
+            Some(bound) => bound.resolve(newest.as_ref()),
+            None => Ok(None),
+        };
+
+        Ok(Self {
+            not_before: resolve(filter.not_before)?,
+            not_after: resolve(filter.not_after)?,
+            included_event_ids: filter.included_event_ids,
+            excluded_event_ids: filter.excluded_event_ids,
+            labels,
+            strict_mode,
+        })
+    }
+
+    /// imports all files, and displays the result of every file as soon as
+    /// it has been imported. Files which could not be imported completely
+    /// are listed at the end
+    pub(crate) async fn import_files(
+        &self,
+        index: &mut Index,
+        evtx_files: &[PathBuf],
+        mut checkpoint: Option<Checkpoint>,
+    ) -> Result<()> {
+        let mut failures = Vec::new();
+        for (file_number, path) in evtx_files.iter().enumerate() {
+            let prefix = format!(
+                "[{}/{}] {}",
+                file_number + 1,
+                evtx_files.len(),
+                path.display()
+            );
+            match self.import_file(index, path, checkpoint.as_mut()).await {
+                Ok(None) => log::info!("{prefix}: already imported"),
+                Ok(Some(statistics)) => {
+                    log::info!(
+                        "{prefix}: {} imported, {} skipped, {} errors",
+                        statistics.imported, statistics.skipped, statistics.errors
+                    );
+                    if statistics.errors > 0 {
+                        failures.push((
+                            path,
+                            format!("{} records could not be read", statistics.errors),
+                        ));
+                    }
+                }
+                Err(why) => {
+                    if self.strict_mode {
+                        return Err(why.context(format!("unable to import '{}'", path.display())));
+                    }
+                    log::error!("{prefix}: failed: {why:#}");
+                    failures.push((path, format!("{why:#}")));
+                }
+            }
+        }
+
+        if !failures.is_empty() {
+            log::warn!(
+                "{} of {} files could not be imported completely:",
+                failures.len(),
+                evtx_files.len()
+            );
+            for (path, reason) in failures {
+                log::warn!("  {}: {reason}", path.display());
+            }
+        }
+        Ok(())
+    }
+
+    /// imports a single file, or returns `None` if the checkpoint states
+    /// that it has already been imported completely
+    async fn import_file(
+        &self,
+        index: &mut Index,
+        path: &Path,
+        checkpoint: Option<&mut Checkpoint>,
+    ) -> Result<Option<FileStatistics>> {
+        let mut progress = SourceProgress::new(checkpoint, path)?;
+        if progress.is_completed() {
+            return Ok(None);
+        }
+
+        let settings = ParserSettings::default().num_threads(0);
+        let file = EvtxFile::open(path)?.with_configuration(settings);
+
+        // documents get an id which is derived from the file and the record id,
+        // so that importing the same file again does not create duplicates
+        let source = path.canonicalize()?;
+
+        let mut statistics = FileStatistics::default();
+        for record in file.records_json_value() {
+            let record = match record {
+                Ok(record) => record,
+                Err(why) => {
+                    if self.strict_mode {
+                        bail!(why);
+                    }
+                    log::warn!(
+                        "error while reading record from '{}': {why}",
+                        path.display()
+                    );
+                    statistics.errors += 1;
+                    continue;
+                }
+            };
+
+            let event = match WindowsEvent::from_record_data(
+                record.event_record_id,
+                record.timestamp,
+                &record.data,
+            ) {
+                Ok(event) => event,
+                Err(why) => {
+                    if self.strict_mode {
+                        return Err(why);
+                    }
+                    log::warn!(
+                        "error while converting record from '{}': {why}",
+                        path.display()
+                    );
+                    statistics.errors += 1;
+                    continue;
+                }
+            };
+
+            if !self.is_accepted(&record, &event) {
+                statistics.skipped += 1;
+                continue;
+            }
+
+            let key = format!("{}:{}", source.display(), record.event_record_id);
+            let mut imported = false;
+            for mut document in event.into_values() {
+                self.labels.apply_to(&mut document);
+                imported |= progress.add_document(index, document, Some(&key)).await?;
+            }
+            if imported {
+                statistics.imported += 1;
+            } else {
+                statistics.skipped += 1;
+            }
+        }
+        progress.finish(index).await?;
+        Ok(Some(statistics))
+    }
+
+    fn is_accepted(&self, record: &SerializedEvtxRecord<Value>, event: &WindowsEvent) -> bool {
+        if let Some(not_before) = self.not_before.as_ref() {
+            if &record.timestamp < not_before {
+                return false;
+            }
+        }
+
+        if let Some(not_after) = self.not_after.as_ref() {
+            if &record.timestamp > not_after {
+                return false;
+            }
+        }
+
+        let event_id = event.event_id();
+        if !self.included_event_ids.is_empty()
+            && !self
+                .included_event_ids
+                .iter()
+                .any(|id| u64::from(*id) == event_id)
+        {
+            return false;
+        }
+
+        !self
+            .excluded_event_ids
+            .iter()
+            .any(|id| u64::from(*id) == event_id)
+    }
+}
+
+/// replaces every directory by the evtx files it contains
+pub(crate) fn expand_directories(evtx_files: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let mut result = Vec::with_capacity(evtx_files.len());
+    for path in evtx_files.iter() {
+        if path.is_dir() {
+            let mut files = Vec::new();
+            for entry in std::fs::read_dir(path)? {
+                let entry_path = entry?.path();
+                if entry_path.is_file()
+                    && entry_path
+                        .extension()
+                        .is_some_and(|ext| ext.eq_ignore_ascii_case("evtx"))
+                {
+                    files.push(entry_path);
+                }
+            }
+            if files.is_empty() {
+                log::warn!("directory '{}' contains no evtx files", path.display());
+            }
+            files.sort();
+            result.extend(files);
+        } else {
+            result.push(path.clone());
+        }
+    }
+    Ok(result)
+}
+
+/// finds the timestamp of the newest record, only reading the record headers
+fn newest_timestamp(evtx_files: &[PathBuf]) -> Option<DateTime<Utc>> {
+    let mut newest = None;
+    for path in evtx_files.iter() {
+        // unreadable files are reported while importing them
+        let file = match EvtxFile::open(path) {
+            Ok(file) => file,
+            Err(why) => {
+                log::warn!("unable to read '{}': {why}", path.display());
+                continue;
+            }
+        };
+        newest = file
+            .record_headers()
+            .map(|header| header.timestamp)
+            .chain(newest)
+            .max();
+    }
+    newest
+}
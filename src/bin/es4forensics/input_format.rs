@@ -0,0 +1,104 @@
+use anyhow::{anyhow, Result};
+use clap::ValueEnum;
+use dfir_toolkit::common::bodyfile::Bodyfile3Line;
+use dfir_toolkit::es4forensics::objects::{Mactime2Entry, PosixFile};
+use dfir_toolkit::es4forensics::TimelineObject;
+use serde_json::Value;
+
+#[derive(ValueEnum, Clone, Copy, Default)]
+pub(crate) enum InputFormat {
+    /// one elasticsearch document per line, which is imported as is
+    #[default]
+    Json,
+
+    /// a bodyfile, which yields one document per distinct timestamp of every file
+    Bodyfile,
+
+    /// the JSON output of mactime2, which yields one document per line
+    Mactime2,
+}
+
+impl InputFormat {
+    /// converts a single line of the input into the documents which are imported
+    pub(crate) fn documents(&self, line: &str) -> Result<Vec<Value>> {
+        match self {
+            Self::Json => Ok(vec![serde_json::from_str(line)?]),
+            Self::Bodyfile => {
+                let bfline =
+                    Bodyfile3Line::try_from(line).map_err(|why| anyhow!(why.describe(line)))?;
+                Ok(PosixFile::try_from(bfline)?.into_values().collect())
+            }
+            Self::Mactime2 => Ok(Mactime2Entry::try_from(line)?.into_values().collect()),
+        }
+    }
+}
+
+// labels which are added to every imported document, to be able to tell
+// evidence apart if data of several cases or hosts is stored in one index.
+// (not a doc comment, because clap would use it as help of the subcommand)
+#[derive(clap::Args, Clone, Default)]
+pub(crate) struct DocumentLabels {
+    /// add this case id to every document (as 'labels.case_id')
+    #[clap(long("case-id"))]
+    pub(crate) case_id: Option<String>,
+
+    /// add this host name to every document (as 'labels.host', and as 'host.name' if it is missing)
+    #[clap(long("host-label"))]
+    pub(crate) host: Option<String>,
+}
+
+impl DocumentLabels {
+    pub(crate) fn apply_to(&self, document: &mut Value) {
+        if let Some(case_id) = &self.case_id {
+            document["labels"]["case_id"] = case_id.clone().into();
+        }
+        if let Some(host) = &self.host {
+            document["labels"]["host"] = host.clone().into();
+
+            // documents of Windows events already contain the computer name
+            if document["host"]["name"].is_null() {
+                document["host"]["name"] = host.clone().into();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{DocumentLabels, InputFormat};
+
+    #[test]
+    fn import_bodyfile() {
+        let line = "0|/Users/Administrator ($FILE_NAME)|93552-48-2|d/drwxrwxrwx|0|0|92|1577092511|1577092511|1577092511|-1";
+        let documents = InputFormat::Bodyfile.documents(line).unwrap();
+        assert_eq!(documents.len(), 1);
+        assert_eq!(documents[0]["file"]["macb_short"], "mac.");
+        assert_eq!(documents[0]["file"]["mode"], "d/drwxrwxrwx");
+    }
+
+    #[test]
+    fn add_labels() {
+        let labels = DocumentLabels {
+            case_id: Some("2023-042".into()),
+            host: Some("ws01".into()),
+        };
+
+        let mut document = json!({"message": "foo"});
+        labels.apply_to(&mut document);
+        assert_eq!(
+            document,
+            json!({
+                "message": "foo",
+                "labels": {"case_id": "2023-042", "host": "ws01"},
+                "host": {"name": "ws01"}
+            })
+        );
+
+        let mut document = json!({"host": {"name": "DC01"}});
+        labels.apply_to(&mut document);
+        assert_eq!(document["host"]["name"], "DC01");
+        assert_eq!(document["labels"]["host"], "ws01");
+    }
+}
@@ -1,10 +1,19 @@
+mod api_key;
+mod checkpoint;
 mod cli;
+mod evtx_import;
+mod input_format;
+mod tls;
 
 
 use std::io::BufRead;
-use anyhow::{Result, anyhow};
+use std::path::PathBuf;
+use anyhow::{Context, Result, anyhow, bail};
 
-use cli::{Cli, Action};
+use checkpoint::{Checkpoint, SourceProgress};
+use cli::{Action, BulkOptions, Cli};
+use evtx_import::{expand_directories, EvtxImport};
+use input_format::{DocumentLabels, InputFormat};
 use elasticsearch::auth::Credentials;
 use dfir_toolkit::es4forensics::*;
 use dfir_toolkit::common::{FancyParser, FileInput};
@@ -14,62 +23,119 @@ async fn main() -> Result<()> {
     let cli: Cli = Cli::parse_cli();
     
     let action = cli.action.clone();
-    let e4f: Es4Forensics = cli.into();
+    let e4f = Es4Forensics::try_from(cli)?;
     e4f.run(action).await
 }
 
 struct Es4Forensics {
     strict_mode: bool,
+    create_template: bool,
     index_name: String,
     host: String,
     port: u16,
     protocol: Protocol,
+    flavor: Option<Flavor>,
     omit_certificate_validation: bool,
-    username: String,
-    password: String,
+    ca_certificate: Option<Vec<u8>>,
+    credentials: Credentials,
 }
 
 impl Es4Forensics {
     pub async fn run(self, action: Action) -> Result<()> {
 
-        let builder = self.create_index_builder()?;
+        let mut builder = self.create_index_builder()?;
+        builder.detect_flavor().await?;
+        builder.check_credentials().await?;
 
         match action {
             Action::CreateIndex => {
                 if builder.index_exists().await? {
                     return Err(anyhow!("index '{}' exists already", self.index_name));
                 }
+                if self.create_template {
+                    builder.put_index_template().await?;
+                }
                 builder.create_index().await?;
                 Ok(())
             }
-            Action::Import{input_file, bulk_size} => {
-                self.import(builder, input_file.into(), bulk_size).await
+            Action::Import{input_file, format, labels, bulk_options, checkpoint} => {
+                let mut checkpoint = open_checkpoint(checkpoint)?;
+                if checkpoint.is_some() && input_file.is_std() {
+                    bail!("--checkpoint cannot be used when reading from stdin");
+                }
+                let mut index = self.connect(builder, bulk_options).await?;
+                let progress = SourceProgress::new(checkpoint.as_mut(), input_file.path())?;
+                if progress.is_completed() {
+                    ::log::info!("'{}' has already been imported completely", input_file.path().display());
+                } else {
+                    self.import(&mut index, input_file.into(), format, labels, progress).await?;
+                }
+                self.finish(index).await
+            }
+            Action::ImportEvtx{evtx_files, filter, labels, bulk_options, checkpoint} => {
+                let checkpoint = open_checkpoint(checkpoint)?;
+                let evtx_files = expand_directories(&evtx_files)?;
+                let evtx_import = EvtxImport::new(filter, &evtx_files, labels, self.strict_mode)?;
+                let mut index = self.connect(builder, bulk_options).await?;
+                evtx_import.import_files(&mut index, &evtx_files, checkpoint).await?;
+                self.finish(index).await
             }
         }
     }
 
-    async fn import(&self, builder: IndexBuilder, reader: FileInput, bulk_size: usize) -> Result<()> {
+    /// connects to the index, after creating or updating its index template
+    async fn connect(&self, builder: IndexBuilder, bulk_options: BulkOptions) -> Result<Index> {
+        if self.create_template {
+            builder.put_index_template().await?;
+        }
         let mut index = builder.connect().await?;
-        index.set_cache_size(bulk_size).await?;
+        index.set_cache_size(bulk_options.batch_size).await?;
+        index.set_max_payload_size(bulk_options.batch_bytes).await?;
+        index.set_retry_policy(RetryPolicy {
+            max_retries: bulk_options.max_retries,
+            initial_delay: bulk_options.retry_delay.duration().to_std()?,
+            ..Default::default()
+        });
+        if let Some(dead_letter_file) = bulk_options.dead_letter_file {
+            index.set_dead_letter_file(&dead_letter_file)?;
+        }
+        Ok(index)
+    }
+
+    async fn import(&self, index: &mut Index, reader: FileInput, format: InputFormat, labels: DocumentLabels, mut progress: SourceProgress<'_>) -> Result<()> {
 
         for line in reader.lines() {
             let line = line?;
-            let value = match serde_json::from_str(&line) {
+            let documents = match format.documents(&line) {
                 Ok(v) => v,
                 Err(why) => {
                     if self.strict_mode {
-                        return Err(anyhow!(why))
+                        return Err(why)
                     } else {
                         ::log::error!("error while parsing: {}", why);
-                        ::log::error!("failed JSON was:     {}", line);
+                        ::log::error!("failed line was:     {}", line);
                         continue;
                     }
                 }
             };
 
-            index.add_bulk_document(value).await?;
+            for mut document in documents {
+                labels.apply_to(&mut document);
+                progress.add_document(index, document, None).await?;
+            }
         }
+        progress.finish(index).await
+    }
+
+    /// sends all remaining documents and displays the statistics of the import
+    async fn finish(&self, mut index: Index) -> Result<()> {
         index.flush().await?;
+
+        let statistics = index.statistics();
+        ::log::info!("{statistics}");
+        if self.strict_mode && statistics.failed > 0 {
+            return Err(anyhow!("{} documents could not be indexed", statistics.failed));
+        }
         Ok(())
     }    
 
@@ -77,32 +143,53 @@ impl Es4Forensics {
         let mut builder = IndexBuilder::with_name(self.index_name.clone())
             .with_host(self.host.clone())
             .with_port(self.port)
-            .with_credentials(Credentials::Basic(
-                self.username.clone(),
-                self.password.clone(),
-            ))
+            .with_credentials(self.credentials.clone())
             .with_protocol(self.protocol.clone());
 
+        if let Some(flavor) = self.flavor {
+            builder = builder.with_flavor(flavor);
+        }
+
         if self.omit_certificate_validation {
-            ::log::warn!("disabling certificate validation");
+            ::log::warn!(
+                "certificate validation is disabled, the identity of '{}' will not be verified",
+                self.host
+            );
             builder = builder.without_certificate_validation();
         }
 
+        if let Some(ca_certificate) = &self.ca_certificate {
+            builder = builder.with_ca_certificate(ca_certificate.clone());
+        }
+
         Ok(builder)
     }
 }
 
-impl From<Cli> for Es4Forensics {
-    fn from(cli: Cli) -> Self {
-        Self {
+fn open_checkpoint(path: Option<PathBuf>) -> Result<Option<Checkpoint>> {
+    path.map(|path| Checkpoint::open(&path)).transpose()
+}
+
+impl TryFrom<Cli> for Es4Forensics {
+    type Error = anyhow::Error;
+
+    fn try_from(cli: Cli) -> Result<Self> {
+        Ok(Self {
             strict_mode: cli.strict_mode,
+            create_template: !cli.no_template,
             host: cli.host.clone(),
             port: cli.port,
-            username: cli.username.clone(),
-            password: cli.password.clone(),
+            credentials: cli.credentials()?,
             index_name: cli.index_name.clone(),
             protocol: cli.protocol.clone(),
+            flavor: cli.flavor,
             omit_certificate_validation: cli.omit_certificate_validation,
-        }
+            ca_certificate: match &cli.ca_cert {
+                Some(path) => Some(std::fs::read(path).with_context(|| {
+                    format!("unable to read CA certificate from '{}'", path.display())
+                })?),
+                None => None,
+            },
+        })
     }
 }
\ No newline at end of file
@@ -0,0 +1,58 @@
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use elasticsearch::auth::ClientCertificate;
+use openssl::{pkcs12::Pkcs12, pkey::PKey, stack::Stack, x509::X509};
+
+/// reads a PEM encoded client certificate (optionally followed by its chain)
+/// and the matching private key. The elasticsearch client only accepts
+/// client certificates in PKCS#12 format, so both are converted
+pub(crate) fn read_client_certificate(
+    cert_file: &Path,
+    key_file: &Path,
+) -> Result<ClientCertificate> {
+    let cert_pem = std::fs::read(cert_file).with_context(|| {
+        format!(
+            "unable to read client certificate from '{}'",
+            cert_file.display()
+        )
+    })?;
+    let key_pem = std::fs::read(key_file)
+        .with_context(|| format!("unable to read client key from '{}'", key_file.display()))?;
+
+    let mut certificates = X509::stack_from_pem(&cert_pem)
+        .with_context(|| format!("invalid client certificate in '{}'", cert_file.display()))?;
+    if certificates.is_empty() {
+        bail!("no client certificate found in '{}'", cert_file.display());
+    }
+    let certificate = certificates.remove(0);
+
+    // the key is never part of an error message
+    let key = PKey::private_key_from_pem(&key_pem)
+        .with_context(|| format!("invalid private key in '{}'", key_file.display()))?;
+    if !key.public_eq(&*certificate.public_key()?) {
+        bail!(
+            "the private key in '{}' does not belong to the client certificate in '{}'",
+            key_file.display(),
+            cert_file.display()
+        );
+    }
+
+    let mut builder = Pkcs12::builder();
+    builder
+        .name(env!("CARGO_BIN_NAME"))
+        .pkey(&key)
+        .cert(&certificate);
+    if !certificates.is_empty() {
+        let mut chain = Stack::new()?;
+        for certificate in certificates {
+            chain.push(certificate)?;
+        }
+        builder.ca(chain);
+    }
+    let pkcs12 = builder
+        .build2("")
+        .context("unable to convert the client certificate to PKCS#12")?;
+
+    Ok(ClientCertificate::Pkcs12(pkcs12.to_der()?, None))
+}
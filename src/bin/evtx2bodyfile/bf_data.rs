@@ -1,8 +1,8 @@
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 use anyhow::{anyhow, bail, Result};
-use chrono::{DateTime, Utc};
-use dfir_toolkit::common::bodyfile::{Bodyfile3Line, Modified};
+use chrono::{DateTime, SecondsFormat, Utc};
+use dfir_toolkit::common::bodyfile::{BehavesLikeI64, Bodyfile3Line, Modified};
 use dfir_toolkit::es4forensics::{objects::WindowsEvent, TimelineObject};
 use evtx::SerializedEvtxRecord;
 use getset::{Getters, Setters};
@@ -10,6 +10,7 @@ use serde::Serialize;
 use serde_json::{json, Value};
 
 use crate::macros::from_json;
+use crate::name_field::{NameField, NameFormat};
 
 #[derive(Serialize, Getters, Setters)]
 pub(crate) struct BfData<'a> {
@@ -22,19 +23,97 @@ pub(crate) struct BfData<'a> {
     provider_name: &'a Value,
     channel_name: &'a Value,
     activity_id: Option<&'a Value>,
-    custom_data: HashMap<&'a String, &'a Value>,
+    custom_data: BTreeMap<&'a String, &'a Value>,
 
     #[serde(skip)]
     #[getset(set = "pub (crate)")]
     enable_json_output: bool,
 }
 
+/// a record in JSON format, which contains the fields of the bodyfile line
+/// together with the structured event data
+#[derive(Serialize)]
+struct BodyfileJson<'a, 'b> {
+    name: &'b str,
+    atime: i64,
+    mtime: i64,
+    ctime: i64,
+    crtime: i64,
+
+    #[serde(flatten)]
+    data: &'b BfData<'a>,
+}
+
+fn bodyfile_time<T: BehavesLikeI64>(time: &T) -> i64 {
+    time.as_ref().copied().unwrap_or(-1)
+}
+
+fn value_to_text(value: Option<&Value>) -> String {
+    match value {
+        None | Some(Value::Null) => "".to_owned(),
+        Some(Value::String(s)) => s.to_owned(),
+        Some(v) => v.to_string(),
+    }
+}
+
 impl<'a> BfData<'a> {
-    pub(crate) fn try_into_mactime(&self) -> Result<String> {
-        let bf_line = Bodyfile3Line::new()
+    fn bodyfile_line(&self, name_format: &NameFormat) -> Bodyfile3Line {
+        let name = match name_format.fields() {
+            None => json!(self).to_string(),
+            Some((fields, separator)) => fields
+                .iter()
+                .map(|field| self.field_text(field))
+                .collect::<Vec<_>>()
+                .join(separator),
+        };
+        Bodyfile3Line::new()
             .with_mtime(Modified::from(self.timestamp.timestamp()))
-            .with_owned_name(json!(self).to_string());
-        Ok(bf_line.to_string())
+            .with_owned_name(name)
+    }
+
+    /// returns the value of a field as text, which is empty if the event
+    /// does not contain this field
+    fn field_text(&self, field: &NameField) -> String {
+        match field {
+            NameField::Time => self.timestamp.to_rfc3339_opts(SecondsFormat::AutoSi, true),
+            NameField::RecordId => self.event_record_id.to_string(),
+            NameField::EventId => value_to_text(Some(self.event_id)),
+            NameField::Level => value_to_text(Some(self.level)),
+            NameField::Provider => value_to_text(Some(self.provider_name)),
+            NameField::Channel => value_to_text(Some(self.channel_name)),
+            NameField::Computer => value_to_text(Some(self.computer)),
+            NameField::ActivityId => value_to_text(self.activity_id),
+            NameField::Data(name) => value_to_text(self.data_value(name)),
+        }
+    }
+
+    /// searches for a value in EventData or UserData. The values of
+    /// UserData are nested in an additional element, which is searched as
+    /// well.
+    fn data_value(&self, name: &str) -> Option<&'a Value> {
+        self.custom_data.values().find_map(|data| {
+            data.get(name).or_else(|| match data {
+                Value::Object(children) => children.values().find_map(|c| c.get(name)),
+                _ => None,
+            })
+        })
+    }
+
+    pub(crate) fn try_into_mactime(&self, name_format: &NameFormat) -> Result<String> {
+        Ok(self.bodyfile_line(name_format).to_string())
+    }
+
+    pub(crate) fn try_into_bodyfile_json(&self, name_format: &NameFormat) -> Result<String> {
+        let bf_line = self.bodyfile_line(name_format);
+        let value = BodyfileJson {
+            name: bf_line.get_name(),
+            atime: bodyfile_time(bf_line.get_atime()),
+            mtime: bodyfile_time(bf_line.get_mtime()),
+            ctime: bodyfile_time(bf_line.get_ctime()),
+            crtime: bodyfile_time(bf_line.get_crtime()),
+            data: self,
+        };
+        serde_json::to_string(&value).map_err(|why| anyhow!(why))
     }
 
     pub(crate) fn try_into_json(&self) -> Result<String> {
@@ -54,7 +133,7 @@ impl<'a> TryFrom<&BfData<'a>> for String {
         if value.enable_json_output {
             value.try_into_json()
         } else {
-            value.try_into_mactime()
+            value.try_into_mactime(&NameFormat::default())
         }
     }
 }
@@ -92,7 +171,7 @@ impl<'a> TryFrom<&'a SerializedEvtxRecord<Value>> for BfData<'a> {
             .and_then(|c| c.get("#attributes"))
             .and_then(|c| c.get("ActivityId"));
 
-        let mut custom_data = HashMap::new();
+        let mut custom_data = BTreeMap::new();
         if let Value::Object(contents) = event {
             for (key, value) in contents.iter() {
                 if key != "System" && key != "#attributes" {
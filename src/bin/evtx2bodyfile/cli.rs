@@ -1,7 +1,10 @@
+use std::path::PathBuf;
+
+use crate::name_field::NameField;
 use crate::output_format::OutputFormat;
 use clap::{Parser, ValueHint};
 use clio::Input;
-use dfir_toolkit::common::HasVerboseFlag;
+use dfir_toolkit::common::{HasVerboseFlag, OutputArgs, Verbosity};
 use getset::Getters;
 use log::LevelFilter;
 
@@ -18,12 +21,90 @@ pub(crate) struct Cli {
     #[clap(short('F'), long("format"), default_value_t=OutputFormat::Bodyfile)]
     format: OutputFormat,
 
-    /// fail upon read error
+    /// fail upon the first chunk or record which cannot be read or
+    /// converted. Otherwise, these are skipped, and a summary of the skipped
+    /// chunks and records is printed for every file
     #[clap(short('S'), long("strict"))]
     strict: bool,
 
+    /// write a summary of the converted and skipped records of every file
+    /// as JSON into this file
+    #[clap(long("summary-json"), value_name = "FILE", value_hint=ValueHint::FilePath)]
+    summary_json: Option<PathBuf>,
+
+    /// write a report of all chunks, records and damaged ranges which have
+    /// been skipped into this file, together with their offsets in the
+    /// evtx file
+    #[clap(long("report"), value_name = "FILE", value_hint=ValueHint::FilePath)]
+    report: Option<PathBuf>,
+
+    /// number of files which are converted at the same time (default: number of cores)
+    #[clap(short('j'), long("threads"), value_parser=clap::value_parser!(u16).range(1..))]
+    threads: Option<u16>,
+
+    /// write the lines of different files as soon as they have been
+    /// converted, so that they are interleaved in an unpredictable order.
+    /// Otherwise, all lines of a file are written before the lines of the
+    /// next file, in the order of the files on the command line
+    #[clap(long("interleave"))]
+    interleave: bool,
+
+    /// write the output of every input file into a separate file in this
+    /// directory, instead of writing to stdout. The output files are named
+    /// after the input files; if multiple input files have the same name,
+    /// a number is appended to the names of all but the first of them. No
+    /// file is created for input files which could not be converted
+    #[clap(
+        short('O'),
+        long("output-dir"),
+        value_name = "DIR",
+        value_hint=ValueHint::DirPath,
+        conflicts_with_all = ["interleave", "output"]
+    )]
+    output_dir: Option<PathBuf>,
+
+    /// replace existing files in the output directory
+    #[clap(long("overwrite"), requires = "output_dir")]
+    overwrite: bool,
+
+    /// convert only events with the specified event ids, separated by ','
+    #[clap(
+        long("include-event-ids"),
+        value_name = "EVENT_IDS",
+        value_delimiter = ','
+    )]
+    included_event_ids: Vec<u16>,
+
+    /// do not convert events with the specified event ids, separated by ','
+    #[clap(
+        long("exclude-event-ids"),
+        value_name = "EVENT_IDS",
+        value_delimiter = ','
+    )]
+    excluded_event_ids: Vec<u16>,
+
+    /// convert only events from the specified channel (case insensitive).
+    /// This option can be specified multiple times
+    #[clap(long("include-channel"), value_name = "CHANNEL")]
+    included_channels: Vec<String>,
+
+    /// fields which are written into the name column of the bodyfile,
+    /// separated by ','. Possible fields are time, recordid, eventid, level,
+    /// provider, channel, computer, activityid and data.<NAME> for values of
+    /// EventData or UserData. Missing fields are empty. By default, the name
+    /// contains all attributes of the event as JSON object
+    #[clap(long("name-fields"), value_name = "FIELDS", value_delimiter = ',')]
+    name_fields: Option<Vec<NameField>>,
+
+    /// separator between the fields selected by '--name-fields'
+    #[clap(long("name-separator"), value_name = "SEPARATOR", default_value = ";")]
+    name_separator: String,
+
+    #[clap(flatten)]
+    output: OutputArgs,
+
     #[clap(flatten)]
-    verbose: clap_verbosity_flag::Verbosity,
+    verbose: Verbosity,
 }
 
 impl HasVerboseFlag for Cli {
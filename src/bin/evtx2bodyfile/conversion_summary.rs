@@ -0,0 +1,200 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::io::Write;
+
+use serde::Serialize;
+
+/// size of the file header, after which the first chunk starts
+const FILE_HEADER_SIZE: u64 = 4096;
+
+/// size of a chunk
+const CHUNK_SIZE: u64 = 65536;
+
+#[derive(Clone, Copy, PartialEq)]
+enum SkippedKind {
+    Chunk,
+    Record,
+    Damaged,
+}
+
+/// a range of the file which has been skipped, because it could not be
+/// parsed
+#[derive(Serialize)]
+pub(crate) struct SkippedRange {
+    #[serde(skip)]
+    kind: SkippedKind,
+
+    /// number of the chunk
+    chunk: u64,
+
+    /// offset of the first byte, relative to the begin of the file
+    begin: u64,
+
+    /// offset after the last byte, relative to the begin of the file
+    end: u64,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    record_id: Option<u64>,
+
+    reason: String,
+}
+
+impl SkippedRange {
+    fn chunk_offset(chunk: u64) -> u64 {
+        FILE_HEADER_SIZE + chunk * CHUNK_SIZE
+    }
+
+    /// a chunk which has been skipped as a whole
+    pub(crate) fn chunk(chunk: u64, reason: String) -> Self {
+        let begin = Self::chunk_offset(chunk);
+        Self {
+            kind: SkippedKind::Chunk,
+            chunk,
+            begin,
+            end: begin + CHUNK_SIZE,
+            record_id: None,
+            reason: format!("invalid chunk: {reason}"),
+        }
+    }
+
+    /// a record which could not be parsed. `begin` and `end` are relative
+    /// to the begin of the chunk
+    pub(crate) fn record(chunk: u64, begin: u64, end: u64, record_id: u64, reason: String) -> Self {
+        let chunk_offset = Self::chunk_offset(chunk);
+        Self {
+            kind: SkippedKind::Record,
+            chunk,
+            begin: chunk_offset + begin,
+            end: chunk_offset + end,
+            record_id: Some(record_id),
+            reason: format!("invalid record: {reason}"),
+        }
+    }
+
+    /// a part of a chunk without any valid record header. `begin` and `end`
+    /// are relative to the begin of the chunk
+    pub(crate) fn damaged(chunk: u64, begin: u64, end: u64) -> Self {
+        let chunk_offset = Self::chunk_offset(chunk);
+        Self {
+            kind: SkippedKind::Damaged,
+            chunk,
+            begin: chunk_offset + begin,
+            end: chunk_offset + end,
+            record_id: None,
+            reason: "damaged record header".to_owned(),
+        }
+    }
+}
+
+impl Display for SkippedRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "chunk {}", self.chunk)?;
+        if let Some(record_id) = self.record_id {
+            write!(f, ", record {record_id}")?;
+        }
+        write!(
+            f,
+            " (bytes {:#x}..{:#x}): {}",
+            self.begin, self.end, self.reason
+        )
+    }
+}
+
+/// statistics about the conversion of one file, which tell how many
+/// chunks and records have been skipped and why
+#[derive(Serialize, Default)]
+pub(crate) struct ConversionSummary {
+    file: String,
+    converted_records: usize,
+    skipped_chunks: usize,
+    skipped_records: usize,
+    damaged_ranges: usize,
+
+    /// number of skipped chunks or records per reason
+    reasons: BTreeMap<String, usize>,
+
+    /// all parts of the file which have been skipped, in the order of the
+    /// file
+    skipped_ranges: Vec<SkippedRange>,
+
+    /// error which stopped the conversion of this file
+    error: Option<String>,
+}
+
+impl ConversionSummary {
+    pub(crate) fn new(file: String) -> Self {
+        Self {
+            file,
+            ..Default::default()
+        }
+    }
+
+    pub(crate) fn add_converted_record(&mut self) {
+        self.converted_records += 1;
+    }
+
+    /// registers a part of the file which could not be parsed
+    pub(crate) fn add_skipped_range(&mut self, range: SkippedRange) {
+        match range.kind {
+            SkippedKind::Chunk => self.skipped_chunks += 1,
+            SkippedKind::Record => self.skipped_records += 1,
+            SkippedKind::Damaged => self.damaged_ranges += 1,
+        }
+        *self.reasons.entry(range.reason.clone()).or_default() += 1;
+        self.skipped_ranges.push(range);
+    }
+
+    /// registers a record which has been read, but could not be converted
+    pub(crate) fn add_conversion_error(&mut self) {
+        self.skipped_records += 1;
+        *self
+            .reasons
+            .entry("unable to convert record".to_owned())
+            .or_default() += 1;
+    }
+
+    pub(crate) fn set_error(&mut self, why: &anyhow::Error) {
+        self.error = Some(format!("{why:#}"));
+    }
+
+    fn has_skipped(&self) -> bool {
+        self.skipped_chunks > 0 || self.skipped_records > 0 || self.damaged_ranges > 0
+    }
+
+    fn headline(&self) -> String {
+        format!(
+            "{}: converted {} records, skipped {} chunks, {} records and {} damaged ranges",
+            self.file,
+            self.converted_records,
+            self.skipped_chunks,
+            self.skipped_records,
+            self.damaged_ranges
+        )
+    }
+
+    /// logs the summary as a warning, together with the reasons, if anything
+    /// has been skipped. Otherwise, it is only logged as information.
+    pub(crate) fn log(&self) {
+        if self.has_skipped() {
+            let mut message = self.headline();
+            for (reason, count) in self.reasons.iter() {
+                message.push_str(&format!("\n    {count}x {reason}"));
+            }
+            log::warn!("{message}");
+        } else {
+            log::info!("{}", self.headline());
+        }
+    }
+
+    /// writes a report which lists all skipped parts of the file
+    pub(crate) fn write_report<W: Write>(&self, mut w: W) -> std::io::Result<()> {
+        writeln!(w, "{}", self.headline())?;
+        for range in self.skipped_ranges.iter() {
+            writeln!(w, "    {range}")?;
+        }
+        if let Some(error) = &self.error {
+            writeln!(w, "    conversion aborted: {error}")?;
+        }
+        Ok(())
+    }
+}
@@ -1,104 +1,224 @@
-use anyhow::Result;
+use std::io::Read;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context, Result};
 use clio::Input;
-use evtx::{EvtxParser, SerializedEvtxRecord};
-use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
-use ouroboros::self_referencing;
+use evtx::err::EvtxError;
+use dfir_toolkit::evtx::{ChunkEntry, ChunkRecords};
+use evtx::{EvtxChunkData, ParserSettings, SerializedEvtxRecord};
 use serde_json::Value;
 
+use crate::conversion_summary::{ConversionSummary, SkippedRange};
 use crate::output_formatter::OutputFormatter;
+use crate::progress::{FileProgress, Progress};
+use crate::record_filter::RecordFilter;
 
 pub(crate) struct EvtxFile(Input);
 
-#[self_referencing()]
-pub(crate) struct EvtxFileIterator {
-    parser: EvtxParser<Input>,
-
-    #[borrows(mut parser)]
-    #[not_covariant]
-    inner_iterator:
-        Box<dyn Iterator<Item = evtx::err::Result<SerializedEvtxRecord<Value>>> + 'this>,
+impl From<&Input> for EvtxFile {
+    fn from(input: &Input) -> Self {
+        Self(input.clone())
+    }
 }
 
-impl Iterator for EvtxFileIterator {
-    type Item = SerializedEvtxRecord<Value>;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        self.with_inner_iterator_mut(|iter| loop {
-            match iter.next() {
-                None => return None,
-                Some(Ok(record)) => return Some(record),
-                Some(Err(why)) => log::warn!("{}", why),
-            }
-        })
+/// returns the innermost cause of an error of the evtx parser, which is
+/// more meaningful than the generic messages of the outer errors
+fn root_cause(why: &EvtxError) -> String {
+    let mut cause: &dyn std::error::Error = why;
+    while let Some(source) = cause.source() {
+        cause = source;
     }
+    cause.to_string()
 }
 
-impl IntoIterator for EvtxFile {
-    type Item = SerializedEvtxRecord<Value>;
-
-    type IntoIter = EvtxFileIterator;
+/// converts the records of one file and handles the parts of the file
+/// which cannot be parsed
+struct RecordConverter<'a, F, E> {
+    name: String,
+    formatter: &'a F,
+    filter: &'a RecordFilter,
+    strict: bool,
+    summary: &'a mut ConversionSummary,
+    progress: &'a FileProgress<'a>,
+    emit: E,
+}
 
-    fn into_iter(self) -> Self::IntoIter {
-        let parser = EvtxParser::from_read_seek(self.0).expect("unable to create parser");
+impl<'a, F, E> RecordConverter<'a, F, E>
+where
+    F: OutputFormatter,
+    E: FnMut(String) -> Result<()>,
+{
+    /// registers a part of the file which cannot be parsed. In strict mode,
+    /// this is an error.
+    fn skip(&mut self, range: SkippedRange) -> Result<()> {
+        if self.strict {
+            return Err(anyhow!("{range}").context(format!("error while reading '{}'", self.name)));
+        }
+        log::warn!("{}: {range}", self.name);
+        self.summary.add_skipped_range(range);
+        Ok(())
+    }
 
-        EvtxFileIteratorBuilder {
-            parser,
-            inner_iterator_builder: |parser: &mut EvtxParser<Input>| {
-                Box::new(parser.records_json_value())
-            },
+    fn convert(&mut self, value: SerializedEvtxRecord<Value>) -> Result<()> {
+        match self.formatter.record_to_string(&value) {
+            Ok(s) => {
+                (self.emit)(s)?;
+                self.summary.add_converted_record();
+            }
+            Err(why) if self.strict => {
+                return Err(why.context(format!("error while reading '{}'", self.name)));
+            }
+            Err(why) => {
+                log::warn!("{}: Error while reading record: {why}", self.name);
+                self.summary.add_conversion_error();
+            }
         }
-        .build()
+        Ok(())
     }
-}
 
-impl From<&Input> for EvtxFile {
-    fn from(input: &Input) -> Self {
-        Self(input.clone())
+    /// converts all records of a chunk. Chunks with an invalid header are
+    /// skipped as a whole. Inside of a chunk, records with an invalid header
+    /// are skipped, and the conversion continues with the next valid record.
+    fn convert_chunk(&mut self, chunk_number: u64, mut chunk_data: EvtxChunkData) -> Result<()> {
+        if !chunk_data.validate_header_checksum() {
+            return self.skip(SkippedRange::chunk(
+                chunk_number,
+                "invalid header checksum".to_owned(),
+            ));
+        }
+        if !chunk_data.validate_data_checksum() {
+            log::warn!(
+                "{}: chunk {chunk_number} has an invalid data checksum, trying to recover its records",
+                self.name
+            );
+        }
+
+        let settings = Arc::new(ParserSettings::default());
+        let chunk = match chunk_data.parse(settings) {
+            Ok(chunk) => chunk,
+            Err(why) => {
+                let why = EvtxError::FailedToParseChunk {
+                    chunk_id: chunk_number,
+                    source: why,
+                };
+                return self.skip(SkippedRange::chunk(chunk_number, root_cause(&why)));
+            }
+        };
+
+        for entry in ChunkRecords::new(&chunk) {
+            match entry {
+                ChunkEntry::Record { record, begin, end } => {
+                    self.progress.inc_records();
+                    if !self.filter.accepts(&record) {
+                        continue;
+                    }
+                    let record_id = record.event_record_id;
+                    match record.into_json_value() {
+                        Ok(value) => self.convert(value)?,
+                        Err(why) => self.skip(SkippedRange::record(
+                            chunk_number,
+                            begin,
+                            end,
+                            record_id,
+                            root_cause(&why),
+                        ))?,
+                    }
+                }
+                ChunkEntry::InvalidRecord {
+                    record_id,
+                    begin,
+                    end,
+                    why,
+                } => self.skip(SkippedRange::record(
+                    chunk_number,
+                    begin,
+                    end,
+                    record_id,
+                    root_cause(&why),
+                ))?,
+                ChunkEntry::Damaged { begin, end } => {
+                    self.skip(SkippedRange::damaged(chunk_number, begin, end))?
+                }
+            }
+        }
+        Ok(())
     }
 }
 
 impl EvtxFile {
-    pub(crate) fn print_records<F>(self, formatter: F, treat_errors_as_warnings: bool) -> Result<()>
+    /// name of the file, as specified on the command line
+    pub(crate) fn name(&self) -> String {
+        self.0.path().display().to_string()
+    }
+
+    /// converts all records of the file which are accepted by `filter`
+    /// and passes every line to `emit`. The chunks of the file are read
+    /// independently from each other, so that damaged chunks or records
+    /// don't prevent the conversion of the remainder of the file. In strict
+    /// mode, the conversion stops at the first chunk or record which cannot
+    /// be read or converted. Otherwise, these are skipped and registered in
+    /// `summary`. Errors are prefixed with the name of the file, because
+    /// multiple files might be converted at the same time.
+    pub(crate) fn print_records<F, E>(
+        self,
+        formatter: &F,
+        filter: &RecordFilter,
+        strict: bool,
+        progress: &Progress,
+        summary: &mut ConversionSummary,
+        emit: E,
+    ) -> Result<()>
     where
         F: OutputFormatter,
+        E: FnMut(String) -> Result<()>,
     {
-        let bar = self.create_progress_bar().unwrap();
-        for value in self.into_iter() {
-            match formatter.record_to_string(&value) {
-                Ok(s) => println!("{s}"),
-                Err(why) => {
-                    if treat_errors_as_warnings {
-                        log::warn!("Error while reading record: {why}");
-                    } else {
-                        bar.finish_and_clear();
-                        return Err(why);
-                    }
-                }
-            }
+        let file_progress = progress.add_file(self.0.path(), self.len())?;
+        let converter = RecordConverter {
+            name: self.name(),
+            formatter,
+            filter,
+            strict,
+            summary,
+            progress: &file_progress,
+            emit,
+        };
+        self.convert_chunks(converter)
+    }
 
-            bar.inc(1);
+    fn convert_chunks<F, E>(self, mut converter: RecordConverter<F, E>) -> Result<()>
+    where
+        F: OutputFormatter,
+        E: FnMut(String) -> Result<()>,
+    {
+        let name = self.name();
+        let file = self
+            .open()
+            .with_context(|| format!("unable to create parser for '{name}'"))?
+            .with_progress(converter.progress.position_updater());
+
+        for (chunk_data, chunk_number) in file.chunks() {
+            match chunk_data {
+                Ok(chunk_data) => converter.convert_chunk(chunk_number, chunk_data)?,
+                Err(why) => converter.skip(SkippedRange::chunk(chunk_number, root_cause(&why)))?,
+            }
         }
-        bar.finish_and_clear();
         Ok(())
     }
 
-    pub(crate) fn count_records(&self) -> Result<usize> {
-        let mut parser = EvtxParser::from_read_seek(self.0.clone())?;
-        Ok(parser.serialized_records(|r| r.and(Ok(()))).count())
+    /// maps a local file into memory. Other inputs, like stdin, cannot be
+    /// mapped and are read completely
+    fn open(mut self) -> Result<dfir_toolkit::evtx::EvtxFile> {
+        if self.0.is_local() {
+            Ok(dfir_toolkit::evtx::EvtxFile::open(self.0.path().path())?)
+        } else {
+            let mut data = Vec::new();
+            self.0.read_to_end(&mut data)?;
+            Ok(dfir_toolkit::evtx::EvtxFile::from_buffer(self.name(), data)?)
+        }
     }
 
-    pub(crate) fn create_progress_bar(&self) -> Result<ProgressBar> {
-        let count = self.count_records()?;
-
-        let bar = ProgressBar::new(count as u64);
-        let target = ProgressDrawTarget::stderr_with_hz(10);
-        bar.set_draw_target(target);
-
-        let progress_style = ProgressStyle::default_bar()
-            .template("[{elapsed_precise}] {bar:40.cyan/blue} {pos:>9}/{len:9}({percent}%) {msg}")?
-            .progress_chars("##-");
-        bar.set_style(progress_style);
-
-        Ok(bar)
+    /// size of the file in bytes
+    pub(crate) fn len(&self) -> u64 {
+        self.0.len().unwrap_or_default()
     }
 }
@@ -1,16 +1,40 @@
-use anyhow::{bail, Result};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
+use std::sync::{Mutex, MutexGuard};
+use std::thread::Scope;
+
+use anyhow::{bail, Context, Result};
 use cli::Cli;
-use dfir_toolkit::common::FancyParser;
+use conversion_summary::ConversionSummary;
+use dfir_toolkit::common::{FancyParser, FileOutput};
 use evtx_file::EvtxFile;
-use output_formatter::{BodyfileOutputFormatter, JsonOutputFormatter};
+use name_field::NameFormat;
+use output_formatter::{
+    BodyfileJsonOutputFormatter, BodyfileOutputFormatter, JsonOutputFormatter, OutputFormatter,
+};
+use progress::Progress;
+use record_filter::RecordFilter;
 
 mod bf_data;
 mod cli;
+mod conversion_summary;
 mod evtx_file;
+mod output_files;
 mod output_format;
 mod output_formatter;
+mod progress;
+mod record_filter;
 #[macro_use]
 mod macros;
+mod name_field;
+
+/// number of converted lines per file which are buffered when not using
+/// `--interleave`, before the worker has to wait for the output of the
+/// previous files
+const LINE_BUFFER: usize = 4096;
 
 fn main() -> Result<()> {
     let cli = Cli::parse_cli();
@@ -22,16 +46,251 @@ fn main() -> Result<()> {
         );
     }
 
-    for input in cli.evtx_files().iter() {
-        let file = EvtxFile::from(input);
-        match cli.format() {
-            output_format::OutputFormat::Json => {
-                file.print_records(JsonOutputFormatter, !cli.strict())?
+    let name_format = NameFormat::new(cli.name_fields().clone(), cli.name_separator());
+    match cli.format() {
+        output_format::OutputFormat::Json => convert_files(&cli, JsonOutputFormatter),
+        output_format::OutputFormat::Bodyfile => {
+            convert_files(&cli, BodyfileOutputFormatter(name_format))
+        }
+        output_format::OutputFormat::BodyfileJson => {
+            convert_files(&cli, BodyfileJsonOutputFormatter(name_format))
+        }
+    }
+}
+
+/// converts the files using a pool of worker threads. Every worker takes
+/// the next file which has not been converted yet. In strict mode, no new
+/// files are started after the first error.
+fn convert_files<F>(cli: &Cli, formatter: F) -> Result<()>
+where
+    F: OutputFormatter + Sync,
+{
+    let inputs = cli.evtx_files();
+    let threads = match cli.threads() {
+        Some(threads) => usize::from(*threads),
+        None => std::thread::available_parallelism().map_or(1, |n| n.get()),
+    }
+    .clamp(1, inputs.len().max(1));
+
+    let filter = RecordFilter::from(cli);
+    let total_bytes = inputs.iter().filter_map(|i| i.len()).sum();
+    let progress = Progress::new(!cli.verbose().is_silent(), total_bytes)?;
+    let pool = WorkerPool {
+        threads,
+        files: inputs.len(),
+        next_file: AtomicUsize::new(0),
+        failed: AtomicBool::new(false),
+        errors: Mutex::new(Vec::new()),
+        summaries: Mutex::new(Vec::new()),
+    };
+    // returns `true` if the file has been converted without errors
+    let convert = |index: usize, emit: &mut dyn FnMut(String) -> Result<()>| {
+        let file = EvtxFile::from(&inputs[index]);
+        let mut summary = ConversionSummary::new(file.name());
+        let result = file.print_records(
+            &formatter,
+            &filter,
+            *cli.strict(),
+            &progress,
+            &mut summary,
+            emit,
+        );
+        let succeeded = match result {
+            Ok(()) => true,
+            Err(why) => {
+                summary.set_error(&why);
+                pool.add_error(index, why, *cli.strict());
+                false
             }
-            output_format::OutputFormat::Bodyfile => {
-                file.print_records(BodyfileOutputFormatter, !cli.strict())?
+        };
+        progress.suspend_stderr(|| summary.log());
+        pool.summaries.lock().unwrap().push((index, summary));
+        succeeded
+    };
+
+    // the output file is only created if all files have been converted
+    let mut output = match cli.output_dir() {
+        Some(_) => None,
+        None => Some(cli.output().create()?),
+    };
+
+    if let Some(output_dir) = cli.output_dir() {
+        let paths = output_files::output_paths(
+            inputs,
+            output_dir,
+            cli.format().extension(),
+            *cli.overwrite(),
+        )?;
+        std::thread::scope(|scope| {
+            pool.spawn(scope, |index| {
+                let result = FileOutput::create(&paths[index], *cli.overwrite())
+                    .map_err(anyhow::Error::from)
+                    .and_then(|mut output| {
+                        if convert(index, &mut |line| Ok(writeln!(output, "{line}")?)) {
+                            output.commit()?;
+                        }
+                        Ok(())
+                    });
+                if let Err(why) = result {
+                    pool.add_error(index, why, *cli.strict());
+                }
+            });
+        });
+    } else if *cli.interleave() {
+        let output = Mutex::new(output.as_mut().expect("missing output"));
+        std::thread::scope(|scope| {
+            pool.spawn(scope, |index| {
+                convert(index, &mut |line| {
+                    Ok(progress.suspend(|| writeln!(output.lock().unwrap(), "{line}"))?)
+                });
+            });
+        });
+    } else {
+        let (senders, receivers): (Vec<_>, Vec<Receiver<String>>) =
+            inputs.iter().map(|_| sync_channel(LINE_BUFFER)).unzip();
+
+        // a worker takes the sender of a file when it starts converting the
+        // file, and drops it afterwards, which tells the writer to continue
+        // with the next file
+        let senders: Vec<Mutex<Option<SyncSender<String>>>> =
+            senders.into_iter().map(|s| Mutex::new(Some(s))).collect();
+        let release_senders = |from: usize| {
+            for sender in senders[from..].iter() {
+                sender.lock().unwrap().take();
+            }
+        };
+
+        std::thread::scope(|scope| {
+            pool.spawn(scope, |index| {
+                let sender = senders[index].lock().unwrap().take();
+                if let Some(sender) = sender {
+                    convert(index, &mut |line| Ok(sender.send(line)?));
+                }
+            });
+
+            let output = output.as_mut().expect("missing output");
+            for (index, receiver) in receivers.into_iter().enumerate() {
+                for line in receiver.iter() {
+                    if let Err(why) = progress.suspend(|| writeln!(output, "{line}")) {
+                        // dropping the receivers stops the workers
+                        release_senders(index);
+                        return Err(why);
+                    }
+                }
+
+                // files which have not been started yet will not be started
+                if pool.failed.load(Ordering::SeqCst) {
+                    release_senders(index);
+                }
+            }
+            Ok(())
+        })?;
+    }
+
+    if let Some(path) = cli.summary_json() {
+        pool.write_summaries(path)?;
+    }
+    if let Some(path) = cli.report() {
+        pool.write_report(path)?;
+    }
+    let result = pool.into_result();
+    if let (Ok(()), Some(output)) = (&result, output) {
+        output.commit()?;
+    }
+    result
+}
+
+struct WorkerPool {
+    threads: usize,
+    files: usize,
+    next_file: AtomicUsize,
+    failed: AtomicBool,
+
+    /// errors together with the index of the file which caused them
+    errors: Mutex<Vec<(usize, anyhow::Error)>>,
+
+    /// summaries of all files which have been started, together with the
+    /// index of the file
+    summaries: Mutex<Vec<(usize, ConversionSummary)>>,
+}
+
+impl WorkerPool {
+    /// starts the workers, which call `convert` with the index of every
+    /// file which is to be converted
+    fn spawn<'scope, C>(&'scope self, scope: &'scope Scope<'scope, '_>, convert: C)
+    where
+        C: Fn(usize) + Send + Sync + 'scope,
+    {
+        let convert = std::sync::Arc::new(convert);
+        for _ in 0..self.threads {
+            let convert = convert.clone();
+            scope.spawn(move || loop {
+                if self.failed.load(Ordering::SeqCst) {
+                    break;
+                }
+                let index = self.next_file.fetch_add(1, Ordering::SeqCst);
+                if index >= self.files {
+                    break;
+                }
+                convert(index);
+            });
+        }
+    }
+
+    /// registers an error which occured while converting a file. In strict
+    /// mode, no new files are started afterwards
+    fn add_error(&self, index: usize, why: anyhow::Error, strict: bool) {
+        if strict {
+            self.failed.store(true, Ordering::SeqCst);
+        }
+        self.errors.lock().unwrap().push((index, why));
+    }
+
+    /// returns the summaries, sorted in the order of the files on the
+    /// command line
+    fn sorted_summaries(&self) -> MutexGuard<'_, Vec<(usize, ConversionSummary)>> {
+        let mut summaries = self.summaries.lock().unwrap();
+        summaries.sort_by_key(|(index, _)| *index);
+        summaries
+    }
+
+    /// writes the summaries as JSON array, in the order of the files on the
+    /// command line
+    fn write_summaries(&self, path: &Path) -> Result<()> {
+        let summaries = self.sorted_summaries();
+        let summaries: Vec<_> = summaries.iter().map(|(_, summary)| summary).collect();
+        let file =
+            File::create(path).with_context(|| format!("unable to create '{}'", path.display()))?;
+        serde_json::to_writer_pretty(file, &summaries)?;
+        Ok(())
+    }
+
+    /// writes a report of the skipped parts of all files, in the order of
+    /// the files on the command line
+    fn write_report(&self, path: &Path) -> Result<()> {
+        let file =
+            File::create(path).with_context(|| format!("unable to create '{}'", path.display()))?;
+        let mut writer = BufWriter::new(file);
+        for (_, summary) in self.sorted_summaries().iter() {
+            summary.write_report(&mut writer)?;
+        }
+        Ok(writer.flush()?)
+    }
+
+    /// returns the error of the first file which failed, in the order of
+    /// the files on the command line. All other errors are logged.
+    fn into_result(self) -> Result<()> {
+        let mut errors = self.errors.into_inner().unwrap();
+        errors.sort_by_key(|(index, _)| *index);
+        let mut errors = errors.into_iter().map(|(_, why)| why);
+        match errors.next() {
+            None => Ok(()),
+            Some(first_error) => {
+                for why in errors {
+                    log::error!("{why:#}");
+                }
+                Err(first_error)
             }
         }
     }
-    Ok(())
 }
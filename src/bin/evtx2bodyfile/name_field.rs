@@ -0,0 +1,69 @@
+use std::str::FromStr;
+
+use anyhow::bail;
+
+/// an attribute of the event which can be selected using `--name-fields`.
+/// The names are the same as the field names used by `evtxls`
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum NameField {
+    Time,
+    RecordId,
+    EventId,
+    Level,
+    Provider,
+    Channel,
+    Computer,
+    ActivityId,
+
+    /// a value of EventData or UserData, e.g. `data.TargetUserName`
+    Data(String),
+}
+
+impl FromStr for NameField {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(name) = s.strip_prefix("data.") {
+            if name.is_empty() {
+                bail!("missing name of the data field in '{s}'");
+            }
+            return Ok(Self::Data(name.to_owned()));
+        }
+        Ok(match s {
+            "time" => Self::Time,
+            "recordid" => Self::RecordId,
+            "eventid" => Self::EventId,
+            "level" => Self::Level,
+            "provider" => Self::Provider,
+            "channel" => Self::Channel,
+            "computer" => Self::Computer,
+            "activityid" => Self::ActivityId,
+            _ => bail!(
+                "invalid name field '{s}', use one of time, recordid, eventid, level, provider, channel, computer, activityid or data.<NAME>"
+            ),
+        })
+    }
+}
+
+/// specifies how the name column of a bodyfile line is composed. By
+/// default, the name contains all attributes of the event as JSON object.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct NameFormat {
+    fields: Option<(Vec<NameField>, String)>,
+}
+
+impl NameFormat {
+    pub(crate) fn new(fields: Option<Vec<NameField>>, separator: &str) -> Self {
+        Self {
+            fields: fields.map(|fields| (fields, separator.to_owned())),
+        }
+    }
+
+    /// returns the selected fields together with the separator, or `None`
+    /// if the default format is to be used
+    pub(crate) fn fields(&self) -> Option<(&[NameField], &str)> {
+        self.fields
+            .as_ref()
+            .map(|(fields, separator)| (&fields[..], &separator[..]))
+    }
+}
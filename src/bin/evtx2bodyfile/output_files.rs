@@ -0,0 +1,52 @@
+use std::collections::HashSet;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use clio::Input;
+
+/// determines the names of the output files in `dir`, one for every input
+/// file. The name of an output file is the stem of the input file together
+/// with `extension`. If multiple input files have the same stem, a number
+/// is appended to the name of all but the first of them, in the order of
+/// the input files.
+pub(crate) fn output_paths(
+    inputs: &[Input],
+    dir: &Path,
+    extension: &str,
+    overwrite: bool,
+) -> Result<Vec<PathBuf>> {
+    std::fs::create_dir_all(dir)
+        .with_context(|| format!("unable to create directory '{}'", dir.display()))?;
+
+    let mut used_names = HashSet::new();
+    let mut paths = Vec::with_capacity(inputs.len());
+    for input in inputs {
+        let stem = input
+            .path()
+            .file_stem()
+            .map(|s| s.to_owned())
+            .unwrap_or_else(|| OsString::from("evtx"));
+
+        let mut name = stem.clone();
+        let mut counter = 1;
+        while !used_names.insert(name.clone()) {
+            counter += 1;
+            name = stem.clone();
+            name.push(format!("_{counter}"));
+        }
+
+        let mut file_name = name;
+        file_name.push(".");
+        file_name.push(extension);
+        let path = dir.join(file_name);
+        if !overwrite && path.exists() {
+            bail!(
+                "output file '{}' already exists, use '--overwrite' to replace it",
+                path.display()
+            );
+        }
+        paths.push(path);
+    }
+    Ok(paths)
+}
@@ -3,9 +3,27 @@ use strum_macros::Display;
 
 #[derive(ValueEnum, Clone, Display)]
 pub(crate) enum OutputFormat {
+    /// JSON documents which can be imported with es4forensics
     #[strum(serialize = "json")]
     Json,
     
     #[strum(serialize = "bodyfile")]
     Bodyfile,
+
+    /// one JSON object per event, containing the fields of the bodyfile line
+    /// together with the event metadata and the full event data. This is an
+    /// extended bodyfile, which can be read by mactime2
+    #[strum(serialize = "bodyfile-json")]
+    BodyfileJson,
+}
+
+impl OutputFormat {
+    /// extension of the files written with `--output-dir`
+    pub(crate) fn extension(&self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Bodyfile => "bodyfile",
+            Self::BodyfileJson => "bodyfile.json",
+        }
+    }
 }
@@ -2,11 +2,14 @@ use evtx::SerializedEvtxRecord;
 use serde_json::Value;
 
 use crate::bf_data::BfData;
+use crate::name_field::NameFormat;
 
 #[derive(Default)]
 pub(crate) struct JsonOutputFormatter;
 #[derive(Default)]
-pub(crate) struct BodyfileOutputFormatter;
+pub(crate) struct BodyfileOutputFormatter(pub(crate) NameFormat);
+#[derive(Default)]
+pub(crate) struct BodyfileJsonOutputFormatter(pub(crate) NameFormat);
 
 pub(crate) trait OutputFormatter {
     fn record_to_string(&self, record: &SerializedEvtxRecord<Value>) -> anyhow::Result<String>;
@@ -22,6 +25,13 @@ impl OutputFormatter for JsonOutputFormatter {
 impl OutputFormatter for BodyfileOutputFormatter {
     fn record_to_string(&self, record: &SerializedEvtxRecord<Value>) -> anyhow::Result<String> {
         let bf_data = BfData::try_from(record)?;
-        bf_data.try_into_mactime()
+        bf_data.try_into_mactime(&self.0)
+    }
+}
+
+impl OutputFormatter for BodyfileJsonOutputFormatter {
+    fn record_to_string(&self, record: &SerializedEvtxRecord<Value>) -> anyhow::Result<String> {
+        let bf_data = BfData::try_from(record)?;
+        bf_data.try_into_bodyfile_json(&self.0)
     }
 }
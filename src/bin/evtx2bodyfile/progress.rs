@@ -0,0 +1,145 @@
+use std::cell::Cell;
+use std::io::IsTerminal;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use anyhow::Result;
+use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
+
+/// displays one progress bar for every file which is currently being
+/// converted, and one bar for the overall progress. If stderr is not a
+/// terminal, nothing is displayed at all.
+pub(crate) struct Progress {
+    bars: MultiProgress,
+    total: ProgressBar,
+    records: AtomicU64,
+
+    /// the bars must be hidden temporarily while writing to stdout, but only
+    /// if stdout is displayed on the same terminal
+    suspend_output: bool,
+}
+
+impl Progress {
+    /// creates the progress display for files with a total size of
+    /// `total_bytes`
+    pub(crate) fn new(enabled: bool, total_bytes: u64) -> Result<Self> {
+        let enabled = enabled && std::io::stderr().is_terminal();
+        let target = if enabled {
+            ProgressDrawTarget::stderr_with_hz(10)
+        } else {
+            ProgressDrawTarget::hidden()
+        };
+        let bars = MultiProgress::with_draw_target(target);
+        let total = bars.add(ProgressBar::new(total_bytes));
+        total.set_style(Self::style()?);
+        total.set_prefix("total");
+
+        Ok(Self {
+            bars,
+            total,
+            records: AtomicU64::new(0),
+            suspend_output: enabled && std::io::stdout().is_terminal(),
+        })
+    }
+
+    fn style() -> Result<ProgressStyle> {
+        Ok(ProgressStyle::default_bar()
+            .template(
+                "[{elapsed_precise}] {prefix} {bar:40.cyan/blue} {bytes:>10}/{total_bytes:10}({percent}%) ETA {eta} {msg}",
+            )?
+            .progress_chars("##-"))
+    }
+
+    /// creates a progress bar for a file, which is updated whenever the
+    /// parser reads a chunk of the file
+    pub(crate) fn add_file(&self, path: &Path, len: u64) -> Result<FileProgress<'_>> {
+        let bar = self.bars.add(ProgressBar::new(len));
+        bar.set_style(Self::style()?);
+        bar.set_prefix(
+            path.file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default(),
+        );
+        Ok(FileProgress {
+            progress: self,
+            bar,
+            position: Cell::new(0),
+            records: Cell::new(0),
+        })
+    }
+
+    /// runs `f`, which writes to stdout, without breaking the progress bars
+    pub(crate) fn suspend<F: FnOnce() -> R, R>(&self, f: F) -> R {
+        if self.suspend_output {
+            self.bars.suspend(f)
+        } else {
+            f()
+        }
+    }
+
+    /// runs `f`, which writes to stderr, without breaking the progress bars
+    pub(crate) fn suspend_stderr<F: FnOnce() -> R, R>(&self, f: F) -> R {
+        self.bars.suspend(f)
+    }
+
+    fn inc(&self, bytes: u64, records: u64) {
+        self.total.inc(bytes);
+        let records = self.records.fetch_add(records, Ordering::Relaxed) + records;
+        if !self.total.is_hidden() {
+            self.total.set_message(format!("{records} records"));
+        }
+    }
+}
+
+impl Drop for Progress {
+    fn drop(&mut self) {
+        self.total.finish_and_clear();
+    }
+}
+
+/// progress of one single evtx file
+pub(crate) struct FileProgress<'p> {
+    progress: &'p Progress,
+    bar: ProgressBar,
+
+    /// position of the bar which has already been added to the total
+    position: Cell<u64>,
+    records: Cell<u64>,
+}
+
+impl<'p> FileProgress<'p> {
+    /// returns a function which sets the number of bytes which have been
+    /// read from the file
+    pub(crate) fn position_updater(&self) -> impl Fn(u64) + Send + Sync + 'static {
+        let bar = self.bar.clone();
+        move |position| bar.set_position(position)
+    }
+
+    /// counts a record which has been read from the file
+    pub(crate) fn inc_records(&self) {
+        self.records.set(self.records.get() + 1);
+        if !self.bar.is_hidden() {
+            self.bar
+                .set_message(format!("{} records", self.records.get()));
+        }
+
+        let position = self.bar.position();
+        let bytes = position.saturating_sub(self.position.get());
+        self.position.set(self.position.get().max(position));
+        self.progress.inc(bytes, 1);
+    }
+}
+
+impl Drop for FileProgress<'_> {
+    fn drop(&mut self) {
+        // the remainder of the file counts as done, even if it has not
+        // been read completely
+        let remaining = self
+            .bar
+            .length()
+            .unwrap_or_default()
+            .saturating_sub(self.position.get());
+        self.progress.inc(remaining, 0);
+        self.bar.finish_and_clear();
+    }
+}
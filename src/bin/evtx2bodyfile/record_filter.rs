@@ -0,0 +1,104 @@
+use dfir_toolkit::evtx::RecordFields;
+use evtx::EvtxRecord;
+use serde_json::Value;
+
+use crate::cli::Cli;
+
+/// selects the records which are to be converted. The filter works on the
+/// parsed record, before it is converted into JSON, so that records which
+/// are not needed don't have to be serialized at all.
+pub(crate) struct RecordFilter {
+    included_event_ids: Vec<u16>,
+    excluded_event_ids: Vec<u16>,
+
+    /// lowercase names of the channels
+    included_channels: Vec<String>,
+}
+
+impl From<&Cli> for RecordFilter {
+    fn from(cli: &Cli) -> Self {
+        Self {
+            included_event_ids: cli.included_event_ids().clone(),
+            excluded_event_ids: cli.excluded_event_ids().clone(),
+            included_channels: cli
+                .included_channels()
+                .iter()
+                .map(|c| c.to_lowercase())
+                .collect(),
+        }
+    }
+}
+
+impl RecordFilter {
+    fn is_empty(&self) -> bool {
+        self.included_event_ids.is_empty()
+            && self.excluded_event_ids.is_empty()
+            && self.included_channels.is_empty()
+    }
+
+    /// returns `true` if the record passes all filters. The fields are read
+    /// from the tokens of the record. Only if this is not possible, e.g.
+    /// because a template is missing, the record is converted into JSON.
+    /// Records which cannot be converted are accepted, so that their
+    /// conversion reports the error.
+    pub(crate) fn accepts(&self, record: &EvtxRecord) -> bool {
+        if self.is_empty() {
+            return true;
+        }
+
+        match RecordFields::read_system(record) {
+            Some(system) => self.accepts_fields(system.event_id(), system.channel()),
+            None => match record.clone().into_json_value() {
+                Ok(value) => {
+                    let system = value.data.get("Event").and_then(|e| e.get("System"));
+                    self.accepts_fields(
+                        system.and_then(event_id),
+                        system
+                            .and_then(|s| s.get("Channel"))
+                            .and_then(|c| c.as_str()),
+                    )
+                }
+                Err(_) => true,
+            },
+        }
+    }
+
+    /// Records which lack the filtered attribute only pass if no inclusion
+    /// filter is active for this attribute.
+    fn accepts_fields(&self, event_id: Option<u16>, channel: Option<&str>) -> bool {
+        if !(self.included_event_ids.is_empty() && self.excluded_event_ids.is_empty()) {
+            match event_id {
+                Some(event_id) => {
+                    if !self.included_event_ids.is_empty()
+                        && !self.included_event_ids.contains(&event_id)
+                    {
+                        return false;
+                    }
+                    if self.excluded_event_ids.contains(&event_id) {
+                        return false;
+                    }
+                }
+                None => {
+                    if !self.included_event_ids.is_empty() {
+                        return false;
+                    }
+                }
+            }
+        }
+
+        if !self.included_channels.is_empty() {
+            match channel {
+                Some(channel) if self.included_channels.contains(&channel.to_lowercase()) => (),
+                _ => return false,
+            }
+        }
+
+        true
+    }
+}
+
+fn event_id(system: &Value) -> Option<u16> {
+    let event_id = system.get("EventID")?;
+    let event_id = event_id.get("#text").unwrap_or(event_id);
+    event_id.as_u64().and_then(|id| u16::try_from(id).ok())
+}
@@ -0,0 +1,375 @@
+use std::{collections::HashMap, path::Path};
+
+use chrono::Duration;
+use dfir_toolkit::common::{HumanDuration, Timezone};
+use dfir_toolkit::evtx::{
+    adaptive_threshold, find_record_id_gaps, find_time_gaps, EventId, EvtxFile,
+};
+
+use super::{
+    AuditRecord, BusinessHours, Evidence, Finding, Heuristic, Severity, EVENTLOG,
+    SECURITY_AUDITING, SERVICE_CONTROL_MANAGER,
+};
+
+/// ids of all events which are used by any heuristic
+const RELEVANT_EVENT_IDS: [u64; 9] = [104, 1102, 4624, 4697, 4719, 4720, 4726, 4912, 7045];
+
+/// logon types of logons where the user sits in front of the host or
+/// uses a remote desktop
+const INTERACTIVE_LOGON_TYPES: [&str; 3] = ["2", "10", "11"];
+
+/// accounts which are deleted within this time after their creation are
+/// reported
+const SHORT_LIVED_ACCOUNT_HOURS: i64 = 24;
+
+/// the last interactive logon of an account
+struct LastLogon<'a> {
+    record: &'a AuditRecord,
+    origin: String,
+}
+
+/// runs the enabled heuristics over the records of one or more files
+pub(crate) struct AnomalyScanner {
+    heuristics: Vec<Heuristic>,
+    business_hours: BusinessHours,
+    timezone: Timezone,
+    logon_interval: Duration,
+    records: Vec<AuditRecord>,
+    record_ids: Vec<(String, Vec<EventId>)>,
+}
+
+impl AnomalyScanner {
+    pub(crate) fn new(
+        heuristics: Vec<Heuristic>,
+        business_hours: BusinessHours,
+        timezone: Timezone,
+        logon_interval: Duration,
+    ) -> Self {
+        Self {
+            heuristics,
+            business_hours,
+            timezone,
+            logon_interval,
+            records: Vec::new(),
+            record_ids: Vec::new(),
+        }
+    }
+
+    /// reads all records of `path`. Records which cannot be parsed are
+    /// skipped, but they are still missing in the record ids and might
+    /// therefore be reported as gap
+    pub(crate) fn read_file(&mut self, path: &Path) -> anyhow::Result<()> {
+        let file = path.display().to_string();
+        let mut record_ids = Vec::new();
+        for record in EvtxFile::open(path)?.records_json_value() {
+            match record {
+                Err(why) => log::warn!("{file}: error while parsing a record: {why}"),
+                Ok(record) => {
+                    record_ids.push(EventId::from(&record));
+                    if let Some(record) = AuditRecord::try_from(&file, &record) {
+                        if RELEVANT_EVENT_IDS.contains(&record.event_id) {
+                            self.records.push(record);
+                        }
+                    }
+                }
+            }
+        }
+        record_ids.sort();
+        self.record_ids.push((file, record_ids));
+        Ok(())
+    }
+
+    /// returns the findings of all enabled heuristics, sorted by time
+    pub(crate) fn findings(mut self) -> Vec<Finding> {
+        self.records.sort_by_key(|r| r.timestamp);
+
+        let mut findings = Vec::new();
+        for heuristic in self.heuristics.iter() {
+            findings.extend(match heuristic {
+                Heuristic::LogCleared => self.cleared_logs(),
+                Heuristic::AuditPolicy => self.audit_policy_changes(),
+                Heuristic::ServiceInstall => self.service_installs(),
+                Heuristic::ShortLivedAccount => self.short_lived_accounts(),
+                Heuristic::ImplausibleLogon => self.implausible_logons(),
+                Heuristic::Gaps => self.gaps(),
+            });
+        }
+        findings.sort_by(|lhs, rhs| {
+            lhs.first_timestamp
+                .cmp(&rhs.first_timestamp)
+                .then(rhs.severity.cmp(&lhs.severity))
+        });
+        findings
+    }
+
+    fn cleared_logs(&self) -> Vec<Finding> {
+        self.records
+            .iter()
+            .filter_map(|r| {
+                let subject = r.account("Subject").unwrap_or_else(|| "-".to_owned());
+                let description = if r.is(EVENTLOG, 1102) {
+                    format!("the security log has been cleared by {subject}")
+                } else if r.is(EVENTLOG, 104) {
+                    format!(
+                        "the log '{}' has been cleared by {subject}",
+                        r.display_field("Channel")
+                    )
+                } else {
+                    return None;
+                };
+                Some(Finding::new(
+                    Heuristic::LogCleared,
+                    Severity::High,
+                    description,
+                    vec![r.into()],
+                ))
+            })
+            .collect()
+    }
+
+    fn audit_policy_changes(&self) -> Vec<Finding> {
+        self.records
+            .iter()
+            .filter_map(|r| {
+                let subject = r.account("Subject").unwrap_or_else(|| "-".to_owned());
+                let description = if r.is(SECURITY_AUDITING, 4719) {
+                    format!(
+                        "the system audit policy has been changed by {subject} (subcategory: {}, changes: {})",
+                        r.display_field("SubcategoryGuid"),
+                        r.display_field("AuditPolicyChanges")
+                    )
+                } else if r.is(SECURITY_AUDITING, 4912) {
+                    format!(
+                        "the audit policy of {} has been changed by {subject} (subcategory: {}, changes: {})",
+                        r.display_field("TargetUserSid"),
+                        r.display_field("SubcategoryGuid"),
+                        r.display_field("AuditPolicyChanges")
+                    )
+                } else {
+                    return None;
+                };
+                Some(Finding::new(
+                    Heuristic::AuditPolicy,
+                    Severity::Medium,
+                    description,
+                    vec![r.into()],
+                ))
+            })
+            .collect()
+    }
+
+    fn service_installs(&self) -> Vec<Finding> {
+        self.records
+            .iter()
+            .filter(|r| {
+                !self
+                    .business_hours
+                    .contains(&self.timezone.convert(&r.timestamp))
+            })
+            .filter_map(|r| {
+                let image = if r.is(SERVICE_CONTROL_MANAGER, 7045) {
+                    r.display_field("ImagePath")
+                } else if r.is(SECURITY_AUDITING, 4697) {
+                    r.display_field("ServiceFileName")
+                } else {
+                    return None;
+                };
+                let description = format!(
+                    "the service '{}' ({image}) has been installed on {} outside of business hours, at {}",
+                    r.display_field("ServiceName"),
+                    r.computer,
+                    self.timezone.format(&r.timestamp)
+                );
+                Some(Finding::new(
+                    Heuristic::ServiceInstall,
+                    Severity::Medium,
+                    description,
+                    vec![r.into()],
+                ))
+            })
+            .collect()
+    }
+
+    fn short_lived_accounts(&self) -> Vec<Finding> {
+        let account_key = |r: &AuditRecord| {
+            r.field("TargetSid")
+                .or_else(|| r.account("Target"))
+                .map(|key| key.to_lowercase())
+        };
+
+        let mut created_accounts = HashMap::new();
+        let mut findings = Vec::new();
+        for r in self.records.iter() {
+            if r.is(SECURITY_AUDITING, 4720) {
+                if let Some(key) = account_key(r) {
+                    created_accounts.insert(key, r);
+                }
+            } else if r.is(SECURITY_AUDITING, 4726) {
+                let creation = match account_key(r).and_then(|key| created_accounts.remove(&key)) {
+                    Some(creation) => creation,
+                    None => continue,
+                };
+                let lifetime = r.timestamp - creation.timestamp;
+                if lifetime > Duration::hours(SHORT_LIVED_ACCOUNT_HOURS) {
+                    continue;
+                }
+                let description = format!(
+                    "the account {} has been created by {} and deleted by {} {} later",
+                    creation.account("Target").unwrap_or_else(|| "-".to_owned()),
+                    creation
+                        .account("Subject")
+                        .unwrap_or_else(|| "-".to_owned()),
+                    r.account("Subject").unwrap_or_else(|| "-".to_owned()),
+                    HumanDuration::from(lifetime)
+                );
+                findings.push(Finding::new(
+                    Heuristic::ShortLivedAccount,
+                    Severity::Medium,
+                    description,
+                    vec![creation.into(), r.into()],
+                ));
+            }
+        }
+        findings
+    }
+
+    /// finds interactive logons of the same account from different origins
+    /// within `logon_interval`. The origin of a local logon is the host
+    /// itself, the origin of a remote desktop logon is the client.
+    fn implausible_logons(&self) -> Vec<Finding> {
+        let mut last_logons: HashMap<String, LastLogon> = HashMap::new();
+        let mut findings = Vec::new();
+        for r in self.records.iter() {
+            if !r.is(SECURITY_AUDITING, 4624) {
+                continue;
+            }
+            let logon_type = match r.field("LogonType") {
+                Some(logon_type) if INTERACTIVE_LOGON_TYPES.contains(&logon_type.as_str()) => {
+                    logon_type
+                }
+                _ => continue,
+            };
+            let account = match r.account("Target") {
+                Some(account) if is_personal_account(r, &account) => account,
+                _ => continue,
+            };
+
+            let origin = if logon_type == "10" {
+                r.field("WorkstationName").or_else(|| r.field("IpAddress"))
+            } else {
+                None
+            }
+            .unwrap_or_else(|| r.computer.clone());
+            let origin = short_hostname(&origin);
+
+            if let Some(last) = last_logons.get(&account.to_lowercase()) {
+                let interval = r.timestamp - last.record.timestamp;
+                if last.origin != origin && interval <= self.logon_interval {
+                    let description = format!(
+                        "{account} logged on interactively from {} and from {origin} within {}",
+                        last.origin,
+                        HumanDuration::from(interval)
+                    );
+                    findings.push(Finding::new(
+                        Heuristic::ImplausibleLogon,
+                        Severity::High,
+                        description,
+                        vec![last.record.into(), r.into()],
+                    ));
+                }
+            }
+            last_logons.insert(account.to_lowercase(), LastLogon { record: r, origin });
+        }
+        findings
+    }
+
+    /// reports record id gaps and time gaps, like `evtxscan` does. Record id
+    /// gaps without a time gap are a strong indicator of selective deletion.
+    /// Time gaps are only reported if there is no record id gap, which
+    /// would be reported anyway.
+    fn gaps(&self) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        for (file, ids) in self.record_ids.iter() {
+            let threshold = adaptive_threshold(ids);
+            for gap in find_record_id_gaps(ids) {
+                let evidence = vec![
+                    Evidence::new(file, gap.before),
+                    Evidence::new(file, gap.after),
+                ];
+                let (severity, description) = if gap.has_time_gap(threshold) {
+                    (
+                        Severity::Medium,
+                        format!(
+                            "{} records are missing between event {} and event {}, which are {} apart",
+                            gap.missing_ids(),
+                            gap.before.event_record_id(),
+                            gap.after.event_record_id(),
+                            HumanDuration::from(gap.duration())
+                        ),
+                    )
+                } else {
+                    (
+                        Severity::High,
+                        format!(
+                            "{} records are missing between event {} and event {}, which are only {} apart (possible selective deletion)",
+                            gap.missing_ids(),
+                            gap.before.event_record_id(),
+                            gap.after.event_record_id(),
+                            HumanDuration::from(gap.duration())
+                        ),
+                    )
+                };
+                findings.push(Finding::new(
+                    Heuristic::Gaps,
+                    severity,
+                    description,
+                    evidence,
+                ));
+            }
+
+            for gap in find_time_gaps(ids, threshold)
+                .into_iter()
+                .filter(|gap| gap.after.follows(gap.before))
+            {
+                let description = format!(
+                    "no records for {} between event {} and event {}",
+                    HumanDuration::from(gap.duration()),
+                    gap.before.event_record_id(),
+                    gap.after.event_record_id()
+                );
+                findings.push(Finding::new(
+                    Heuristic::Gaps,
+                    Severity::Low,
+                    description,
+                    vec![
+                        Evidence::new(file, gap.before),
+                        Evidence::new(file, gap.after),
+                    ],
+                ));
+            }
+        }
+        findings
+    }
+}
+
+/// machine accounts and builtin accounts like those of the window manager
+/// do logon interactively on every host, so they are not considered
+fn is_personal_account(record: &AuditRecord, account: &str) -> bool {
+    if account.ends_with('$') {
+        return false;
+    }
+    match record.field("TargetUserSid") {
+        Some(sid) => !sid.starts_with("S-1-5-") || sid.starts_with("S-1-5-21-"),
+        None => true,
+    }
+}
+
+/// converts FQDNs to their hostname, so that logons which use different
+/// notations of the same host can be compared
+fn short_hostname(host: &str) -> String {
+    if host.parse::<std::net::IpAddr>().is_ok() {
+        host.to_owned()
+    } else {
+        host.split('.').next().unwrap_or(host).to_uppercase()
+    }
+}
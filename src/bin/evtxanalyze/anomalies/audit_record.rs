@@ -0,0 +1,93 @@
+use chrono::{DateTime, Utc};
+use evtx::SerializedEvtxRecord;
+use serde_json::{Map, Value};
+
+pub(crate) const SECURITY_AUDITING: &str = "Microsoft-Windows-Security-Auditing";
+pub(crate) const EVENTLOG: &str = "Microsoft-Windows-Eventlog";
+pub(crate) const SERVICE_CONTROL_MANAGER: &str = "Service Control Manager";
+
+/// the parts of an event record which are needed by the heuristics
+pub(crate) struct AuditRecord {
+    pub(crate) file: String,
+    pub(crate) record_id: u64,
+    pub(crate) timestamp: DateTime<Utc>,
+    pub(crate) event_id: u64,
+    pub(crate) provider: String,
+    pub(crate) computer: String,
+    data: Map<String, Value>,
+}
+
+impl AuditRecord {
+    /// extracts the system fields and the event data of `record`. Returns
+    /// `None` if the record has no event id or no provider
+    pub(crate) fn try_from(file: &str, record: &SerializedEvtxRecord<Value>) -> Option<Self> {
+        let event = record.data.get("Event")?;
+        let system = event.get("System")?;
+
+        let event_id = system.get("EventID")?;
+        let event_id = event_id.get("#text").unwrap_or(event_id).as_u64()?;
+        let provider = system
+            .get("Provider")?
+            .get("#attributes")?
+            .get("Name")?
+            .as_str()?
+            .to_owned();
+        let computer = system
+            .get("Computer")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_owned();
+
+        // some events store their data in an element below 'UserData',
+        // e.g. 'LogFileCleared'
+        let data = match event.get("EventData") {
+            Some(data) => data.as_object(),
+            None => event
+                .get("UserData")
+                .and_then(Value::as_object)
+                .and_then(|user_data| user_data.values().find_map(Value::as_object)),
+        }
+        .cloned()
+        .unwrap_or_default();
+
+        Some(Self {
+            file: file.to_owned(),
+            record_id: record.event_record_id,
+            timestamp: record.timestamp,
+            event_id,
+            provider,
+            computer,
+            data,
+        })
+    }
+
+    pub(crate) fn is(&self, provider: &str, event_id: u64) -> bool {
+        self.event_id == event_id && self.provider == provider
+    }
+
+    /// returns the value of a field of the event data as string. Empty
+    /// values and placeholders like '-' are returned as `None`
+    pub(crate) fn field(&self, name: &str) -> Option<String> {
+        let value = match self.data.get(name)? {
+            Value::String(s) => s.trim().to_owned(),
+            Value::Number(n) => n.to_string(),
+            _ => return None,
+        };
+        (!value.is_empty() && value != "-").then_some(value)
+    }
+
+    /// returns the field or '-', which is used in descriptions
+    pub(crate) fn display_field(&self, name: &str) -> String {
+        self.field(name).unwrap_or_else(|| "-".to_owned())
+    }
+
+    /// returns the account stored in the fields `<prefix>DomainName` and
+    /// `<prefix>UserName`, like `DOMAIN\user`
+    pub(crate) fn account(&self, prefix: &str) -> Option<String> {
+        let username = self.field(&format!("{prefix}UserName"))?;
+        match self.field(&format!("{prefix}DomainName")) {
+            Some(domain) => Some(format!("{domain}\\{username}")),
+            None => Some(username),
+        }
+    }
+}
@@ -0,0 +1,51 @@
+use std::{fmt::Display, str::FromStr};
+
+use anyhow::{anyhow, bail};
+use chrono::{DateTime, Datelike, FixedOffset, Timelike, Weekday};
+
+/// the hours of the day from monday to friday where changes to a system
+/// are expected, like `7-19`. The end is exclusive.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BusinessHours {
+    begin: u32,
+    end: u32,
+}
+
+impl Default for BusinessHours {
+    fn default() -> Self {
+        Self { begin: 7, end: 19 }
+    }
+}
+
+impl BusinessHours {
+    pub(crate) fn contains(&self, timestamp: &DateTime<FixedOffset>) -> bool {
+        !matches!(timestamp.weekday(), Weekday::Sat | Weekday::Sun)
+            && (self.begin..self.end).contains(&timestamp.hour())
+    }
+}
+
+impl FromStr for BusinessHours {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (begin, end) = s.split_once('-').ok_or_else(|| {
+            anyhow!("invalid business hours '{s}', expected something like '7-19'")
+        })?;
+        let parse_hour = |hour: &str| {
+            hour.trim()
+                .parse::<u32>()
+                .map_err(|_| anyhow!("invalid hour '{hour}' in business hours '{s}'"))
+        };
+        let (begin, end) = (parse_hour(begin)?, parse_hour(end)?);
+        if begin >= end || end > 24 {
+            bail!("invalid business hours '{s}', the hours must be ascending and between 0 and 24");
+        }
+        Ok(Self { begin, end })
+    }
+}
+
+impl Display for BusinessHours {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}-{}", self.begin, self.end)
+    }
+}
@@ -0,0 +1,94 @@
+use std::{fmt::Display, path::Path};
+
+use chrono::{DateTime, Utc};
+use dfir_toolkit::evtx::EventId;
+use serde::Serialize;
+
+use super::{AuditRecord, Heuristic};
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Severity {
+    Low,
+    Medium,
+    High,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Low => write!(f, "low"),
+            Self::Medium => write!(f, "medium"),
+            Self::High => write!(f, "high"),
+        }
+    }
+}
+
+/// a record which supports a finding
+#[derive(Serialize, Clone)]
+pub(crate) struct Evidence {
+    pub(crate) file: String,
+    pub(crate) record_id: u64,
+    pub(crate) timestamp: DateTime<Utc>,
+}
+
+impl Evidence {
+    pub(crate) fn new(file: &str, id: &EventId) -> Self {
+        Self {
+            file: file.to_owned(),
+            record_id: id.event_record_id(),
+            timestamp: *id.timestamp(),
+        }
+    }
+}
+
+impl From<&AuditRecord> for Evidence {
+    fn from(record: &AuditRecord) -> Self {
+        Self {
+            file: record.file.clone(),
+            record_id: record.record_id,
+            timestamp: record.timestamp,
+        }
+    }
+}
+
+impl Display for Evidence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let path = Path::new(&self.file);
+        let filename = path.file_name().unwrap_or(path.as_os_str());
+        write!(f, "{}:{}", filename.to_string_lossy(), self.record_id)
+    }
+}
+
+#[derive(Serialize)]
+pub(crate) struct Finding {
+    pub(crate) heuristic: Heuristic,
+    pub(crate) severity: Severity,
+    pub(crate) first_timestamp: DateTime<Utc>,
+    pub(crate) last_timestamp: DateTime<Utc>,
+    pub(crate) description: String,
+    pub(crate) evidence: Vec<Evidence>,
+}
+
+impl Finding {
+    /// creates a new finding, whose timestamps are taken from `evidence`,
+    /// which must not be empty
+    pub(crate) fn new(
+        heuristic: Heuristic,
+        severity: Severity,
+        description: String,
+        evidence: Vec<Evidence>,
+    ) -> Self {
+        assert!(!evidence.is_empty());
+        let first_timestamp = evidence.iter().map(|e| e.timestamp).min().unwrap();
+        let last_timestamp = evidence.iter().map(|e| e.timestamp).max().unwrap();
+        Self {
+            heuristic,
+            severity,
+            first_timestamp,
+            last_timestamp,
+            description,
+            evidence,
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use std::io::Write;
+
+use chrono::SecondsFormat;
+
+use super::Finding;
+
+const HEADERS: [&str; 6] = [
+    "severity",
+    "heuristic",
+    "first",
+    "last",
+    "records",
+    "description",
+];
+
+/// writes the findings as a table with aligned columns. The description
+/// is the last column, so that it does not need to be padded
+pub(crate) fn write_findings_table<W: Write>(
+    mut w: W,
+    findings: &[Finding],
+) -> std::io::Result<()> {
+    let rows: Vec<[String; 6]> = findings
+        .iter()
+        .map(|finding| {
+            [
+                finding.severity.to_string(),
+                finding.heuristic.name().to_owned(),
+                finding
+                    .first_timestamp
+                    .to_rfc3339_opts(SecondsFormat::Secs, true),
+                finding
+                    .last_timestamp
+                    .to_rfc3339_opts(SecondsFormat::Secs, true),
+                finding
+                    .evidence
+                    .iter()
+                    .map(|e| e.to_string())
+                    .collect::<Vec<_>>()
+                    .join(","),
+                finding.description.clone(),
+            ]
+        })
+        .collect();
+
+    let mut widths = HEADERS.map(str::len);
+    for row in rows.iter() {
+        for (width, cell) in widths.iter_mut().zip(row.iter()) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let headers = HEADERS.map(|h| h.to_uppercase());
+    for row in std::iter::once(&headers).chain(rows.iter()) {
+        let (last, cells) = row.split_last().unwrap();
+        for (cell, width) in cells.iter().zip(widths.iter()) {
+            write!(w, "{cell:width$}  ")?;
+        }
+        writeln!(w, "{last}")?;
+    }
+    Ok(())
+}
@@ -0,0 +1,41 @@
+use clap::ValueEnum;
+use serde::Serialize;
+
+#[derive(ValueEnum, Serialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "kebab-case")]
+pub enum Heuristic {
+    /// a log has been cleared (events 1102 and 104)
+    LogCleared,
+
+    /// the system audit policy or a per-user audit policy has been
+    /// changed (events 4719 and 4912)
+    AuditPolicy,
+
+    /// a service has been installed outside of business hours (events
+    /// 7045 and 4697)
+    ServiceInstall,
+
+    /// a user account has been deleted within 24 hours after its creation
+    /// (events 4720 and 4726)
+    ShortLivedAccount,
+
+    /// an account has been used for interactive logons from two different
+    /// hosts within an implausibly short interval (event 4624)
+    ImplausibleLogon,
+
+    /// gaps in the record ids or in the timestamps of the records
+    Gaps,
+}
+
+impl Heuristic {
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            Self::LogCleared => "log-cleared",
+            Self::AuditPolicy => "audit-policy",
+            Self::ServiceInstall => "service-install",
+            Self::ShortLivedAccount => "short-lived-account",
+            Self::ImplausibleLogon => "implausible-logon",
+            Self::Gaps => "gaps",
+        }
+    }
+}
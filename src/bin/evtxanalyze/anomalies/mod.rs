@@ -0,0 +1,13 @@
+mod anomaly_scanner;
+mod audit_record;
+mod business_hours;
+mod finding;
+mod findings_table;
+mod heuristic;
+
+pub(crate) use anomaly_scanner::*;
+pub(crate) use audit_record::*;
+pub use business_hours::*;
+pub(crate) use finding::*;
+pub(crate) use findings_table::*;
+pub use heuristic::*;
@@ -1,9 +1,11 @@
 use std::{io::stdout, path::PathBuf};
 
 use clap::{Parser, Subcommand, ValueEnum, ValueHint};
-use dfir_toolkit::common::HasVerboseFlag;
+use dfir_toolkit::common::{HasVerboseFlag, HumanDuration, Rfc3339Datetime, Timezone, Verbosity};
 use log::LevelFilter;
 
+use super::anomalies::{write_findings_table, AnomalyScanner, BusinessHours, Heuristic};
+use super::pstree::RootSelector;
 use super::sessions::SessionStore;
 
 #[derive(ValueEnum, Clone)]
@@ -18,6 +20,25 @@ pub enum Format {
     Dot,
 }
 
+#[derive(ValueEnum, Clone)]
+pub enum SessionsFormat {
+    /// one line per session, with all usernames and clients of the session
+    Overview,
+
+    /// one line per session, to be imported into a timeline. The duration
+    /// of a session is formatted as HH:MM:SS
+    Csv,
+}
+
+#[derive(ValueEnum, Clone)]
+pub enum AnomaliesFormat {
+    /// one line per finding
+    Table,
+
+    /// list of findings, including the files of the supporting records
+    Json,
+}
+
 #[derive(Subcommand)]
 pub enum Command {
     /// generate a process tree
@@ -34,6 +55,22 @@ pub enum Command {
         /// output format
         #[clap(short('F'), long("format"), value_enum, default_value_t=Format::Csv)]
         format: Format,
+
+        /// display only the subtrees of processes with this pid (decimal, or
+        /// hexadecimal with a '0x' prefix) or whose image matches this regular
+        /// expression (case insensitive)
+        #[clap(long("root"), value_name = "PID|IMAGE_REGEX")]
+        root: Option<RootSelector>,
+
+        /// display only processes created at or after this time (hint: use
+        /// RFC 3339 syntax). Their ancestors are displayed as context
+        #[clap(short('f'), long("from"))]
+        from: Option<Rfc3339Datetime>,
+
+        /// display only processes created at or before this time (hint: use
+        /// RFC 3339 syntax). Their ancestors are displayed as context
+        #[clap(short('t'), long("to"))]
+        to: Option<Rfc3339Datetime>,
     },
 
     /// display sessions
@@ -48,6 +85,10 @@ pub enum Command {
         /// include anonymous sessions
         #[clap(long("include-anonymous"))]
         include_anonymous: bool,
+
+        /// output format
+        #[clap(short('F'), long("format"), value_enum, default_value_t=SessionsFormat::Overview)]
+        format: SessionsFormat,
     },
 
     /// display one single session
@@ -62,6 +103,39 @@ pub enum Command {
         /// Session ID
         session_id: String,
     },
+
+    /// search Security and System logs for suspicious events and gaps
+    #[clap(name = "anomalies")]
+    Anomalies {
+        /// Names of the evtx files to analyze (should be the paths to
+        /// "Security.evtx" and "System.evtx")
+        #[clap(value_hint=ValueHint::FilePath, num_args=1.., required=true)]
+        evtx_files: Vec<PathBuf>,
+
+        /// output format
+        #[clap(short('F'), long("format"), value_enum, default_value_t=AnomaliesFormat::Table)]
+        format: AnomaliesFormat,
+
+        /// do not run these heuristics (can be specified multiple times or
+        /// as comma separated list)
+        #[clap(short('D'), long("disable"), value_enum, value_delimiter = ',')]
+        disabled_heuristics: Vec<Heuristic>,
+
+        /// hours of the day from monday to friday which are considered as
+        /// business hours; the end is exclusive
+        #[clap(long("business-hours"), value_name = "BEGIN-END", default_value_t = BusinessHours::default())]
+        business_hours: BusinessHours,
+
+        /// timezone of the business hours. Use either a timezone name like
+        /// 'Europe/Berlin' or a fixed offset like '+02:00'
+        #[clap(long("timezone"), default_value_t = Timezone::default(), allow_hyphen_values = true)]
+        timezone: Timezone,
+
+        /// interactive logons of the same account from different hosts
+        /// within this interval are reported, e.g. '90s', '30m' or '1h30m'
+        #[clap(long("logon-interval"), value_name = "DURATION", default_value = "10m")]
+        logon_interval: HumanDuration,
+    },
 }
 
 /// crate provide functions to analyze evtx files
@@ -72,7 +146,7 @@ pub(crate) struct Cli {
     pub(crate) command: Command,
 
     #[command(flatten)]
-    pub(crate) verbose: clap_verbosity_flag::Verbosity,
+    pub(crate) verbose: Verbosity,
 }
 
 impl Cli {
@@ -104,12 +178,16 @@ impl Cli {
             Command::Sessions {
                 evtx_files_dir,
                 include_anonymous,
+                format,
             } => {
                 let sessions = SessionStore::import(evtx_files_dir, *include_anonymous)?;
 
                 let mut csv_writer = csv::Writer::from_writer(stdout());
                 for session in sessions {
-                    session.into_csv(&mut csv_writer)?;
+                    match format {
+                        SessionsFormat::Overview => session.into_csv(&mut csv_writer)?,
+                        SessionsFormat::Csv => session.into_timeline_csv(&mut csv_writer)?,
+                    }
                 }
                 csv_writer.flush()?;
                 Ok(())
@@ -117,6 +195,44 @@ impl Cli {
             _ => unreachable!(),
         }
     }
+
+    pub fn display_anomalies(&self) -> anyhow::Result<()> {
+        match &self.command {
+            Command::Anomalies {
+                evtx_files,
+                format,
+                disabled_heuristics,
+                business_hours,
+                timezone,
+                logon_interval,
+            } => {
+                let heuristics = Heuristic::value_variants()
+                    .iter()
+                    .filter(|h| !disabled_heuristics.contains(h))
+                    .copied()
+                    .collect();
+                let mut scanner = AnomalyScanner::new(
+                    heuristics,
+                    *business_hours,
+                    *timezone,
+                    logon_interval.duration(),
+                );
+                for evtx_file in evtx_files {
+                    scanner.read_file(evtx_file)?;
+                }
+
+                let findings = scanner.findings();
+                match format {
+                    AnomaliesFormat::Table => write_findings_table(stdout(), &findings)?,
+                    AnomaliesFormat::Json => {
+                        println!("{}", serde_json::to_string_pretty(&findings)?)
+                    }
+                }
+                Ok(())
+            }
+            _ => unreachable!(),
+        }
+    }
 }
 
 impl HasVerboseFlag for Cli {
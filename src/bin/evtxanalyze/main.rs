@@ -3,6 +3,7 @@ use dfir_toolkit::common::FancyParser;
 use log::log_enabled;
 use pstree::display_pstree;
 
+mod anomalies;
 mod cli;
 mod pstree;
 mod sessions;
@@ -15,6 +16,7 @@ fn main() -> anyhow::Result<()> {
         Command::PsTree { .. } => display_pstree(&cli),
         Command::Sessions { .. } => cli.display_sessions(),
         Command::Session { .. } => cli.display_single_session(),
+        Command::Anomalies { .. } => cli.display_anomalies(),
     };
 
     if let Err(why) = result {
@@ -22,9 +24,9 @@ fn main() -> anyhow::Result<()> {
         if let Some(cause) = why.source() {
             log::error!("caused by: {cause}");
         }
-        if log_enabled!(log::Level::Warn) {
+        if log_enabled!(log::Level::Debug) {
             for line in format!("{}", why.backtrace()).lines() {
-                log::warn!("{line}");
+                log::debug!("{line}");
             }
         }
         std::process::exit(exitcode::DATAERR);
@@ -1,18 +1,19 @@
 pub mod process;
+pub mod tree_filter;
 pub mod unique_pid;
 use std::{
     cell::RefCell,
-    collections::{BTreeMap, HashMap, HashSet},
+    collections::{HashMap, HashSet},
     io::stdout,
     ops::Deref,
-    rc::{Rc, Weak},
+    rc::Rc,
 };
 
 use chrono::{DateTime, Utc};
-use evtx::EvtxParser;
+use dfir_toolkit::evtx::EvtxFile;
 pub(crate) use process::*;
+pub(crate) use tree_filter::*;
 use regex::Regex;
-use serde_json::{json, Value};
 
 use crate::{
     cli::{Command, Format},
@@ -27,6 +28,9 @@ pub(crate) fn display_pstree(cli: &Cli) -> anyhow::Result<()> {
             username,
             evtx_file,
             format,
+            root,
+            from,
+            to,
         } => {
             let username_regex = username
                 .as_ref()
@@ -40,32 +44,40 @@ pub(crate) fn display_pstree(cli: &Cli) -> anyhow::Result<()> {
                 }
             };
 
-            let mut parser = EvtxParser::from_path(evtx_file)?;
+            let file = EvtxFile::open(evtx_file)?;
             let mut unique_pids = HashMap::new();
             let mut events = HashMap::new();
+            let mut terminations = Vec::new();
             let mut handled_records = 0;
             let mut expected_records: usize = 0;
-            for record in parser.records_json_value() {
+            for record in file.records_json_value() {
                 expected_records += 1;
                 match record {
-                    Err(why) => {
-                        log::error!("error while parsing a record; read {handled_records} until now. I'll try to continue with the next record");
-                        log::warn!("{why}")
-                    }
-                    Ok(record) => match Process::try_from(record) {
+                    Err(why) => log::warn!(
+                        "skipping a record of '{}' which could not be parsed: {why}",
+                        evtx_file.display()
+                    ),
+                    Ok(record) => match ProcessTermination::try_from(&record) {
                         Err(why) => log::error!("{why}"),
-                        Ok(Some(process)) => {
-                            if has_username(&process) {
-                                let pid = UniquePid::from(&process);
-                                unique_pids
-                                    .entry(process.new_process_id)
-                                    .or_insert_with(HashSet::new)
-                                    .insert(pid.clone());
-                                events.insert(pid, Rc::new(RefCell::new(process)));
-                            }
+                        Ok(Some(termination)) => {
+                            terminations.push(termination);
                             handled_records += 1;
                         }
-                        Ok(None) => handled_records += 1,
+                        Ok(None) => match Process::try_from(record) {
+                            Err(why) => log::error!("{why}"),
+                            Ok(Some(process)) => {
+                                if has_username(&process) {
+                                    let pid = UniquePid::from(&process);
+                                    unique_pids
+                                        .entry(process.new_process_id)
+                                        .or_insert_with(HashSet::new)
+                                        .insert(pid.clone());
+                                    events.insert(pid, Rc::new(RefCell::new(process)));
+                                }
+                                handled_records += 1;
+                            }
+                            Ok(None) => handled_records += 1,
+                        },
                     },
                 }
             }
@@ -73,70 +85,87 @@ pub(crate) fn display_pstree(cli: &Cli) -> anyhow::Result<()> {
             log::info!("finished reading all records");
 
             if handled_records < expected_records {
-                log::warn!("I expected {expected_records}, but only {handled_records} could be handled.")
+                log::warn!(
+                    "{} of {expected_records} records of '{}' could not be read",
+                    expected_records - handled_records,
+                    evtx_file.display()
+                )
             }
 
-            log::warn!("found {} process creations", events.len());
+            log::info!("found {} process creations", events.len());
 
             for new_process in events.values() {
                 let parent_pid = new_process.borrow().process_id;
                 let timestamp = new_process.borrow().timestamp;
 
-                /* find the unique parent pid. We assume that it is the pid with the
-                 * largest timestamp which is less than the current timestamp */
-                if let Some(parent_candidates) = unique_pids.get(&parent_pid) {
-                    let mut sorted_candidates: Vec<&UniquePid> = parent_candidates
-                        .iter()
-                        .filter(|p| p.timestamp() <= &timestamp)
-                        .collect();
-                    sorted_candidates.sort();
-                    if let Some(parent_pid) = sorted_candidates.last() {
-                        if let Some(parent) = events.get(parent_pid) {
-                            new_process.borrow_mut().is_root = false;
-                            let child_ts = new_process.borrow().timestamp;
-                            let child_process = Rc::downgrade(new_process);
-                            parent.borrow_mut().children.insert(child_ts, child_process);
-                        } else {
-                            log::error!("parent process not found: {parent_pid}");
-                        }
+                if let Some(parent_pid) = find_process(&unique_pids, parent_pid, &timestamp) {
+                    if let Some(parent) = events.get(parent_pid) {
+                        new_process.borrow_mut().is_root = false;
+                        let child_ts = new_process.borrow().timestamp;
+                        let child_process = Rc::downgrade(new_process);
+                        parent.borrow_mut().children.insert(child_ts, child_process);
                     } else {
-                        log::error!("found no parent for {}", new_process.borrow().command_line);
+                        log::error!("parent process not found: {parent_pid}");
                     }
+                } else if unique_pids.contains_key(&parent_pid) {
+                    log::error!("found no parent for {}", new_process.borrow().command_line);
                 }
             }
 
-            let root_processes: BTreeMap<_, _> = events
+            for termination in terminations {
+                if let Some(pid) =
+                    find_process(&unique_pids, termination.process_id, &termination.timestamp)
+                {
+                    if let Some(process) = events.get(pid) {
+                        process
+                            .borrow_mut()
+                            .end_timestamp
+                            .get_or_insert(termination.timestamp);
+                    }
+                }
+            }
+
+            let mut root_processes: ProcessMap = events
                 .values()
                 .filter(|e| e.borrow().is_root)
                 .map(|e| {
                     let timestamp = e.borrow().timestamp;
-                    let value = Value::from(&*e.borrow());
-                    (timestamp, value)
+                    let proc = Rc::downgrade(e);
+                    (timestamp, proc)
                 })
                 .collect();
 
-            log::warn!("{} processes have no parent", root_processes.len());
+            log::info!("{} processes have no parent", root_processes.len());
+
+            if root.is_some() || from.is_some() || to.is_some() {
+                for process in events.values() {
+                    process.borrow_mut().is_hidden = true;
+                }
+                if let Some(root) = root {
+                    root_processes = root.select(&root_processes);
+                    if root_processes.is_empty() {
+                        log::warn!("found no matching root process");
+                    }
+                }
+                root_processes =
+                    restrict_to_window(&root_processes, from.as_deref(), to.as_deref());
+            }
 
             match format {
                 Format::Json => {
-                    let root_processes: BTreeMap<_, _> = events
+                    let root_processes: Vec<_> = root_processes
                         .values()
-                        .filter(|e| e.borrow().is_root)
-                        .map(|e| {
-                            let timestamp = e.borrow().timestamp;
-                            let value = Value::from(&*e.borrow());
-                            (timestamp, value)
-                        })
+                        .filter_map(|p| p.upgrade())
+                        .map(|p| ProcessTreeNode::from(&*p.borrow()))
                         .collect();
-
-                    let procs_as_json = json!(root_processes);
-                    println!("{}", serde_json::to_string_pretty(&procs_as_json)?);
+                    println!("{}", serde_json::to_string_pretty(&root_processes)?);
                 }
 
                 Format::Csv => {
                     let mut wtr = csv::Writer::from_writer(stdout().lock());
                     let processes: HashSet<_> = events
                         .values()
+                        .filter(|p| !p.borrow().is_hidden)
                         .map(|p| ProcessTableEntry::from(p.as_ref().borrow().deref()))
                         .collect();
                     for process in processes {
@@ -145,41 +174,14 @@ pub(crate) fn display_pstree(cli: &Cli) -> anyhow::Result<()> {
                 }
 
                 Format::Markdown => {
-                    let root_processes: BTreeMap<_, _> = events
-                        .values()
-                        .filter(|e| e.borrow().is_root)
-                        .map(|e| {
-                            let timestamp = e.borrow().timestamp;
-                            let proc = Rc::downgrade(e);
-                            (timestamp, proc)
-                        })
-                        .collect();
                     display_markdown(&root_processes, 0);
                 }
 
                 Format::LaTeX => {
-                    let root_processes: BTreeMap<_, _> = events
-                        .values()
-                        .filter(|e| e.borrow().is_root)
-                        .map(|e| {
-                            let timestamp = e.borrow().timestamp;
-                            let proc = Rc::downgrade(e);
-                            (timestamp, proc)
-                        })
-                        .collect();
                     display_latex(&root_processes);
                 }
 
                 Format::Dot => {
-                    let root_processes: BTreeMap<_, _> = events
-                        .values()
-                        .filter(|e| e.borrow().is_root)
-                        .map(|e| {
-                            let timestamp = e.borrow().timestamp;
-                            let proc = Rc::downgrade(e);
-                            (timestamp, proc)
-                        })
-                        .collect();
                     println!("digraph {{");
                     println!("rankdir=\"LR\";");
                     display_dot(&root_processes);
@@ -193,7 +195,7 @@ pub(crate) fn display_pstree(cli: &Cli) -> anyhow::Result<()> {
     }
 }
 
-fn display_markdown(procs: &BTreeMap<DateTime<Utc>, Weak<RefCell<Process>>>, indent: usize) {
+fn display_markdown(procs: &ProcessMap, indent: usize) {
     for proc in procs.values() {
         if let Some(proc) = proc.upgrade() {
             println!("{}- {}", " ".repeat(indent), proc.borrow());
@@ -202,7 +204,7 @@ fn display_markdown(procs: &BTreeMap<DateTime<Utc>, Weak<RefCell<Process>>>, ind
     }
 }
 
-fn display_latex(procs: &BTreeMap<DateTime<Utc>, Weak<RefCell<Process>>>) {
+fn display_latex(procs: &ProcessMap) {
     if !procs.is_empty() {
         println!("\\begin{{enumerate}}");
         for proc in procs.values() {
@@ -212,7 +214,12 @@ fn display_latex(procs: &BTreeMap<DateTime<Utc>, Weak<RefCell<Process>>>) {
                 let filename = &p.new_process_name;
                 let timestamp = p.timestamp.format("%FT%T");
                 let user = p.subject_user_name.replace('_', "\\_").replace('$', "\\$");
-                println!("\\item[\\texttt{{{pid}}}] \\filename{{{filename}}}, gestartet: \\ts{{{timestamp}}}, Benutzer: \\username{{{user}}}",);
+                let context = if p.is_context {
+                    " \\textit{(nur Kontext)}"
+                } else {
+                    ""
+                };
+                println!("\\item[\\texttt{{{pid}}}] \\filename{{{filename}}}, gestartet: \\ts{{{timestamp}}}, Benutzer: \\username{{{user}}}{context}",);
                 display_latex(&proc.borrow().children);
             }
         }
@@ -220,26 +227,95 @@ fn display_latex(procs: &BTreeMap<DateTime<Utc>, Weak<RefCell<Process>>>) {
     }
 }
 
-fn display_dot(procs: &BTreeMap<DateTime<Utc>, Weak<RefCell<Process>>>) {
+/// finds the process with the pid `pid` which was running at `timestamp`.
+/// We assume that this is the process with the largest creation timestamp
+/// which is less than `timestamp`
+fn find_process<'p>(
+    unique_pids: &'p HashMap<u64, HashSet<UniquePid>>,
+    pid: u64,
+    timestamp: &DateTime<Utc>,
+) -> Option<&'p UniquePid> {
+    unique_pids
+        .get(&pid)?
+        .iter()
+        .filter(|p| p.timestamp() <= timestamp)
+        .max()
+}
+
+fn display_dot(procs: &ProcessMap) {
     for proc in procs.values() {
         if let Some(proc) = proc.upgrade() {
             let p = proc.borrow();
             dot_display_process(&p);
-            println!(
-                "p{} -> p{} [label=\"{}\"]",
-                p.process_id,
-                p.new_process_id,
-                p.timestamp.format("%FT%T")
-            );
-            display_dot(&proc.borrow().children);
+            for child in p.children.values().filter_map(|c| c.upgrade()) {
+                println!(
+                    "p{} -> p{};",
+                    p.event_record_id,
+                    child.borrow().event_record_id
+                );
+            }
+            display_dot(&p.children);
         }
     }
 }
 
+/// every process creation event creates a new node, so we use the event
+/// record id to identify nodes; pids are reused by Windows
 fn dot_display_process(process: &Process) {
+    let image = process
+        .new_process_name
+        .rsplit('\\')
+        .next()
+        .unwrap_or_default();
+    let mut lines = vec![
+        format!("{image} (0x{:04x})", process.new_process_id),
+        process.command_line.clone(),
+        format!("user: {}", process.user()),
+        format!(
+            "started: {}",
+            process
+                .timestamp
+                .to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+        ),
+    ];
+    if let Some(end_timestamp) = process.end_timestamp {
+        lines.push(format!(
+            "ended: {}",
+            end_timestamp.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+        ));
+    }
+    if process.is_context {
+        lines.push("(context only)".to_owned());
+    }
+    let label = lines
+        .iter()
+        .map(|line| dot_escape(line))
+        .collect::<Vec<_>>()
+        .join("\\l");
+    let style = if process.is_context {
+        ", style=\"dashed\", color=\"gray\", fontcolor=\"gray\""
+    } else {
+        ""
+    };
     println!(
-        "p{} [label=<<FONT FACE=\"Courier\">{}</FONT>>, shape=\"box\"];",
-        process.new_process_id,
-        process.new_process_name.replace('\\', "\\\\")
+        "p{} [label=\"{label}\\l\", shape=\"box\", fontname=\"Courier\"{style}];",
+        process.event_record_id
     );
 }
+
+/// escapes a string to be used inside of a quoted Graphviz label. Besides
+/// quotes, backslashes must be escaped because Graphviz interprets
+/// sequences like `\N` or `\G` in labels.
+fn dot_escape(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\\' => result.push_str("\\\\"),
+            '"' => result.push_str("\\\""),
+            '\n' => result.push_str("\\l"),
+            c if c.is_control() => result.push(' '),
+            c => result.push(c),
+        }
+    }
+    result
+}
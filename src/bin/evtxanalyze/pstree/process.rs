@@ -1,16 +1,10 @@
-use std::{
-    cell::RefCell,
-    collections::{BTreeMap, HashMap},
-    fmt::Display,
-    hash::Hash,
-    rc::Weak,
-};
+use std::{cell::RefCell, collections::BTreeMap, fmt::Display, hash::Hash, rc::Weak};
 
 use anyhow::bail;
 use chrono::{DateTime, Utc};
 use evtx::SerializedEvtxRecord;
 use serde::Serialize;
-use serde_json::{json, Value};
+use serde_json::Value;
 
 pub(crate) struct Process {
     pub(crate) timestamp: DateTime<Utc>,
@@ -30,8 +24,15 @@ pub(crate) struct Process {
     pub(crate) target_logon_id: String,
     pub(crate) parent_process_name: Option<String>,
     pub(crate) mandatory_label: Option<String>,
+    pub(crate) end_timestamp: Option<DateTime<Utc>>,
     pub(crate) children: BTreeMap<DateTime<Utc>, Weak<RefCell<Self>>>,
     pub(crate) is_root: bool,
+
+    /// the process is only displayed because one of its descendants is displayed
+    pub(crate) is_context: bool,
+
+    /// the process has been filtered out
+    pub(crate) is_hidden: bool,
 }
 
 #[derive(Serialize, Clone)]
@@ -50,6 +51,7 @@ pub(crate) struct ProcessTableEntry {
     pub(crate) target_domain_name: String,
     pub(crate) target_logon_id: String,
     pub(crate) parent_process_name: Option<String>,
+    pub(crate) context_only: bool,
 }
 
 impl From<&Process> for ProcessTableEntry {
@@ -69,6 +71,7 @@ impl From<&Process> for ProcessTableEntry {
             target_domain_name: value.target_domain_name.clone(),
             target_logon_id: value.target_logon_id.clone(),
             parent_process_name: value.parent_process_name.clone(),
+            context_only: value.is_context,
         }
     }
 }
@@ -90,62 +93,42 @@ impl PartialEq for ProcessTableEntry {
     }
 }
 
-impl From<&Process> for Value {
-    fn from(process: &Process) -> Self {
-        let children: BTreeMap<_, _> = process
-            .children
-            .values()
-            .filter_map(|x| x.upgrade())
-            .map(|p| {
-                let p: &Process = &p.borrow();
-                let v: Value = p.into();
-                (p.timestamp, v)
-            })
-            .collect();
-        let mut result: HashMap<_, _> = vec![
-            ("timestamp".to_owned(), json!(process.timestamp)),
-            ("event_record_id".to_owned(), json!(process.event_record_id)),
-            ("SubjectUserSid".to_owned(), json!(process.subject_user_sid)),
-            (
-                "SubjectUserName".to_owned(),
-                json!(process.subject_user_name),
-            ),
-            (
-                "SubjectDomainName".to_owned(),
-                json!(process.subject_domain_name),
-            ),
-            ("SubjectLogonId".to_owned(), json!(process.subject_logon_id)),
-            ("NewProcessId".to_owned(), json!(process.new_process_id)),
-            ("NewProcessName".to_owned(), json!(process.new_process_name)),
-            (
-                "TokenElevationType".to_owned(),
-                json!(process.token_elevation_type),
-            ),
-            ("ProcessId".to_owned(), json!(process.process_id)),
-            ("CommandLine".to_owned(), json!(process.command_line)),
-            ("TargetUserSid".to_owned(), json!(process.target_user_sid)),
-            ("TargetUserName".to_owned(), json!(process.target_user_name)),
-            (
-                "TargetDomainName".to_owned(),
-                json!(process.target_domain_name),
-            ),
-            ("TargetLogonId".to_owned(), json!(process.target_logon_id)),
-            (
-                "ParentProcessName".to_owned(),
-                json!(process.parent_process_name),
-            ),
-            ("MandatoryLabel".to_owned(), json!(process.mandatory_label)),
-        ]
-        .into_iter()
-        .collect();
-
-        result.extend(
-            children
-                .into_iter()
-                .map(|(k, v)| (k.to_rfc3339_opts(chrono::SecondsFormat::Secs, true), v)),
-        );
+/// a process with all of its children, used for the nested JSON output
+#[derive(Serialize)]
+pub(crate) struct ProcessTreeNode {
+    pub(crate) pid: u64,
+    pub(crate) parent_pid: u64,
+    pub(crate) image: String,
+    pub(crate) command_line: String,
+    pub(crate) user: String,
+    pub(crate) start: DateTime<Utc>,
+    pub(crate) end: Option<DateTime<Utc>>,
+    pub(crate) mandatory_label: Option<String>,
+    pub(crate) event_record_id: u64,
+    pub(crate) context_only: bool,
+    pub(crate) children: Vec<ProcessTreeNode>,
+}
 
-        json!(result)
+impl From<&Process> for ProcessTreeNode {
+    fn from(process: &Process) -> Self {
+        Self {
+            pid: process.new_process_id,
+            parent_pid: process.process_id,
+            image: process.new_process_name.clone(),
+            command_line: process.command_line.clone(),
+            user: process.user().to_owned(),
+            start: process.timestamp,
+            end: process.end_timestamp,
+            mandatory_label: process.mandatory_label.clone(),
+            event_record_id: process.event_record_id,
+            context_only: process.is_context,
+            children: process
+                .children
+                .values()
+                .filter_map(|x| x.upgrade())
+                .map(|p| Self::from(&*p.borrow()))
+                .collect(),
+        }
     }
 }
 
@@ -158,8 +141,12 @@ impl Display for Process {
             self.new_process_id,
             self.timestamp
                 .to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
-            self.target_user_name
-        )
+            self.user()
+        )?;
+        if self.is_context {
+            write!(f, " *(context only)*")?;
+        }
+        Ok(())
     }
 }
 
@@ -202,23 +189,61 @@ fn u64_from_hex_value(value: &Value) -> anyhow::Result<u64> {
     }
 }
 
-impl Process {
-    pub fn try_from(record: SerializedEvtxRecord<Value>) -> anyhow::Result<Option<Self>> {
+fn event_id(record: &SerializedEvtxRecord<Value>) -> anyhow::Result<u64> {
+    let value = &record.data;
+    let event = from_json!(value, "Event");
+    let system = from_json!(event, "System");
+    u64_from_value({
+        let event_id = from_json!(system, "EventID");
+        match event_id.get("#text") {
+            Some(eid) => eid,
+            None => event_id,
+        }
+    })
+}
+
+/// termination of a process, as logged by event 4689
+pub(crate) struct ProcessTermination {
+    pub(crate) timestamp: DateTime<Utc>,
+    pub(crate) process_id: u64,
+}
+
+impl ProcessTermination {
+    pub fn try_from(record: &SerializedEvtxRecord<Value>) -> anyhow::Result<Option<Self>> {
+        if event_id(record)? != 4689 {
+            return Ok(None);
+        }
+
         let value = &record.data;
-        let event = from_json!(value, "Event");
-        let system = from_json!(event, "System");
-        let event_id = u64_from_value({
-            let event_id = from_json!(system, "EventID");
-            match event_id.get("#text") {
-                Some(eid) => eid,
-                None => event_id,
-            }
-        })?;
+        let event_data = from_json!(value, "Event", "EventData");
+        let process_id = u64_from_hex_value(from_json!(event_data, "ProcessId"))?;
+
+        Ok(Some(Self {
+            timestamp: record.timestamp,
+            process_id,
+        }))
+    }
+}
+
+impl Process {
+    /// name of the user which runs the process. Older versions of event 4688
+    /// don't contain the target user, in which case the process runs as the
+    /// subject user.
+    pub fn user(&self) -> &str {
+        if self.target_user_name.is_empty() || self.target_user_name == "-" {
+            &self.subject_user_name
+        } else {
+            &self.target_user_name
+        }
+    }
 
-        if event_id != 4688 {
+    pub fn try_from(record: SerializedEvtxRecord<Value>) -> anyhow::Result<Option<Self>> {
+        if event_id(&record)? != 4688 {
             return Ok(None);
         }
 
+        let value = &record.data;
+        let event = from_json!(value, "Event");
         let event_data = from_json!(event, "EventData");
 
         let subject_user_sid = from_json!(event_data, "SubjectUserSid")
@@ -242,30 +267,30 @@ impl Process {
             .as_str()
             .unwrap()
             .into();
-        let token_elevation_type = from_json!(event_data, "TokenElevationType")
+        let token_elevation_type = from_json_or_null!(event_data, "TokenElevationType")
             .as_str()
-            .unwrap()
+            .unwrap_or("-")
             .into();
         let process_id = u64_from_hex_value(from_json!(event_data, "ProcessId"))?;
         let command_line = from_json!(event_data, "CommandLine")
             .as_str()
             .unwrap()
             .into();
-        let target_user_sid = from_json!(event_data, "TargetUserSid")
+        let target_user_sid = from_json_or_null!(event_data, "TargetUserSid")
             .as_str()
-            .unwrap()
+            .unwrap_or("-")
             .into();
-        let target_user_name = from_json!(event_data, "TargetUserName")
+        let target_user_name = from_json_or_null!(event_data, "TargetUserName")
             .as_str()
-            .unwrap()
+            .unwrap_or("-")
             .into();
-        let target_domain_name = from_json!(event_data, "TargetDomainName")
+        let target_domain_name = from_json_or_null!(event_data, "TargetDomainName")
             .as_str()
-            .unwrap()
+            .unwrap_or("-")
             .into();
-        let target_logon_id = from_json!(event_data, "TargetLogonId")
+        let target_logon_id = from_json_or_null!(event_data, "TargetLogonId")
             .as_str()
-            .unwrap()
+            .unwrap_or("-")
             .into();
         let parent_process_name = from_json_or_null!(event_data, "ParentProcessName")
             .as_str()
@@ -292,8 +317,11 @@ impl Process {
             target_logon_id,
             parent_process_name,
             mandatory_label,
+            end_timestamp: None,
             children: Default::default(),
             is_root: true,
+            is_context: false,
+            is_hidden: false,
         }))
     }
 }
@@ -0,0 +1,105 @@
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    rc::{Rc, Weak},
+    str::FromStr,
+};
+
+use chrono::{DateTime, Utc};
+use regex::Regex;
+
+use super::Process;
+
+pub(crate) type ProcessMap = BTreeMap<DateTime<Utc>, Weak<RefCell<Process>>>;
+
+/// selects the processes whose subtrees shall be displayed
+#[derive(Clone)]
+pub(crate) enum RootSelector {
+    Pid(u64),
+    Image(Regex),
+}
+
+impl FromStr for RootSelector {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let pid = match s.strip_prefix("0x") {
+            Some(hex) => u64::from_str_radix(hex, 16).ok(),
+            None => s.parse().ok(),
+        };
+        match pid {
+            Some(pid) => Ok(Self::Pid(pid)),
+            None => Ok(Self::Image(Regex::new(&format!("(?i){s}"))?)),
+        }
+    }
+}
+
+impl RootSelector {
+    pub(crate) fn matches(&self, process: &Process) -> bool {
+        match self {
+            Self::Pid(pid) => process.new_process_id == *pid,
+            Self::Image(regex) => regex.is_match(&process.new_process_name),
+        }
+    }
+
+    /// returns the topmost processes which match this selector. Processes
+    /// which are part of the subtree of another matching process are not
+    /// returned, because they are displayed as part of this subtree.
+    pub(crate) fn select(&self, procs: &ProcessMap) -> ProcessMap {
+        let mut selected = ProcessMap::new();
+        self.select_into(procs, &mut selected);
+        selected
+    }
+
+    fn select_into(&self, procs: &ProcessMap, selected: &mut ProcessMap) {
+        for (timestamp, proc) in procs {
+            if let Some(p) = proc.upgrade() {
+                if self.matches(&p.borrow()) {
+                    selected.insert(*timestamp, Weak::clone(proc));
+                } else {
+                    self.select_into(&p.borrow().children, selected);
+                }
+            }
+        }
+    }
+}
+
+/// restricts the tree to processes which have been created in the time
+/// window between `from` and `to`. The ancestors of those processes are
+/// kept, but marked as context. All other processes are removed from the
+/// tree and marked as hidden.
+pub(crate) fn restrict_to_window(
+    procs: &ProcessMap,
+    from: Option<&DateTime<Utc>>,
+    to: Option<&DateTime<Utc>>,
+) -> ProcessMap {
+    procs
+        .iter()
+        .filter(|(_, proc)| match proc.upgrade() {
+            Some(p) => restrict_process(&p, from, to),
+            None => false,
+        })
+        .map(|(timestamp, proc)| (*timestamp, Weak::clone(proc)))
+        .collect()
+}
+
+/// returns `true` if the process must be displayed
+fn restrict_process(
+    process: &Rc<RefCell<Process>>,
+    from: Option<&DateTime<Utc>>,
+    to: Option<&DateTime<Utc>>,
+) -> bool {
+    let timestamp = process.borrow().timestamp;
+    let in_window = from.map(|from| &timestamp >= from).unwrap_or(true)
+        && to.map(|to| &timestamp <= to).unwrap_or(true);
+
+    let children = std::mem::take(&mut process.borrow_mut().children);
+    let children = restrict_to_window(&children, from, to);
+    let is_displayed = in_window || !children.is_empty();
+
+    let mut p = process.borrow_mut();
+    p.children = children;
+    p.is_context = !in_window;
+    p.is_hidden = !is_displayed;
+    is_displayed
+}
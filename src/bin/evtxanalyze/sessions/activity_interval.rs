@@ -0,0 +1,33 @@
+use std::fmt::Display;
+
+use chrono::{DateTime, Utc};
+
+/// a period of time in which the user was connected to a session. If no
+/// disconnect or logoff has been found, the interval has no end.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ActivityInterval {
+    begin: DateTime<Utc>,
+    end: Option<DateTime<Utc>>,
+}
+
+impl ActivityInterval {
+    pub fn new(begin: DateTime<Utc>) -> Self {
+        Self { begin, end: None }
+    }
+
+    pub fn close(&mut self, end: DateTime<Utc>) {
+        self.end = Some(end);
+    }
+}
+
+/// uses the ISO 8601 notation for time intervals, e.g.
+/// `2024-03-12T09:00:02/2024-03-12T10:00:00`
+impl Display for ActivityInterval {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/", self.begin.format("%Y-%m-%dT%H:%M:%S"))?;
+        if let Some(end) = self.end {
+            write!(f, "{}", end.format("%Y-%m-%dT%H:%M:%S"))?;
+        }
+        Ok(())
+    }
+}
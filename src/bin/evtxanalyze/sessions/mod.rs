@@ -5,13 +5,19 @@ mod session_event_templates;
 mod session_event_error;
 mod session_as_json;
 mod session_as_csv;
+mod session_as_timeline_csv;
 mod event_as_csv;
 mod active_directory_domain_name;
+mod session_activity;
+mod activity_interval;
 pub use session::*;
 pub use session_store::*;
 pub use session_event::*;
 pub use session_event_error::*;
 pub use session_as_json::*;
 pub use session_as_csv::*;
+pub use session_as_timeline_csv::*;
 pub use event_as_csv::*;
-pub use active_directory_domain_name::*;
\ No newline at end of file
+pub use active_directory_domain_name::*;
+pub use session_activity::*;
+pub use activity_interval::*;
\ No newline at end of file
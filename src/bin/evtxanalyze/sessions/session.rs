@@ -3,8 +3,13 @@ use std::{
     io::Write,
 };
 
-use super::{ActiveDirectoryDomainName, SessionAsCsv, SessionAsJson, SessionEvent};
-use dfirtk_eventdata::SessionId;
+use chrono::{DateTime, Utc};
+
+use super::{
+    ActiveDirectoryDomainName, ActivityInterval, SessionActivity, SessionAsCsv, SessionAsJson,
+    SessionAsTimelineCsv, SessionEvent,
+};
+use dfirtk_eventdata::{EventProvider, SessionId};
 
 pub struct Session {
     events: BTreeSet<SessionEvent>,
@@ -14,6 +19,7 @@ pub struct Session {
     clients: HashSet<String>,
     server: Option<String>,
     computer: String,
+    terminal_services_session_id: Option<String>,
 }
 
 impl Session {
@@ -25,8 +31,27 @@ impl Session {
         self.events.iter()
     }
 
+    pub fn computer(&self) -> &str {
+        &self.computer
+    }
+
+    pub fn usernames(&self) -> &HashSet<String> {
+        &self.usernames
+    }
+
+    pub fn terminal_services_session_id(&self) -> Option<&String> {
+        self.terminal_services_session_id.as_ref()
+    }
+
     pub fn add_event(&mut self, event: SessionEvent) {
         assert_eq!(event.session_id(), &self.session_id);
+        self.add_related_event(event);
+    }
+
+    /// adds an event which belongs to this session, but which has another
+    /// session id. This happens if events of different logs are correlated,
+    /// e.g. events of the terminal services and of the Security log.
+    pub fn add_related_event(&mut self, event: SessionEvent) {
         let mut domain_from_username = None;
         let username;
 
@@ -91,9 +116,69 @@ impl Session {
             }
         }
 
+        if let Some(id) = event.terminal_services_session_id() {
+            match &self.terminal_services_session_id {
+                None => self.terminal_services_session_id = Some(id),
+                Some(s) => {
+                    if s != &id {
+                        log::warn!("multiple terminal services sessions on one single connection are not supported: {s} != {id}");
+                    }
+                }
+            }
+        }
+
         self.events.insert(event);
     }
 
+    /// moves all events of `other` into this session
+    pub fn merge(&mut self, other: Session) {
+        for event in other.events {
+            self.add_related_event(event);
+        }
+    }
+
+    /// returns the periods of time in which the user was connected to this
+    /// session. A session can have multiple intervals if the user
+    /// disconnected and reconnected later.
+    pub fn activity_intervals(&self) -> Vec<ActivityInterval> {
+        let mut intervals = Vec::new();
+        let mut current: Option<ActivityInterval> = None;
+        for event in self.events.iter() {
+            let timestamp = event.record().timestamp;
+            match event.activity() {
+                None | Some(SessionActivity::Authentication) => (),
+                Some(activity) if activity.is_active() => {
+                    current.get_or_insert_with(|| ActivityInterval::new(timestamp));
+                }
+                Some(_) => {
+                    if let Some(mut interval) = current.take() {
+                        interval.close(timestamp);
+                        intervals.push(interval);
+                    }
+                }
+            }
+        }
+        intervals.extend(current);
+
+        // sessions without a logon or reconnect event span all their events
+        if intervals.is_empty() {
+            let mut interval = ActivityInterval::new(self.first_event().record().timestamp);
+            interval.close(self.last_event().record().timestamp);
+            intervals.push(interval);
+        }
+        intervals
+    }
+
+    /// returns `true` if this session has not been found in the Security
+    /// log, e.g. because the Security log has been rolled over, and it
+    /// was reconstructed from other logs only
+    pub fn is_partially_reconstructed(&self) -> bool {
+        !self
+            .events
+            .iter()
+            .any(|e| e.event_type().provider() == EventProvider::SecurityAuditing)
+    }
+
     pub fn first_event(&self) -> &SessionEvent {
         debug_assert!(!self.events.is_empty());
         self.events.first().unwrap()
@@ -111,6 +196,49 @@ impl Session {
         writer.serialize(Into::<SessionAsCsv>::into(self))
     }
 
+    pub fn into_timeline_csv<W>(self, writer: &mut csv::Writer<W>) -> csv::Result<()>
+    where
+        W: Write,
+    {
+        writer.serialize(Into::<SessionAsTimelineCsv>::into(self))
+    }
+
+    /// the logon type of the first logon found in the Security log
+    pub fn logon_type(&self) -> Option<u64> {
+        self.events
+            .iter()
+            .filter(|e| e.activity() == Some(SessionActivity::Logon))
+            .find_map(|e| e.logon_type())
+    }
+
+    /// the time of the first logon, or of the first event if no logon has
+    /// been found
+    pub fn logon_time(&self) -> DateTime<Utc> {
+        self.events
+            .iter()
+            .find(|e| e.activity() == Some(SessionActivity::Logon))
+            .unwrap_or_else(|| self.first_event())
+            .record()
+            .timestamp
+    }
+
+    /// the time of the first logoff after the user connected to the session
+    /// for the last time. If there is no such logoff, the session was still
+    /// open when the logs were collected.
+    pub fn logoff_time(&self) -> Option<DateTime<Utc>> {
+        let mut logoff = None;
+        for event in self.events.iter() {
+            match event.activity() {
+                Some(activity) if activity.is_active() => logoff = None,
+                Some(SessionActivity::Logoff) if logoff.is_none() => {
+                    logoff = Some(event.record().timestamp)
+                }
+                _ => (),
+            }
+        }
+        logoff
+    }
+
     pub fn is_anonymous(&self) -> bool {
         if self.usernames.is_empty() {
             false
@@ -143,6 +271,7 @@ impl From<SessionEvent> for Session {
             clients: HashSet::new(),
             server: None,
             computer,
+            terminal_services_session_id: None,
         };
 
         me.add_event(value);
@@ -197,6 +326,12 @@ impl Into<SessionAsCsv> for Session {
         let duration = end - begin;
         let session_id = self.session_id().clone();
         let events = self.events.len();
+        let activity: Vec<_> = self
+            .activity_intervals()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        let partially_reconstructed = self.is_partially_reconstructed();
         let usernames: Vec<_> = self.usernames.into_iter().collect();
         let clients: Vec<_> = self.clients.into_iter().collect();
         SessionAsCsv {
@@ -209,7 +344,44 @@ impl Into<SessionAsCsv> for Session {
             clients: clients.join(", "),
             server: self.server,
             computer: self.computer,
+            terminal_services_session_id: self.terminal_services_session_id,
+            activity: activity.join(", "),
+            partially_reconstructed,
             events,
         }
     }
 }
+
+#[allow(clippy::from_over_into)]
+impl Into<SessionAsTimelineCsv> for Session {
+    fn into(self) -> SessionAsTimelineCsv {
+        let logon = self.logon_time();
+        let logoff = self.logoff_time();
+        let logon_type = self.logon_type();
+        let evidence: Vec<_> = self
+            .events
+            .iter()
+            .map(|e| {
+                let channel = e.record().data["Event"]["System"]["Channel"]
+                    .as_str()
+                    .unwrap_or_default();
+                format!("{channel}:{}", e.record().event_record_id)
+            })
+            .collect();
+        let mut usernames: Vec<_> = self.usernames.into_iter().collect();
+        usernames.sort();
+        let mut clients: Vec<_> = self.clients.into_iter().collect();
+        clients.sort();
+        SessionAsTimelineCsv {
+            user: usernames.join(", "),
+            domain: self.domain,
+            logon_type,
+            source: clients.join(", "),
+            logon,
+            logoff,
+            duration: logoff.map(|logoff| logoff - logon),
+            session_id: self.session_id,
+            evidence: evidence.join(";"),
+        }
+    }
+}
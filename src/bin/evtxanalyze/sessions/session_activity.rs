@@ -0,0 +1,25 @@
+/// describes how an event changes the state of a session
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SessionActivity {
+    /// the user has been authenticated, but the session has not been started yet
+    Authentication,
+
+    /// a new session has been started
+    Logon,
+
+    /// the user reconnected to an existing session
+    Reconnect,
+
+    /// the user disconnected, but the session is still alive
+    Disconnect,
+
+    /// the session has been terminated
+    Logoff,
+}
+
+impl SessionActivity {
+    /// returns `true` if the user is active in the session after this activity
+    pub fn is_active(&self) -> bool {
+        matches!(self, Self::Logon | Self::Reconnect)
+    }
+}
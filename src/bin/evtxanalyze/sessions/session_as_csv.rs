@@ -27,6 +27,12 @@ pub struct SessionAsCsv {
 
     pub session_id: SessionId,
 
+    pub terminal_services_session_id: Option<String>,
+
+    pub activity: String,
+
+    pub partially_reconstructed: bool,
+
     pub events: usize,
 }
 
@@ -0,0 +1,68 @@
+use chrono::{DateTime, Duration, Utc};
+use dfirtk_eventdata::SessionId;
+use serde::{Serialize, Serializer};
+
+use super::ActiveDirectoryDomainName;
+
+/// a session as one row of a CSV file, which can be imported into a timeline
+#[derive(Serialize, Debug)]
+pub struct SessionAsTimelineCsv {
+    pub user: String,
+
+    pub domain: Option<ActiveDirectoryDomainName>,
+
+    pub logon_type: Option<u64>,
+
+    pub source: String,
+
+    #[serde(serialize_with = "serialize_timestamp")]
+    pub logon: DateTime<Utc>,
+
+    /// is empty if no logoff has been found, e.g. because the session is still open
+    #[serde(serialize_with = "serialize_optional_timestamp")]
+    pub logoff: Option<DateTime<Utc>>,
+
+    /// formatted as `HH:MM:SS`, hours may exceed 24
+    #[serde(serialize_with = "serialize_duration")]
+    pub duration: Option<Duration>,
+
+    pub session_id: SessionId,
+
+    /// channel and record id of all events of this session, separated by `;`
+    pub evidence: String,
+}
+
+fn serialize_duration<S>(duration: &Option<Duration>, s: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match duration {
+        Some(duration) => {
+            let seconds = duration.num_seconds();
+            s.serialize_str(&format!(
+                "{:02}:{:02}:{:02}",
+                seconds / 3600,
+                seconds % 3600 / 60,
+                seconds % 60
+            ))
+        }
+        None => s.serialize_none(),
+    }
+}
+
+fn serialize_timestamp<S>(ts: &DateTime<Utc>, s: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    s.serialize_str(&ts.to_rfc3339_opts(chrono::SecondsFormat::Secs, true))
+}
+
+fn serialize_optional_timestamp<S>(ts: &Option<DateTime<Utc>>, s: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match ts {
+        Some(ts) => serialize_timestamp(ts, s),
+        None => s.serialize_none(),
+    }
+}
@@ -7,7 +7,7 @@ use evtx::SerializedEvtxRecord;
 use serde_json::Value;
 
 use dfirtk_eventdata::SessionEventInfo;
-use super::EventAsCsv;
+use super::{EventAsCsv, SessionActivity};
 use super::session_event_templates::*;
 
 use super::SessionEventError;
@@ -44,6 +44,60 @@ impl SessionEvent {
         &self.session_id
     }
 
+    /// name of the computer which logged this event
+    pub fn computer(&self) -> &str {
+        self.record.data["Event"]["System"]["Computer"]
+            .as_str()
+            .unwrap_or_default()
+    }
+
+    /// describes how this event changes the state of its session
+    pub fn activity(&self) -> Option<SessionActivity> {
+        let event_id = self.event_type.event_id().value();
+        match self.event_type.provider() {
+            EventProvider::TerminalServicesRemoteConnectionManager => match event_id {
+                1149 => Some(SessionActivity::Authentication),
+                _ => None,
+            },
+            EventProvider::TerminalServicesLocalSessionManager => match event_id {
+                21 => Some(SessionActivity::Logon),
+                23 => Some(SessionActivity::Logoff),
+                24 | 39 | 40 => Some(SessionActivity::Disconnect),
+                25 => Some(SessionActivity::Reconnect),
+                _ => None,
+            },
+            EventProvider::SecurityAuditing => match event_id {
+                4624 => Some(SessionActivity::Logon),
+                4634 | 4647 => Some(SessionActivity::Logoff),
+                4778 => Some(SessionActivity::Reconnect),
+                4779 => Some(SessionActivity::Disconnect),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// the id of the terminal services session, which is logged by the
+    /// LocalSessionManager. Be aware that these ids are reused by Windows.
+    pub fn terminal_services_session_id(&self) -> Option<String> {
+        if self.event_type.provider() != EventProvider::TerminalServicesLocalSessionManager {
+            return None;
+        }
+        match &self.record.data["Event"]["UserData"]["EventXML"]["SessionID"] {
+            Value::String(id) => Some(id.clone()),
+            Value::Number(id) => Some(id.to_string()),
+            _ => None,
+        }
+    }
+
+    /// the logon type of a logon or logoff event in the Security log
+    pub fn logon_type(&self) -> Option<u64> {
+        let logon_type = &self.record.data["Event"]["EventData"]["LogonType"];
+        logon_type
+            .as_u64()
+            .or_else(|| logon_type.as_str().and_then(|s| s.parse().ok()))
+    }
+
     pub fn to_csv<W>(&self, writer: &mut csv::Writer<W>) -> csv::Result<()>
     where
         W: Write,
@@ -100,9 +154,20 @@ impl TryFrom<SerializedEvtxRecord<serde_json::Value>> for SessionEvent {
     }
 }
 
+/// events are ordered by their timestamp. Different events can have the
+/// same timestamp, so we must also compare their ids to not lose any event
 impl Ord for SessionEvent {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.record.timestamp.cmp(&other.record.timestamp)
+        self.record
+            .timestamp
+            .cmp(&other.record.timestamp)
+            .then_with(|| self.record.event_record_id.cmp(&other.record.event_record_id))
+            .then_with(|| {
+                self.event_type
+                    .event_id()
+                    .value()
+                    .cmp(&other.event_type.event_id().value())
+            })
     }
 }
 
@@ -116,6 +181,6 @@ impl Eq for SessionEvent {}
 
 impl PartialEq for SessionEvent {
     fn eq(&self, other: &Self) -> bool {
-        self.record.timestamp.eq(&other.record.timestamp)
+        self.cmp(other).is_eq()
     }
 }
@@ -29,7 +29,6 @@ pub struct TSRCMUserAuthenticationSucceeded {}
     username_path = "Event/UserData/EventXML/User",
     client_address_path = "Event/UserData/EventXML/Address"
 )]
-
 pub struct TSLCMSessionLogonSucceeded {}
 
 #[derive(SessionEvent, Default)]
@@ -70,7 +69,9 @@ pub struct TSLCMSessionHasBeenDisconnected {}
     provider = "Microsoft-Windows-TerminalServices-LocalSessionManager",
     event_id = 25,
     description = "Remote Desktop Services: Session reconnection succeeded",
-    session_id = "ActivityId"
+    session_id = "ActivityId",
+    username_path = "Event/UserData/EventXML/User",
+    client_address_path = "Event/UserData/EventXML/Address"
 )]
 pub struct TSLCMSessionReconnectionSucceeded {}
 
@@ -1,11 +1,12 @@
 use std::{collections::HashMap, path::Path};
 
 use anyhow::bail;
+use chrono::{DateTime, Duration, Utc};
 use dfirtk_eventdata::SessionId;
-use evtx::EvtxParser;
+use dfir_toolkit::evtx::EvtxFile;
 use walkdir::WalkDir;
 
-use super::{Session, SessionEvent};
+use super::{Session, SessionActivity, SessionEvent};
 
 static KNOWN_FILES: &[&str] = &[
     "Security.evtx",
@@ -14,8 +15,27 @@ static KNOWN_FILES: &[&str] = &[
     "Microsoft-Windows-TerminalServices-LocalSessionManager%4Operational.evtx",
 ];
 
+/// maximum time between the authentication of a user (RemoteConnectionManager
+/// event 1149) and the logon or reconnect to a terminal services session
+const AUTHENTICATION_TIMEOUT: Duration = Duration::seconds(120);
+
+/// maximum time between the logon or reconnect to a terminal services session
+/// and the corresponding logon in the Security log
+const LOGON_TOLERANCE: Duration = Duration::seconds(60);
+
+/// logon types of RemoteInteractive and Unlock, which are used for logons
+/// to terminal services sessions
+const TERMINAL_SERVICES_LOGON_TYPES: &[u64] = &[10, 7];
+
 pub struct SessionStore {
     sessions: HashMap<SessionId, Session>,
+
+    /// events of the terminal services, which cannot be assigned to a session
+    /// by their session id
+    terminal_services_events: Vec<SessionEvent>,
+
+    /// sessions which have been reconstructed from terminal services events
+    terminal_services_sessions: Vec<Session>,
 }
 
 impl SessionStore {
@@ -36,6 +56,8 @@ impl SessionStore {
 
         let mut sessions = Self {
             sessions: HashMap::<SessionId, Session>::new(),
+            terminal_services_events: Vec::new(),
+            terminal_services_sessions: Vec::new(),
         };
 
         for filename in KNOWN_FILES {
@@ -78,7 +100,7 @@ impl SessionStore {
 
             log::info!("importing {} into session store", path.to_string_lossy());
 
-            for event in EvtxParser::from_path(path)?
+            for event in EvtxFile::open(path)?
                 .records_json_value()
                 .map(|r| r.expect("error reading event"))
                 .map(SessionEvent::try_from)
@@ -89,6 +111,8 @@ impl SessionStore {
             }
         }
 
+        sessions.reconstruct_terminal_services_sessions();
+
         Ok(Self {
             sessions: sessions
                 .sessions
@@ -101,10 +125,26 @@ impl SessionStore {
                     }
                 })
                 .collect(),
+            terminal_services_events: Vec::new(),
+            terminal_services_sessions: sessions
+                .terminal_services_sessions
+                .into_iter()
+                .filter(|s| include_anonymous || !s.is_anonymous())
+                .collect(),
         })
     }
 
     fn add_event(&mut self, event: SessionEvent) {
+        if event.terminal_services_session_id().is_some()
+            || event.activity() == Some(SessionActivity::Authentication)
+        {
+            self.terminal_services_events.push(event);
+        } else {
+            self.add_session_event(event);
+        }
+    }
+
+    fn add_session_event(&mut self, event: SessionEvent) {
         if self.sessions.contains_key(event.session_id()) {
             self.sessions
                 .entry(event.session_id().clone())
@@ -115,20 +155,166 @@ impl SessionStore {
         }
     }
 
-    pub fn find_session(&self, index: &str) -> Option<&Session> {
+    /// The terminal services use their own session ids, which are reused
+    /// after a session has been terminated. So, a session begins with a
+    /// logon and ends with a logoff of the same terminal services session id.
+    /// Every time the user connects to the session, the user has to
+    /// authenticate, and a new logon is logged in the Security log. These
+    /// events are merged into the terminal services session.
+    fn reconstruct_terminal_services_sessions(&mut self) {
+        let mut events = std::mem::take(&mut self.terminal_services_events);
+        events.sort();
+
+        let mut open_sessions: HashMap<(String, String), Session> = HashMap::new();
+        let mut authentications = Vec::new();
+        let mut sessions = Vec::new();
+
+        for event in events {
+            let ts_session_id = match event.terminal_services_session_id() {
+                Some(id) => id,
+                None => {
+                    authentications.push(event);
+                    continue;
+                }
+            };
+            let key = (event.computer().to_owned(), ts_session_id);
+            let activity = event.activity();
+
+            let auth_events = match activity {
+                Some(a) if a.is_active() => {
+                    let (matching, others) = std::mem::take(&mut authentications)
+                        .into_iter()
+                        .partition(|auth| authenticates(auth, &event));
+                    authentications = others;
+                    matching
+                }
+                _ => Vec::new(),
+            };
+
+            let mut session = match open_sessions.remove(&key) {
+                Some(previous) if activity == Some(SessionActivity::Logon) => {
+                    log::warn!(
+                        "found no logoff of terminal services session {} on {}",
+                        key.1,
+                        key.0
+                    );
+                    sessions.push(previous);
+                    Session::from(event)
+                }
+                Some(mut session) => {
+                    session.add_related_event(event);
+                    session
+                }
+                None => Session::from(event),
+            };
+
+            for auth in auth_events {
+                session.add_related_event(auth);
+            }
+
+            if activity == Some(SessionActivity::Logoff) {
+                sessions.push(session);
+            } else {
+                open_sessions.insert(key, session);
+            }
+        }
+        sessions.extend(open_sessions.into_values());
+
+        // authentications without a session are displayed as they are
+        for auth in authentications {
+            self.add_session_event(auth);
+        }
+
+        for mut session in sessions {
+            let connects: Vec<_> = session
+                .iter_events()
+                .filter(|e| e.activity().map(|a| a.is_active()).unwrap_or(false))
+                .map(|e| e.record().timestamp)
+                .collect();
+
+            for timestamp in connects {
+                if let Some(id) = self.find_security_logon(&session, &timestamp) {
+                    let logon = self.sessions.remove(&id).unwrap();
+                    session.merge(logon);
+                }
+            }
+            self.terminal_services_sessions.push(session);
+        }
+    }
+
+    /// searches for the logon to a terminal services session in the
+    /// Security log, which is the logon of the same user which is the nearest
+    /// to `timestamp`.
+    fn find_security_logon(
+        &self,
+        ts_session: &Session,
+        timestamp: &DateTime<Utc>,
+    ) -> Option<SessionId> {
+        let usernames: Vec<_> = ts_session
+            .usernames()
+            .iter()
+            .map(|u| u.to_lowercase())
+            .collect();
         self.sessions
             .iter()
-            .find(|(k, _)| match k {
-                SessionId::ActivityId(id)
-                | SessionId::SessionName(id)
-                | SessionId::LogonId(id)
-                | SessionId::SessionId(id) => index == id,
-                SessionId::None(id) => index == id.to_string(),
+            .filter(|(id, _)| matches!(id, SessionId::LogonId(_)))
+            .filter_map(|(id, session)| {
+                let logon = session.first_event();
+                if logon.activity() != Some(SessionActivity::Logon)
+                    || !logon
+                        .logon_type()
+                        .map(|t| TERMINAL_SERVICES_LOGON_TYPES.contains(&t))
+                        .unwrap_or(false)
+                    || session.computer() != ts_session.computer()
+                    || !session
+                        .usernames()
+                        .iter()
+                        .any(|u| usernames.contains(&u.to_lowercase()))
+                {
+                    return None;
+                }
+                let distance = (logon.record().timestamp - *timestamp).abs();
+                (distance <= LOGON_TOLERANCE).then_some((distance, id))
             })
+            .min_by_key(|(distance, _)| *distance)
+            .map(|(_, id)| id.clone())
+    }
+
+    pub fn find_session(&self, index: &str) -> Option<&Session> {
+        let matches = |k: &SessionId| match k {
+            SessionId::ActivityId(id)
+            | SessionId::SessionName(id)
+            | SessionId::LogonId(id)
+            | SessionId::SessionId(id) => index == id,
+            SessionId::None(id) => index == id.to_string(),
+        };
+        self.sessions
+            .iter()
+            .find(|(k, _)| matches(k))
             .map(|(_, v)| v)
+            .or_else(|| {
+                self.terminal_services_sessions.iter().find(|s| {
+                    s.terminal_services_session_id()
+                        .map(|id| id == index)
+                        .unwrap_or(false)
+                        || s.iter_events().any(|e| matches(e.session_id()))
+                })
+            })
     }
 }
 
+/// checks if `auth` is the authentication which preceded the logon or
+/// reconnect `event`
+fn authenticates(auth: &SessionEvent, event: &SessionEvent) -> bool {
+    let auth_ts = auth.record().timestamp;
+    let event_ts = event.record().timestamp;
+    auth.computer() == event.computer()
+        && auth_ts <= event_ts
+        && event_ts - auth_ts <= AUTHENTICATION_TIMEOUT
+        && auth.event_type().client_address(auth.record())
+            == event.event_type().client_address(event.record())
+}
+
 impl IntoIterator for SessionStore {
     type Item = Session;
 
@@ -136,6 +322,7 @@ impl IntoIterator for SessionStore {
 
     fn into_iter(self) -> Self::IntoIter {
         let mut v = Vec::from_iter(self.sessions.into_values());
+        v.extend(self.terminal_services_sessions);
         v.sort();
         v.into_iter()
     }
@@ -1,39 +1,79 @@
 use clap::{Parser, ValueHint};
-use dfir_toolkit::common::HasVerboseFlag;
+use dfir_toolkit::common::{HasVerboseFlag, OutputArgs, Rfc3339Datetime, Verbosity};
+use dfir_toolkit::evtx::RecordIdRanges;
 use log::LevelFilter;
 
 use crate::output_format::OutputFormat;
 
-/// Display one or more events from an evtx file
+/// Display one or more events from one or more evtx files
 #[derive(Parser)]
 #[clap(name=env!("CARGO_BIN_NAME"), author, version)]
 pub (crate) struct Cli {
-    /// Name of the evtx file to read from
-    #[clap(value_hint=ValueHint::FilePath)]
-    pub (crate) evtx_file: String,
+    /// Names of the evtx files to read from. Record identifiers are
+    /// interpreted per file
+    #[clap(value_hint=ValueHint::FilePath, num_args=1.., required=true)]
+    pub (crate) evtx_files: Vec<String>,
 
     /// filter: minimal event record identifier
-    #[clap(long)]
+    #[clap(long, conflicts_with = "record_ids")]
     pub (crate) min: Option<u64>,
 
     /// filter: maximal event record identifier
-    #[clap(long)]
+    #[clap(long, conflicts_with = "record_ids")]
     pub (crate) max: Option<u64>,
 
     /// show only the one event with this record identifier
-    #[clap(short, long)]
+    #[clap(short, long, conflicts_with = "record_ids")]
     pub (crate) id: Option<u64>,
 
+    /// filter: show only events whose record identifier is contained in
+    /// one of these ranges, e.g. '..500', '12000..' or '1000..2000,5000'.
+    /// The number of ids in these ranges which are missing in a file is
+    /// reported to stderr
+    #[clap(long("range"), value_name = "RANGES")]
+    pub (crate) record_ids: Option<RecordIdRanges>,
+
+    /// filter: hide events older than the specified date (hint: use RFC 3339 syntax)
+    #[clap(short('f'), long("from"))]
+    pub (crate) not_before: Option<Rfc3339Datetime>,
+
+    /// filter: hide events newer than the specified date (hint: use RFC 3339 syntax)
+    #[clap(short('t'), long("to"))]
+    pub (crate) not_after: Option<Rfc3339Datetime>,
+
+    /// assume that the records are sorted by time, so that reading can stop
+    /// at the first record after the time window. If a record out of order
+    /// is found before, the file is read completely
+    #[clap(long("assume-sorted"), requires = "not_after")]
+    pub (crate) assume_sorted: bool,
+
+    /// sort the records of all files by time. Otherwise, the records are
+    /// displayed in the order of the files
+    #[clap(long("sort"))]
+    pub (crate) sort: bool,
+
     /// don't display the records in a table format
     #[clap(short('T'), long("display-table"))]
     pub (crate) show_table: bool,
 
+    /// highlight this term in the output. If specified multiple times, every
+    /// term is highlighted using a different color
+    #[clap(long("highlight"), value_name = "TERM")]
+    pub (crate) highlight: Vec<String>,
+
+    /// match the terms specified with '--highlight' case sensitively
+    #[clap(long("case-sensitive"), requires = "highlight")]
+    pub (crate) case_sensitive: bool,
+
     /// output format
     #[clap(value_enum, short('F'), long("format"), default_value_t = OutputFormat::Xml)]
     pub (crate) format: OutputFormat,
 
     #[clap(flatten)]
-    verbose: clap_verbosity_flag::Verbosity,
+    pub (crate) output: OutputArgs,
+
+    #[clap(flatten)]
+    verbose: Verbosity,
 }
 
 impl HasVerboseFlag for Cli {
@@ -0,0 +1,105 @@
+use regex::{Captures, Regex, RegexBuilder};
+
+use crate::cli::Cli;
+
+/// escape sequences which are used to enclose matches of the search terms.
+/// Only the background is changed, so that the colors of JSON output are
+/// kept
+const TERMINAL_MARKERS: [(&str, &str); 6] = [
+    ("\x1b[7m", "\x1b[27m"),
+    ("\x1b[43m", "\x1b[49m"),
+    ("\x1b[46m", "\x1b[49m"),
+    ("\x1b[45m", "\x1b[49m"),
+    ("\x1b[42m", "\x1b[49m"),
+    ("\x1b[44m", "\x1b[49m"),
+];
+
+const PLAIN_MARKERS: (&str, &str) = (">>>", "<<<");
+
+/// marks every occurrence of the terms specified with `--highlight`. If
+/// the output is written to a terminal, every term gets its own color;
+/// otherwise matches are enclosed by `>>>` and `<<<`
+pub(crate) struct Highlighter {
+    regex: Option<Regex>,
+    use_colors: bool,
+}
+
+impl Highlighter {
+    pub(crate) fn from_cli(cli: &Cli, use_colors: bool) -> Self {
+        Self::new(&cli.highlight, !cli.case_sensitive, use_colors)
+    }
+
+    fn new<S: AsRef<str>>(terms: &[S], ignore_case: bool, use_colors: bool) -> Self {
+        let regex = if terms.is_empty() {
+            None
+        } else {
+            let pattern = terms
+                .iter()
+                .map(|term| format!("({})", regex::escape(term.as_ref())))
+                .collect::<Vec<_>>()
+                .join("|");
+            Some(
+                RegexBuilder::new(&pattern)
+                    .case_insensitive(ignore_case)
+                    .build()
+                    .expect("escaped search terms must be valid regular expressions"),
+            )
+        };
+        Self { regex, use_colors }
+    }
+
+    pub(crate) fn highlight(&self, text: String) -> String {
+        match &self.regex {
+            None => text,
+            Some(regex) => regex
+                .replace_all(&text, |caps: &Captures| {
+                    let (begin, end) = self.markers(caps);
+                    format!("{begin}{}{end}", &caps[0])
+                })
+                .into_owned(),
+        }
+    }
+
+    fn markers(&self, caps: &Captures) -> (&'static str, &'static str) {
+        if self.use_colors {
+            // group 0 is the whole match, group n belongs to the n-th term
+            let term = (1..caps.len())
+                .find(|idx| caps.get(*idx).is_some())
+                .unwrap_or(1)
+                - 1;
+            TERMINAL_MARKERS[term % TERMINAL_MARKERS.len()]
+        } else {
+            PLAIN_MARKERS
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Highlighter;
+
+    #[test]
+    fn highlight_plain() {
+        let h = Highlighter::new(&["admin", "cmd.exe"], true, false);
+        assert_eq!(
+            h.highlight("Administrator started CMD.EXE".to_owned()),
+            ">>>Admin<<<istrator started >>>CMD.EXE<<<"
+        );
+        assert_eq!(h.highlight("cmdXexe".to_owned()), "cmdXexe");
+    }
+
+    #[test]
+    fn highlight_case_sensitive() {
+        let h = Highlighter::new(&["Admin"], false, false);
+        assert_eq!(h.highlight("admin Admin".to_owned()), "admin >>>Admin<<<");
+    }
+
+    #[test]
+    fn highlight_with_colors() {
+        let h = Highlighter::new(&["admin", "cmd"], true, true);
+        assert_eq!(
+            h.highlight("admin cmd".to_owned()),
+            "\x1b[7madmin\x1b[27m \x1b[43mcmd\x1b[49m"
+        );
+    }
+}
@@ -1,49 +1,138 @@
-use std::path::PathBuf;
+use std::{collections::HashMap, path::PathBuf};
 
 use anyhow::Result;
-use evtx::EvtxParser;
+use dfir_toolkit::evtx::EvtxFile;
 
-use dfir_toolkit::common::FancyParser;
+use dfir_toolkit::common::{FancyParser, FileOutput};
 
 mod cli;
+mod highlighter;
 mod output_format;
 mod record_filter;
 mod record_list_formatter;
-mod unfiltered;
+mod time_window;
 
 use cli::Cli;
 use output_format::OutputFormat;
-use record_filter::RecordFilter;
-use record_list_formatter::RecordListFormatter;
+use record_filter::{RecordFilter, Selection};
+use record_list_formatter::{display_xml_and_json, RecordListFormatter, RecordPair, SourcedRecord};
+use time_window::TimeWindow;
+
+/// exit code which is used if no record matched the filters
+const NO_RECORDS_FOUND: i32 = 1;
 
 fn main() -> Result<()> {
     let cli = Cli::parse_cli();
 
-    let path = PathBuf::from(&cli.evtx_file);
+    let mut output = match cli.output.create() {
+        Ok(output) => output,
+        Err(why) => {
+            log::error!("{why}");
+            std::process::exit(exitcode::CANTCREAT);
+        }
+    };
 
-    let parser = EvtxParser::from_path(path)?;
+    match display_records(&cli, &mut output).and_then(|count| output.commit().map(|_| count).map_err(Into::into)) {
+        Ok(0) => {
+            log::warn!("no records matched the specified filters");
+            std::process::exit(NO_RECORDS_FOUND);
+        }
+        Ok(_) => Ok(()),
+        Err(why) => {
+            log::error!("{why}");
+            std::process::exit(exitcode::DATAERR);
+        }
+    }
+}
+
+/// writes the selected records to `output` and returns the number of
+/// records
+fn display_records(cli: &Cli, output: &mut FileOutput) -> Result<usize> {
+    let time_window = TimeWindow::from(cli);
 
     match cli.format {
         OutputFormat::Json => {
-            let (record_ids, records) = if let Some(filter_id) = cli.id {
-                serde_json::Value::filter_by_id(parser, filter_id)
-            } else {
-                let min = cli.min.unwrap_or(u64::MIN);
-                let max = cli.max.unwrap_or(u64::MAX);
-                serde_json::Value::filter_by_range(parser, min, max)
-            };
-            serde_json::Value::display_results(record_ids, records, &cli);
+            let records = select_records::<serde_json::Value>(cli, &time_window)?;
+            let count = records.len();
+            serde_json::Value::display_results(records, cli, output)?;
+            Ok(count)
         }
         OutputFormat::Xml => {
-            let (record_ids, records) = if let Some(filter_id) = cli.id {
-                String::filter_by_id(parser, filter_id)
-            } else {
-                let min = cli.min.unwrap_or(u64::MIN);
-                let max = cli.max.unwrap_or(u64::MAX);
-                String::filter_by_range(parser, min, max)
-            };
-            String::display_results(record_ids, records, &cli);
+            let records = select_records::<String>(cli, &time_window)?;
+            let count = records.len();
+            String::display_results(records, cli, output)?;
+            Ok(count)
+        }
+        OutputFormat::Both => {
+            let mut records = Vec::new();
+            for evtx_file in cli.evtx_files.iter() {
+                let file = EvtxFile::open(PathBuf::from(evtx_file))?;
+                let xml_records = String::select(&file, cli, &time_window);
+                report_missing_ids(cli, evtx_file, &xml_records);
+                let mut json_records: HashMap<_, _> =
+                    serde_json::Value::select(&file, cli, &time_window)
+                        .records
+                        .into_iter()
+                        .map(|r| (r.event_record_id, r))
+                        .collect();
+                records.extend(xml_records.records.into_iter().map(|xml| RecordPair {
+                    source: evtx_file,
+                    json: json_records.remove(&xml.event_record_id),
+                    xml,
+                }));
+            }
+            if cli.sort {
+                records.sort_by_key(|r| r.xml.timestamp);
+            }
+            let count = records.len();
+            display_xml_and_json(records, cli, output)?;
+            Ok(count)
+        }
+    }
+}
+
+/// reads the selected records of all files, in the order of the files or
+/// sorted by time
+fn select_records<'a, T>(cli: &'a Cli, time_window: &TimeWindow) -> Result<Vec<SourcedRecord<'a, T>>>
+where
+    T: RecordFilter,
+{
+    let mut records = Vec::new();
+    for evtx_file in cli.evtx_files.iter() {
+        let file = EvtxFile::open(PathBuf::from(evtx_file))?;
+        let selection = T::select(&file, cli, time_window);
+        report_missing_ids(cli, evtx_file, &selection);
+        records.extend(
+            selection
+                .records
+                .into_iter()
+                .map(|record| SourcedRecord {
+                    source: evtx_file,
+                    record,
+                }),
+        );
+    }
+
+    // this is a stable sort, so records with the same timestamp keep the
+    // order of the files
+    if cli.sort {
+        records.sort_by_key(|r| r.record.timestamp);
+    }
+    Ok(records)
+}
+
+/// reports how many of the ids requested with `--range` don't exist in the
+/// file. Gaps in the record ids might be a sign of tampering, records which
+/// could not be read are reported separately
+fn report_missing_ids<T>(cli: &Cli, evtx_file: &str, selection: &Selection<T>) {
+    if selection.unreadable() > 0 {
+        log::warn!("{} records of '{evtx_file}' could not be read", selection.unreadable());
+    }
+    if let Some(ranges) = &cli.record_ids {
+        match selection.missing_ids(ranges) {
+            Some(0) => (),
+            Some(missing) => log::warn!("{missing} of the requested record ids are missing in '{evtx_file}'"),
+            None => log::info!("'{evtx_file}' has not been read completely, so missing record ids cannot be counted"),
         }
     }
-    Ok(())
 }
@@ -3,4 +3,8 @@
 pub (crate) enum OutputFormat {
     Json,
     Xml,
+
+    /// XML and JSON of every record, either side by side (if stdout is a
+    /// wide terminal) or one after the other, separated by XML comments
+    Both,
 }
\ No newline at end of file
@@ -1,75 +1,231 @@
-use std::{io::{Read, Seek}, collections::HashMap, fs::File};
+use std::collections::HashSet;
 
-use evtx::{EvtxParser, SerializedEvtxRecord};
+use chrono::{DateTime, Utc};
+use dfir_toolkit::evtx::{EvtxFile, RecordIdRanges};
+use evtx::{err::EvtxError, EvtxRecord, SerializedEvtxRecord};
 
-use crate::unfiltered::Unfiltered;
+use crate::{cli::Cli, time_window::TimeWindow};
 
+/// the records selected from one file
+pub (crate) struct Selection<T> {
+    pub (crate) records: Vec<SerializedEvtxRecord<T>>,
 
-pub (crate) trait RecordFilter: Sized {
-    type ReaderType: Read + Seek;
+    /// ids of all records whose id has been accepted, regardless of the
+    /// time window
+    found_ids: HashSet<u64>,
 
-    fn unfiltered(parser: &mut EvtxParser<Self::ReaderType>) -> Unfiltered<Self>;
+    /// smallest and largest record id of the file
+    id_bounds: Option<(u64, u64)>,
+
+    /// `false` if reading stopped before the end of the file
+    complete: bool,
+
+    /// number of records which could not be read
+    unreadable: u64,
+
+    /// number of records which could not be read, and whose id is unknown
+    unknown_ids: u64,
+}
+
+impl<T> Selection<T> {
+    /// returns the number of ids in `ranges` for which no record exists,
+    /// or `None` if the file has not been read completely. Open ends of
+    /// the ranges are limited to the smallest and the largest id of the
+    /// file. Records whose id could not be read are not counted as missing,
+    /// because their ids might be in `ranges`.
+    pub (crate) fn missing_ids(&self, ranges: &RecordIdRanges) -> Option<u64> {
+        if !self.complete {
+            return None;
+        }
+        let Some((first_id, last_id)) = self.id_bounds else {
+            return Some(0);
+        };
+
+        let requested: u64 = ranges
+            .ranges()
+            .map(|range| {
+                let lower = range.begin().unwrap_or(first_id);
+                let upper = range.end().unwrap_or(last_id);
+                if lower <= upper {
+                    upper - lower + 1
+                } else {
+                    0
+                }
+            })
+            .fold(0, u64::saturating_add);
+        Some(
+            requested
+                .saturating_sub(self.found_ids.len() as u64)
+                .saturating_sub(self.unknown_ids),
+        )
+    }
+
+    /// returns the number of records which could not be read
+    pub (crate) fn unreadable(&self) -> u64 {
+        self.unreadable
+    }
+}
+
+pub (crate) trait RecordFilter: Sized + Send {
+    #[allow(clippy::result_large_err)]
+    fn convert(record: EvtxRecord<'_>) -> evtx::err::Result<SerializedEvtxRecord<Self>>;
+
+    /// applies the filters specified on the command line
+    fn select(
+        file: &EvtxFile,
+        cli: &Cli,
+        time_window: &TimeWindow,
+    ) -> Selection<Self> {
+        if let Some(filter_id) = cli.id {
+            Self::filter_by_id(file, filter_id, time_window)
+        } else if let Some(ranges) = &cli.record_ids {
+            Self::filter_by_ranges(file, ranges, time_window)
+        } else {
+            let min = cli.min.unwrap_or(u64::MIN);
+            let max = cli.max.unwrap_or(u64::MAX);
+            Self::filter_by_range(file, min, max, time_window)
+        }
+    }
 
     fn filter_by_id(
-        mut parser: EvtxParser<Self::ReaderType>,
+        file: &EvtxFile,
         filter_id: u64,
-    ) -> (Vec<u64>, HashMap<u64, SerializedEvtxRecord<Self>>) {
-        let mut record_ids: Vec<u64> = Vec::new();
-        let mut records: HashMap<u64, SerializedEvtxRecord<Self>> = HashMap::new();
-        if let Some(result) = Self::unfiltered(&mut parser).find(|record| match record {
-                    Ok(evt) => evt.event_record_id == filter_id,
-                    _ => false,
-                }) {
-            let evt = result.unwrap();
-            record_ids.push(evt.event_record_id);
-            records.insert(evt.event_record_id, evt);
-        }
-        (record_ids, records)
+        time_window: &TimeWindow,
+    ) -> Selection<Self> {
+        Self::filter_records(file, |id| id == filter_id, time_window, true)
     }
 
     fn filter_by_range(
-        mut parser: EvtxParser<Self::ReaderType>,
+        file: &EvtxFile,
         min: u64,
         max: u64,
-    ) -> (Vec<u64>, HashMap<u64, SerializedEvtxRecord<Self>>) {
-        let mut record_ids: Vec<u64> = Vec::new();
-        let mut records: HashMap<u64, SerializedEvtxRecord<Self>> = HashMap::new();
-
-        for record in Self::unfiltered(&mut parser) {
-            match record {
-                Err(_) => (),
-                Ok(evt) => {
-                    let id = evt.event_record_id;
-
-                    if id >= min && id <= max {
-                        record_ids.push(id);
-                        records.insert(id, evt);
+        time_window: &TimeWindow,
+    ) -> Selection<Self> {
+        Self::filter_records(file, |id| id >= min && id <= max, time_window, false)
+    }
+
+    fn filter_by_ranges(
+        file: &EvtxFile,
+        ranges: &RecordIdRanges,
+        time_window: &TimeWindow,
+    ) -> Selection<Self> {
+        Self::filter_records(file, |id| ranges.contains(id), time_window, false)
+    }
+
+    /// returns all records whose id is accepted by `id_filter` and which
+    /// have been created in `time_window`. Only those records are converted
+    /// into the output format.
+    ///
+    /// If the records are assumed to be sorted by time, reading stops at the
+    /// first record which is newer than `time_window`, unless a record out
+    /// of order has been found before.
+    #[allow(clippy::result_large_err)]
+    fn filter_records<F>(
+        file: &EvtxFile,
+        id_filter: F,
+        time_window: &TimeWindow,
+        stop_after_first_match: bool,
+    ) -> Selection<Self>
+    where
+        F: Fn(u64) -> bool + Send + Sync + Clone,
+    {
+        let mut records: Vec<SerializedEvtxRecord<Self>> = Vec::new();
+        let mut found_ids = HashSet::new();
+        let mut id_bounds: Option<(u64, u64)> = None;
+        let mut complete = true;
+        let mut last_timestamp: Option<DateTime<Utc>> = None;
+        let mut is_sorted = true;
+        let mut unreadable = 0;
+        let mut unknown_ids = 0;
+
+        // a record which cannot be converted still exists, so its id is
+        // not missing
+        let results = file.serialized_records(|record| {
+            record.map(|record| {
+                let id = record.event_record_id;
+                let timestamp = record.timestamp;
+                let accepted = id_filter(id);
+                if accepted && time_window.contains(&timestamp) {
+                    (id, timestamp, accepted, Some(Self::convert(record)))
+                } else {
+                    (id, timestamp, accepted, None)
+                }
+            })
+        });
+
+        for result in results {
+            match result {
+                Err(EvtxError::FailedToParseRecord { record_id, source }) => {
+                    log::warn!("unable to read the record {record_id}: {source}");
+                    unreadable += 1;
+                    if id_filter(record_id) {
+                        found_ids.insert(record_id);
+                    }
+                }
+                Err(why) => {
+                    log::warn!("unable to read a record: {why}");
+                    unreadable += 1;
+                    unknown_ids += 1;
+                }
+                Ok((id, timestamp, accepted, record)) => {
+                    if last_timestamp.is_some_and(|last| timestamp < last) {
+                        is_sorted = false;
+                    }
+                    last_timestamp = Some(timestamp);
+                    id_bounds = Some(match id_bounds {
+                        None => (id, id),
+                        Some((first, last)) => (first.min(id), last.max(id)),
+                    });
+                    if accepted {
+                        found_ids.insert(id);
+                    }
+
+                    match record {
+                        Some(Err(why)) => {
+                            log::warn!("unable to read the record {id}: {why}");
+                            unreadable += 1;
+                        }
+                        Some(Ok(evt)) => {
+                            records.push(evt);
+                            if stop_after_first_match {
+                                complete = false;
+                                break;
+                            }
+                        }
+                        None => {
+                            if time_window.assume_sorted() && is_sorted && time_window.is_past(&timestamp) {
+                                log::info!("stopped reading at the first record after the end of the time window");
+                                complete = false;
+                                break;
+                            }
+                        }
                     }
                 }
             }
         }
 
-        record_ids.sort_unstable();
-        (record_ids, records)
+        records.sort_unstable_by_key(|r| r.event_record_id);
+        Selection {
+            records,
+            found_ids,
+            id_bounds,
+            complete,
+            unreadable,
+            unknown_ids,
+        }
     }
 }
 
 impl RecordFilter for serde_json::Value {
-    type ReaderType = File;
-
-    fn unfiltered(parser: &mut EvtxParser<Self::ReaderType>) -> Unfiltered<Self> {
-        Unfiltered {
-            inner: Box::new(parser.records_json_value()),
-        }
+    #[allow(clippy::result_large_err)]
+    fn convert(record: EvtxRecord<'_>) -> evtx::err::Result<SerializedEvtxRecord<Self>> {
+        record.into_json_value()
     }
 }
 
 impl RecordFilter for String {
-    type ReaderType = File;
-
-    fn unfiltered(parser: &mut EvtxParser<Self::ReaderType>) -> Unfiltered<Self> {
-        Unfiltered {
-            inner: Box::new(parser.records()),
-        }
+    #[allow(clippy::result_large_err)]
+    fn convert(record: EvtxRecord<'_>) -> evtx::err::Result<SerializedEvtxRecord<Self>> {
+        record.into_xml()
     }
 }
@@ -1,50 +1,151 @@
-use std::collections::HashMap;
+use std::io::Write;
 
-use colored_json::to_colored_json_auto;
+use anyhow::Result;
+use colored_json::{to_colored_json, ColorMode};
 use evtx::SerializedEvtxRecord;
 use term_table::{row::Row, table_cell::TableCell};
 
-use crate::cli::Cli;
+use dfir_toolkit::common::FileOutput;
+
+use crate::{cli::Cli, highlighter::Highlighter};
+
+/// a record together with the name of the file it has been read from
+pub (crate) struct SourcedRecord<'a, T> {
+    pub (crate) source: &'a str,
+    pub (crate) record: SerializedEvtxRecord<T>,
+}
 
 pub (crate) trait RecordListFormatter: Sized {
-    fn format(record: &SerializedEvtxRecord<Self>) -> String;
+    fn format(record: &SerializedEvtxRecord<Self>, use_colors: bool) -> String;
 
-    fn display_results(
-        record_ids: Vec<u64>,
-        records: HashMap<u64, SerializedEvtxRecord<Self>>,
-        cli: &Cli,
-    ) {
+    /// displays the records. If records of more than one file are
+    /// displayed, the name of the source file is displayed as well
+    fn display_results(records: Vec<SourcedRecord<Self>>, cli: &Cli, output: &mut FileOutput) -> Result<()> {
+        let use_colors = output.is_terminal();
+        let highlighter = Highlighter::from_cli(cli, use_colors);
+        let with_source = cli.evtx_files.len() > 1;
         if !cli.show_table {
-            for id in record_ids.into_iter() {
-                let record = &records[&id];
-                println!("{}", Self::format(record));
+            for SourcedRecord { source, record } in records.into_iter() {
+                if with_source {
+                    writeln!(output, "<!-- source: {source} -->")?;
+                }
+                writeln!(output, "{}", highlighter.highlight(Self::format(&record, use_colors)))?;
             }
         } else {
             let mut table = term_table::Table::new();
             if let Some(size) = termsize::get() {
-                table.set_max_column_widths(vec![(0, 12), (1, (size.cols - 16).into())])
+                if with_source {
+                    table.set_max_column_widths(vec![
+                        (0, 12),
+                        (1, 24),
+                        (2, size.cols.saturating_sub(42).into()),
+                    ])
+                } else {
+                    table.set_max_column_widths(vec![(0, 12), (1, (size.cols - 16).into())])
+                }
             }
 
-            for id in record_ids.into_iter() {
-                let record = &records[&id];
-                table.add_row(Row::new(vec![
-                    TableCell::new(id),
-                    TableCell::new(Self::format(record)),
-                ]));
+            for SourcedRecord { source, record } in records.into_iter() {
+                let mut cells = vec![TableCell::new(record.event_record_id)];
+                if with_source {
+                    cells.push(TableCell::new(source));
+                }
+                cells.push(TableCell::new(highlighter.highlight(Self::format(&record, use_colors))));
+                table.add_row(Row::new(cells));
             }
-            println!("{}", table.render());
+            writeln!(output, "{}", table.render())?;
         }
+        Ok(())
     }
 }
 
 impl RecordListFormatter for String {
-    fn format(record: &SerializedEvtxRecord<Self>) -> String {
+    fn format(record: &SerializedEvtxRecord<Self>, _use_colors: bool) -> String {
         record.data.clone()
     }
 }
 
 impl RecordListFormatter for serde_json::Value {
-    fn format(record: &SerializedEvtxRecord<Self>) -> String {
-        to_colored_json_auto(&record.data).unwrap()
+    fn format(record: &SerializedEvtxRecord<Self>, use_colors: bool) -> String {
+        let mode = if use_colors { ColorMode::On } else { ColorMode::Off };
+        to_colored_json(&record.data, mode).unwrap()
+    }
+}
+
+/// minimum width of the terminal which is required to display XML and JSON
+/// side by side
+const MIN_SIDE_BY_SIDE_WIDTH: u16 = 160;
+
+/// the XML and the JSON representation of the same record
+pub(crate) struct RecordPair<'a> {
+    pub(crate) source: &'a str,
+    pub(crate) xml: SerializedEvtxRecord<String>,
+    pub(crate) json: Option<SerializedEvtxRecord<serde_json::Value>>,
+}
+
+/// displays the XML and the JSON of every record. If the output is a wide
+/// terminal, both are displayed side by side. Otherwise, they are displayed
+/// one after the other, separated by lines like
+/// `<!-- record 42: xml -->`, `<!-- record 42: json -->` and
+/// `<!-- end of record 42 -->`. Because `<` is escaped in XML text and JSON
+/// lines never start with `<`, these lines cannot be part of a record. If
+/// records of more than one file are displayed, every record is preceded by
+/// `<!-- source: FILENAME -->`.
+pub(crate) fn display_xml_and_json(records: Vec<RecordPair>, cli: &Cli, output: &mut FileOutput) -> Result<()> {
+    let use_colors = output.is_terminal();
+    let highlighter = Highlighter::from_cli(cli, use_colors);
+    let with_source = cli.evtx_files.len() > 1;
+    let format_xml = |record: &RecordPair| highlighter.highlight(String::format(&record.xml, use_colors));
+    let format_json = |record: &RecordPair| match &record.json {
+        Some(json) => highlighter.highlight(serde_json::Value::format(json, use_colors)),
+        None => {
+            log::warn!(
+                "unable to convert record {} of '{}' into JSON",
+                record.xml.event_record_id,
+                record.source
+            );
+            "".to_owned()
+        }
+    };
+
+    let terminal_width = if output.is_terminal() {
+        termsize::get().map(|size| size.cols)
+    } else {
+        None
+    };
+
+    match terminal_width {
+        Some(cols) if cli.show_table || cols >= MIN_SIDE_BY_SIDE_WIDTH => {
+            let content_width = (cols.saturating_sub(22) / 2).into();
+            let mut table = term_table::Table::new();
+            table.set_max_column_widths(vec![(0, 12), (1, content_width), (2, content_width)]);
+            for record in records.iter() {
+                let id = if with_source {
+                    format!("{}\n{}", record.xml.event_record_id, record.source)
+                } else {
+                    record.xml.event_record_id.to_string()
+                };
+                table.add_row(Row::new(vec![
+                    TableCell::new(id),
+                    TableCell::new(format_xml(record)),
+                    TableCell::new(format_json(record)),
+                ]));
+            }
+            writeln!(output, "{}", table.render())?;
+        }
+        _ => {
+            for record in records.iter() {
+                let id = record.xml.event_record_id;
+                if with_source {
+                    writeln!(output, "<!-- source: {} -->", record.source)?;
+                }
+                writeln!(output, "<!-- record {id}: xml -->")?;
+                writeln!(output, "{}", format_xml(record))?;
+                writeln!(output, "<!-- record {id}: json -->")?;
+                writeln!(output, "{}", format_json(record))?;
+                writeln!(output, "<!-- end of record {id} -->")?;
+            }
+        }
     }
-}
\ No newline at end of file
+    Ok(())
+}
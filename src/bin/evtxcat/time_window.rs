@@ -0,0 +1,46 @@
+use chrono::{DateTime, Utc};
+
+use crate::cli::Cli;
+
+/// time range in which the displayed records must have been created
+#[derive(Clone, Default)]
+pub(crate) struct TimeWindow {
+    not_before: Option<DateTime<Utc>>,
+    not_after: Option<DateTime<Utc>>,
+
+    /// the records are expected to be sorted by time
+    assume_sorted: bool,
+}
+
+impl TimeWindow {
+    pub(crate) fn contains(&self, timestamp: &DateTime<Utc>) -> bool {
+        self.not_before
+            .as_ref()
+            .map(|b| timestamp >= b)
+            .unwrap_or(true)
+            && self
+                .not_after
+                .as_ref()
+                .map(|a| timestamp <= a)
+                .unwrap_or(true)
+    }
+
+    /// returns `true` if `timestamp` is newer than the end of this window
+    pub(crate) fn is_past(&self, timestamp: &DateTime<Utc>) -> bool {
+        self.not_after.as_ref().is_some_and(|a| timestamp > a)
+    }
+
+    pub(crate) fn assume_sorted(&self) -> bool {
+        self.assume_sorted
+    }
+}
+
+impl From<&Cli> for TimeWindow {
+    fn from(cli: &Cli) -> Self {
+        Self {
+            not_before: cli.not_before.as_ref().map(|t| **t),
+            not_after: cli.not_after.as_ref().map(|t| **t),
+            assume_sorted: cli.assume_sorted,
+        }
+    }
+}
@@ -1,10 +1,20 @@
 use clap::{ColorChoice, Parser, ValueEnum, ValueHint};
 
-use dfir_toolkit::common::{Rfc3339Datetime, HasVerboseFlag};
+use dfirtk_eventdata::EventLevel;
+use dfir_toolkit::common::{
+    AnonymizeArgs, HasVerboseFlag, OutputArgs, TimeBound, TimezoneArgs, Verbosity,
+};
+use dfir_toolkit::evtx::RecordIdRanges;
 use log::LevelFilter;
 use regex::Regex;
 
+use crate::data_filter::DataFilter;
+use crate::output_field::OutputField;
+use crate::output_format::OutputFormat;
+use crate::source_filter::NamePattern;
 use crate::system_field::SystemField;
+use crate::template::Template;
+use crate::user_filter::UserPattern;
 
 #[derive(ValueEnum, Clone)]
 pub(crate) enum SortOrder {
@@ -22,11 +32,32 @@ pub(crate) enum SortOrder {
 #[derive(Parser)]
 #[clap(name=env!("CARGO_BIN_NAME"), author, version,long_about=None)]
 pub(crate) struct Cli {
-    /// Name of the evtx files to read from
-    #[clap(value_hint=ValueHint::FilePath)]
+    /// Name of the evtx files to read from. Directories are replaced by the
+    /// evtx files they contain
+    #[clap(value_hint=ValueHint::AnyPath)]
     pub(crate) evtx_files: Vec<String>,
 
-    /// use this delimiter instead of generating fixed space columns
+    /// output format
+    #[clap(short('F'), long("format"), value_enum, default_value_t=OutputFormat::Plain)]
+    pub(crate) format: OutputFormat,
+
+    /// print every record using this template instead of one of the
+    /// predefined formats, e.g. '{time} {eventid} {data.TargetUserName}'.
+    /// Valid placeholders are {time}, {eventid}, {recordid}, {level},
+    /// {provider}, {channel}, {computer}, {source} and {data.<NAME>}. Use
+    /// '{{' and '}}' for literal braces
+    #[clap(long("template"), conflicts_with = "format")]
+    pub(crate) template: Option<Template>,
+
+    /// text to insert for placeholders which refer to a missing field
+    #[clap(long("missing"), default_value = "", requires = "template")]
+    pub(crate) missing_placeholder: String,
+
+    #[clap(flatten)]
+    pub(crate) timezone: TimezoneArgs,
+
+    /// use this delimiter instead of generating fixed space columns. If used
+    /// together with '--format csv', this overrides the default of ','
     #[clap(short('d'), long("delimiter"))]
     pub(crate) delimiter: Option<char>,
 
@@ -49,17 +80,91 @@ pub(crate) struct Cli {
     )]
     pub(crate) excluded_event_ids: Vec<u16>,
 
-    /// highlight interesting content using colors
+    /// List only events whose event record id is contained in one of these
+    /// ranges, e.g. '1000..2000,5000,7000..'
+    #[clap(long("range"), value_name = "RANGES")]
+    pub(crate) record_ids: Option<RecordIdRanges>,
+
+    /// List only events with at least this severity. Events without a level
+    /// and events with level 0 ('LogAlways') are treated as 'information'
+    #[clap(long("min-level"), value_enum, ignore_case = true)]
+    pub(crate) min_level: Option<EventLevel>,
+
+    /// List only events from providers whose name contains this value
+    /// (case insensitive). Prefix the value with '=' to require an exact
+    /// match. Can be specified multiple times
+    #[clap(long("include-provider"), value_name = "PROVIDER")]
+    pub(crate) included_providers: Vec<NamePattern>,
+
+    /// Exclude events from providers whose name contains this value. Prefix
+    /// the value with '=' to require an exact match
+    #[clap(long("exclude-provider"), value_name = "PROVIDER")]
+    pub(crate) excluded_providers: Vec<NamePattern>,
+
+    /// List only events from channels whose name contains this value (case
+    /// insensitive). Prefix the value with '=' to require an exact match
+    #[clap(long("include-channel"), value_name = "CHANNEL")]
+    pub(crate) included_channels: Vec<NamePattern>,
+
+    /// Exclude events from channels whose name contains this value. Prefix
+    /// the value with '=' to require an exact match
+    #[clap(long("exclude-channel"), value_name = "CHANNEL")]
+    pub(crate) excluded_channels: Vec<NamePattern>,
+
+    /// List only events whose EventData contains a field with the
+    /// specified value. Use 'NAME=VALUE' for exact matches and 'NAME~=REGEX'
+    /// for regular expressions. If specified multiple times, all conditions
+    /// must match
+    #[clap(long("data"), value_name = "FILTER")]
+    pub(crate) data_filters: Vec<DataFilter>,
+
+    /// like '--data', but it suffices if any of these conditions matches
+    #[clap(long("data-any"), value_name = "FILTER")]
+    pub(crate) any_data_filters: Vec<DataFilter>,
+
+    /// List only events whose content matches this regular expression. The
+    /// content is serialized as compact JSON with sorted keys, e.g.
+    /// '"TargetUserName":"jsmith"'
+    #[clap(long("grep"), value_name = "REGEX")]
+    pub(crate) grep: Option<String>,
+
+    /// Exclude events whose content matches this regular expression, see
+    /// '--grep'
+    #[clap(long("grep-v"), value_name = "REGEX")]
+    pub(crate) grep_v: Option<String>,
+
+    /// use case insensitive matching for '--grep' and '--grep-v'
+    #[clap(short('I'), long("ignore-case"))]
+    pub(crate) ignore_case: bool,
+
+    /// List only events of this user (case insensitive). The value can be
+    /// a bare user name or 'DOMAIN\user'. The user of an event is taken from
+    /// 'TargetUserName' (e.g. for logons), 'SubjectUserName' or 'User',
+    /// depending on the event
+    #[clap(long("include-user"), value_name = "USER")]
+    pub(crate) included_users: Vec<UserPattern>,
+
+    /// Exclude events of this user, see '--include-user'
+    #[clap(long("exclude-user"), value_name = "USER")]
+    pub(crate) excluded_users: Vec<UserPattern>,
+
+    /// highlight interesting content using colors. The timestamp is colored
+    /// according to the level of the event (red for errors, yellow for
+    /// warnings), and the common event fields are dimmed. With 'auto',
+    /// colors are only used if the output is a terminal
     #[clap(short('C'), long("color"), default_value_t = ColorChoice::Auto)]
     pub(crate) display_colors: ColorChoice,
 
-    /// hide events older than the specified date (hint: use RFC 3339 syntax)
-    #[clap(short('f'), long("from"))]
-    pub(crate) not_before: Option<Rfc3339Datetime>,
+    /// hide events older than the specified date (hint: use RFC 3339 syntax).
+    /// Relative values like '-24h' are relative to the newest record; valid
+    /// units are 's', 'm', 'h', 'd' and 'w'
+    #[clap(short('f'), long("from"), allow_hyphen_values = true)]
+    pub(crate) not_before: Option<TimeBound>,
 
-    /// hide events newer than the specified date (hint: use RFC 3339 syntax)
-    #[clap(short('t'), long("to"))]
-    pub(crate) not_after: Option<Rfc3339Datetime>,
+    /// hide events newer than the specified date (hint: use RFC 3339 syntax).
+    /// Relative values are supported as with '--from'
+    #[clap(short('t'), long("to"), allow_hyphen_values = true)]
+    pub(crate) not_after: Option<TimeBound>,
 
     /// highlight event data based on this regular expression
     #[clap(short('r'), long("regex"))]
@@ -69,6 +174,17 @@ pub(crate) struct Cli {
     #[clap(short('s'), long("sort"), value_enum, default_value_t=SortOrder::Storage)]
     pub(crate) sort_order: SortOrder,
 
+    /// merge the records of all files into one stream sorted by time, and
+    /// display the source file of every record. Every file is expected to be
+    /// sorted by time; otherwise all records are sorted in memory
+    #[clap(long("sort-merge"), conflicts_with = "sort_order")]
+    pub(crate) sort_merge: bool,
+
+    /// display a progress bar for every file on stderr. This is ignored if
+    /// stderr is not a terminal
+    #[clap(long("progress"))]
+    pub(crate) progress: bool,
+
     /// display fields common to all events. multiple values must be separated by ','
     #[clap(
         short('b'),
@@ -85,8 +201,28 @@ pub(crate) struct Cli {
     #[clap(short('B'), long("hide-base-fields"), default_value_t=false)]
     pub (crate) hide_base_fields: bool,
 
+    /// display only these fields, in this order. This replaces the default
+    /// layout of the 'plain', 'csv', 'tsv' and 'jsonl' formats. Multiple
+    /// values must be separated by ','
+    #[clap(
+        long("fields"),
+        value_enum,
+        value_delimiter = ',',
+        conflicts_with_all = ["display_system_fields", "hide_base_fields", "template"])]
+    pub(crate) fields: Option<Vec<OutputField>>,
+
+    /// don't display the contents of EventData or UserData
+    #[clap(long("hide-data"), conflicts_with = "template")]
+    pub(crate) hide_data: bool,
+
+    #[clap(flatten)]
+    pub(crate) output: OutputArgs,
+
+    #[clap(flatten)]
+    pub(crate) anonymize: AnonymizeArgs,
+
     #[clap(flatten)]
-    verbose: clap_verbosity_flag::Verbosity,
+    verbose: Verbosity,
 }
 
 impl HasVerboseFlag for Cli {
@@ -1,27 +1,16 @@
-use evtx::SerializedEvtxRecord;
-use serde_json::Value;
-
-use super::SystemField;
-
-pub struct CsvRecord<'a> {
-    pub (crate) record: SerializedEvtxRecord<Value>,
-    pub (crate) delimiter: char,
-    pub (crate) system_fields: &'a Vec<SystemField>,
+/// a single record, prepared to be written by a [`csv::Writer`]
+pub struct CsvRecord {
+    pub(crate) timestamp: String,
+    pub(crate) source: Option<String>,
+    pub(crate) system_fields: Vec<String>,
+    pub(crate) event_data: Option<String>,
 }
-/*
-impl<'a> Serialize for CsvRecord<'a> {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: serde::Serializer,
-    {
-        serializer.serialize_str(&self.record.timestamp.to_rfc3339())?;
-        let fields = <SerializedEvtxRecord<Value> as FilterBySystemField>::filter_fields(
-            &self.record,
-            &self.system_fields[..],
-        ).unwrap();
-        for field in fields.into_iter() {
-            serializer.serialize_str(&field.to_string());
-        }
+
+impl CsvRecord {
+    pub fn fields(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(&self.timestamp[..])
+            .chain(self.source.as_deref())
+            .chain(self.system_fields.iter().map(|f| &f[..]))
+            .chain(self.event_data.as_deref())
     }
 }
- */
\ No newline at end of file
@@ -1,34 +1,84 @@
+use clap::ValueEnum;
+use dfir_toolkit::common::Timezone;
 use evtx::SerializedEvtxRecord;
 use serde_json::Value;
 
-use super::{SystemField, CsvRecord};
+use crate::system_field::{FilterBySystemField, SystemField};
 
+use crate::csv_record::CsvRecord;
 
 pub struct CsvRecordBuilder<'a> {
-    system_fields: &'a Vec<SystemField>,
-    delimiter: char,
+    system_fields: &'a [SystemField],
+    with_source: bool,
+    with_event_data: bool,
+    timezone: &'a Timezone,
 }
 
-impl<'a> From<&'a Vec<SystemField>> for CsvRecordBuilder<'a> {
-    fn from(system_fields: &'a Vec<SystemField>) -> Self {
+impl<'a> CsvRecordBuilder<'a> {
+    /// if `with_source` is set, there is an additional column `source`
+    /// after the timestamp
+    pub fn new(system_fields: &'a [SystemField], with_source: bool, timezone: &'a Timezone) -> Self {
         Self {
             system_fields,
-            delimiter: ';'
+            with_source,
+            with_event_data: true,
+            timezone,
         }
     }
-}
 
-impl<'a> CsvRecordBuilder<'a> {
-    pub fn build_from_record(&self, record: SerializedEvtxRecord<Value>) -> CsvRecord<'a> {
-        CsvRecord {
-            record,
-            delimiter: self.delimiter,
-            system_fields: self.system_fields
+    /// if `with_event_data` is not set, the column `event_data` is omitted
+    pub fn with_event_data(mut self, with_event_data: bool) -> Self {
+        self.with_event_data = with_event_data;
+        self
+    }
+
+    /// returns the column names, in the same order as the fields of the
+    /// records created by [`CsvRecordBuilder::build_from_record`]. The names
+    /// of the system fields are written in snake_case, like the other
+    /// columns
+    pub fn header(&self) -> Vec<String> {
+        let mut header = vec!["timestamp".to_owned()];
+        if self.with_source {
+            header.push("source".to_owned());
         }
+        header.extend(self.system_fields.iter().map(|f| {
+            f.to_possible_value()
+                .expect("no system field is skipped")
+                .get_name()
+                .replace('-', "_")
+        }));
+        if self.with_event_data {
+            header.push("event_data".to_owned());
+        }
+        header
     }
 
-    pub fn with_delimiter(mut self, delimiter: char) -> Self {
-        self.delimiter = delimiter;
-        self
+    pub fn build_from_record(
+        &self,
+        record: &SerializedEvtxRecord<Value>,
+        source: Option<&str>,
+    ) -> anyhow::Result<CsvRecord> {
+        let system_fields =
+            <SerializedEvtxRecord<Value> as FilterBySystemField>::filter_fields(
+                record,
+                self.system_fields,
+            )?
+            .iter()
+            .map(|f| f.to_string().trim().to_owned())
+            .collect();
+
+        let event = &record.data["Event"];
+        let event_data = match event.get("UserData").or_else(|| event.get("EventData")) {
+            _ if !self.with_event_data => None,
+            None | Some(Value::Null) => Some("".to_owned()),
+            Some(v) => Some(v.to_string()),
+        };
+
+        Ok(CsvRecord {
+            timestamp: self.timezone.format(&record.timestamp),
+            source: source.filter(|_| self.with_source).map(|s| s.to_owned()),
+            system_fields,
+            event_data,
+        })
     }
-}
\ No newline at end of file
+}
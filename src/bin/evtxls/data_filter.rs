@@ -0,0 +1,116 @@
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail};
+use dfir_toolkit::evtx::RecordFields;
+use evtx::SerializedEvtxRecord;
+use regex::Regex;
+use serde_json::Value;
+
+/// Condition on the value of a single field in `EventData` (or `UserData`).
+///
+/// Filters are specified as `NAME=VALUE` for exact matches or as
+/// `NAME~=REGEX` for regular expression matches.
+#[derive(Clone, Debug)]
+pub(crate) struct DataFilter {
+    name: String,
+    matcher: DataMatcher,
+}
+
+#[derive(Clone, Debug)]
+enum DataMatcher {
+    Exact(String),
+    Regex(Regex),
+}
+
+impl DataFilter {
+    /// returns `true` if the record contains the field and its value
+    /// matches. Records without the named field never match.
+    pub(crate) fn matches(&self, record: &SerializedEvtxRecord<Value>) -> bool {
+        self.matches_value(find_data_value(record, &self.name))
+    }
+
+    /// like [DataFilter::matches], but works on the fields which have been
+    /// read from the parsed record, before it is converted into JSON
+    pub(crate) fn matches_fields(&self, fields: &RecordFields) -> bool {
+        self.matches_value(fields.data(&self.name))
+    }
+
+    fn matches_value(&self, value: Option<&Value>) -> bool {
+        match value {
+            None => false,
+            Some(Value::String(s)) => self.matches_str(s),
+            Some(v) => self.matches_str(&v.to_string()),
+        }
+    }
+
+    fn matches_str(&self, value: &str) -> bool {
+        match &self.matcher {
+            DataMatcher::Exact(expected) => value == expected,
+            DataMatcher::Regex(regex) => regex.is_match(value),
+        }
+    }
+}
+
+/// searches for a field in `EventData`, or in the first element inside of
+/// `UserData`, which is where the event specific fields are stored
+pub(crate) fn find_data_value<'a>(record: &'a SerializedEvtxRecord<Value>, name: &str) -> Option<&'a Value> {
+    let event = record.data.get("Event")?;
+    if let Some(value) = event.get("EventData").and_then(|d| d.get(name)) {
+        return Some(value);
+    }
+    match event.get("UserData")? {
+        Value::Object(user_data) => user_data
+            .iter()
+            .filter(|(k, _)| *k != "#attributes")
+            .find_map(|(_, v)| v.get(name)),
+        _ => None,
+    }
+}
+
+impl FromStr for DataFilter {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let idx = s
+            .find('=')
+            .ok_or_else(|| anyhow!("expected NAME=VALUE or NAME~=REGEX, but found '{s}'"))?;
+        let value = &s[idx + 1..];
+        let (name, matcher) = match s[..idx].strip_suffix('~') {
+            Some(name) => (name, DataMatcher::Regex(Regex::new(value)?)),
+            None => (&s[..idx], DataMatcher::Exact(value.to_owned())),
+        };
+        if name.is_empty() {
+            bail!("missing field name in '{s}'");
+        }
+        Ok(Self {
+            name: name.to_owned(),
+            matcher,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DataFilter;
+
+    #[test]
+    fn parse_filters() {
+        let exact: DataFilter = "TargetUserName=admin".parse().unwrap();
+        assert_eq!(exact.name, "TargetUserName");
+        assert!(exact.matches_str("admin"));
+        assert!(!exact.matches_str("administrator"));
+
+        let regex: DataFilter = "IpAddress~=^10\\.".parse().unwrap();
+        assert_eq!(regex.name, "IpAddress");
+        assert!(regex.matches_str("10.0.0.5"));
+        assert!(!regex.matches_str("203.0.113.7"));
+
+        let with_equals: DataFilter = "CommandLine=a=b".parse().unwrap();
+        assert_eq!(with_equals.name, "CommandLine");
+        assert!(with_equals.matches_str("a=b"));
+
+        assert!("TargetUserName".parse::<DataFilter>().is_err());
+        assert!("=admin".parse::<DataFilter>().is_err());
+        assert!("Name~=(".parse::<DataFilter>().is_err());
+    }
+}
@@ -0,0 +1,51 @@
+use colored::{ColoredString, Colorize};
+use dfirtk_eventdata::EventLevel;
+use evtx::SerializedEvtxRecord;
+use serde_json::Value;
+
+/// returns the level of a record. Events without a level and events with
+/// level 0 ('LogAlways') are treated as informational
+pub(crate) fn level_of(record: &SerializedEvtxRecord<Value>) -> EventLevel {
+    match EventLevel::try_from(record) {
+        Ok(EventLevel::LogAlways) | Err(_) => EventLevel::Information,
+        Ok(level) => level,
+    }
+}
+
+/// applies the color which is used to display events of this level:
+/// errors are red, warnings are yellow and verbose events are dimmed
+pub(crate) fn paint(level: EventLevel, s: &str) -> ColoredString {
+    match level {
+        EventLevel::Critical => s.bright_red().bold(),
+        EventLevel::Error => s.red(),
+        EventLevel::Warning => s.yellow(),
+        EventLevel::Verbose => s.dimmed(),
+        _ => s.normal(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use dfirtk_eventdata::EventLevel;
+    use evtx::SerializedEvtxRecord;
+    use serde_json::json;
+
+    use super::level_of;
+
+    fn record_with_system(system: serde_json::Value) -> SerializedEvtxRecord<serde_json::Value> {
+        SerializedEvtxRecord {
+            event_record_id: 1,
+            timestamp: "2024-03-12T10:00:00Z".parse().unwrap(),
+            data: json!({"Event": {"System": system}}),
+        }
+    }
+
+    #[test]
+    fn map_levels() {
+        assert_eq!(level_of(&record_with_system(json!({"Level": 2}))), EventLevel::Error);
+        assert_eq!(level_of(&record_with_system(json!({"Level": "3"}))), EventLevel::Warning);
+        assert_eq!(level_of(&record_with_system(json!({"Level": 0}))), EventLevel::Information);
+        assert_eq!(level_of(&record_with_system(json!({}))), EventLevel::Information);
+        assert!(EventLevel::Critical < EventLevel::Warning);
+    }
+}
@@ -0,0 +1,79 @@
+use anyhow::Result;
+use evtx::SerializedEvtxRecord;
+use regex::{Regex, RegexBuilder};
+use serde_json::Value;
+
+/// Filters records by searching their whole content, which is serialized as
+/// compact JSON with sorted keys, using regular expressions
+pub(crate) struct GrepFilter {
+    include: Option<Regex>,
+    exclude: Option<Regex>,
+}
+
+impl GrepFilter {
+    pub(crate) fn new(
+        include: Option<&str>,
+        exclude: Option<&str>,
+        ignore_case: bool,
+    ) -> Result<Self> {
+        let build = |pattern: &str| {
+            RegexBuilder::new(pattern)
+                .case_insensitive(ignore_case)
+                .build()
+        };
+        Ok(Self {
+            include: include.map(build).transpose()?,
+            exclude: exclude.map(build).transpose()?,
+        })
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.include.is_none() && self.exclude.is_none()
+    }
+
+    pub(crate) fn matches(&self, record: &SerializedEvtxRecord<Value>) -> Result<bool> {
+        if self.is_empty() {
+            return Ok(true);
+        }
+        let content = serde_json::to_string(&record.data)?;
+        Ok(self
+            .include
+            .as_ref()
+            .map(|regex| regex.is_match(&content))
+            .unwrap_or(true)
+            && !self
+                .exclude
+                .as_ref()
+                .map(|regex| regex.is_match(&content))
+                .unwrap_or(false))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use evtx::SerializedEvtxRecord;
+    use serde_json::json;
+
+    use super::GrepFilter;
+
+    #[test]
+    fn grep_records() {
+        let record = SerializedEvtxRecord {
+            event_record_id: 1,
+            timestamp: "2024-03-12T10:00:00Z".parse().unwrap(),
+            data: json!({"Event": {"EventData": {"CommandLine": "powershell.exe -enc SQBFAFgA"}}}),
+        };
+        let grep = |include, exclude, ignore_case| {
+            GrepFilter::new(include, exclude, ignore_case)
+                .unwrap()
+                .matches(&record)
+                .unwrap()
+        };
+        assert!(grep(None, None, false));
+        assert!(grep(Some(r#""CommandLine":"powershell"#), None, false));
+        assert!(!grep(Some("PowerShell"), None, false));
+        assert!(grep(Some("PowerShell"), None, true));
+        assert!(!grep(None, Some("-enc"), false));
+        assert!(grep(Some("powershell"), Some("cmd.exe"), false));
+    }
+}
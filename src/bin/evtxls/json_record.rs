@@ -0,0 +1,159 @@
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, FixedOffset, Utc};
+use dfir_toolkit::common::Timezone;
+use evtx::SerializedEvtxRecord;
+use serde::Serialize;
+use serde_json::Value;
+
+/// JSON representation of a single record, as emitted by `--format jsonl`.
+///
+/// System fields are stored as top-level attributes, while the contents of
+/// `EventData` or `UserData` are kept as nested objects in `custom_data`.
+/// All values are taken over from the parsed record, so numbers stay numbers.
+#[derive(Serialize)]
+pub(crate) struct JsonRecord<'a> {
+    pub(crate) timestamp: JsonTimestamp,
+
+    /// original timestamp, only set if `timestamp` has been converted into
+    /// another timezone
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) timestamp_utc: Option<DateTime<Utc>>,
+    pub(crate) event_record_id: u64,
+    pub(crate) event_id: &'a Value,
+    pub(crate) level: Option<&'a Value>,
+    pub(crate) provider_name: Option<&'a Value>,
+    pub(crate) channel_name: Option<&'a Value>,
+    pub(crate) computer: Option<&'a Value>,
+
+    /// not set if `--hide-data` has been specified
+    #[serde(skip_serializing_if = "Option::is_none")]
+    custom_data: Option<BTreeMap<&'a String, &'a Value>>,
+
+    /// name of the file, only set when merging several files
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) source: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+pub(crate) enum JsonTimestamp {
+    Utc(DateTime<Utc>),
+    Local(DateTime<FixedOffset>),
+}
+
+impl<'a> JsonRecord<'a> {
+    /// converts the timestamp into `timezone`, keeping the original value
+    /// in `timestamp_utc`
+    pub(crate) fn with_timezone(mut self, timezone: &Timezone) -> Self {
+        if let JsonTimestamp::Utc(timestamp) = self.timestamp {
+            if !timezone.is_utc() {
+                self.timestamp = JsonTimestamp::Local(timezone.convert(&timestamp));
+                self.timestamp_utc = Some(timestamp);
+            }
+        }
+        self
+    }
+
+    pub(crate) fn without_custom_data(mut self) -> Self {
+        self.custom_data = None;
+        self
+    }
+
+    /// returns the contents of `UserData` or `EventData`
+    pub(crate) fn event_data(&self) -> Option<&'a Value> {
+        let custom_data = self.custom_data.as_ref()?;
+        custom_data
+            .iter()
+            .find(|(k, _)| **k == "UserData")
+            .or_else(|| custom_data.iter().find(|(k, _)| **k == "EventData"))
+            .map(|(_, v)| *v)
+            .filter(|v| !v.is_null())
+    }
+}
+
+impl<'a> TryFrom<&'a SerializedEvtxRecord<Value>> for JsonRecord<'a> {
+    type Error = anyhow::Error;
+
+    fn try_from(record: &'a SerializedEvtxRecord<Value>) -> Result<Self, Self::Error> {
+        let event = record
+            .data
+            .get("Event")
+            .ok_or_else(|| anyhow!("missing 'Event' key in {}", record.data))?;
+        let system = event
+            .get("System")
+            .ok_or_else(|| anyhow!("missing 'System' key in {event}"))?;
+
+        let event_id = {
+            let event_id = system
+                .get("EventID")
+                .ok_or_else(|| anyhow!("missing 'EventID' key in {system}"))?;
+            match event_id.get("#text") {
+                Some(eid) => eid,
+                None => event_id,
+            }
+        };
+
+        let mut custom_data = BTreeMap::new();
+        if let Value::Object(contents) = event {
+            for (key, value) in contents.iter() {
+                if key != "System" && key != "#attributes" {
+                    custom_data.insert(key, value);
+                }
+            }
+        }
+
+        Ok(Self {
+            timestamp: JsonTimestamp::Utc(record.timestamp),
+            timestamp_utc: None,
+            event_record_id: record.event_record_id,
+            event_id,
+            level: system.get("Level"),
+            provider_name: system
+                .get("Provider")
+                .and_then(|p| p.get("#attributes"))
+                .and_then(|p| p.get("Name")),
+            channel_name: system.get("Channel"),
+            computer: system.get("Computer"),
+            custom_data: Some(custom_data),
+            source: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use evtx::SerializedEvtxRecord;
+    use serde_json::json;
+
+    use super::JsonRecord;
+
+    #[test]
+    fn numbers_stay_numbers() {
+        let record = SerializedEvtxRecord {
+            event_record_id: 42,
+            timestamp: "2024-03-12T10:00:00Z".parse().unwrap(),
+            data: json!({
+                "Event": {
+                    "#attributes": {"xmlns": "http://schemas.microsoft.com/win/2004/08/events/event"},
+                    "System": {
+                        "EventID": 4624,
+                        "Level": 0,
+                        "Provider": {"#attributes": {"Name": "Microsoft-Windows-Security-Auditing"}},
+                        "Channel": "Security",
+                        "Computer": "WKS01"
+                    },
+                    "EventData": {"TargetUserName": "jsmith", "LogonType": 3}
+                }
+            }),
+        };
+        let value = json!(JsonRecord::try_from(&record).unwrap());
+        assert_eq!(value["event_record_id"], json!(42));
+        assert_eq!(value["event_id"], json!(4624));
+        assert_eq!(value["level"], json!(0));
+        assert_eq!(value["provider_name"], json!("Microsoft-Windows-Security-Auditing"));
+        assert_eq!(value["custom_data"]["EventData"]["LogonType"], json!(3));
+        assert!(value["custom_data"].get("#attributes").is_none());
+    }
+}
@@ -1,136 +1,622 @@
 mod cli;
+mod csv_record;
+mod csv_record_builder;
+mod data_filter;
+mod event_level;
+mod grep_filter;
 mod highlighted_string;
+mod json_record;
+mod merge;
+mod output_field;
+mod output_format;
+mod progress;
+mod source_filter;
 mod system_field;
+mod template;
+mod user_filter;
 
 use std::{
-    io::{Read, Seek},
-    path::PathBuf,
+    cell::{RefCell, RefMut},
+    io::{ErrorKind, Write},
+    path::{Path, PathBuf},
 };
 
-use anyhow::Result;
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
 use cli::{Cli, SortOrder};
 use colored::{control::SHOULD_COLORIZE, Colorize};
+use csv_record_builder::CsvRecordBuilder;
+use data_filter::DataFilter;
+use event_level::{level_of, paint};
+use grep_filter::GrepFilter;
 use dfirtk_eventdata::EventId;
-use evtx::{EvtxParser, ParserSettings, SerializedEvtxRecord};
+use evtx::{EvtxRecord, SerializedEvtxRecord};
 
 use highlighted_string::HighlightedStringBuilder;
+use json_record::JsonRecord;
+use merge::MergedRecords;
+use output_field::{OutputField, SelectedFields};
+use output_format::OutputFormat;
+use progress::{FileProgress, Progress};
+use source_filter::SourceFilter;
+use user_filter::EventUser;
 use serde_json::Value;
 
-use dfir_toolkit::common::{FancyParser, FormattableDatetime};
+use dfir_toolkit::common::{Anonymizer, FancyParser, FileOutput};
+use dfir_toolkit::evtx::{EvtxFile, RecordFields};
 
-use crate::system_field::FilterBySystemField;
+use crate::system_field::{FilterBySystemField, SystemField};
 
 struct EvtxLs {
     cli: Cli,
+    evtx_files: Vec<PathBuf>,
     hs_builder: HighlightedStringBuilder,
+    csv_writer: RefCell<Option<csv::Writer<FileOutput>>>,
+
+    /// the output of all formats except CSV and TSV, which is written by `csv_writer`
+    output: RefCell<Option<FileOutput>>,
+    not_before: Option<DateTime<Utc>>,
+    not_after: Option<DateTime<Utc>>,
+    progress: Progress,
+    grep_filter: GrepFilter,
+
+    /// fields selected with `--fields`, without `data` if `--hide-data` has
+    /// been specified
+    output_fields: Option<Vec<OutputField>>,
+
+    /// replaces user names, host names and IP addresses if `--anonymize`
+    /// has been specified. Records are anonymized after filtering them
+    anonymizer: Option<Anonymizer>,
 }
 
 impl EvtxLs {
-    fn new() -> Self {
+    fn new() -> Result<Self> {
         let cli = Cli::parse_cli();
         let hs_builder = HighlightedStringBuilder::new(cli.highlight.clone());
 
-        Self { cli, hs_builder }
+        match cli.display_colors {
+            // a file is never colorized, even if stdout is a terminal
+            clap::ColorChoice::Auto if cli.output.path().is_some() => {
+                SHOULD_COLORIZE.set_override(false)
+            }
+
+            // Remove the manual override and let the environment decide if it’s ok to colorize
+            clap::ColorChoice::Auto => SHOULD_COLORIZE.unset_override(),
+
+            //Use this to force colored to ignore the environment and always/never colorize
+            clap::ColorChoice::Always => SHOULD_COLORIZE.set_override(true),
+            clap::ColorChoice::Never => SHOULD_COLORIZE.set_override(false),
+        };
+
+        let evtx_files = Self::expand_directories(&cli.evtx_files)?;
+
+        let delimiter = match cli.format {
+            OutputFormat::Csv => Some(cli.delimiter.unwrap_or(',')),
+            OutputFormat::Tsv => Some(cli.delimiter.unwrap_or('\t')),
+            _ => None,
+        };
+
+        if delimiter.is_some_and(|delimiter| !delimiter.is_ascii()) {
+            bail!("the delimiter must be an ASCII character when writing CSV or TSV");
+        }
+
+        let bounds = [cli.not_before.as_ref(), cli.not_after.as_ref()];
+        let newest = if bounds.iter().flatten().any(|b| b.is_relative()) {
+            Self::newest_timestamp(&evtx_files)?
+        } else {
+            None
+        };
+        let not_before = match &cli.not_before {
+            Some(bound) => bound.resolve(newest.as_ref())?,
+            None => None,
+        };
+        let not_after = match &cli.not_after {
+            Some(bound) => bound.resolve(newest.as_ref())?,
+            None => None,
+        };
+
+        let progress = Progress::new(cli.progress);
+        let grep_filter =
+            GrepFilter::new(cli.grep.as_deref(), cli.grep_v.as_deref(), cli.ignore_case)?;
+
+        let output_fields = cli.fields.as_ref().map(|fields| {
+            fields
+                .iter()
+                .filter(|f| !(cli.hide_data && **f == OutputField::Data))
+                .copied()
+                .collect()
+        });
+
+        let anonymizer = cli.anonymize.anonymizer()?;
+        let output = cli.output.create()?;
+        let (csv_writer, output) = match delimiter {
+            None => (None, Some(output)),
+            Some(delimiter) => (
+                Some(
+                    csv::WriterBuilder::new()
+                        .delimiter(delimiter as u8)
+                        .from_writer(output),
+                ),
+                None,
+            ),
+        };
+
+        Ok(Self {
+            cli,
+            evtx_files,
+            hs_builder,
+            csv_writer: RefCell::new(csv_writer),
+            output: RefCell::new(output),
+            not_before,
+            not_after,
+            progress,
+            grep_filter,
+            output_fields,
+            anonymizer,
+        })
     }
 
-    fn run(self) -> Result<()> {
-        let mut records = Vec::new();
+    /// replaces every directory by the evtx files it contains
+    fn expand_directories(evtx_files: &[String]) -> Result<Vec<PathBuf>> {
+        let mut result = Vec::with_capacity(evtx_files.len());
+        for f_name in evtx_files.iter() {
+            let path = PathBuf::from(f_name);
+            if path.is_dir() {
+                let mut files = Vec::new();
+                for entry in std::fs::read_dir(&path)? {
+                    let entry_path = entry?.path();
+                    if entry_path.is_file()
+                        && entry_path
+                            .extension()
+                            .is_some_and(|ext| ext.eq_ignore_ascii_case("evtx"))
+                    {
+                        files.push(entry_path);
+                    }
+                }
+                if files.is_empty() {
+                    log::warn!("directory '{}' contains no evtx files", path.display());
+                }
+                files.sort();
+                result.extend(files);
+            } else {
+                result.push(path);
+            }
+        }
+        Ok(result)
+    }
 
-        for f_name in self.cli.evtx_files.iter() {
-            let path = PathBuf::from(&f_name);
+    /// finds the timestamp of the newest record, only reading the record
+    /// headers
+    #[allow(clippy::result_large_err)]
+    fn newest_timestamp(evtx_files: &[PathBuf]) -> Result<Option<DateTime<Utc>>> {
+        let mut newest = None;
+        for path in evtx_files.iter() {
+            let file = EvtxFile::open(path)?;
+            newest = file
+                .record_headers()
+                .map(|header| header.timestamp)
+                .chain(newest)
+                .max();
+        }
+        Ok(newest)
+    }
 
-            let settings = ParserSettings::default().num_threads(0);
-            let parser = EvtxParser::from_path(path)?.with_configuration(settings);
+    /// checks if the records of a file are sorted by time, only reading the
+    /// record headers
+    #[allow(clippy::result_large_err)]
+    fn is_sorted_by_time(path: &PathBuf) -> Result<bool> {
+        let file = EvtxFile::open(path)?;
+        let mut last_timestamp = None;
+        for timestamp in file.record_headers().map(|header| header.timestamp) {
+            if last_timestamp.is_some_and(|last| timestamp < last) {
+                return Ok(false);
+            }
+            last_timestamp = Some(timestamp);
+        }
+        Ok(true)
+    }
 
-            records.extend(self.read_records(parser)?);
+    fn system_fields(&self) -> &[SystemField] {
+        if self.cli.hide_base_fields {
+            &[]
+        } else {
+            &self.cli.display_system_fields[..]
         }
+    }
+
+    fn output(&self) -> RefMut<'_, FileOutput> {
+        RefMut::map(self.output.borrow_mut(), |output| {
+            output.as_mut().expect("missing output")
+        })
+    }
 
-        match self.cli.sort_order {
-            SortOrder::Storage => assert!(records.is_empty()),
-            SortOrder::RecordId => {
-                records.sort_by(|a, b| a.event_record_id.cmp(&b.event_record_id))
+    fn csv_record_builder(&self) -> CsvRecordBuilder<'_> {
+        CsvRecordBuilder::new(self.system_fields(), self.cli.sort_merge, &self.cli.timezone)
+            .with_event_data(!self.cli.hide_data)
+    }
+
+    fn sources(&self) -> Vec<String> {
+        self.evtx_files
+            .iter()
+            .map(|p| p.display().to_string())
+            .collect()
+    }
+
+    fn run(self) -> Result<()> {
+        if let Some(writer) = self.csv_writer.borrow_mut().as_mut() {
+            match self.output_fields.as_ref() {
+                Some(fields) => writer.write_record(fields.iter().map(OutputField::name))?,
+                None => writer.write_record(self.csv_record_builder().header())?,
             }
-            SortOrder::Time => records.sort_by(|a, b| a.timestamp.cmp(&b.timestamp)),
         }
 
-        if !records.is_empty() {
+        let source_filter = SourceFilter::from(&self.cli);
+        if self.cli.sort_merge {
+            self.merge_records(&source_filter)?;
+        } else {
+            let mut records = Vec::new();
+            for path in self.evtx_files.iter() {
+                let (file, file_progress) = self.progress.open(path)?;
+
+                let collect = !matches!(self.cli.sort_order, SortOrder::Storage);
+                records.extend(self.read_records(
+                    path,
+                    file,
+                    &file_progress,
+                    &source_filter,
+                    collect,
+                )?);
+            }
+
+            match self.cli.sort_order {
+                SortOrder::Storage => assert!(records.is_empty()),
+                SortOrder::RecordId => records.sort_by_key(|r| r.event_record_id),
+                SortOrder::Time => records.sort_by_key(|r| r.timestamp),
+            }
+
             for record in records.into_iter() {
-                self.display_record(&record)?;
+                self.display_record(&record, None)?;
             }
         }
+        source_filter.log_summary();
 
+        // the output file is only created if all records have been written
+        let output = match self.csv_writer.into_inner() {
+            Some(writer) => writer.into_inner().map_err(|why| why.into_error())?,
+            None => self.output.into_inner().expect("missing output"),
+        };
+        output.commit()?;
         Ok(())
     }
 
-    fn read_records<T: Read + Seek>(
-        &self,
-        mut parser: EvtxParser<T>,
-    ) -> Result<Vec<SerializedEvtxRecord<Value>>> {
-        match self.cli.display_colors {
-            // Remove the manual override and let the environment decide if it’s ok to colorize
-            clap::ColorChoice::Auto => SHOULD_COLORIZE.unset_override(),
+    /// displays the records of all files sorted by time. If every file is
+    /// sorted, this is done using a k-way merge; otherwise all records are
+    /// loaded into memory and sorted
+    #[allow(clippy::result_large_err)]
+    fn merge_records(&self, source_filter: &SourceFilter) -> Result<()> {
+        let sources = self.sources();
+
+        let mut all_sorted = true;
+        for path in self.evtx_files.iter() {
+            if !Self::is_sorted_by_time(path)? {
+                log::warn!(
+                    "the records in '{}' are not sorted by time, falling back to sorting all records in memory",
+                    path.display()
+                );
+                all_sorted = false;
+                break;
+            }
+        }
 
-            //Use this to force colored to ignore the environment and always/never colorize
-            clap::ColorChoice::Always => SHOULD_COLORIZE.set_override(true),
-            clap::ColorChoice::Never => SHOULD_COLORIZE.set_override(false),
-        };
+        if !all_sorted {
+            let mut records = Vec::new();
+            for (source, path) in self.evtx_files.iter().enumerate() {
+                let (file, file_progress) = self.progress.open(path)?;
+                records.extend(
+                    self.read_records(path, file, &file_progress, source_filter, true)?
+                        .into_iter()
+                        .map(|r| (source, r)),
+                );
+            }
+
+            // this is a stable sort, so records with the same timestamp
+            // keep the order of the files
+            records.sort_by_key(|(_, r)| r.timestamp);
+            for (source, record) in records.into_iter() {
+                self.display_record(&record, Some(&sources[source]))?;
+            }
+            return Ok(());
+        }
+
+        let mut files = Vec::with_capacity(self.evtx_files.len());
+        let mut file_progresses = Vec::with_capacity(self.evtx_files.len());
+        for path in self.evtx_files.iter() {
+            let (file, file_progress) = self.progress.open(path)?;
+            files.push(file);
+            file_progresses.push(file_progress);
+        }
 
+        let record_iterators: Vec<_> = files
+            .iter()
+            .zip(file_progresses.iter())
+            .zip(sources.iter())
+            .map(|((file, file_progress), source)| {
+                self.filtered_records(file, file_progress, source, source_filter)
+            })
+            .collect();
+
+        for result in MergedRecords::from(record_iterators) {
+            let (source, record) = result?;
+            self.display_record(&record, Some(&sources[source]))?;
+        }
+        Ok(())
+    }
+
+    /// returns the records of a file as JSON. Records outside of the
+    /// requested ranges and records which don't match the data filters are
+    /// skipped before they are converted into JSON, and are returned as
+    /// `None`
+    #[allow(clippy::result_large_err)]
+    fn json_records<'a>(
+        &'a self,
+        file: &'a EvtxFile,
+    ) -> impl Iterator<Item = evtx::err::Result<Option<SerializedEvtxRecord<Value>>>> + 'a {
+        let record_ids = self.cli.record_ids.as_ref();
+        let (data_filters, any_data_filters) = (&self.cli.data_filters, &self.cli.any_data_filters);
+        file.serialized_records(move |record| {
+            record.and_then(|record| match record_ids {
+                Some(ranges) if !ranges.contains(record.event_record_id) => Ok(None),
+                _ if !Self::may_match_data_filters(&record, data_filters, any_data_filters) => {
+                    Ok(None)
+                }
+                _ => record.into_json_value().map(Some),
+            })
+        })
+    }
+
+    /// checks the data filters on the fields of the parsed record. If the
+    /// fields cannot be read without serializing the record, the record is
+    /// checked by [Self::matches_data_filters] after its conversion
+    fn may_match_data_filters(
+        record: &EvtxRecord,
+        data_filters: &[DataFilter],
+        any_data_filters: &[DataFilter],
+    ) -> bool {
+        if data_filters.is_empty() && any_data_filters.is_empty() {
+            return true;
+        }
+        match RecordFields::read(record) {
+            None => true,
+            Some(fields) => {
+                data_filters.iter().all(|f| f.matches_fields(&fields))
+                    && (any_data_filters.is_empty()
+                        || any_data_filters.iter().any(|f| f.matches_fields(&fields)))
+            }
+        }
+    }
+
+    /// returns all records of a file which pass all filters
+    fn filtered_records<'a>(
+        &'a self,
+        file: &'a EvtxFile,
+        file_progress: &'a FileProgress,
+        source: &'a str,
+        source_filter: &'a SourceFilter,
+    ) -> impl Iterator<Item = Result<SerializedEvtxRecord<Value>>> + 'a {
+        self.json_records(file)
+            .filter_map(move |result| match result {
+                Err(why) => {
+                    log::warn!("skipping a record of '{source}' which could not be parsed: {why}");
+                    None
+                }
+                Ok(None) => None,
+                Ok(Some(record)) => match self.is_accepted(&record, source_filter) {
+                    Ok(true) => {
+                        file_progress.inc_emitted();
+                        Some(Ok(record))
+                    }
+                    Ok(false) => None,
+                    Err(why) => Some(Err(why)),
+                },
+            })
+    }
+
+    fn read_records(
+        &self,
+        path: &Path,
+        file: EvtxFile,
+        file_progress: &FileProgress,
+        source_filter: &SourceFilter,
+        collect: bool,
+    ) -> Result<Vec<SerializedEvtxRecord<Value>>> {
         let mut records = Vec::new();
         let mut handled_records = 0;
         let mut expected_records: usize = 0;
 
-        for result in parser.records_json_value() {
+        for result in self.json_records(&file) {
             expected_records += 1;
             match result {
-                Err(why) => {
-                    log::error!("error while parsing a record; read {handled_records} until now. I'll try to continue with the next record");
-                    log::warn!("{why}")
-                }
-                Ok(record) => {
+                Err(why) => log::warn!(
+                    "skipping a record of '{}' which could not be parsed: {why}",
+                    path.display()
+                ),
+                Ok(None) => handled_records += 1,
+                Ok(Some(record)) => {
                     handled_records += 1;
-                    if let Some(not_before) = self.cli.not_before.as_ref() {
-                        if &record.timestamp < not_before {
-                            continue;
-                        }
-                    }
-
-                    if let Some(not_after) = self.cli.not_after.as_ref() {
-                        if &record.timestamp > not_after {
-                            continue;
-                        }
+                    if !self.is_accepted(&record, source_filter)? {
+                        continue;
                     }
+                    file_progress.inc_emitted();
 
-                    if !self.cli.included_event_ids.is_empty() {
-                        let event_id = EventId::try_from(&record)?.into();
-                        if !self.cli.included_event_ids.contains(&event_id) {
-                            continue;
-                        }
-                    }
-
-                    if !self.cli.excluded_event_ids.is_empty() {
-                        let event_id = EventId::try_from(&record)?.into();
-                        if self.cli.excluded_event_ids.contains(&event_id) {
-                            continue;
-                        }
-                    }
-
-                    if matches!(self.cli.sort_order, SortOrder::Storage) {
-                        self.display_record(&record)?
-                    } else {
+                    if collect {
                         records.push(record);
+                    } else {
+                        self.display_record(&record, None)?
                     }
                 }
             }
         }
 
         if handled_records < expected_records {
-            log::warn!("I expected {expected_records}, but only {handled_records} could be handled.")
+            log::warn!(
+                "{} of {expected_records} records of '{}' could not be read",
+                expected_records - handled_records,
+                path.display()
+            )
         }
 
         Ok(records)
     }
 
-    fn display_record(&self, record: &SerializedEvtxRecord<Value>) -> Result<()> {
+    /// checks if the record passes all filters
+    fn is_accepted(
+        &self,
+        record: &SerializedEvtxRecord<Value>,
+        source_filter: &SourceFilter,
+    ) -> Result<bool> {
+        if let Some(not_before) = self.not_before.as_ref() {
+            if &record.timestamp < not_before {
+                return Ok(false);
+            }
+        }
+
+        if let Some(not_after) = self.not_after.as_ref() {
+            if &record.timestamp > not_after {
+                return Ok(false);
+            }
+        }
+
+        if !self.cli.included_event_ids.is_empty() {
+            let event_id = EventId::try_from(record)?.into();
+            if !self.cli.included_event_ids.contains(&event_id) {
+                return Ok(false);
+            }
+        }
+
+        if !self.cli.excluded_event_ids.is_empty() {
+            let event_id = EventId::try_from(record)?.into();
+            if self.cli.excluded_event_ids.contains(&event_id) {
+                return Ok(false);
+            }
+        }
+
+        if let Some(min_level) = self.cli.min_level {
+            if level_of(record) > min_level {
+                return Ok(false);
+            }
+        }
+
+        if !(source_filter.accepts(record)
+            && self.matches_data_filters(record)
+            && self.matches_user_filters(record))
+        {
+            return Ok(false);
+        }
+
+        self.grep_filter.matches(record)
+    }
+
+    fn matches_user_filters(&self, record: &SerializedEvtxRecord<Value>) -> bool {
+        if self.cli.included_users.is_empty() && self.cli.excluded_users.is_empty() {
+            return true;
+        }
+        match EventUser::try_from(record) {
+            Err(_) => self.cli.included_users.is_empty(),
+            Ok(user) => {
+                (self.cli.included_users.is_empty()
+                    || self.cli.included_users.iter().any(|p| p.matches(&user)))
+                    && !self.cli.excluded_users.iter().any(|p| p.matches(&user))
+            }
+        }
+    }
+
+    fn matches_data_filters(&self, record: &SerializedEvtxRecord<Value>) -> bool {
+        self.cli.data_filters.iter().all(|f| f.matches(record))
+            && (self.cli.any_data_filters.is_empty()
+                || self.cli.any_data_filters.iter().any(|f| f.matches(record)))
+    }
+
+    fn display_record(
+        &self,
+        record: &SerializedEvtxRecord<Value>,
+        source: Option<&str>,
+    ) -> Result<()> {
+        match self.anonymizer.as_ref() {
+            None => self
+                .progress
+                .suspend(|| self.display_record_unsuspended(record, source)),
+            Some(anonymizer) => {
+                let mut record = record.clone();
+                anonymizer.anonymize_value(&mut record.data);
+                let source = source.map(|source| anonymizer.anonymize_path(source));
+                self.progress
+                    .suspend(|| self.display_record_unsuspended(&record, source.as_deref()))
+            }
+        }
+    }
+
+    fn display_record_unsuspended(
+        &self,
+        record: &SerializedEvtxRecord<Value>,
+        source: Option<&str>,
+    ) -> Result<()> {
+        if let Some(template) = self.cli.template.as_ref() {
+            let line = template.render(
+                record,
+                source,
+                &self.cli.timezone,
+                &self.cli.missing_placeholder,
+            )?;
+            writeln!(self.output(), "{line}")?;
+            return Ok(());
+        }
+
+        if let Some(fields) = self.output_fields.as_ref() {
+            return self.display_fields(record, source, fields);
+        }
+
+        match self.cli.format {
+            OutputFormat::Plain => self.display_plain_record(record, source),
+            OutputFormat::Jsonl => self.display_json_record(record, source),
+            OutputFormat::Csv | OutputFormat::Tsv => self.display_csv_record(record, source),
+        }
+    }
+
+    fn display_csv_record(
+        &self,
+        record: &SerializedEvtxRecord<Value>,
+        source: Option<&str>,
+    ) -> Result<()> {
+        let csv_record = self.csv_record_builder().build_from_record(record, source)?;
+        let mut writer = self.csv_writer.borrow_mut();
+        writer
+            .as_mut()
+            .expect("missing CSV writer")
+            .write_record(csv_record.fields())?;
+        Ok(())
+    }
+
+    fn display_json_record(
+        &self,
+        record: &SerializedEvtxRecord<Value>,
+        source: Option<&str>,
+    ) -> Result<()> {
+        let mut json_record = JsonRecord::try_from(record)?.with_timezone(&self.cli.timezone);
+        json_record.source = source;
+        if self.cli.hide_data {
+            json_record = json_record.without_custom_data();
+        }
+        let mut output = self.output();
+        serde_json::to_writer(&mut *output, &json_record)?;
+        writeln!(output)?;
+        Ok(())
+    }
+
+    fn display_plain_record(
+        &self,
+        record: &SerializedEvtxRecord<Value>,
+        source: Option<&str>,
+    ) -> Result<()> {
         let system_fields = if self.cli.hide_base_fields {
             "".to_owned()
         } else {
@@ -159,21 +645,98 @@ impl EvtxLs {
             }
         };
 
-        let event_data = self
-            .format_custom_data(record, "UserData")
-            .or_else(|| self.format_custom_data(record, "EventData"))
-            .unwrap_or_else(|| "".to_owned())
-            .replace("\\u001b", "\u{001b}");
-
-        let timestamp = FormattableDatetime::from(&record.timestamp);
+        // the timestamp is colored according to the severity of the event,
+        // while the fields which are common to all events are dimmed
+        let timestamp = self.cli.timezone.format(&record.timestamp);
+        let timestamp = paint(level_of(record), &timestamp);
         let delimiter = self.cli.delimiter.unwrap_or(' ');
+        let source = match source {
+            Some(source) => format!("{source}{delimiter}"),
+            None => "".to_owned(),
+        };
+        let common_fields = format!("{source}{system_fields}");
 
-        let output = format!("{timestamp}{delimiter}{system_fields}{event_data}").normal();
-        println!("{output}");
+        if self.cli.hide_data {
+            let common_fields = common_fields.strip_suffix(delimiter).unwrap_or_default();
+            if common_fields.is_empty() {
+                writeln!(self.output(), "{timestamp}")?;
+            } else {
+                let common_fields = common_fields.dimmed();
+                writeln!(self.output(), "{timestamp}{delimiter}{common_fields}")?;
+            }
+        } else {
+            let common_fields = common_fields.dimmed();
+            let event_data = self.format_event_data(record);
+            writeln!(
+                self.output(),
+                "{timestamp}{delimiter}{common_fields}{event_data}"
+            )?;
+        }
 
         Ok(())
     }
 
+    /// displays only the fields which have been selected using `--fields`
+    fn display_fields(
+        &self,
+        record: &SerializedEvtxRecord<Value>,
+        source: Option<&str>,
+        fields: &[OutputField],
+    ) -> Result<()> {
+        let mut json_record = JsonRecord::try_from(record)?.with_timezone(&self.cli.timezone);
+        json_record.source = source;
+
+        match self.cli.format {
+            OutputFormat::Jsonl => {
+                let selected_fields = SelectedFields {
+                    fields,
+                    record: &json_record,
+                };
+                let mut output = self.output();
+                serde_json::to_writer(&mut *output, &selected_fields)?;
+                writeln!(output)?;
+            }
+            OutputFormat::Plain => {
+                let line_parts: Vec<String> = fields
+                    .iter()
+                    .map(|f| match f {
+                        OutputField::Time => {
+                            paint(level_of(record), &self.cli.timezone.format(&record.timestamp))
+                                .to_string()
+                        }
+                        OutputField::Data => self.format_event_data(record),
+                        f => f.text(&json_record),
+                    })
+                    .collect();
+                let delimiter = self.cli.delimiter.unwrap_or(' ').to_string();
+                writeln!(self.output(), "{}", line_parts.join(&delimiter))?;
+            }
+            OutputFormat::Csv | OutputFormat::Tsv => {
+                let csv_fields: Vec<String> = fields
+                    .iter()
+                    .map(|f| match f {
+                        OutputField::Time => self.cli.timezone.format(&record.timestamp),
+                        f => f.text(&json_record),
+                    })
+                    .collect();
+                let mut writer = self.csv_writer.borrow_mut();
+                writer
+                    .as_mut()
+                    .expect("missing CSV writer")
+                    .write_record(csv_fields)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// formats the contents of `UserData` or `EventData`
+    fn format_event_data(&self, record: &SerializedEvtxRecord<Value>) -> String {
+        self.format_custom_data(record, "UserData")
+            .or_else(|| self.format_custom_data(record, "EventData"))
+            .unwrap_or_else(|| "".to_owned())
+            .replace("\\u001b", "\u{001b}")
+    }
+
     fn format_custom_data(
         &self,
         record: &SerializedEvtxRecord<Value>,
@@ -192,7 +755,29 @@ impl EvtxLs {
     }
 }
 
+/// checks if writing to stdout failed because the reading end of the pipe
+/// has been closed, e.g. by `head`
+fn is_broken_pipe(why: &anyhow::Error) -> bool {
+    why.chain().any(|cause| {
+        let kind = if let Some(why) = cause.downcast_ref::<std::io::Error>() {
+            Some(why.kind())
+        } else if let Some(why) = cause.downcast_ref::<serde_json::Error>() {
+            why.io_error_kind()
+        } else if let Some(why) = cause.downcast_ref::<csv::Error>() {
+            match why.kind() {
+                csv::ErrorKind::Io(why) => Some(why.kind()),
+                _ => None,
+            }
+        } else {
+            None
+        };
+        kind == Some(ErrorKind::BrokenPipe)
+    })
+}
+
 fn main() -> Result<()> {
-    sigpipe::reset();
-    EvtxLs::new().run()
+    match EvtxLs::new().and_then(|evtxls| evtxls.run()) {
+        Err(why) if is_broken_pipe(&why) => Ok(()),
+        result => result,
+    }
 }
@@ -0,0 +1,128 @@
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+
+use chrono::{DateTime, Utc};
+use evtx::SerializedEvtxRecord;
+use serde_json::Value;
+
+type Record = SerializedEvtxRecord<Value>;
+
+struct HeapEntry {
+    timestamp: DateTime<Utc>,
+    source: usize,
+    record: Record,
+}
+
+impl PartialEq for HeapEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for HeapEntry {}
+
+impl PartialOrd for HeapEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for HeapEntry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // records with the same timestamp are sorted by the order of their sources
+        self.timestamp
+            .cmp(&other.timestamp)
+            .then(self.source.cmp(&other.source))
+    }
+}
+
+/// Merges the records of several sources into one stream which is sorted by
+/// time, assuming that every source itself is sorted.
+///
+/// Only the next record of every source is kept in memory. The iterator
+/// yields the index of the source together with the record.
+pub(crate) struct MergedRecords<I>
+where
+    I: Iterator<Item = anyhow::Result<Record>>,
+{
+    sources: Vec<I>,
+    heap: BinaryHeap<Reverse<HeapEntry>>,
+
+    /// sources whose next record must be read before the heap can be used
+    pending: Vec<usize>,
+}
+
+impl<I> From<Vec<I>> for MergedRecords<I>
+where
+    I: Iterator<Item = anyhow::Result<Record>>,
+{
+    fn from(sources: Vec<I>) -> Self {
+        let pending = (0..sources.len()).rev().collect();
+        Self {
+            heap: BinaryHeap::with_capacity(sources.len()),
+            sources,
+            pending,
+        }
+    }
+}
+
+impl<I> Iterator for MergedRecords<I>
+where
+    I: Iterator<Item = anyhow::Result<Record>>,
+{
+    type Item = anyhow::Result<(usize, Record)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(source) = self.pending.pop() {
+            match self.sources[source].next() {
+                None => (),
+                Some(Ok(record)) => self.heap.push(Reverse(HeapEntry {
+                    timestamp: record.timestamp,
+                    source,
+                    record,
+                })),
+                Some(Err(why)) => {
+                    self.pending.push(source);
+                    return Some(Err(why));
+                }
+            }
+        }
+
+        let Reverse(entry) = self.heap.pop()?;
+        self.pending.push(entry.source);
+        Some(Ok((entry.source, entry.record)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use evtx::SerializedEvtxRecord;
+    use serde_json::Value;
+
+    use super::MergedRecords;
+
+    fn record(id: u64, minute: u32) -> anyhow::Result<SerializedEvtxRecord<Value>> {
+        Ok(SerializedEvtxRecord {
+            event_record_id: id,
+            timestamp: format!("2024-03-12T10:{minute:02}:00Z").parse().unwrap(),
+            data: Value::Null,
+        })
+    }
+
+    #[test]
+    fn merge_sorted_sources() {
+        let sources = vec![
+            vec![record(1, 0), record(2, 5), record(3, 10)].into_iter(),
+            vec![].into_iter(),
+            vec![record(10, 1), record(11, 5), record(12, 20)].into_iter(),
+        ];
+        let merged: Vec<_> = MergedRecords::from(sources)
+            .map(|r| r.unwrap())
+            .map(|(source, record)| (source, record.event_record_id))
+            .collect();
+        assert_eq!(
+            merged,
+            vec![(0, 1), (2, 10), (0, 2), (2, 11), (0, 3), (2, 12)]
+        );
+    }
+}
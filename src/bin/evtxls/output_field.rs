@@ -0,0 +1,104 @@
+use clap::ValueEnum;
+use serde::{ser::SerializeMap, Serialize};
+use serde_json::{json, Value};
+
+use crate::json_record::JsonRecord;
+
+/// a field which can be selected using `--fields`. The names are the same
+/// as the names of the placeholders which can be used with `--template`
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub(crate) enum OutputField {
+    /// timestamp of the record
+    Time,
+
+    /// the record number assigned to the event when it was logged
+    #[value(name = "recordid")]
+    RecordId,
+
+    /// the identifier that the provider used to identify the event
+    #[value(name = "eventid")]
+    EventId,
+
+    /// severity of the event
+    Level,
+
+    /// name of the provider which has logged the event
+    Provider,
+
+    /// name of the channel
+    Channel,
+
+    /// name of the computer
+    Computer,
+
+    /// name of the file, only available when using '--sort-merge'
+    Source,
+
+    /// contents of EventData or UserData
+    Data,
+}
+
+impl OutputField {
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            Self::Time => "time",
+            Self::RecordId => "recordid",
+            Self::EventId => "eventid",
+            Self::Level => "level",
+            Self::Provider => "provider",
+            Self::Channel => "channel",
+            Self::Computer => "computer",
+            Self::Source => "source",
+            Self::Data => "data",
+        }
+    }
+
+    /// returns the value of this field, as it is written in JSON output
+    pub(crate) fn value(&self, record: &JsonRecord) -> Value {
+        fn or_null(value: Option<&Value>) -> Value {
+            value.cloned().unwrap_or(Value::Null)
+        }
+
+        match self {
+            Self::Time => json!(record.timestamp),
+            Self::RecordId => json!(record.event_record_id),
+            Self::EventId => record.event_id.clone(),
+            Self::Level => or_null(record.level),
+            Self::Provider => or_null(record.provider_name),
+            Self::Channel => or_null(record.channel_name),
+            Self::Computer => or_null(record.computer),
+            Self::Source => record.source.map(|s| json!(s)).unwrap_or(Value::Null),
+            Self::Data => or_null(record.event_data()),
+        }
+    }
+
+    /// returns the value of this field, as it is written in plain or CSV
+    /// output
+    pub(crate) fn text(&self, record: &JsonRecord) -> String {
+        match self.value(record) {
+            Value::Null => "".to_owned(),
+            Value::String(s) => s,
+            v => v.to_string(),
+        }
+    }
+}
+
+/// JSON object which contains only the selected fields, in the order in
+/// which they have been selected
+pub(crate) struct SelectedFields<'a> {
+    pub(crate) fields: &'a [OutputField],
+    pub(crate) record: &'a JsonRecord<'a>,
+}
+
+impl<'a> Serialize for SelectedFields<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(self.fields.len()))?;
+        for field in self.fields {
+            map.serialize_entry(field.name(), &field.value(self.record))?;
+        }
+        map.end()
+    }
+}
@@ -0,0 +1,19 @@
+use clap::ValueEnum;
+
+#[derive(ValueEnum, Clone)]
+pub(crate) enum OutputFormat {
+    /// human readable output, one line per record
+    Plain,
+
+    /// JSON Lines, one JSON object per record
+    Jsonl,
+
+    /// comma separated values, including a header row. The columns are
+    /// named in snake_case, e.g. 'timestamp', 'event_id',
+    /// 'event_record_id' and 'event_data'
+    Csv,
+
+    /// tab separated values, including a header row with the same column
+    /// names as 'csv'
+    Tsv,
+}
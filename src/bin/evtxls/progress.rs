@@ -0,0 +1,93 @@
+use std::{cell::Cell, io::IsTerminal, path::Path};
+
+use anyhow::Result;
+use dfir_toolkit::evtx::EvtxFile;
+use evtx::ParserSettings;
+use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
+
+/// displays one progress bar per evtx file on stderr. If stderr is not a
+/// terminal, nothing is displayed at all.
+pub(crate) struct Progress {
+    bars: MultiProgress,
+
+    /// the bars must be hidden temporarily while writing to stdout, but only
+    /// if stdout is displayed on the same terminal
+    suspend_output: bool,
+}
+
+impl Progress {
+    pub(crate) fn new(enabled: bool) -> Self {
+        let enabled = enabled && std::io::stderr().is_terminal();
+        let target = if enabled {
+            ProgressDrawTarget::stderr_with_hz(10)
+        } else {
+            ProgressDrawTarget::hidden()
+        };
+        Self {
+            bars: MultiProgress::with_draw_target(target),
+            suspend_output: enabled && std::io::stdout().is_terminal(),
+        }
+    }
+
+    /// opens an evtx file and creates a progress bar, which is updated
+    /// whenever the parser reads a chunk of the file
+    pub(crate) fn open(&self, path: &Path) -> Result<(EvtxFile, FileProgress)> {
+        let settings = ParserSettings::default().num_threads(0);
+        let file = EvtxFile::open(path)?.with_configuration(settings);
+        let bar = self.bars.add(ProgressBar::new(file.size()));
+
+        let progress_style = ProgressStyle::default_bar()
+            .template(
+                "[{elapsed_precise}] {prefix} {bar:40.cyan/blue} {bytes:>10}/{total_bytes:10}({percent}%) ETA {eta} {msg}",
+            )?
+            .progress_chars("##-");
+        bar.set_style(progress_style);
+        bar.set_prefix(
+            path.file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default(),
+        );
+
+        let position = bar.clone();
+        let file = file.with_progress(move |bytes| position.set_position(bytes));
+        Ok((
+            file,
+            FileProgress {
+                bar,
+                emitted: Cell::new(0),
+            },
+        ))
+    }
+
+    /// runs `f`, which writes to stdout, without breaking the progress bars
+    pub(crate) fn suspend<F: FnOnce() -> R, R>(&self, f: F) -> R {
+        if self.suspend_output {
+            self.bars.suspend(f)
+        } else {
+            f()
+        }
+    }
+}
+
+/// progress of one single evtx file
+pub(crate) struct FileProgress {
+    bar: ProgressBar,
+    emitted: Cell<u64>,
+}
+
+impl FileProgress {
+    /// counts a record which passed all filters
+    pub(crate) fn inc_emitted(&self) {
+        self.emitted.set(self.emitted.get() + 1);
+        if !self.bar.is_hidden() {
+            self.bar
+                .set_message(format!("{} records", self.emitted.get()));
+        }
+    }
+}
+
+impl Drop for FileProgress {
+    fn drop(&mut self) {
+        self.bar.finish_and_clear();
+    }
+}
@@ -0,0 +1,163 @@
+use std::cell::Cell;
+use std::str::FromStr;
+
+use evtx::SerializedEvtxRecord;
+use serde_json::Value;
+
+use crate::cli::Cli;
+
+/// Case insensitive pattern for provider or channel names. By default, the
+/// pattern matches if it is contained in the name. If it starts with `=`, the
+/// name must match exactly.
+#[derive(Clone, Debug)]
+pub(crate) struct NamePattern {
+    pattern: String,
+    exact: bool,
+}
+
+impl NamePattern {
+    pub(crate) fn matches(&self, name: &str) -> bool {
+        let name = name.to_lowercase();
+        if self.exact {
+            name == self.pattern
+        } else {
+            name.contains(&self.pattern)
+        }
+    }
+}
+
+impl FromStr for NamePattern {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.strip_prefix('=') {
+            Some(name) => Self {
+                pattern: name.to_lowercase(),
+                exact: true,
+            },
+            None => Self {
+                pattern: s.to_lowercase(),
+                exact: false,
+            },
+        })
+    }
+}
+
+struct PatternList<'a> {
+    description: &'static str,
+    patterns: &'a [NamePattern],
+    include: bool,
+    removed: Cell<usize>,
+}
+
+impl<'a> PatternList<'a> {
+    fn new(description: &'static str, patterns: &'a [NamePattern], include: bool) -> Self {
+        Self {
+            description,
+            patterns,
+            include,
+            removed: Cell::new(0),
+        }
+    }
+
+    /// returns `true` if the record may be displayed
+    fn accepts(&self, name: Option<&str>) -> bool {
+        if self.patterns.is_empty() {
+            return true;
+        }
+        let matches = name
+            .map(|name| self.patterns.iter().any(|p| p.matches(name)))
+            .unwrap_or(false);
+        if matches == self.include {
+            true
+        } else {
+            self.removed.set(self.removed.get() + 1);
+            false
+        }
+    }
+}
+
+/// filters records by the name of their provider and their channel, and
+/// counts how many records have been removed by every filter
+pub(crate) struct SourceFilter<'a> {
+    include_providers: PatternList<'a>,
+    exclude_providers: PatternList<'a>,
+    include_channels: PatternList<'a>,
+    exclude_channels: PatternList<'a>,
+}
+
+impl<'a> From<&'a Cli> for SourceFilter<'a> {
+    fn from(cli: &'a Cli) -> Self {
+        Self {
+            include_providers: PatternList::new(
+                "--include-provider",
+                &cli.included_providers,
+                true,
+            ),
+            exclude_providers: PatternList::new(
+                "--exclude-provider",
+                &cli.excluded_providers,
+                false,
+            ),
+            include_channels: PatternList::new("--include-channel", &cli.included_channels, true),
+            exclude_channels: PatternList::new(
+                "--exclude-channel",
+                &cli.excluded_channels,
+                false,
+            ),
+        }
+    }
+}
+
+impl<'a> SourceFilter<'a> {
+    pub(crate) fn accepts(&self, record: &SerializedEvtxRecord<Value>) -> bool {
+        let system = record.data.get("Event").and_then(|e| e.get("System"));
+        let provider = system
+            .and_then(|s| s.get("Provider"))
+            .and_then(|p| p.get("#attributes"))
+            .and_then(|p| p.get("Name"))
+            .and_then(Value::as_str);
+        let channel = system
+            .and_then(|s| s.get("Channel"))
+            .and_then(Value::as_str);
+
+        self.include_providers.accepts(provider)
+            && self.exclude_providers.accepts(provider)
+            && self.include_channels.accepts(channel)
+            && self.exclude_channels.accepts(channel)
+    }
+
+    pub(crate) fn log_summary(&self) {
+        for list in [
+            &self.include_providers,
+            &self.exclude_providers,
+            &self.include_channels,
+            &self.exclude_channels,
+        ] {
+            if !list.patterns.is_empty() {
+                log::info!(
+                    "{} removed {} records",
+                    list.description,
+                    list.removed.get()
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NamePattern;
+
+    #[test]
+    fn match_names() {
+        let substring: NamePattern = "sysmon".parse().unwrap();
+        assert!(substring.matches("Microsoft-Windows-Sysmon"));
+        assert!(substring.matches("Microsoft-Windows-Sysmon/Operational"));
+        assert!(!substring.matches("Security"));
+
+        let exact: NamePattern = "=security".parse().unwrap();
+        assert!(exact.matches("Security"));
+        assert!(!exact.matches("Microsoft-Windows-Security-Auditing"));
+    }
+}
@@ -0,0 +1,194 @@
+use std::fmt::Write;
+use std::str::FromStr;
+
+use anyhow::{bail, Result};
+use dfir_toolkit::common::Timezone;
+use evtx::SerializedEvtxRecord;
+use serde_json::Value;
+
+use crate::data_filter::find_data_value;
+use crate::json_record::JsonRecord;
+
+const FIELD_NAMES: &[&str] = &[
+    "time",
+    "eventid",
+    "recordid",
+    "level",
+    "provider",
+    "channel",
+    "computer",
+    "source",
+    "data.<NAME>",
+];
+
+/// User defined output format for a single record, like
+/// `{time} {eventid} {data.TargetUserName}`.
+///
+/// Literal braces are written as `{{` and `}}`.
+#[derive(Clone, Debug)]
+pub(crate) struct Template {
+    parts: Vec<TemplatePart>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum TemplatePart {
+    Literal(String),
+    Field(TemplateField),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum TemplateField {
+    Time,
+    EventId,
+    RecordId,
+    Level,
+    Provider,
+    Channel,
+    Computer,
+    Source,
+    Data(String),
+}
+
+impl FromStr for TemplateField {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "time" => Self::Time,
+            "eventid" => Self::EventId,
+            "recordid" => Self::RecordId,
+            "level" => Self::Level,
+            "provider" => Self::Provider,
+            "channel" => Self::Channel,
+            "computer" => Self::Computer,
+            "source" => Self::Source,
+            _ => match s.strip_prefix("data.") {
+                Some(name) if !name.is_empty() => Self::Data(name.to_owned()),
+                _ => bail!(
+                    "unknown placeholder '{{{s}}}', valid placeholders are: {}",
+                    FIELD_NAMES.join(", ")
+                ),
+            },
+        })
+    }
+}
+
+impl FromStr for Template {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut chars = s.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => name.push(c),
+                            None => bail!("unterminated placeholder '{{{name}' in template"),
+                        }
+                    }
+                    if !literal.is_empty() {
+                        parts.push(TemplatePart::Literal(std::mem::take(&mut literal)));
+                    }
+                    parts.push(TemplatePart::Field(name.parse()?));
+                }
+                '}' => bail!("unexpected '}}' in template, use '}}}}' for a literal brace"),
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            parts.push(TemplatePart::Literal(literal));
+        }
+        Ok(Self { parts })
+    }
+}
+
+impl Template {
+    /// renders the record, using `missing` for fields which don't exist in
+    /// this record. `source` is only available when merging several files
+    pub(crate) fn render(
+        &self,
+        record: &SerializedEvtxRecord<Value>,
+        source: Option<&str>,
+        timezone: &Timezone,
+        missing: &str,
+    ) -> Result<String> {
+        let json_record = JsonRecord::try_from(record)?;
+        let mut output = String::new();
+        for part in self.parts.iter() {
+            match part {
+                TemplatePart::Literal(s) => output.push_str(s),
+                TemplatePart::Field(field) => {
+                    let value = match field {
+                        TemplateField::Time => {
+                            output.push_str(&timezone.format(&record.timestamp));
+                            continue;
+                        }
+                        TemplateField::Source => {
+                            output.push_str(source.unwrap_or(missing));
+                            continue;
+                        }
+                        TemplateField::RecordId => {
+                            write!(output, "{}", record.event_record_id)?;
+                            continue;
+                        }
+                        TemplateField::EventId => Some(json_record.event_id),
+                        TemplateField::Level => json_record.level,
+                        TemplateField::Provider => json_record.provider_name,
+                        TemplateField::Channel => json_record.channel_name,
+                        TemplateField::Computer => json_record.computer,
+                        TemplateField::Data(name) => find_data_value(record, name),
+                    };
+                    match value {
+                        None | Some(Value::Null) => output.push_str(missing),
+                        Some(Value::String(s)) => output.push_str(s),
+                        Some(v) => write!(output, "{v}")?,
+                    }
+                }
+            }
+        }
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Template, TemplateField, TemplatePart};
+
+    #[test]
+    fn parse_template() {
+        let template: Template = "{{{eventid}}} {data.IpAddress} -> x".parse().unwrap();
+        assert_eq!(
+            template.parts,
+            vec![
+                TemplatePart::Literal("{".into()),
+                TemplatePart::Field(TemplateField::EventId),
+                TemplatePart::Literal("} ".into()),
+                TemplatePart::Field(TemplateField::Data("IpAddress".into())),
+                TemplatePart::Literal(" -> x".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_templates() {
+        let err = "{foo}".parse::<Template>().unwrap_err().to_string();
+        assert!(err.contains("valid placeholders are: time, eventid"));
+        assert!("{time".parse::<Template>().is_err());
+        assert!("time}".parse::<Template>().is_err());
+        assert!("{data.}".parse::<Template>().is_err());
+    }
+}
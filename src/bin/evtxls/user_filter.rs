@@ -0,0 +1,211 @@
+use std::str::FromStr;
+
+use anyhow::bail;
+use dfirtk_eventdata::EventId;
+use evtx::SerializedEvtxRecord;
+use serde_json::Value;
+
+use crate::data_filter::find_data_value;
+
+/// a user, optionally together with its domain
+#[derive(Debug, PartialEq)]
+pub(crate) struct EventUser {
+    domain: Option<String>,
+    name: String,
+}
+
+impl EventUser {
+    fn new(name: &str, domain: Option<&str>) -> Self {
+        match name.split_once('\\') {
+            Some((domain, name)) => Self {
+                domain: Some(domain.to_lowercase()),
+                name: name.to_lowercase(),
+            },
+            None => Self {
+                domain: domain.map(str::to_lowercase),
+                name: name.to_lowercase(),
+            },
+        }
+    }
+}
+
+/// fields which contain the name and the domain of the user of an event,
+/// in the order in which they are searched
+type UserFields = &'static [(&'static str, Option<&'static str>)];
+
+const DEFAULT_USER_FIELDS: UserFields = &[
+    ("TargetUserName", Some("TargetDomainName")),
+    ("SubjectUserName", Some("SubjectDomainName")),
+    ("User", None),
+];
+
+/// returns the fields which contain the user of an event. Most events use
+/// the same field names; the exceptions are listed here
+fn user_fields(provider: &str, event_id: u16) -> UserFields {
+    match (provider, event_id) {
+        ("Microsoft-Windows-Security-Auditing", 4778 | 4779) => {
+            &[("AccountName", Some("AccountDomain"))]
+        }
+        ("Microsoft-Windows-TerminalServices-RemoteConnectionManager", 1149) => {
+            &[("Param1", Some("Param2"))]
+        }
+        _ => DEFAULT_USER_FIELDS,
+    }
+}
+
+impl TryFrom<&SerializedEvtxRecord<Value>> for EventUser {
+    type Error = ();
+
+    /// extracts the user which an event is about. For logon events, this is
+    /// the user who logged on (`TargetUserName`), not the user who has
+    /// initiated the logon
+    fn try_from(record: &SerializedEvtxRecord<Value>) -> Result<Self, Self::Error> {
+        let system = record.data.get("Event").and_then(|e| e.get("System"));
+        let provider = system
+            .and_then(|s| s.get("Provider"))
+            .and_then(|p| p.get("#attributes"))
+            .and_then(|p| p.get("Name"))
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        let event_id = EventId::try_from(record).map(|id| id.0).unwrap_or_default();
+
+        user_fields(provider, event_id)
+            .iter()
+            .find_map(|(name_field, domain_field)| {
+                let name = find_data_value(record, name_field)
+                    .and_then(Value::as_str)
+                    .filter(|name| !name.is_empty() && *name != "-")?;
+                let domain = domain_field
+                    .and_then(|f| find_data_value(record, f))
+                    .and_then(Value::as_str)
+                    .filter(|domain| !domain.is_empty() && *domain != "-");
+                Some(Self::new(name, domain))
+            })
+            .ok_or(())
+    }
+}
+
+/// Case insensitive pattern for user names. If the pattern contains a
+/// domain (`DOMAIN\user`), the domain must match as well.
+#[derive(Clone, Debug)]
+pub(crate) struct UserPattern {
+    domain: Option<String>,
+    name: String,
+}
+
+impl UserPattern {
+    pub(crate) fn matches(&self, user: &EventUser) -> bool {
+        self.name == user.name
+            && self
+                .domain
+                .as_ref()
+                .map(|domain| user.domain.as_ref() == Some(domain))
+                .unwrap_or(true)
+    }
+}
+
+impl FromStr for UserPattern {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let user = EventUser::new(s, None);
+        if user.name.is_empty() {
+            bail!("missing user name in '{s}'");
+        }
+        Ok(Self {
+            domain: user.domain,
+            name: user.name,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use evtx::SerializedEvtxRecord;
+    use serde_json::json;
+
+    use super::{EventUser, UserPattern};
+
+    fn record(
+        provider: &str,
+        event_id: u64,
+        event_data: serde_json::Value,
+    ) -> SerializedEvtxRecord<serde_json::Value> {
+        SerializedEvtxRecord {
+            event_record_id: 1,
+            timestamp: "2024-03-12T10:00:00Z".parse().unwrap(),
+            data: json!({"Event": {
+                "System": {
+                    "Provider": {"#attributes": {"Name": provider}},
+                    "EventID": event_id
+                },
+                "EventData": event_data
+            }}),
+        }
+    }
+
+    #[test]
+    fn extract_users() {
+        let logon = record(
+            "Microsoft-Windows-Security-Auditing",
+            4624,
+            json!({"SubjectUserName": "WKS01$", "TargetUserName": "jsmith", "TargetDomainName": "CORP"}),
+        );
+        assert_eq!(
+            EventUser::try_from(&logon),
+            Ok(EventUser::new("jsmith", Some("corp")))
+        );
+
+        let failed = record(
+            "Microsoft-Windows-Security-Auditing",
+            4625,
+            json!({"SubjectUserName": "-", "TargetUserName": "-"}),
+        );
+        assert!(EventUser::try_from(&failed).is_err());
+
+        let reconnect = record(
+            "Microsoft-Windows-Security-Auditing",
+            4778,
+            json!({"AccountName": "jsmith", "AccountDomain": "CORP"}),
+        );
+        assert_eq!(
+            EventUser::try_from(&reconnect),
+            Ok(EventUser::new("CORP\\jsmith", None))
+        );
+
+        let sysmon = record(
+            "Microsoft-Windows-Sysmon",
+            1,
+            json!({"User": "CORP\\jsmith"}),
+        );
+        assert_eq!(
+            EventUser::try_from(&sysmon),
+            Ok(EventUser::new("jsmith", Some("CORP")))
+        );
+    }
+
+    #[test]
+    fn match_users() {
+        let user = EventUser::new("JSmith", Some("CORP"));
+        assert!("jsmith".parse::<UserPattern>().unwrap().matches(&user));
+        assert!("corp\\JSMITH"
+            .parse::<UserPattern>()
+            .unwrap()
+            .matches(&user));
+        assert!(!"other\\jsmith"
+            .parse::<UserPattern>()
+            .unwrap()
+            .matches(&user));
+        assert!("corp\\".parse::<UserPattern>().is_err());
+
+        let without_domain = EventUser::new("jsmith", None);
+        assert!("jsmith"
+            .parse::<UserPattern>()
+            .unwrap()
+            .matches(&without_domain));
+        assert!(!"corp\\jsmith"
+            .parse::<UserPattern>()
+            .unwrap()
+            .matches(&without_domain));
+    }
+}
@@ -1,15 +1,21 @@
 use clap::{Parser, ValueHint};
-use dfir_toolkit::common::HasVerboseFlag;
+use dfir_toolkit::common::{HasVerboseFlag, HumanDuration, Verbosity};
 use log::LevelFilter;
 
+use crate::output_format::OutputFormat;
 
-/// Find time skews in an evtx file
+
+/// Find time skews, time gaps and gaps in the record ids of evtx files
+///
+/// The exit code is 1 if anything has been found, 65 if no findings have
+/// been made but some files could not be read, and 0 otherwise.
 #[derive(Parser)]
 #[clap(name=env!("CARGO_BIN_NAME"), author, version)]
 pub (crate) struct Cli {
-    /// name of the evtx file to scan
-    #[clap(value_hint=ValueHint::FilePath)]
-    pub (crate) evtx_file: String,
+    /// names of the evtx files to scan. Directories are searched
+    /// recursively for files with the extension '.evtx'
+    #[clap(value_hint=ValueHint::AnyPath, num_args=1.., required=true)]
+    pub (crate) evtx_files: Vec<String>,
 
     /// display also the contents of the records befor and after a time skew
     #[clap(short = 'S', long)]
@@ -19,8 +25,24 @@ pub (crate) struct Cli {
     #[clap(short = 'N', long, default_value_t = 5)]
     pub (crate) negative_tolerance: u32,
 
+    /// minimum duration without any records which is reported as a gap,
+    /// e.g. '90s', '30m', '2h' or '1h30m'. By default, the threshold adapts
+    /// to the cadence of the log: it is 100 times the median interval
+    /// between two consecutive records, but at least 10 minutes
+    #[clap(short = 'G', long, value_name = "DURATION")]
+    pub (crate) threshold: Option<HumanDuration>,
+
+    /// display the N records before and after every time gap and every
+    /// gap in the record ids
+    #[clap(short = 'C', long, value_name = "N", value_parser = clap::value_parser!(u16).range(1..))]
+    pub (crate) context: Option<u16>,
+
+    /// output format. '--show-records' is only used by the text format
+    #[clap(value_enum, short('F'), long("format"), default_value_t = OutputFormat::Text)]
+    pub (crate) format: OutputFormat,
+
     #[clap(flatten)]
-    verbose: clap_verbosity_flag::Verbosity,
+    verbose: Verbosity,
 }
 
 impl HasVerboseFlag for Cli {
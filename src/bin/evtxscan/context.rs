@@ -0,0 +1,124 @@
+use chrono::{DateTime, Utc};
+use dfir_toolkit::evtx::EventId;
+use evtx::SerializedEvtxRecord;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::scanned_file::ScannedFile;
+
+/// maximum length of the summary of the event data
+const MAX_SUMMARY_LENGTH: usize = 120;
+
+/// a record which is displayed before or after a gap
+#[derive(Serialize)]
+pub(crate) struct ContextRecord {
+    pub(crate) event_record_id: u64,
+    pub(crate) timestamp: DateTime<Utc>,
+    pub(crate) event_id: Option<Value>,
+    pub(crate) provider: Option<String>,
+
+    /// the values of `EventData` or `UserData` in a single line
+    pub(crate) summary: String,
+}
+
+impl From<&SerializedEvtxRecord<Value>> for ContextRecord {
+    fn from(record: &SerializedEvtxRecord<Value>) -> Self {
+        let event = &record.data["Event"];
+        let system = &event["System"];
+        let event_id = match &system["EventID"] {
+            Value::Null => None,
+            Value::Object(o) => o.get("#text").cloned(),
+            v => Some(v.clone()),
+        };
+        let provider = system["Provider"]["#attributes"]["Name"]
+            .as_str()
+            .map(String::from);
+
+        Self {
+            event_record_id: record.event_record_id,
+            timestamp: record.timestamp,
+            event_id,
+            provider,
+            summary: summarize(event),
+        }
+    }
+}
+
+impl ContextRecord {
+    pub(crate) fn line(&self) -> String {
+        format!(
+            "{} {:>8} {:>5} {}: {}",
+            self.timestamp.format("%FT%T"),
+            self.event_record_id,
+            self.event_id
+                .as_ref()
+                .map(|id| id.to_string())
+                .unwrap_or_default(),
+            self.provider.as_deref().unwrap_or("-"),
+            self.summary
+        )
+    }
+}
+
+/// creates a line like `Key1=Value1, Key2=Value2` from `EventData` or from
+/// the first element of `UserData`
+fn summarize(event: &Value) -> String {
+    let data = match (&event["EventData"], &event["UserData"]) {
+        (Value::Object(data), _) => Some(data),
+        (_, Value::Object(user_data)) => user_data.values().find_map(Value::as_object),
+        _ => None,
+    };
+
+    let summary = data
+        .map(|data| {
+            data.iter()
+                .filter(|(key, _)| *key != "#attributes")
+                .filter_map(|(key, value)| match value {
+                    Value::Null => None,
+                    Value::String(s) if s.is_empty() || s == "-" => None,
+                    Value::String(s) => Some(format!("{key}={s}")),
+                    v => Some(format!("{key}={v}")),
+                })
+                .collect::<Vec<_>>()
+                .join(", ")
+        })
+        .unwrap_or_default();
+
+    if summary.chars().count() > MAX_SUMMARY_LENGTH {
+        let mut summary: String = summary.chars().take(MAX_SUMMARY_LENGTH - 3).collect();
+        summary.push_str("...");
+        summary
+    } else {
+        summary
+    }
+}
+
+/// the records before and after a gap
+#[derive(Serialize)]
+pub(crate) struct GapContext {
+    pub(crate) records_before: Vec<ContextRecord>,
+    pub(crate) records_after: Vec<ContextRecord>,
+}
+
+impl GapContext {
+    /// returns up to `count` records up to and including `before` and up to
+    /// `count` records after it
+    pub(crate) fn new(file: &ScannedFile, before: &EventId, count: usize) -> Self {
+        let ids = file.record_ids();
+        let position = ids
+            .binary_search_by_key(&before.event_record_id(), |id| id.event_record_id())
+            .unwrap_or_else(|p| p.saturating_sub(1));
+        let first = (position + 1).saturating_sub(count);
+        let last = (position + 1 + count).min(ids.len());
+
+        let to_context = |ids: &[EventId]| -> Vec<ContextRecord> {
+            ids.iter()
+                .map(|id| ContextRecord::from(&file.records()[id]))
+                .collect()
+        };
+        Self {
+            records_before: to_context(&ids[first..=position]),
+            records_after: to_context(&ids[position + 1..last]),
+        }
+    }
+}
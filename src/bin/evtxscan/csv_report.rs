@@ -0,0 +1,138 @@
+use std::io::Write;
+
+use anyhow::Result;
+use chrono::{DateTime, SecondsFormat, Utc};
+use dfir_toolkit::evtx::EventId;
+use serde::Serialize;
+
+use crate::{findings::Findings, scanned_file::UnreadableFile};
+
+/// columns of the CSV report. This header must not be changed, because
+/// reports of different hosts are expected to be concatenated
+pub(crate) const CSV_HEADER: [&str; 7] = [
+    "file",
+    "finding",
+    "start",
+    "end",
+    "duration_seconds",
+    "first_record_id",
+    "last_record_id",
+];
+
+#[derive(Serialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum FindingType {
+    TimeGap,
+    RecordIdGap,
+
+    /// record ids are missing, but no time gap has been found at this
+    /// position
+    RecordIdGapWithoutTimeGap,
+    TimeSkew,
+    UnreadableRecord,
+    UnreadableFile,
+}
+
+/// a single line of the CSV report
+#[derive(Serialize)]
+struct CsvFinding<'a> {
+    file: &'a str,
+    finding: FindingType,
+    start: Option<String>,
+    end: Option<String>,
+    duration_seconds: Option<i64>,
+    first_record_id: Option<u64>,
+    last_record_id: Option<u64>,
+}
+
+impl<'a> CsvFinding<'a> {
+    fn between(file: &'a str, finding: FindingType, first: &EventId, last: &EventId) -> Self {
+        Self {
+            file,
+            finding,
+            start: Some(format_timestamp(first.timestamp())),
+            end: Some(format_timestamp(last.timestamp())),
+            duration_seconds: Some((*last.timestamp() - *first.timestamp()).num_seconds()),
+            first_record_id: Some(first.event_record_id()),
+            last_record_id: Some(last.event_record_id()),
+        }
+    }
+}
+
+fn format_timestamp(timestamp: &DateTime<Utc>) -> String {
+    timestamp.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+}
+
+/// writes one line per finding. Unreadable records and unreadable files are
+/// listed with the finding types `unreadable_record` and `unreadable_file`;
+/// columns which are unknown remain empty
+pub(crate) fn write_csv_report<W: Write>(
+    writer: W,
+    findings: &[Findings],
+    unreadable_files: &[UnreadableFile],
+) -> Result<()> {
+    let mut writer = csv::WriterBuilder::new()
+        .has_headers(false)
+        .from_writer(writer);
+    writer.write_record(CSV_HEADER)?;
+
+    for file_findings in findings.iter() {
+        let file = file_findings.file.path();
+        for gap in file_findings.time_gaps.iter() {
+            writer.serialize(CsvFinding::between(
+                file,
+                FindingType::TimeGap,
+                gap.before,
+                gap.after,
+            ))?;
+        }
+        for gap in file_findings.record_id_gaps.iter() {
+            let finding = if gap.has_time_gap(file_findings.threshold) {
+                FindingType::RecordIdGap
+            } else {
+                FindingType::RecordIdGapWithoutTimeGap
+            };
+            writer.serialize(CsvFinding::between(file, finding, gap.before, gap.after))?;
+        }
+        for skew in file_findings.time_skews.iter() {
+            writer.serialize(CsvFinding::between(
+                file,
+                FindingType::TimeSkew,
+                skew.before,
+                skew.after,
+            ))?;
+        }
+
+        let unreadable_ids = file_findings.file.unreadable_ids();
+        let unknown_ids = file_findings.file.unreadable_records() - unreadable_ids.len();
+        let ids = unreadable_ids
+            .iter()
+            .map(|id| Some(*id))
+            .chain(std::iter::repeat(None).take(unknown_ids));
+        for id in ids {
+            writer.serialize(CsvFinding {
+                file,
+                finding: FindingType::UnreadableRecord,
+                start: None,
+                end: None,
+                duration_seconds: None,
+                first_record_id: id,
+                last_record_id: id,
+            })?;
+        }
+    }
+
+    for unreadable_file in unreadable_files.iter() {
+        writer.serialize(CsvFinding {
+            file: &unreadable_file.file,
+            finding: FindingType::UnreadableFile,
+            start: None,
+            end: None,
+            duration_seconds: None,
+            first_record_id: None,
+            last_record_id: None,
+        })?;
+    }
+    writer.flush()?;
+    Ok(())
+}
@@ -0,0 +1,68 @@
+use chrono::Duration;
+
+use dfir_toolkit::common::HumanDuration;
+use dfir_toolkit::evtx::{adaptive_threshold, EventId, RecordIdGap, TimeGap};
+
+use crate::{cli::Cli, context::GapContext, scanned_file::ScannedFile, time_skew::TimeSkew};
+
+/// everything which has been found in a single file
+pub(crate) struct Findings<'a> {
+    pub(crate) file: &'a ScannedFile,
+    pub(crate) threshold: Duration,
+    pub(crate) time_gaps: Vec<TimeGap<'a>>,
+    pub(crate) record_id_gaps: Vec<RecordIdGap<'a>>,
+    pub(crate) time_skews: Vec<TimeSkew<'a>>,
+
+    /// number of records which are displayed before and after every gap
+    pub(crate) context: Option<usize>,
+}
+
+impl<'a> Findings<'a> {
+    pub(crate) fn new(file: &'a ScannedFile, cli: &Cli) -> Self {
+        let threshold = gap_threshold(cli, file);
+        Self {
+            file,
+            threshold,
+            time_gaps: file.time_gaps(threshold),
+            record_id_gaps: file.record_id_gaps(),
+            time_skews: file.time_skews(negative_tolerance(cli)),
+            context: cli.context.map(usize::from),
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.time_gaps.is_empty() && self.record_id_gaps.is_empty() && self.time_skews.is_empty()
+    }
+
+    /// returns the records around the gap after `before`, if they have
+    /// been requested
+    pub(crate) fn context(&self, before: &EventId) -> Option<GapContext> {
+        self.context
+            .map(|count| GapContext::new(self.file, before, count))
+    }
+
+    pub(crate) fn largest_time_gap(&self) -> Option<&TimeGap<'a>> {
+        self.time_gaps.iter().max_by_key(|gap| gap.duration())
+    }
+}
+
+pub(crate) fn negative_tolerance(cli: &Cli) -> Duration {
+    Duration::seconds(cli.negative_tolerance.into())
+}
+
+/// uses the threshold specified on the command line, or computes one which
+/// fits to the cadence of the file
+fn gap_threshold(cli: &Cli, file: &ScannedFile) -> Duration {
+    match &cli.threshold {
+        Some(threshold) => threshold.duration(),
+        None => {
+            let threshold = adaptive_threshold(file.record_ids());
+            log::info!(
+                "using a gap threshold of {} for '{}'",
+                HumanDuration::from(threshold),
+                file.path()
+            );
+            threshold
+        }
+    }
+}
@@ -1,12 +1,9 @@
-use std::{collections::HashMap, path::PathBuf};
-
 use anyhow::Result;
 use chrono::Duration;
 use cli::Cli;
 use colored_json::to_colored_json_auto;
-use dfir_toolkit::common::FancyParser;
+use dfir_toolkit::common::{FancyParser, HumanDuration};
 use dfir_toolkit::evtx::{EventId, Range};
-use evtx::{EvtxParser, SerializedEvtxRecord};
 use term_table::row;
 use term_table::{
     row::Row,
@@ -14,57 +11,250 @@ use term_table::{
 };
 
 mod cli;
+mod context;
+mod csv_report;
+mod findings;
+mod output_format;
+mod report;
+mod scanned_file;
+mod time_skew;
+
+use context::ContextRecord;
+use csv_report::write_csv_report;
+use findings::{negative_tolerance, Findings};
+use output_format::OutputFormat;
+use report::{ScanReport, ScanSummary};
+use scanned_file::{expand_directories, RecordMap, ScannedFile, UnreadableFile};
+use time_skew::TimeSkew;
+
+/// exit code which is used if anything suspicious has been found
+const FINDINGS_FOUND: i32 = 1;
 
 fn main() -> Result<()> {
     let cli = Cli::parse_cli();
-    let mut record_ids: Vec<EventId> = Vec::new();
-    let mut records: HashMap<EventId, SerializedEvtxRecord<serde_json::Value>> = HashMap::new();
-
-    let path = PathBuf::from(&cli.evtx_file);
-
-    let mut parser = EvtxParser::from_path(path)?;
-    for record in parser.records_json_value() {
-        match record {
-            Err(_) => (),
-            Ok(evt) => {
-                let id = EventId::from(&evt);
-                record_ids.push(id.clone());
-                records.insert(id, evt);
+
+    let (paths, mut unreadable_files) = expand_directories(&cli.evtx_files);
+    let mut files = Vec::with_capacity(paths.len());
+    for path in paths {
+        match ScannedFile::read(&path) {
+            Ok(file) => files.push(file),
+            Err(why) => {
+                log::error!("unable to scan '{}': {why:#}", path.display());
+                unreadable_files.push(UnreadableFile {
+                    file: path.display().to_string(),
+                    error: format!("{why:#}"),
+                })
             }
         }
     }
-    record_ids.sort();
-
-    let mut current_range = None;
-    let mut ranges: Vec<Range> = Vec::new();
-
-    for id in record_ids.iter() {
-        if current_range.is_none() {
-            current_range = Some(Range::from(id.clone()));
-        } else {
-            let range = current_range.as_mut().unwrap();
-            if range.can_contain(id) {
-                range.add_event(id.clone());
-            } else {
-                ranges.push(current_range.replace(Range::from(id.clone())).unwrap());
+    let findings: Vec<_> = files.iter().map(|file| Findings::new(file, &cli)).collect();
+
+    match cli.format {
+        OutputFormat::Text => {
+            let multiple_files = findings.len() + unreadable_files.len() > 1;
+            for file_findings in findings.iter() {
+                if multiple_files {
+                    println!("FILE: {}", file_findings.file.path());
+                    println!();
+                }
+                print_findings(file_findings, &cli);
+            }
+            if multiple_files {
+                print_summary(&ScanSummary::new(&findings, &unreadable_files));
             }
+            print_unreadable_files(&unreadable_files);
+        }
+        OutputFormat::Json => {
+            let report = ScanReport::new(&findings, &unreadable_files);
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        }
+        OutputFormat::Csv => {
+            write_csv_report(std::io::stdout(), &findings, &unreadable_files)?;
         }
     }
 
-    if let Some(range) = current_range.take() {
-        ranges.push(range)
+    if findings.iter().any(|f| !f.is_empty()) {
+        std::process::exit(FINDINGS_FOUND);
+    } else if !unreadable_files.is_empty() {
+        std::process::exit(exitcode::DATAERR);
     }
-    ranges.sort();
-    print_ranges(&ranges, &records, &cli);
     Ok(())
 }
 
-fn print_ranges(
-    ranges: &[Range],
-    records: &HashMap<EventId, SerializedEvtxRecord<serde_json::Value>>,
-    cli: &Cli,
-) {
-    let allowed_bias = Duration::seconds(cli.negative_tolerance.into());
+fn print_findings(findings: &Findings, cli: &Cli) {
+    let records = findings.file.records();
+    print_ranges(&findings.file.ranges(), &findings.time_skews, records, cli);
+    print_time_gaps(findings, cli);
+    print_record_id_gaps(findings);
+    print_unreadable_records(findings);
+}
+
+fn print_summary(summary: &ScanSummary) {
+    println!("SUMMARY:");
+    println!("  {} files scanned", summary.scanned_files);
+    println!("  {} records unreadable", summary.unreadable_records);
+    println!("  {} time gaps", summary.time_gaps);
+    println!("  {} record id gaps", summary.record_id_gaps);
+    println!("  {} time skews", summary.time_skews);
+    if let Some(largest) = &summary.largest_time_gap {
+        println!(
+            "  largest time gap: {} in '{}' (between event {} and event {})",
+            HumanDuration::from(Duration::seconds(largest.gap.duration_seconds)),
+            largest.file,
+            largest.gap.record_id_before,
+            largest.gap.record_id_after
+        );
+    }
+    println!();
+}
+
+fn print_unreadable_files(unreadable_files: &[UnreadableFile]) {
+    if unreadable_files.is_empty() {
+        return;
+    }
+    println!("UNREADABLE FILES:");
+    for file in unreadable_files {
+        println!("  {}: {}", file.file, file.error);
+    }
+    println!();
+}
+
+fn print_unreadable_records(findings: &Findings) {
+    let file = findings.file;
+    if file.unreadable_records() == 0 {
+        return;
+    }
+    println!("UNREADABLE RECORDS:");
+    println!("  {} records unreadable", file.unreadable_records());
+    if !file.unreadable_ids().is_empty() {
+        let ids: Vec<_> = file.unreadable_ids().iter().map(u64::to_string).collect();
+        println!("  record ids: {}", ids.join(", "));
+    }
+    println!();
+}
+
+/// displays the records around a gap, if they have been requested with
+/// '--context'
+fn print_context(findings: &Findings, before: &EventId) {
+    if let Some(context) = findings.context(before) {
+        println!("    records before the gap:");
+        for record in context.records_before.iter() {
+            println!("      {}", record.line());
+        }
+        println!("    records after the gap:");
+        for record in context.records_after.iter() {
+            println!("      {}", record.line());
+        }
+    }
+}
+
+fn print_record_id_gaps(findings: &Findings) {
+    let (with_time_gap, without_time_gap): (Vec<_>, Vec<_>) = findings
+        .record_id_gaps
+        .iter()
+        .partition(|gap| gap.has_time_gap(findings.threshold));
+
+    for (title, gaps) in [
+        ("RECORD ID GAPS WITHOUT TIME GAP (possible selective deletion):", without_time_gap),
+        ("RECORD ID GAPS:", with_time_gap),
+    ] {
+        if gaps.is_empty() {
+            continue;
+        }
+        println!("{title}");
+        for gap in gaps {
+            println!(
+                "  {} missing records between event {} and event {}:",
+                gap.missing_ids(),
+                gap.before.event_record_id(),
+                gap.after.event_record_id()
+            );
+            println!(
+                "    event {} occurred at {},",
+                gap.before.event_record_id(),
+                gap.before.timestamp().format("%FT%T")
+            );
+            println!(
+                "    event {} occurred at {} ({} later)",
+                gap.after.event_record_id(),
+                gap.after.timestamp().format("%FT%T"),
+                HumanDuration::from(gap.duration())
+            );
+            print_context(findings, gap.before);
+            println!();
+        }
+    }
+}
+
+fn print_time_gaps(findings: &Findings, cli: &Cli) {
+    let gaps = &findings.time_gaps;
+    let records = findings.file.records();
+    if gaps.is_empty() {
+        return;
+    }
+
+    if cli.show_records {
+        let mut table = term_table::Table::new();
+        if let Some(size) = termsize::get() {
+            table.set_max_column_widths(vec![
+                (0, 12),
+                (1, (size.cols / 2 - 8).into()),
+                (2, (size.cols / 2 - 8).into()),
+            ])
+        }
+        for gap in gaps.iter() {
+            table.add_row(Row::new(vec![
+                TableCell::new(format!("time gap:\n{}", HumanDuration::from(gap.duration()))),
+                TableCell::new(format!(
+                    "last event {} occurred at {}",
+                    gap.before.event_record_id(),
+                    gap.before.timestamp().format("%FT%T")
+                )),
+                TableCell::new(format!(
+                    "next event {} occurred at {}",
+                    gap.after.event_record_id(),
+                    gap.after.timestamp().format("%FT%T")
+                )),
+            ]));
+            table.add_row(Row::new(vec![
+                TableCell::new(""),
+                TableCell::new(to_colored_json_auto(&records[gap.before].data).unwrap()),
+                TableCell::new(to_colored_json_auto(&records[gap.after].data).unwrap()),
+            ]));
+            if let Some(context) = findings.context(gap.before) {
+                let lines = |records: &[ContextRecord]| {
+                    records.iter().map(|r| r.line()).collect::<Vec<_>>().join("\n")
+                };
+                table.add_row(Row::new(vec![
+                    TableCell::new("context:"),
+                    TableCell::new(lines(&context.records_before)),
+                    TableCell::new(lines(&context.records_after)),
+                ]));
+            }
+        }
+        println!("{}", table.render());
+    } else {
+        println!("TIME GAPS:");
+        for gap in gaps.iter() {
+            println!("  no events for {}:", HumanDuration::from(gap.duration()));
+            println!(
+                "    last event {} occurred at {},",
+                gap.before.event_record_id(),
+                gap.before.timestamp().format("%FT%T")
+            );
+            println!(
+                "    next event {} occurred at {}",
+                gap.after.event_record_id(),
+                gap.after.timestamp().format("%FT%T")
+            );
+            print_context(findings, gap.before);
+            println!();
+        }
+    }
+}
+
+fn print_ranges(ranges: &[Range], skews: &[TimeSkew], records: &RecordMap, cli: &Cli) {
+    let tolerance = negative_tolerance(cli);
     if cli.show_records {
         for range in ranges.iter() {
             let mut table = term_table::Table::new();
@@ -101,34 +291,28 @@ fn print_ranges(
                     (2, (size.cols / 2 - 8).into()),
                 ])
             }
-            let mut last_event: Option<&EventId> = None;
-            for current_event in range.events() {
-                if let Some(event) = last_event {
-                    if *current_event.timestamp() + allowed_bias < *event.timestamp() {
-                        table.add_row(Row::new(vec![
-                            TableCell::new("time skew:"),
-                            TableCell::new(format!(
-                                "last event {} occurred at {}",
-                                event.event_record_id(),
-                                event.timestamp().format("%FT%T")
-                            )),
-                            TableCell::new(format!(
-                                "current event {} occurred at {}",
-                                current_event.event_record_id(),
-                                current_event.timestamp().format("%FT%T")
-                            )),
-                        ]));
-
-                        let record1 = &records[event];
-                        let record2 = &records[current_event];
-                        table.add_row(Row::new(vec![
-                            TableCell::new(""),
-                            TableCell::new(to_colored_json_auto(&record1.data).unwrap()),
-                            TableCell::new(to_colored_json_auto(&record2.data).unwrap()),
-                        ]));
-                    }
-                }
-                last_event = Some(current_event);
+            for skew in skews_in_range(skews, range) {
+                table.add_row(Row::new(vec![
+                    TableCell::new("time skew:"),
+                    TableCell::new(format!(
+                        "last event {} occurred at {}",
+                        skew.before.event_record_id(),
+                        skew.before.timestamp().format("%FT%T")
+                    )),
+                    TableCell::new(format!(
+                        "current event {} occurred at {}",
+                        skew.after.event_record_id(),
+                        skew.after.timestamp().format("%FT%T")
+                    )),
+                ]));
+
+                let record1 = &records[skew.before];
+                let record2 = &records[skew.after];
+                table.add_row(Row::new(vec![
+                    TableCell::new(""),
+                    TableCell::new(to_colored_json_auto(&record1.data).unwrap()),
+                    TableCell::new(to_colored_json_auto(&record2.data).unwrap()),
+                ]));
             }
             println!("{}", table.render());
         }
@@ -137,29 +321,37 @@ fn print_ranges(
             println!("RANGE: {}", range);
             println!("  {} events", range.len());
 
-            let mut last_event: Option<&EventId> = None;
-            for current_event in range.events() {
-                if let Some(event) = last_event {
-                    if *current_event.timestamp() + allowed_bias < *event.timestamp() {
-                        println!("  time skew detected:");
-                        println!(
-                            "    last event            {} occurred at {},",
-                            event.event_record_id(),
-                            event.timestamp().format("%FT%T")
-                        );
-                        println!(
-                            "    but the current event {} occurred at {}",
-                            current_event.event_record_id(),
-                            current_event.timestamp().format("%FT%T")
-                        );
-
-                        let duration = *current_event.timestamp() - *event.timestamp();
-                        println!("    this is a duration of {}", duration);
-                        println!();
-                    }
-                }
-                last_event = Some(current_event);
+            for skew in skews_in_range(skews, range) {
+                println!("  time skew detected:");
+                println!(
+                    "    last event            {} occurred at {},",
+                    skew.before.event_record_id(),
+                    skew.before.timestamp().format("%FT%T")
+                );
+                println!(
+                    "    but the current event {} occurred at {}",
+                    skew.after.event_record_id(),
+                    skew.after.timestamp().format("%FT%T")
+                );
+                println!("    this is a duration of {}", HumanDuration::from(skew.duration()));
+                println!();
             }
         }
     }
+
+    println!(
+        "TIME SKEWS: {} events are older than their predecessor (tolerance: {})",
+        skews.len(),
+        HumanDuration::from(tolerance)
+    );
+    println!();
+}
+
+/// returns the time skews whose later record belongs to `range`. A skew
+/// between two ranges is displayed together with the second range
+fn skews_in_range<'a>(skews: &'a [TimeSkew], range: &'a Range) -> impl Iterator<Item = &'a TimeSkew<'a>> {
+    let ids = range.begin().event_record_id()..=range.end().event_record_id();
+    skews
+        .iter()
+        .filter(move |skew| ids.contains(&skew.after.event_record_id()))
 }
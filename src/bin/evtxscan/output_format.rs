@@ -0,0 +1,16 @@
+#[derive(clap::ValueEnum, Clone)]
+pub(crate) enum OutputFormat {
+    /// human readable report
+    Text,
+
+    /// structured report, which contains the file summary and all findings
+    Json,
+
+    /// one line per finding, with the columns 'file', 'finding', 'start',
+    /// 'end', 'duration_seconds', 'first_record_id' and 'last_record_id'.
+    /// Findings are 'time_gap', 'record_id_gap',
+    /// 'record_id_gap_without_time_gap', 'time_skew', 'unreadable_record'
+    /// and 'unreadable_file'.
+    /// Timestamps are in ISO 8601 format, in UTC
+    Csv,
+}
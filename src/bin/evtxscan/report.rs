@@ -0,0 +1,237 @@
+use chrono::{DateTime, Utc};
+use dfir_toolkit::evtx::{EventId, RecordIdGap, TimeGap};
+use serde::Serialize;
+
+use chrono::Duration;
+
+use crate::{
+    context::GapContext,
+    findings::Findings,
+    scanned_file::{ScannedFile, UnreadableFile},
+    time_skew::TimeSkew,
+};
+
+/// machine readable report of a scan
+#[derive(Serialize)]
+pub(crate) struct ScanReport<'a> {
+    pub(crate) files: Vec<FileReport<'a>>,
+    pub(crate) unreadable_files: &'a [UnreadableFile],
+    pub(crate) summary: ScanSummary<'a>,
+}
+
+impl<'a> ScanReport<'a> {
+    pub(crate) fn new(findings: &'a [Findings], unreadable_files: &'a [UnreadableFile]) -> Self {
+        Self {
+            files: findings.iter().map(FileReport::new).collect(),
+            unreadable_files,
+            summary: ScanSummary::new(findings, unreadable_files),
+        }
+    }
+}
+
+/// summary of all scanned files
+#[derive(Serialize)]
+pub(crate) struct ScanSummary<'a> {
+    pub(crate) scanned_files: usize,
+    pub(crate) unreadable_files: usize,
+    pub(crate) unreadable_records: usize,
+    pub(crate) time_gaps: usize,
+    pub(crate) record_id_gaps: usize,
+    pub(crate) time_skews: usize,
+    pub(crate) largest_time_gap: Option<LargestTimeGap<'a>>,
+}
+
+impl<'a> ScanSummary<'a> {
+    pub(crate) fn new(findings: &'a [Findings], unreadable_files: &[UnreadableFile]) -> Self {
+        Self {
+            scanned_files: findings.len(),
+            unreadable_files: unreadable_files.len(),
+            unreadable_records: findings.iter().map(|f| f.file.unreadable_records()).sum(),
+            time_gaps: findings.iter().map(|f| f.time_gaps.len()).sum(),
+            record_id_gaps: findings.iter().map(|f| f.record_id_gaps.len()).sum(),
+            time_skews: findings.iter().map(|f| f.time_skews.len()).sum(),
+            largest_time_gap: findings
+                .iter()
+                .filter_map(|f| f.largest_time_gap().map(|gap| (f.file.path(), gap)))
+                .max_by_key(|(_, gap)| gap.duration())
+                .map(|(file, gap)| LargestTimeGap {
+                    file,
+                    gap: TimeGapReport::from(gap),
+                }),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub(crate) struct LargestTimeGap<'a> {
+    pub(crate) file: &'a str,
+
+    #[serde(flatten)]
+    pub(crate) gap: TimeGapReport,
+}
+
+#[derive(Serialize)]
+pub(crate) struct FileReport<'a> {
+    pub(crate) file: &'a str,
+    pub(crate) summary: FileSummary,
+    pub(crate) time_gaps: Vec<TimeGapReport>,
+    pub(crate) record_id_gaps: Vec<RecordIdGapReport>,
+    pub(crate) time_skews: Vec<TimeSkewReport>,
+}
+
+impl<'a> FileReport<'a> {
+    pub(crate) fn new(findings: &Findings<'a>) -> Self {
+        Self {
+            file: findings.file.path(),
+            summary: FileSummary::new(findings.file, &findings.time_skews),
+            time_gaps: findings
+                .time_gaps
+                .iter()
+                .map(|gap| TimeGapReport::from(gap).with_context(findings.context(gap.before)))
+                .collect(),
+            record_id_gaps: findings
+                .record_id_gaps
+                .iter()
+                .map(|gap| {
+                    RecordIdGapReport::new(gap, findings.threshold)
+                        .with_context(findings.context(gap.before))
+                })
+                .collect(),
+            time_skews: findings
+                .time_skews
+                .iter()
+                .map(TimeSkewReport::from)
+                .collect(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub(crate) struct FileSummary {
+    pub(crate) first_record: Option<RecordReference>,
+    pub(crate) last_record: Option<RecordReference>,
+    pub(crate) total_records: usize,
+
+    /// number of records which exist, but cannot be read
+    pub(crate) unreadable_records: usize,
+
+    /// number of records which are older than their predecessor
+    pub(crate) time_skews: usize,
+}
+
+impl FileSummary {
+    fn new(file: &ScannedFile, time_skews: &[TimeSkew]) -> Self {
+        Self {
+            first_record: file.record_ids().first().map(RecordReference::from),
+            last_record: file.record_ids().last().map(RecordReference::from),
+            total_records: file.record_ids().len(),
+            unreadable_records: file.unreadable_records(),
+            time_skews: time_skews.len(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub(crate) struct RecordReference {
+    pub(crate) event_record_id: u64,
+    pub(crate) timestamp: DateTime<Utc>,
+}
+
+impl From<&EventId> for RecordReference {
+    fn from(id: &EventId) -> Self {
+        Self {
+            event_record_id: id.event_record_id(),
+            timestamp: *id.timestamp(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub(crate) struct TimeGapReport {
+    pub(crate) start: DateTime<Utc>,
+    pub(crate) end: DateTime<Utc>,
+    pub(crate) duration_seconds: i64,
+    pub(crate) record_id_before: u64,
+    pub(crate) record_id_after: u64,
+
+    /// only set if '--context' has been specified
+    #[serde(flatten)]
+    pub(crate) context: Option<GapContext>,
+}
+
+impl TimeGapReport {
+    fn with_context(mut self, context: Option<GapContext>) -> Self {
+        self.context = context;
+        self
+    }
+}
+
+impl From<&TimeGap<'_>> for TimeGapReport {
+    fn from(gap: &TimeGap) -> Self {
+        Self {
+            start: *gap.before.timestamp(),
+            end: *gap.after.timestamp(),
+            duration_seconds: gap.duration().num_seconds(),
+            record_id_before: gap.before.event_record_id(),
+            record_id_after: gap.after.event_record_id(),
+            context: None,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub(crate) struct RecordIdGapReport {
+    pub(crate) record_id_before: u64,
+    pub(crate) record_id_after: u64,
+    pub(crate) missing_ids: u64,
+    pub(crate) start: DateTime<Utc>,
+    pub(crate) end: DateTime<Utc>,
+    pub(crate) duration_seconds: i64,
+
+    /// `false` if the records are missing although no time gap has been
+    /// found at this position, which indicates selective deletion
+    pub(crate) with_time_gap: bool,
+
+    /// only set if '--context' has been specified
+    #[serde(flatten)]
+    pub(crate) context: Option<GapContext>,
+}
+
+impl RecordIdGapReport {
+    fn new(gap: &RecordIdGap, threshold: Duration) -> Self {
+        Self {
+            record_id_before: gap.before.event_record_id(),
+            record_id_after: gap.after.event_record_id(),
+            missing_ids: gap.missing_ids(),
+            start: *gap.before.timestamp(),
+            end: *gap.after.timestamp(),
+            duration_seconds: gap.duration().num_seconds(),
+            with_time_gap: gap.has_time_gap(threshold),
+            context: None,
+        }
+    }
+
+    fn with_context(mut self, context: Option<GapContext>) -> Self {
+        self.context = context;
+        self
+    }
+}
+
+#[derive(Serialize)]
+pub(crate) struct TimeSkewReport {
+    pub(crate) previous_record: RecordReference,
+    pub(crate) record: RecordReference,
+
+    /// always negative, because the record is older than its predecessor
+    pub(crate) duration_seconds: i64,
+}
+
+impl From<&TimeSkew<'_>> for TimeSkewReport {
+    fn from(skew: &TimeSkew) -> Self {
+        Self {
+            previous_record: RecordReference::from(skew.before),
+            record: RecordReference::from(skew.after),
+            duration_seconds: skew.duration().num_seconds(),
+        }
+    }
+}
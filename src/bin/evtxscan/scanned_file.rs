@@ -0,0 +1,197 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use chrono::Duration;
+use dfir_toolkit::evtx::{
+    find_record_id_gaps, find_time_gaps, EventId, EvtxFile, Range, RecordIdGap, TimeGap,
+};
+use evtx::{err::EvtxError, SerializedEvtxRecord};
+use serde::Serialize;
+use walkdir::WalkDir;
+
+use crate::time_skew::{find_time_skews, TimeSkew};
+
+pub(crate) type RecordMap = HashMap<EventId, SerializedEvtxRecord<serde_json::Value>>;
+
+/// all records of an evtx file, sorted by their event record id
+pub(crate) struct ScannedFile {
+    path: String,
+    record_ids: Vec<EventId>,
+    records: RecordMap,
+
+    /// ids of the records which exist, but cannot be read
+    unreadable_ids: BTreeSet<u64>,
+
+    /// number of records which cannot be read, including those whose id
+    /// is unknown
+    unreadable_records: usize,
+}
+
+impl ScannedFile {
+    #[allow(clippy::result_large_err)]
+    pub(crate) fn read(path: &Path) -> Result<Self> {
+        let mut record_ids: Vec<EventId> = Vec::new();
+        let mut records: RecordMap = HashMap::new();
+        let mut unreadable_ids = BTreeSet::new();
+        let mut unreadable_records = 0;
+
+        // the id of a record which cannot be converted is still known, so
+        // it is kept before the conversion
+        let file = EvtxFile::open(path)?;
+        let results = file.serialized_records(|record| {
+            record.map(|record| (record.event_record_id, record.into_json_value()))
+        });
+        for result in results {
+            match result {
+                Err(EvtxError::FailedToParseRecord { record_id, source }) => {
+                    log::warn!(
+                        "unable to read the record {record_id} of '{}': {source}",
+                        path.display()
+                    );
+                    unreadable_ids.insert(record_id);
+                    unreadable_records += 1;
+                }
+                Ok((record_id, Err(why))) => {
+                    log::warn!(
+                        "unable to read the record {record_id} of '{}': {why}",
+                        path.display()
+                    );
+                    unreadable_ids.insert(record_id);
+                    unreadable_records += 1;
+                }
+                Err(why) => {
+                    log::warn!("unable to read a record of '{}': {why}", path.display());
+                    unreadable_records += 1;
+                }
+                Ok((_, Ok(evt))) => {
+                    let id = EventId::from(&evt);
+                    record_ids.push(id.clone());
+                    records.insert(id, evt);
+                }
+            }
+        }
+        record_ids.sort();
+
+        Ok(Self {
+            path: path.display().to_string(),
+            record_ids,
+            records,
+            unreadable_ids,
+            unreadable_records,
+        })
+    }
+
+    pub(crate) fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub(crate) fn record_ids(&self) -> &[EventId] {
+        &self.record_ids
+    }
+
+    pub(crate) fn records(&self) -> &RecordMap {
+        &self.records
+    }
+
+    pub(crate) fn unreadable_ids(&self) -> &BTreeSet<u64> {
+        &self.unreadable_ids
+    }
+
+    pub(crate) fn unreadable_records(&self) -> usize {
+        self.unreadable_records
+    }
+
+    /// splits the records into ranges of consecutive record ids
+    pub(crate) fn ranges(&self) -> Vec<Range> {
+        let mut current_range = None;
+        let mut ranges: Vec<Range> = Vec::new();
+
+        for id in self.record_ids.iter() {
+            if current_range.is_none() {
+                current_range = Some(Range::from(id.clone()));
+            } else {
+                let range = current_range.as_mut().unwrap();
+                if range.can_contain(id) {
+                    range.add_event(id.clone());
+                } else {
+                    ranges.push(current_range.replace(Range::from(id.clone())).unwrap());
+                }
+            }
+        }
+
+        if let Some(range) = current_range.take() {
+            ranges.push(range)
+        }
+        ranges.sort();
+        ranges
+    }
+
+    pub(crate) fn time_gaps(&self, threshold: Duration) -> Vec<TimeGap<'_>> {
+        find_time_gaps(&self.record_ids, threshold)
+    }
+
+    /// gaps which only consist of unreadable records are not reported,
+    /// because these records have not been deleted
+    pub(crate) fn record_id_gaps(&self) -> Vec<RecordIdGap<'_>> {
+        find_record_id_gaps(&self.record_ids)
+            .into_iter()
+            .filter(|gap| {
+                let missing = gap.before.event_record_id() + 1..gap.after.event_record_id();
+                self.unreadable_ids.range(missing).count() as u64 != gap.missing_ids()
+            })
+            .collect()
+    }
+
+    pub(crate) fn time_skews(&self, tolerance: Duration) -> Vec<TimeSkew<'_>> {
+        find_time_skews(&self.record_ids, tolerance)
+    }
+}
+
+/// a file which could not be scanned
+#[derive(Serialize)]
+pub(crate) struct UnreadableFile {
+    pub(crate) file: String,
+    pub(crate) error: String,
+}
+
+/// replaces every directory by the evtx files it contains, including those
+/// in subdirectories. Directories which cannot be read are returned as
+/// unreadable files
+pub(crate) fn expand_directories(paths: &[String]) -> (Vec<PathBuf>, Vec<UnreadableFile>) {
+    let mut files = Vec::new();
+    let mut failures = Vec::new();
+    for path in paths.iter().map(PathBuf::from) {
+        if !path.is_dir() {
+            files.push(path);
+            continue;
+        }
+
+        let mut evtx_files = Vec::new();
+        for entry in WalkDir::new(&path).sort_by_file_name() {
+            match entry {
+                Err(why) => failures.push(UnreadableFile {
+                    file: why.path().unwrap_or(&path).display().to_string(),
+                    error: why.to_string(),
+                }),
+                Ok(entry) => {
+                    if entry.file_type().is_file()
+                        && entry
+                            .path()
+                            .extension()
+                            .is_some_and(|ext| ext.eq_ignore_ascii_case("evtx"))
+                    {
+                        evtx_files.push(entry.into_path());
+                    }
+                }
+            }
+        }
+        if evtx_files.is_empty() {
+            log::warn!("directory '{}' contains no evtx files", path.display());
+        }
+        files.extend(evtx_files);
+    }
+    (files, failures)
+}
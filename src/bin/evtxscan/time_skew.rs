@@ -0,0 +1,27 @@
+use chrono::Duration;
+use dfir_toolkit::evtx::EventId;
+
+/// a record which has been created before its predecessor in record order
+pub(crate) struct TimeSkew<'a> {
+    pub(crate) before: &'a EventId,
+    pub(crate) after: &'a EventId,
+}
+
+impl<'a> TimeSkew<'a> {
+    /// the (negative) duration between both records
+    pub(crate) fn duration(&self) -> Duration {
+        *self.after.timestamp() - *self.before.timestamp()
+    }
+}
+
+/// returns all records of `ids` which are older than their predecessor by
+/// more than `tolerance`. `ids` must be sorted by the event record id
+pub(crate) fn find_time_skews(ids: &[EventId], tolerance: Duration) -> Vec<TimeSkew<'_>> {
+    ids.windows(2)
+        .filter(|pair| *pair[1].timestamp() + tolerance < *pair[0].timestamp())
+        .map(|pair| TimeSkew {
+            before: &pair[0],
+            after: &pair[1],
+        })
+        .collect()
+}
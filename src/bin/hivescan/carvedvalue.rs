@@ -0,0 +1,90 @@
+use nt_hive2::{KeyValue, Offset, RegistryValue};
+
+/// a larger number of values is most probably the result of garbage in a
+/// reused cell
+const MAX_VALUES_COUNT: usize = 0x10000;
+
+/// a value record which has been found in a free cell
+pub(crate) struct CarvedValue {
+    offset: Offset,
+    formatted: String,
+}
+
+impl CarvedValue {
+    /// `resident_data` is the data which is stored in the value record
+    /// itself, decoded according to the type of the value
+    pub fn new(offset: Offset, value: &KeyValue, resident_data: Option<&RegistryValue>) -> Self {
+        Self {
+            offset,
+            formatted: format_value(value.name(), resident_data.unwrap_or(value.value())),
+        }
+    }
+
+    pub fn offset(&self) -> &Offset {
+        &self.offset
+    }
+
+    /// the name and the data of the value
+    pub fn formatted(&self) -> &str {
+        &self.formatted
+    }
+}
+
+/// formats a value as `"name" = data`
+pub(crate) fn format_value(name: &str, value: &RegistryValue) -> String {
+    match value {
+        RegistryValue::RegFileTime => format!("\"{name}\" = <unsupported>"),
+        value => format!("\"{name}\" = {value}"),
+    }
+}
+
+/// the offsets in the values list of a key node. `nt_hive2` does not expose
+/// these offsets, but they are needed to find the keys which deleted values
+/// belong to
+pub(crate) struct ValueOffsets {
+    /// the values of the key
+    pub(crate) values: Vec<Offset>,
+
+    /// offsets in the unused rest of the list cell, which may refer to
+    /// values which have been deleted from the key
+    pub(crate) slack: Vec<Offset>,
+}
+
+impl ValueOffsets {
+    /// reads the values list of the key node at `nk_offset` from the raw
+    /// hive bins
+    pub fn read(data: &[u8], nk_offset: &Offset) -> Self {
+        let mut offsets = Self {
+            values: Vec::new(),
+            slack: Vec::new(),
+        };
+        let nk_offset = nk_offset.0 as usize;
+        let (Some(count), Some(list)) =
+            (u32_at(data, nk_offset + 40), u32_at(data, nk_offset + 44))
+        else {
+            return offsets;
+        };
+        let Some(cell_size) = u32_at(data, list as usize) else {
+            return offsets;
+        };
+        let capacity = ((cell_size as i32).unsigned_abs() as usize).saturating_sub(4) / 4;
+        let count = count as usize;
+        if count == 0 || count > MAX_VALUES_COUNT || count > capacity {
+            return offsets;
+        }
+        for i in 0..capacity {
+            match u32_at(data, list as usize + 4 + i * 4) {
+                Some(offset) if i < count => offsets.values.push(Offset(offset)),
+                Some(offset) => offsets.slack.push(Offset(offset)),
+                None => break,
+            }
+        }
+        offsets
+    }
+}
+
+fn u32_at(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(
+        data.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
@@ -1,9 +1,30 @@
 use std::path::PathBuf;
 
-use clap::{Parser, ValueHint};
-use dfir_toolkit::common::HasVerboseFlag;
+use clap::{Parser, ValueEnum, ValueHint};
+use dfir_toolkit::common::{HasVerboseFlag, Verbosity};
 use log::LevelFilter;
+use regex::Regex;
 
+use crate::findingfilter::FindingFilter;
+
+#[derive(ValueEnum, Clone, PartialEq, Eq)]
+pub(crate) enum OutputFormat {
+    /// deleted keys and their values, together with the offsets where they
+    /// have been found. Deleted values are shown below the keys whose values
+    /// lists still refer to them, or as orphaned values. Keys whose parent
+    /// could not be found are shown below '[unknown-parent]'
+    Text,
+
+    /// one bodyfile line per key, using the last-written time as mtime.
+    /// Deleted keys are marked as such, and the paths of keys whose parent
+    /// could not be found begin with '[unknown-parent]'
+    Bodyfile,
+
+    /// list of all keys which have been found, with their paths, their
+    /// last-written times, whether they have been recovered from free cells
+    /// and the offsets of their cells in the hive file
+    Json,
+}
 
 /// scans a registry hive file for deleted entries
 #[derive(Parser)]
@@ -19,11 +40,39 @@ pub (crate) struct Cli {
     pub (crate) logfiles: Vec<PathBuf>,
 
     #[clap(flatten)]
-    pub (crate) verbose: clap_verbosity_flag::Verbosity,
+    pub (crate) verbose: Verbosity,
 
-    /// output as bodyfile format
-    #[clap(short('b'))]
+    /// output as bodyfile format (same as '--format bodyfile')
+    #[clap(short('b'), long("bodyfile"), conflicts_with = "format")]
     pub (crate) print_bodyfile: bool,
+
+    /// output format
+    #[clap(short('F'), long("format"), value_enum, default_value_t = OutputFormat::Text)]
+    pub (crate) format: OutputFormat,
+
+    /// show only keys whose name matches this regular expression, which is
+    /// case insensitive. Orphaned values are not shown if a filter is used
+    #[clap(long("name-regex"), value_name = "REGEX", value_parser = parse_name_regex)]
+    pub (crate) name_regex: Option<Regex>,
+
+    /// show only keys whose path contains this string, case insensitive.
+    /// Both '\' and '/' can be used to separate the names of keys
+    #[clap(long("path-contains"), value_name = "STRING")]
+    pub (crate) path_contains: Option<String>,
+}
+
+impl Cli {
+    pub(crate) fn output_format(&self) -> OutputFormat {
+        if self.print_bodyfile {
+            OutputFormat::Bodyfile
+        } else {
+            self.format.clone()
+        }
+    }
+
+    pub(crate) fn finding_filter(&self) -> FindingFilter {
+        FindingFilter::new(self.name_regex.clone(), self.path_contains.as_deref())
+    }
 }
 
 impl HasVerboseFlag for Cli {
@@ -32,6 +81,10 @@ impl HasVerboseFlag for Cli {
     }
 }
 
+fn parse_name_regex(s: &str) -> Result<Regex, String> {
+    Regex::new(&format!("(?i){s}")).map_err(|why| format!("invalid regular expression '{s}': {why}"))
+}
+
 fn validate_file(s: &str) -> Result<PathBuf, String> {
     let pb = PathBuf::from(s);
     if pb.is_file() && pb.exists() {
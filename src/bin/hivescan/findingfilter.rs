@@ -0,0 +1,59 @@
+use std::cell::Cell;
+
+use regex::Regex;
+
+/// selects the keys which are shown, by `--name-regex` and `--path-contains`,
+/// and counts the keys which have been suppressed
+pub(crate) struct FindingFilter {
+    name_regex: Option<Regex>,
+
+    /// lowercase, with backslashes as separators
+    path_contains: Option<String>,
+
+    suppressed: Cell<usize>,
+}
+
+impl FindingFilter {
+    pub fn new(name_regex: Option<Regex>, path_contains: Option<&str>) -> Self {
+        Self {
+            name_regex,
+            path_contains: path_contains.map(normalize_path),
+            suppressed: Cell::new(0),
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.name_regex.is_some() || self.path_contains.is_some()
+    }
+
+    /// returns `true` if the key with `name` and `path` should be shown.
+    /// Paths may be separated by backslashes or slashes. Keys which are
+    /// not shown are counted as suppressed
+    pub fn accepts(&self, name: &str, path: &str) -> bool {
+        let accepted = self
+            .name_regex
+            .as_ref()
+            .map_or(true, |regex| regex.is_match(name))
+            && self
+                .path_contains
+                .as_ref()
+                .map_or(true, |needle| normalize_path(path).contains(needle));
+        if !accepted {
+            self.suppress(1);
+        }
+        accepted
+    }
+
+    /// counts findings which are suppressed without being checked
+    pub fn suppress(&self, count: usize) {
+        self.suppressed.set(self.suppressed.get() + count);
+    }
+
+    pub fn suppressed(&self) -> usize {
+        self.suppressed.get()
+    }
+}
+
+fn normalize_path(path: &str) -> String {
+    path.replace('/', "\\").to_lowercase()
+}
@@ -1,4 +1,7 @@
-use crate::cli::Cli;
+use crate::carvedvalue::{format_value, CarvedValue};
+use crate::cli::{Cli, OutputFormat};
+use crate::findingfilter::FindingFilter;
+use crate::keyfinding::KeyFinding;
 use crate::regtreeentry::RegTreeEntry;
 use anyhow::Result;
 use dfir_toolkit::common::bodyfile::Bodyfile3Line;
@@ -8,13 +11,17 @@ use std::{io::Read, io::Seek};
 
 use crate::regtreebuilder::RegTreeBuilder;
 
+/// replaces the path of the parent of keys whose parent could not be found,
+/// in all output formats
+const UNKNOWN_PARENT: &str = "[unknown-parent]";
+
 pub(crate) struct HiveScanApplication<RS>
 where
     RS: Read + Seek,
 {
-    #[allow(dead_code)]
     cli: Cli,
 
+    filter: FindingFilter,
     root_offset: Offset,
     hive: Option<Hive<RS, CleanHive>>,
 }
@@ -25,6 +32,7 @@ where
 {
     pub fn new(cli: Cli, hive: Hive<RS, CleanHive>) -> Self {
         Self {
+            filter: cli.finding_filter(),
             cli,
             root_offset: hive.root_cell_offset(),
             hive: Some(hive),
@@ -45,44 +53,71 @@ where
 
         assert!(self.hive.is_none());
 
+        match self.cli.output_format() {
+            OutputFormat::Bodyfile => {
+                self.for_each_key(&builder, &mut |path, entry| {
+                    print_bodyfile_line(path, entry)
+                });
+            }
+            OutputFormat::Json => {
+                let mut findings = Vec::new();
+                self.for_each_key(&builder, &mut |path, entry| {
+                    findings.push(KeyFinding::new(path, entry))
+                });
+                println!("{}", serde_json::to_string_pretty(&findings)?);
+            }
+            OutputFormat::Text => self.print_text(&builder),
+        }
+
+        // this is shown by default, because the output itself does not
+        // indicate that findings are missing
+        if self.filter.is_active() && self.filter.suppressed() > 0 {
+            log::warn!(
+                "{} findings have been suppressed by the filters",
+                self.filter.suppressed()
+            );
+        }
+        Ok(())
+    }
+
+    fn print_text(&self, builder: &RegTreeBuilder) {
         for node in builder.root_nodes() {
-            if node.borrow().offset() == &self.root_offset {
-                // this is the root entry, which we don't print by itself
-                for grandchild in node.borrow().children() {
-                    self.print_entry("", &grandchild, false);
+            let node = node.borrow();
+            if node.offset() == &self.root_offset {
+                // this is the root entry, which we don't print by itself,
+                // unless deleted values refer to it
+                let path = node.nk().name();
+                if !node.deleted_values().is_empty() && self.filter.accepts(path, path) {
+                    self.print_section(path, &node);
+                }
+                for child in node.children() {
+                    self.print_entry(path, &child, false);
                 }
             } else {
-                let parent = format!("/$Orphaned/{:x}", node.borrow().nk().parent.0);
-                self.print_entry(&parent, &node.borrow(), false);
+                self.print_entry(UNKNOWN_PARENT, &node, false);
             }
         }
-        Ok(())
+
+        // orphaned values have no key which could match the filters
+        let orphaned_values = builder.orphaned_values();
+        if self.filter.is_active() {
+            self.filter.suppress(orphaned_values.len());
+        } else if !orphaned_values.is_empty() {
+            println!("[{UNKNOWN_PARENT}]; deleted values whose key could not be found");
+            for value in orphaned_values {
+                print_deleted_value(value);
+            }
+            println!();
+        }
     }
 
     fn print_entry(&self, path: &str, entry: &RegTreeEntry, force_print: bool) {
-        let path = format!("{}/{}", path, entry.nk().name());
+        let path = format!("{}\\{}", path, entry.nk().name());
 
-        if self.cli.print_bodyfile {
-            let bf_name = if entry.is_deleted() {
-                format!("{} (deleted)", path)
-            } else {
-                path.clone()
-            };
-
-            let bf_line = Bodyfile3Line::new()
-                .with_owned_name(bf_name)
-                .with_inode(&format!("{:x}", entry.offset().0))
-                .with_ctime(entry.nk().timestamp().into());
-            println!("{}", bf_line);
-        } else if entry.is_deleted() || force_print {
-            println!(
-                "[{}]; last change at {}, found at offset 0x{:x}",
-                path,
-                entry.nk().timestamp().to_rfc3339(),
-                entry.offset().0 + BASEBLOCK_SIZE as u32
-            );
-            self.print_values_of(entry);
-            println!();
+        if (entry.is_deleted() || force_print || !entry.deleted_values().is_empty())
+            && self.filter.accepts(entry.nk().name(), &path)
+        {
+            self.print_section(&path, entry);
         }
 
         for child in entry.children() {
@@ -90,9 +125,88 @@ where
         }
     }
 
+    /// calls `visit` for every key which has been found, together with its
+    /// path. Paths are separated by backslashes and begin with the name of
+    /// the root key, like the paths in the text output and the paths which
+    /// are printed by `regdump`. Keys whose parent could not be found are
+    /// placed below [UNKNOWN_PARENT]. Keys which do not match the filters
+    /// are skipped
+    fn for_each_key<F>(&self, builder: &RegTreeBuilder, visit: &mut F)
+    where
+        F: FnMut(&str, &RegTreeEntry),
+    {
+        let mut visit_matching = |path: &str, entry: &RegTreeEntry| {
+            if self.filter.accepts(entry.nk().name(), path) {
+                visit(path, entry)
+            }
+        };
+        for node in builder.root_nodes() {
+            let node = node.borrow();
+            if node.offset() == &self.root_offset {
+                visit_entry(None, &node, &mut visit_matching);
+            } else {
+                visit_entry(Some(UNKNOWN_PARENT), &node, &mut visit_matching);
+            }
+        }
+    }
+
+    fn print_section(&self, path: &str, entry: &RegTreeEntry) {
+        println!(
+            "[{}]; last change at {}, found at offset 0x{:x}",
+            path,
+            entry.nk().timestamp().to_rfc3339(),
+            entry.offset().0 + BASEBLOCK_SIZE as u32
+        );
+        self.print_values_of(entry);
+        println!();
+    }
+
+    /// prints the values of the key, followed by the deleted values which
+    /// still belong to it
     fn print_values_of(&self, entry: &RegTreeEntry) {
         for value in entry.nk().values() {
-            println!("\"{}\" = {}", value.name(), value.value());
+            println!("{}", format_value(value.name(), value.value()));
         }
+        for value in entry.deleted_values() {
+            print_deleted_value(value);
+        }
+    }
+}
+
+/// calls `visit` for the key and all of its descendants
+fn visit_entry<F>(parent_path: Option<&str>, entry: &RegTreeEntry, visit: &mut F)
+where
+    F: FnMut(&str, &RegTreeEntry),
+{
+    let path = match parent_path {
+        Some(parent_path) => format!("{}\\{}", parent_path, entry.nk().name()),
+        None => entry.nk().name().to_owned(),
+    };
+    visit(&path, entry);
+
+    for child in entry.children() {
+        visit_entry(Some(&path), &child, visit);
     }
 }
+
+fn print_bodyfile_line(path: &str, entry: &RegTreeEntry) {
+    let bf_name = if entry.is_deleted() {
+        format!("{} (deleted)", path)
+    } else {
+        path.to_owned()
+    };
+
+    let bf_line = Bodyfile3Line::new()
+        .with_owned_name(bf_name)
+        .with_inode(&format!("{:x}", entry.offset().0))
+        .with_mtime(entry.nk().timestamp().into());
+    println!("{}", bf_line);
+}
+
+fn print_deleted_value(value: &CarvedValue) {
+    println!(
+        "{}; deleted, found at offset 0x{:x}",
+        value.formatted(),
+        value.offset().0 + BASEBLOCK_SIZE as u32
+    );
+}
@@ -0,0 +1,36 @@
+use chrono::{DateTime, Utc};
+use nt_hive2::BASEBLOCK_SIZE;
+use serde::Serialize;
+
+use crate::regtreeentry::RegTreeEntry;
+
+/// a key which has been found while scanning the cells of a hive, used for
+/// `--format json`
+#[derive(Serialize)]
+pub(crate) struct KeyFinding {
+    name: String,
+
+    /// full path of the key. If the parent of the key could not be found,
+    /// the path begins with `[unknown-parent]`
+    path: String,
+
+    last_written: DateTime<Utc>,
+
+    /// `false` if the key has been recovered from a free cell
+    allocated: bool,
+
+    /// offset of the cell in the hive file, including the base block
+    offset: u32,
+}
+
+impl KeyFinding {
+    pub fn new(path: &str, entry: &RegTreeEntry) -> Self {
+        Self {
+            name: entry.nk().name().to_owned(),
+            path: path.to_owned(),
+            last_written: *entry.nk().timestamp(),
+            allocated: !entry.is_deleted(),
+            offset: entry.offset().0 + BASEBLOCK_SIZE as u32,
+        }
+    }
+}
@@ -4,7 +4,10 @@ use dfir_toolkit::common::FancyParser;
 use nt_hive2::{ContainsHive, Hive, HiveParseMode};
 use std::{fs::File, io::BufReader};
 
+mod carvedvalue;
+mod findingfilter;
 mod hivescanapplication;
+mod keyfinding;
 mod regtreebuilder;
 mod regtreeentry;
 use hivescanapplication::*;
@@ -39,7 +42,7 @@ fn main() -> Result<()> {
             app.run()
         }
         Err(why) => {
-            eprintln!("unable to open '{}': {}", cli.hive_file, why);
+            log::error!("unable to open '{}': {}", cli.hive_file, why);
             std::process::exit(-1);
         }
     }
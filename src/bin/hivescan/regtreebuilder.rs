@@ -1,10 +1,12 @@
 use std::{
-    cell::RefCell, cmp::max, collections::{hash_map, HashMap}, rc::Rc
+    cell::RefCell, cmp::max, collections::{hash_map, HashMap}, io::{Cursor, Read, Seek, SeekFrom}, rc::Rc
 };
 
 use binread::BinReaderExt;
-use nt_hive2::{CleanHive, Hive, KeyNode, Offset};
+use nt_hive2::{CleanHive, Hive, KeyNode, KeyValue, Offset};
+use dfir_toolkit::registry::read_resident_data;
 
+use crate::carvedvalue::{CarvedValue, ValueOffsets};
 use crate::regtreeentry::*;
 
 pub(crate) struct RegTreeBuilder {
@@ -17,6 +19,9 @@ pub(crate) struct RegTreeBuilder {
     /// contains the offsets of all non-added entries which are parents of
     /// already added entries, and the entries that miss their parents
     missing_parents: HashMap<Offset, Vec<Rc<RefCell<RegTreeEntry>>>>,
+
+    /// contains all deleted values which are not referenced by any key
+    orphaned_values: Vec<CarvedValue>,
 }
 
 impl<B> From<Hive<B, CleanHive>> for RegTreeBuilder
@@ -41,7 +46,7 @@ impl<'a> Iterator for RootNodes<'a> {
 }
 
 impl RegTreeBuilder {
-    pub fn from_hive<B, C>(hive: Hive<B, CleanHive>, progress_callback: C) -> Self
+    pub fn from_hive<B, C>(mut hive: Hive<B, CleanHive>, progress_callback: C) -> Self
     where
         B: BinReaderExt,
         C: Fn(u64),
@@ -50,8 +55,17 @@ impl RegTreeBuilder {
             subtrees: HashMap::new(),
             entries: HashMap::new(),
             missing_parents: HashMap::new(),
+            orphaned_values: Vec::new(),
         };
 
+        // the offsets of values are not exposed by nt_hive2, so they are
+        // read from the raw hive bins
+        let mut data = Vec::new();
+        if let Err(why) = hive.seek(SeekFrom::Start(0)).and_then(|_| hive.read_to_end(&mut data)) {
+            log::warn!("unable to read the hive bins, deleted values will be reported as orphaned: {why}");
+        }
+        let mut deleted_values = HashMap::new();
+
         let mut last_offset = Offset(0);
         let mut last_cell_end = 0;
         for cell in hive.hivebins().flat_map(|hivebin| hivebin.cells()) {
@@ -61,6 +75,12 @@ impl RegTreeBuilder {
             assert_ne!(last_offset, my_offset);
             log::trace!("found new cell at offset 0x{:x}", my_offset.0);
 
+            if is_deleted {
+                if let Some(value) = read_deleted_value(&data, my_offset) {
+                    deleted_values.insert(my_offset, value);
+                }
+            }
+
             if let Ok(nk) = TryInto::<KeyNode>::try_into(cell) {
                 me.insert_nk(my_offset, nk, is_deleted);
             }
@@ -69,15 +89,42 @@ impl RegTreeBuilder {
             last_cell_end = max(last_cell_end, u64::from(last_offset.0) + u64::try_from(my_size).unwrap());
             progress_callback(last_cell_end);
         }
+        me.assign_deleted_values(&data, deleted_values);
         me
     }
 
-    pub fn root_nodes(&self) -> RootNodes {
+    /// assigns deleted values to the keys whose values lists still refer to
+    /// them. Values in the list of a key are already part of its key node,
+    /// but the slack of the list may still refer to values which have been
+    /// deleted from the key. All other values are orphaned
+    fn assign_deleted_values(&mut self, data: &[u8], mut deleted_values: HashMap<Offset, CarvedValue>) {
+        let mut slack_values = Vec::new();
+        for (offset, entry) in self.entries.iter() {
+            let offsets = ValueOffsets::read(data, offset);
+            for value_offset in offsets.values {
+                deleted_values.remove(&value_offset);
+            }
+            slack_values.extend(offsets.slack.into_iter().map(|value_offset| (value_offset, entry)));
+        }
+        for (value_offset, entry) in slack_values {
+            if let Some(value) = deleted_values.remove(&value_offset) {
+                entry.borrow_mut().add_deleted_value(value);
+            }
+        }
+        self.orphaned_values = deleted_values.into_values().collect();
+        self.orphaned_values.sort_by_key(|value| value.offset().0);
+    }
+
+    pub fn root_nodes(&self) -> RootNodes<'_> {
         RootNodes {
             values: self.subtrees.values(),
         }
     }
 
+    pub fn orphaned_values(&self) -> &[CarvedValue] {
+        &self.orphaned_values
+    }
+
     fn insert_nk(&mut self, nk_offset: Offset, nk: KeyNode, is_deleted: bool) {
         assert!(!self.subtrees.contains_key(&nk_offset), "KeyNode at offset 0x{:08x} is already in the set of subtrees", nk_offset.0);
         assert!(!self.entries.contains_key(&nk_offset));
@@ -126,3 +173,27 @@ impl RegTreeBuilder {
         }
     }
 }
+
+/// reads the value record in the free cell at `offset`, if there is any.
+/// Offsets in `data` are the same as in the hive bins, so that the data of
+/// the value can be read from `data`, too
+fn read_deleted_value(data: &[u8], offset: Offset) -> Option<CarvedValue> {
+    let start = offset.0 as usize;
+    if data.get(start + 4..start + 6) != Some(b"vk") {
+        return None;
+    }
+
+    // skip the cell size and the signature
+    let mut cursor = Cursor::new(data);
+    cursor.set_position(start as u64 + 6);
+    match cursor.read_le::<KeyValue>() {
+        Ok(value) => {
+            let resident_data = read_resident_data(&data[start..]);
+            Some(CarvedValue::new(offset, &value, resident_data.as_ref()))
+        }
+        Err(why) => {
+            log::warn!("unable to read the deleted value at offset 0x{:08x}: {why}", offset.0);
+            None
+        }
+    }
+}
@@ -1,22 +1,32 @@
 use std::{rc::Rc, cell::{RefCell, Ref}, slice::Iter};
 use nt_hive2::{Offset, KeyNode};
 
+use crate::carvedvalue::CarvedValue;
+
 pub (crate) struct RegTreeEntry {
     offset: Offset,
     nk: KeyNode,
     is_deleted: bool,
     children: Vec<Rc<RefCell<Self>>>,
+
+    /// deleted values which are still referenced by the values list of
+    /// this key. The values of deleted keys are part of their key node
+    deleted_values: Vec<CarvedValue>,
 }
 
 impl RegTreeEntry {
     pub fn new(offset: Offset, nk: KeyNode, is_deleted: bool) -> Self {
-        Self { offset, nk, is_deleted, children: Vec::new() }
+        Self { offset, nk, is_deleted, children: Vec::new(), deleted_values: Vec::new() }
     }
 
     pub fn add_child(&mut self, child: Rc<RefCell<Self>>) {
         self.children.push(child);
     }
 
+    pub fn add_deleted_value(&mut self, value: CarvedValue) {
+        self.deleted_values.push(value);
+    }
+
     pub fn offset(&self) -> &Offset {
         &self.offset
     }
@@ -29,7 +39,11 @@ impl RegTreeEntry {
         self.is_deleted
     }
 
-    pub fn children(&self) -> Children {
+    pub fn deleted_values(&self) -> &[CarvedValue] {
+        &self.deleted_values
+    }
+
+    pub fn children(&self) -> Children<'_> {
         Children{
             children: self.children.iter()
         }
@@ -0,0 +1,183 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use ipnet::{Ipv4Net, Ipv6Net};
+
+/// the class of an IP address, according to the IANA special-purpose address
+/// registries
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AddressClass {
+    Public,
+    Private,
+    Loopback,
+    LinkLocal,
+    Multicast,
+    Documentation,
+
+    /// addresses which must never appear on the internet, like `0.0.0.0/8`,
+    /// the shared address space or reserved ranges
+    Bogon,
+}
+
+const IPV4_CLASSES: &[(Ipv4Net, AddressClass)] = &[
+    (v4([0, 0, 0, 0], 8), AddressClass::Bogon),
+    (v4([10, 0, 0, 0], 8), AddressClass::Private),
+    (v4([100, 64, 0, 0], 10), AddressClass::Bogon),
+    (v4([127, 0, 0, 0], 8), AddressClass::Loopback),
+    (v4([169, 254, 0, 0], 16), AddressClass::LinkLocal),
+    (v4([172, 16, 0, 0], 12), AddressClass::Private),
+    (v4([192, 0, 0, 0], 24), AddressClass::Bogon),
+    (v4([192, 0, 2, 0], 24), AddressClass::Documentation),
+    (v4([192, 168, 0, 0], 16), AddressClass::Private),
+    (v4([198, 18, 0, 0], 15), AddressClass::Bogon),
+    (v4([198, 51, 100, 0], 24), AddressClass::Documentation),
+    (v4([203, 0, 113, 0], 24), AddressClass::Documentation),
+    (v4([224, 0, 0, 0], 4), AddressClass::Multicast),
+    (v4([240, 0, 0, 0], 4), AddressClass::Bogon),
+];
+
+/// IPv4-mapped addresses (`::ffff:0:0/96`) are not listed here, because they
+/// are classified by their IPv4 address
+#[rustfmt::skip]
+const IPV6_CLASSES: &[(Ipv6Net, AddressClass)] = &[
+    (v6([0, 0, 0, 0, 0, 0, 0, 0], 128), AddressClass::Bogon),
+    (v6([0, 0, 0, 0, 0, 0, 0, 1], 128), AddressClass::Loopback),
+    (v6([0x100, 0, 0, 0, 0, 0, 0, 0], 64), AddressClass::Bogon),
+    (v6([0x2001, 0x2, 0, 0, 0, 0, 0, 0], 48), AddressClass::Bogon),
+    (v6([0x2001, 0xdb8, 0, 0, 0, 0, 0, 0], 32), AddressClass::Documentation),
+    (v6([0x3fff, 0, 0, 0, 0, 0, 0, 0], 20), AddressClass::Documentation),
+    (v6([0xfc00, 0, 0, 0, 0, 0, 0, 0], 7), AddressClass::Private),
+    (v6([0xfe80, 0, 0, 0, 0, 0, 0, 0], 10), AddressClass::LinkLocal),
+    (v6([0xff00, 0, 0, 0, 0, 0, 0, 0], 8), AddressClass::Multicast),
+];
+
+const fn v4(octets: [u8; 4], prefix_len: u8) -> Ipv4Net {
+    let [a, b, c, d] = octets;
+    match Ipv4Net::new(Ipv4Addr::new(a, b, c, d), prefix_len) {
+        Ok(net) => net,
+        Err(_) => panic!("invalid prefix length"),
+    }
+}
+
+const fn v6(segments: [u16; 8], prefix_len: u8) -> Ipv6Net {
+    let [a, b, c, d, e, f, g, h] = segments;
+    match Ipv6Net::new(Ipv6Addr::new(a, b, c, d, e, f, g, h), prefix_len) {
+        Ok(net) => net,
+        Err(_) => panic!("invalid prefix length"),
+    }
+}
+
+impl From<&IpAddr> for AddressClass {
+    fn from(address: &IpAddr) -> Self {
+        match address {
+            IpAddr::V4(address) => Self::from(address),
+            IpAddr::V6(address) => match address.to_ipv4_mapped() {
+                Some(mapped) => Self::from(&mapped),
+                None => Self::from(address),
+            },
+        }
+    }
+}
+
+impl From<&Ipv4Addr> for AddressClass {
+    fn from(address: &Ipv4Addr) -> Self {
+        IPV4_CLASSES
+            .iter()
+            .find(|(net, _)| net.contains(address))
+            .map_or(Self::Public, |(_, class)| *class)
+    }
+}
+
+impl From<&Ipv6Addr> for AddressClass {
+    fn from(address: &Ipv6Addr) -> Self {
+        IPV6_CLASSES
+            .iter()
+            .find(|(net, _)| net.contains(address))
+            .map_or(Self::Public, |(_, class)| *class)
+    }
+}
+
+/// restricts the IP addresses to some classes
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub enum AddressScope {
+    #[default]
+    All,
+
+    /// only public addresses
+    PublicOnly,
+
+    /// only addresses of private networks
+    PrivateOnly,
+}
+
+impl AddressScope {
+    pub fn allows(&self, address: &IpAddr) -> bool {
+        match self {
+            Self::All => true,
+            Self::PublicOnly => AddressClass::from(address) == AddressClass::Public,
+            Self::PrivateOnly => AddressClass::from(address) == AddressClass::Private,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::IpAddr;
+
+    use super::{AddressClass, AddressScope};
+
+    #[test]
+    fn classify_addresses() {
+        for (address, class) in [
+            ("8.8.8.8", AddressClass::Public),
+            ("100.128.0.1", AddressClass::Public),
+            ("0.1.2.3", AddressClass::Bogon),
+            ("10.1.2.3", AddressClass::Private),
+            ("100.64.0.1", AddressClass::Bogon),
+            ("127.0.0.1", AddressClass::Loopback),
+            ("169.254.1.1", AddressClass::LinkLocal),
+            ("172.31.255.255", AddressClass::Private),
+            ("172.32.0.1", AddressClass::Public),
+            ("192.0.0.8", AddressClass::Bogon),
+            ("192.0.2.1", AddressClass::Documentation),
+            ("192.168.0.1", AddressClass::Private),
+            ("198.19.0.1", AddressClass::Bogon),
+            ("198.51.100.1", AddressClass::Documentation),
+            ("203.0.113.1", AddressClass::Documentation),
+            ("224.0.0.251", AddressClass::Multicast),
+            ("240.0.0.1", AddressClass::Bogon),
+            ("255.255.255.255", AddressClass::Bogon),
+            ("2a00:1450:4001:82a::200e", AddressClass::Public),
+            ("::", AddressClass::Bogon),
+            ("::1", AddressClass::Loopback),
+            ("100::1", AddressClass::Bogon),
+            ("2001:2::1", AddressClass::Bogon),
+            ("2001:db8::1", AddressClass::Documentation),
+            ("3fff::1", AddressClass::Documentation),
+            ("fd12:3456::1", AddressClass::Private),
+            ("fe80::1", AddressClass::LinkLocal),
+            ("ff02::1", AddressClass::Multicast),
+            ("::ffff:192.168.0.1", AddressClass::Private),
+            ("::ffff:8.8.8.8", AddressClass::Public),
+        ] {
+            let address: IpAddr = address.parse().unwrap();
+            assert_eq!(AddressClass::from(&address), class, "{address}");
+        }
+    }
+
+    #[test]
+    fn restrict_scope() {
+        let public: IpAddr = "8.8.8.8".parse().unwrap();
+        let private: IpAddr = "fd12:3456::1".parse().unwrap();
+        let loopback: IpAddr = "127.0.0.1".parse().unwrap();
+
+        assert!([public, private, loopback]
+            .iter()
+            .all(|address| AddressScope::All.allows(address)));
+        assert!(AddressScope::PublicOnly.allows(&public));
+        assert!(!AddressScope::PublicOnly.allows(&private));
+        assert!(!AddressScope::PublicOnly.allows(&loopback));
+        assert!(!AddressScope::PrivateOnly.allows(&public));
+        assert!(AddressScope::PrivateOnly.allows(&private));
+        assert!(!AddressScope::PrivateOnly.allows(&loopback));
+    }
+}
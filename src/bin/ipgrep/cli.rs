@@ -1,10 +1,27 @@
-use std::{net::Ipv4Addr, path::PathBuf, io::BufRead};
+use std::{
+    io::BufRead,
+    net::IpAddr,
+    path::{Path, PathBuf},
+};
 
 use clap::Parser;
-use dfir_toolkit::common::HasVerboseFlag;
+use dfir_toolkit::common::{HasVerboseFlag, Verbosity};
+use ipnet::IpNet;
 use log::LevelFilter;
 
-use crate::{ip_filter::IpFilter, format_ipv4};
+use crate::{
+    address_class::AddressScope,
+    defang::{Fanging, OutputFanging},
+    format_ip::{find_indicator_matches, find_indicators, format_ip},
+    indicator::{IndicatorType, IndicatorTypes},
+    indicator_counter::IndicatorCounter,
+    ip_filter::IpFilter,
+    ip_match::IpVersions,
+    json_match::{print_json_matches, JsonSource},
+    net_filter::NetFilter,
+    output_format::OutputFormat,
+    search_options::SearchOptions,
+};
 
 /// search for IP addresses in text files
 #[derive(Parser)]
@@ -43,14 +60,101 @@ pub(crate) struct Cli {
         value_delimiter = ',',
         ignore_case = true
     )]
-    pub(crate) ignore_ips: Vec<Ipv4Addr>,
+    pub(crate) ignore_ips: Vec<IpAddr>,
+
+    /// consider only IP addresses which are contained in any of the specified networks (in CIDR
+    /// notation, e.g. '10.0.0.0/8'). Values are delimited with comma
+    #[clap(
+        long("include-net"),
+        value_name = "CIDR",
+        use_value_delimiter = true,
+        value_delimiter = ','
+    )]
+    pub(crate) include_nets: Vec<IpNet>,
+
+    /// ignore IP addresses which are contained in any of the specified networks (in CIDR
+    /// notation, e.g. '10.12.0.0/16'). Values are delimited with comma
+    #[clap(
+        long("exclude-net"),
+        value_name = "CIDR",
+        use_value_delimiter = true,
+        value_delimiter = ','
+    )]
+    pub(crate) exclude_nets: Vec<IpNet>,
+
+    /// consider only public IP addresses, which are neither private, loopback, link-local,
+    /// multicast, documentation nor bogon addresses
+    #[clap(long("public-only"), conflicts_with = "private_only")]
+    pub(crate) public_only: bool,
+
+    /// consider only IP addresses of private networks ('10.0.0.0/8', '172.16.0.0/12',
+    /// '192.168.0.0/16' and 'fc00::/7'), e.g. to search for lateral movement
+    #[clap(long("private-only"))]
+    pub(crate) private_only: bool,
+
+    /// types of indicators to search for, instead of IP addresses only. Values are delimited
+    /// with comma
+    #[clap(
+        long("types"),
+        value_enum,
+        use_value_delimiter = true,
+        value_delimiter = ',',
+        ignore_case = true
+    )]
+    pub(crate) types: Vec<IndicatorType>,
+
+    /// search for IPv4 addresses only
+    #[clap(short('4'), long("v4-only"), conflicts_with = "v6_only")]
+    pub(crate) v4_only: bool,
+
+    /// search for IPv6 addresses only
+    #[clap(short('6'), long("v6-only"))]
+    pub(crate) v6_only: bool,
+
+    /// also find defanged IP addresses, like '10[.]0[.]0[.]5', '10(.)0(.)0(.)5', '10 dot 0 dot 0
+    /// dot 5' or '2001[:]db8[:][:]1'
+    #[clap(long("defanged"))]
+    pub(crate) defanged: bool,
+
+    /// also find defanged IP addresses (like '--defanged'), and print the lines with all
+    /// defanged indicators refanged (e.g. 'hxxp://10[.]0[.]0[.]5' becomes 'http://10.0.0.5')
+    #[clap(long("refang"), conflicts_with = "defang")]
+    pub(crate) refang: bool,
+
+    /// defang the IP addresses and URL schemes in the printed lines, for safe sharing (e.g.
+    /// 'http://10.0.0.5' becomes 'hxxp://10[.]0[.]0[.]5')
+    #[clap(long("defang"))]
+    pub(crate) defang: bool,
+
+    /// output format
+    #[clap(
+        short('F'),
+        long("format"),
+        value_enum,
+        default_value_t = OutputFormat::Plain,
+        conflicts_with_all = ["count", "count_by_file"]
+    )]
+    pub(crate) format: OutputFormat,
+
+    /// maximum length of the line in JSON output, which is trimmed around the indicator
+    #[clap(long("max-line-length"), default_value_t = 256)]
+    pub(crate) max_line_length: usize,
+
+    /// instead of printing the matching lines, print every distinct IP address with the number
+    /// of its occurrences. If '--types' is specified, the type of every indicator is printed, too
+    #[clap(long("count"), conflicts_with = "count_by_file")]
+    pub(crate) count: bool,
+
+    /// like '--count', but count the IP addresses of every input file separately
+    #[clap(long("count-by-file"))]
+    pub(crate) count_by_file: bool,
 
     /// highlight interesting content using colors
     #[clap(short('c'), long("colors"))]
     pub(crate) display_colors: bool,
 
     #[command(flatten)]
-    pub(crate) verbose: clap_verbosity_flag::Verbosity,
+    pub(crate) verbose: Verbosity,
 }
 
 impl HasVerboseFlag for Cli {
@@ -60,25 +164,135 @@ impl HasVerboseFlag for Cli {
 }
 
 impl Cli {
-    pub (crate) fn ipgrep<R: BufRead>(&self, mut reader: R) -> anyhow::Result<()> {
+    fn ip_versions(&self) -> IpVersions {
+        IpVersions {
+            ipv4: !self.v6_only,
+            ipv6: !self.v4_only,
+        }
+    }
+
+    fn indicator_types(&self) -> IndicatorTypes {
+        if self.types.is_empty() {
+            return IndicatorTypes {
+                ip_versions: Some(self.ip_versions()),
+                others: Vec::new(),
+            };
+        }
+        IndicatorTypes {
+            ip_versions: self
+                .types
+                .contains(&IndicatorType::Ip)
+                .then(|| self.ip_versions()),
+            others: self
+                .types
+                .iter()
+                .filter(|t| **t != IndicatorType::Ip)
+                .copied()
+                .collect(),
+        }
+    }
+
+    fn address_scope(&self) -> AddressScope {
+        if self.public_only {
+            AddressScope::PublicOnly
+        } else if self.private_only {
+            AddressScope::PrivateOnly
+        } else {
+            AddressScope::All
+        }
+    }
+
+    fn fanging(&self) -> Fanging {
+        let output = if self.refang {
+            OutputFanging::Refang
+        } else if self.defang {
+            OutputFanging::Defang
+        } else {
+            OutputFanging::AsIs
+        };
+        Fanging {
+            match_defanged: self.defanged || self.refang,
+            output,
+        }
+    }
+
+    /// searches for IP addresses in the lines of `reader`. If addresses are to be
+    /// counted, they are added to `counter` instead of printing the lines
+    pub(crate) fn ipgrep<R: BufRead>(
+        &self,
+        file: &Path,
+        mut reader: R,
+        counter: &mut IndicatorCounter,
+    ) -> anyhow::Result<()> {
         let mut line = String::new();
+        let mut line_number = 0;
+        let mut line_offset = 0;
+        let options = SearchOptions {
+            excludes: &self.exclude,
+            includes: &self.include,
+            ignore_ips: &self.ignore_ips,
+            nets: NetFilter {
+                include: &self.include_nets,
+                exclude: &self.exclude_nets,
+                scope: self.address_scope(),
+            },
+            types: self.indicator_types(),
+            fanging: self.fanging(),
+        };
         loop {
             line.clear();
             match reader.read_line(&mut line) {
                 Ok(0) => break,
                 Err(_) => break,
+                Ok(_) if self.count || self.count_by_file => {
+                    for indicator in find_indicators(&options, &line) {
+                        counter.add(indicator);
+                    }
+                }
+                Ok(_) if self.format == OutputFormat::Jsonl => {
+                    let matches = find_indicator_matches(&options, &line);
+                    let source = JsonSource {
+                        file: &file.to_string_lossy(),
+                        line_number: line_number + 1,
+                        line_offset,
+                        line: &line,
+                    };
+                    print_json_matches(
+                        &source,
+                        &matches,
+                        self.max_line_length,
+                        self.fanging().output == OutputFanging::Defang,
+                    )?;
+                }
                 Ok(_) => {
-                    if let Some(hline) = format_ipv4(
-                        &self.exclude[..],
-                        &self.include[..],
-                        &self.ignore_ips[..],
-                        &line,
-                    ) {
+                    if let Some(hline) = format_ip(&options, &line) {
                         print!("{hline}");
                     }
                 }
             }
+            line_number += 1;
+            line_offset += line.len() as u64;
         }
         Ok(())
     }
-}
\ No newline at end of file
+
+    /// prints the counted indicators, prefixed by the name of the input file
+    /// if they are counted by file, and resets the counter
+    pub(crate) fn print_counts(&self, file: Option<&Path>, counter: &mut IndicatorCounter) {
+        for (indicator, count) in counter.take_sorted() {
+            let mut columns = Vec::new();
+            if let Some(file) = file {
+                columns.push(file.display().to_string());
+            }
+            columns.push(count.to_string());
+            if !self.types.is_empty() {
+                columns.push(indicator.indicator_type().to_string());
+            }
+            columns.push(match self.fanging().output {
+                OutputFanging::Defang => indicator.defanged(),
+                _ => indicator.to_string(),
+            });
+            println!("{}", columns.join("\t"));
+        }
+    }
+}
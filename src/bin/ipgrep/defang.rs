@@ -0,0 +1,171 @@
+use std::ops::Range;
+
+use lazy_regex::regex;
+
+/// how defanged indicators (like `10[.]0[.]0[.]5` or `hxxp://`) are handled
+#[derive(Clone, Copy, Default)]
+pub struct Fanging {
+    /// also find IP addresses which have been defanged
+    pub match_defanged: bool,
+    pub output: OutputFanging,
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputFanging {
+    /// print the lines as they are
+    #[default]
+    AsIs,
+
+    /// print the lines with all defanged indicators refanged
+    Refang,
+
+    /// print the lines with all IP addresses and URL schemes defanged
+    Defang,
+}
+
+/// a line in which all defanged indicators have been refanged, which keeps
+/// track of the positions in the original line
+pub struct RefangedLine {
+    text: String,
+
+    /// the position in the original line of every byte of `text`, and the
+    /// length of the original line
+    offsets: Vec<usize>,
+}
+
+impl From<&str> for RefangedLine {
+    fn from(line: &str) -> Self {
+        let defanged_regex = regex!(
+            r"(?i)\[\.\]|\(\.\)|\{\.\}|\[dot\]|\(dot\)|\{dot\}| dot |\[:\]|\(:\)|\{:\}|\[://\]|\bhxxp"
+        );
+
+        let mut text = String::with_capacity(line.len());
+        let mut offsets = Vec::with_capacity(line.len() + 1);
+        let mut push = |text: &mut String, s: &str, offset: usize| {
+            text.push_str(s);
+            offsets.extend(std::iter::repeat(offset).take(s.len()));
+        };
+
+        let mut last_end = 0;
+        for m in defanged_regex.find_iter(line) {
+            let replacement = match &m.as_str().to_lowercase()[..] {
+                // ' dot ' is refanged only between digits, to keep ordinary
+                // text unchanged
+                " dot " => {
+                    let is_digit_before = line[..m.start()].ends_with(|c: char| c.is_ascii_digit());
+                    let is_digit_after = line[m.end()..].starts_with(|c: char| c.is_ascii_digit());
+                    if !(is_digit_before && is_digit_after) {
+                        continue;
+                    }
+                    ".".to_owned()
+                }
+                "[://]" => "://".to_owned(),
+                "[:]" | "(:)" | "{:}" => ":".to_owned(),
+                "hxxp" => m.as_str().replace('x', "t").replace('X', "T"),
+                _ => ".".to_owned(),
+            };
+            for (index, c) in line[last_end..m.start()].char_indices() {
+                push(&mut text, c.encode_utf8(&mut [0; 4]), last_end + index);
+            }
+            push(&mut text, &replacement, m.start());
+            last_end = m.end();
+        }
+        for (index, c) in line[last_end..].char_indices() {
+            push(&mut text, c.encode_utf8(&mut [0; 4]), last_end + index);
+        }
+        offsets.push(line.len());
+        Self { text, offsets }
+    }
+}
+
+impl RefangedLine {
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// the range in the original line which corresponds to a range of the
+    /// refanged text
+    pub fn original_range(&self, range: Range<usize>) -> Range<usize> {
+        self.offsets[range.start]..self.offsets[range.end]
+    }
+}
+
+/// defangs an IP address, so that `refang(defang_address(address))` is the
+/// original address
+pub fn defang_address(address: &str) -> String {
+    address.replace('.', "[.]").replace(':', "[:]")
+}
+
+/// defangs the schemes of HTTP URLs
+pub fn defang_urls(text: &str) -> String {
+    let scheme_regex = regex!(r"(?i)\b(h)(tt)(ps?://)");
+    scheme_regex
+        .replace_all(text, |captures: &lazy_regex::Captures| {
+            let tt = captures[2].replace('t', "x").replace('T', "X");
+            format!("{}{tt}{}", &captures[1], &captures[3])
+        })
+        .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{defang_address, defang_urls, RefangedLine};
+    use crate::ip_match::{find_ip_addresses, IpVersions};
+
+    fn refang(line: &str) -> String {
+        RefangedLine::from(line).text().to_owned()
+    }
+
+    #[test]
+    fn refang_common_styles() {
+        for (defanged, refanged) in [
+            ("10[.]0[.]0[.]5", "10.0.0.5"),
+            ("10(.)0(.)0(.)5", "10.0.0.5"),
+            ("10{.}0{.}0{.}5", "10.0.0.5"),
+            ("10[dot]0[DOT]0(dot)5", "10.0.0.5"),
+            ("10 dot 0 dot 0 dot 5", "10.0.0.5"),
+            ("2001[:]db8[:][:]1", "2001:db8::1"),
+            ("hxxp://1.2.3.4/index.html", "http://1.2.3.4/index.html"),
+            ("HXXPS[://]1[.]2[.]3[.]4", "HTTPS://1.2.3.4"),
+            // ordinary text remains unchanged
+            (
+                "connect dot com, see 1.2.3.4 dot",
+                "connect dot com, see 1.2.3.4 dot",
+            ),
+        ] {
+            assert_eq!(refang(defanged), refanged);
+        }
+    }
+
+    #[test]
+    fn map_to_original_line() {
+        let line = RefangedLine::from("from 10[.]0[.]0[.]5 to x");
+        assert_eq!(line.text(), "from 10.0.0.5 to x");
+        assert_eq!(line.original_range(5..13), 5..19);
+        assert_eq!(line.original_range(14..16), 20..22);
+        assert_eq!(line.original_range(17..18), 23..24);
+    }
+
+    #[test]
+    fn defang_is_reversible() {
+        for address in [
+            "10.0.0.5",
+            "2001:db8::1",
+            "fe80::1%eth0",
+            "::ffff:192.0.2.1",
+        ] {
+            let defanged = defang_address(address);
+            assert!(find_ip_addresses(&defanged, IpVersions::default()).is_empty());
+            assert_eq!(refang(&defanged), address);
+        }
+        for url in [
+            "http://10.0.0.5/",
+            "HTTPS://[::1]:443/",
+            "see https://example.com",
+        ] {
+            let defanged = defang_urls(url);
+            assert!(!defanged.to_lowercase().contains("http"));
+            assert_eq!(refang(&defanged), url);
+        }
+    }
+}
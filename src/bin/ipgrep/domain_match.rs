@@ -0,0 +1,101 @@
+use std::ops::Range;
+
+use lazy_regex::regex;
+
+/// the top level domains which are accepted. Top level domains which are more
+/// often seen as file extensions or in source code (like `.zip`, `.py`, `.sh`,
+/// `.md`, `.so`, `.app` or `.name`) are deliberately missing
+const TOP_LEVEL_DOMAINS: &[&str] = &[
+    // generic top level domains
+    "aero", "arpa", "asia", "biz", "blog", "buzz", "cam", "cfd", "click", "cloud", "club", "com",
+    "coop", "cyou", "dev", "download", "edu", "email", "fun", "gov", "icu", "info", "int", "jobs",
+    "link", "live", "loan", "mil", "mobi", "monster", "museum", "net", "news", "online", "onion",
+    "org", "page", "pro", "quest", "rest", "sbs", "shop", "site", "space", "store", "support",
+    "tech", "tel", "today", "top", "travel", "vip", "website", "win", "work", "xyz",
+    // country code top level domains
+    "ac", "ad", "ae", "af", "ag", "ai", "al", "am", "ao", "aq", "ar", "as", "at", "au", "aw", "ax",
+    "az", "ba", "bb", "bd", "be", "bf", "bg", "bh", "bi", "bj", "bm", "bn", "bo", "br", "bs", "bt",
+    "bw", "by", "bz", "ca", "cc", "cd", "cf", "cg", "ch", "ci", "ck", "cl", "cm", "cn", "co", "cr",
+    "cu", "cv", "cw", "cx", "cy", "cz", "de", "dj", "dk", "dm", "do", "dz", "ec", "ee", "eg", "er",
+    "es", "et", "eu", "fi", "fj", "fk", "fm", "fo", "fr", "ga", "gd", "ge", "gf", "gg", "gh", "gi",
+    "gl", "gm", "gn", "gp", "gq", "gr", "gs", "gt", "gu", "gw", "gy", "hk", "hm", "hn", "hr", "ht",
+    "hu", "id", "ie", "il", "im", "in", "io", "iq", "ir", "is", "it", "je", "jm", "jo", "jp", "ke",
+    "kg", "kh", "ki", "km", "kn", "kp", "kr", "kw", "ky", "kz", "la", "lb", "lc", "li", "lk", "lr",
+    "ls", "lt", "lu", "lv", "ly", "ma", "mc", "me", "mg", "mh", "mk", "ml", "mm", "mn", "mo", "mp",
+    "mq", "mr", "ms", "mt", "mu", "mv", "mw", "mx", "my", "mz", "na", "nc", "ne", "nf", "ng", "ni",
+    "nl", "no", "np", "nr", "nu", "nz", "om", "pa", "pe", "pf", "pg", "ph", "pk", "pl", "pm", "pn",
+    "pr", "ps", "pt", "pw", "qa", "re", "ro", "ru", "rw", "sa", "sb", "sc", "sd", "se", "sg", "si",
+    "sk", "sl", "sm", "sn", "sr", "ss", "st", "su", "sv", "sx", "sy", "sz", "tc", "td", "tf", "tg",
+    "th", "tj", "tk", "tl", "tm", "tn", "to", "tr", "tt", "tv", "tw", "tz", "ua", "ug", "uk", "us",
+    "uy", "uz", "va", "vc", "ve", "vg", "vi", "vn", "vu", "wf", "ws", "ye", "yt", "za", "zm", "zw",
+];
+
+/// finds all domain names in a line, ordered by their position
+pub fn find_domains(line: &str) -> Vec<Range<usize>> {
+    let domain_regex = regex!(r"(?i)\b(?:[a-z0-9](?:[a-z0-9-]{0,61}[a-z0-9])?\.)+[a-z]{2,63}\b");
+
+    domain_regex
+        .find_iter(line)
+        .filter(|m| has_known_top_level_domain(m.as_str()))
+        .map(|m| m.range())
+        .collect()
+}
+
+/// checks if `name` is a domain name with a known top level domain
+pub fn is_domain(name: &str) -> bool {
+    let domain_regex = regex!(r"(?i)^(?:[a-z0-9](?:[a-z0-9-]{0,61}[a-z0-9])?\.)+[a-z]{2,63}$");
+    domain_regex.is_match(name) && has_known_top_level_domain(name)
+}
+
+fn has_known_top_level_domain(name: &str) -> bool {
+    match name.rsplit_once('.') {
+        Some((_, tld)) => TOP_LEVEL_DOMAINS.contains(&&tld.to_lowercase()[..]),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find_domains, is_domain};
+
+    #[test]
+    fn find_domains_in_corpus() {
+        let table: &[(&str, &[&str])] = &[
+            ("example.com", &["example.com"]),
+            ("query for www.Example.CO.UK.", &["www.Example.CO.UK"]),
+            ("mail from user@mail.example.org\n", &["mail.example.org"]),
+            ("see https://xn--bcher-kva.de/path", &["xn--bcher-kva.de"]),
+            (
+                "c2 at evil-domain.xyz, backup.evil.top",
+                &["evil-domain.xyz", "backup.evil.top"],
+            ),
+            // negatives
+            ("installed archive.zip and setup.exe", &[]),
+            ("loading libc.so.6 from /usr/lib", &[]),
+            ("python main.py and README.md", &[]),
+            ("System.IO.File.ReadAllText", &[]),
+            ("version 1.2.3 at 10.0.0.1", &[]),
+            ("user.name = admin", &[]),
+            ("label_with.com_underscore", &[]),
+            ("localhost and intranet", &[]),
+        ];
+
+        for (line, expected) in table {
+            let found: Vec<_> = find_domains(line)
+                .into_iter()
+                .map(|range| &line[range])
+                .collect();
+            assert_eq!(found, expected.to_vec(), "line: {line}");
+        }
+    }
+
+    #[test]
+    fn check_domains() {
+        assert!(is_domain("example.com"));
+        assert!(is_domain("sub.EXAMPLE.de"));
+        assert!(!is_domain("example"));
+        assert!(!is_domain("example.com/"));
+        assert!(!is_domain("-example.com"));
+        assert!(!is_domain("10.0.0.1"));
+    }
+}
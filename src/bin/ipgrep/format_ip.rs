@@ -0,0 +1,507 @@
+
+use colored::{ColoredString, Colorize};
+
+use crate::{
+    defang::{defang_urls, OutputFanging, RefangedLine},
+    indicator::{self, find_other_indicators, remove_overlapping, Indicator, IndicatorMatch},
+    ip_filter::IpFilter,
+    ip_match::find_ip_addresses,
+    ip_with_properties::IpWithProperties,
+    search_options::SearchOptions,
+};
+
+pub fn format_ip(options: &SearchOptions, line: &str) -> Option<String> {
+    let fanging = options.fanging;
+
+    // defanged addresses are searched for in the refanged line
+    let refanged = fanging.match_defanged.then(|| RefangedLine::from(line));
+    let search_line = refanged.as_ref().map_or(line, |refanged| refanged.text());
+
+    let mut matches = select_matches(options, search_line)?;
+
+    // unless the line is to be refanged, the original line is printed
+    let line = match (&refanged, fanging.output) {
+        (Some(refanged), OutputFanging::AsIs) => {
+            for m in matches.iter_mut() {
+                m.range = refanged.original_range(m.range.clone());
+            }
+            line
+        }
+        _ => search_line,
+    };
+    let fang = |text: &str| match fanging.output {
+        OutputFanging::Defang => defang_urls(text),
+        _ => text.to_owned(),
+    };
+
+    let mut result = String::new();
+    let mut last_end = 0;
+
+    for m in matches {
+        // add the non-matching string between the last match and the current match to the result
+        result.push_str(&fang(&line[last_end..m.range.start]));
+        last_end = m.range.end;
+
+        let text = match fanging.output {
+            OutputFanging::Defang => {
+                indicator::defang(m.indicator.indicator_type(), &line[m.range])
+            }
+            _ => line[m.range].to_owned(),
+        };
+        let text = &text[..];
+        let highlighted_text: ColoredString = match m.indicator {
+            Indicator::Ip(address) if options.ignore_ips.contains(&address) => {
+                result.push_str(text);
+                continue;
+            }
+            Indicator::Ip(address) if IpWithProperties::from(address).is_global() => {
+                text.red().on_bright_yellow()
+            }
+            Indicator::Ip(_) => text.bright_purple(),
+            Indicator::Other(..) => text.bright_cyan(),
+        };
+        #[allow(clippy::unnecessary_to_owned)]
+        result.push_str(&(highlighted_text.to_string()));
+    }
+
+    if last_end < line.len() {
+        result.push_str(&fang(&line[last_end..]));
+    }
+
+    Some(result)
+}
+
+/// returns the indicators which would be highlighted by [`format_ip`], or
+/// nothing if the line would not be displayed
+pub fn find_indicators(options: &SearchOptions, line: &str) -> Vec<Indicator> {
+    find_indicator_matches(options, line)
+        .into_iter()
+        .map(|m| m.indicator)
+        .collect()
+}
+
+/// like [`find_indicators`], but returns the positions of the indicators in
+/// the line, too
+pub fn find_indicator_matches(options: &SearchOptions, line: &str) -> Vec<IndicatorMatch> {
+    let refanged = options
+        .fanging
+        .match_defanged
+        .then(|| RefangedLine::from(line));
+    let search_line = refanged.as_ref().map_or(line, |refanged| refanged.text());
+
+    select_matches(options, search_line)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|m| match m.indicator {
+            Indicator::Ip(address) => !options.ignore_ips.contains(&address),
+            Indicator::Other(..) => true,
+        })
+        .map(|m| match &refanged {
+            Some(refanged) => IndicatorMatch {
+                range: refanged.original_range(m.range),
+                indicator: m.indicator,
+            },
+            None => m,
+        })
+        .collect()
+}
+
+/// returns all matches in a line (including the ignored IP addresses), if the
+/// line shall be displayed. The filters only refer to IP addresses, so a line
+/// without IP addresses is only displayed if there are no includes
+fn select_matches(options: &SearchOptions, line: &str) -> Option<Vec<IndicatorMatch>> {
+    let SearchOptions {
+        excludes,
+        includes,
+        ignore_ips,
+        nets,
+        types,
+        ..
+    } = options;
+
+    // addresses outside of the specified networks are not considered as matches
+    let ip_matches: Vec<_> = match types.ip_versions {
+        Some(versions) => find_ip_addresses(line, versions)
+            .into_iter()
+            .filter(|m| nets.allows(&m.address))
+            .collect(),
+        None => Vec::new(),
+    };
+
+    if excludes.contains(&IpFilter::IPv4) && ip_matches.iter().any(|m| m.address.is_ipv4()) {
+        return None;
+    }
+
+    if excludes.contains(&IpFilter::IPv6) && ip_matches.iter().any(|m| m.address.is_ipv6()) {
+        return None;
+    }
+
+    // if no specific includes are specified, then this line
+    // shall be displayed if there is any ip address (unless this is to be excluded)
+    let mut display_this_line = includes.is_empty();
+
+    for m in ip_matches.iter() {
+        if ignore_ips.contains(&m.address) {
+            continue;
+        }
+        let ip_addr = IpWithProperties::from(m.address);
+
+        if excludes.contains(&IpFilter::Loopback) && ip_addr.is_loopback() {
+            return None;
+        }
+
+        if excludes.contains(&IpFilter::Private) && ip_addr.is_private() {
+            return None;
+        }
+
+        if excludes.contains(&IpFilter::Public) && ip_addr.is_global() {
+            return None;
+        }
+
+        display_this_line |= includes.contains(&IpFilter::IPv4) && m.address.is_ipv4();
+        display_this_line |= includes.contains(&IpFilter::IPv6) && m.address.is_ipv6();
+        display_this_line |= includes.contains(&IpFilter::Loopback) && ip_addr.is_loopback();
+        display_this_line |= includes.contains(&IpFilter::Private) && ip_addr.is_private();
+        display_this_line |= includes.contains(&IpFilter::Public) && ip_addr.is_global();
+    }
+
+    if !display_this_line {
+        return None;
+    }
+
+    let mut matches: Vec<IndicatorMatch> = ip_matches.into_iter().map(Into::into).collect();
+    matches.extend(find_other_indicators(line, &types.others));
+    if matches.is_empty() {
+        None
+    } else {
+        Some(remove_overlapping(matches))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::IpAddr;
+
+    use colored::Colorize;
+
+    use crate::{
+        defang::{Fanging, OutputFanging},
+        format_ip::{find_indicators, format_ip},
+        indicator::{Indicator, IndicatorType, IndicatorTypes},
+        ip_filter::IpFilter,
+        ip_match::IpVersions,
+        net_filter::NetFilter,
+        search_options::SearchOptions,
+    };
+
+    #[test]
+    fn test_loopback1() {
+        assert!(format_ip(
+            &SearchOptions {
+                includes: &[IpFilter::Loopback],
+                ..Default::default()
+            },
+            "127.0.0.1"
+        )
+        .is_some());
+    }
+    
+    #[test]
+    fn test_loopback2() {
+        let expected = format!("abc {}", "127.0.0.1".bright_purple());
+        assert_eq!(
+            format_ip(
+                &SearchOptions {
+                    includes: &[IpFilter::Loopback],
+                    ..Default::default()
+                },
+                "abc 127.0.0.1"
+            ),
+            Some(expected)
+        );
+    }
+    
+    #[test]
+    fn test_loopback3() {
+        let expected = format!("abc {} def", "127.0.0.1".bright_purple());
+        assert_eq!(
+            format_ip(
+                &SearchOptions {
+                    includes: &[IpFilter::Loopback],
+                    ..Default::default()
+                },
+                "abc 127.0.0.1 def"
+            ),
+            Some(expected)
+        );
+    }
+    
+    #[test]
+    fn test_loopback4() {
+        assert!(format_ip(
+            &SearchOptions {
+                includes: &[IpFilter::Loopback],
+                ..Default::default()
+            },
+            "hello, world"
+        )
+        .is_none());
+    }
+    
+    #[test]
+    fn test_loopback5() {
+        assert!(format_ip(
+            &SearchOptions {
+                includes: &[IpFilter::Loopback],
+                ..Default::default()
+            },
+            "192.168.0.1"
+        )
+        .is_none());
+    }
+    
+    #[test]
+    fn test_loopback6() {
+        let expected = format!(
+            "abc {} def {}",
+            "127.0.0.1".bright_purple(),
+            "192.168.0.1".bright_purple()
+        );
+        assert_eq!(
+            format_ip(
+                &SearchOptions {
+                    includes: &[IpFilter::Loopback],
+                    ..Default::default()
+                },
+                "abc 127.0.0.1 def 192.168.0.1"
+            ),
+            Some(expected)
+        );
+    }
+    
+    #[test]
+    fn test_loopback7() {
+        assert!(format_ip(
+            &SearchOptions {
+                excludes: &[IpFilter::Private],
+                includes: &[IpFilter::Loopback],
+                ..Default::default()
+            },
+            "abc 127.0.0.1 def 192.168.0.1"
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn test_ipv6() {
+        let expected = format!(
+            "abc [{}]:443 def {}",
+            "::1".bright_purple(),
+            "2a00:1450:4001:82a::200e".red().on_bright_yellow()
+        );
+        assert_eq!(
+            format_ip(
+                &SearchOptions::default(),
+                "abc [::1]:443 def 2a00:1450:4001:82a::200e"
+            ),
+            Some(expected)
+        );
+    }
+
+    #[test]
+    fn test_nets() {
+        let include = vec!["10.0.0.0/8".parse().unwrap()];
+        let exclude = vec!["10.12.0.0/16".parse().unwrap()];
+        let options = SearchOptions {
+            nets: NetFilter {
+                include: &include,
+                exclude: &exclude,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let expected = format!(
+            "abc 10.12.0.1 def {} 192.168.0.1",
+            "10.1.0.1".bright_purple()
+        );
+        assert_eq!(
+            format_ip(&options, "abc 10.12.0.1 def 10.1.0.1 192.168.0.1"),
+            Some(expected)
+        );
+        assert!(format_ip(&options, "abc 10.12.0.1 def 2001:db8::1").is_none());
+    }
+
+    #[test]
+    fn test_exclude_ipv6() {
+        assert!(format_ip(
+            &SearchOptions {
+                excludes: &[IpFilter::IPv6],
+                ..Default::default()
+            },
+            "abc 127.0.0.1 def fe80::1"
+        )
+        .is_none());
+        assert!(format_ip(
+            &SearchOptions {
+                excludes: &[IpFilter::IPv6],
+                types: IndicatorTypes {
+                    ip_versions: Some(IpVersions {
+                        ipv4: true,
+                        ipv6: false
+                    }),
+                    others: vec![]
+                },
+                ..Default::default()
+            },
+            "abc 127.0.0.1 def fe80::1"
+        )
+        .is_some());
+    }
+
+    #[test]
+    fn test_defanged() {
+        let line = "abc 10[.]0[.]0[.]5 def hxxp://8 dot 8 dot 8 dot 8/\n";
+        let format = |output| {
+            format_ip(
+                &SearchOptions {
+                    fanging: Fanging {
+                        match_defanged: true,
+                        output,
+                    },
+                    ..Default::default()
+                },
+                line
+            )
+        };
+
+        assert!(format_ip(&SearchOptions::default(), line).is_none());
+
+        let expected = format!(
+            "abc {} def hxxp://{}/\n",
+            "10[.]0[.]0[.]5".bright_purple(),
+            "8 dot 8 dot 8 dot 8".red().on_bright_yellow()
+        );
+        assert_eq!(format(OutputFanging::AsIs), Some(expected));
+
+        let expected = format!(
+            "abc {} def http://{}/\n",
+            "10.0.0.5".bright_purple(),
+            "8.8.8.8".red().on_bright_yellow()
+        );
+        assert_eq!(format(OutputFanging::Refang), Some(expected));
+
+        let expected = format!(
+            "abc {} def hxxp://{}/\n",
+            "10[.]0[.]0[.]5".bright_purple(),
+            "8[.]8[.]8[.]8".red().on_bright_yellow()
+        );
+        assert_eq!(format(OutputFanging::Defang), Some(expected));
+    }
+
+    #[test]
+    fn test_defang() {
+        let expected = format!(
+            "see hxxps://example.com and hxxp://{}:8080/ from [{}]",
+            "192[.]168[.]0[.]1".bright_purple(),
+            "2a00[:]1450[:]4001[:]82a[:][:]200e"
+                .red()
+                .on_bright_yellow()
+        );
+        assert_eq!(
+            format_ip(
+                &SearchOptions {
+                    fanging: Fanging {
+                        match_defanged: false,
+                        output: OutputFanging::Defang
+                    },
+                    ..Default::default()
+                },
+                "see https://example.com and http://192.168.0.1:8080/ from [2a00:1450:4001:82a::200e]"
+            ),
+            Some(expected)
+        );
+    }
+
+    #[test]
+    fn test_find_indicators() {
+        let find = |includes: &[IpFilter], line| {
+            find_indicators(
+                &SearchOptions {
+                    includes,
+                    ignore_ips: &["10.0.0.1".parse().unwrap()],
+                    fanging: Fanging {
+                        match_defanged: true,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                line
+            )
+        };
+        assert_eq!(
+            find(&[], "from 10.0.0.1 to 8[.]8[.]8[.]8 and 8.8.8.8"),
+            vec![
+                Indicator::Ip("8.8.8.8".parse::<IpAddr>().unwrap()),
+                Indicator::Ip("8.8.8.8".parse().unwrap())
+            ]
+        );
+        assert!(find(&[IpFilter::Loopback], "from 10.0.0.1 to 8.8.8.8").is_empty());
+    }
+
+    #[test]
+    fn test_other_indicators() {
+        let types = IndicatorTypes {
+            ip_versions: None,
+            others: vec![IndicatorType::Url, IndicatorType::Md5],
+        };
+        let line = "fetched http://evil.xyz/a.exe (d41d8cd98f00b204e9800998ecf8427e) from 10.0.0.1";
+
+        let expected = format!(
+            "fetched {} ({}) from 10.0.0.1",
+            "http://evil.xyz/a.exe".bright_cyan(),
+            "d41d8cd98f00b204e9800998ecf8427e".bright_cyan()
+        );
+        assert_eq!(
+            format_ip(
+                &SearchOptions {
+                    types: types.clone(),
+                    ..Default::default()
+                },
+                line
+            ),
+            Some(expected)
+        );
+
+        let expected = format!(
+            "fetched {} ({}) from 10.0.0.1",
+            "hxxp://evil[.]xyz/a.exe".bright_cyan(),
+            "d41d8cd98f00b204e9800998ecf8427e".bright_cyan()
+        );
+        assert_eq!(
+            format_ip(
+                &SearchOptions {
+                    types: types.clone(),
+                    fanging: Fanging {
+                        match_defanged: false,
+                        output: OutputFanging::Defang
+                    },
+                    ..Default::default()
+                },
+                line
+            ),
+            Some(expected)
+        );
+
+        // filters refer to IP addresses only
+        assert!(format_ip(
+            &SearchOptions {
+                includes: &[IpFilter::Public],
+                types,
+                ..Default::default()
+            },
+            line
+        )
+        .is_none());
+    }
+}
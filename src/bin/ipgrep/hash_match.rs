@@ -0,0 +1,81 @@
+use std::ops::Range;
+
+use lazy_regex::regex;
+
+use crate::indicator::IndicatorType;
+
+/// finds all MD5, SHA1 and SHA256 hashes in a line, ordered by their
+/// position. A hash must consist of lowercase or uppercase hex digits only,
+/// so that mixed-case identifiers, numbers and padding (like `0000...`) are
+/// not mistaken as hashes
+pub fn find_hashes(line: &str) -> Vec<(Range<usize>, IndicatorType)> {
+    let hash_regex = regex!(r"\b[0-9A-Fa-f]{32,64}\b");
+
+    let mut matches = Vec::new();
+    for m in hash_regex.find_iter(line) {
+        let hash_type = match m.len() {
+            32 => IndicatorType::Md5,
+            40 => IndicatorType::Sha1,
+            64 => IndicatorType::Sha256,
+            _ => continue,
+        };
+
+        let hash = m.as_str();
+        let has_lowercase = hash.bytes().any(|b| b.is_ascii_lowercase());
+        let has_uppercase = hash.bytes().any(|b| b.is_ascii_uppercase());
+        if has_lowercase && has_uppercase
+            || hash.bytes().all(|b| b.is_ascii_digit())
+            || hash.bytes().all(|b| b == hash.as_bytes()[0])
+        {
+            continue;
+        }
+
+        matches.push((m.range(), hash_type));
+    }
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::find_hashes;
+    use crate::indicator::IndicatorType;
+
+    #[test]
+    fn find_hashes_in_corpus() {
+        let md5 = "d41d8cd98f00b204e9800998ecf8427e";
+        let sha1 = "DA39A3EE5E6B4B0D3255BFEF95601890AFD80709";
+        let sha256 = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+        let table: &[(&str, &[(&str, IndicatorType)])] = &[
+            (md5, &[(md5, IndicatorType::Md5)]),
+            (
+                &format!("MD5 ({md5}) SHA1: {sha1}\n"),
+                &[(md5, IndicatorType::Md5), (sha1, IndicatorType::Sha1)],
+            ),
+            (
+                &format!("{sha256}  /bin/ls"),
+                &[(sha256, IndicatorType::Sha256)],
+            ),
+            // negatives
+            ("too short d41d8cd98f00b204e9800998ecf8427", &[]),
+            ("too long d41d8cd98f00b204e9800998ecf8427e0", &[]),
+            (
+                "sha512 cf83e1357eefb8bdf1542850d66d8007d620e4050b5715dc83f4a921d36ce9ce47d0d13c5d85f2b0ff8318d2877eec2f63b931bd47417a81a538327af927da3e",
+                &[],
+            ),
+            ("mixed case D41d8cd98f00b204e9800998ecf8427e", &[]),
+            ("number 12345678901234567890123456789012", &[]),
+            ("padding 0000000000000000000000000000000000000000", &[]),
+            ("part of a word xd41d8cd98f00b204e9800998ecf8427e", &[]),
+            ("guid {3F2504E0-4F89-11D3-9A0C-0305E82C3301}", &[]),
+        ];
+
+        for (line, expected) in table {
+            let found: Vec<_> = find_hashes(line)
+                .into_iter()
+                .map(|(range, hash_type)| (&line[range], hash_type))
+                .collect();
+            assert_eq!(found, expected.to_vec(), "line: {line}");
+        }
+    }
+}
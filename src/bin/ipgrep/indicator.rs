@@ -0,0 +1,247 @@
+use std::{fmt::Display, net::IpAddr, ops::Range};
+
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::{
+    defang::{defang_address, defang_urls},
+    domain_match::find_domains,
+    hash_match::find_hashes,
+    ip_match::{IpMatch, IpVersions},
+    url_match::find_urls,
+};
+
+#[derive(ValueEnum, Serialize, Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum IndicatorType {
+    Ip,
+    Domain,
+    Url,
+    Md5,
+    Sha1,
+    Sha256,
+}
+
+impl Display for IndicatorType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.to_possible_value() {
+            Some(value) => f.write_str(value.get_name()),
+            None => Ok(()),
+        }
+    }
+}
+
+/// the types of indicators which are searched for
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IndicatorTypes {
+    /// the IP versions which are searched for, if IP addresses are searched for at all
+    pub ip_versions: Option<IpVersions>,
+
+    /// all other types of indicators which are searched for
+    pub others: Vec<IndicatorType>,
+}
+
+impl Default for IndicatorTypes {
+    fn default() -> Self {
+        Self {
+            ip_versions: Some(IpVersions::default()),
+            others: Vec::new(),
+        }
+    }
+}
+
+/// an indicator which has been found in a line. Domain names and hashes are
+/// normalized to lowercase, so that they can be counted
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Indicator {
+    Ip(IpAddr),
+    Other(IndicatorType, String),
+}
+
+impl Indicator {
+    pub fn indicator_type(&self) -> IndicatorType {
+        match self {
+            Self::Ip(_) => IndicatorType::Ip,
+            Self::Other(indicator_type, _) => *indicator_type,
+        }
+    }
+
+    /// returns the indicator in a form which can be shared safely
+    pub fn defanged(&self) -> String {
+        match self {
+            Self::Ip(address) => defang_address(&address.to_string()),
+            Self::Other(indicator_type, text) => defang(*indicator_type, text),
+        }
+    }
+}
+
+impl Display for Indicator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Ip(address) => address.fmt(f),
+            Self::Other(_, text) => f.write_str(text),
+        }
+    }
+}
+
+/// defangs the text of an indicator, which has been found in a line
+pub fn defang(indicator_type: IndicatorType, text: &str) -> String {
+    match indicator_type {
+        IndicatorType::Ip => defang_address(text),
+        IndicatorType::Domain => text.replace('.', "[.]"),
+        IndicatorType::Url => {
+            // only the host is defanged, because the path might contain dots
+            // which are not part of the host, like 'index.html'
+            let (scheme, rest) = text.split_once("://").unwrap_or(("", text));
+            let host_end = rest.find(['/', '?', '#']).unwrap_or(rest.len());
+            defang_urls(&format!(
+                "{scheme}://{}{}",
+                rest[..host_end].replace('.', "[.]"),
+                &rest[host_end..]
+            ))
+        }
+        IndicatorType::Md5 | IndicatorType::Sha1 | IndicatorType::Sha256 => text.to_owned(),
+    }
+}
+
+/// an indicator which has been found in a line
+#[derive(Debug, PartialEq, Eq)]
+pub struct IndicatorMatch {
+    pub range: Range<usize>,
+    pub indicator: Indicator,
+}
+
+impl From<IpMatch> for IndicatorMatch {
+    fn from(m: IpMatch) -> Self {
+        Self {
+            range: m.range,
+            indicator: Indicator::Ip(m.address),
+        }
+    }
+}
+
+/// finds all indicators of the specified types (except IP addresses) in a line
+pub fn find_other_indicators(line: &str, types: &[IndicatorType]) -> Vec<IndicatorMatch> {
+    let mut matches = Vec::new();
+    let mut add = |range: Range<usize>, indicator_type| {
+        let text = match indicator_type {
+            IndicatorType::Url => line[range.clone()].to_owned(),
+            _ => line[range.clone()].to_lowercase(),
+        };
+        matches.push(IndicatorMatch {
+            range,
+            indicator: Indicator::Other(indicator_type, text),
+        });
+    };
+
+    if types.contains(&IndicatorType::Url) {
+        find_urls(line)
+            .into_iter()
+            .for_each(|range| add(range, IndicatorType::Url));
+    }
+    if types.contains(&IndicatorType::Domain) {
+        find_domains(line)
+            .into_iter()
+            .for_each(|range| add(range, IndicatorType::Domain));
+    }
+    for (range, hash_type) in find_hashes(line) {
+        if types.contains(&hash_type) {
+            add(range, hash_type);
+        }
+    }
+    matches
+}
+
+/// orders the matches by their position. If matches overlap, only the longest
+/// one is kept, so that e.g. an IP address or a domain name which is part of
+/// a URL is only returned as part of the URL
+pub fn remove_overlapping(mut matches: Vec<IndicatorMatch>) -> Vec<IndicatorMatch> {
+    matches.sort_by_key(|m| (m.range.start, usize::MAX - m.range.len()));
+
+    let mut result: Vec<IndicatorMatch> = Vec::with_capacity(matches.len());
+    for m in matches {
+        match result.last() {
+            Some(last) if last.range.end > m.range.start => {
+                if m.range.len() > last.range.len() {
+                    result.pop();
+                    result.push(m);
+                }
+            }
+            _ => result.push(m),
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{defang, find_other_indicators, remove_overlapping, Indicator, IndicatorType};
+    use crate::{defang::RefangedLine, ip_match::find_ip_addresses, ip_match::IpVersions};
+
+    #[test]
+    fn find_overlapping_indicators() {
+        let line = "GET http://www.example.com/x from 10.0.0.1, see Example.com";
+        let all_types = [IndicatorType::Domain, IndicatorType::Url];
+
+        let mut matches: Vec<_> = find_ip_addresses(line, IpVersions::default())
+            .into_iter()
+            .map(Into::into)
+            .collect();
+        matches.extend(find_other_indicators(line, &all_types));
+        let found: Vec<_> = remove_overlapping(matches)
+            .into_iter()
+            .map(|m| (&line[m.range], m.indicator))
+            .collect();
+
+        assert_eq!(
+            found,
+            vec![
+                (
+                    "http://www.example.com/x",
+                    Indicator::Other(IndicatorType::Url, "http://www.example.com/x".into())
+                ),
+                ("10.0.0.1", Indicator::Ip("10.0.0.1".parse().unwrap())),
+                (
+                    "Example.com",
+                    Indicator::Other(IndicatorType::Domain, "example.com".into())
+                ),
+            ]
+        );
+
+        // without URLs, the domain name in the URL is found
+        assert_eq!(
+            find_other_indicators(line, &[IndicatorType::Domain]).len(),
+            2
+        );
+    }
+
+    #[test]
+    fn defang_is_reversible() {
+        for (indicator_type, text, expected) in [
+            (
+                IndicatorType::Domain,
+                "www.example.com",
+                "www[.]example[.]com",
+            ),
+            (
+                IndicatorType::Url,
+                "https://www.example.com:443/index.html?a=b.c",
+                "hxxps://www[.]example[.]com:443/index.html?a=b.c",
+            ),
+            (
+                IndicatorType::Url,
+                "http://10.0.0.1/",
+                "hxxp://10[.]0[.]0[.]1/",
+            ),
+            (
+                IndicatorType::Md5,
+                "d41d8cd98f00b204e9800998ecf8427e",
+                "d41d8cd98f00b204e9800998ecf8427e",
+            ),
+        ] {
+            let defanged = defang(indicator_type, text);
+            assert_eq!(defanged, expected);
+            assert_eq!(RefangedLine::from(&defanged[..]).text(), text);
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use std::collections::HashMap;
+
+use crate::indicator::Indicator;
+
+/// counts the occurrences of every distinct indicator. Only the distinct
+/// indicators are stored, so that the memory usage does not depend on the
+/// size of the input
+#[derive(Default)]
+pub struct IndicatorCounter {
+    counts: HashMap<Indicator, u64>,
+}
+
+impl IndicatorCounter {
+    pub fn add(&mut self, indicator: Indicator) {
+        *self.counts.entry(indicator).or_default() += 1;
+    }
+
+    /// returns the counted indicators, ordered by their number of occurrences
+    /// (descending) and by indicator, and resets the counter
+    pub fn take_sorted(&mut self) -> Vec<(Indicator, u64)> {
+        let mut counts: Vec<_> = self.counts.drain().collect();
+        counts.sort_by(|(lhs_indicator, lhs_count), (rhs_indicator, rhs_count)| {
+            rhs_count
+                .cmp(lhs_count)
+                .then_with(|| lhs_indicator.cmp(rhs_indicator))
+        });
+        counts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IndicatorCounter;
+    use crate::indicator::{Indicator, IndicatorType};
+
+    fn ip(address: &str) -> Indicator {
+        Indicator::Ip(address.parse().unwrap())
+    }
+
+    #[test]
+    fn count_indicators() {
+        let domain = Indicator::Other(IndicatorType::Domain, "example.com".into());
+        let mut counter = IndicatorCounter::default();
+        for indicator in [
+            ip("10.0.0.2"),
+            ip("8.8.8.8"),
+            domain.clone(),
+            ip("10.0.0.1"),
+            ip("8.8.8.8"),
+            ip("::1"),
+            ip("10.0.0.2"),
+            ip("8.8.8.8"),
+            domain.clone(),
+        ] {
+            counter.add(indicator);
+        }
+
+        let expected = vec![
+            (ip("8.8.8.8"), 3),
+            (ip("10.0.0.2"), 2),
+            (domain, 2),
+            (ip("10.0.0.1"), 1),
+            (ip("::1"), 1),
+        ];
+        assert_eq!(counter.take_sorted(), expected);
+        assert!(counter.take_sorted().is_empty());
+    }
+}
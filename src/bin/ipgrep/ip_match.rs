@@ -0,0 +1,244 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::ops::Range;
+use std::str::FromStr;
+
+use lazy_regex::regex;
+
+/// the IP versions which are searched for
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IpVersions {
+    pub ipv4: bool,
+    pub ipv6: bool,
+}
+
+impl Default for IpVersions {
+    fn default() -> Self {
+        Self {
+            ipv4: true,
+            ipv6: true,
+        }
+    }
+}
+
+/// an IP address which has been found in a line
+#[derive(Debug, PartialEq, Eq)]
+pub struct IpMatch {
+    /// the position of the address in the line, including its zone index
+    pub range: Range<usize>,
+    pub address: IpAddr,
+}
+
+/// finds all IP addresses of the specified versions in a line, ordered by
+/// their position. An IPv4 address which is embedded in an IPv6 address is
+/// not returned separately
+pub fn find_ip_addresses(line: &str, versions: IpVersions) -> Vec<IpMatch> {
+    let ipv6_matches = if versions.ipv6 {
+        find_ipv6_addresses(line)
+    } else {
+        Vec::new()
+    };
+    let mut matches = if versions.ipv4 {
+        find_ipv4_addresses(line)
+            .into_iter()
+            .filter(|ipv4| {
+                !ipv6_matches.iter().any(|ipv6| {
+                    ipv4.range.start < ipv6.range.end && ipv6.range.start < ipv4.range.end
+                })
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
+    matches.extend(ipv6_matches);
+    matches.sort_by_key(|m| m.range.start);
+    matches
+}
+
+fn find_ipv4_addresses(line: &str) -> Vec<IpMatch> {
+    let ipv4_regex = regex!(
+        r"\b(?:(?:25[0-5]|2[0-4][0-9]|[01]?[0-9][0-9]?)\.){3}(?:25[0-5]|2[0-4][0-9]|[01]?[0-9][0-9]?)\b"
+    );
+
+    let mut matches = Vec::new();
+    for m in ipv4_regex.find_iter(line) {
+        // Sometimes there are values like OIDs, which look like IPv4 addresses.
+        // We can detect those by doing a look-ahead resp. look-behind. But because
+        // are not supported by the regex create, we do this by hand
+        if m.start() > 0 && line.as_bytes()[m.start() - 1] == b'.'
+            || m.end() < line.len() - 1 && line.as_bytes()[m.end()] == b'.'
+        {
+            continue;
+        }
+
+        match Ipv4Addr::from_str(m.as_str()) {
+            Ok(addr) => matches.push(IpMatch {
+                range: m.range(),
+                address: IpAddr::V4(addr),
+            }),
+            Err(_) => {
+                log::warn!("invalid IP address: {}, don't highlighting it", m.as_str());
+            }
+        }
+    }
+    matches
+}
+
+/// IPv6 addresses are found by searching for sequences of hex digits, colons
+/// and dots (for an embedded IPv4 address), followed by an optional zone
+/// index, which can be parsed as IPv6 address. Because lookarounds are not
+/// supported by the regex crate, the boundaries are checked by hand
+fn find_ipv6_addresses(line: &str) -> Vec<IpMatch> {
+    let candidate_regex = regex!(r"[0-9A-Fa-f:.]*:[0-9A-Fa-f:.]*(?:%[0-9A-Za-z_\-]+)?");
+
+    let mut matches = Vec::new();
+    for m in candidate_regex.find_iter(line) {
+        let mut range = m.range();
+
+        // a trailing dot or colon is usually punctuation, e.g. at the end of
+        // a sentence or before a message
+        let candidate = &line[range.clone()];
+        if candidate.ends_with('.') || candidate.ends_with(':') && !candidate.ends_with("::") {
+            range.end -= 1;
+        }
+
+        let candidate = &line[range.clone()];
+        let address = match candidate.split_once('%') {
+            Some((address, _zone_index)) => address,
+            None => candidate,
+        };
+        let Ok(address) = Ipv6Addr::from_str(address) else {
+            continue;
+        };
+
+        let is_bracketed = range.start > 0
+            && line.as_bytes()[range.start - 1] == b'['
+            && line.as_bytes().get(range.end) == Some(&b']');
+        if !is_bracketed && !is_ipv6_boundary(line, range.clone()) {
+            continue;
+        }
+
+        // '::' is very common in source code and log messages, so it is only
+        // accepted in brackets, like in '[::]:80'
+        if address.is_unspecified() && candidate == "::" && !is_bracketed {
+            continue;
+        }
+
+        // MAC addresses and hex dumps consist of groups of two hex digits
+        if !candidate.contains("::")
+            && address.to_ipv4_mapped().is_none()
+            && candidate.split(':').all(|group| group.len() == 2)
+        {
+            continue;
+        }
+
+        matches.push(IpMatch {
+            range,
+            address: IpAddr::V6(address),
+        });
+    }
+    matches
+}
+
+/// an IPv6 address must not be part of a word, e.g. a hex string or an
+/// identifier
+fn is_ipv6_boundary(line: &str, range: Range<usize>) -> bool {
+    let is_word_character = |c: char| c.is_alphanumeric() || c == '_';
+    let before = line[..range.start].chars().next_back();
+    let after = line[range.end..].chars().next();
+    !before.is_some_and(is_word_character) && !after.is_some_and(is_word_character)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::IpAddr;
+
+    use super::{find_ip_addresses, IpVersions};
+
+    fn find(line: &str, versions: IpVersions) -> Vec<(&str, IpAddr)> {
+        find_ip_addresses(line, versions)
+            .into_iter()
+            .map(|m| (&line[m.range], m.address))
+            .collect()
+    }
+
+    #[test]
+    fn find_addresses() {
+        // each line is followed by the matched text and the addresses which
+        // should be found in it
+        let table: &[(&str, &[(&str, &str)])] = &[
+            ("abc 127.0.0.1 def", &[("127.0.0.1", "127.0.0.1")]),
+            ("2001:db8::1", &[("2001:db8::1", "2001:db8::1")]),
+            (
+                "connect from 2001:0db8:85a3:0000:0000:8a2e:0370:7334\n",
+                &[(
+                    "2001:0db8:85a3:0000:0000:8a2e:0370:7334",
+                    "2001:db8:85a3::8a2e:370:7334",
+                )],
+            ),
+            ("ping ::1.", &[("::1", "::1")]),
+            ("listen on fe80::1:", &[("fe80::1", "fe80::1")]),
+            ("prefix fe80::/10", &[("fe80::", "fe80::")]),
+            ("link local fe80::1%eth0 up", &[("fe80::1%eth0", "fe80::1")]),
+            (
+                "mapped ::ffff:192.0.2.128",
+                &[("::ffff:192.0.2.128", "::ffff:192.0.2.128")],
+            ),
+            (
+                "https://[2001:db8::1]:443/index.html",
+                &[("2001:db8::1", "2001:db8::1")],
+            ),
+            ("listening on [::]:80", &[("::", "::")]),
+            (
+                "from 10.0.0.1 to 2001:db8::2",
+                &[("10.0.0.1", "10.0.0.1"), ("2001:db8::2", "2001:db8::2")],
+            ),
+            ("10.0.0.1:8080", &[("10.0.0.1", "10.0.0.1")]),
+            // false positives
+            ("mac 00:1a:2b:3c:4d:5e", &[]),
+            ("eui 00:1a:2b:ff:fe:3c:4d:5e", &[]),
+            ("at 2024-03-01 12:34:56.789", &[]),
+            ("time 12:34:56", &[]),
+            ("std::vec::Vec", &[]),
+            ("Foo::bar()", &[]),
+            ("deadbeef::cafez", &[]),
+            ("x2001:db8::1", &[]),
+            ("scope :: separator", &[]),
+            ("oid 1.3.6.1.4.1.311", &[]),
+        ];
+
+        for (line, expected) in table {
+            let expected: Vec<_> = expected
+                .iter()
+                .map(|(text, address)| (*text, address.parse::<IpAddr>().unwrap()))
+                .collect();
+            assert_eq!(find(line, IpVersions::default()), expected, "line: {line}");
+        }
+    }
+
+    #[test]
+    fn find_selected_versions() {
+        let line = "from 10.0.0.1 to 2001:db8::2";
+        let v4_only = IpVersions {
+            ipv4: true,
+            ipv6: false,
+        };
+        let v6_only = IpVersions {
+            ipv4: false,
+            ipv6: true,
+        };
+        assert_eq!(
+            find(line, v4_only),
+            vec![("10.0.0.1", "10.0.0.1".parse().unwrap())]
+        );
+        assert_eq!(
+            find(line, v6_only),
+            vec![("2001:db8::2", "2001:db8::2".parse().unwrap())]
+        );
+
+        // an embedded IPv4 address is part of the IPv6 address
+        assert_eq!(
+            find("::ffff:192.0.2.128", v6_only),
+            vec![("::ffff:192.0.2.128", "::ffff:192.0.2.128".parse().unwrap())]
+        );
+    }
+}
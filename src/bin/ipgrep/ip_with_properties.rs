@@ -0,0 +1,40 @@
+use std::net::IpAddr;
+
+use crate::{ipv4_with_properties::Ipv4WithProperties, ipv6_with_properties::Ipv6WithProperties};
+
+pub enum IpWithProperties {
+    V4(Ipv4WithProperties),
+    V6(Ipv6WithProperties),
+}
+
+impl From<IpAddr> for IpWithProperties {
+    fn from(value: IpAddr) -> Self {
+        match value {
+            IpAddr::V4(address) => Self::V4(address.into()),
+            IpAddr::V6(address) => Self::V6(address.into()),
+        }
+    }
+}
+
+impl IpWithProperties {
+    pub fn is_global(&self) -> bool {
+        match self {
+            Self::V4(address) => address.is_global(),
+            Self::V6(address) => address.is_global(),
+        }
+    }
+
+    pub fn is_loopback(&self) -> bool {
+        match self {
+            Self::V4(address) => address.is_loopback(),
+            Self::V6(address) => address.is_loopback(),
+        }
+    }
+
+    pub fn is_private(&self) -> bool {
+        match self {
+            Self::V4(address) => address.is_private(),
+            Self::V6(address) => address.is_private(),
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use std::net::Ipv6Addr;
+
+use crate::ipv4_with_properties::Ipv4WithProperties;
+
+pub struct Ipv6WithProperties {
+    wrapped_address: Ipv6Addr,
+}
+
+impl From<Ipv6Addr> for Ipv6WithProperties {
+    fn from(value: Ipv6Addr) -> Self {
+        Self {
+            wrapped_address: value,
+        }
+    }
+}
+
+impl AsRef<Ipv6Addr> for Ipv6WithProperties {
+    fn as_ref(&self) -> &Ipv6Addr {
+        &self.wrapped_address
+    }
+}
+
+impl Ipv6WithProperties {
+    /// Returns [`true`] if the address appears to be globally reachable. The properties of an
+    /// IPv4-mapped address (`::ffff:0:0/96`) are those of the mapped IPv4 address.
+    ///
+    /// For a complete overview see the IANA IPv6 Special-Purpose Address Registry.
+    #[must_use]
+    pub fn is_global(&self) -> bool {
+        if let Some(ipv4) = self.mapped_ipv4() {
+            return ipv4.is_global();
+        }
+        !(self.wrapped_address.is_unspecified()
+            || self.wrapped_address.is_loopback()
+            // discard-only address block (`100::/64`)
+            || self.segments()[..4] == [0x100, 0, 0, 0]
+            || self.is_documentation()
+            || self.is_benchmarking()
+            || self.is_unique_local()
+            || self.is_unicast_link_local()
+            // multicast addresses whose scope is not global
+            || (self.wrapped_address.is_multicast() && self.segments()[0] & 0x000f != 0xe))
+    }
+
+    /// Returns [`true`] if this is a unique local address (`fc00::/7`), which is the IPv6
+    /// counterpart of the private IPv4 address ranges, as defined in [IETF RFC 4193].
+    ///
+    /// [IETF RFC 4193]: https://tools.ietf.org/html/rfc4193
+    #[must_use]
+    pub fn is_unique_local(&self) -> bool {
+        (self.segments()[0] & 0xfe00) == 0xfc00
+    }
+
+    /// Returns [`true`] if this is a unicast address with link-local scope (`fe80::/10`).
+    #[must_use]
+    pub fn is_unicast_link_local(&self) -> bool {
+        (self.segments()[0] & 0xffc0) == 0xfe80
+    }
+
+    /// Returns [`true`] if this address is part of the `2001:db8::/32` range, which is
+    /// reserved for documentation in [IETF RFC 3849].
+    ///
+    /// [IETF RFC 3849]: https://tools.ietf.org/html/rfc3849
+    #[must_use]
+    pub fn is_documentation(&self) -> bool {
+        self.segments()[..2] == [0x2001, 0xdb8]
+    }
+
+    /// Returns [`true`] if this address is part of the `2001:2::/48` range, which is reserved
+    /// for network devices benchmarking in [IETF RFC 5180].
+    ///
+    /// [IETF RFC 5180]: https://tools.ietf.org/html/rfc5180
+    #[must_use]
+    pub fn is_benchmarking(&self) -> bool {
+        self.segments()[..3] == [0x2001, 0x2, 0]
+    }
+
+    #[must_use]
+    pub fn is_loopback(&self) -> bool {
+        match self.mapped_ipv4() {
+            Some(ipv4) => ipv4.is_loopback(),
+            None => self.wrapped_address.is_loopback(),
+        }
+    }
+
+    #[must_use]
+    pub fn is_private(&self) -> bool {
+        match self.mapped_ipv4() {
+            Some(ipv4) => ipv4.is_private(),
+            None => self.is_unique_local(),
+        }
+    }
+
+    fn mapped_ipv4(&self) -> Option<Ipv4WithProperties> {
+        self.wrapped_address
+            .to_ipv4_mapped()
+            .map(Ipv4WithProperties::from)
+    }
+
+    fn segments(&self) -> [u16; 8] {
+        self.wrapped_address.segments()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv6Addr;
+
+    use super::Ipv6WithProperties;
+
+    fn properties(address: &str) -> Ipv6WithProperties {
+        Ipv6WithProperties::from(address.parse::<Ipv6Addr>().unwrap())
+    }
+
+    #[test]
+    fn global_addresses() {
+        for address in ["2a00:1450:4001:82a::200e", "ff0e::1", "::ffff:8.8.8.8"] {
+            assert!(properties(address).is_global(), "{address}");
+        }
+        for address in [
+            "::",
+            "::1",
+            "100::1",
+            "2001:db8::1",
+            "2001:2::1",
+            "fd12:3456::1",
+            "fe80::1",
+            "ff02::1",
+            "::ffff:192.168.0.1",
+        ] {
+            assert!(!properties(address).is_global(), "{address}");
+        }
+    }
+
+    #[test]
+    fn private_and_loopback_addresses() {
+        assert!(properties("fd12:3456::1").is_private());
+        assert!(properties("::ffff:10.1.2.3").is_private());
+        assert!(!properties("fe80::1").is_private());
+        assert!(properties("::1").is_loopback());
+        assert!(properties("::ffff:127.0.0.1").is_loopback());
+        assert!(!properties("::2").is_loopback());
+    }
+}
@@ -0,0 +1,152 @@
+use std::{io::Write, ops::Range};
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::{
+    defang::{defang_urls, RefangedLine},
+    indicator::{self, IndicatorMatch, IndicatorType},
+};
+
+/// JSON representation of a single indicator, as emitted by `--format jsonl`
+#[derive(Serialize)]
+pub(crate) struct JsonMatch<'a> {
+    pub(crate) indicator: String,
+
+    #[serde(rename = "type")]
+    pub(crate) indicator_type: IndicatorType,
+
+    /// name of the input file, or '-' for stdin
+    pub(crate) file: &'a str,
+
+    /// number of the line, starting with 1
+    pub(crate) line_number: u64,
+
+    /// position of the indicator in the input file
+    pub(crate) byte_offset: u64,
+
+    /// the line which contains the indicator, without line ending and trimmed
+    /// around the indicator if it is too long
+    pub(crate) line: String,
+}
+
+/// the source of the indicators which are printed by [`print_json_matches`]
+pub(crate) struct JsonSource<'a> {
+    pub(crate) file: &'a str,
+    pub(crate) line_number: u64,
+
+    /// position of the line in the input file
+    pub(crate) line_offset: u64,
+    pub(crate) line: &'a str,
+}
+
+/// prints every match as JSON object in a separate line. The ranges of the
+/// matches must refer to `source.line`
+pub(crate) fn print_json_matches(
+    source: &JsonSource,
+    matches: &[IndicatorMatch],
+    max_line_length: usize,
+    defang: bool,
+) -> Result<()> {
+    let mut stdout = std::io::stdout().lock();
+    for m in matches {
+        let mut window = context_window(source.line, m.range.clone(), max_line_length);
+
+        // defanged indicators must not be cut, because the cut off part could
+        // not be defanged
+        if defang {
+            for other in matches {
+                if other.range.start < window.start && window.start < other.range.end {
+                    window.start = other.range.start;
+                }
+                if other.range.start < window.end && window.end < other.range.end {
+                    window.end = other.range.end;
+                }
+            }
+        }
+
+        let json_match = JsonMatch {
+            indicator: if defang {
+                m.indicator.defanged()
+            } else {
+                m.indicator.to_string()
+            },
+            indicator_type: m.indicator.indicator_type(),
+            file: source.file,
+            line_number: source.line_number,
+            byte_offset: source.line_offset + m.range.start as u64,
+            line: if defang {
+                defang_window(source.line, window, matches)
+            } else {
+                source.line[window].to_owned()
+            },
+        };
+        serde_json::to_writer(&mut stdout, &json_match)?;
+        writeln!(stdout)?;
+    }
+    Ok(())
+}
+
+/// returns the part of the line (without line ending) which is displayed for
+/// the indicator at `range`. If the line is longer than `max_length` bytes,
+/// the part is centered around the indicator
+fn context_window(line: &str, range: Range<usize>, max_length: usize) -> Range<usize> {
+    let line_end = line.trim_end_matches(['\r', '\n']).len();
+    if line_end <= max_length {
+        return 0..line_end;
+    }
+    if range.len() >= max_length {
+        return range.start..range.end.min(line_end);
+    }
+
+    let centered_start = range.start.saturating_sub((max_length - range.len()) / 2);
+    let mut end = (centered_start + max_length).min(line_end);
+    let mut start = end - max_length;
+    while !line.is_char_boundary(start) {
+        start += 1;
+    }
+    while !line.is_char_boundary(end) {
+        end -= 1;
+    }
+    start..end
+}
+
+fn defang_window(line: &str, window: Range<usize>, matches: &[IndicatorMatch]) -> String {
+    let mut result = String::new();
+    let mut last_end = window.start;
+    for m in matches
+        .iter()
+        .filter(|m| window.start <= m.range.start && m.range.end <= window.end)
+    {
+        result.push_str(&defang_urls(&line[last_end..m.range.start]));
+        // the indicator is refanged first, because it might have been defanged
+        // already in the input
+        let text = RefangedLine::from(&line[m.range.clone()]);
+        result.push_str(&indicator::defang(
+            m.indicator.indicator_type(),
+            text.text(),
+        ));
+        last_end = m.range.end;
+    }
+    result.push_str(&defang_urls(&line[last_end..window.end]));
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::context_window;
+
+    #[test]
+    fn trim_long_lines() {
+        let line = "0123456789 10.0.0.1 0123456789\r\n";
+        assert_eq!(context_window(line, 11..19, 100), 0..30);
+        assert_eq!(&line[context_window(line, 11..19, 12)], "9 10.0.0.1 0");
+        assert_eq!(&line[context_window(line, 0..10, 14)], "0123456789 10.");
+        assert_eq!(&line[context_window(line, 20..30, 14)], "0.1 0123456789");
+        assert_eq!(&line[context_window(line, 11..19, 4)], "10.0.0.1");
+
+        // lines are never cut inside of a character
+        let line = "äöü 10.0.0.1 äöü";
+        assert_eq!(&line[context_window(line, 7..15, 11)], " 10.0.0.1 ");
+    }
+}
@@ -1,15 +1,29 @@
 use anyhow::Result;
 use cli::Cli;
+use indicator_counter::IndicatorCounter;
 use colored::control::SHOULD_COLORIZE;
-use std::{fs::File, io::BufReader};
+use std::{fs::File, io::BufReader, path::Path};
 
 use dfir_toolkit::common::FancyParser;
 
+mod address_class;
 mod cli;
-mod format_ipv4;
+mod defang;
+mod domain_match;
+mod format_ip;
+mod hash_match;
+mod indicator;
+mod indicator_counter;
 mod ip_filter;
+mod ip_match;
+mod ip_with_properties;
 mod ipv4_with_properties;
-use crate::format_ipv4::format_ipv4;
+mod ipv6_with_properties;
+mod json_match;
+mod net_filter;
+mod output_format;
+mod search_options;
+mod url_match;
 
 fn main() -> Result<()> {
     let app = Cli::parse_cli();
@@ -18,14 +32,25 @@ fn main() -> Result<()> {
         SHOULD_COLORIZE.set_override(true);
     }
 
+    let mut counter = IndicatorCounter::default();
     if app.file.is_empty() {
-        app.ipgrep(std::io::stdin().lock())?;
+        app.ipgrep(Path::new("-"), std::io::stdin().lock(), &mut counter)?;
+        if app.count_by_file {
+            app.print_counts(Some(Path::new("-")), &mut counter);
+        }
     } else {
         for file in app.file.iter() {
             let f = File::open(file)?;
-            app.ipgrep(BufReader::new(f))?;
+            app.ipgrep(file, BufReader::new(f), &mut counter)?;
+            if app.count_by_file {
+                app.print_counts(Some(file), &mut counter);
+            }
         }
     }
 
+    if app.count {
+        app.print_counts(None, &mut counter);
+    }
+
     Ok(())
 }
@@ -0,0 +1,81 @@
+use std::net::IpAddr;
+
+use ipnet::IpNet;
+
+use crate::address_class::AddressScope;
+
+/// restricts the IP addresses to those which are contained in any of the
+/// `include` networks (if some are specified) and in none of the `exclude`
+/// networks, and which are allowed by `scope`. An address is never contained
+/// in a network of the other address family
+#[derive(Default)]
+pub struct NetFilter<'n> {
+    pub include: &'n [IpNet],
+    pub exclude: &'n [IpNet],
+    pub scope: AddressScope,
+}
+
+impl NetFilter<'_> {
+    pub fn allows(&self, address: &IpAddr) -> bool {
+        (self.include.is_empty() || self.include.iter().any(|net| net.contains(address)))
+            && !self.exclude.iter().any(|net| net.contains(address))
+            && self.scope.allows(address)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::IpAddr;
+
+    use ipnet::IpNet;
+
+    use super::NetFilter;
+    use crate::address_class::AddressScope;
+
+    fn nets(nets: &[&str]) -> Vec<IpNet> {
+        nets.iter().map(|net| net.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn include_and_exclude_nets() {
+        let include = nets(&["10.0.0.0/8", "2001:db8::/32"]);
+        let exclude = nets(&["10.12.0.0/16", "2001:db8:1::/48"]);
+        let filter = NetFilter {
+            include: &include,
+            exclude: &exclude,
+            ..Default::default()
+        };
+
+        for (address, is_allowed) in [
+            ("10.1.2.3", true),
+            ("10.12.1.1", false),
+            ("192.168.0.1", false),
+            ("2001:db8::1", true),
+            ("2001:db8:1::1", false),
+            ("fe80::1", false),
+            // address families are never mixed
+            ("::ffff:10.1.2.3", false),
+        ] {
+            let address: IpAddr = address.parse().unwrap();
+            assert_eq!(filter.allows(&address), is_allowed, "{address}");
+        }
+    }
+
+    #[test]
+    fn allow_all_by_default() {
+        assert!(NetFilter::default().allows(&"8.8.8.8".parse().unwrap()));
+    }
+
+    #[test]
+    fn restrict_scope() {
+        let include = nets(&["10.0.0.0/8", "8.8.0.0/16"]);
+        let filter = NetFilter {
+            include: &include,
+            scope: AddressScope::PublicOnly,
+            ..Default::default()
+        };
+        assert!(filter.allows(&"8.8.8.8".parse().unwrap()));
+        assert!(!filter.allows(&"10.1.2.3".parse().unwrap()));
+        assert!(!filter.allows(&"1.1.1.1".parse().unwrap()));
+    }
+}
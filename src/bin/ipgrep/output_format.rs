@@ -0,0 +1,10 @@
+use clap::ValueEnum;
+
+#[derive(ValueEnum, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum OutputFormat {
+    /// the matching lines, with highlighted indicators
+    Plain,
+
+    /// JSON Lines, one JSON object per indicator
+    Jsonl,
+}
@@ -0,0 +1,22 @@
+use std::net::IpAddr;
+
+use crate::{defang::Fanging, indicator::IndicatorTypes, ip_filter::IpFilter, net_filter::NetFilter};
+
+/// selects the lines to be displayed and the indicators to be highlighted
+#[derive(Default)]
+pub struct SearchOptions<'a> {
+    /// lines which contain such IP addresses are not displayed
+    pub excludes: &'a [IpFilter],
+
+    /// only lines which contain such IP addresses are displayed, unless
+    /// this is empty
+    pub includes: &'a [IpFilter],
+
+    /// these IP addresses are neither highlighted nor considered by the
+    /// filters
+    pub ignore_ips: &'a [IpAddr],
+
+    pub nets: NetFilter<'a>,
+    pub types: IndicatorTypes,
+    pub fanging: Fanging,
+}
@@ -1,18 +1,42 @@
-use clap::{Parser, ValueHint};
-use dfir_toolkit::common::HasVerboseFlag;
+use clap::{Parser, ValueEnum, ValueHint};
+use dfir_toolkit::common::{HasVerboseFlag, Verbosity};
 use log::LevelFilter;
-use clio::Input;
+use std::path::PathBuf;
+
+#[derive(ValueEnum, Clone, Debug)]
+pub(crate) enum OutputFormat {
+    /// one bodyfile line per LNK file, with the timestamps of the link target
+    Bodyfile,
+
+    /// one JSON object per line and LNK file, with all metadata of the LNK file: target
+    /// path, arguments, working directory, icon location, link flags, volume information,
+    /// the NetBIOS name and MAC address of the machine of the link target and the timestamps.
+    /// Fields which are not contained in the LNK file are null
+    Json,
+}
 
 /// Parse Windows LNK files and create bodyfile output
 #[derive(Parser, Debug)]
 #[clap(name=env!("CARGO_BIN_NAME"), author, version, long_about = None)]
 pub (crate) struct Cli {
-    /// Names of the LNK files to read from
-    #[clap(value_parser, value_hint=ValueHint::FilePath, default_value="-", display_order(100))]
-    pub(crate) lnk_files: Vec<Input>,
+    /// Names of the LNK files or jump lists (`*.automaticDestinations-ms` or
+    /// `*.customDestinations-ms`) to read from. Directories are searched recursively
+    /// for LNK files and jump lists
+    #[clap(value_parser, value_hint=ValueHint::AnyPath, default_value="-", display_order(100))]
+    pub(crate) lnk_files: Vec<PathBuf>,
+
+    /// output format
+    #[clap(short('F'), long("format"), value_enum, default_value_t = OutputFormat::Bodyfile, display_order(200))]
+    pub(crate) format: OutputFormat,
+
+    /// also print the timestamps of the link target which are stored in its shell item,
+    /// and the timestamps of the LNK file itself (as found in the file system). Every
+    /// line is annotated with the source of its timestamps, because they can disagree
+    #[clap(short('a'), long("all-entries"), display_order(300))]
+    pub(crate) all_entries: bool,
 
     #[clap(flatten)]
-    pub (crate) verbose: clap_verbosity_flag::Verbosity,
+    pub (crate) verbose: Verbosity,
 }
 
 impl HasVerboseFlag for Cli {
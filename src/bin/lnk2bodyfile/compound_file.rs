@@ -0,0 +1,216 @@
+use anyhow::{bail, Result};
+
+const SIGNATURE: [u8; 8] = [0xd0, 0xcf, 0x11, 0xe0, 0xa1, 0xb1, 0x1a, 0xe1];
+const HEADER_SIZE: usize = 512;
+const DIRECTORY_ENTRY_SIZE: usize = 128;
+
+/// number of sector numbers of the DIFAT which are stored in the header
+const HEADER_DIFAT_ENTRIES: usize = 109;
+
+/// sector numbers above this value mark the end of a chain or free sectors
+const MAX_SECTOR_NUMBER: u32 = 0xfffffffa;
+
+const STREAM_OBJECT: u8 = 2;
+const ROOT_STORAGE_OBJECT: u8 = 5;
+
+/// a minimal reader of the Compound File Binary format (aka OLE2 structured
+/// storage), which is the container format of `*.automaticDestinations-ms`
+/// jump lists. It supports only what is needed to read all streams of the
+/// file, and does not care about the hierarchy of storages
+pub struct CompoundFile<'d> {
+    data: &'d [u8],
+    sector_size: usize,
+    mini_sector_size: usize,
+    mini_stream_cutoff: u64,
+
+    /// version 3 files may have garbage in the upper half of stream sizes
+    is_version_3: bool,
+    fat: Vec<u32>,
+    mini_fat: Vec<u32>,
+    mini_stream: Vec<u8>,
+    directory: Vec<u8>,
+}
+
+/// a stream of a compound file, with its name and content
+pub struct Stream {
+    pub name: String,
+    pub data: Vec<u8>,
+}
+
+impl<'d> CompoundFile<'d> {
+    pub fn parse(data: &'d [u8]) -> Result<Self> {
+        if data.len() < HEADER_SIZE || data[0..8] != SIGNATURE {
+            bail!("this is not a compound file");
+        }
+        let sector_shift = read_u16(data, 0x1e);
+        let mini_sector_shift = read_u16(data, 0x20);
+        if !(7..=16).contains(&sector_shift) || mini_sector_shift >= sector_shift {
+            bail!("invalid sector size in compound file header");
+        }
+
+        let mut me = Self {
+            data,
+            sector_size: 1 << sector_shift,
+            mini_sector_size: 1 << mini_sector_shift,
+            mini_stream_cutoff: u64::from(read_u32(data, 0x38)),
+            is_version_3: read_u16(data, 0x1a) == 3,
+            fat: Vec::new(),
+            mini_fat: Vec::new(),
+            mini_stream: Vec::new(),
+            directory: Vec::new(),
+        };
+
+        // the DIFAT contains the sector numbers of the FAT. Its first entries
+        // are stored in the header, the others in a chain of DIFAT sectors,
+        // whose last entry is the number of the next DIFAT sector
+        let mut fat_sectors: Vec<u32> = (0..HEADER_DIFAT_ENTRIES)
+            .map(|index| read_u32(data, 0x4c + index * 4))
+            .collect();
+        let mut difat_sector = read_u32(data, 0x44);
+        let entries_per_sector = me.sector_size / 4;
+        for _ in 0..read_u32(data, 0x48) {
+            if difat_sector > MAX_SECTOR_NUMBER {
+                break;
+            }
+            let sector = me.sector(difat_sector)?;
+            fat_sectors
+                .extend((0..entries_per_sector - 1).map(|index| read_u32(sector, index * 4)));
+            difat_sector = read_u32(sector, (entries_per_sector - 1) * 4);
+        }
+        fat_sectors.truncate(read_u32(data, 0x2c) as usize);
+
+        for fat_sector in fat_sectors {
+            let sector = me.sector(fat_sector)?;
+            me.fat
+                .extend((0..entries_per_sector).map(|index| read_u32(sector, index * 4)));
+        }
+
+        me.directory = me.read_chain(read_u32(data, 0x30), None)?;
+        let mini_fat = me.read_chain(read_u32(data, 0x3c), None)?;
+        me.mini_fat = (0..mini_fat.len() / 4)
+            .map(|index| read_u32(&mini_fat, index * 4))
+            .collect();
+
+        // the mini stream is stored in the sectors of the root entry
+        let root = match me
+            .entries()
+            .find(|entry| entry[0x42] == ROOT_STORAGE_OBJECT)
+        {
+            Some(root) => (read_u32(root, 0x74), me.stream_size(root)),
+            None => bail!("the compound file has no root entry"),
+        };
+        me.mini_stream = me.read_chain(root.0, Some(root.1))?;
+        Ok(me)
+    }
+
+    /// all streams of the compound file, in the order of their directory
+    /// entries
+    pub fn streams(&self) -> Result<Vec<Stream>> {
+        let mut streams = Vec::new();
+        for entry in self.entries().filter(|entry| entry[0x42] == STREAM_OBJECT) {
+            let name_length = (read_u16(entry, 0x40) as usize).min(64);
+            let name: Vec<u16> = (0..name_length / 2)
+                .map(|index| read_u16(entry, index * 2))
+                .take_while(|c| *c != 0)
+                .collect();
+            let (start, size) = (read_u32(entry, 0x74), self.stream_size(entry));
+            let data = if size < self.mini_stream_cutoff {
+                self.read_mini_chain(start, size)?
+            } else {
+                self.read_chain(start, Some(size))?
+            };
+            streams.push(Stream {
+                name: String::from_utf16_lossy(&name),
+                data,
+            });
+        }
+        Ok(streams)
+    }
+
+    fn stream_size(&self, entry: &[u8]) -> u64 {
+        if self.is_version_3 {
+            u64::from(read_u32(entry, 0x78))
+        } else {
+            read_u64(entry, 0x78)
+        }
+    }
+
+    fn entries(&self) -> impl Iterator<Item = &[u8]> {
+        self.directory.chunks_exact(DIRECTORY_ENTRY_SIZE)
+    }
+
+    fn sector(&self, sector: u32) -> Result<&'d [u8]> {
+        let offset = (sector as usize + 1) * self.sector_size;
+        match self.data.get(offset..offset + self.sector_size) {
+            Some(sector) => Ok(sector),
+            None => bail!("sector {sector} is beyond the end of the compound file"),
+        }
+    }
+
+    /// reads the sectors of a chain in the FAT. The number of sectors is
+    /// limited by the size of the FAT, so that a cyclic chain cannot cause an
+    /// endless loop
+    fn read_chain(&self, start: u32, size: Option<u64>) -> Result<Vec<u8>> {
+        let mut data = Vec::new();
+        let mut sector = start;
+        for _ in 0..=self.fat.len() {
+            if sector > MAX_SECTOR_NUMBER {
+                break;
+            }
+            data.extend_from_slice(self.sector(sector)?);
+            sector = next_sector(&self.fat, sector)?;
+        }
+        truncate(data, size)
+    }
+
+    /// reads a chain of sectors of the mini stream, using the mini FAT
+    fn read_mini_chain(&self, start: u32, size: u64) -> Result<Vec<u8>> {
+        let mut data = Vec::new();
+        let mut sector = start;
+        for _ in 0..=self.mini_fat.len() {
+            if sector > MAX_SECTOR_NUMBER {
+                break;
+            }
+            let offset = sector as usize * self.mini_sector_size;
+            match self.mini_stream.get(offset..offset + self.mini_sector_size) {
+                Some(mini_sector) => data.extend_from_slice(mini_sector),
+                None => bail!("mini sector {sector} is beyond the end of the mini stream"),
+            }
+            sector = next_sector(&self.mini_fat, sector)?;
+        }
+        truncate(data, Some(size))
+    }
+}
+
+fn next_sector(fat: &[u32], sector: u32) -> Result<u32> {
+    match fat.get(sector as usize) {
+        Some(next) => Ok(*next),
+        None => bail!("sector {sector} is not contained in the allocation table"),
+    }
+}
+
+fn truncate(mut data: Vec<u8>, size: Option<u64>) -> Result<Vec<u8>> {
+    if let Some(size) = size {
+        if (data.len() as u64) < size {
+            bail!("stream of {size} bytes is longer than its chain of sectors");
+        }
+        data.truncate(size as usize);
+    }
+    Ok(data)
+}
+
+fn read_u16(data: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([data[offset], data[offset + 1]])
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    let mut bytes = [0; 4];
+    bytes.copy_from_slice(&data[offset..offset + 4]);
+    u32::from_le_bytes(bytes)
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    let mut bytes = [0; 8];
+    bytes.copy_from_slice(&data[offset..offset + 8]);
+    u64::from_le_bytes(bytes)
+}
@@ -0,0 +1,91 @@
+use std::path::Path;
+
+use anyhow::Result;
+
+use crate::compound_file::CompoundFile;
+
+/// the beginning of every LNK file: the size of its header (0x4C) and the
+/// class identifier 00021401-0000-0000-C000-000000000046
+const LNK_HEADER: [u8; 20] = [
+    0x4c, 0x00, 0x00, 0x00, 0x01, 0x14, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0xc0, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x46,
+];
+
+/// name of the stream of an automatic destinations jump list which contains
+/// the MRU list, and not a LNK file
+const DEST_LIST: &str = "DestList";
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum SourceKind {
+    LnkFile,
+    JumpList(JumpListKind),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum JumpListKind {
+    /// `*.automaticDestinations-ms`, a compound file which contains one
+    /// stream per LNK file
+    AutomaticDestinations,
+
+    /// `*.customDestinations-ms`, a sequence of LNK files with some data
+    /// between them
+    CustomDestinations,
+}
+
+impl SourceKind {
+    /// determines the kind of a file by its extension, or `None` if it is
+    /// neither a LNK file nor a jump list
+    pub fn of(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_string_lossy().to_lowercase();
+        match &extension[..] {
+            "lnk" => Some(Self::LnkFile),
+            "automaticdestinations-ms" => Some(Self::JumpList(JumpListKind::AutomaticDestinations)),
+            "customdestinations-ms" => Some(Self::JumpList(JumpListKind::CustomDestinations)),
+            _ => None,
+        }
+    }
+}
+
+/// a LNK file which is embedded in a jump list
+pub struct EmbeddedLink {
+    /// name of the stream of an automatic destinations jump list, or the
+    /// number of the link in a custom destinations jump list
+    pub name: String,
+    pub data: Vec<u8>,
+}
+
+/// extracts the LNK files which are embedded in a jump list
+pub fn embedded_links(data: &[u8], kind: JumpListKind) -> Result<Vec<EmbeddedLink>> {
+    match kind {
+        JumpListKind::AutomaticDestinations => Ok(CompoundFile::parse(data)?
+            .streams()?
+            .into_iter()
+            .filter(|stream| stream.name != DEST_LIST)
+            .map(|stream| EmbeddedLink {
+                name: stream.name,
+                data: stream.data,
+            })
+            .collect()),
+        JumpListKind::CustomDestinations => {
+            // the data between the links is not needed to parse them, because
+            // the parser stops at the end of a LNK file
+            let offsets: Vec<_> = data
+                .windows(LNK_HEADER.len())
+                .enumerate()
+                .filter(|(_, window)| *window == LNK_HEADER)
+                .map(|(offset, _)| offset)
+                .collect();
+            Ok(offsets
+                .iter()
+                .enumerate()
+                .map(|(index, begin)| {
+                    let end = offsets.get(index + 1).copied().unwrap_or(data.len());
+                    EmbeddedLink {
+                        name: (index + 1).to_string(),
+                        data: data[*begin..end].to_vec(),
+                    }
+                })
+                .collect())
+        }
+    }
+}
@@ -1,20 +1,114 @@
+use std::io::Write;
+use std::panic::UnwindSafe;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
 use anyhow::bail;
-use clio::Input;
+use chrono::{DateTime, Utc};
 use dfir_toolkit::common::bodyfile::Bodyfile3Line;
 use lnk::{LinkInfo, ShellLink, ShellLinkHeader};
 
+use crate::lnk_metadata::LnkMetadata;
+use crate::shell_item::TargetShellItem;
+use crate::tracker_data::TrackerData;
+
 pub struct LnkFile {
     lnk_file: ShellLink,
+
+    /// the path of the LNK file, or `None` if it is embedded in a jump list
+    file_path: Option<PathBuf>,
     file_name: String,
+    tracker: Option<TrackerData>,
 }
 
 impl LnkFile {
-    pub fn print_bodyfile(&self) {
-        self.print_bodyfile_for_me();
+    pub fn open(file_path: &Path) -> anyhow::Result<Self> {
+        let file_name = match file_path.file_name() {
+            Some(file_name) => file_name.to_string_lossy().into_owned(),
+            None => bail!("'{}' is not a file", file_path.display()),
+        };
+        let data = std::fs::read(file_path)?;
+        let lnk_file = parse_shell_link(&file_name, || ShellLink::open(file_path))?;
+        Ok(Self {
+            lnk_file,
+            file_path: Some(file_path.to_path_buf()),
+            file_name,
+            tracker: TrackerData::find(&data),
+        })
+    }
+
+    /// parses a LNK file which is embedded in a jump list. The `lnk` crate
+    /// can only read files, so the data is written into a temporary file
+    pub fn from_data(data: &[u8], file_name: String) -> anyhow::Result<Self> {
+        let mut tmpfile = tempfile::NamedTempFile::new()?;
+        tmpfile.write_all(data)?;
+        tmpfile.flush()?;
+        let lnk_file = parse_shell_link(&file_name, || ShellLink::open(tmpfile.path()))?;
+        Ok(Self {
+            lnk_file,
+            file_path: None,
+            file_name,
+            tracker: TrackerData::find(data),
+        })
+    }
+
+    /// prints the timestamps of the link target. If `all_entries` is set, the
+    /// timestamps of the shell item of the link target and of the LNK file
+    /// itself are printed as well, and every line is annotated with the
+    /// source of its timestamps
+    pub fn print_bodyfile(&self, all_entries: bool) {
+        if all_entries {
+            println!(
+                "{}",
+                self.target_entry()
+                    .with_owned_name(self.annotated_name("shell link header"))
+            );
+            if let Some(shell_item) = TargetShellItem::find(&self.lnk_file) {
+                self.print_bodyfile_for_shell_item(&shell_item);
+            }
+            if let Some(file_path) = &self.file_path {
+                print_bodyfile_for_file(file_path);
+            }
+        } else {
+            println!("{}", self.target_entry());
+        }
+    }
+
+    pub fn print_json(&self) -> anyhow::Result<()> {
+        let metadata = LnkMetadata::new(&self.lnk_file, &self.file_name, self.tracker.as_ref());
+        println!("{}", serde_json::to_string(&metadata)?);
+        Ok(())
     }
 
-    fn print_bodyfile_for_me(&self) {
+    /// the bodyfile line of the link target, with the timestamps from the
+    /// shell link header
+    fn target_entry(&self) -> Bodyfile3Line {
         let header = self.lnk_file.header();
+        let atime = ShellLinkHeader::access_time(header);
+        let mtime = ShellLinkHeader::write_time(header);
+        let crtime = ShellLinkHeader::creation_time(header);
+
+        Bodyfile3Line::new()
+            .with_owned_name(self.target_name())
+            .with_size(ShellLinkHeader::file_size(header).into())
+            .with_crtime(crtime.datetime().into())
+            .with_mtime(mtime.datetime().into())
+            .with_atime(atime.datetime().into())
+    }
+
+    fn print_bodyfile_for_shell_item(&self, shell_item: &TargetShellItem) {
+        let header = self.lnk_file.header();
+        let bfline = Bodyfile3Line::new()
+            .with_owned_name(self.annotated_name("shell item extension block"))
+            .with_size(ShellLinkHeader::file_size(header).into())
+            .with_crtime(shell_item.created().into())
+            .with_mtime(shell_item.modified().into())
+            .with_atime(shell_item.accessed().into());
+
+        println!("{bfline}");
+    }
+
+    fn target_name(&self) -> String {
         let localpath = match self.lnk_file.link_info() {
             Some(s1) => match LinkInfo::local_base_path(s1) {
                 Some(s2) => s2,
@@ -26,40 +120,78 @@ impl LnkFile {
             Some(s) => s,
             None => "-",
         };
-        let atime = ShellLinkHeader::access_time(header);
-        let mtime = ShellLinkHeader::write_time(header);
-        let crtime = ShellLinkHeader::creation_time(header);
+        format!(
+            "{} {} (referred to by \"{}\")",
+            localpath, arguments, self.file_name
+        )
+    }
 
-        let bfline = Bodyfile3Line::new()
-            .with_name(&format!(
-                "{} {} (referred to by \"{}\")",
-                localpath, arguments, self.file_name
-            ))
-            .with_size(ShellLinkHeader::file_size(header).into())
-            .with_crtime(crtime.datetime().into())
-            .with_mtime(mtime.datetime().into())
-            .with_atime(atime.datetime().into());
+    fn annotated_name(&self, source: &str) -> String {
+        format!("{} [{source}]", self.target_name())
+    }
+}
 
-        println!("{bfline}");
+/// prints the timestamps of a LNK file or jump list, as found in the file
+/// system
+pub fn print_bodyfile_for_file(file_path: &Path) {
+    let metadata = match std::fs::metadata(file_path) {
+        Ok(metadata) => metadata,
+        Err(why) => {
+            log::error!(
+                "unable to read metadata of '{}': {why}",
+                file_path.display()
+            );
+            return;
+        }
+    };
+    let timestamp = |time: std::io::Result<SystemTime>| time.ok().map(DateTime::<Utc>::from);
+
+    let mut bfline = Bodyfile3Line::new()
+        .with_owned_name(format!("{} [file system]", file_path.display()))
+        .with_size(metadata.len());
+    if let Some(crtime) = timestamp(metadata.created()) {
+        bfline = bfline.with_crtime(crtime.into());
+    }
+    if let Some(mtime) = timestamp(metadata.modified()) {
+        bfline = bfline.with_mtime(mtime.into());
+    }
+    if let Some(atime) = timestamp(metadata.accessed()) {
+        bfline = bfline.with_atime(atime.into());
     }
+
+    println!("{bfline}");
 }
 
-impl TryFrom<&Input> for LnkFile {
-    type Error = anyhow::Error;
-
-    fn try_from(input: &Input) -> Result<Self, Self::Error> {
-        let file_path = input.path().to_path_buf();
-        let file_name = file_path.file_name().unwrap().to_str().unwrap().to_string();
-        match ShellLink::open(file_path) {
-            Ok(lnk_file) => Ok(Self {
-                lnk_file,
-                file_name,
-            }),
-            Err(e) => bail!(
-                "{:?}: The file {} is not in a valid ShellLink format",
-                e,
+/// the `lnk` crate panics if it reads some kinds of malformed data. Such a
+/// panic is caught and reported as error, so that one malformed file does not
+/// abort the processing of all other files
+fn parse_shell_link<F>(file_name: &str, parse: F) -> anyhow::Result<ShellLink>
+where
+    F: FnOnce() -> Result<ShellLink, lnk::Error> + UnwindSafe,
+{
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(|_| {}));
+    let result = std::panic::catch_unwind(parse);
+    std::panic::set_hook(default_hook);
+
+    match result {
+        Ok(Ok(lnk_file)) => Ok(lnk_file),
+        Ok(Err(e)) => bail!(
+            "{:?}: The file {} is not in a valid ShellLink format",
+            e,
+            file_name
+        ),
+        Err(panic) => {
+            let why = panic
+                .downcast_ref::<String>()
+                .map(String::as_str)
+                .or_else(|| panic.downcast_ref::<&str>().copied())
+                .unwrap_or("unknown error");
+            bail!(
+                "{}: The file {} is not in a valid ShellLink format",
+                why,
                 file_name
-            ),
+            )
         }
     }
 }
@@ -0,0 +1,121 @@
+use chrono::{DateTime, Utc};
+use lnk::linkinfo::DriveType;
+use lnk::{FileTime, LinkInfo, ShellLink};
+use serde::Serialize;
+
+use crate::tracker_data::TrackerData;
+
+/// everything which is known about an LNK file, used for `--format json`.
+/// Fields which are not contained in the LNK file are `null`
+#[derive(Serialize)]
+pub struct LnkMetadata {
+    lnk_file: String,
+    target_path: Option<String>,
+    arguments: Option<String>,
+    working_dir: Option<String>,
+    relative_path: Option<String>,
+    icon_location: Option<String>,
+    description: Option<String>,
+    link_flags: Vec<String>,
+    file_attributes: Vec<String>,
+    target_size: u32,
+    creation_time: Option<DateTime<Utc>>,
+    access_time: Option<DateTime<Utc>>,
+    write_time: Option<DateTime<Utc>>,
+    drive_type: Option<&'static str>,
+
+    /// formatted like `1234-ABCD`, as it is displayed by Windows
+    volume_serial_number: Option<String>,
+    volume_label: Option<String>,
+
+    /// NetBIOS name of the machine where the link target was last known
+    machine_id: Option<String>,
+    mac_address: Option<String>,
+}
+
+impl LnkMetadata {
+    pub fn new(lnk_file: &ShellLink, file_name: &str, tracker: Option<&TrackerData>) -> Self {
+        let header = lnk_file.header();
+        let volume_id = lnk_file
+            .link_info()
+            .as_ref()
+            .and_then(|link_info| link_info.volume_id().as_ref());
+        Self {
+            lnk_file: file_name.to_owned(),
+            target_path: lnk_file.link_info().as_ref().and_then(target_path),
+            arguments: non_empty(lnk_file.arguments()),
+            working_dir: non_empty(lnk_file.working_dir()),
+            relative_path: non_empty(lnk_file.relative_path()),
+            icon_location: non_empty(lnk_file.icon_location()),
+            description: non_empty(lnk_file.name()),
+            link_flags: flag_names(format!("{:?}", header.link_flags())),
+            file_attributes: flag_names(format!("{:?}", header.file_attributes())),
+            target_size: header.file_size(),
+            creation_time: timestamp(header.creation_time()),
+            access_time: timestamp(header.access_time()),
+            write_time: timestamp(header.write_time()),
+            drive_type: volume_id.map(|volume_id| drive_type(volume_id.drive_type())),
+            volume_serial_number: volume_id.map(|volume_id| {
+                let serial = volume_id.drive_serial_number();
+                format!("{:04X}-{:04X}", serial >> 16, serial & 0xffff)
+            }),
+            volume_label: volume_id
+                .map(|volume_id| volume_id.volume_label())
+                .filter(|label| !label.is_empty())
+                .cloned(),
+            machine_id: tracker
+                .map(|tracker| tracker.machine_id())
+                .filter(|machine_id| !machine_id.is_empty())
+                .map(str::to_owned),
+            mac_address: tracker.map(|tracker| tracker.mac_address()),
+        }
+    }
+}
+
+/// the local path of the link target, preferring its Unicode version
+fn target_path(link_info: &LinkInfo) -> Option<String> {
+    let base_path = link_info
+        .local_base_path_unicode()
+        .as_ref()
+        .or(link_info.local_base_path().as_ref())?;
+    let suffix = link_info
+        .common_path_suffix_unicode()
+        .as_ref()
+        .unwrap_or(link_info.common_path_suffix());
+    Some(format!("{base_path}{suffix}"))
+}
+
+fn non_empty(value: &Option<String>) -> Option<String> {
+    value.as_ref().filter(|value| !value.is_empty()).cloned()
+}
+
+/// the names of the flags, which are written by `Debug` like `A | B`
+fn flag_names(flags: String) -> Vec<String> {
+    flags
+        .split(" | ")
+        .filter(|flag| !flag.is_empty() && *flag != "(empty)")
+        .map(str::to_owned)
+        .collect()
+}
+
+/// FILETIMEs of zero mean that no time has been set
+fn timestamp(filetime: FileTime) -> Option<DateTime<Utc>> {
+    let value: u64 = filetime.into();
+    if value == 0 {
+        None
+    } else {
+        Some(filetime.datetime().and_utc())
+    }
+}
+
+fn drive_type(drive_type: &DriveType) -> &'static str {
+    match drive_type {
+        DriveType::DriveUnknown => "unknown",
+        DriveType::DriveNoRootDir => "no_root_dir",
+        DriveType::DriveRemovable => "removable",
+        DriveType::DriveFixed => "fixed",
+        DriveType::DriveRemote => "remote",
+        DriveType::DriveCDRom => "cdrom",
+        DriveType::DriveRamdisk => "ramdisk",
+    }
+}
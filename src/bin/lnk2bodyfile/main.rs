@@ -2,31 +2,32 @@ use anyhow::{bail, Result};
 use cli::Cli;
 use dfir_toolkit::common::FancyParser;
 
-use crate::lnk_file::LnkFile;
+use crate::processor::Processor;
 
 mod cli;
+mod compound_file;
+mod jump_list;
 mod lnk_file;
+mod lnk_metadata;
+mod processor;
+mod shell_item;
+mod tracker_data;
 
 fn main() -> Result<()> {
     let cli = Cli::parse_cli();
 
-    if cli.lnk_files.iter().any(|f| !f.can_seek()) {
+    if cli.lnk_files.iter().any(|f| f.as_os_str() == "-") {
         bail!(
             "{} cannot read from a stream; you must specify a file",
             env!("CARGO_BIN_NAME")
         );
     }
 
-    for filename in cli.lnk_files.iter() {
-        let lnkfile = match LnkFile::try_from(filename) {
-            Ok(file) => file,
-            Err(why) => {
-                log::error!("{why}");
-                continue;
-            }
-        };
-        lnkfile.print_bodyfile();
+    let mut processor = Processor::new(cli.format, cli.all_entries);
+    for path in cli.lnk_files.iter() {
+        processor.process(path)?;
     }
+    processor.log_summary();
 
     Ok(())
 }
@@ -0,0 +1,137 @@
+use std::fmt::Display;
+use std::path::Path;
+
+use walkdir::WalkDir;
+
+use crate::cli::OutputFormat;
+use crate::jump_list::{embedded_links, JumpListKind, SourceKind};
+use crate::lnk_file::{print_bodyfile_for_file, LnkFile};
+
+/// processes LNK files and jump lists, and counts how many of them have been
+/// processed. A file which cannot be parsed is reported and skipped
+pub struct Processor {
+    format: OutputFormat,
+    all_entries: bool,
+    processed_files: usize,
+    links: usize,
+    failures: usize,
+}
+
+impl Processor {
+    pub fn new(format: OutputFormat, all_entries: bool) -> Self {
+        Self {
+            format,
+            all_entries,
+            processed_files: 0,
+            links: 0,
+            failures: 0,
+        }
+    }
+
+    /// processes a file, or all LNK files and jump lists in a directory and
+    /// its subdirectories. A file which has been named explicitly is treated
+    /// as LNK file, unless its extension denotes a jump list
+    pub fn process(&mut self, path: &Path) -> anyhow::Result<()> {
+        if !path.is_dir() {
+            let kind = SourceKind::of(path).unwrap_or(SourceKind::LnkFile);
+            return self.process_file(path, kind);
+        }
+
+        for entry in WalkDir::new(path).sort_by_file_name() {
+            match entry {
+                Ok(entry) if entry.file_type().is_file() => {
+                    if let Some(kind) = SourceKind::of(entry.path()) {
+                        self.process_file(entry.path(), kind)?;
+                    }
+                }
+                Ok(_) => (),
+                Err(why) => {
+                    log::error!("{why}");
+                    self.failures += 1;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn process_file(&mut self, path: &Path, kind: SourceKind) -> anyhow::Result<()> {
+        match kind {
+            SourceKind::LnkFile => match LnkFile::open(path) {
+                Ok(lnkfile) => {
+                    self.print(&lnkfile)?;
+                    self.processed_files += 1;
+                }
+                Err(why) => {
+                    log::error!("{}: {why}", path.display());
+                    self.failures += 1;
+                }
+            },
+            SourceKind::JumpList(kind) => self.process_jump_list(path, kind)?,
+        }
+        Ok(())
+    }
+
+    /// prints all links of a jump list. A malformed link is skipped, so that
+    /// the other links of the jump list are printed nevertheless
+    fn process_jump_list(&mut self, path: &Path, kind: JumpListKind) -> anyhow::Result<()> {
+        let links = match std::fs::read(path)
+            .map_err(anyhow::Error::from)
+            .and_then(|data| embedded_links(&data, kind))
+        {
+            Ok(links) => links,
+            Err(why) => {
+                log::error!("{}: {why}", path.display());
+                self.failures += 1;
+                return Ok(());
+            }
+        };
+
+        let file_name = path
+            .file_name()
+            .map(|file_name| file_name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        for link in links {
+            match LnkFile::from_data(&link.data, format!("{file_name}:{}", link.name)) {
+                Ok(lnkfile) => self.print(&lnkfile)?,
+                Err(why) => {
+                    log::error!("{}: link {}: {why}", path.display(), link.name);
+                    self.failures += 1;
+                }
+            }
+        }
+        if self.all_entries && matches!(self.format, OutputFormat::Bodyfile) {
+            print_bodyfile_for_file(path);
+        }
+        self.processed_files += 1;
+        Ok(())
+    }
+
+    fn print(&mut self, lnkfile: &LnkFile) -> anyhow::Result<()> {
+        match self.format {
+            OutputFormat::Bodyfile => lnkfile.print_bodyfile(self.all_entries),
+            OutputFormat::Json => lnkfile.print_json()?,
+        }
+        self.links += 1;
+        Ok(())
+    }
+
+    /// logs the numbers of files, links and failures. If any file could not
+    /// be processed, this is logged as a warning
+    pub(crate) fn log_summary(&self) {
+        if self.failures > 0 {
+            log::warn!("{self}");
+        } else {
+            log::info!("{self}");
+        }
+    }
+}
+
+impl Display for Processor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "processed {} files with {} links, {} failures",
+            self.processed_files, self.links, self.failures
+        )
+    }
+}
@@ -0,0 +1,79 @@
+use chrono::NaiveDate;
+use lnk::ShellLink;
+
+/// signature of the extension block 0xBEEF0004 of a file entry shell item,
+/// which contains the creation and access time of the item
+const BEEF0004_SIGNATURE: [u8; 4] = [0x04, 0x00, 0xef, 0xbe];
+
+/// the timestamps of the link target, as they are stored in the last file
+/// entry shell item of the LinkTargetIDList. Windows takes them from the file
+/// system when the link is created, but stores them as FAT timestamps (with a
+/// resolution of two seconds), so that they can differ from the timestamps in
+/// the shell link header
+pub struct TargetShellItem {
+    modified: Option<i64>,
+    accessed: Option<i64>,
+    created: Option<i64>,
+}
+
+impl TargetShellItem {
+    pub fn find(lnk_file: &ShellLink) -> Option<Self> {
+        let id_list = lnk_file.link_target_id_list().as_ref()?;
+
+        // the class type of a file entry shell item is 0x30 to 0x3f
+        let data = id_list
+            .id_list()
+            .iter()
+            .rev()
+            .map(|item| item.data())
+            .find(|data| data.len() >= 10 && data[0] & 0x70 == 0x30)?;
+
+        let (created, accessed) = match data
+            .windows(BEEF0004_SIGNATURE.len())
+            .position(|window| window == BEEF0004_SIGNATURE)
+            .and_then(|offset| data.get(offset + 4..offset + 12))
+        {
+            Some(extension) => (
+                fat_timestamp(&extension[0..4]),
+                fat_timestamp(&extension[4..8]),
+            ),
+            None => (None, None),
+        };
+
+        Some(Self {
+            modified: fat_timestamp(&data[6..10]),
+            accessed,
+            created,
+        })
+    }
+
+    pub fn modified(&self) -> Option<i64> {
+        self.modified
+    }
+
+    pub fn accessed(&self) -> Option<i64> {
+        self.accessed
+    }
+
+    pub fn created(&self) -> Option<i64> {
+        self.created
+    }
+}
+
+/// converts a FAT date and time (in UTC) into a UNIX timestamp. An empty
+/// date is invalid and results in `None`
+fn fat_timestamp(data: &[u8]) -> Option<i64> {
+    let date = u16::from_le_bytes([data[0], data[1]]);
+    let time = u16::from_le_bytes([data[2], data[3]]);
+    NaiveDate::from_ymd_opt(
+        1980 + i32::from(date >> 9),
+        u32::from((date >> 5) & 0x0f),
+        u32::from(date & 0x1f),
+    )?
+    .and_hms_opt(
+        u32::from(time >> 11),
+        u32::from((time >> 5) & 0x3f),
+        u32::from(time & 0x1f) * 2,
+    )
+    .map(|ts| ts.and_utc().timestamp())
+}
@@ -0,0 +1,56 @@
+/// the header of a TrackerDataBlock: its size (0x60), its signature
+/// (0xA0000003), the length of the remaining data (0x58) and its version (0)
+const TRACKER_DATA_HEADER: [u8; 16] = [
+    0x60, 0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0xa0, 0x58, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+];
+const TRACKER_DATA_SIZE: usize = 0x60;
+
+/// the content of the TrackerDataBlock of an LNK file, which is used by the
+/// Distributed Link Tracking service to find a moved link target. The `lnk`
+/// crate parses this block, but does not expose it, so it is read from the
+/// raw data of the file
+pub struct TrackerData {
+    machine_id: String,
+    mac_address: [u8; 6],
+}
+
+impl TrackerData {
+    /// searches the TrackerDataBlock in the data of an LNK file. Its header
+    /// is so specific that it cannot be found elsewhere by accident
+    pub fn find(data: &[u8]) -> Option<Self> {
+        let offset = data
+            .windows(TRACKER_DATA_HEADER.len())
+            .position(|window| window == TRACKER_DATA_HEADER)?;
+        let block = data.get(offset..offset + TRACKER_DATA_SIZE)?;
+
+        // NetBIOS name of the machine, in the system default code page
+        let machine_id = &block[16..32];
+        let machine_id = &machine_id[..machine_id
+            .iter()
+            .position(|c| *c == 0)
+            .unwrap_or(machine_id.len())];
+
+        // the second GUID of the droid is the object ID of the file, which is
+        // a version 1 UUID whose last 6 bytes are the MAC address of the
+        // machine where it has been created
+        let mut mac_address = [0; 6];
+        mac_address.copy_from_slice(&block[58..64]);
+
+        Some(Self {
+            machine_id: String::from_utf8_lossy(machine_id).into_owned(),
+            mac_address,
+        })
+    }
+
+    pub fn machine_id(&self) -> &str {
+        &self.machine_id
+    }
+
+    pub fn mac_address(&self) -> String {
+        self.mac_address
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect::<Vec<_>>()
+            .join(":")
+    }
+}
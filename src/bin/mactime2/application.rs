@@ -1,23 +1,41 @@
-use chrono_tz::Tz;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Arc;
+
+use anyhow::Context;
+use dfir_toolkit::common::{AnonymizeArgs, FileOutput, OutputArgs, Timezone};
 use clap::ValueEnum;
-use clio::Input;
 use strum_macros::Display;
 
 use crate::output::OldCsvOutput;
 
-use super::bodyfile::{BodyfileDecoder, BodyfileReader, BodyfileSorter};
+use super::bodyfile::{
+    BodyfileDecoder, BodyfileSorter, DedupField, ExtendedBodyfileLine,
+    LineDiagnostics, Mactime2Writer, DEFAULT_DEDUP_FIELDS,
+};
 use super::cli::Cli;
 use super::error::MactimeError;
-use super::filter::{Consumer, Joinable, Provider, RunOptions, Sorter};
-use super::output::{CsvOutput, JsonSorter, TxtOutput};
-use super::stream::StreamReader;
+use super::filter::{Consumer, Joinable, RunOptions, Sorter};
+use super::labeled_input::LabeledInput;
+use super::name_filter::NameFilter;
+use super::output::{
+    CsvOutput, JsonOutput, JsonSorter, SplitBy, SplitOutput, StatsOutput, TxtOutput, CSV_DELIMITER,
+};
+use super::stream::StreamSource;
+use super::time_window::TimeWindow;
+
+/// byte order mark, which is needed by some spreadsheet applications to
+/// detect that a CSV file is encoded in UTF-8
+const UTF8_BOM: &[u8] = b"\xef\xbb\xbf";
 
 #[derive(ValueEnum, Clone, Display)]
 enum InputFormat {
     #[strum(serialize = "bodyfile")]
     Bodyfile,
 
-    #[cfg(feature = "elastic")]
+    #[cfg(feature = "ecs")]
     #[strum(serialize = "json")]
     Json,
 }
@@ -32,12 +50,21 @@ pub(crate) enum OutputFormat {
     #[strum(serialize = "txt")]
     Txt,
 
-    /// Javascript Object Notation
+    /// a JSON array with one object per timeline entry, containing the
+    /// fields timestamp, macb, size, uid, gid, mode, inode, name and source,
+    /// sorted like the other formats
     #[strum(serialize = "json")]
     Json,
 
-    /// JSON-format to be used by elasticsearch
-    #[cfg(feature = "elastic")]
+    /// JSON Lines, with one compact object per line and neither a wrapping
+    /// array nor separating commas, so that it can be piped into bulk
+    /// ingestion. The fields are the same as in 'json'
+    #[strum(serialize = "jsonl")]
+    Jsonl,
+
+    /// deprecated: JSON documents for elasticsearch, using the Elastic Common
+    /// Schema, with one document per timestamp of every file
+    #[cfg(feature = "ecs")]
     #[strum(serialize = "elastic")]
     Elastic,
 
@@ -46,58 +73,252 @@ pub(crate) enum OutputFormat {
     OldCsv,
 }
 
+impl OutputFormat {
+    /// extension of the files which are written by `--split-by`
+    fn file_extension(&self) -> &'static str {
+        match self {
+            OutputFormat::Csv | OutputFormat::OldCsv => "csv",
+            OutputFormat::Txt => "txt",
+            OutputFormat::Json => "json",
+            OutputFormat::Jsonl => "jsonl",
+            #[cfg(feature = "ecs")]
+            OutputFormat::Elastic => "json",
+        }
+    }
+}
+
+#[derive(Clone)]
 pub struct Mactime2Application {
     format: OutputFormat,
-    bodyfile: Input,
-    dst_zone: Tz,
+    bodyfiles: Vec<LabeledInput>,
+    dst_zone: Timezone,
     show_headers: bool,
+    delimiter: Option<u8>,
+    bom: bool,
+    ecs: bool,
+    show_utc: bool,
+    max_memory: Option<usize>,
+    tmpdir: PathBuf,
+    dedup_fields: Option<Vec<DedupField>>,
+    bad_lines: Option<PathBuf>,
+    time_window: TimeWindow,
+    name_filter: NameFilter,
+    split_by: Option<SplitBy>,
+    output_dir: Option<PathBuf>,
+    output: OutputArgs,
+    anonymize: AnonymizeArgs,
+    stats: Option<Option<PathBuf>>,
     strict_mode: bool,
 }
 
 impl Mactime2Application {
     fn create_sorter(
         &self,
-        decoder: &mut BodyfileDecoder,
+        receiver: Receiver<ExtendedBodyfileLine>,
+        stats: Option<Box<dyn Write + Send>>,
+        output: FileOutput,
     ) -> Box<dyn Sorter<Result<(), MactimeError>>> {
         let options = RunOptions {
             strict_mode: self.strict_mode,
+            time_window: self.time_window,
         };
 
-        if matches!(self.format, OutputFormat::Json) {
-            Box::new(JsonSorter::with_receiver(decoder.get_receiver(), options))
+        if self.writes_elastic_documents() {
+            Box::new(JsonSorter::with_receiver(receiver, options).with_output(output))
         } else {
-            let mut sorter =
-                BodyfileSorter::default().with_receiver(decoder.get_receiver(), options);
+            let mut sorter = BodyfileSorter::default()
+                .with_receiver(receiver, options)
+                .with_max_memory(self.max_memory, self.tmpdir.clone())
+                .with_dedup(self.dedup_fields.as_deref());
 
-            sorter = sorter.with_output(match self.format {
-                OutputFormat::OldCsv => {
-                    Box::new(OldCsvOutput::new(std::io::stdout(), self.dst_zone))
+            let output: Box<dyn Mactime2Writer<FileOutput>> = match (self.split_by, &self.output_dir) {
+                (Some(_), Some(output_dir)) => {
+                    // every file is a timeline of its own, so it gets a header
+                    let mut app = self.clone();
+                    app.show_headers = true;
+                    Box::new(SplitOutput::new(
+                        output_dir.clone(),
+                        self.format.file_extension(),
+                        self.dst_zone,
+                        move |mut file| {
+                            if app.bom {
+                                file.write_all(UTF8_BOM)?;
+                            }
+                            Ok(app.create_output(file))
+                        },
+                    ))
+                }
+                _ => self.create_output(output),
+            };
+            sorter = match stats {
+                Some(stats) => {
+                    sorter.with_output(Box::new(StatsOutput::new(output, stats, self.dst_zone)))
                 }
+                None => sorter.with_output(output),
+            };
+            Box::new(sorter)
+        }
+    }
 
-                OutputFormat::Csv => Box::new(CsvOutput::new(
-                    std::io::stdout(),
+    fn create_output<W>(&self, writer: W) -> Box<dyn Mactime2Writer<W>>
+    where
+        W: Write + Send + 'static,
+    {
+        match self.format {
+            OutputFormat::OldCsv => {
+                Box::new(OldCsvOutput::new(writer, self.dst_zone).with_source(self.show_source()))
+            }
+
+            OutputFormat::Csv => Box::new(
+                CsvOutput::new(
+                    writer,
                     self.dst_zone,
                     self.show_headers,
-                )),
-                OutputFormat::Txt => Box::new(TxtOutput::new(std::io::stdout(), self.dst_zone)),
-                _ => panic!("invalid execution path"),
-            });
-            Box::new(sorter)
+                    self.delimiter.unwrap_or(CSV_DELIMITER),
+                )
+                .with_utc(self.show_utc)
+                .with_source(self.show_source()),
+            ),
+            OutputFormat::Txt => {
+                Box::new(TxtOutput::new(writer, self.dst_zone).with_source(self.show_source()))
+            }
+            OutputFormat::Json | OutputFormat::Jsonl => Box::new(
+                JsonOutput::new(writer, self.dst_zone, self.ecs)
+                    .with_utc(self.show_utc)
+                    .with_array(matches!(self.format, OutputFormat::Json)),
+            ),
+            _ => panic!("invalid execution path"),
         }
     }
 
+    /// `true` if the ECS documents of the deprecated format 'elastic' are
+    /// written, which are not sorted like the timeline
+    fn writes_elastic_documents(&self) -> bool {
+        #[cfg(feature = "ecs")]
+        return matches!(self.format, OutputFormat::Elastic);
+
+        #[cfg(not(feature = "ecs"))]
+        return false;
+    }
+
+    /// the formats which have no source field get a source column if
+    /// multiple bodyfiles are merged, or if a label has been specified
+    fn show_source(&self) -> bool {
+        self.bodyfiles.len() > 1 || self.bodyfiles.iter().any(|b| b.has_explicit_label())
+    }
+
     pub fn run(&self) -> anyhow::Result<()> {
+        if self.ecs && !matches!(self.format, OutputFormat::Json | OutputFormat::Jsonl) {
+            anyhow::bail!(
+                "'--ecs' can only be used with the formats 'json' and 'jsonl', but not with '{}'",
+                self.format
+            );
+        }
+        if (self.delimiter.is_some() || self.bom) && !matches!(self.format, OutputFormat::Csv) {
+            anyhow::bail!(
+                "'--delimiter' and '--bom' can only be used with the format 'csv', but not with '{}'",
+                self.format
+            );
+        }
+
+        if self.show_utc
+            && !matches!(
+                self.format,
+                OutputFormat::Csv | OutputFormat::Json | OutputFormat::Jsonl
+            )
+        {
+            anyhow::bail!(
+                "'--show-utc' can only be used with the formats 'csv', 'json' and 'jsonl', but not with '{}'",
+                self.format
+            );
+        }
+        if self.writes_elastic_documents() {
+            if self.max_memory.is_some() {
+                anyhow::bail!("'--max-memory' cannot be used with the format 'elastic'");
+            }
+            if self.dedup_fields.is_some() {
+                anyhow::bail!("'--dedup' and '--dedup-by' cannot be used with the format 'elastic'");
+            }
+            if self.split_by.is_some() {
+                anyhow::bail!("'--split-by' cannot be used with the format 'elastic'");
+            }
+            if self.stats.is_some() {
+                anyhow::bail!("'--stats' cannot be used with the format 'elastic'");
+            }
+            log::warn!(
+                "the format 'elastic' is deprecated and will be removed in a future release, \
+                 use '--format jsonl --ecs' instead"
+            );
+        }
+        if self.time_window.is_empty() {
+            anyhow::bail!("the time window is empty, because '--from' is after '--to'");
+        }
+        // the key file is read before any output is created, so that a
+        // missing or invalid key never leaves an empty output behind
+        let anonymizer = self.anonymize.anonymizer()?.map(Arc::new);
+
+        let output = if let Some(output_dir) = &self.output_dir {
+            std::fs::create_dir_all(output_dir)
+                .with_context(|| format!("unable to create '{}'", output_dir.display()))?;
+            FileOutput::stdout()
+        } else {
+            let mut output = self.output.create()?;
+            if self.bom {
+                output.write_all(UTF8_BOM)?;
+            }
+            output
+        };
+
+        let bad_lines = match &self.bad_lines {
+            Some(path) => Some(
+                File::create(path)
+                    .with_context(|| format!("unable to create '{}'", path.display()))?,
+            ),
+            None => None,
+        };
+
+        let stats: Option<Box<dyn Write + Send>> = match &self.stats {
+            Some(Some(path)) => Some(Box::new(BufWriter::new(
+                File::create(path)
+                    .with_context(|| format!("unable to create '{}'", path.display()))?,
+            ))),
+            Some(None) => Some(Box::new(std::io::stderr())),
+            None => None,
+        };
+
         let options = RunOptions {
             strict_mode: self.strict_mode,
+            time_window: self.time_window,
         };
-
-        let mut reader = <BodyfileReader as StreamReader<String, ()>>::from(self.bodyfile.clone())?;
-        let mut decoder = BodyfileDecoder::with_receiver(reader.get_receiver(), options);
-        let mut sorter = self.create_sorter(&mut decoder);
+        let (tx, rx) = mpsc::channel();
+        let mut sorter = self.create_sorter(rx, stats, output);
         sorter.run();
 
-        let _ = reader.join();
-        let _ = decoder.join();
+        // the bodyfiles are read one after another, so that entries with the
+        // same timestamp are always in the order of the bodyfiles
+        for bodyfile in self.bodyfiles.iter() {
+            let bad_lines = match &bad_lines {
+                Some(file) => Some(file.try_clone()?),
+                None => None,
+            };
+            let diagnostics = LineDiagnostics::new(
+                bodyfile.path().unwrap_or_else(|| "stdin".to_owned()),
+                bad_lines,
+            )
+            .with_label(bodyfile.label());
+            let source = StreamSource::try_from(bodyfile.input().clone())?;
+            let mut decoder = BodyfileDecoder::with_sender(
+                source,
+                tx.clone(),
+                options,
+                diagnostics,
+                self.name_filter.clone(),
+                anonymizer.clone(),
+            );
+            let _ = decoder.join();
+        }
+        drop(tx);
+
         sorter.join().unwrap()?;
         Ok(())
     }
@@ -105,13 +326,24 @@ impl Mactime2Application {
 
 impl From<Cli> for Mactime2Application {
     fn from(cli: Cli) -> Self {
+        let time_window = TimeWindow::from(&cli);
+        let name_filter = NameFilter::from(&cli);
+        let dedup_fields = if !cli.dedup_by.is_empty() {
+            Some(cli.dedup_by.clone())
+        } else if cli.dedup {
+            Some(DEFAULT_DEDUP_FIELDS.to_vec())
+        } else {
+            None
+        };
         let format = match cli.output_format {
             Some(f) => f,
             None => {
-                if cli.csv_format {
+                if cli.csv_format || cli.delimiter.is_some() || cli.bom {
                     OutputFormat::Csv
                 } else if cli.json_format {
                     OutputFormat::Json
+                } else if cli.ecs {
+                    OutputFormat::Jsonl
                 } else {
                     OutputFormat::Txt
                 }
@@ -120,9 +352,24 @@ impl From<Cli> for Mactime2Application {
 
         Self {
             format,
-            bodyfile: cli.input_file,
-            dst_zone: cli.dst_zone.into_tz().unwrap(),
+            bodyfiles: cli.input_files,
+            dst_zone: cli.dst_zone,
             show_headers: cli.show_headers,
+            delimiter: cli.delimiter,
+            bom: cli.bom,
+            ecs: cli.ecs,
+            show_utc: cli.show_utc,
+            max_memory: cli.max_memory,
+            tmpdir: cli.tmpdir.unwrap_or_else(std::env::temp_dir),
+            dedup_fields,
+            bad_lines: cli.bad_lines,
+            time_window,
+            name_filter,
+            split_by: cli.split_by,
+            output_dir: cli.output_dir,
+            output: cli.output,
+            anonymize: cli.anonymize,
+            stats: cli.stats,
             strict_mode: cli.strict_mode,
         }
     }
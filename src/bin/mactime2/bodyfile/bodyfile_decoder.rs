@@ -1,76 +1,176 @@
-use crate::filter::{Consumer, Filter, Joinable, Provider, RunOptions};
-use dfir_toolkit::common::bodyfile::Bodyfile3Line;
+use crate::filter::{Joinable, RunOptions};
+use crate::name_filter::NameFilter;
+use crate::stream::StreamSource;
+
+use super::{ExtendedBodyfileLine, ExtendedLineError};
 use std::convert::TryFrom;
-use std::sync::mpsc::{self, Receiver, Sender};
+use std::fs::File;
+use dfir_toolkit::common::bodyfile::BodyfileReader;
+use dfir_toolkit::common::Anonymizer;
+use encoding_rs_io::DecodeReaderBytesBuilder;
+use std::io::{BufReader, BufWriter, Write};
+use std::sync::mpsc::Sender;
+use std::sync::Arc;
 use std::thread::JoinHandle;
 
 pub struct BodyfileDecoder {
     worker: Option<JoinHandle<()>>,
-    rx: Option<Receiver<Bodyfile3Line>>,
 }
 
-impl Filter<String, Bodyfile3Line, ()> for BodyfileDecoder {
-    fn worker(reader: Receiver<String>, tx: Sender<Bodyfile3Line>, options: RunOptions) {
-        loop {
-            let mut line = match reader.recv() {
-                Err(_) => {
-                    break;
-                }
-                Ok(l) => l,
-            };
+/// reports malformed lines of a bodyfile
+pub struct LineDiagnostics {
+    /// name of the bodyfile, which is used in messages
+    input_name: String,
 
-            if line.starts_with('#') {
-                continue;
-            }
-            Self::trim_newline(&mut line);
+    /// label of the bodyfile, which is attached to every line
+    label: Option<Arc<str>>,
 
-            let bf_line = match Bodyfile3Line::try_from(line.as_ref()) {
-                Err(e) => {
-                    if options.strict_mode {
-                        log::warn!("bodyfile parser error: {}", e);
-                        panic!("failed while parsing: {:?}", line);
-                    } else {
-                        log::warn!("bodyfile parser error: {}", e);
-                        #[cfg(debug_assertions)]
-                        log::warn!("failed line was: {:?}", line);
-                    }
-                    continue;
-                }
-                Ok(l) => l,
-            };
+    /// receives the malformed lines, as they have been read
+    bad_lines: Option<BufWriter<File>>,
+    skipped_lines: usize,
+}
 
-            if tx.send(bf_line).is_err() {
-                break;
+impl LineDiagnostics {
+    pub fn new(input_name: String, bad_lines: Option<File>) -> Self {
+        Self {
+            input_name,
+            label: None,
+            bad_lines: bad_lines.map(BufWriter::new),
+            skipped_lines: 0,
+        }
+    }
+
+    pub fn with_label(mut self, label: Option<Arc<str>>) -> Self {
+        self.label = label;
+        self
+    }
+
+    /// handles a line which could not be parsed. In strict mode, mactime2 is
+    /// aborted before anything has been written to stdout, because the
+    /// timeline is only written after all lines have been read
+    fn malformed_line(
+        &mut self,
+        line_number: usize,
+        line: &str,
+        reason: &dyn std::fmt::Display,
+        strict_mode: bool,
+    ) {
+        if strict_mode {
+            log::error!(
+                "malformed bodyfile line {line_number} in '{}': {reason}: {:?}",
+                self.input_name, line
+            );
+            std::process::exit(1);
+        }
+
+        log::warn!(
+            "skipping malformed bodyfile line {line_number} in '{}': {reason}",
+            self.input_name
+        );
+        self.skipped_lines += 1;
+        if let Some(bad_lines) = self.bad_lines.as_mut() {
+            if let Err(why) = writeln!(bad_lines, "{line}") {
+                log::error!("unable to write malformed line: {why}");
+                self.bad_lines = None;
             }
         }
     }
-}
 
-impl Provider<Bodyfile3Line, ()> for BodyfileDecoder {
-    fn get_receiver(&mut self) -> Receiver<Bodyfile3Line> {
-        self.rx.take().unwrap()
+    fn finish(mut self) {
+        if let Some(mut bad_lines) = self.bad_lines.take() {
+            if let Err(why) = bad_lines.flush() {
+                log::error!("unable to write malformed lines: {why}");
+            }
+        }
+        if self.skipped_lines > 0 {
+            log::warn!(
+                "skipped {} malformed lines in '{}'",
+                self.skipped_lines, self.input_name
+            );
+        }
     }
 }
 
-impl Consumer<String> for BodyfileDecoder {
-    fn with_receiver(reader: Receiver<String>, options: RunOptions) -> Self {
-        let (tx, rx): (Sender<Bodyfile3Line>, Receiver<Bodyfile3Line>) = mpsc::channel();
+impl BodyfileDecoder {
+    /// decodes the lines of one bodyfile and sends them to `tx`, so that the
+    /// lines of multiple bodyfiles can be sent to the same sorter. Every line
+    /// is labeled with the source given by `diagnostics`, and is anonymized
+    /// after it has passed the name filter
+    pub fn with_sender(
+        source: StreamSource,
+        tx: Sender<ExtendedBodyfileLine>,
+        options: RunOptions,
+        diagnostics: LineDiagnostics,
+        name_filter: NameFilter,
+        anonymizer: Option<Arc<Anonymizer>>,
+    ) -> Self {
         Self {
             worker: Some(std::thread::spawn(move || {
-                Self::worker(reader, tx, options)
+                Self::decode(source, tx, options, diagnostics, name_filter, anonymizer)
             })),
-            rx: Some(rx),
         }
     }
-}
 
-impl BodyfileDecoder {
-    fn trim_newline(s: &mut String) {
-        if s.ends_with('\n') {
-            s.pop();
-            if s.ends_with('\r') {
-                s.pop();
+    fn decode(
+        source: StreamSource,
+        tx: Sender<ExtendedBodyfileLine>,
+        options: RunOptions,
+        mut diagnostics: LineDiagnostics,
+        name_filter: NameFilter,
+        anonymizer: Option<Arc<Anonymizer>>,
+    ) {
+        let source = DecodeReaderBytesBuilder::new()
+            .encoding(Some(encoding_rs::UTF_8))
+            .utf8_passthru(true)
+            .build(source);
+        let mut reader = BodyfileReader::new(BufReader::new(source));
+        let mut suppressed_lines = 0;
+        while let Some(line) = reader.next_line() {
+            let line = match line {
+                // a truncated or corrupted compressed stream must not yield
+                // an incomplete timeline. Nothing has been written yet,
+                // because the timeline is written after all lines have been read
+                Err(why) => {
+                    log::error!("{why}");
+                    std::process::exit(1);
+                }
+                Ok(line) => line,
+            };
+            let line_number = reader.line_number();
+
+            let bf_line = match ExtendedBodyfileLine::try_from(line.as_str()) {
+                Err(ExtendedLineError::Bodyfile(e)) => {
+                    let reason = e.describe(&line);
+                    diagnostics.malformed_line(line_number, &line, &reason, options.strict_mode);
+                    continue;
+                }
+                Err(e) => {
+                    diagnostics.malformed_line(line_number, &line, &e, options.strict_mode);
+                    continue;
+                }
+                Ok(l) => l.with_source(diagnostics.label.clone()),
+            };
+
+            if !name_filter.matches(bf_line.get_name()) {
+                suppressed_lines += 1;
+                continue;
             }
+
+            let bf_line = match anonymizer.as_deref() {
+                Some(anonymizer) => bf_line.anonymize(anonymizer),
+                None => bf_line,
+            };
+
+            if tx.send(bf_line).is_err() {
+                break;
+            }
+        }
+        diagnostics.finish();
+
+        if name_filter.is_restricted() {
+            log::warn!(
+                "suppressed {suppressed_lines} bodyfile lines by '--include' and '--exclude'"
+            );
         }
     }
 }
@@ -1,38 +1,45 @@
 use dfir_toolkit::common::bodyfile::{BehavesLikeI64, Bodyfile3Line};
-use std::borrow::Borrow;
+use dfir_toolkit::common::FileOutput;
 use std::cmp::Ordering;
-use std::collections::{BTreeMap, HashSet};
-use std::io::{Stdout, Write};
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::PathBuf;
 use std::sync::mpsc::Receiver;
 use std::sync::Arc;
 use std::thread::JoinHandle;
 
 use crate::error::MactimeError;
 use crate::filter::{Joinable, RunOptions, Runnable, Sorter};
+use crate::time_window::TimeWindow;
 
-use super::MACBFlags;
+use super::{DedupField, Deduplicator, ExtendedBodyfileLine, MACBFlags, TimelineBuffer};
 
 pub trait Mactime2Writer<W>: Send
 where
     W: Write + Send
 {
     fn write_line(&mut self, timestamp: &i64, entry: &ListEntry) -> std::io::Result<()>;
-    
-    #[allow(dead_code)]
-    fn into_writer(self) -> W;
+
+    /// writes all buffered entries, which must be done after the last entry
+    fn flush(&mut self) -> std::io::Result<()>;
+
+    fn into_writer(self: Box<Self>) -> W;
 }
 
 #[derive(Default)]
 pub struct BodyfileSorter {
     worker: Option<JoinHandle<Result<(), MactimeError>>>,
-    receiver: Option<Receiver<Bodyfile3Line>>,
-    output: Option<Box<dyn Mactime2Writer<Stdout>>>,
+    receiver: Option<Receiver<ExtendedBodyfileLine>>,
+    output: Option<Box<dyn Mactime2Writer<FileOutput>>>,
+    time_window: TimeWindow,
+    timeline: Option<TimelineBuffer>,
+    dedup: Option<Deduplicator>,
 }
 
 #[derive(Debug)]
 pub struct ListEntry {
     pub flags: MACBFlags,
-    pub line: Arc<Bodyfile3Line>,
+    pub line: Arc<ExtendedBodyfileLine>,
 }
 
 impl Eq for ListEntry {}
@@ -58,9 +65,10 @@ impl Ord for ListEntry {
 }
 
 fn insert_timestamp(
-    entries: &mut BTreeMap<i64, Vec<ListEntry>>,
+    entries: &mut TimelineBuffer,
     flag: MACBFlags,
-    line: Arc<Bodyfile3Line>,
+    line: Arc<ExtendedBodyfileLine>,
+    time_window: &TimeWindow,
 ) {
     let timestamp = if flag.contains(MACBFlags::M) {
         *line.get_mtime().as_ref().unwrap()
@@ -73,20 +81,11 @@ fn insert_timestamp(
     } else {
         -1
     };
-
-    match entries.get_mut(&timestamp) {
-        None => {
-            let mut entries_at_ts = Vec::new();
-            let entry = ListEntry { flags: flag, line };
-            entries_at_ts.push(entry);
-            entries.insert(timestamp, entries_at_ts);
-        }
-
-        Some(entries_at_ts) => {
-            let entry = ListEntry { flags: flag, line };
-            entries_at_ts.push(entry);
-        }
+    if !time_window.contains(timestamp) {
+        return;
     }
+
+    entries.insert(timestamp, ListEntry { flags: flag, line });
 }
 
 impl Runnable for BodyfileSorter {
@@ -99,27 +98,56 @@ impl Runnable for BodyfileSorter {
             .output
             .take()
             .expect("no output provided; please call with_output()");
-        self.worker = Some(std::thread::spawn(move || Self::worker(receiver, output)));
+        let time_window = self.time_window;
+        let timeline = self.timeline.take().unwrap_or_default();
+        let dedup = self.dedup.take();
+        self.worker = Some(std::thread::spawn(move || {
+            Self::worker(receiver, output, time_window, timeline, dedup)
+        }));
     }
 }
 
 impl BodyfileSorter {
-    pub fn with_receiver(mut self, decoder: Receiver<Bodyfile3Line>, _: RunOptions) -> Self {
+    pub fn with_receiver(
+        mut self,
+        decoder: Receiver<ExtendedBodyfileLine>,
+        options: RunOptions,
+    ) -> Self {
         self.receiver = Some(decoder);
+        self.time_window = options.time_window;
         self
     }
 
-    pub fn with_output(mut self, output: Box<dyn Mactime2Writer<Stdout>>) -> Self {
+    /// sets the number of bytes which may be used to sort the timeline in
+    /// memory. If more memory is needed, sorted runs are written into
+    /// temporary files in `tmpdir`
+    pub fn with_max_memory(mut self, max_memory: Option<usize>, tmpdir: PathBuf) -> Self {
+        self.timeline = Some(TimelineBuffer::default().with_max_memory(max_memory, tmpdir));
+        self
+    }
+
+    /// suppresses entries which are equal to an earlier entry in `fields`
+    pub fn with_dedup(mut self, fields: Option<&[DedupField]>) -> Self {
+        self.dedup = fields.map(Deduplicator::new);
+        self
+    }
+
+    pub fn with_output(mut self, output: Box<dyn Mactime2Writer<FileOutput>>) -> Self {
         self.output = Some(output);
         self
     }
 
     fn worker(
-        decoder: Receiver<Bodyfile3Line>,
-        mut output: Box<dyn Mactime2Writer<Stdout>>,
+        decoder: Receiver<ExtendedBodyfileLine>,
+        mut output: Box<dyn Mactime2Writer<FileOutput>>,
+        time_window: TimeWindow,
+        mut entries: TimelineBuffer,
+        mut dedup: Option<Deduplicator>,
     ) -> Result<(), MactimeError> {
-        let mut entries: BTreeMap<i64, Vec<ListEntry>> = BTreeMap::new();
-        let mut names: HashSet<(String, String)> = HashSet::new();
+        let mut names: HashSet<(Option<String>, String, String)> = HashSet::new();
+
+        // the names of all files would need as much memory as the timeline
+        let check_names = !entries.has_memory_limit();
 
         loop {
             let line = Arc::new(match decoder.recv() {
@@ -129,17 +157,29 @@ impl BodyfileSorter {
                 Ok(l) => l,
             });
 
-            // each name && inode SHOULD occur only once
-            {
-                let bf: &Bodyfile3Line = line.borrow();
-                if names.contains(&(bf.get_inode().to_owned(), bf.get_name().to_owned())) {
-                    log::warn!(
-                        "ambigious file name: '{}' and inode '{}'",
-                        bf.get_name(),
-                        bf.get_inode()
-                    );
+            // each name && inode SHOULD occur only once per source
+            if check_names {
+                let bf: &Bodyfile3Line = &line;
+                let key = (
+                    line.source().map(str::to_owned),
+                    bf.get_inode().to_owned(),
+                    bf.get_name().to_owned(),
+                );
+                if names.contains(&key) {
+                    match line.source() {
+                        Some(source) => log::warn!(
+                            "ambigious file name in '{source}': '{}' and inode '{}'",
+                            bf.get_name(),
+                            bf.get_inode()
+                        ),
+                        None => log::warn!(
+                            "ambigious file name: '{}' and inode '{}'",
+                            bf.get_name(),
+                            bf.get_inode()
+                        ),
+                    }
                 }
-                names.insert((bf.get_inode().to_owned(), bf.get_name().to_owned()));
+                names.insert(key);
             } // delete the borrow to line
 
             // we need *some* value in mactimes!
@@ -148,7 +188,13 @@ impl BodyfileSorter {
                 && line.get_ctime().is_none()
                 && line.get_crtime().is_none()
             {
-                insert_timestamp(&mut entries, MACBFlags::NONE, Arc::clone(&line));
+                insert_timestamp(
+                    &mut entries,
+                    MACBFlags::NONE,
+                    Arc::clone(&line),
+                    &time_window,
+                );
+                entries.limit_memory_usage()?;
                 continue;
             }
 
@@ -186,15 +232,26 @@ impl BodyfileSorter {
             }
             for flag in flags.iter() {
                 if flag != &MACBFlags::NONE {
-                    insert_timestamp(&mut entries, *flag, Arc::clone(&line));
+                    insert_timestamp(&mut entries, *flag, Arc::clone(&line), &time_window);
                 }
             }
+            entries.limit_memory_usage()?;
         }
 
-        for (ts, entries_at_ts) in entries.iter() {
-            for line in entries_at_ts {
-                output.write_line(ts, line)?;
+        entries.write_to(|timestamp, entry| {
+            let is_duplicate = dedup
+                .as_mut()
+                .is_some_and(|dedup| dedup.is_duplicate(*timestamp, entry));
+            if is_duplicate {
+                Ok(())
+            } else {
+                output.write_line(timestamp, entry)
             }
+        })?;
+        output.flush()?;
+        output.into_writer().commit()?;
+        if let Some(dedup) = dedup {
+            log::info!("removed {} duplicate entries", dedup.removed());
         }
         Ok(())
     }
@@ -0,0 +1,132 @@
+use std::collections::HashSet;
+
+use clap::ValueEnum;
+
+use super::ListEntry;
+
+/// fields of a timeline entry which can be compared to detect duplicates
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DedupField {
+    Timestamp,
+    Macb,
+    Name,
+    Size,
+    Inode,
+    Mode,
+    Uid,
+    Gid,
+    Md5,
+}
+
+/// the fields which are compared by `--dedup`
+pub const DEFAULT_DEDUP_FIELDS: [DedupField; 5] = [
+    DedupField::Timestamp,
+    DedupField::Macb,
+    DedupField::Name,
+    DedupField::Size,
+    DedupField::Inode,
+];
+
+/// detects timeline entries which are equal to an earlier entry in the
+/// selected fields. The entries must be sorted by their timestamps. If the
+/// timestamp is one of the fields, only the keys of the entries with the
+/// current timestamp need to be stored
+pub struct Deduplicator {
+    fields: Vec<DedupField>,
+    current_timestamp: Option<i64>,
+    seen: HashSet<String>,
+    removed: usize,
+}
+
+impl Deduplicator {
+    pub fn new(fields: &[DedupField]) -> Self {
+        let mut unique_fields = Vec::with_capacity(fields.len());
+        for field in fields {
+            if !unique_fields.contains(field) {
+                unique_fields.push(*field);
+            }
+        }
+        Self {
+            fields: unique_fields,
+            current_timestamp: None,
+            seen: HashSet::new(),
+            removed: 0,
+        }
+    }
+
+    pub fn is_duplicate(&mut self, timestamp: i64, entry: &ListEntry) -> bool {
+        if self.fields.contains(&DedupField::Timestamp) && self.current_timestamp != Some(timestamp)
+        {
+            self.current_timestamp = Some(timestamp);
+            self.seen.clear();
+        }
+
+        let key = self
+            .fields
+            .iter()
+            .map(|field| match field {
+                DedupField::Timestamp => timestamp.to_string(),
+                DedupField::Macb => entry.flags.to_string(),
+                DedupField::Name => entry.line.get_name().to_owned(),
+                DedupField::Size => entry.line.get_size().to_string(),
+                DedupField::Inode => entry.line.get_inode().to_owned(),
+                DedupField::Mode => entry.line.get_mode_as_string().to_owned(),
+                DedupField::Uid => entry.line.get_uid().to_string(),
+                DedupField::Gid => entry.line.get_gid().to_string(),
+                DedupField::Md5 => entry.line.get_md5().to_owned(),
+            })
+            .collect::<Vec<_>>()
+            .join("\0");
+
+        if self.seen.insert(key) {
+            false
+        } else {
+            self.removed += 1;
+            true
+        }
+    }
+
+    /// the number of entries which have been detected as duplicates
+    pub fn removed(&self) -> usize {
+        self.removed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use dfir_toolkit::common::bodyfile::Bodyfile3Line;
+
+    use super::{DedupField, Deduplicator, DEFAULT_DEDUP_FIELDS};
+    use crate::bodyfile::{ListEntry, MACBFlags};
+
+    fn entry(name: &str, size: u64, flags: MACBFlags) -> ListEntry {
+        ListEntry {
+            flags,
+            line: Arc::new(Bodyfile3Line::new().with_name(name).with_size(size).into()),
+        }
+    }
+
+    #[test]
+    fn detect_duplicates() {
+        let mut dedup = Deduplicator::new(&DEFAULT_DEDUP_FIELDS);
+        assert!(!dedup.is_duplicate(1, &entry("/a", 1, MACBFlags::M)));
+        assert!(!dedup.is_duplicate(1, &entry("/b", 1, MACBFlags::M)));
+        assert!(dedup.is_duplicate(1, &entry("/a", 1, MACBFlags::M)));
+        assert!(!dedup.is_duplicate(1, &entry("/a", 2, MACBFlags::M)));
+        assert!(!dedup.is_duplicate(1, &entry("/a", 1, MACBFlags::A)));
+        assert!(!dedup.is_duplicate(2, &entry("/a", 1, MACBFlags::M)));
+        assert!(dedup.is_duplicate(2, &entry("/a", 1, MACBFlags::M)));
+        assert_eq!(dedup.removed(), 2);
+    }
+
+    #[test]
+    fn detect_duplicates_by_name() {
+        let mut dedup = Deduplicator::new(&[DedupField::Name, DedupField::Name]);
+        assert!(!dedup.is_duplicate(1, &entry("/a", 1, MACBFlags::M)));
+        assert!(dedup.is_duplicate(2, &entry("/a", 2, MACBFlags::A)));
+        assert!(!dedup.is_duplicate(3, &entry("/b", 1, MACBFlags::M)));
+        assert_eq!(dedup.removed(), 1);
+    }
+}
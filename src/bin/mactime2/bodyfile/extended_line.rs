@@ -0,0 +1,219 @@
+use std::fmt;
+use std::ops::Deref;
+use std::sync::Arc;
+
+use dfir_toolkit::common::bodyfile::{Bodyfile3Line, Bodyfile3ParserError};
+use dfir_toolkit::common::Anonymizer;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+/// a line of the input, which is either a classic bodyfile line or an
+/// extended line. An extended line is a JSON object with the fields of a
+/// bodyfile line and arbitrary additional attributes, e.g.
+///
+/// ```json
+/// {"name": "logon of 'admin'", "mtime": 1715845546, "event_id": 4624}
+/// ```
+///
+/// Only `name` is required. The other bodyfile fields are `md5`, `inode`,
+/// `mode`, `uid`, `gid`, `size`, `atime`, `mtime`, `ctime` and `crtime`, and
+/// missing timestamps are treated like `-1`. All other fields are kept as
+/// attributes, which are passed through to the JSON output
+#[derive(Debug)]
+pub struct ExtendedBodyfileLine {
+    line: Bodyfile3Line,
+    attributes: Option<Map<String, Value>>,
+
+    /// label of the bodyfile which contained this line
+    source: Option<Arc<str>>,
+}
+
+#[derive(Error, Debug)]
+pub enum ExtendedLineError {
+    #[error(transparent)]
+    Bodyfile(#[from] Bodyfile3ParserError),
+
+    #[error("invalid extended bodyfile line: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("invalid timestamp {0} in extended bodyfile line")]
+    IllegalTimestamp(i64),
+}
+
+impl ExtendedBodyfileLine {
+    /// the additional attributes of an extended line, or `None` if this is a
+    /// classic bodyfile line
+    pub fn attributes(&self) -> Option<&Map<String, Value>> {
+        self.attributes.as_ref()
+    }
+
+    pub fn source(&self) -> Option<&str> {
+        self.source.as_deref()
+    }
+
+    pub fn with_source(mut self, source: Option<Arc<str>>) -> Self {
+        self.source = source;
+        self
+    }
+
+    /// replaces user names, host names and IP addresses in the name and in
+    /// the attributes by pseudonyms
+    pub fn anonymize(mut self, anonymizer: &Anonymizer) -> Self {
+        let name = anonymizer.anonymize_text(self.line.get_name());
+        self.line = self.line.with_owned_name(name);
+        if let Some(attributes) = self.attributes.as_mut() {
+            anonymizer.anonymize_map(attributes);
+        }
+        self
+    }
+
+    fn from_json(line: &str) -> Result<Self, ExtendedLineError> {
+        let json: JsonBodyfileLine = serde_json::from_str(line)?;
+        let time = |t: Option<i64>| match t {
+            Some(t) if t < -1 => Err(ExtendedLineError::IllegalTimestamp(t)),
+            t => Ok(t.unwrap_or(-1)),
+        };
+        let line = Bodyfile3Line::new()
+            .with_owned_md5(json.md5.unwrap_or_else(|| "0".to_owned()))
+            .with_owned_name(json.name)
+            .with_owned_inode(json.inode.unwrap_or_else(|| "0".to_owned()))
+            .with_owned_mode(json.mode.unwrap_or_default())
+            .with_uid(json.uid)
+            .with_gid(json.gid)
+            .with_size(json.size)
+            .with_atime(time(json.atime)?.into())
+            .with_mtime(time(json.mtime)?.into())
+            .with_ctime(time(json.ctime)?.into())
+            .with_crtime(time(json.crtime)?.into());
+        Ok(Self {
+            line,
+            attributes: Some(json.attributes),
+            source: None,
+        })
+    }
+}
+
+impl From<Bodyfile3Line> for ExtendedBodyfileLine {
+    fn from(line: Bodyfile3Line) -> Self {
+        Self {
+            line,
+            attributes: None,
+            source: None,
+        }
+    }
+}
+
+impl Deref for ExtendedBodyfileLine {
+    type Target = Bodyfile3Line;
+
+    fn deref(&self) -> &Self::Target {
+        &self.line
+    }
+}
+
+impl TryFrom<&str> for ExtendedBodyfileLine {
+    type Error = ExtendedLineError;
+
+    /// parses an extended line if `line` begins with `{`, and a classic
+    /// bodyfile line otherwise
+    fn try_from(line: &str) -> Result<Self, Self::Error> {
+        if line.trim_start().starts_with('{') {
+            Self::from_json(line)
+        } else {
+            Ok(Bodyfile3Line::try_from(line)?.into())
+        }
+    }
+}
+
+impl fmt::Display for ExtendedBodyfileLine {
+    /// writes the line in the format it has been read from, so that it can
+    /// be parsed again. The source is not written
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some(attributes) = &self.attributes else {
+            return self.line.fmt(f);
+        };
+        let json = JsonBodyfileLineRef {
+            line: &self.line,
+            attributes,
+        };
+        f.write_str(&serde_json::to_string(&json).map_err(|_| fmt::Error)?)
+    }
+}
+
+#[derive(Deserialize)]
+struct JsonBodyfileLine {
+    md5: Option<String>,
+    name: String,
+    inode: Option<String>,
+    mode: Option<String>,
+    #[serde(default)]
+    uid: u64,
+    #[serde(default)]
+    gid: u64,
+    #[serde(default)]
+    size: u64,
+    atime: Option<i64>,
+    mtime: Option<i64>,
+    ctime: Option<i64>,
+    crtime: Option<i64>,
+
+    #[serde(flatten)]
+    attributes: Map<String, Value>,
+}
+
+#[derive(Serialize)]
+struct JsonBodyfileLineRef<'l> {
+    #[serde(flatten)]
+    line: &'l Bodyfile3Line,
+
+    #[serde(flatten)]
+    attributes: &'l Map<String, Value>,
+}
+
+#[cfg(test)]
+mod tests {
+    use dfir_toolkit::common::bodyfile::BehavesLikeI64;
+    use serde_json::json;
+
+    use super::{ExtendedBodyfileLine, ExtendedLineError};
+
+    #[test]
+    fn parse_extended_line() {
+        let line = ExtendedBodyfileLine::try_from(
+            r#"{"name": "logon of 'admin'", "mtime": 1715845546, "atime": -1, "event_id": 4624, "data": {"user": "admin"}}"#,
+        )
+        .unwrap();
+        assert_eq!(line.get_name(), "logon of 'admin'");
+        assert_eq!(line.get_mtime().as_ref(), Some(&1715845546));
+        assert!(line.get_atime().is_none());
+        assert!(line.get_crtime().is_none());
+        assert_eq!(line.get_inode(), "0");
+        assert_eq!(
+            serde_json::Value::from(line.attributes().unwrap().clone()),
+            json!({"event_id": 4624, "data": {"user": "admin"}})
+        );
+
+        // the line must survive being written into a sorted run
+        let copy = ExtendedBodyfileLine::try_from(line.to_string().as_str()).unwrap();
+        assert_eq!(copy.to_string(), line.to_string());
+        assert_eq!(copy.attributes(), line.attributes());
+
+        let classic = "0|/etc/passwd|1|r/rrw-r--r--|0|0|0|-1|1715845546|-1|-1";
+        let line = ExtendedBodyfileLine::try_from(classic).unwrap();
+        assert!(line.attributes().is_none());
+        assert_eq!(line.to_string(), classic);
+    }
+
+    #[test]
+    fn reject_invalid_extended_line() {
+        assert!(matches!(
+            ExtendedBodyfileLine::try_from(r#"{"mtime": 1}"#),
+            Err(ExtendedLineError::Json(_))
+        ));
+        assert!(matches!(
+            ExtendedBodyfileLine::try_from(r#"{"name": "a", "mtime": -2}"#),
+            Err(ExtendedLineError::IllegalTimestamp(-2))
+        ));
+    }
+}
@@ -1,9 +1,13 @@
 mod bodyfile_decoder;
-mod bodyfile_reader;
 mod bodyfile_sorter;
+mod deduplicator;
+mod extended_line;
 mod macb_flags;
+mod timeline_buffer;
 
 pub use bodyfile_decoder::*;
-pub use bodyfile_reader::*;
 pub use bodyfile_sorter::*;
-pub use macb_flags::*;
\ No newline at end of file
+pub use deduplicator::*;
+pub use extended_line::*;
+pub use macb_flags::*;
+pub use timeline_buffer::*;
\ No newline at end of file
@@ -0,0 +1,278 @@
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap, HashMap};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Error, ErrorKind, Seek, Write};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use super::{ExtendedBodyfileLine, ListEntry, MACBFlags};
+
+/// estimated number of bytes which are needed to store a timeline entry,
+/// without the strings of its bodyfile line
+const ENTRY_OVERHEAD: usize = std::mem::size_of::<ListEntry>() + 32;
+
+/// timeline entries, sorted by their timestamps. Entries with the same
+/// timestamp keep the order in which they have been inserted.
+///
+/// If a memory limit is set and the entries need more memory than that, they
+/// are written as a sorted run into a temporary file. All runs are merged when
+/// the timeline is written. The temporary files have no name in the file
+/// system (or are deleted on close on Windows), so they are removed by the
+/// operating system even if mactime2 is interrupted
+pub struct TimelineBuffer {
+    entries: BTreeMap<i64, Vec<ListEntry>>,
+    memory_usage: usize,
+    max_memory: Option<usize>,
+    tmpdir: PathBuf,
+    runs: Vec<File>,
+}
+
+impl Default for TimelineBuffer {
+    fn default() -> Self {
+        Self {
+            entries: BTreeMap::new(),
+            memory_usage: 0,
+            max_memory: None,
+            tmpdir: std::env::temp_dir(),
+            runs: Vec::new(),
+        }
+    }
+}
+
+impl TimelineBuffer {
+    pub fn with_max_memory(mut self, max_memory: Option<usize>, tmpdir: PathBuf) -> Self {
+        self.max_memory = max_memory;
+        self.tmpdir = tmpdir;
+        self
+    }
+
+    pub fn has_memory_limit(&self) -> bool {
+        self.max_memory.is_some()
+    }
+
+    pub fn insert(&mut self, timestamp: i64, entry: ListEntry) {
+        self.memory_usage += ENTRY_OVERHEAD + line_size(&entry.line);
+        self.entries.entry(timestamp).or_default().push(entry);
+    }
+
+    /// writes the entries into a sorted run if they need more memory than
+    /// allowed. This should only be called after all entries of a bodyfile line
+    /// have been inserted, because the memory of the line is freed only then
+    pub fn limit_memory_usage(&mut self) -> std::io::Result<()> {
+        match self.max_memory {
+            Some(max_memory) if self.memory_usage > max_memory => self.write_run(),
+            _ => Ok(()),
+        }
+    }
+
+    fn write_run(&mut self) -> std::io::Result<()> {
+        let entries = std::mem::take(&mut self.entries);
+        self.memory_usage = 0;
+        if entries.is_empty() {
+            return Ok(());
+        }
+
+        let mut writer = BufWriter::new(tempfile::tempfile_in(&self.tmpdir).map_err(|why| {
+            Error::new(
+                why.kind(),
+                format!(
+                    "unable to create a temporary file in '{}': {why}",
+                    self.tmpdir.display()
+                ),
+            )
+        })?);
+        for (timestamp, entries_at_ts) in entries {
+            for entry in entries_at_ts {
+                // the source is quoted, because it can contain tabs
+                writeln!(
+                    writer,
+                    "{timestamp}\t{}\t{}\t{}",
+                    entry.flags.bits(),
+                    serde_json::to_string(&entry.line.source())?,
+                    entry.line
+                )?;
+            }
+        }
+        let mut run = writer.into_inner().map_err(|why| why.into_error())?;
+        run.rewind()?;
+        log::info!(
+            "wrote sorted run #{} to a temporary file",
+            self.runs.len() + 1
+        );
+        self.runs.push(run);
+        Ok(())
+    }
+
+    /// passes all entries to `write_line`, in the order of their timestamps
+    pub fn write_to<F>(mut self, mut write_line: F) -> std::io::Result<()>
+    where
+        F: FnMut(&i64, &ListEntry) -> std::io::Result<()>,
+    {
+        if self.runs.is_empty() {
+            for (timestamp, entries_at_ts) in self.entries.iter() {
+                for entry in entries_at_ts {
+                    write_line(timestamp, entry)?;
+                }
+            }
+            return Ok(());
+        }
+
+        self.write_run()?;
+        let mut runs: Vec<_> = self.runs.into_iter().map(RunReader::from).collect();
+
+        // if entries of different runs have the same timestamp, the entry of
+        // the earlier run has been inserted first
+        let mut heads = BinaryHeap::new();
+        for (index, run) in runs.iter_mut().enumerate() {
+            if let Some(timestamp) = run.next_entry()? {
+                heads.push(Reverse((timestamp, index)));
+            }
+        }
+        while let Some(Reverse((timestamp, index))) = heads.pop() {
+            let run = &mut runs[index];
+            write_line(&timestamp, run.current.as_ref().unwrap())?;
+            if let Some(timestamp) = run.next_entry()? {
+                heads.push(Reverse((timestamp, index)));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// reads the entries of a sorted run, one after another
+struct RunReader {
+    reader: BufReader<File>,
+    buffer: String,
+    current: Option<ListEntry>,
+
+    /// the sources which have been read, so that they are not allocated for
+    /// every entry
+    sources: HashMap<String, Arc<str>>,
+}
+
+impl From<File> for RunReader {
+    fn from(file: File) -> Self {
+        Self {
+            reader: BufReader::new(file),
+            buffer: String::new(),
+            current: None,
+            sources: HashMap::new(),
+        }
+    }
+}
+
+impl RunReader {
+    /// reads the next entry and returns its timestamp, or `None` if the run
+    /// has been read completely
+    fn next_entry(&mut self) -> std::io::Result<Option<i64>> {
+        self.buffer.clear();
+        if self.reader.read_line(&mut self.buffer)? == 0 {
+            self.current = None;
+            return Ok(None);
+        }
+        let invalid = || Error::new(ErrorKind::InvalidData, "invalid entry in a sorted run");
+        let mut columns = self.buffer.trim_end_matches('\n').splitn(4, '\t');
+        let (Some(timestamp), Some(flags), Some(source), Some(line)) = (
+            columns.next(),
+            columns.next(),
+            columns.next(),
+            columns.next(),
+        ) else {
+            return Err(invalid());
+        };
+        let timestamp = timestamp.parse().map_err(|_| invalid())?;
+        let flags = flags
+            .parse()
+            .ok()
+            .and_then(MACBFlags::from_bits)
+            .ok_or_else(invalid)?;
+        let source = match serde_json::from_str::<Option<String>>(source).map_err(|_| invalid())? {
+            Some(source) => Some(Arc::clone(
+                self.sources
+                    .entry(source)
+                    .or_insert_with_key(|source| Arc::from(source.as_str())),
+            )),
+            None => None,
+        };
+        let line = ExtendedBodyfileLine::try_from(line)
+            .map_err(|_| invalid())?
+            .with_source(source);
+        self.current = Some(ListEntry {
+            flags,
+            line: Arc::new(line),
+        });
+        Ok(Some(timestamp))
+    }
+}
+
+/// estimated number of bytes which are needed to store the strings of a
+/// bodyfile line. The line is shared by up to four entries, but the share of
+/// every entry is counted, which is a safe overestimation
+fn line_size(line: &ExtendedBodyfileLine) -> usize {
+    let attributes = line
+        .attributes()
+        .and_then(|attributes| serde_json::to_string(attributes).ok())
+        .map(|attributes| attributes.len())
+        .unwrap_or_default();
+    std::mem::size_of::<ExtendedBodyfileLine>()
+        + attributes
+        + line.get_md5().len()
+        + line.get_name().len()
+        + line.get_inode().len()
+        + line.get_mode_as_string().len()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use dfir_toolkit::common::bodyfile::Bodyfile3Line;
+
+    use super::TimelineBuffer;
+    use crate::bodyfile::{ListEntry, MACBFlags};
+
+    fn timeline(mut buffer: TimelineBuffer) -> (usize, Vec<(i64, String)>) {
+        for (index, timestamp) in [5, 3, 5, 1, 3, 5, 2, 4, 1, 5].into_iter().enumerate() {
+            let line = Bodyfile3Line::new()
+                .with_name(&format!("/file\t|{index}"))
+                .with_inode(&index.to_string())
+                .with_mtime(timestamp.into());
+            buffer.insert(
+                timestamp,
+                ListEntry {
+                    flags: MACBFlags::M,
+                    line: Arc::new(line.into()),
+                },
+            );
+            buffer.limit_memory_usage().unwrap();
+        }
+        let runs = buffer.runs.len();
+        let mut timeline = Vec::new();
+        buffer
+            .write_to(|timestamp, entry| {
+                timeline.push((*timestamp, entry.line.get_name().to_owned()));
+                Ok(())
+            })
+            .unwrap();
+        (runs, timeline)
+    }
+
+    #[test]
+    fn merge_sorted_runs_stable() {
+        let (runs, in_memory) = timeline(TimelineBuffer::default());
+        assert_eq!(runs, 0);
+        assert_eq!(in_memory[0], (1, "/file\t|3".to_owned()));
+        assert_eq!(in_memory[9], (5, "/file\t|9".to_owned()));
+
+        let tmpdir = std::env::temp_dir();
+        let (runs, spilled) = timeline(TimelineBuffer::default().with_max_memory(Some(0), tmpdir));
+        assert_eq!(runs, 10);
+        assert_eq!(in_memory, spilled);
+
+        let tmpdir = std::env::temp_dir();
+        let (runs, spilled) =
+            timeline(TimelineBuffer::default().with_max_memory(Some(1000), tmpdir));
+        assert!(runs > 1 && runs < 10);
+        assert_eq!(in_memory, spilled);
+    }
+}
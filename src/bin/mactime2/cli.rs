@@ -1,33 +1,65 @@
-use chrono_tz::Tz;
-use clap::{Parser, ValueHint};
-use clio::Input;
+use std::path::PathBuf;
+
+use clap::{ColorChoice, Parser, ValueHint};
 use log::LevelFilter;
+use regex::Regex;
 
-use dfir_toolkit::common::{HasVerboseFlag, TzArgument};
+use dfir_toolkit::common::{
+    AnonymizeArgs, HasVerboseFlag, OutputArgs, Rfc3339Datetime, Timezone, Verbosity,
+};
 
+use super::bodyfile::DedupField;
+use super::labeled_input::{parse_labeled_input, LabeledInput};
+use super::output::SplitBy;
 use super::OutputFormat;
 
-#[cfg(feature = "gzip")]
 const BODYFILE_HELP: &str =
-    "path to input file or '-' for stdin (files ending with .gz will be treated as being gzipped)";
-#[cfg(not(feature = "gzip"))]
-const BODYFILE_HELP: &str = "path to input file or '-' for stdin";
+    "path to input file or '-' for stdin (gzip and zstd compressed input is detected by the \
+     extension .gz or .zst or by its content, and is decompressed while reading). \
+     Can be specified multiple times to merge bodyfiles, optionally with a label like \
+     'host1:fs.bodyfile', which is written as source of every entry (default: the path)";
 
 const AFTER_HELP: &str = color_print::cstr!(
     r##"<red><bold>IMPORTANT</bold>
 
 Note that POSIX specifies that all UNIX timestamps are UTC timestamps. It is
 up to you to ensure that the bodyfile only contains UNIX timestamps that
-comply with the POSIX standard.</red>"##
+comply with the POSIX standard.</red>
+
+<bold>COLORS</bold>
+
+Rows of the TXT output are colored by the source of their entries. Bodyfile
+producers tag their entries by prefixing the name with '<<Tag>>: ', e.g.
+"Prefetch: run 'CMD.EXE'". The following tags are recognized (case-insensitive):
+
+  <yellow>Evtx, Event, EventLog</yellow>
+  <cyan>Registry, ShimCache, ShellBag, UserAssist, Search</cyan>
+  <green>Prefetch</green>
+  <magenta>Lnk</magenta>
+  <blue>Zip</blue>
+
+Entries without a tag are file system entries and are not colored.
+
+<bold>EXTENDED BODYFILES</bold>
+
+Besides classic bodyfile lines, the input can contain extended lines, which
+are JSON objects like the ones written by 'evtx2bodyfile --format bodyfile-json':
+
+  {"name": "logon of 'admin'", "mtime": 1715845546, "event_id": 4624}
+
+Only 'name' is required. The other bodyfile fields are 'md5', 'inode', 'mode',
+'uid', 'gid', 'size', 'atime', 'mtime', 'ctime' and 'crtime', where missing
+timestamps are treated like -1. All other fields are attributes, which are
+written as field 'attributes' by the formats 'json' and 'jsonl'. Classic and
+extended lines can be mixed."##
 );
 
 /// Replacement for `mactime`
 #[derive(Parser)]
 #[clap(name="mactime2", author, version, long_about = None, after_help=AFTER_HELP)]
-
 pub struct Cli {
-    #[clap(short('b'), value_parser, value_hint=ValueHint::FilePath, default_value="-", help=BODYFILE_HELP, display_order(100))]
-    pub(crate) input_file: Input,
+    #[clap(short('b'), value_name("INPUT_FILE"), value_parser(parse_labeled_input), value_hint=ValueHint::FilePath, default_value="-", help=BODYFILE_HELP, display_order(100))]
+    pub(crate) input_files: Vec<LabeledInput>,
 
     /// output format, if not specified, default value is 'txt'
     #[clap(
@@ -58,6 +90,22 @@ pub struct Cli {
     )]
     pub(crate) show_headers: bool,
 
+    /// delimiter of the CSV output, which must be a single ASCII character (use '\t' for
+    /// tabs). Implies `--format=csv` if no format has been specified
+    #[clap(
+        id("delimiter"),
+        long("delimiter"),
+        value_parser(parse_delimiter),
+        display_order(616),
+        conflicts_with("json")
+    )]
+    pub(crate) delimiter: Option<u8>,
+
+    /// write a UTF-8 byte order mark at the beginning of the CSV output, so that spreadsheet
+    /// applications detect the encoding. Implies `--format=csv` if no format has been specified
+    #[clap(id("bom"), long("bom"), display_order(617), conflicts_with("json"))]
+    pub(crate) bom: bool,
+
     /// output as JSON instead of TXT. This is a convenience option, which is identical to `--format=json`
     /// and will be removed in a future release.
     #[clap(
@@ -67,19 +115,154 @@ pub struct Cli {
         conflicts_with_all(["csv", "format", "show-headers"]))]
     pub(crate) json_format: bool,
 
-    /// name of offset of destination timezone (or 'list' to display all possible values
-    #[clap(short('t'), long("to-timezone"), display_order(400), default_value_t=TzArgument::Tz(Tz::UTC))]
-    pub dst_zone: TzArgument,
+    /// use the field names of the Elastic Common Schema in the JSON output ('@timestamp',
+    /// 'file.path', 'file.size', 'event.action', ...). Implies `--format=jsonl` if no format
+    /// has been specified
+    #[clap(
+        id("ecs"),
+        long("ecs"),
+        display_order(625),
+        conflicts_with_all(["csv", "show-headers", "delimiter", "bom"]))]
+    pub(crate) ecs: bool,
+
+    /// split the timeline into multiple files, which are written into the directory given by
+    /// `--output-dir` and are named like 'timeline-2024-03-12.csv'. Every file begins with its
+    /// own header row. Entries with zero timestamps are written into 'timeline-epoch.csv'
+    #[clap(long("split-by"), value_enum, requires("output_dir"), display_order(626))]
+    pub(crate) split_by: Option<SplitBy>,
+
+    /// directory where the files created by `--split-by` are written into
+    #[clap(long("output-dir"), value_hint=ValueHint::DirPath, requires("split_by"), conflicts_with("output"), display_order(627))]
+    pub(crate) output_dir: Option<PathBuf>,
+
+    #[clap(flatten)]
+    pub(crate) output: OutputArgs,
+
+    #[clap(flatten)]
+    pub(crate) anonymize: AnonymizeArgs,
+
+    /// color the rows of the TXT output according to the source of their entries, which is
+    /// recognized by a tag at the beginning of the name, like 'Prefetch: '. With 'auto',
+    /// colors are only used if the output is a terminal
+    #[clap(long("color"), default_value_t = ColorChoice::Auto, display_order(630))]
+    pub(crate) display_colors: ColorChoice,
+
+    /// destination timezone, either as name like 'Europe/Berlin' or as fixed offset like
+    /// '+02:00' (or 'list' to display all possible names). Every timestamp is converted with
+    /// the offset which is valid at this time, so that daylight saving time is respected
+    #[clap(short('t'), long("to-timezone"), visible_alias("timezone"), display_order(400), default_value_t=Timezone::default(), allow_hyphen_values=true)]
+    pub dst_zone: Timezone,
+
+    /// add the UTC timestamp as additional column `utc_timestamp` to the CSV output, or as
+    /// additional field `utc_timestamp` to the JSON output
+    #[clap(id("show-utc"), long("show-utc"), display_order(405), conflicts_with("ecs"))]
+    pub(crate) show_utc: bool,
+
+    /// filter: hide timeline entries older than the specified date (hint: use RFC 3339 syntax)
+    #[clap(long("from"), display_order(410))]
+    pub(crate) not_before: Option<Rfc3339Datetime>,
+
+    /// filter: hide timeline entries newer than the specified date (hint: use RFC 3339 syntax)
+    #[clap(long("to"), display_order(420))]
+    pub(crate) not_after: Option<Rfc3339Datetime>,
+
+    /// display entries with zero timestamps and entries without any timestamp, even if they
+    /// are outside of the time window given by `--from` and `--to`. Without a time window,
+    /// these entries are always displayed
+    #[clap(long("include-zero"), display_order(430))]
+    pub(crate) include_zero: bool,
+
+    /// filter: display only entries whose name matches this regular expression (e.g.
+    /// '(?i)\\users\\'). If specified multiple times, entries which match one of the
+    /// expressions are displayed
+    #[clap(long("include"), value_name("REGEX"), value_parser(parse_name_regex), display_order(440))]
+    pub(crate) include: Vec<Regex>,
+
+    /// filter: hide entries whose name matches this regular expression (e.g.
+    /// '\\windows\\winsxs\\'). This is applied after `--include` and can be specified
+    /// multiple times. The number of hidden bodyfile lines is logged with `--verbose`
+    #[clap(long("exclude"), value_name("REGEX"), value_parser(parse_name_regex), display_order(445))]
+    pub(crate) exclude: Vec<Regex>,
+
+    /// suppress entries which are identical to an earlier entry in timestamp, macb flags, name,
+    /// size and inode. The number of removed duplicates is printed to stderr
+    #[clap(long("dedup"), display_order(510))]
+    pub(crate) dedup: bool,
+
+    /// like `--dedup`, but compare only the specified fields. If 'timestamp' is not one of
+    /// them, the keys of all entries are kept in memory
+    #[clap(long("dedup-by"), value_name("FIELDS"), value_enum, value_delimiter(','), display_order(515))]
+    pub(crate) dedup_by: Vec<DedupField>,
+
+    /// maximum amount of memory which is used to sort the timeline, e.g. '512M' or '4G'. If
+    /// more memory is needed, sorted parts of the timeline are written into temporary files,
+    /// which are merged afterwards. In this case, ambiguous file names are not reported
+    #[clap(long("max-memory"), value_name("SIZE"), value_parser(parse_memory_size), display_order(520))]
+    pub(crate) max_memory: Option<usize>,
+
+    /// directory for the temporary files which are needed by `--max-memory`. The files are
+    /// removed automatically, even if mactime2 is interrupted
+    #[clap(long("tmpdir"), value_hint=ValueHint::DirPath, requires("max_memory"), display_order(530))]
+    pub(crate) tmpdir: Option<PathBuf>,
+
+    /// print summary statistics after the timeline: the number of entries in total and per
+    /// source, the first and last timestamp, the busiest day and hour and the number of entries
+    /// with zero timestamps. The statistics are written to stderr, or into PATH if specified
+    #[clap(long("stats"), value_name("PATH"), num_args(0..=1), value_hint=ValueHint::FilePath, display_order(540))]
+    pub(crate) stats: Option<Option<PathBuf>>,
 
     // /// convert only, but do not sort
     // #[clap(short('c'), long("convert-only"), display_order(450))]
     // pub(crate) dont_sort: bool,
-    /// strict mode: do not only warn, but abort if an error occurs
+    /// strict mode: do not only warn, but abort if an error occurs, e.g. at the first malformed
+    /// line of the bodyfile. Otherwise, malformed lines are skipped and counted
     #[clap(long("strict"), display_order(500))]
     pub(crate) strict_mode: bool,
 
+    /// write the malformed lines of the bodyfile, which have been skipped, into this file
+    #[clap(long("bad-lines"), value_name("PATH"), value_hint=ValueHint::FilePath, conflicts_with("strict_mode"), display_order(505))]
+    pub(crate) bad_lines: Option<PathBuf>,
+
     #[clap(flatten)]
-    pub(crate) verbose: clap_verbosity_flag::Verbosity,
+    pub(crate) verbose: Verbosity,
+}
+
+fn parse_delimiter(delimiter: &str) -> Result<u8, String> {
+    match delimiter {
+        "\\t" => Ok(b'\t'),
+        _ => match delimiter.as_bytes() {
+            [c] if c.is_ascii() && !matches!(c, b'"' | b'\r' | b'\n') => Ok(*c),
+            _ => Err(format!(
+                "'{delimiter}' is no valid delimiter, which must be a single ASCII character \
+                 other than a quote or a line break"
+            )),
+        },
+    }
+}
+
+fn parse_name_regex(s: &str) -> Result<Regex, String> {
+    Regex::new(s).map_err(|why| format!("invalid regular expression '{s}': {why}"))
+}
+
+/// parses a number of bytes, which can have one of the binary units 'K', 'M',
+/// 'G' or 'T'
+fn parse_memory_size(size: &str) -> Result<usize, String> {
+    let invalid = || format!("'{size}' is no valid memory size, use something like '512M' or '4G'");
+    let upper = size.trim().to_ascii_uppercase();
+    let number = upper.strip_suffix('B').unwrap_or(&upper);
+    let (number, exponent) = match number.chars().last() {
+        Some('K') => (&number[..number.len() - 1], 1),
+        Some('M') => (&number[..number.len() - 1], 2),
+        Some('G') => (&number[..number.len() - 1], 3),
+        Some('T') => (&number[..number.len() - 1], 4),
+        _ => (number, 0),
+    };
+    number
+        .trim()
+        .parse::<usize>()
+        .ok()
+        .and_then(|number| number.checked_mul(1024usize.checked_pow(exponent)?))
+        .ok_or_else(invalid)
 }
 
 impl HasVerboseFlag for Cli {
@@ -87,3 +270,19 @@ impl HasVerboseFlag for Cli {
         self.verbose.log_level_filter()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::parse_memory_size;
+
+    #[test]
+    fn parse_memory_sizes() {
+        assert_eq!(parse_memory_size("1000"), Ok(1000));
+        assert_eq!(parse_memory_size("16k"), Ok(16 * 1024));
+        assert_eq!(parse_memory_size("512M"), Ok(512 * 1024 * 1024));
+        assert_eq!(parse_memory_size("4 GB"), Ok(4 * 1024 * 1024 * 1024));
+        assert!(parse_memory_size("G").is_err());
+        assert!(parse_memory_size("-1M").is_err());
+        assert!(parse_memory_size("4X").is_err());
+    }
+}
@@ -1,22 +1,17 @@
-use std::sync::mpsc::{Sender, Receiver};
+use std::sync::mpsc::Receiver;
+
+use crate::time_window::TimeWindow;
 
 #[derive(Copy, Clone)]
 pub struct RunOptions {
     pub strict_mode: bool,
-}
-
-pub trait Provider<To, R>: Joinable<R> {
-    fn get_receiver(&mut self) -> Receiver<To>;
+    pub time_window: TimeWindow,
 }
 
 pub trait Consumer<From> {
     fn with_receiver(previous: Receiver<From>, options: RunOptions) -> Self;
 }
 
-pub trait Filter<From, To, R> : Consumer<From> + Provider<To, R> {
-    fn worker(reader: Receiver<From>, tx: Sender<To>, options: RunOptions);
-}
-
 pub trait Joinable<R> {
     fn join(&mut self) -> std::thread::Result<R>;
 }
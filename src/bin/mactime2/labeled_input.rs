@@ -0,0 +1,80 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use clio::Input;
+
+/// a bodyfile together with the label of its entries, which is given as
+/// `<LABEL>:<PATH>`. If no label is given, the path is used as label
+#[derive(Clone)]
+pub(crate) struct LabeledInput {
+    label: Option<Arc<str>>,
+    input: Input,
+}
+
+impl LabeledInput {
+    pub(crate) fn input(&self) -> &Input {
+        &self.input
+    }
+
+    pub(crate) fn has_explicit_label(&self) -> bool {
+        self.label.is_some()
+    }
+
+    /// the name of the bodyfile, or `None` if it is read from stdin
+    pub(crate) fn path(&self) -> Option<String> {
+        if self.input.is_std() {
+            None
+        } else {
+            Some(self.input.path().to_string_lossy().into_owned())
+        }
+    }
+
+    /// the label of the entries of this bodyfile, which is `None` only for
+    /// stdin without an explicit label
+    pub(crate) fn label(&self) -> Option<Arc<str>> {
+        self.label.clone().or_else(|| self.path().map(Arc::from))
+    }
+}
+
+/// parses `<LABEL>:<PATH>` or `<PATH>`. Because paths can contain colons, e.g.
+/// on Windows, an argument which is the path of an existing file is never
+/// split into label and path
+pub(crate) fn parse_labeled_input(value: &str) -> Result<LabeledInput, String> {
+    let (label, path) = match value.split_once(':') {
+        Some((label, path))
+            if !label.is_empty() && !path.is_empty() && !Path::new(value).exists() =>
+        {
+            (Some(Arc::from(label)), path)
+        }
+        _ => (None, value),
+    };
+    let input = Input::new(path).map_err(|why| format!("unable to open '{path}': {why}"))?;
+    Ok(LabeledInput { label, input })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_labeled_input;
+
+    #[test]
+    fn split_label_and_path() {
+        let input = parse_labeled_input("-").unwrap();
+        assert!(!input.has_explicit_label());
+        assert!(input.label().is_none());
+
+        let input = parse_labeled_input("host1:-").unwrap();
+        assert!(input.has_explicit_label());
+        assert_eq!(input.label().as_deref(), Some("host1"));
+        assert!(input.path().is_none());
+
+        let path = env!("CARGO_MANIFEST_DIR").to_owned() + "/Cargo.toml";
+        let input = parse_labeled_input(&format!("host2:{path}")).unwrap();
+        assert_eq!(input.label().as_deref(), Some("host2"));
+        assert_eq!(input.path(), Some(path.clone()));
+
+        let input = parse_labeled_input(&path).unwrap();
+        assert_eq!(input.label().as_deref(), Some(path.as_str()));
+
+        assert!(parse_labeled_input("host3:/nonexistent/file").is_err());
+    }
+}
@@ -3,23 +3,30 @@ mod stream;
 mod bodyfile;
 mod error;
 mod filter;
+mod labeled_input;
 mod output;
 mod cli;
+mod name_filter;
+mod time_window;
 
 use application::*;
 use cli::*;
 
 use anyhow::Result;
-use dfir_toolkit::common::{FancyParser, TzArgument};
+use clap::ColorChoice;
+use colored::control::SHOULD_COLORIZE;
+use dfir_toolkit::common::FancyParser;
 
 fn main() -> Result<()> {
     let cli: Cli = Cli::parse_cli();
 
-    if cli.dst_zone.is_list() {
-        TzArgument::display_zones();
-        return Ok(());
-    }
-    debug_assert!(cli.dst_zone.is_tz());
+    match cli.display_colors {
+        // a file is never colorized, even if stdout is a terminal
+        ColorChoice::Auto if cli.output.path().is_some() => SHOULD_COLORIZE.set_override(false),
+        ColorChoice::Auto => SHOULD_COLORIZE.unset_override(),
+        ColorChoice::Always => SHOULD_COLORIZE.set_override(true),
+        ColorChoice::Never => SHOULD_COLORIZE.set_override(false),
+    };
 
     let app: Mactime2Application = cli.into();
 
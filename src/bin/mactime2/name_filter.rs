@@ -0,0 +1,58 @@
+use regex::Regex;
+
+use crate::cli::Cli;
+
+/// selects bodyfile lines by their names. A line is kept if its name matches
+/// at least one of the `includes` (or if there are none), and none of the
+/// `excludes`
+#[derive(Clone, Default)]
+pub(crate) struct NameFilter {
+    includes: Vec<Regex>,
+    excludes: Vec<Regex>,
+}
+
+impl NameFilter {
+    pub(crate) fn is_restricted(&self) -> bool {
+        !(self.includes.is_empty() && self.excludes.is_empty())
+    }
+
+    pub(crate) fn matches(&self, name: &str) -> bool {
+        (self.includes.is_empty() || self.includes.iter().any(|r| r.is_match(name)))
+            && !self.excludes.iter().any(|r| r.is_match(name))
+    }
+}
+
+impl From<&Cli> for NameFilter {
+    fn from(cli: &Cli) -> Self {
+        Self {
+            includes: cli.include.clone(),
+            excludes: cli.exclude.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use regex::Regex;
+
+    use super::NameFilter;
+
+    #[test]
+    fn include_before_exclude() {
+        let filter = NameFilter {
+            includes: vec![Regex::new(r"(?i)\\users\\").unwrap()],
+            excludes: vec![Regex::new(r"\\AppData\\").unwrap()],
+        };
+        assert!(filter.matches(r"C:\Users\admin\Desktop\notes.txt"));
+        assert!(filter.matches(r"C:\USERS\admin\Desktop\notes.txt"));
+        assert!(!filter.matches(r"C:\Users\admin\AppData\Local\Temp\x.tmp"));
+        assert!(!filter.matches(r"C:\Windows\System32\cmd.exe"));
+
+        let filter = NameFilter {
+            includes: vec![],
+            excludes: vec![Regex::new(r"\\winsxs\\").unwrap()],
+        };
+        assert!(filter.matches(r"C:\Windows\System32\cmd.exe"));
+        assert!(!filter.matches(r"C:\Windows\winsxs\cmd.exe"));
+    }
+}
@@ -0,0 +1,66 @@
+use colored::Color;
+
+/// the kind of artifact a timeline entry has been derived from. Producers of
+/// bodyfiles tag their entries by prefixing the name with `<Tag>: `, e.g.
+/// `Prefetch: run 'CMD.EXE'`. Entries without a known tag are treated as
+/// file system entries
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ArtifactSource {
+    Evtx,
+    Registry,
+    Prefetch,
+    Lnk,
+    Zip,
+}
+
+impl ArtifactSource {
+    /// recognizes the tag at the beginning of `name`. Tags are compared case
+    /// insensitive
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        let (tag, _) = name.split_once(": ")?;
+        match tag.to_ascii_lowercase().as_str() {
+            "evtx" | "event" | "eventlog" => Some(Self::Evtx),
+            "registry" | "shimcache" | "shellbag" | "userassist" | "search" => Some(Self::Registry),
+            "prefetch" => Some(Self::Prefetch),
+            "lnk" => Some(Self::Lnk),
+            "zip" => Some(Self::Zip),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn color(&self) -> Color {
+        match self {
+            Self::Evtx => Color::Yellow,
+            Self::Registry => Color::Cyan,
+            Self::Prefetch => Color::Green,
+            Self::Lnk => Color::Magenta,
+            Self::Zip => Color::Blue,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ArtifactSource;
+
+    #[test]
+    fn recognize_tags() {
+        assert_eq!(
+            ArtifactSource::from_name("Prefetch: run 'CMD.EXE' (run 3 times)"),
+            Some(ArtifactSource::Prefetch)
+        );
+        assert_eq!(
+            ArtifactSource::from_name("ShimCache: 'C:\\Windows\\a.exe' (position 1)"),
+            Some(ArtifactSource::Registry)
+        );
+        assert_eq!(
+            ArtifactSource::from_name("EVTX: 4624 logon of 'admin'"),
+            Some(ArtifactSource::Evtx)
+        );
+        assert_eq!(
+            ArtifactSource::from_name("/home/user/notes: draft.txt"),
+            None
+        );
+        assert_eq!(ArtifactSource::from_name("/etc/passwd"), None);
+    }
+}
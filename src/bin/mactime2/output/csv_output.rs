@@ -2,7 +2,7 @@ use std::io::Write;
 
 use chrono_tz::Tz;
 use csv::WriterBuilder;
-use dfir_toolkit::common::ForensicsTimestamp;
+use dfir_toolkit::common::{ForensicsTimestamp, Timezone};
 use serde::Serialize;
 
 use crate::bodyfile::{ListEntry, MACBFlags, Mactime2Writer};
@@ -11,8 +11,14 @@ pub(crate) struct CsvOutput<W>
 where
     W: Write + Send,
 {
-    dst_zone: Tz,
+    dst_zone: Timezone,
     writer: csv::Writer<W>,
+
+    /// add the timestamp in UTC as additional column
+    show_utc: bool,
+
+    /// add the label of the bodyfile as last column
+    show_source: bool,
 }
 
 pub const CSV_DELIMITER: u8 = b',';
@@ -21,15 +27,29 @@ impl<W> CsvOutput<W>
 where
     W: Write + Send,
 {
-    pub fn new(writer: W, dst_zone: Tz, has_headers: bool) -> Self {
+    /// Fields which contain the delimiter, quotes or line breaks are quoted
+    /// as described in RFC 4180
+    pub fn new(writer: W, dst_zone: Timezone, has_headers: bool, delimiter: u8) -> Self {
         Self {
             dst_zone,
             writer: WriterBuilder::new()
-                .delimiter(CSV_DELIMITER)
+                .delimiter(delimiter)
                 .has_headers(has_headers)
                 .from_writer(writer),
+            show_utc: false,
+            show_source: false,
         }
     }
+
+    pub fn with_utc(mut self, show_utc: bool) -> Self {
+        self.show_utc = show_utc;
+        self
+    }
+
+    pub fn with_source(mut self, show_source: bool) -> Self {
+        self.show_source = show_source;
+        self
+    }
     #[allow(dead_code)]
     pub fn with_writer(mut self, writer: W) -> Self
     where
@@ -54,12 +74,22 @@ where
             gid: entry.line.get_gid(),
             inode: entry.line.get_inode(),
             name: entry.line.get_name(),
+            utc_timestamp: self
+                .show_utc
+                .then(|| ForensicsTimestamp::new(*timestamp, Tz::UTC)),
+            source: self
+                .show_source
+                .then(|| entry.line.source().unwrap_or_default()),
         };
         self.writer.serialize(csv_line)?;
         Ok(())
     }
 
-    fn into_writer(self) -> W {
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.writer.flush()
+    }
+
+    fn into_writer(self: Box<Self>) -> W {
         self.writer.into_inner().unwrap()
     }
 }
@@ -74,6 +104,12 @@ struct CsvLine<'e> {
     gid: &'e u64,
     inode: &'e str,
     name: &'e str,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    utc_timestamp: Option<ForensicsTimestamp>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    source: Option<&'e str>,
 }
 
 #[cfg(test)]
@@ -105,12 +141,17 @@ mod tests {
             let bf_line = Bodyfile3Line::new().with_crtime(unix_ts.into());
             let entry = ListEntry {
                 flags: MACBFlags::B,
-                line: Arc::new(bf_line),
+                line: Arc::new(bf_line.into()),
             };
 
-            let mut output = CsvOutput::new(Cursor::new(vec![]), Tz::UTC, false);
+            let mut output = CsvOutput::new(
+                Cursor::new(vec![]),
+                Tz::UTC.into(),
+                false,
+                crate::output::CSV_DELIMITER,
+            );
             output.write_line(&unix_ts, &entry).unwrap();
-            let mut output = BufReader::new(Cursor::new(output.into_writer().into_inner())).lines();
+            let mut output = BufReader::new(Cursor::new(Box::new(output).into_writer().into_inner())).lines();
             let out_line = output.next().unwrap().unwrap();
 
             let out_ts = out_line.split(',').next().unwrap();
@@ -133,13 +174,14 @@ mod tests {
             let bf_line = Bodyfile3Line::new().with_crtime(unix_ts.into());
             let entry = ListEntry {
                 flags: MACBFlags::B,
-                line: Arc::new(bf_line),
+                line: Arc::new(bf_line.into()),
             };
 
-            let mut output = CsvOutput::new(Cursor::new(vec![]), tz, false);
+            let mut output =
+                CsvOutput::new(Cursor::new(vec![]), tz.into(), false, crate::output::CSV_DELIMITER);
             let delimiter: char = crate::output::CSV_DELIMITER.into();
             output.write_line(&unix_ts, &entry).unwrap();
-            let mut output = BufReader::new(Cursor::new(output.into_writer().into_inner())).lines();
+            let mut output = BufReader::new(Cursor::new(Box::new(output).into_writer().into_inner())).lines();
             let out_line = output.next().unwrap().unwrap();
 
             let out_ts = out_line.split(delimiter).next().unwrap();
@@ -155,4 +197,51 @@ mod tests {
         }
         Ok(())
     }
+
+    #[test]
+    fn quote_fields_with_delimiter() {
+        let bf_line = Bodyfile3Line::new()
+            .with_name("/tmp/a;b,\"c\"")
+            .with_crtime(0.into());
+        let entry = ListEntry {
+            flags: MACBFlags::B,
+            line: Arc::new(bf_line.into()),
+        };
+
+        let mut output = CsvOutput::new(Cursor::new(vec![]), Tz::UTC.into(), false, b';');
+        output.write_line(&0, &entry).unwrap();
+        let output = String::from_utf8(Box::new(output).into_writer().into_inner()).unwrap();
+        assert_eq!(
+            output,
+            "1970-01-01T00:00:00+00:00;0;...b;;0;0;0;\"/tmp/a;b,\"\"c\"\"\"\n"
+        );
+    }
+
+    #[test]
+    fn convert_around_dst_transition() {
+        // 2024-03-31T00:30:00Z and 2024-03-31T01:30:00Z, daylight saving time
+        // begins at 01:00 UTC in Europe/Berlin
+        let mut output = CsvOutput::new(
+            Cursor::new(vec![]),
+            chrono_tz::Europe::Berlin.into(),
+            false,
+            crate::output::CSV_DELIMITER,
+        )
+        .with_utc(true);
+        for unix_ts in [1711845000, 1711848600] {
+            let entry = ListEntry {
+                flags: MACBFlags::M,
+                line: Arc::new(Bodyfile3Line::new().with_mtime(unix_ts.into()).into()),
+            };
+            output.write_line(&unix_ts, &entry).unwrap();
+        }
+        let output = String::from_utf8(Box::new(output).into_writer().into_inner()).unwrap();
+        let mut lines = output.lines();
+        let line = lines.next().unwrap();
+        assert!(line.starts_with("2024-03-31T01:30:00+01:00,"));
+        assert!(line.ends_with(",2024-03-31T00:30:00+00:00"));
+        let line = lines.next().unwrap();
+        assert!(line.starts_with("2024-03-31T03:30:00+02:00,"));
+        assert!(line.ends_with(",2024-03-31T01:30:00+00:00"));
+    }
 }
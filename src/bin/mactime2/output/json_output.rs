@@ -0,0 +1,295 @@
+use std::io::Write;
+
+use chrono::DateTime;
+use dfir_toolkit::common::Timezone;
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+use crate::bodyfile::{ListEntry, MACBFlags, Mactime2Writer};
+
+/// writes one JSON object per timeline entry, either one per line or as
+/// elements of a JSON array. Unlike the other formats, the timestamp is
+/// always written in RFC 3339 format, regardless of `$DFIR_DATE`
+pub(crate) struct JsonOutput<W>
+where
+    W: Write + Send,
+{
+    dst_zone: Timezone,
+
+    /// use the field names of the Elastic Common Schema
+    ecs: bool,
+
+    /// add the timestamp in UTC as field `utc_timestamp`
+    show_utc: bool,
+
+    /// write the entries as elements of a JSON array
+    array: bool,
+
+    /// number of entries which have been written so far
+    entries: u64,
+
+    /// `true` if the closing bracket of the array has been written
+    closed: bool,
+    writer: W,
+}
+
+impl<W> JsonOutput<W>
+where
+    W: Write + Send,
+{
+    pub fn new(writer: W, dst_zone: Timezone, ecs: bool) -> Self {
+        Self {
+            dst_zone,
+            ecs,
+            show_utc: false,
+            array: false,
+            entries: 0,
+            closed: false,
+            writer,
+        }
+    }
+
+    pub fn with_utc(mut self, show_utc: bool) -> Self {
+        self.show_utc = show_utc;
+        self
+    }
+
+    pub fn with_array(mut self, array: bool) -> Self {
+        self.array = array;
+        self
+    }
+
+    /// writes an entry, either as single line or as element of the array
+    fn write_entry<T: Serialize>(&mut self, entry: &T) -> std::io::Result<()> {
+        if self.array {
+            let separator: &[u8] = if self.entries == 0 { b"[\n" } else { b",\n" };
+            self.writer.write_all(separator)?;
+            serde_json::to_writer(&mut self.writer, entry)?;
+        } else {
+            serde_json::to_writer(&mut self.writer, entry)?;
+            writeln!(&mut self.writer)?;
+        }
+        self.entries += 1;
+        Ok(())
+    }
+
+    /// writes an entry with the field names of the Elastic Common Schema. The
+    /// macb flags are written as `event.action`, and the label of the
+    /// bodyfile as `log.file.path`
+    fn write_ecs_entry(&mut self, timestamp: String, entry: &ListEntry) -> std::io::Result<()> {
+        let mut document = json!({
+            "@timestamp": timestamp,
+            "event": {
+                "action": event_actions(entry.flags),
+            },
+            "file": {
+                "path": entry.line.get_name(),
+                "size": entry.line.get_size(),
+                "inode": entry.line.get_inode(),
+                "uid": entry.line.get_uid(),
+                "gid": entry.line.get_gid(),
+                "mode": entry.line.get_mode_as_string(),
+            },
+        });
+        if let Some(source) = entry.line.source() {
+            document["log"] = json!({"file": {"path": source}});
+        }
+        if let Some(attributes) = entry.line.attributes() {
+            document["attributes"] = json!(attributes);
+        }
+        self.write_entry(&document)
+    }
+}
+
+impl<W> Mactime2Writer<W> for JsonOutput<W>
+where
+    W: Write + Send,
+{
+    fn write_line(&mut self, timestamp: &i64, entry: &ListEntry) -> std::io::Result<()> {
+        let utc_timestamp = match DateTime::from_timestamp(*timestamp, 0) {
+            Some(ts) => ts,
+            None => panic!("unable to convert '{timestamp}' into unix timestamp"),
+        };
+        let timestamp = utc_timestamp.with_timezone(&self.dst_zone).to_rfc3339();
+        if self.ecs {
+            return self.write_ecs_entry(timestamp, entry);
+        }
+        let json_line = JsonLine {
+            timestamp,
+            macb: entry.flags.into(),
+            size: *entry.line.get_size(),
+            uid: *entry.line.get_uid(),
+            gid: *entry.line.get_gid(),
+            mode: entry.line.get_mode_as_string(),
+            inode: entry.line.get_inode(),
+            name: entry.line.get_name(),
+            source: entry.line.source(),
+            utc_timestamp: self.show_utc.then(|| utc_timestamp.to_rfc3339()),
+            attributes: entry.line.attributes(),
+        };
+        self.write_entry(&json_line)
+    }
+
+    /// closes the array, so no more entries can be written afterwards
+    fn flush(&mut self) -> std::io::Result<()> {
+        if self.array && !self.closed {
+            let end: &[u8] = if self.entries == 0 { b"[]\n" } else { b"\n]\n" };
+            self.writer.write_all(end)?;
+            self.closed = true;
+        }
+        self.writer.flush()
+    }
+
+    fn into_writer(self: Box<Self>) -> W {
+        self.writer
+    }
+}
+
+#[derive(Serialize)]
+struct JsonLine<'e> {
+    timestamp: String,
+    macb: Macb,
+    size: u64,
+    uid: u64,
+    gid: u64,
+    mode: &'e str,
+    inode: &'e str,
+    name: &'e str,
+    source: Option<&'e str>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    utc_timestamp: Option<String>,
+
+    /// the additional attributes of an extended bodyfile line
+    #[serde(skip_serializing_if = "Option::is_none")]
+    attributes: Option<&'e Map<String, Value>>,
+}
+
+#[derive(Serialize)]
+struct Macb {
+    m: bool,
+    a: bool,
+    c: bool,
+    b: bool,
+}
+
+impl From<MACBFlags> for Macb {
+    fn from(flags: MACBFlags) -> Self {
+        Self {
+            m: flags.contains(MACBFlags::M),
+            a: flags.contains(MACBFlags::A),
+            c: flags.contains(MACBFlags::C),
+            b: flags.contains(MACBFlags::B),
+        }
+    }
+}
+
+/// the names of the changes of a file which are indicated by `flags`, in the
+/// order m, a, c and b
+fn event_actions(flags: MACBFlags) -> Vec<&'static str> {
+    [
+        (MACBFlags::M, "modified"),
+        (MACBFlags::A, "accessed"),
+        (MACBFlags::C, "changed"),
+        (MACBFlags::B, "created"),
+    ]
+    .into_iter()
+    .filter(|(flag, _)| flags.contains(*flag))
+    .map(|(_, action)| action)
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io::Cursor, sync::Arc};
+
+    use chrono_tz::Europe;
+    use dfir_toolkit::common::bodyfile::Bodyfile3Line;
+    use serde_json::{json, Value};
+
+    use super::JsonOutput;
+    use crate::bodyfile::{ExtendedBodyfileLine, ListEntry, MACBFlags, Mactime2Writer};
+
+    fn entry(source: Option<&str>) -> ListEntry {
+        let line = Bodyfile3Line::new()
+            .with_name("/etc/passwd")
+            .with_inode("1234")
+            .with_mode("r/rrw-r--r--")
+            .with_size(2048)
+            .with_uid(1000)
+            .with_gid(100)
+            .with_mtime(1715845546.into());
+        ListEntry {
+            flags: MACBFlags::M | MACBFlags::C,
+            line: Arc::new(ExtendedBodyfileLine::from(line).with_source(source.map(Arc::from))),
+        }
+    }
+
+    #[test]
+    fn write_explicit_fields() {
+        let mut output = JsonOutput::new(Cursor::new(vec![]), Europe::Berlin.into(), false);
+        output
+            .write_line(&1715845546, &entry(Some("sample.bodyfile")))
+            .unwrap();
+        let output = Box::new(output).into_writer().into_inner();
+        let value: Value = serde_json::from_slice(&output).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "timestamp": "2024-05-16T09:45:46+02:00",
+                "macb": {"m": true, "a": false, "c": true, "b": false},
+                "size": 2048,
+                "uid": 1000,
+                "gid": 100,
+                "mode": "r/rrw-r--r--",
+                "inode": "1234",
+                "name": "/etc/passwd",
+                "source": "sample.bodyfile"
+            })
+        );
+        assert!(output.ends_with(b"}\n"));
+    }
+
+    #[test]
+    fn write_array() {
+        let mut output =
+            JsonOutput::new(Cursor::new(vec![]), Europe::Berlin.into(), false).with_array(true);
+        output.write_line(&1715845546, &entry(None)).unwrap();
+        output.write_line(&1715845547, &entry(None)).unwrap();
+        output.flush().unwrap();
+        let output = Box::new(output).into_writer().into_inner();
+        let value: Value = serde_json::from_slice(&output).unwrap();
+        let entries = value.as_array().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1]["timestamp"], "2024-05-16T09:45:47+02:00");
+
+        let mut output =
+            JsonOutput::new(Cursor::new(vec![]), Europe::Berlin.into(), false).with_array(true);
+        output.flush().unwrap();
+        assert_eq!(Box::new(output).into_writer().into_inner(), b"[]\n");
+    }
+
+    #[test]
+    fn write_ecs_fields() {
+        let mut output = JsonOutput::new(Cursor::new(vec![]), Europe::Berlin.into(), true);
+        output.write_line(&1715845546, &entry(None)).unwrap();
+        let output = Box::new(output).into_writer().into_inner();
+        let value: Value = serde_json::from_slice(&output).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "@timestamp": "2024-05-16T09:45:46+02:00",
+                "event": {"action": ["modified", "changed"]},
+                "file": {
+                    "path": "/etc/passwd",
+                    "size": 2048,
+                    "inode": "1234",
+                    "uid": 1000,
+                    "gid": 100,
+                    "mode": "r/rrw-r--r--"
+                }
+            })
+        );
+        assert_eq!(output.iter().filter(|c| **c == b'\n').count(), 1);
+    }
+}
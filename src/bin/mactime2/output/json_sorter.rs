@@ -1,24 +1,29 @@
 use std::{
-    borrow::Borrow,
     collections::{BTreeMap, BTreeSet},
+    io::Write,
     sync::{mpsc::Receiver, Arc},
     thread::JoinHandle,
 };
 
 use dfir_toolkit::{
-    common::bodyfile::Bodyfile3Line,
+    common::{bodyfile::Bodyfile3Line, FileOutput},
     es4forensics::{objects::PosixFile, Timestamp, TimelineObject},
 };
+use serde_json::json;
 use std::convert::TryFrom;
 
 use crate::{
+    bodyfile::ExtendedBodyfileLine,
     error::MactimeError,
     filter::RunOptions,
     filter::{Consumer, Joinable, Runnable, Sorter},
+    time_window::TimeWindow,
 };
 pub struct JsonSorter {
     worker: Option<JoinHandle<Result<(), MactimeError>>>,
-    receiver: Option<Receiver<Bodyfile3Line>>,
+    receiver: Option<Receiver<ExtendedBodyfileLine>>,
+    time_window: TimeWindow,
+    output: Option<FileOutput>,
 }
 
 impl Joinable<Result<(), MactimeError>> for JsonSorter {
@@ -27,11 +32,13 @@ impl Joinable<Result<(), MactimeError>> for JsonSorter {
     }
 }
 
-impl Consumer<Bodyfile3Line> for JsonSorter {
-    fn with_receiver(previous: Receiver<Bodyfile3Line>, _options: RunOptions) -> Self {
+impl Consumer<ExtendedBodyfileLine> for JsonSorter {
+    fn with_receiver(previous: Receiver<ExtendedBodyfileLine>, options: RunOptions) -> Self {
         Self {
             receiver: Some(previous),
             worker: None,
+            time_window: options.time_window,
+            output: None,
         }
     }
 }
@@ -42,8 +49,10 @@ impl Runnable for JsonSorter {
             .receiver
             .take()
             .expect("no receiver provided; please call with_receiver()");
+        let time_window = self.time_window;
+        let output = self.output.take().unwrap_or_else(FileOutput::stdout);
         self.worker = Some(std::thread::spawn(move || {
-            Self::json_worker(receiver)
+            Self::json_worker(receiver, time_window, output)
         }));
     }
 }
@@ -51,7 +60,16 @@ impl Runnable for JsonSorter {
 impl Sorter<Result<(), MactimeError>> for JsonSorter {}
 
 impl JsonSorter {
-    fn json_worker(decoder: Receiver<Bodyfile3Line>) -> Result<(), MactimeError> {
+    pub fn with_output(mut self, output: FileOutput) -> Self {
+        self.output = Some(output);
+        self
+    }
+
+    fn json_worker(
+        decoder: Receiver<ExtendedBodyfileLine>,
+        time_window: TimeWindow,
+        mut output: FileOutput,
+    ) -> Result<(), MactimeError> {
         let mut entries: BTreeMap<Timestamp, BTreeSet<String>> = BTreeMap::new();
         loop {
             let line = Arc::new(match decoder.recv() {
@@ -61,12 +79,18 @@ impl JsonSorter {
                 Ok(l) => l,
             });
 
-            let bfline: &Bodyfile3Line = line.borrow();
+            let bfline: &Bodyfile3Line = &line;
             let pf = PosixFile::try_from(bfline).unwrap();
 
             let lines: Vec<(Timestamp, String)> = pf
                 .into_tuples()
-                .map(|(t, v)| (t, serde_json::to_string(&v).unwrap()))
+                .filter(|(t, _)| time_window.contains(t.timestamp_millis().div_euclid(1000)))
+                .map(|(t, mut v)| {
+                    if let Some(source) = line.source() {
+                        v["log"] = json!({"file": {"path": source}});
+                    }
+                    (t, serde_json::to_string(&v).unwrap())
+                })
                 .collect();
 
             if lines.is_empty() {
@@ -81,9 +105,10 @@ impl JsonSorter {
 
         for lines in entries.into_values() {
             for line in lines {
-                println!("{}", line);
+                writeln!(output, "{}", line)?;
             }
         }
+        output.commit()?;
         Ok(())
     }
 }
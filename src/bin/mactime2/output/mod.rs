@@ -1,9 +1,17 @@
+mod artifact_source;
 mod csv_output;
 mod old_csv_output;
 mod txt_output;
 mod json_sorter;
+mod json_output;
+mod split_output;
+mod stats_output;
 
+pub (crate) use artifact_source::*;
 pub (crate) use csv_output::*;
 pub (crate) use old_csv_output::*;
 pub (crate) use txt_output::*;
-pub (crate) use json_sorter::*;
\ No newline at end of file
+pub (crate) use json_sorter::*;
+pub (crate) use json_output::*;
+pub (crate) use split_output::*;
+pub (crate) use stats_output::*;
\ No newline at end of file
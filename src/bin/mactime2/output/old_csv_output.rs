@@ -1,7 +1,6 @@
 use std::io::Write;
 
-use chrono_tz::Tz;
-use dfir_toolkit::common::ForensicsTimestamp;
+use dfir_toolkit::common::{ForensicsTimestamp, Timezone};
 
 use crate::bodyfile::{ListEntry, Mactime2Writer};
 
@@ -9,16 +8,28 @@ pub(crate) struct OldCsvOutput<W>
 where
     W: Write + Send,
 {
-    dst_zone: Tz,
+    dst_zone: Timezone,
     writer: W,
+
+    /// add the label of the bodyfile as last column
+    show_source: bool,
 }
 
 impl<W> OldCsvOutput<W>
 where
     W: Write + Send,
 {
-    pub fn new(writer: W, dst_zone: Tz) -> Self {
-        Self { dst_zone, writer }
+    pub fn new(writer: W, dst_zone: Timezone) -> Self {
+        Self {
+            dst_zone,
+            writer,
+            show_source: false,
+        }
+    }
+
+    pub fn with_source(mut self, show_source: bool) -> Self {
+        self.show_source = show_source;
+        self
     }
 }
 
@@ -28,7 +39,7 @@ where
 {
     fn write_line(&mut self, timestamp: &i64, entry: &ListEntry) -> std::io::Result<()> {
         let timestamp = ForensicsTimestamp::from(*timestamp).with_timezone(self.dst_zone);
-        writeln!(
+        write!(
             self.writer,
             "{},{},{},{},{},{},{},\"{}\"",
             timestamp,
@@ -39,10 +50,22 @@ where
             entry.line.get_gid(),
             entry.line.get_inode(),
             entry.line.get_name()
-        )
+        )?;
+        if self.show_source {
+            write!(
+                self.writer,
+                ",\"{}\"",
+                entry.line.source().unwrap_or_default()
+            )?;
+        }
+        writeln!(self.writer)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.writer.flush()
     }
 
-    fn into_writer(self) -> W {
+    fn into_writer(self: Box<Self>) -> W {
         self.writer
     }
 }
@@ -75,13 +98,13 @@ mod tests {
             let bf_line = Bodyfile3Line::new().with_crtime(unix_ts.into());
             let entry = ListEntry {
                 flags: MACBFlags::B,
-                line: Arc::new(bf_line),
+                line: Arc::new(bf_line.into()),
             };
 
-            let mut output = OldCsvOutput::new(Cursor::new(vec![]), Tz::UTC);
+            let mut output = OldCsvOutput::new(Cursor::new(vec![]), Tz::UTC.into());
 
             output.write_line(&unix_ts, &entry).unwrap();
-            let mut output = BufReader::new(Cursor::new(output.into_writer().into_inner())).lines();
+            let mut output = BufReader::new(Cursor::new(Box::new(output).into_writer().into_inner())).lines();
             let out_line = output.next().unwrap().unwrap();
 
             let out_ts = out_line.split(',').next().unwrap();
@@ -100,17 +123,17 @@ mod tests {
     fn test_correct_ts_random_tz() -> Result<(), String> {
         for _ in 1..100 {
             let tz = random_tz();
-            let mut output = OldCsvOutput::new(Cursor::new(vec![]), tz);
+            let mut output = OldCsvOutput::new(Cursor::new(vec![]), tz.into());
 
             let unix_ts = rand::random::<u32>() as i64;
             let bf_line = Bodyfile3Line::new().with_crtime(unix_ts.into());
             let entry = ListEntry {
                 flags: MACBFlags::B,
-                line: Arc::new(bf_line),
+                line: Arc::new(bf_line.into()),
             };
 
             output.write_line(&unix_ts, &entry).unwrap();
-            let mut output = BufReader::new(Cursor::new(output.into_writer().into_inner())).lines();
+            let mut output = BufReader::new(Cursor::new(Box::new(output).into_writer().into_inner())).lines();
             let out_line = output.next().unwrap().unwrap();
 
             let out_ts = out_line.split(',').next().unwrap();
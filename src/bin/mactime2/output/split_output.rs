@@ -0,0 +1,105 @@
+use std::fs::File;
+use std::io::{BufWriter, Error};
+use std::path::PathBuf;
+
+use chrono::DateTime;
+use dfir_toolkit::common::{FileOutput, Timezone};
+use clap::ValueEnum;
+
+use crate::bodyfile::{ListEntry, Mactime2Writer};
+
+/// name of the file part of entries with zero timestamps, and of entries
+/// without any timestamp
+const EPOCH: &str = "epoch";
+
+#[derive(ValueEnum, Clone, Copy)]
+pub(crate) enum SplitBy {
+    /// one file per calendar day in the destination timezone
+    Day,
+}
+
+type DailyOutput = Box<dyn Mactime2Writer<BufWriter<File>>>;
+
+/// writes the timeline into one file per day, e.g. `timeline-2024-03-12.csv`.
+/// The files are created when their first entry is written, and every file is
+/// written by its own output, so that it gets its own header. Because the
+/// entries are sorted, only one file is open at a time
+pub(crate) struct SplitOutput {
+    output_dir: PathBuf,
+    extension: &'static str,
+    dst_zone: Timezone,
+    create_output: Box<dyn Fn(BufWriter<File>) -> std::io::Result<DailyOutput> + Send>,
+    current: Option<(String, DailyOutput)>,
+}
+
+impl SplitOutput {
+    pub fn new<F>(
+        output_dir: PathBuf,
+        extension: &'static str,
+        dst_zone: Timezone,
+        create_output: F,
+    ) -> Self
+    where
+        F: Fn(BufWriter<File>) -> std::io::Result<DailyOutput> + Send + 'static,
+    {
+        Self {
+            output_dir,
+            extension,
+            dst_zone,
+            create_output: Box::new(create_output),
+            current: None,
+        }
+    }
+
+    /// the part of the file name which identifies the file of an entry
+    fn file_key(&self, timestamp: i64) -> String {
+        if matches!(timestamp, -1 | 0) {
+            return EPOCH.to_owned();
+        }
+        match DateTime::from_timestamp(timestamp, 0) {
+            Some(ts) => ts
+                .with_timezone(&self.dst_zone)
+                .format("%Y-%m-%d")
+                .to_string(),
+            None => panic!("unable to convert '{timestamp}' into unix timestamp"),
+        }
+    }
+
+    fn open(&self, key: &str) -> std::io::Result<DailyOutput> {
+        let path = self
+            .output_dir
+            .join(format!("timeline-{key}.{}", self.extension));
+        let file = File::create(&path).map_err(|why| {
+            Error::new(
+                why.kind(),
+                format!("unable to create '{}': {why}", path.display()),
+            )
+        })?;
+        (self.create_output)(BufWriter::new(file))
+    }
+}
+
+impl Mactime2Writer<FileOutput> for SplitOutput {
+    fn write_line(&mut self, timestamp: &i64, entry: &ListEntry) -> std::io::Result<()> {
+        let key = self.file_key(*timestamp);
+        if !matches!(&self.current, Some((current_key, _)) if *current_key == key) {
+            self.flush()?;
+            let output = self.open(&key)?;
+            self.current = Some((key, output));
+        }
+        let (_, output) = self.current.as_mut().unwrap();
+        output.write_line(timestamp, entry)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self.current.as_mut() {
+            Some((_, output)) => output.flush(),
+            None => Ok(()),
+        }
+    }
+
+    /// nothing is written to stdout, because every file has its own writer
+    fn into_writer(self: Box<Self>) -> FileOutput {
+        FileOutput::stdout()
+    }
+}
@@ -0,0 +1,249 @@
+use std::collections::BTreeMap;
+use std::io::Write;
+
+use chrono::DateTime;
+use dfir_toolkit::common::{FileOutput, ForensicsTimestamp, Timezone};
+
+use crate::bodyfile::{ListEntry, Mactime2Writer};
+
+/// passes all entries to another output and writes summary statistics of
+/// them after the last entry, so that one can check whether the timeline
+/// covers the expected period
+pub(crate) struct StatsOutput {
+    output: Box<dyn Mactime2Writer<FileOutput>>,
+    destination: Box<dyn Write + Send>,
+    stats: TimelineStatistics,
+}
+
+impl StatsOutput {
+    pub fn new(
+        output: Box<dyn Mactime2Writer<FileOutput>>,
+        destination: Box<dyn Write + Send>,
+        dst_zone: Timezone,
+    ) -> Self {
+        Self {
+            output,
+            destination,
+            stats: TimelineStatistics::new(dst_zone),
+        }
+    }
+}
+
+impl Mactime2Writer<FileOutput> for StatsOutput {
+    fn write_line(&mut self, timestamp: &i64, entry: &ListEntry) -> std::io::Result<()> {
+        self.stats.add(*timestamp, entry);
+        self.output.write_line(timestamp, entry)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.output.flush()?;
+        self.stats.write_to(&mut self.destination)?;
+        self.destination.flush()
+    }
+
+    fn into_writer(self: Box<Self>) -> FileOutput {
+        self.output.into_writer()
+    }
+}
+
+/// statistics of the entries of a timeline, which must be added in the order
+/// of their timestamps
+struct TimelineStatistics {
+    dst_zone: Timezone,
+    entries: usize,
+    entries_per_source: BTreeMap<String, usize>,
+    zero_timestamps: usize,
+    first_timestamp: Option<i64>,
+    last_timestamp: Option<i64>,
+    days: BusiestPeriod,
+    hours: BusiestPeriod,
+}
+
+impl TimelineStatistics {
+    fn new(dst_zone: Timezone) -> Self {
+        Self {
+            dst_zone,
+            entries: 0,
+            entries_per_source: BTreeMap::new(),
+            zero_timestamps: 0,
+            first_timestamp: None,
+            last_timestamp: None,
+            days: BusiestPeriod::default(),
+            hours: BusiestPeriod::default(),
+        }
+    }
+
+    fn add(&mut self, timestamp: i64, entry: &ListEntry) {
+        self.entries += 1;
+        let source = entry.line.source().unwrap_or("stdin");
+        match self.entries_per_source.get_mut(source) {
+            Some(entries) => *entries += 1,
+            None => {
+                self.entries_per_source.insert(source.to_owned(), 1);
+            }
+        }
+
+        if matches!(timestamp, -1 | 0) {
+            self.zero_timestamps += 1;
+            return;
+        }
+
+        // the periods only need to be calculated if the timestamp changes
+        if self.last_timestamp == Some(timestamp) {
+            self.days.add_to_current();
+            self.hours.add_to_current();
+            return;
+        }
+        self.first_timestamp.get_or_insert(timestamp);
+        self.last_timestamp = Some(timestamp);
+        match DateTime::from_timestamp(timestamp, 0) {
+            Some(ts) => {
+                let ts = ts.with_timezone(&self.dst_zone);
+                self.days.add(ts.format("%Y-%m-%d").to_string());
+                self.hours.add(ts.format("%Y-%m-%d %H:00").to_string());
+            }
+            None => panic!("unable to convert '{timestamp}' into unix timestamp"),
+        }
+    }
+
+    fn format_timestamp(&self, timestamp: Option<i64>) -> String {
+        match timestamp {
+            Some(ts) => ForensicsTimestamp::from(ts)
+                .with_timezone(self.dst_zone)
+                .to_string(),
+            None => "none".to_owned(),
+        }
+    }
+
+    fn write_to<D: Write>(&mut self, destination: &mut D) -> std::io::Result<()> {
+        writeln!(destination, "timeline statistics:")?;
+        writeln!(destination, "  entries:                   {}", self.entries)?;
+        writeln!(destination, "  entries per source:")?;
+        for (source, entries) in self.entries_per_source.iter() {
+            writeln!(destination, "    {:<25}{entries}", format!("{source}:"))?;
+        }
+        writeln!(
+            destination,
+            "  first timestamp:           {}",
+            self.format_timestamp(self.first_timestamp)
+        )?;
+        writeln!(
+            destination,
+            "  last timestamp:            {}",
+            self.format_timestamp(self.last_timestamp)
+        )?;
+        writeln!(
+            destination,
+            "  busiest day:               {}",
+            self.days.busiest()
+        )?;
+        writeln!(
+            destination,
+            "  busiest hour:              {}",
+            self.hours.busiest()
+        )?;
+        writeln!(
+            destination,
+            "  zero or no timestamp:      {}",
+            self.zero_timestamps
+        )
+    }
+}
+
+/// finds the period with the most entries. Because the entries are sorted,
+/// all entries of a period are added one after another. If multiple periods
+/// have the same number of entries, the earliest of them is the busiest
+#[derive(Default)]
+struct BusiestPeriod {
+    current: Option<(String, usize)>,
+    busiest: Option<(String, usize)>,
+}
+
+impl BusiestPeriod {
+    fn add(&mut self, period: String) {
+        match self.current.as_mut() {
+            Some((current, entries)) if *current == period => *entries += 1,
+            _ => {
+                self.finish_current();
+                self.current = Some((period, 1));
+            }
+        }
+    }
+
+    fn add_to_current(&mut self) {
+        if let Some((_, entries)) = self.current.as_mut() {
+            *entries += 1;
+        }
+    }
+
+    fn finish_current(&mut self) {
+        if let Some((period, entries)) = self.current.take() {
+            if !matches!(&self.busiest, Some((_, busiest)) if *busiest >= entries) {
+                self.busiest = Some((period, entries));
+            }
+        }
+    }
+
+    fn busiest(&mut self) -> String {
+        self.finish_current();
+        match &self.busiest {
+            Some((period, entries)) => format!("{period} ({entries} entries)"),
+            None => "none".to_owned(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use chrono_tz::Tz;
+    use dfir_toolkit::common::bodyfile::Bodyfile3Line;
+
+    use super::TimelineStatistics;
+    use crate::bodyfile::{ExtendedBodyfileLine, ListEntry, MACBFlags};
+
+    #[test]
+    fn summarize_timeline() {
+        let mut stats = TimelineStatistics::new(Tz::Europe__Berlin.into());
+        for (timestamp, source) in [
+            (0, Some("host1")),
+            (1715845546, Some("host1")),
+            (1715845546, Some("host2")),
+            (1715850000, None),
+            (1715932800, Some("host1")),
+            (1715932801, Some("host1")),
+        ] {
+            let line = ExtendedBodyfileLine::from(Bodyfile3Line::new().with_name("/file"))
+                .with_source(source.map(Arc::from));
+            let entry = ListEntry {
+                flags: MACBFlags::M,
+                line: Arc::new(line),
+            };
+            stats.add(timestamp, &entry);
+        }
+
+        let mut summary = Vec::new();
+        stats.write_to(&mut summary).unwrap();
+        let summary = String::from_utf8(summary).unwrap();
+        let value = |key: &str| {
+            summary
+                .lines()
+                .find_map(|line| line.trim_start().strip_prefix(key))
+                .map(|value| value.trim_start().to_owned())
+        };
+        assert_eq!(value("entries:").as_deref(), Some("6"));
+        assert_eq!(value("host1:").as_deref(), Some("4"));
+        assert_eq!(value("host2:").as_deref(), Some("1"));
+        assert_eq!(value("stdin:").as_deref(), Some("1"));
+        assert_eq!(
+            value("busiest day:").as_deref(),
+            Some("2024-05-16 (3 entries)")
+        );
+        assert_eq!(
+            value("busiest hour:").as_deref(),
+            Some("2024-05-16 09:00 (2 entries)")
+        );
+        assert_eq!(value("zero or no timestamp:").as_deref(), Some("1"));
+    }
+}
@@ -1,32 +1,42 @@
-use chrono_tz::Tz;
-use dfir_toolkit::common::ForensicsTimestamp;
+use colored::Colorize;
+use dfir_toolkit::common::{ForensicsTimestamp, Timezone};
 use std::{cell::RefCell, io::Write};
 
 use crate::bodyfile::{ListEntry, Mactime2Writer};
+use crate::output::ArtifactSource;
 
 pub struct TxtOutput<W>
 where
     W: Write + Send,
 {
-    dst_zone: Tz,
+    dst_zone: Timezone,
     last_ts: (RefCell<i64>, RefCell<String>),
     empty_ts: RefCell<String>,
     writer: W,
+
+    /// write the label of the bodyfile in brackets before the name
+    show_source: bool,
 }
 
 impl<W> TxtOutput<W>
 where
     W: Write + Send,
 {
-    pub fn new(writer: W, dst_zone: Tz) -> Self {
+    pub fn new(writer: W, dst_zone: Timezone) -> Self {
         Self {
             dst_zone,
             last_ts: (RefCell::new(i64::MIN), RefCell::new("".to_owned())),
             empty_ts: RefCell::new("                         ".to_owned()),
             writer,
+            show_source: false,
         }
     }
 
+    pub fn with_source(mut self, show_source: bool) -> Self {
+        self.show_source = show_source;
+        self
+    }
+
     #[allow(dead_code)]
     pub fn with_writer(mut self, writer: W) -> Self
     where
@@ -51,9 +61,13 @@ where
         } else {
             self.empty_ts.borrow()
         };
-        writeln!(
-            &mut self.writer,
-            "{} {:>8} {} {:<12} {:<7} {:<7} {} {}",
+        let source = if self.show_source {
+            format!("[{}] ", entry.line.source().unwrap_or_default())
+        } else {
+            String::new()
+        };
+        let line = format!(
+            "{} {:>8} {} {:<12} {:<7} {:<7} {} {}{}",
             ts,
             entry.line.get_size(),
             entry.flags,
@@ -61,11 +75,22 @@ where
             entry.line.get_uid(),
             entry.line.get_gid(),
             entry.line.get_inode(),
+            source,
             entry.line.get_name()
-        )
+        );
+
+        // `colored` decides if colors are used at all
+        match ArtifactSource::from_name(entry.line.get_name()) {
+            Some(source) => writeln!(&mut self.writer, "{}", line.color(source.color())),
+            None => writeln!(&mut self.writer, "{line}"),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.writer.flush()
     }
 
-    fn into_writer(self) -> W {
+    fn into_writer(self: Box<Self>) -> W {
         self.writer
     }
 }
@@ -96,13 +121,13 @@ mod tests {
             let bf_line = Bodyfile3Line::new().with_crtime(Created::from(unix_ts));
             let entry = ListEntry {
                 flags: MACBFlags::B,
-                line: Arc::new(bf_line),
+                line: Arc::new(bf_line.into()),
             };
 
-            let mut output = TxtOutput::new(Cursor::new(vec![]), Tz::UTC);
+            let mut output = TxtOutput::new(Cursor::new(vec![]), Tz::UTC.into());
             output.write_line(&unix_ts, &entry).unwrap();
             output.write_line(&unix_ts, &entry).unwrap();
-            let mut output = BufReader::new(Cursor::new(output.into_writer().into_inner())).lines();
+            let mut output = BufReader::new(Cursor::new(Box::new(output).into_writer().into_inner())).lines();
 
             let out_line = output.next().unwrap().unwrap();
             let out_line2 = output.next().unwrap().unwrap();
@@ -128,13 +153,13 @@ mod tests {
             let bf_line = Bodyfile3Line::new().with_crtime(Created::from(unix_ts));
             let entry = ListEntry {
                 flags: MACBFlags::B,
-                line: Arc::new(bf_line),
+                line: Arc::new(bf_line.into()),
             };
 
-            let mut output = TxtOutput::new(Cursor::new(vec![]), tz);
+            let mut output = TxtOutput::new(Cursor::new(vec![]), tz.into());
             output.write_line(&unix_ts, &entry).unwrap();
             output.write_line(&unix_ts, &entry).unwrap();
-            let mut output = BufReader::new(Cursor::new(output.into_writer().into_inner())).lines();
+            let mut output = BufReader::new(Cursor::new(Box::new(output).into_writer().into_inner())).lines();
 
             let out_line = output.next().unwrap().unwrap();
             let out_line2 = output.next().unwrap().unwrap();
@@ -1,23 +1,66 @@
-use std::io::Read;
+use std::io::{BufRead, BufReader, Error, Read};
 
 use clio::Input;
-#[cfg(feature = "gzip")]
-use flate2::read::GzDecoder;
-pub(crate) struct StreamSource(Box<dyn Read + Send>);
-
-impl From<Input> for StreamSource {
-    fn from(input: Input) -> Self {
-        #[cfg(feature = "gzip")]
-        if input.path().ends_with(".gz") {
-            return Self(Box::new(GzDecoder::new(input)));
+use flate2::bufread::MultiGzDecoder;
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+
+#[cfg(feature = "zstd")]
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+
+/// the content of an input, which is decompressed if it is compressed with
+/// gzip or zstd. Compressed inputs are detected by their extension or by
+/// their first bytes, so that compressed data can also be read from stdin.
+/// Errors which occur while reading contain the name of the input
+pub(crate) struct StreamSource {
+    name: String,
+    reader: Box<dyn Read + Send>,
+}
+
+impl TryFrom<Input> for StreamSource {
+    type Error = Error;
+
+    fn try_from(input: Input) -> Result<Self, Self::Error> {
+        let name = if input.is_std() {
+            "stdin".to_owned()
+        } else {
+            input.path().to_string_lossy().into_owned()
+        };
+        let mut reader = BufReader::new(input);
+        let magic = reader
+            .fill_buf()
+            .map_err(|why| named_error(&name, why))?
+            .to_vec();
+
+        if name.ends_with(".gz") || magic.starts_with(GZIP_MAGIC) {
+            let reader = Box::new(MultiGzDecoder::new(reader));
+            return Ok(Self { name, reader });
+        }
+
+        #[cfg(feature = "zstd")]
+        if name.ends_with(".zst") || magic.starts_with(ZSTD_MAGIC) {
+            let reader = Box::new(
+                zstd::stream::read::Decoder::with_buffer(reader)
+                    .map_err(|why| named_error(&name, why))?,
+            );
+            return Ok(Self { name, reader });
         }
 
-        Self(Box::new(input))
+        Ok(Self {
+            name,
+            reader: Box::new(reader),
+        })
     }
 }
 
 impl Read for StreamSource {
     fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
-        self.0.read(buf)
+        self.reader
+            .read(buf)
+            .map_err(|why| named_error(&self.name, why))
     }
-}
\ No newline at end of file
+}
+
+fn named_error(name: &str, why: Error) -> Error {
+    Error::new(why.kind(), format!("unable to read '{name}': {why}"))
+}
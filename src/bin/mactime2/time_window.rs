@@ -0,0 +1,76 @@
+use crate::cli::Cli;
+
+/// time range in which the displayed timeline entries must be. Zero
+/// timestamps and entries without any timestamp are outside of every window,
+/// unless `include_zero` is set
+#[derive(Copy, Clone, Default)]
+pub(crate) struct TimeWindow {
+    not_before: Option<i64>,
+    not_after: Option<i64>,
+    include_zero: bool,
+}
+
+impl TimeWindow {
+    pub(crate) fn is_restricted(&self) -> bool {
+        self.not_before.is_some() || self.not_after.is_some()
+    }
+
+    /// `timestamp` is the unix timestamp of an entry, or `-1` if the entry
+    /// has no timestamp at all
+    pub(crate) fn contains(&self, timestamp: i64) -> bool {
+        if !self.is_restricted() {
+            return true;
+        }
+        if matches!(timestamp, -1 | 0) {
+            return self.include_zero;
+        }
+        self.not_before.map(|b| timestamp >= b).unwrap_or(true)
+            && self.not_after.map(|a| timestamp <= a).unwrap_or(true)
+    }
+
+    /// returns `true` if the window ends before it begins
+    pub(crate) fn is_empty(&self) -> bool {
+        matches!((self.not_before, self.not_after), (Some(b), Some(a)) if b > a)
+    }
+}
+
+impl From<&Cli> for TimeWindow {
+    fn from(cli: &Cli) -> Self {
+        Self {
+            not_before: cli.not_before.as_ref().map(|t| t.timestamp()),
+            not_after: cli.not_after.as_ref().map(|t| t.timestamp()),
+            include_zero: cli.include_zero,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TimeWindow;
+
+    #[test]
+    fn window_excludes_zero_timestamps() {
+        let window = TimeWindow {
+            not_before: None,
+            not_after: Some(1000),
+            include_zero: false,
+        };
+        assert!(window.contains(1));
+        assert!(window.contains(1000));
+        assert!(!window.contains(1001));
+        assert!(!window.contains(0));
+        assert!(!window.contains(-1));
+
+        let window = TimeWindow {
+            include_zero: true,
+            ..window
+        };
+        assert!(window.contains(0));
+        assert!(window.contains(-1));
+        assert!(!window.contains(1001));
+
+        let window = TimeWindow::default();
+        assert!(window.contains(0));
+        assert!(window.contains(i64::MAX));
+    }
+}
@@ -1,10 +1,21 @@
 use clap::Parser;
+use clap::ValueEnum;
 use clap::ValueHint;
 use clio::ClioPath;
-use dfir_toolkit::common::HasVerboseFlag;
+use dfir_toolkit::common::{HasVerboseFlag, Verbosity};
 use getset::Getters;
 use log::LevelFilter;
 
+#[derive(ValueEnum, Clone)]
+pub(crate) enum OutputFormat {
+    /// one bodyfile line per last run time, with the run count and the prefetch hash in the name
+    Bodyfile,
+
+    /// one JSON object per line and prefetch file, with the name of the executable, the
+    /// prefetch hash, the run count, all last run times and the timestamps of the prefetch file
+    Json,
+}
+
 /// creates bodyfile from Windows Prefetch files
 #[derive(Parser, Getters)]
 #[clap(name=env!("CARGO_BIN_NAME"), author, version)]
@@ -14,12 +25,18 @@ pub(crate) struct Cli {
     #[clap(value_hint=ValueHint::FilePath)]
     prefetch_files: Vec<ClioPath>,
 
-    /// show not only the executed files, but all references files -- such as libraries -- as well
-    #[clap(short='I')]
-    include_metrics: bool,
+    /// show not only the executed files, but all files and directories which have been referenced
+    /// by the executable -- such as libraries -- as well. Every referenced path is shown once,
+    /// using the last run time as its access time
+    #[clap(short='I', long="include-references")]
+    include_references: bool,
+
+    /// output format
+    #[clap(short('F'), long("format"), value_enum, default_value_t = OutputFormat::Bodyfile)]
+    format: OutputFormat,
 
     #[clap(flatten)]
-    verbose: clap_verbosity_flag::Verbosity,
+    verbose: Verbosity,
 }
 
 impl HasVerboseFlag for Cli {
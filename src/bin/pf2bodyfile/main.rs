@@ -1,11 +1,13 @@
 mod cli;
+mod prefetch_info;
+mod prefetch_reader;
 
-use cli::Cli;
-use dfir_toolkit::common::bodyfile::Bodyfile3Line;
+use cli::{Cli, OutputFormat};
 use dfir_toolkit::common::FancyParser;
 use forensic_rs::prelude::*;
-use frnsc_prefetch::prelude::*;
 use log::{error, warn};
+use prefetch_info::PrefetchInfo;
+use prefetch_reader::read_prefetch;
 use std::path::Path;
 
 fn main() -> anyhow::Result<()> {
@@ -42,14 +44,25 @@ fn main() -> anyhow::Result<()> {
                                 modified = None;
                             }
                         }
-                        let pf_file = read_prefetch_file(pf_filename, virtual_file).unwrap();
+                        let pf_file = match read_prefetch(pf_filename, virtual_file) {
+                            Ok(pf_file) => pf_file,
+                            Err(why) => {
+                                log::warn!("unable to read '{pf_filename}': {why}");
+                                continue;
+                            }
+                        };
 
-                        pf_file.display_prefetch_file(
+                        let pf_info = PrefetchInfo::new(
+                            &pf_file,
                             pf_filename,
-                            *cli.include_metrics(),
+                            *cli.include_references(),
                             created,
                             modified,
                         )?;
+                        match cli.format() {
+                            OutputFormat::Bodyfile => pf_info.print_bodyfile(),
+                            OutputFormat::Json => pf_info.print_json()?,
+                        }
                     } else {
                         error!("invalid Unicode characters in filename: '{pf_os_filename:?}'")
                     }
@@ -64,59 +77,3 @@ fn main() -> anyhow::Result<()> {
     }
     Ok(())
 }
-
-trait DisplayPrefetchFile {
-    fn display_prefetch_file(
-        &self,
-        pf_file_name: &str,
-        include_metrics: bool,
-        created: Option<i64>,
-        modified: Option<i64>,
-    ) -> anyhow::Result<()>;
-}
-
-impl DisplayPrefetchFile for PrefetchFile {
-    fn display_prefetch_file(
-        &self,
-        pf_file_name: &str,
-        include_metrics: bool,
-        created: Option<i64>,
-        modified: Option<i64>,
-    ) -> anyhow::Result<()> {
-        for time in &self.last_run_times {
-            let accessed =
-                winstructs::timestamp::WinTimestamp::new(&time.filetime().to_le_bytes())?
-                    .to_datetime()
-                    .into();
-
-            let mut bf_line = Bodyfile3Line::new()
-                .with_owned_name(format!(
-                    "Prefetch: run '{}' (run {} times, read from '{pf_file_name}')",
-                    self.name, self.run_count
-                ))
-                .with_atime(accessed);
-
-            if let Some(ts) = created {
-                bf_line = bf_line.with_crtime(ts.into());
-            }
-            if let Some(ts) = modified {
-                bf_line = bf_line.with_mtime(ts.into());
-            }
-            println!("{bf_line}");
-
-            if include_metrics {
-                for metric in &self.metrics {
-                    let mf = &metric.file;
-                    let bf_line = Bodyfile3Line::new()
-                        .with_owned_name(format!(
-                            "Prefetch: running '{} possibly loaded '{mf}', read from '{pf_file_name}')",
-                            self.name
-                        ))
-                        .with_atime(accessed);
-                    println!("{bf_line}");
-                }
-            }
-        }
-        Ok(())
-    }
-}
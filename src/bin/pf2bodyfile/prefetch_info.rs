@@ -0,0 +1,187 @@
+use chrono::{DateTime, Utc};
+use dfir_toolkit::common::bodyfile::{Accessed, Bodyfile3Line};
+use forensic_rs::utils::time::Filetime;
+use frnsc_prefetch::prelude::PrefetchFile;
+use serde::Serialize;
+
+/// the contents of a prefetch file which are shown in the timeline
+#[derive(Serialize)]
+pub struct PrefetchInfo {
+    prefetch_file: String,
+    executable: String,
+
+    /// the hash of the path of the executable, which is part of the name of
+    /// the prefetch file
+    prefetch_hash: Option<String>,
+    version: u32,
+    run_count: u32,
+
+    /// the last run times, newest first. Prefetch files of Windows 8 and
+    /// later store up to eight run times; unused and duplicate entries are
+    /// omitted
+    last_run_times: Vec<DateTime<Utc>>,
+
+    /// timestamps of the prefetch file in the file system
+    created: Option<DateTime<Utc>>,
+    modified: Option<DateTime<Utc>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    referenced_files: Option<Vec<String>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    referenced_directories: Option<Vec<String>>,
+}
+
+impl PrefetchInfo {
+    pub fn new(
+        pf_file: &PrefetchFile,
+        pf_file_name: &str,
+        include_references: bool,
+        created: Option<i64>,
+        modified: Option<i64>,
+    ) -> anyhow::Result<Self> {
+        let references = |paths: Vec<String>| include_references.then_some(paths);
+        Ok(Self {
+            prefetch_file: pf_file_name.to_owned(),
+            executable: pf_file.name.clone(),
+            prefetch_hash: prefetch_hash(pf_file_name),
+            version: pf_file.version,
+            run_count: pf_file.run_count,
+            last_run_times: last_run_times(&pf_file.last_run_times)?,
+            created: created.and_then(|ts| DateTime::from_timestamp(ts, 0)),
+            modified: modified.and_then(|ts| DateTime::from_timestamp(ts, 0)),
+            referenced_files: references(
+                pf_file
+                    .metrics
+                    .iter()
+                    .map(|metric| metric.file.clone())
+                    .collect(),
+            ),
+            referenced_directories: references(
+                pf_file
+                    .volume
+                    .iter()
+                    .flat_map(|volume| volume.directory_strings.iter().cloned())
+                    .collect(),
+            ),
+        })
+    }
+
+    /// prints one line per last run time, and one line per referenced file
+    /// and directory
+    pub fn print_bodyfile(&self) {
+        let prefetch_hash = match &self.prefetch_hash {
+            Some(hash) => format!(", hash {hash}"),
+            None => String::new(),
+        };
+        for time in &self.last_run_times {
+            let mut bf_line = Bodyfile3Line::new()
+                .with_owned_name(format!(
+                    "Prefetch: run '{}' (run {} times{prefetch_hash}, read from '{}')",
+                    self.executable, self.run_count, self.prefetch_file
+                ))
+                .with_atime(time.into());
+
+            if let Some(ts) = &self.created {
+                bf_line = bf_line.with_crtime(ts.into());
+            }
+            if let Some(ts) = &self.modified {
+                bf_line = bf_line.with_mtime(ts.into());
+            }
+            println!("{bf_line}");
+        }
+
+        // the prefetch file does not store when a path has been referenced,
+        // so the last run time is used
+        let last_run_time = match self.last_run_times.first() {
+            Some(time) => time.into(),
+            None => Accessed::from(None),
+        };
+        let files = self
+            .referenced_files
+            .iter()
+            .flatten()
+            .map(|file| ("file", file));
+        let directories = self
+            .referenced_directories
+            .iter()
+            .flatten()
+            .map(|directory| ("directory", directory));
+        for (kind, path) in files.chain(directories) {
+            let bf_line = Bodyfile3Line::new()
+                .with_owned_name(format!(
+                    "Prefetch: {kind} '{path}' referenced by '{}' (read from '{}')",
+                    self.executable, self.prefetch_file
+                ))
+                .with_atime(last_run_time);
+            println!("{bf_line}");
+        }
+    }
+
+    pub fn print_json(&self) -> anyhow::Result<()> {
+        println!("{}", serde_json::to_string(self)?);
+        Ok(())
+    }
+}
+
+/// the name of a prefetch file is `<EXECUTABLE>-<HASH>.pf`
+fn prefetch_hash(pf_file_name: &str) -> Option<String> {
+    let (_, hash) = pf_file_name.rsplit_once('-')?;
+    let hash = match hash.rsplit_once('.') {
+        Some((hash, _)) => hash,
+        None => hash,
+    };
+    (hash.len() == 8 && hash.chars().all(|c| c.is_ascii_hexdigit())).then(|| hash.to_owned())
+}
+
+/// converts the last run times, omitting unused (zero) and duplicate entries
+fn last_run_times(times: &[Filetime]) -> anyhow::Result<Vec<DateTime<Utc>>> {
+    let mut last_run_times = Vec::new();
+    for time in times.iter().map(|time| time.filetime()) {
+        if time == 0 {
+            continue;
+        }
+        let time = winstructs::timestamp::WinTimestamp::new(&time.to_le_bytes())?.to_datetime();
+        if !last_run_times.contains(&time) {
+            last_run_times.push(time);
+        }
+    }
+    Ok(last_run_times)
+}
+
+#[cfg(test)]
+mod tests {
+    use forensic_rs::utils::time::Filetime;
+
+    use super::{last_run_times, prefetch_hash};
+
+    #[test]
+    fn omit_unused_and_duplicate_run_times() {
+        let times = [
+            Filetime::new(133_500_000_000_000_000),
+            Filetime::new(133_400_000_000_000_000),
+            Filetime::new(133_500_000_000_000_000),
+            Filetime::new(0),
+            Filetime::new(0),
+        ];
+        let times: Vec<_> = last_run_times(&times)
+            .unwrap()
+            .into_iter()
+            .map(|time| time.timestamp())
+            .collect();
+        assert_eq!(times, vec![1705526400, 1695526400]);
+    }
+
+    #[test]
+    fn parse_prefetch_hash() {
+        assert_eq!(
+            prefetch_hash("WMIADAP.EXE-F8DFDFA2.pf").as_deref(),
+            Some("F8DFDFA2")
+        );
+        assert_eq!(
+            prefetch_hash("MY-TOOL.EXE-0123ABCD.pf").as_deref(),
+            Some("0123ABCD")
+        );
+        assert!(prefetch_hash("WMIADAP.EXE.pf").is_none());
+    }
+}
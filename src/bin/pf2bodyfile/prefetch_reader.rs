@@ -0,0 +1,119 @@
+use std::io::{Cursor, Read, Seek, SeekFrom};
+
+use anyhow::bail;
+use forensic_rs::prelude::*;
+use forensic_rs::traits::vfs::VMetadata;
+use frnsc_prefetch::decompress::{decompress, CompressionAlgorithm};
+use frnsc_prefetch::prelude::*;
+
+/// signature of compressed prefetch files, which are written by Windows 8
+/// and later
+const MAM_SIGNATURE: &[u8] = b"MAM";
+
+/// larger files are rejected by `frnsc-prefetch` as well
+const MAX_DECOMPRESSED_SIZE: usize = 1_000_000;
+
+/// Windows 11 24H2 writes prefetch files of version 31, which use the layout
+/// of version 30 but are not known to `frnsc-prefetch`
+const VERSION_30: u32 = 30;
+const VERSION_31: u32 = 31;
+const KNOWN_VERSIONS: [u32; 4] = [17, 23, 26, VERSION_30];
+
+/// reads a prefetch file, which may be compressed. An unsupported version
+/// results in an error which names the version
+pub fn read_prefetch(
+    pf_filename: &str,
+    mut file: Box<dyn VirtualFile>,
+) -> anyhow::Result<PrefetchFile> {
+    let mut data = Vec::new();
+    file.read_to_end(&mut data)?;
+    if data.starts_with(MAM_SIGNATURE) {
+        data = decompress_mam(&data)?;
+    }
+
+    if data.len() < 84 || &data[4..8] != b"SCCA" {
+        bail!("invalid prefetch signature");
+    }
+    let version = u32::from_le_bytes(data[0..4].try_into().unwrap());
+    if version == VERSION_31 {
+        data[0..4].copy_from_slice(&VERSION_30.to_le_bytes());
+    } else if !KNOWN_VERSIONS.contains(&version) {
+        bail!("unsupported prefetch version {version}");
+    }
+
+    let mut pf_file =
+        read_prefetch_file_no_compressed(pf_filename, Box::new(MemoryFile::from(data)))?;
+    pf_file.version = version;
+    Ok(pf_file)
+}
+
+/// decompresses a prefetch file with a MAM header: the signature, whose
+/// upper byte contains the compression algorithm and a flag for a CRC32
+/// checksum, and the size of the decompressed data
+fn decompress_mam(data: &[u8]) -> anyhow::Result<Vec<u8>> {
+    if data.len() < 8 {
+        bail!("the compressed prefetch file is truncated");
+    }
+    let algorithm = data[3] & 0x0f;
+    let has_checksum = data[3] & 0xf0 != 0;
+    let size = u32::from_le_bytes(data[4..8].try_into().unwrap()) as usize;
+    if size > MAX_DECOMPRESSED_SIZE {
+        bail!("the decompressed prefetch file would be abnormally large ({size} bytes)");
+    }
+    let compressed = if has_checksum {
+        data.get(12..).unwrap_or_default()
+    } else {
+        &data[8..]
+    };
+
+    let mut decompressed = Vec::with_capacity(size);
+    match CompressionAlgorithm::from(u32::from(algorithm)) {
+        algorithm @ (CompressionAlgorithm::CompressionFormatLznt1
+        | CompressionAlgorithm::CompressionFormatXpress
+        | CompressionAlgorithm::CompressionFormatXpressHuff) => {
+            decompress(compressed, &mut decompressed, algorithm)?
+        }
+        _ => bail!("unsupported compression algorithm {algorithm} in prefetch file"),
+    }
+    if decompressed.len() != size {
+        bail!(
+            "the compressed prefetch file is truncated (decompressed {} of {size} bytes)",
+            decompressed.len()
+        );
+    }
+    Ok(decompressed)
+}
+
+/// provides decompressed data to `frnsc-prefetch`, which can only read from
+/// a virtual file
+struct MemoryFile(Cursor<Vec<u8>>);
+
+impl From<Vec<u8>> for MemoryFile {
+    fn from(data: Vec<u8>) -> Self {
+        Self(Cursor::new(data))
+    }
+}
+
+impl Read for MemoryFile {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.0.read(buf)
+    }
+}
+
+impl Seek for MemoryFile {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        self.0.seek(pos)
+    }
+}
+
+impl VirtualFile for MemoryFile {
+    fn metadata(&self) -> ForensicResult<VMetadata> {
+        Ok(VMetadata {
+            created: None,
+            accessed: None,
+            modified: None,
+            file_type: VFileType::File,
+            size: self.0.get_ref().len() as u64,
+        })
+    }
+}
@@ -1,6 +1,9 @@
-use clap::{Parser, ValueHint};
-use dfir_toolkit::common::HasVerboseFlag;
+use clap::{Parser, ValueEnum, ValueHint};
+use dfir_toolkit::common::{HasVerboseFlag, Verbosity};
 use log::LevelFilter;
+use regex::Regex;
+
+use crate::entry_filter::EntryFilter;
 
 /// Exporter for Windows Registry Policy Files
 #[derive(Parser, Debug)]
@@ -10,13 +13,48 @@ pub (crate) struct Cli {
     #[clap(value_hint=ValueHint::FilePath)]
     pub (crate) polfile: String,
 
+    /// output format
+    #[clap(short('F'), long("format"), value_enum, default_value_t = OutputFormat::Csv)]
+    pub (crate) format: OutputFormat,
+
+    /// export only entries of this key or of its subkeys, case insensitive.
+    /// Both '\' and '/' can be used to separate the names of keys
+    #[clap(long("key-prefix"), value_name = "KEY")]
+    pub (crate) key_prefix: Option<String>,
+
+    /// export only entries whose value name matches this regular expression,
+    /// which is case insensitive
+    #[clap(long("value-name-regex"), value_name = "REGEX", value_parser = parse_value_name_regex)]
+    pub (crate) value_name_regex: Option<Regex>,
+
     #[clap(flatten)]
-    pub (crate) verbose: clap_verbosity_flag::Verbosity,
+    pub (crate) verbose: Verbosity,
 
 }
 
+impl Cli {
+    pub(crate) fn entry_filter(&self) -> EntryFilter {
+        EntryFilter::new(self.key_prefix.as_deref(), self.value_name_regex.clone())
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum OutputFormat {
+    /// one CSV line per entry
+    Csv,
+
+    /// list of all entries, with the names of their registry types and their
+    /// data as numbers, strings, lists of strings or base64 encoded bytes.
+    /// Malformed entries are included with an 'error' field
+    Json,
+}
+
 impl HasVerboseFlag for Cli {
     fn log_level_filter(&self) -> LevelFilter {
         self.verbose.log_level_filter()
     }
-}
\ No newline at end of file
+}
+
+fn parse_value_name_regex(s: &str) -> Result<Regex, String> {
+    Regex::new(&format!("(?i){s}")).map_err(|why| format!("invalid regular expression '{s}': {why}"))
+}
@@ -0,0 +1,71 @@
+use regex::Regex;
+
+/// selects the entries which are exported, by `--key-prefix` and
+/// `--value-name-regex`. Like in the registry, names are compared case
+/// insensitive
+#[derive(Default)]
+pub(crate) struct EntryFilter {
+    /// lowercase, with backslashes as separators and without a trailing
+    /// backslash
+    key_prefix: Option<String>,
+
+    value_name_regex: Option<Regex>,
+}
+
+impl EntryFilter {
+    pub fn new(key_prefix: Option<&str>, value_name_regex: Option<Regex>) -> Self {
+        Self {
+            key_prefix: key_prefix
+                .map(|prefix| normalize_key(prefix).trim_end_matches('\\').to_owned()),
+            value_name_regex,
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.key_prefix.is_some() || self.value_name_regex.is_some()
+    }
+
+    /// returns `true` if the entry should be exported. The key must either be
+    /// the key prefix itself or one of its subkeys, so that the prefix
+    /// `Software\Policies\Microsoft\Windows` does not match the key
+    /// `Software\Policies\Microsoft\Windows Defender`
+    pub fn accepts(&self, key: &str, value_name: &str) -> bool {
+        self.key_prefix.as_ref().map_or(true, |prefix| {
+            let key = normalize_key(key);
+            key.strip_prefix(prefix.as_str())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('\\'))
+        }) && self
+            .value_name_regex
+            .as_ref()
+            .map_or(true, |regex| regex.is_match(value_name))
+    }
+}
+
+fn normalize_key(key: &str) -> String {
+    key.replace('/', "\\").to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use regex::Regex;
+
+    use super::EntryFilter;
+
+    #[test]
+    fn match_key_prefix() {
+        let filter = EntryFilter::new(Some("software/policies/microsoft/windows\\"), None);
+        assert!(filter.accepts("Software\\Policies\\Microsoft\\Windows", "a"));
+        assert!(filter.accepts("Software\\Policies\\Microsoft\\Windows\\System", "a"));
+        assert!(!filter.accepts("Software\\Policies\\Microsoft\\Windows Defender", "a"));
+        assert!(!filter.accepts("Software\\Policies\\Microsoft", "a"));
+    }
+
+    #[test]
+    fn match_value_name() {
+        let filter = EntryFilter::new(None, Some(Regex::new("(?i)^disable").unwrap()));
+        assert!(filter.is_active());
+        assert!(filter.accepts("Software", "DisableAntiSpyware"));
+        assert!(!filter.accepts("Software", "**del.DisableAntiSpyware"));
+        assert!(!EntryFilter::default().is_active());
+    }
+}
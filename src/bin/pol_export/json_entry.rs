@@ -0,0 +1,149 @@
+use std::io::Cursor;
+
+use base64::{engine::general_purpose, Engine};
+use binread::BinReaderExt;
+use dfir_toolkit::registry::types::{KeyValueData, KeyValueDataType};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::policy_file_entry::RawPolicyFileEntry;
+
+/// a policy file entry, whose data is decoded into its natural JSON type. If
+/// the entry is malformed, `error` describes why, and the fields which could
+/// not be read are omitted
+#[derive(Serialize, Default)]
+pub(crate) struct JsonEntry {
+    /// offset of the entry in the policy file, which is only set for entries
+    /// which could not be read at all
+    #[serde(skip_serializing_if = "Option::is_none")]
+    offset: Option<u64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    key: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    value_name: Option<String>,
+
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    value_type: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data: Option<Value>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl JsonEntry {
+    /// an entry at `offset` which could not be parsed
+    pub(crate) fn malformed(offset: u64, error: String) -> Self {
+        Self {
+            offset: Some(offset),
+            error: Some(error),
+            ..Default::default()
+        }
+    }
+}
+
+impl From<RawPolicyFileEntry> for JsonEntry {
+    fn from(entry: RawPolicyFileEntry) -> Self {
+        let (data, error) = match decode_data(entry.value_type, &entry.value_data) {
+            Ok(data) => (Some(data), None),
+            Err(why) => (None, Some(why)),
+        };
+        Self {
+            offset: None,
+            key: Some(entry.key),
+            value_name: Some(entry.value_name),
+            value_type: Some(type_name(entry.value_type)),
+            data,
+            error,
+        }
+    }
+}
+
+/// the name of a registry type, as it is used by the Windows API, or the
+/// number of the type if it is unknown
+fn type_name(value_type: u32) -> String {
+    match value_type {
+        0x00 => "REG_NONE",
+        0x01 => "REG_SZ",
+        0x02 => "REG_EXPAND_SZ",
+        0x03 => "REG_BINARY",
+        0x04 => "REG_DWORD",
+        0x05 => "REG_DWORD_BIG_ENDIAN",
+        0x06 => "REG_LINK",
+        0x07 => "REG_MULTI_SZ",
+        0x08 => "REG_RESOURCE_LIST",
+        0x09 => "REG_FULL_RESOURCE_DESCRIPTOR",
+        0x0a => "REG_RESOURCE_REQUIREMENTS_LIST",
+        0x0b => "REG_QWORD",
+        0x10 => "REG_FILETIME",
+        _ => return format!("0x{value_type:08x}"),
+    }
+    .to_owned()
+}
+
+/// decodes the data of an entry. Strings and numbers are decoded, and the
+/// data of all other types is encoded with base64
+fn decode_data(value_type: u32, bytes: &[u8]) -> Result<Value, String> {
+    let data_type: KeyValueDataType = Cursor::new(value_type.to_le_bytes())
+        .read_le()
+        .map_err(|_| format!("unknown registry type 0x{value_type:08x}"))?;
+    let data: KeyValueData = Cursor::new(bytes)
+        .read_le_args((data_type, bytes.len() as u32))
+        .map_err(|why| format!("invalid data: {why}"))?;
+    Ok(match data {
+        KeyValueData::RegNone => Value::Null,
+        KeyValueData::RegSZ(s) | KeyValueData::RegExpandSZ(s) | KeyValueData::RegLink(s) => {
+            Value::from(s)
+        }
+        KeyValueData::RegDWord(n) | KeyValueData::RegDWordBigEndian(n) => Value::from(n),
+        KeyValueData::RegQWord(n) => Value::from(n),
+        KeyValueData::RegMultiSZ(lines) => Value::from(lines),
+        KeyValueData::RegFileTime(timestamp) => Value::from(timestamp.to_rfc3339()),
+        KeyValueData::RegBinary(_)
+        | KeyValueData::RegResourceList(_)
+        | KeyValueData::RegFullResourceDescriptor(_)
+        | KeyValueData::RegResourceRequirementsList(_) => {
+            Value::from(general_purpose::STANDARD.encode(bytes))
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::{decode_data, type_name};
+
+    #[test]
+    fn decode_typed_data() {
+        assert_eq!(decode_data(4, &[1, 0, 0, 0]), Ok(json!(1)));
+        assert_eq!(decode_data(5, &[0, 0, 0, 1]), Ok(json!(1)));
+        assert_eq!(
+            decode_data(11, &[0, 0, 0, 0, 1, 0, 0, 0]),
+            Ok(json!(0x1_0000_0000u64))
+        );
+        assert_eq!(decode_data(1, b"a\0b\0c\0\0\0"), Ok(json!("abc")));
+        assert_eq!(
+            decode_data(7, b"a\0\0\0b\0c\0\0\0\0\0"),
+            Ok(json!(["a", "bc"]))
+        );
+        assert_eq!(decode_data(3, &[0xde, 0xad]), Ok(json!("3q0=")));
+        assert_eq!(decode_data(0, &[]), Ok(Value::Null));
+    }
+
+    #[test]
+    fn reject_malformed_data() {
+        assert!(decode_data(4, &[1, 0])
+            .unwrap_err()
+            .starts_with("invalid data"));
+        assert_eq!(
+            decode_data(0x42, &[1, 0]),
+            Err("unknown registry type 0x00000042".to_owned())
+        );
+        assert_eq!(type_name(0x42), "0x00000042");
+        assert_eq!(type_name(4), "REG_DWORD");
+    }
+}
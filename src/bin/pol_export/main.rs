@@ -1,23 +1,24 @@
-use std::{
-    fs::File,
-    io::{stdout, Read},
-};
+use std::io::{stdout, Cursor, Read, Seek, SeekFrom};
 
 use anyhow::{anyhow, Result};
 use binread::{BinReaderExt, BinResult};
-use cli::Cli;
+use cli::{Cli, OutputFormat};
 use dfir_toolkit::common::FancyParser;
 
 mod cli;
+mod entry_filter;
+mod json_entry;
 mod policy_file_entry;
 
 use csv::Writer;
+use entry_filter::EntryFilter;
+use json_entry::JsonEntry;
 use policy_file_entry::*;
 
 fn main() -> Result<()> {
     let args = Cli::parse_cli();
 
-    let mut polfile = File::open(args.polfile)?;
+    let mut polfile = Cursor::new(std::fs::read(&args.polfile)?);
 
     let mut header: [u8; 4] = [0; 4];
     polfile.read_exact(&mut header)?;
@@ -31,13 +32,23 @@ fn main() -> Result<()> {
         return Err(anyhow!("invalid version number"));
     }
 
+    let filter = args.entry_filter();
+    match args.format {
+        OutputFormat::Csv => export_csv(&mut polfile, &filter),
+        OutputFormat::Json => export_json(&mut polfile, &filter),
+    }
+}
+
+fn export_csv(polfile: &mut Cursor<Vec<u8>>, filter: &EntryFilter) -> Result<()> {
     let mut wtr = Writer::from_writer(stdout());
 
     loop {
         let entry_result: BinResult<PolicyFileEntry> = polfile.read_le();
         match entry_result {
             Ok(entry) => {
-                wtr.serialize(entry)?;
+                if filter.accepts(&entry.key, &entry.value_name) {
+                    wtr.serialize(entry)?;
+                }
             }
             Err(why) => match why {
                 binread::Error::Io(why) if why.kind() == std::io::ErrorKind::OutOfMemory => break,
@@ -53,3 +64,54 @@ fn main() -> Result<()> {
     }
     Ok(())
 }
+
+/// exports all entries as a JSON array. If an entry cannot be read at all,
+/// it is reported as malformed, and reading continues with the next '['
+/// which could begin an entry. Such entries are omitted if a filter is used,
+/// because their keys and value names are unknown
+fn export_json(polfile: &mut Cursor<Vec<u8>>, filter: &EntryFilter) -> Result<()> {
+    let mut entries = Vec::new();
+    loop {
+        let offset = polfile.position();
+        if offset >= polfile.get_ref().len() as u64 {
+            break;
+        }
+        let entry_result: BinResult<RawPolicyFileEntry> = polfile.read_le();
+        match entry_result {
+            Ok(entry) => {
+                if filter.accepts(&entry.key, &entry.value_name) {
+                    entries.push(JsonEntry::from(entry));
+                }
+            }
+            Err(binread::Error::Io(why)) if why.kind() == std::io::ErrorKind::UnexpectedEof => {
+                if !filter.is_active() {
+                    entries.push(JsonEntry::malformed(
+                        offset,
+                        "the entry is truncated".to_owned(),
+                    ));
+                }
+                break;
+            }
+            Err(why) => {
+                if !filter.is_active() {
+                    entries.push(JsonEntry::malformed(offset, why.to_string()));
+                }
+                match next_entry(polfile.get_ref(), offset + 2) {
+                    Some(next) => polfile.seek(SeekFrom::Start(next))?,
+                    None => break,
+                };
+            }
+        }
+    }
+    serde_json::to_writer_pretty(stdout(), &entries)?;
+    println!();
+    Ok(())
+}
+
+/// returns the offset of the next UTF-16 '[' at or after `offset`
+fn next_entry(data: &[u8], offset: u64) -> Option<u64> {
+    (offset as usize..data.len().saturating_sub(1))
+        .step_by(2)
+        .find(|i| data[*i..*i + 2] == *b"[\0")
+        .map(|i| i as u64)
+}
@@ -14,14 +14,14 @@ pub struct PolicyFileEntry {
     _begin: char,
 
     #[br(parse_with=parse_wide_string)]
-    key: String,
+    pub key: String,
 
     #[br(assert(_sep1 == ';'), parse_with=read_char)]
     #[serde(skip_serializing)]
     _sep1: char,
 
     #[br(parse_with=parse_wide_string)]
-    value_name: String,
+    pub value_name: String,
 
     #[br(assert(_sep2 == ';'), parse_with=read_char)]
     #[serde(skip_serializing)]
@@ -60,4 +60,42 @@ fn read_char<R: Read + Seek>(reader: &mut R, _ro: &ReadOptions, _args: ()) -> Bi
         .map(|r| r.unwrap_or(char::REPLACEMENT_CHARACTER))
         .next()
         .unwrap())
-}
\ No newline at end of file
+}
+
+/// a policy file entry whose data has not been parsed yet, so that entries
+/// with malformed data can be reported instead of being dropped
+#[derive_binread]
+#[br(little)]
+pub struct RawPolicyFileEntry {
+    #[br(assert(_begin == '['), parse_with=read_char)]
+    _begin: char,
+
+    #[br(parse_with=parse_wide_string)]
+    pub key: String,
+
+    #[br(assert(_sep1 == ';'), parse_with=read_char)]
+    _sep1: char,
+
+    #[br(parse_with=parse_wide_string)]
+    pub value_name: String,
+
+    #[br(assert(_sep2 == ';'), parse_with=read_char)]
+    _sep2: char,
+
+    pub value_type: u32,
+
+    #[br(assert(_sep3 == ';'), parse_with=read_char)]
+    _sep3: char,
+
+    #[br(temp)]
+    size: u32,
+
+    #[br(assert(_sep4 == ';'), parse_with=read_char)]
+    _sep4: char,
+
+    #[br(count=size)]
+    pub value_data: Vec<u8>,
+
+    #[br(assert(_end == ']'), parse_with=read_char)]
+    _end: char,
+}
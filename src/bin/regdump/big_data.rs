@@ -0,0 +1,311 @@
+use std::{
+    io::{Read, Seek},
+    ops::Deref,
+};
+
+use anyhow::{anyhow, bail, Result};
+use encoding_rs::{ISO_8859_15, UTF_16LE};
+use nt_hive2::{CleanHive, Hive, KeyNode, KeyValue, RegistryValue};
+
+use crate::cell_scanner::{read_cell, read_key_node, read_value_name, u16_at, u32_at};
+
+/// the data of larger values is stored in big data records, which consist of
+/// segments of this size
+const BIG_DATA_SEGMENT_SIZE: u32 = 16344;
+
+/// lists of subkey lists can only be nested once
+const MAX_SUBKEY_LIST_DEPTH: usize = 2;
+
+/// a value together with its data. `nt_hive2` appends the padding of every
+/// segment cell to the data of big data values, so their data is read again
+/// from the segments of the big data record. All other methods are delegated
+/// to the [KeyValue].
+#[derive(Clone, Copy)]
+pub(crate) struct KeyValueRef<'a> {
+    value: &'a KeyValue,
+    data: Option<&'a RegistryValue>,
+}
+
+impl<'a> KeyValueRef<'a> {
+    pub(crate) fn new(value: &'a KeyValue, data: Option<&'a RegistryValue>) -> Self {
+        Self { value, data }
+    }
+
+    /// the data of the value, which has been reassembled if it is stored in
+    /// a big data record
+    pub(crate) fn value(&self) -> &'a RegistryValue {
+        self.data.unwrap_or_else(|| self.value.value())
+    }
+}
+
+impl Deref for KeyValueRef<'_> {
+    type Target = KeyValue;
+
+    fn deref(&self) -> &Self::Target {
+        self.value
+    }
+}
+
+/// the values of a key, with the reassembled data of big data values
+pub(crate) struct KeyValues<'a> {
+    values: &'a [KeyValue],
+    big_data: Vec<Option<RegistryValue>>,
+}
+
+impl<'a> KeyValues<'a> {
+    /// reads the values of the key. If the data of a big data value cannot
+    /// be reassembled, a warning is logged and the data which has been read
+    /// by `nt_hive2` is used
+    pub(crate) fn read<RS>(hive: &mut Hive<RS, CleanHive>, keynode: &'a KeyNode) -> Self
+    where
+        RS: Read + Seek,
+    {
+        let values = keynode.values();
+        let mut big_data: Vec<Option<RegistryValue>> = values.iter().map(|_| None).collect();
+        if values.iter().any(is_big_data) {
+            if let Err(why) = read_big_data_values(hive, keynode, &mut big_data) {
+                log::warn!(
+                    "unable to read the big data values of '{}': {why}",
+                    keynode.name()
+                );
+            }
+        }
+        Self { values, big_data }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = KeyValueRef<'_>> {
+        self.values
+            .iter()
+            .zip(self.big_data.iter())
+            .map(|(value, data)| KeyValueRef::new(value, data.as_ref()))
+    }
+}
+
+/// returns `true` if the data of `value` is stored in a big data record
+pub(crate) fn is_big_data(value: &KeyValue) -> bool {
+    !value.is_resident() && value.data_size() > BIG_DATA_SEGMENT_SIZE
+}
+
+fn read_big_data_values<RS>(
+    hive: &mut Hive<RS, CleanHive>,
+    keynode: &KeyNode,
+    big_data: &mut [Option<RegistryValue>],
+) -> Result<()>
+where
+    RS: Read + Seek,
+{
+    let offset = key_node_offset(hive, keynode)?;
+    let node = read_key_node(&read_cell(hive, offset)?, offset, false)
+        .ok_or_else(|| anyhow!("invalid key node at offset 0x{offset:08x}"))?;
+    let list = read_cell(hive, node.values_list)?;
+    let mut value_offsets = Vec::new();
+    for i in 0..node.values_count as usize {
+        let offset = u32_at(&list, 4 + i * 4)
+            .ok_or_else(|| anyhow!("the list of {} values is truncated", node.values_count))?;
+        value_offsets.push(offset);
+    }
+
+    for (value, data) in keynode.values().iter().zip(big_data.iter_mut()) {
+        if !is_big_data(value) {
+            continue;
+        }
+        // the values are stored in the order of the values list, but names
+        // are compared in case some value could not be read by `nt_hive2`
+        let mut offset = None;
+        for candidate in value_offsets.iter() {
+            let cell = read_cell(hive, *candidate)?;
+            if read_value_name(&cell, 0).as_deref() == Some(value.name()) {
+                offset = Some(*candidate);
+                break;
+            }
+        }
+        let offset =
+            offset.ok_or_else(|| anyhow!("unable to find the value '{}'", value.name()))?;
+        match read_big_data_value(hive, offset, value) {
+            Ok(value) => *data = value,
+            Err(why) => log::warn!(
+                "unable to reassemble the data of the value '{}': {why}",
+                value.name()
+            ),
+        }
+    }
+    Ok(())
+}
+
+/// finds the offset of a key node, by searching it in the subkeys list of its
+/// parent, because `nt_hive2` does not expose the offsets of keys
+fn key_node_offset<RS>(hive: &mut Hive<RS, CleanHive>, keynode: &KeyNode) -> Result<u32>
+where
+    RS: Read + Seek,
+{
+    let root = hive.root_cell_offset().0;
+    let root_node = read_key_node(&read_cell(hive, root)?, root, false);
+    if root_node.is_some_and(|node| {
+        node.parent == keynode.parent.0 && node.name.as_deref() == Some(keynode.name())
+    }) {
+        return Ok(root);
+    }
+
+    let parent = read_key_node(&read_cell(hive, keynode.parent.0)?, keynode.parent.0, false)
+        .ok_or_else(|| anyhow!("invalid parent key at offset 0x{:08x}", keynode.parent.0))?;
+
+    let mut offsets = Vec::new();
+    read_subkey_offsets(hive, parent.subkeys_list, 0, &mut offsets)?;
+    for offset in offsets {
+        let cell = read_cell(hive, offset)?;
+        if let Some(node) = read_key_node(&cell, offset, false) {
+            if node.name.as_deref() == Some(keynode.name()) {
+                return Ok(offset);
+            }
+        }
+    }
+    bail!(
+        "unable to find the key '{}' in the subkeys of its parent",
+        keynode.name()
+    )
+}
+
+fn read_subkey_offsets<RS>(
+    hive: &mut Hive<RS, CleanHive>,
+    list_offset: u32,
+    depth: usize,
+    offsets: &mut Vec<u32>,
+) -> Result<()>
+where
+    RS: Read + Seek,
+{
+    let list = read_cell(hive, list_offset)?;
+    let count = u16_at(&list, 6).unwrap_or_default() as usize;
+    let entry_size = match list.get(4..6) {
+        Some(b"lf") | Some(b"lh") => 8,
+        Some(b"li") | Some(b"ri") => 4,
+        _ => bail!("invalid subkeys list at offset 0x{list_offset:08x}"),
+    };
+    for i in 0..count {
+        let offset = u32_at(&list, 8 + i * entry_size).ok_or_else(|| {
+            anyhow!("the subkeys list at offset 0x{list_offset:08x} is truncated")
+        })?;
+        if list.get(4..6) == Some(b"ri") {
+            if depth >= MAX_SUBKEY_LIST_DEPTH {
+                bail!("the subkeys lists are nested too deeply");
+            }
+            read_subkey_offsets(hive, offset, depth + 1, offsets)?;
+        } else {
+            offsets.push(offset);
+        }
+    }
+    Ok(())
+}
+
+/// reassembles the data of the big data value whose record is at
+/// `value_offset`. Returns `None` for types whose data is not displayed
+pub(crate) fn read_big_data_value<RS>(
+    hive: &mut Hive<RS, CleanHive>,
+    value_offset: u32,
+    value: &KeyValue,
+) -> Result<Option<RegistryValue>>
+where
+    RS: Read + Seek,
+{
+    let bytes = read_big_data(hive, value_offset, value.data_size())?;
+    Ok(convert_data(value.value(), bytes))
+}
+
+/// reads the data of a big data value from its segments. Every segment
+/// contains up to 16344 bytes of data, followed by the padding of its cell
+fn read_big_data<RS>(
+    hive: &mut Hive<RS, CleanHive>,
+    value_offset: u32,
+    data_size: u32,
+) -> Result<Vec<u8>>
+where
+    RS: Read + Seek,
+{
+    let value = read_cell(hive, value_offset)?;
+    let db_offset = u32_at(&value, 12).ok_or_else(|| anyhow!("the value record is truncated"))?;
+    let db = read_cell(hive, db_offset)?;
+    if db.get(4..6) != Some(b"db") {
+        bail!("there is no big data record at offset 0x{db_offset:08x}");
+    }
+    let count = u16_at(&db, 6).unwrap_or_default() as u32;
+    if count != data_size.div_ceil(BIG_DATA_SEGMENT_SIZE) {
+        bail!("{count} segments cannot contain {data_size} bytes");
+    }
+    let list_offset = u32_at(&db, 8).unwrap_or_default();
+    let list = read_cell(hive, list_offset)?;
+
+    let mut data = Vec::with_capacity(data_size as usize);
+    for i in 0..count as usize {
+        let segment_offset = u32_at(&list, 4 + i * 4)
+            .ok_or_else(|| anyhow!("the list of {count} segments is truncated"))?;
+        let segment = read_cell(hive, segment_offset)?;
+        let remaining = data_size as usize - data.len();
+        let size = remaining.min(BIG_DATA_SEGMENT_SIZE as usize);
+        let bytes = segment
+            .get(4..4 + size)
+            .ok_or_else(|| anyhow!("segment {i} at offset 0x{segment_offset:08x} is truncated"))?;
+        data.extend_from_slice(bytes);
+    }
+    Ok(data)
+}
+
+/// converts reassembled data into the type of the data which has been read
+/// by `nt_hive2`. Returns `None` for types whose data is not displayed
+fn convert_data(value: &RegistryValue, bytes: Vec<u8>) -> Option<RegistryValue> {
+    let data = match value {
+        RegistryValue::RegBinary(_) => RegistryValue::RegBinary(bytes),
+        RegistryValue::RegSZ(_) => RegistryValue::RegSZ(decode_string(&bytes)),
+        RegistryValue::RegExpandSZ(_) => RegistryValue::RegExpandSZ(decode_string(&bytes)),
+        RegistryValue::RegMultiSZ(_) => {
+            let strings = decode_string(&bytes);
+            if strings.is_empty() {
+                RegistryValue::RegMultiSZ(Vec::new())
+            } else {
+                RegistryValue::RegMultiSZ(strings.split('\0').map(str::to_owned).collect())
+            }
+        }
+        _ => return None,
+    };
+    Some(data)
+}
+
+/// decodes a string like `nt_hive2` does, and removes the terminating null
+/// characters
+fn decode_string(bytes: &[u8]) -> String {
+    let (s, _, had_errors) = UTF_16LE.decode(bytes);
+    let s = if had_errors {
+        ISO_8859_15.decode(bytes).0
+    } else {
+        s
+    };
+    s.trim_end_matches('\0').to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use nt_hive2::RegistryValue;
+
+    use super::convert_data;
+
+    fn utf16(s: &str) -> Vec<u8> {
+        s.encode_utf16().flat_map(u16::to_le_bytes).collect()
+    }
+
+    #[test]
+    fn convert_reassembled_data() {
+        let data = convert_data(&RegistryValue::RegSZ(String::new()), utf16("abc\0"));
+        assert!(matches!(data, Some(RegistryValue::RegSZ(s)) if s == "abc"));
+
+        let data = convert_data(&RegistryValue::RegMultiSZ(Vec::new()), utf16("a\0bc\0\0"));
+        assert!(matches!(data, Some(RegistryValue::RegMultiSZ(lines)) if lines == ["a", "bc"]));
+
+        let data = convert_data(&RegistryValue::RegBinary(Vec::new()), vec![1, 2, 3]);
+        assert!(matches!(data, Some(RegistryValue::RegBinary(data)) if data == [1, 2, 3]));
+
+        assert!(convert_data(&RegistryValue::RegNone, vec![1, 2, 3]).is_none());
+    }
+}
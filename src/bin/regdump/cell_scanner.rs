@@ -0,0 +1,275 @@
+use std::io::{Read, Seek, SeekFrom};
+
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+use encoding_rs::{ISO_8859_15, UTF_16LE};
+use nt_hive2::{CleanHive, Hive};
+use winstructs::timestamp::WinTimestamp;
+
+const HBIN_HEADER_SIZE: usize = 32;
+
+/// offset of the fixed size part of the key node record, relative to the
+/// beginning of its cell
+const NK_HEADER_SIZE: usize = 4 + 76;
+
+const VK_HEADER_SIZE: usize = 4 + 20;
+
+const KEY_HIVE_ENTRY: u16 = 0x0004;
+const KEY_COMP_NAME: u16 = 0x0020;
+const VALUE_COMP_NAME: u16 = 0x0001;
+
+/// a key node record, which has been found in an allocated or in a free cell.
+/// Only the fields which are needed to recover deleted keys are read.
+pub(crate) struct RawKeyNode {
+    pub(crate) offset: u32,
+    pub(crate) is_deleted: bool,
+    pub(crate) is_root: bool,
+
+    /// `None` if the name exceeds the cell or cannot be decoded
+    pub(crate) name: Option<String>,
+
+    pub(crate) parent: u32,
+    pub(crate) subkeys_list: u32,
+    pub(crate) timestamp: Option<DateTime<Utc>>,
+    pub(crate) values_count: u32,
+    pub(crate) values_list: u32,
+
+    /// offset of the cell which contains the security descriptor of the key
+    pub(crate) security: u32,
+}
+
+/// the records which have been found in the cells of a hive
+#[derive(Default)]
+pub(crate) struct RawCells {
+    pub(crate) key_nodes: Vec<RawKeyNode>,
+
+    /// offsets of the value records which have been found in free cells
+    pub(crate) deleted_values: Vec<u32>,
+}
+
+/// reads all hive bins into memory, so that the offsets in the returned
+/// data are cell offsets
+pub(crate) fn read_hive_bins<RS>(hive: &mut Hive<RS, CleanHive>) -> Result<Vec<u8>>
+where
+    RS: Read + Seek,
+{
+    let mut data = Vec::new();
+    hive.seek(SeekFrom::Start(0))?;
+    hive.read_to_end(&mut data)?;
+    Ok(data)
+}
+
+/// reads the cell at `offset`, beginning with its size
+pub(crate) fn read_cell<RS>(hive: &mut Hive<RS, CleanHive>, offset: u32) -> Result<Vec<u8>>
+where
+    RS: Read + Seek,
+{
+    let mut size = [0; 4];
+    hive.seek(SeekFrom::Start(offset.into()))?;
+    hive.read_exact(&mut size)?;
+    let size = i32::from_le_bytes(size).unsigned_abs() as usize;
+    if size < 8 {
+        bail!("invalid cell size {size} at offset 0x{offset:08x}");
+    }
+    let mut cell = vec![0; size];
+    hive.seek(SeekFrom::Start(offset.into()))?;
+    hive.read_exact(&mut cell)?;
+    Ok(cell)
+}
+
+/// walks through the cells of all hive bins. `data` contains the hive
+/// without its base block, so that offsets in `data` are cell offsets.
+/// Free cells may contain multiple records, because adjacent free cells are
+/// merged. So, every 8-byte aligned position in a free cell is checked for
+/// the signature of a key node or value record.
+pub(crate) fn scan_cells(data: &[u8]) -> RawCells {
+    let mut cells = RawCells::default();
+    let mut hbin_offset = 0;
+    while data.get(hbin_offset..hbin_offset + 4) == Some(b"hbin") {
+        let hbin_size = match u32_at(data, hbin_offset + 8) {
+            Some(size) if size as usize >= HBIN_HEADER_SIZE => size as usize,
+            _ => break,
+        };
+        let hbin_end = (hbin_offset + hbin_size).min(data.len());
+
+        let mut cell_offset = hbin_offset + HBIN_HEADER_SIZE;
+        while let Some(raw_size) = u32_at(data, cell_offset).map(|s| s as i32) {
+            let cell_size = raw_size.unsigned_abs() as usize;
+            if cell_size < 8 || cell_offset + cell_size > hbin_end {
+                log::warn!("invalid cell size {raw_size} at offset 0x{cell_offset:08x}");
+                break;
+            }
+            let cell = &data[cell_offset..cell_offset + cell_size];
+            if raw_size > 0 {
+                for record_offset in (0..cell_size).step_by(8) {
+                    let record = &cell[record_offset..];
+                    let offset = (cell_offset + record_offset) as u32;
+                    match record.get(4..6) {
+                        Some(b"nk") => cells.key_nodes.extend(read_key_node(record, offset, true)),
+                        Some(b"vk") if record.len() >= VK_HEADER_SIZE => {
+                            cells.deleted_values.push(offset)
+                        }
+                        _ => (),
+                    }
+                }
+            } else if cell.get(4..6) == Some(b"nk") {
+                cells
+                    .key_nodes
+                    .extend(read_key_node(cell, cell_offset as u32, false));
+            }
+            cell_offset += cell_size;
+            if cell_offset >= hbin_end {
+                break;
+            }
+        }
+        hbin_offset += hbin_size;
+    }
+    cells
+}
+
+/// reads a key node record, beginning with its cell size. Returns `None` if
+/// the fixed size part of the record exceeds `cell`
+pub(crate) fn read_key_node(cell: &[u8], offset: u32, is_deleted: bool) -> Option<RawKeyNode> {
+    if cell.len() < NK_HEADER_SIZE {
+        return None;
+    }
+    let flags = u16_at(cell, 6)?;
+    let name_length = u16_at(cell, 76)? as usize;
+    let name = cell
+        .get(NK_HEADER_SIZE..NK_HEADER_SIZE + name_length)
+        .filter(|name| !name.is_empty())
+        .and_then(|name| decode_name(name, flags & KEY_COMP_NAME != 0));
+
+    let timestamp = match cell.get(8..16) {
+        Some(filetime) if filetime != [0; 8] => {
+            WinTimestamp::new(filetime).ok().map(|ts| ts.to_datetime())
+        }
+        _ => None,
+    };
+
+    Some(RawKeyNode {
+        offset,
+        is_deleted,
+        is_root: flags & KEY_HIVE_ENTRY != 0,
+        name,
+        parent: u32_at(cell, 20)?,
+        subkeys_list: u32_at(cell, 32)?,
+        timestamp,
+        values_count: u32_at(cell, 40)?,
+        values_list: u32_at(cell, 44)?,
+        security: u32_at(cell, 48)?,
+    })
+}
+
+/// reads the name of the value record in the cell at `offset`. Returns `None`
+/// if there is no value record or if its name cannot be read
+pub(crate) fn read_value_name(data: &[u8], offset: usize) -> Option<String> {
+    let cell = data.get(offset..)?;
+    if cell.get(4..6) != Some(b"vk") {
+        return None;
+    }
+    let name_length = u16_at(cell, 6)? as usize;
+    if name_length == 0 {
+        return Some("(Default)".to_owned());
+    }
+    let flags = u16_at(cell, 20)?;
+    let name = cell.get(VK_HEADER_SIZE..VK_HEADER_SIZE + name_length)?;
+    decode_name(name, flags & VALUE_COMP_NAME != 0)
+}
+
+/// returns the data type of the value record in the cell at `offset`
+pub(crate) fn read_value_type(data: &[u8], offset: usize) -> Option<u32> {
+    u32_at(data, offset + 16)
+}
+
+/// returns `true` if there is a value record in the cell at `offset`
+pub(crate) fn is_value_record(data: &[u8], offset: usize) -> bool {
+    data.get(offset + 4..offset + 6) == Some(b"vk") && data.len() >= offset + VK_HEADER_SIZE
+}
+
+/// names are stored in Latin-1 if they are compressed, and in UTF-16
+/// otherwise. This is the same encoding which is used by `nt_hive2`
+fn decode_name(name: &[u8], compressed: bool) -> Option<String> {
+    let (name, _, had_errors) = if compressed {
+        ISO_8859_15.decode(name)
+    } else {
+        UTF_16LE.decode(name)
+    };
+    (!had_errors).then(|| name.to_string())
+}
+
+pub(crate) fn u32_at(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(
+        data.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+pub(crate) fn u16_at(data: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(
+        data.get(offset..offset + 2)?.try_into().ok()?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::scan_cells;
+
+    fn hbin(cells: &[Vec<u8>]) -> Vec<u8> {
+        let mut data = b"hbin".to_vec();
+        data.resize(32, 0);
+        for cell in cells {
+            data.extend(cell);
+        }
+        let size = data.len() as u32;
+        data[8..12].copy_from_slice(&size.to_le_bytes());
+        data
+    }
+
+    fn cell(size: i32, record: &[u8]) -> Vec<u8> {
+        let mut cell = size.to_le_bytes().to_vec();
+        cell.extend(record);
+        cell.resize(size.unsigned_abs() as usize, 0);
+        cell
+    }
+
+    fn key_node(name: &str, parent: u32, flags: u16) -> Vec<u8> {
+        let mut record = b"nk".to_vec();
+        record.extend(flags.to_le_bytes());
+        record.resize(76, 0);
+        record[16..20].copy_from_slice(&parent.to_le_bytes());
+        record[72..74].copy_from_slice(&(name.len() as u16).to_le_bytes());
+        record.extend(name.as_bytes());
+        record
+    }
+
+    #[test]
+    fn find_records_in_free_cells() {
+        let mut free_cell = cell(96, &key_node("deleted", 0x20, 0x20));
+        free_cell.extend(cell(32, b"vk"));
+        let size = free_cell.len() as i32;
+        free_cell[0..4].copy_from_slice(&size.to_le_bytes());
+
+        let data = hbin(&[cell(-96, &key_node("ROOT", u32::MAX, 0x24)), free_cell]);
+        let cells = scan_cells(&data);
+
+        assert_eq!(cells.key_nodes.len(), 2);
+        assert_eq!(cells.key_nodes[0].name.as_deref(), Some("ROOT"));
+        assert!(cells.key_nodes[0].is_root);
+        assert!(!cells.key_nodes[0].is_deleted);
+        assert_eq!(cells.key_nodes[1].name.as_deref(), Some("deleted"));
+        assert_eq!(cells.key_nodes[1].offset, 0x80);
+        assert_eq!(cells.key_nodes[1].parent, 0x20);
+        assert!(cells.key_nodes[1].is_deleted);
+        assert_eq!(cells.deleted_values, vec![0xe0]);
+    }
+
+    #[test]
+    fn keep_keys_with_unreadable_names() {
+        let mut record = key_node("x", 0x20, 0x20);
+        record[72..74].copy_from_slice(&1000u16.to_le_bytes());
+        let data = hbin(&[cell(88, &record)]);
+        let cells = scan_cells(&data);
+        assert_eq!(cells.key_nodes.len(), 1);
+        assert!(cells.key_nodes[0].name.is_none());
+    }
+}
@@ -1,40 +1,247 @@
 use std::{path::PathBuf, fs::File};
 
-use clap::{Parser, ValueHint};
-use dfir_toolkit::common::HasVerboseFlag;
+use clap::{Parser, ValueEnum, ValueHint};
+use dfir_toolkit::common::{HasVerboseFlag, OutputArgs, Rfc3339Datetime, TimezoneArgs, Verbosity};
+use dfir_toolkit::registry::find_transaction_logs;
 use log::LevelFilter;
 use nt_hive2::{HiveParseMode, Hive};
+use regex::Regex;
+
+use crate::timestamp_filter::TimestampFilter;
+
+#[derive(ValueEnum, Clone, PartialEq, Eq)]
+pub(crate) enum OutputFormat {
+    /// text output, similar to the format of .reg files
+    Text,
+
+    /// export which can be imported with regedit or 'reg.exe import'. The
+    /// root key of the hive is replaced by '--reg-root'
+    Reg,
+
+    /// nested structure of all keys, with their values and subkeys
+    Json,
+
+    /// one JSON object per line for every key, with its full path
+    Jsonl,
+
+    /// one bodyfile line per key, using the last-written time as mtime
+    Bodyfile,
+}
+
+#[derive(ValueEnum, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Decoder {
+    /// decode the ROT13 encoded names and the binary data of UserAssist entries
+    Userassist,
+
+    /// print the entries of the AppCompatCache (aka ShimCache) of a SYSTEM
+    /// hive instead of its keys. The 'text' format displays them as a table
+    Shimcache,
+
+    /// print the folders which are referenced by the shellbags of a
+    /// NTUSER.DAT or UsrClass.dat hive instead of its keys. The 'text' format
+    /// displays them as a table
+    Shellbags,
+}
 
 /// parses registry hive files and prints a bodyfile
+///
+/// The exit code is 3 if a key which has been requested with '--path' or
+/// '--path-regex' does not exist, and 2 if the arguments are invalid.
 #[derive(Parser)]
-#[clap(name=env!("CARGO_BIN_NAME"), author, version, long_about = None)]
+#[clap(name=env!("CARGO_BIN_NAME"), author, version)]
 pub (crate) struct Cli {
     /// name of the file to dump
     #[arg(value_parser = validate_file, value_hint=ValueHint::FilePath)]
     pub(crate) hive_file: PathBuf,
 
     /// transaction LOG file(s). This argument can be specified one or two times.
+    /// By default, the files with the extensions '.LOG1' and '.LOG2' next to
+    /// the hive file are used, if the hive is dirty
     #[clap(short('L'), long("log"), value_hint=ValueHint::FilePath)]
     #[arg(value_parser = validate_file)]
     pub (crate) logfiles: Vec<PathBuf>,
 
-    /// print as bodyfile format
-    #[clap(short('b'), long("bodyfile"))]
+    /// don't apply any transaction logs, but treat the hive as if it was clean
+    #[clap(long("no-logs"), conflicts_with = "logfiles")]
+    pub (crate) no_logs: bool,
+
+    /// print as bodyfile format (same as '--format bodyfile')
+    #[clap(short('b'), long("bodyfile"), conflicts_with = "format")]
     pub (crate) display_bodyfile: bool,
 
+    /// output format. Binary values are encoded with base64 in the JSON formats
+    #[clap(short('F'), long("format"), value_enum, default_value_t = OutputFormat::Text)]
+    pub (crate) format: OutputFormat,
+
+    /// append the number of values to the name of every key, if output is
+    /// in bodyfile format
+    #[clap(long("value-count"))]
+    pub (crate) value_count: bool,
+
+    /// dump only this key and its descendants. The path is relative to the
+    /// root key, its parts are separated by backslashes, and key names are
+    /// compared case insensitive. This argument can be specified multiple times.
+    #[clap(short('p'), long("path"), value_name = "KEY_PATH")]
+    pub (crate) paths: Vec<String>,
+
+    /// dump only the keys whose path relative to the root key matches this
+    /// regular expression (case insensitive), together with their
+    /// descendants. This argument can be specified multiple times.
+    #[clap(short('P'), long("path-regex"), value_name = "REGEX", value_parser = parse_path_regex)]
+    pub (crate) path_regexes: Vec<Regex>,
+
+    /// print only keys which have been written at or after this time (hint:
+    /// use RFC 3339 syntax, or only a date like '2024-03-01')
+    #[clap(long("modified-after"), value_name = "TIMESTAMP")]
+    pub (crate) modified_after: Option<Rfc3339Datetime>,
+
+    /// print only keys which have been written at or before this time (hint:
+    /// use RFC 3339 syntax, or only a date like '2024-03-01')
+    #[clap(long("modified-before"), value_name = "TIMESTAMP")]
+    pub (crate) modified_before: Option<Rfc3339Datetime>,
+
+    /// print also the ancestors of keys which match '--modified-after' or
+    /// '--modified-before', even if they don't match themselves
+    #[clap(long("with-ancestors"))]
+    pub (crate) with_ancestors: bool,
+
+    /// decode the values of well-known keys. UserAssist entries are also
+    /// decoded if their keys have been selected with '--path' or '--path-regex'.
+    /// This argument can be specified multiple times.
+    #[clap(long("decode"), value_enum, value_name = "DECODER")]
+    pub (crate) decoders: Vec<Decoder>,
+
+    /// number of the control set which contains the AppCompatCache. By
+    /// default, the control set which is currently in use is taken from the
+    /// 'Select' key
+    #[clap(long("control-set"), value_name = "NUMBER")]
+    pub (crate) control_set: Option<u32>,
+
+    /// don't decode the values of well-known keys, but display their raw data
+    #[clap(long("raw"), conflicts_with = "decoders")]
+    pub (crate) raw: bool,
+
+    /// recover deleted keys and values from the free cells of the hive and
+    /// display them together with the existing keys. Recovered keys are
+    /// marked as deleted, and their contents may be inconsistent
+    #[clap(long)]
+    pub (crate) deleted: bool,
+
+    /// display the owner, the group and the access control list of every
+    /// key, if output is in text or JSON format
+    #[clap(long)]
+    pub (crate) security: bool,
+
+    /// print every key name, value name and string value whose contents match
+    /// this regular expression, instead of the keys. The whole hive is
+    /// searched, unless keys have been selected with '--path' or
+    /// '--path-regex'. Use '(?i)' for a case insensitive search
+    #[clap(long, value_name = "REGEX", value_parser = parse_regex, conflicts_with = "decoders")]
+    pub (crate) search: Option<Regex>,
+
+    /// search '--search' also in the data of binary values, which is
+    /// interpreted as ASCII and as UTF-16LE text
+    #[clap(long("search-binary"), requires = "search")]
+    pub (crate) search_binary: bool,
+
     /// ignore the base block (e.g. if it was encrypted by some ransomware)
     #[clap(short('I'), long)]
     pub (crate) ignore_base_block: bool,
 
-    /// hide timestamps, if output is in reg format
+    /// hide timestamps, if output is in text format
     #[clap(short('T'), long)]
     pub (crate) hide_timestamps: bool,
 
+    // only used for text output and tables; JSON output and bodyfiles always use UTC
+    #[clap(flatten)]
+    pub(crate) timezone: TimezoneArgs,
+
+    /// display binary values as hexdump with offsets and ASCII characters,
+    /// if output is in text format
+    #[clap(long)]
+    pub (crate) hexdump: bool,
+
+    /// maximum number of bytes which are displayed per value by '--hexdump'
+    #[clap(long, value_name = "BYTES", default_value_t = 256, requires = "hexdump")]
+    pub (crate) hexdump_limit: usize,
+
+    /// key which replaces the root key of the hive if output is in reg format.
+    /// By default, this is the name of the hive file below HKEY_LOCAL_MACHINE,
+    /// like 'HKEY_LOCAL_MACHINE\SOFTWARE'
+    #[clap(long("reg-root"), value_name = "KEY")]
+    pub (crate) reg_root: Option<String>,
+
+    /// encode the output as UTF-16LE with a byte order mark, like regedit
+    /// does, if output is in reg format
+    #[clap(long)]
+    pub (crate) utf16: bool,
+
+    #[clap(flatten)]
+    pub(crate) output: OutputArgs,
+
     #[clap(flatten)]
-    pub(crate) verbose: clap_verbosity_flag::Verbosity,
+    pub(crate) verbose: Verbosity,
 }
 
 impl Cli {
+    pub(crate) fn output_format(&self) -> OutputFormat {
+        if self.display_bodyfile {
+            OutputFormat::Bodyfile
+        } else {
+            self.format.clone()
+        }
+    }
+
+    /// returns the transaction logs which have been specified with '--log',
+    /// or the logs which have been found next to the hive file
+    pub(crate) fn transaction_logs(&self) -> Vec<PathBuf> {
+        if self.logfiles.is_empty() {
+            find_transaction_logs(&self.hive_file)
+        } else {
+            self.logfiles.clone()
+        }
+    }
+
+    pub(crate) fn timestamp_filter(&self) -> TimestampFilter {
+        TimestampFilter::new(
+            self.modified_after.as_deref().copied(),
+            self.modified_before.as_deref().copied(),
+        )
+    }
+
+    /// the key which replaces the root key of the hive in '.reg' exports
+    pub(crate) fn reg_root(&self) -> String {
+        match &self.reg_root {
+            Some(root) => root.trim_end_matches('\\').to_owned(),
+            None => {
+                let hive_name = self
+                    .hive_file
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().to_uppercase())
+                    .unwrap_or_default();
+                format!("HKEY_LOCAL_MACHINE\\{hive_name}")
+            }
+        }
+    }
+
+    /// returns `true` if the values of UserAssist keys shall be decoded
+    pub(crate) fn decode_userassist(&self) -> bool {
+        !self.raw
+            && (self.decoders.contains(&Decoder::Userassist)
+                || !self.paths.is_empty()
+                || !self.path_regexes.is_empty())
+    }
+
+    /// returns `true` if the AppCompatCache shall be printed instead of the keys
+    pub(crate) fn decode_shimcache(&self) -> bool {
+        self.decoders.contains(&Decoder::Shimcache)
+    }
+
+    /// returns `true` if the shellbags shall be printed instead of the keys
+    pub(crate) fn decode_shellbags(&self) -> bool {
+        self.decoders.contains(&Decoder::Shellbags)
+    }
+
     pub fn parse_mode(&self) -> HiveParseMode {
         if self.ignore_base_block {
             match File::open(&self.hive_file) {
@@ -71,6 +278,14 @@ impl HasVerboseFlag for Cli {
     }
 }
 
+fn parse_path_regex(s: &str) -> Result<Regex, String> {
+    Regex::new(&format!("(?i){s}")).map_err(|why| format!("invalid regular expression '{s}': {why}"))
+}
+
+fn parse_regex(s: &str) -> Result<Regex, String> {
+    Regex::new(s).map_err(|why| format!("invalid regular expression '{s}': {why}"))
+}
+
 fn validate_file(s: &str) -> Result<PathBuf, String> {
     let pb = PathBuf::from(s);
     if pb.is_file() && pb.exists() {
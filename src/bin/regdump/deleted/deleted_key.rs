@@ -0,0 +1,46 @@
+use chrono::{DateTime, Utc};
+use nt_hive2::{KeyValue, RegistryValue};
+
+use crate::big_data::KeyValueRef;
+
+/// a key which has been recovered from a free cell. Its values and even its
+/// path may be inconsistent, because the cells which it refers to may have
+/// been reused since the key has been deleted.
+pub(crate) struct DeletedKey {
+    pub(crate) name: String,
+
+    /// full path of the key. Keys whose parent could not be found are placed
+    /// below the `$Orphaned` key, which is a child of the root key
+    pub(crate) path: String,
+
+    pub(crate) last_written: Option<DateTime<Utc>>,
+    pub(crate) values: Vec<DeletedValue>,
+
+    /// describes where the key has been found and which parts of it could
+    /// not be recovered
+    pub(crate) notes: Vec<String>,
+}
+
+/// a value of a deleted key, or a deleted value whose key is unknown
+pub(crate) struct DeletedValue {
+    pub(crate) name: String,
+
+    /// `None` if the data of the value could not be read
+    pub(crate) value: Option<KeyValue>,
+
+    /// the data, if it has been reassembled from a big data record or
+    /// decoded from the value record itself, because `nt_hive2` does not
+    /// read it correctly
+    pub(crate) data: Option<RegistryValue>,
+
+    pub(crate) notes: Vec<String>,
+}
+
+impl DeletedValue {
+    /// the value together with its decoded data, if the data could be read
+    pub(crate) fn key_value(&self) -> Option<KeyValueRef<'_>> {
+        self.value
+            .as_ref()
+            .map(|value| KeyValueRef::new(value, self.data.as_ref()))
+    }
+}
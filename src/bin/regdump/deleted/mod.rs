@@ -0,0 +1,268 @@
+mod deleted_key;
+
+pub(crate) use deleted_key::*;
+
+use std::{
+    collections::{HashMap, HashSet},
+    io::{Read, Seek, SeekFrom},
+};
+
+use anyhow::{bail, Result};
+use binread::BinReaderExt;
+use dfir_toolkit::registry::read_resident_data;
+use nt_hive2::{CleanHive, Hive, KeyValue};
+
+use crate::big_data::{is_big_data, read_big_data_value};
+use crate::cell_scanner::{
+    is_value_record, read_hive_bins, read_value_name, read_value_type, scan_cells, u32_at,
+    RawKeyNode,
+};
+
+/// name of the pseudo key which contains the deleted keys and values whose
+/// parent key could not be found
+const ORPHANED: &str = "$Orphaned";
+
+/// parent chains which are longer than this are considered to be cyclic
+const MAX_DEPTH: usize = 512;
+
+/// a larger number of values is most probably the result of garbage in a
+/// reused cell
+const MAX_VALUES_COUNT: u32 = 0x10000;
+
+/// the data of larger values is not read, because their data offset is most
+/// probably garbage
+const MAX_DATA_SIZE: u32 = 0x0100_0000;
+
+/// the deleted keys of a hive, grouped by the paths of their parent keys
+#[derive(Default)]
+pub(crate) struct DeletedKeys {
+    children: HashMap<String, Vec<DeletedKey>>,
+}
+
+impl DeletedKeys {
+    /// scans the free cells of the hive for key and value records. The
+    /// paths of recovered keys begin with `root_name`, so that they match
+    /// the paths of the existing keys
+    pub(crate) fn scan<RS>(hive: &mut Hive<RS, CleanHive>, root_name: &str) -> Result<Self>
+    where
+        RS: Read + Seek,
+    {
+        let data = read_hive_bins(hive)?;
+        let cells = scan_cells(&data);
+
+        let nodes: HashMap<u32, &RawKeyNode> = cells
+            .key_nodes
+            .iter()
+            .map(|node| (node.offset, node))
+            .collect();
+
+        let mut keys = Self::default();
+        let mut referenced_values = HashSet::new();
+        let mut has_orphans = false;
+        for node in cells.key_nodes.iter().filter(|node| node.is_deleted) {
+            let (path, is_orphaned) = key_path(&nodes, node, root_name);
+            has_orphans |= is_orphaned;
+            keys.insert(recover_key(hive, &data, node, path, &mut referenced_values));
+        }
+
+        let orphaned_values: Vec<_> = cells
+            .deleted_values
+            .iter()
+            .filter(|offset| !referenced_values.contains(*offset))
+            .map(|offset| recover_value(hive, &data, *offset))
+            .collect();
+        if has_orphans || !orphaned_values.is_empty() {
+            keys.insert(DeletedKey {
+                name: ORPHANED.to_owned(),
+                path: format!("{root_name}\\{ORPHANED}"),
+                last_written: None,
+                values: orphaned_values,
+                notes: vec![
+                    "contains deleted keys and values whose parent key could not be found"
+                        .to_owned(),
+                ],
+            });
+        }
+
+        log::info!(
+            "recovered {} deleted keys",
+            keys.children.values().map(Vec::len).sum::<usize>()
+        );
+        Ok(keys)
+    }
+
+    /// returns the deleted keys whose parent has the path `path`. Paths are
+    /// compared case insensitive
+    pub(crate) fn children_of(&self, path: &str) -> &[DeletedKey] {
+        self.children
+            .get(&path.to_lowercase())
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    fn insert(&mut self, key: DeletedKey) {
+        let parent = match key.path.rsplit_once('\\') {
+            Some((parent, _)) => parent.to_lowercase(),
+            None => String::new(),
+        };
+        self.children.entry(parent).or_default().push(key);
+    }
+}
+
+/// builds the path of a key by following the offsets of the parent keys.
+/// Returns the path and whether the chain of parents is broken, in which
+/// case the path begins with the `$Orphaned` pseudo key
+fn key_path(
+    nodes: &HashMap<u32, &RawKeyNode>,
+    node: &RawKeyNode,
+    root_name: &str,
+) -> (String, bool) {
+    let mut names = vec![key_name(node)];
+    let mut parent = node.parent;
+    let is_orphaned = loop {
+        match nodes.get(&parent) {
+            Some(parent) if parent.is_root && !parent.is_deleted => break false,
+            Some(p) if names.len() < MAX_DEPTH => {
+                names.push(key_name(p));
+                parent = p.parent;
+            }
+            _ => {
+                names.push(ORPHANED.to_owned());
+                break true;
+            }
+        }
+    };
+    names.push(root_name.to_owned());
+    names.reverse();
+    (names.join("\\"), is_orphaned)
+}
+
+fn key_name(node: &RawKeyNode) -> String {
+    match &node.name {
+        Some(name) => name.clone(),
+        None => format!("<unnamed key at 0x{:08x}>", node.offset),
+    }
+}
+
+fn recover_key<RS>(
+    hive: &mut Hive<RS, CleanHive>,
+    data: &[u8],
+    node: &RawKeyNode,
+    path: String,
+    referenced_values: &mut HashSet<u32>,
+) -> DeletedKey
+where
+    RS: Read + Seek,
+{
+    let mut notes = vec![format!(
+        "recovered from a free cell at offset 0x{:08x}, its contents may be inconsistent",
+        node.offset
+    )];
+    if node.name.is_none() {
+        notes.push("the name of the key could not be recovered".to_owned());
+    }
+    if node.timestamp.is_none() {
+        notes.push("the last written time could not be recovered".to_owned());
+    }
+
+    let count = node.values_count;
+    let mut values = Vec::new();
+    if count > 0 {
+        let offsets = (count <= MAX_VALUES_COUNT)
+            .then(|| {
+                (0..count as usize)
+                    .map(|i| u32_at(data, node.values_list as usize + 4 + i * 4))
+                    .collect::<Option<Vec<_>>>()
+            })
+            .flatten();
+        match offsets {
+            None => notes.push(format!("the list of {count} values could not be recovered")),
+            Some(offsets) => {
+                for offset in offsets
+                    .into_iter()
+                    .filter(|offset| is_value_record(data, *offset as usize))
+                {
+                    referenced_values.insert(offset);
+                    values.push(recover_value(hive, data, offset));
+                }
+                if values.len() < count as usize {
+                    notes.push(format!(
+                        "{} of {count} values could not be recovered",
+                        count as usize - values.len()
+                    ));
+                }
+            }
+        }
+    }
+
+    DeletedKey {
+        name: key_name(node),
+        path,
+        last_written: node.timestamp,
+        values,
+        notes,
+    }
+}
+
+/// recovers the value record in the cell at `offset`. If its data cannot be
+/// read, only its name is recovered
+fn recover_value<RS>(hive: &mut Hive<RS, CleanHive>, data: &[u8], offset: u32) -> DeletedValue
+where
+    RS: Read + Seek,
+{
+    let mut notes = Vec::new();
+    let name = match read_value_name(data, offset as usize) {
+        Some(name) => name,
+        None => {
+            notes.push("the name of the value could not be recovered".to_owned());
+            format!("<unnamed value at 0x{offset:08x}>")
+        }
+    };
+    let value = match read_value(hive, data, offset) {
+        Ok(value) => Some(value),
+        Err(why) => {
+            let data_type = read_value_type(data, offset as usize).unwrap_or_default();
+            notes.push(format!(
+                "the data of type 0x{data_type:x} could not be recovered: {why}"
+            ));
+            None
+        }
+    };
+    let mut value_data = None;
+    if let Some(value) = value.as_ref() {
+        if value.is_resident() {
+            value_data = data.get(offset as usize..).and_then(read_resident_data);
+        } else if is_big_data(value) {
+            match read_big_data_value(hive, offset, value) {
+                Ok(data) => value_data = data,
+                Err(why) => notes.push(format!(
+                    "the data of the big data value could not be reassembled: {why}"
+                )),
+            }
+        }
+    }
+    DeletedValue {
+        name,
+        value,
+        data: value_data,
+        notes,
+    }
+}
+
+fn read_value<RS>(hive: &mut Hive<RS, CleanHive>, data: &[u8], offset: u32) -> Result<KeyValue>
+where
+    RS: Read + Seek,
+{
+    let offset = offset as usize;
+    let data_size = u32_at(data, offset + 8).unwrap_or_default();
+    let data_offset = u32_at(data, offset + 12).unwrap_or_default();
+    if data_size & 0x8000_0000 == 0
+        && (data_size > MAX_DATA_SIZE || data_offset as usize >= data.len())
+    {
+        bail!("invalid data offset 0x{data_offset:08x}");
+    }
+
+    // skip the cell size and the signature
+    hive.seek(SeekFrom::Start(offset as u64 + 6))?;
+    Ok(hive.read_le()?)
+}
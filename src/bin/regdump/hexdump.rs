@@ -0,0 +1,76 @@
+use std::fmt::Write;
+
+const BYTES_PER_LINE: usize = 16;
+const INDENT: &str = "    ";
+
+/// formats binary data like `hexdump -C`, with the offset, 16 bytes and
+/// their printable ASCII characters in every line. Only the first `limit`
+/// bytes are displayed, followed by the number of bytes which have been
+/// omitted. Every line is indented and ends with a newline.
+pub(crate) fn hexdump(data: &[u8], limit: usize) -> String {
+    let mut output = String::new();
+    let displayed = &data[..data.len().min(limit)];
+    for (line, chunk) in displayed.chunks(BYTES_PER_LINE).enumerate() {
+        let _ = write!(output, "{INDENT}{:08x}  ", line * BYTES_PER_LINE);
+        for i in 0..BYTES_PER_LINE {
+            match chunk.get(i) {
+                Some(b) => {
+                    let _ = write!(output, "{b:02x} ");
+                }
+                None => output.push_str("   "),
+            }
+            if i == BYTES_PER_LINE / 2 - 1 {
+                output.push(' ');
+            }
+        }
+        let ascii: String = chunk
+            .iter()
+            .map(|b| {
+                if b.is_ascii_graphic() || *b == b' ' {
+                    char::from(*b)
+                } else {
+                    '.'
+                }
+            })
+            .collect();
+        let _ = writeln!(output, " |{ascii}|");
+    }
+
+    if data.len() > displayed.len() {
+        let _ = writeln!(
+            output,
+            "{INDENT}\u{2026} {} more bytes",
+            data.len() - displayed.len()
+        );
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::hexdump;
+
+    #[test]
+    fn dump_lines() {
+        let data: Vec<u8> = b"Hello, World!\x00\x01\x02ABC".to_vec();
+        assert_eq!(
+            hexdump(&data, 256),
+            "    00000000  48 65 6c 6c 6f 2c 20 57  6f 72 6c 64 21 00 01 02  |Hello, World!...|\n    \
+             00000010  41 42 43                                          |ABC|\n"
+        );
+        assert_eq!(hexdump(&[], 256), "");
+    }
+
+    #[test]
+    fn truncate_at_limit() {
+        let data = vec![0x41; 40];
+        let output = hexdump(&data, 16);
+        let lines: Vec<_> = output.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(
+            lines[0],
+            "    00000000  41 41 41 41 41 41 41 41  41 41 41 41 41 41 41 41  |AAAAAAAAAAAAAAAA|"
+        );
+        assert_eq!(lines[1], "    \u{2026} 24 more bytes");
+    }
+}
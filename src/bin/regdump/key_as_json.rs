@@ -0,0 +1,369 @@
+use std::io::{Read, Seek};
+
+use anyhow::Result;
+use base64::{engine::general_purpose, Engine};
+use chrono::{DateTime, Utc};
+use nt_hive2::{CleanHive, Hive, KeyNode, RegistryValue};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::big_data::{KeyValueRef, KeyValues};
+use crate::deleted::{DeletedKey, DeletedKeys, DeletedValue};
+use crate::security::{SecurityDescriptor, SecurityDescriptors};
+use crate::subtree::Subtree;
+use crate::timestamp_filter::TimestampFilter;
+use crate::userassist::{is_userassist_key, UserAssistEntry};
+
+/// a registry value, whose data is decoded according to its type. Binary
+/// data is encoded with base64, and multi strings become an array
+#[derive(Serialize)]
+pub(crate) struct ValueAsJson {
+    name: String,
+
+    #[serde(rename = "type")]
+    data_type: String,
+
+    data: Value,
+
+    /// describes which parts of a recovered value could not be recovered
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    notes: Vec<String>,
+}
+
+impl From<KeyValueRef<'_>> for ValueAsJson {
+    fn from(value: KeyValueRef) -> Self {
+        let data = match value.value() {
+            RegistryValue::RegSZ(s)
+            | RegistryValue::RegExpandSZ(s)
+            | RegistryValue::RegLink(s)
+            | RegistryValue::RegResourceList(s)
+            | RegistryValue::RegFullResourceDescriptor(s)
+            | RegistryValue::RegResourceRequirementsList(s) => json!(s),
+            RegistryValue::RegMultiSZ(lines) => json!(lines),
+            RegistryValue::RegDWord(n) | RegistryValue::RegDWordBigEndian(n) => json!(n),
+            RegistryValue::RegQWord(n) => json!(n),
+            RegistryValue::RegBinary(data) => json!(general_purpose::STANDARD.encode(data)),
+            RegistryValue::RegNone | RegistryValue::RegUnknown | RegistryValue::RegFileTime => {
+                Value::Null
+            }
+        };
+        let data_type = match value.data_type() {
+            Some(data_type) => data_type.to_string(),
+            None => "RegUnknown".to_owned(),
+        };
+        Self {
+            name: value.name().to_owned(),
+            data_type,
+            data,
+            notes: Vec::new(),
+        }
+    }
+}
+
+impl ValueAsJson {
+    /// a decoded UserAssist entry, whose name is the name of the program
+    fn from_userassist(program: String, entry: &UserAssistEntry) -> Self {
+        Self {
+            name: program,
+            data_type: "UserAssist".to_owned(),
+            data: json!(entry),
+            notes: Vec::new(),
+        }
+    }
+
+    /// converts the value, and decodes it as UserAssist entry if `decode` is `true`
+    fn new(value: KeyValueRef, decode: bool) -> Self {
+        match decode.then(|| UserAssistEntry::from_value(value)) {
+            Some(Some((program, entry))) => Self::from_userassist(program, &entry),
+            _ => Self::from(value),
+        }
+    }
+
+    fn from_deleted(value: &DeletedValue, decode: bool) -> Self {
+        let mut json_value = match value.key_value() {
+            Some(kv) => Self::new(kv, decode),
+            None => Self {
+                name: value.name.clone(),
+                data_type: "RegUnknown".to_owned(),
+                data: Value::Null,
+                notes: Vec::new(),
+            },
+        };
+        json_value.notes.extend(value.notes.iter().cloned());
+        json_value
+    }
+}
+
+/// the contents of a single key, without its subkeys
+#[derive(Serialize)]
+pub(crate) struct KeyContents {
+    name: String,
+    last_written: Option<DateTime<Utc>>,
+    values: Vec<ValueAsJson>,
+
+    /// `true` if the key has been recovered from a free cell
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    deleted: bool,
+
+    /// describes which parts of a recovered key could not be recovered
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    notes: Vec<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    security: Option<SecurityDescriptor>,
+}
+
+impl KeyContents {
+    /// reads the contents of the key at `path`. If `userassist` is `true`
+    /// and this is a UserAssist key, its entries are decoded
+    fn new(
+        keynode: &KeyNode,
+        values: &KeyValues,
+        path: &str,
+        userassist: bool,
+        security: Option<&SecurityDescriptor>,
+    ) -> Self {
+        let decode = userassist && is_userassist_key(path);
+        let values = values
+            .iter()
+            .map(|value| ValueAsJson::new(value, decode))
+            .collect();
+        Self {
+            name: keynode.name().to_owned(),
+            last_written: Some(*keynode.timestamp()),
+            values,
+            deleted: false,
+            notes: Vec::new(),
+            security: security.cloned(),
+        }
+    }
+
+    fn from_deleted(key: &DeletedKey, userassist: bool) -> Self {
+        let decode = userassist && is_userassist_key(&key.path);
+        Self {
+            name: key.name.clone(),
+            last_written: key.last_written,
+            values: key
+                .values
+                .iter()
+                .map(|value| ValueAsJson::from_deleted(value, decode))
+                .collect(),
+            deleted: true,
+            notes: key.notes.clone(),
+            security: None,
+        }
+    }
+}
+
+/// a key together with all of its subkeys, used for `--format json`
+#[derive(Serialize)]
+pub(crate) struct KeyAsJson {
+    #[serde(flatten)]
+    contents: KeyContents,
+    subkeys: Vec<KeyAsJson>,
+}
+
+impl KeyAsJson {
+    /// reads the key and all of its descendants which match `filter`.
+    /// Keys which don't match are kept if any of their descendants matches.
+    /// Returns `None` if neither the key nor any of its descendants matches.
+    /// `parents` contains the names of the ancestors of the key. Deleted
+    /// subkeys are added after the existing subkeys
+    pub(crate) fn read<RS>(
+        hive: &mut Hive<RS, CleanHive>,
+        keynode: &KeyNode,
+        parents: &mut Vec<String>,
+        filter: &TimestampFilter,
+        userassist: bool,
+        deleted: &DeletedKeys,
+        security: &SecurityDescriptors,
+    ) -> Result<Option<Self>>
+    where
+        RS: Read + Seek,
+    {
+        parents.push(keynode.name().to_owned());
+        let mut subkeys = Vec::new();
+        for sk in keynode.subkeys(hive)?.iter() {
+            subkeys.extend(Self::read(
+                hive,
+                &sk.borrow(),
+                parents,
+                filter,
+                userassist,
+                deleted,
+                security,
+            )?);
+        }
+        let path = parents.join("\\");
+        parents.pop();
+        for key in deleted.children_of(&path) {
+            subkeys.extend(Self::from_deleted(key, filter, userassist, deleted));
+        }
+
+        if subkeys.is_empty() && !filter.matches(keynode) {
+            return Ok(None);
+        }
+        Ok(Some(Self {
+            contents: KeyContents::new(
+                keynode,
+                &KeyValues::read(hive, keynode),
+                &path,
+                userassist,
+                security.of_key(&path),
+            ),
+            subkeys,
+        }))
+    }
+
+    /// converts a deleted key and its deleted descendants which match `filter`
+    fn from_deleted(
+        key: &DeletedKey,
+        filter: &TimestampFilter,
+        userassist: bool,
+        deleted: &DeletedKeys,
+    ) -> Option<Self> {
+        let subkeys: Vec<_> = deleted
+            .children_of(&key.path)
+            .iter()
+            .filter_map(|sk| Self::from_deleted(sk, filter, userassist, deleted))
+            .collect();
+        if subkeys.is_empty() && !filter.matches_timestamp(key.last_written.as_ref()) {
+            return None;
+        }
+        Some(Self {
+            contents: KeyContents::from_deleted(key, userassist),
+            subkeys,
+        })
+    }
+}
+
+/// a key together with its full path, used for `--format jsonl`
+#[derive(Serialize)]
+pub(crate) struct KeyAsJsonLine {
+    path: String,
+
+    #[serde(flatten)]
+    contents: KeyContents,
+}
+
+impl KeyAsJsonLine {
+    pub(crate) fn new(
+        path: String,
+        keynode: &KeyNode,
+        values: &KeyValues,
+        userassist: bool,
+        security: Option<&SecurityDescriptor>,
+    ) -> Self {
+        Self {
+            contents: KeyContents::new(keynode, values, &path, userassist, security),
+            path,
+        }
+    }
+
+    pub(crate) fn from_deleted(key: &DeletedKey, userassist: bool) -> Self {
+        Self {
+            path: key.path.clone(),
+            contents: KeyContents::from_deleted(key, userassist),
+        }
+    }
+
+    /// collects the key and all of its descendants which match `filter`
+    /// into `keys`. `parents` contains the names of the ancestors of the key
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn collect<RS>(
+        hive: &mut Hive<RS, CleanHive>,
+        keynode: &KeyNode,
+        parents: &mut Vec<String>,
+        filter: &TimestampFilter,
+        userassist: bool,
+        deleted: &DeletedKeys,
+        security: &SecurityDescriptors,
+        keys: &mut Vec<Self>,
+    ) -> Result<()>
+    where
+        RS: Read + Seek,
+    {
+        parents.push(keynode.name().to_owned());
+        let path = parents.join("\\");
+        if filter.matches(keynode) {
+            keys.push(Self::new(
+                path.clone(),
+                keynode,
+                &KeyValues::read(hive, keynode),
+                userassist,
+                security.of_key(&path),
+            ));
+        }
+        for sk in keynode.subkeys(hive)?.iter() {
+            Self::collect(
+                hive,
+                &sk.borrow(),
+                parents,
+                filter,
+                userassist,
+                deleted,
+                security,
+                keys,
+            )?;
+        }
+        for key in deleted.children_of(&path) {
+            Self::collect_deleted(key, filter, userassist, deleted, keys);
+        }
+        parents.pop();
+        Ok(())
+    }
+
+    fn collect_deleted(
+        key: &DeletedKey,
+        filter: &TimestampFilter,
+        userassist: bool,
+        deleted: &DeletedKeys,
+        keys: &mut Vec<Self>,
+    ) {
+        if filter.matches_timestamp(key.last_written.as_ref()) {
+            keys.push(Self::from_deleted(key, userassist));
+        }
+        for sk in deleted.children_of(&key.path) {
+            Self::collect_deleted(sk, filter, userassist, deleted, keys);
+        }
+    }
+}
+
+/// a key which has been selected with `--path` or `--path-regex`, together
+/// with its full path and all of its subkeys which match the timestamp filter
+#[derive(Serialize)]
+pub(crate) struct SubtreeAsJson {
+    path: String,
+
+    #[serde(flatten)]
+    key: KeyAsJson,
+}
+
+impl SubtreeAsJson {
+    pub(crate) fn read<RS>(
+        hive: &mut Hive<RS, CleanHive>,
+        subtree: &Subtree,
+        filter: &TimestampFilter,
+        userassist: bool,
+        deleted: &DeletedKeys,
+        security: &SecurityDescriptors,
+    ) -> Result<Option<Self>>
+    where
+        RS: Read + Seek,
+    {
+        let mut parents = subtree.parents.clone();
+        let key = KeyAsJson::read(
+            hive,
+            &subtree.key.borrow(),
+            &mut parents,
+            filter,
+            userassist,
+            deleted,
+            security,
+        )?;
+        Ok(key.map(|key| Self {
+            path: subtree.path(),
+            key,
+        }))
+    }
+}
@@ -1,54 +1,144 @@
 use anyhow::{bail, Result};
 
 use dfir_toolkit::common::bodyfile::Bodyfile3Line;
-use dfir_toolkit::common::{FancyParser, FormattableDatetime};
+use dfir_toolkit::common::{FancyParser, FileOutput, FormattableDatetime, Timezone};
 use nt_hive2::*;
-use simplelog::{Config, SimpleLogger};
+use serde::Serialize;
+use std::cell::RefCell;
 use std::fs::File;
-use std::io::{Read, Seek};
+use std::io::{Read, Seek, Write};
+use std::rc::Rc;
 
-use crate::cli::Cli;
+use crate::big_data::{KeyValueRef, KeyValues};
+use crate::cli::{Cli, OutputFormat};
+use crate::deleted::{DeletedKey, DeletedKeys, DeletedValue};
+use crate::hexdump::hexdump;
+use crate::key_as_json::{KeyAsJson, KeyAsJsonLine, SubtreeAsJson};
+use crate::reg_export::{export_key, reg_path, utf16_output, REG_HEADER};
+use crate::search::{search, write_hits_table};
+use crate::security::{SecurityDescriptor, SecurityDescriptors};
+use crate::shellbags::{read_shellbags, write_shellbags_table};
+use crate::shimcache::{read_shimcache, write_entries_table};
+use crate::subtree::{find_by_path, find_by_regex, Subtree};
+use crate::transaction_logs::apply_transaction_logs;
+use crate::userassist::{is_userassist_key, UserAssistEntry};
 
+mod big_data;
+mod cell_scanner;
 mod cli;
+mod deleted;
+mod hexdump;
+mod key_as_json;
+mod reg_export;
+mod search;
+mod security;
+mod shellbags;
+mod shimcache;
+mod subtree;
+mod table;
+mod timestamp_filter;
+mod transaction_logs;
+mod userassist;
 
-fn main() -> Result<()> {
-    let mut cli = Cli::parse_cli();
-    let _ = SimpleLogger::init(cli.verbose.log_level_filter(), Config::default());
+/// exit code which is used if a key which has been requested with `--path`
+/// or `--path-regex` does not exist. It must differ from 2, which is used
+/// by clap for invalid arguments
+const KEY_NOT_FOUND: i32 = 3;
 
-    fn do_print_key<RS>(hive: &mut Hive<RS, CleanHive>, root_key: &KeyNode, cli: &Cli) -> Result<()>
-    where
-        RS: Read + Seek,
-    {
-        let mut path = Vec::new();
-        print_key(hive, root_key, &mut path, cli)
-    }
+fn main() -> Result<()> {
+    let cli = Cli::parse_cli();
 
     match File::open(&cli.hive_file) {
         Ok(data) => {
             let hive = Hive::new(data, cli.parse_mode()).unwrap();
 
-            let mut clean_hive = match cli.logfiles.len() {
-                0 => {
-                    log::warn!("no log files provided, treating hive as if it was clean");
-                    hive.treat_hive_as_clean()
-                }
-                1 => hive
-                    .with_transaction_log(File::open(cli.logfiles.pop().unwrap())?.try_into()?)?
-                    .apply_logs(),
-                2 => hive
-                    .with_transaction_log(File::open(cli.logfiles.pop().unwrap())?.try_into()?)?
-                    .with_transaction_log(File::open(cli.logfiles.pop().unwrap())?.try_into()?)?
-                    .apply_logs(),
-                _ => {
-                    bail!("more than two transaction log files are not supported")
-                }
+            let mut clean_hive = if cli.no_logs {
+                log::info!("ignoring transaction logs, treating hive as if it was clean");
+                hive.treat_hive_as_clean()
+            } else {
+                apply_transaction_logs(hive, &cli.transaction_logs())?
+            };
+
+            let root_key = Rc::new(RefCell::new(clean_hive.root_key_node().unwrap()));
+            let deleted = if cli.deleted {
+                DeletedKeys::scan(&mut clean_hive, root_key.borrow().name())?
+            } else {
+                DeletedKeys::default()
+            };
+            let security = if cli.security {
+                SecurityDescriptors::read(&mut clean_hive, root_key.borrow().name())?
+            } else {
+                SecurityDescriptors::default()
             };
 
-            let root_key = &clean_hive.root_key_node().unwrap();
-            do_print_key(&mut clean_hive, root_key, &cli).unwrap();
+            // the output file is only created if all keys have been written
+            let mut output = cli.output.create()?;
+            if cli.decode_shimcache() {
+                let entries = read_shimcache(&mut clean_hive, &root_key, cli.control_set)?;
+                print_entries(&mut output, &entries, &cli, write_entries_table, |e| {
+                    e.to_bodyfile_line()
+                })?;
+            } else if cli.decode_shellbags() {
+                let shellbags = read_shellbags(&mut clean_hive, &root_key)?;
+                print_entries(&mut output, &shellbags, &cli, write_shellbags_table, |e| {
+                    e.to_bodyfile_line()
+                })?;
+            } else if let Some(regex) = &cli.search {
+                let (subtrees, all_found) = if cli.paths.is_empty() && cli.path_regexes.is_empty() {
+                    (vec![Subtree::root(root_key)], true)
+                } else {
+                    select_subtrees(&mut clean_hive, &root_key, &cli)?
+                };
+                let hits = search(&mut clean_hive, &subtrees, regex, cli.search_binary)?;
+                print_entries(&mut output, &hits, &cli, write_hits_table, |h| {
+                    h.to_bodyfile_line()
+                })?;
+                if !all_found {
+                    output.commit()?;
+                    std::process::exit(KEY_NOT_FOUND);
+                }
+            } else if cli.paths.is_empty() && cli.path_regexes.is_empty() {
+                let filter = cli.timestamp_filter();
+                if cli.output_format() == OutputFormat::Json && !filter.is_active() {
+                    let root_key = KeyAsJson::read(
+                        &mut clean_hive,
+                        &root_key.borrow(),
+                        &mut Vec::new(),
+                        &filter,
+                        cli.decode_userassist(),
+                        &deleted,
+                        &security,
+                    )?;
+                    writeln!(output, "{}", serde_json::to_string_pretty(&root_key)?)?;
+                } else {
+                    print_subtrees(
+                        &mut output,
+                        &mut clean_hive,
+                        &[Subtree::root(root_key)],
+                        &deleted,
+                        &security,
+                        &cli,
+                    )?;
+                }
+            } else {
+                let (subtrees, all_found) = select_subtrees(&mut clean_hive, &root_key, &cli)?;
+                print_subtrees(
+                    &mut output,
+                    &mut clean_hive,
+                    &subtrees,
+                    &deleted,
+                    &security,
+                    &cli,
+                )?;
+                if !all_found {
+                    output.commit()?;
+                    std::process::exit(KEY_NOT_FOUND);
+                }
+            }
+            output.commit()?;
         }
         Err(why) => {
-            eprintln!(
+            log::error!(
                 "unable to open '{}': {}",
                 cli.hive_file.to_string_lossy(),
                 why
@@ -59,10 +149,155 @@ fn main() -> Result<()> {
     Ok(())
 }
 
+/// prints entries which have been decoded from the hive, like the entries
+/// of the AppCompatCache. The 'text' format displays them as a table
+fn print_entries<'o, T, W, B>(
+    output: &'o mut FileOutput,
+    entries: &[T],
+    cli: &Cli,
+    write_table: W,
+    bodyfile_line: B,
+) -> Result<()>
+where
+    T: Serialize,
+    W: Fn(&'o mut FileOutput, &[T], &Timezone) -> std::io::Result<()>,
+    B: Fn(&T) -> Bodyfile3Line,
+{
+    match cli.output_format() {
+        OutputFormat::Text => write_table(output, entries, &cli.timezone)?,
+        OutputFormat::Reg => bail!("decoded entries cannot be exported in reg format"),
+        OutputFormat::Json => writeln!(output, "{}", serde_json::to_string_pretty(&entries)?)?,
+        OutputFormat::Jsonl => {
+            for entry in entries.iter() {
+                writeln!(output, "{}", serde_json::to_string(entry)?)?;
+            }
+        }
+        OutputFormat::Bodyfile => {
+            for entry in entries.iter() {
+                writeln!(output, "{}", bodyfile_line(entry))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// searches the keys which have been requested with `--path` and
+/// `--path-regex`. Returns the keys which have been found, and whether
+/// every path and every regex has been found
+fn select_subtrees<RS>(
+    hive: &mut Hive<RS, CleanHive>,
+    root_key: &Rc<RefCell<KeyNode>>,
+    cli: &Cli,
+) -> Result<(Vec<Subtree>, bool)>
+where
+    RS: Read + Seek,
+{
+    let mut subtrees = Vec::new();
+    let mut all_found = true;
+    for path in cli.paths.iter() {
+        match find_by_path(hive, root_key, path)? {
+            Some(subtree) => subtrees.push(subtree),
+            None => {
+                log::error!(
+                    "there is no key '{path}' in '{}'",
+                    cli.hive_file.to_string_lossy()
+                );
+                all_found = false;
+            }
+        }
+    }
+    for regex in cli.path_regexes.iter() {
+        let found = find_by_regex(hive, root_key, regex)?;
+        if found.is_empty() {
+            log::error!(
+                "there is no key matching '{regex}' in '{}'",
+                cli.hive_file.to_string_lossy()
+            );
+            all_found = false;
+        }
+        subtrees.extend(found);
+    }
+    Ok((subtrees, all_found))
+}
+
+fn print_subtrees<RS>(
+    output: &mut FileOutput,
+    hive: &mut Hive<RS, CleanHive>,
+    subtrees: &[Subtree],
+    deleted: &DeletedKeys,
+    security: &SecurityDescriptors,
+    cli: &Cli,
+) -> Result<()>
+where
+    RS: Read + Seek,
+{
+    let filter = cli.timestamp_filter();
+    if cli.output_format() == OutputFormat::Json && filter.is_active() && !cli.with_ancestors {
+        // without ancestors, there is no tree which could be displayed
+        let mut keys = Vec::new();
+        for subtree in subtrees {
+            let mut parents = subtree.parents.clone();
+            KeyAsJsonLine::collect(
+                hive,
+                &subtree.key.borrow(),
+                &mut parents,
+                &filter,
+                cli.decode_userassist(),
+                deleted,
+                security,
+                &mut keys,
+            )?;
+        }
+        writeln!(output, "{}", serde_json::to_string_pretty(&keys)?)?;
+    } else if cli.output_format() == OutputFormat::Json {
+        let mut trees = Vec::new();
+        for subtree in subtrees {
+            trees.extend(SubtreeAsJson::read(
+                hive,
+                subtree,
+                &filter,
+                cli.decode_userassist(),
+                deleted,
+                security,
+            )?);
+        }
+        writeln!(output, "{}", serde_json::to_string_pretty(&trees)?)?;
+    } else {
+        if cli.output_format() == OutputFormat::Reg {
+            write_output(output, REG_HEADER, true, cli)?;
+        }
+        for subtree in subtrees {
+            let mut path = subtree.parents.clone();
+            let mut ancestors = Vec::new();
+            print_key(
+                output,
+                hive,
+                &subtree.key.borrow(),
+                &mut path,
+                &mut ancestors,
+                deleted,
+                security,
+                cli,
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// prints the key and all of its descendants which match the timestamp
+/// filter. `ancestors` contains the output of those ancestors which have
+/// not been printed yet, because they don't match the filter. They are
+/// printed before the first matching descendant if `--with-ancestors` has
+/// been specified. Deleted subkeys are printed after the existing subkeys.
+#[allow(clippy::too_many_arguments)]
 fn print_key<RS>(
+    output: &mut FileOutput,
     hive: &mut Hive<RS, CleanHive>,
     keynode: &KeyNode,
     path: &mut Vec<String>,
+    ancestors: &mut Vec<Option<String>>,
+    deleted: &DeletedKeys,
+    security: &SecurityDescriptors,
     cli: &Cli,
 ) -> Result<()>
 where
@@ -71,36 +306,276 @@ where
     path.push(keynode.name().to_string());
 
     let current_path = path.join("\\");
-    if cli.display_bodyfile {
+    let values = KeyValues::read(hive, keynode);
+    let key_output = format_key(
+        keynode,
+        &values,
+        current_path.clone(),
+        security.of_key(&current_path),
+        cli,
+    )?;
+    print_or_defer(
+        output,
+        key_output,
+        cli.timestamp_filter().matches(keynode),
+        ancestors,
+        cli,
+    )?;
+
+    for sk in keynode.subkeys(hive).unwrap().iter() {
+        print_key(
+            output,
+            hive,
+            &sk.borrow(),
+            path,
+            ancestors,
+            deleted,
+            security,
+            cli,
+        )?;
+    }
+    for key in deleted.children_of(&current_path) {
+        print_deleted_key(output, key, ancestors, deleted, cli)?;
+    }
+    ancestors.pop();
+    path.pop();
+
+    Ok(())
+}
+
+/// prints a deleted key and its deleted descendants, like [print_key]
+fn print_deleted_key(
+    output: &mut FileOutput,
+    key: &DeletedKey,
+    ancestors: &mut Vec<Option<String>>,
+    deleted: &DeletedKeys,
+    cli: &Cli,
+) -> Result<()> {
+    let key_output = format_deleted_key(key, cli)?;
+    let matches = cli
+        .timestamp_filter()
+        .matches_timestamp(key.last_written.as_ref());
+    print_or_defer(output, key_output, matches, ancestors, cli)?;
+
+    for sk in deleted.children_of(&key.path) {
+        print_deleted_key(output, sk, ancestors, deleted, cli)?;
+    }
+    ancestors.pop();
+    Ok(())
+}
+
+/// prints the output of a key which matches the timestamp filter, after
+/// the output of its ancestors which has not been printed yet. The output of
+/// other keys is kept as ancestor, if `--with-ancestors` has been specified
+fn print_or_defer(
+    output: &mut FileOutput,
+    key_output: String,
+    matches: bool,
+    ancestors: &mut Vec<Option<String>>,
+    cli: &Cli,
+) -> Result<()> {
+    if matches {
+        for ancestor in ancestors.iter_mut().filter_map(Option::take) {
+            write_output(output, &ancestor, false, cli)?;
+        }
+        write_output(output, &key_output, false, cli)?;
+        ancestors.push(None);
+    } else if cli.with_ancestors {
+        ancestors.push(Some(key_output));
+    } else {
+        ancestors.push(None);
+    }
+    Ok(())
+}
+
+/// writes `key_output` to `output`. If '--utf16' has been specified for a
+/// '.reg' export, it is encoded as UTF-16LE, and `is_start` denotes whether
+/// the byte order mark must be written
+fn write_output(
+    output: &mut FileOutput,
+    key_output: &str,
+    is_start: bool,
+    cli: &Cli,
+) -> Result<()> {
+    if cli.utf16 && cli.output_format() == OutputFormat::Reg {
+        output.write_all(&utf16_output(key_output, is_start))?;
+    } else {
+        write!(output, "{key_output}")?;
+    }
+    Ok(())
+}
+
+fn format_key(
+    keynode: &KeyNode,
+    values: &KeyValues,
+    current_path: String,
+    security: Option<&SecurityDescriptor>,
+    cli: &Cli,
+) -> Result<String> {
+    if cli.output_format() == OutputFormat::Bodyfile {
+        let name = if cli.value_count {
+            format!("{current_path} ({} values)", values.len())
+        } else {
+            current_path
+        };
         let bf_line = Bodyfile3Line::new()
-            .with_name(&current_path)
-            .with_ctime(keynode.timestamp().into());
-        println!("{}", bf_line);
+            .with_name(&name)
+            .with_mtime(keynode.timestamp().into());
+        Ok(format!("{}\n", bf_line))
+    } else if cli.output_format() == OutputFormat::Jsonl {
+        let line = KeyAsJsonLine::new(
+            current_path,
+            keynode,
+            values,
+            cli.decode_userassist(),
+            security,
+        );
+        Ok(format!("{}\n", serde_json::to_string(&line)?))
+    } else if cli.output_format() == OutputFormat::Reg {
+        Ok(export_key(
+            &reg_path(&cli.reg_root(), &current_path),
+            values.iter(),
+        ))
     } else {
-        if cli.hide_timestamps {
-            println!("\n[{}]", &current_path);
+        let mut output = if cli.hide_timestamps {
+            format!("\n[{}]\n", &current_path)
         } else {
-            println!("\n[{}]; {}", &current_path, FormattableDatetime::from(keynode.timestamp()));
+            format!(
+                "\n[{}]; {}\n",
+                &current_path,
+                FormattableDatetime::from(keynode.timestamp().with_timezone(&*cli.timezone))
+            )
+        };
+        if let Some(security) = security {
+            output.push_str(&format_security(security));
         }
 
-        print_values(keynode);
+        let userassist = cli.decode_userassist() && is_userassist_key(&current_path);
+        output.push_str(&format_values(values, userassist, cli));
+        Ok(output)
     }
+}
 
-    for sk in keynode.subkeys(hive).unwrap().iter() {
-        print_key(hive, &sk.borrow(), path, cli)?;
+/// formats a deleted key like [format_key]. Deleted keys are marked as
+/// such, and the notes about their recovery are displayed as comments
+fn format_deleted_key(key: &DeletedKey, cli: &Cli) -> Result<String> {
+    if cli.output_format() == OutputFormat::Bodyfile {
+        let name = if cli.value_count {
+            format!("{} ({} values) (deleted)", key.path, key.values.len())
+        } else {
+            format!("{} (deleted)", key.path)
+        };
+        let mut bf_line = Bodyfile3Line::new().with_name(&name);
+        if let Some(last_written) = &key.last_written {
+            bf_line = bf_line.with_mtime(last_written.into());
+        }
+        Ok(format!("{}\n", bf_line))
+    } else if cli.output_format() == OutputFormat::Jsonl {
+        let line = KeyAsJsonLine::from_deleted(key, cli.decode_userassist());
+        Ok(format!("{}\n", serde_json::to_string(&line)?))
+    } else if cli.output_format() == OutputFormat::Reg {
+        // the notes become comments in front of the key
+        let mut output = String::from("\r\n; deleted key\r\n");
+        for note in key
+            .notes
+            .iter()
+            .chain(key.values.iter().flat_map(|v| v.notes.iter()))
+        {
+            output.push_str(&format!("; {note}\r\n"));
+        }
+        let exported = export_key(
+            &reg_path(&cli.reg_root(), &key.path),
+            key.values.iter().filter_map(DeletedValue::key_value),
+        );
+        output.push_str(exported.trim_start());
+        Ok(output)
+    } else {
+        let mut output = match (&key.last_written, cli.hide_timestamps) {
+            (Some(last_written), false) => format!(
+                "\n[{}]; {}; deleted\n",
+                key.path,
+                FormattableDatetime::from(last_written.with_timezone(&*cli.timezone))
+            ),
+            _ => format!("\n[{}]; deleted\n", key.path),
+        };
+        for note in key.notes.iter() {
+            output.push_str(&format!("; {note}\n"));
+        }
+
+        let userassist = cli.decode_userassist() && is_userassist_key(&key.path);
+        for value in key.values.iter() {
+            match value.key_value() {
+                Some(kv) => output.push_str(&format_value(kv, userassist, cli)),
+                None => output.push_str(&format!("\"{}\" = <unrecoverable>\n", value.name)),
+            }
+            for note in value.notes.iter() {
+                output.push_str(&format!("; {note}\n"));
+            }
+        }
+        Ok(output)
     }
-    path.pop();
+}
 
-    Ok(())
+/// formats the security descriptor of a key as comments
+fn format_security(security: &SecurityDescriptor) -> String {
+    let trustee = |trustee: &Option<_>| match trustee {
+        Some(trustee) => format!("{trustee}"),
+        None => "-".to_owned(),
+    };
+    let mut output = format!(
+        "; owner: {}\n; group: {}\n",
+        trustee(&security.owner),
+        trustee(&security.group)
+    );
+    match &security.dacl {
+        None if security.errors.is_empty() => {
+            output.push_str("; no DACL, everyone has full access\n")
+        }
+        None => (),
+        Some(dacl) => {
+            if security.protected {
+                output.push_str("; the DACL does not inherit entries\n");
+            }
+            for ace in dacl.iter() {
+                output.push_str(&format!("; {ace}\n"));
+            }
+        }
+    }
+    for error in security.errors.iter() {
+        output.push_str(&format!("; invalid security descriptor: {error}\n"));
+    }
+    output
 }
 
-fn print_values(keynode: &KeyNode) {
-    for value in keynode.values() {
-        let data_type = match value.data_type() {
-            Some(dt) => format!("{dt}:"),
-            None => "".into(),
-        };
+/// formats the values of a key. If `userassist` is `true`, the values are
+/// displayed as decoded UserAssist entries, if possible
+fn format_values(values: &KeyValues, userassist: bool, cli: &Cli) -> String {
+    values
+        .iter()
+        .map(|value| format_value(value, userassist, cli))
+        .collect()
+}
+
+fn format_value(value: KeyValueRef, userassist: bool, cli: &Cli) -> String {
+    if userassist {
+        if let Some((program, entry)) = UserAssistEntry::from_value(value) {
+            let entry = entry.with_timezone(*cli.timezone);
+            return format!("\"{program}\" = UserAssist:{entry}\n");
+        }
+    }
+
+    let data_type = match value.data_type() {
+        Some(dt) => format!("{dt}:"),
+        None => "".into(),
+    };
 
-        println!("\"{}\" = {data_type}{}", value.name(), value.value());
+    if let (true, RegistryValue::RegBinary(data)) = (cli.hexdump, value.value()) {
+        return format!(
+            "\"{}\" = {data_type}\n{}",
+            value.name(),
+            hexdump(data, cli.hexdump_limit)
+        );
     }
+
+    format!("\"{}\" = {data_type}{}\n", value.name(), value.value())
 }
@@ -0,0 +1,267 @@
+use std::fmt::Write;
+
+use nt_hive2::{KeyValue, RegistryValue};
+
+use crate::big_data::KeyValueRef;
+
+/// first line of every file which has been exported by regedit
+pub(crate) const REG_HEADER: &str = "Windows Registry Editor Version 5.00\r\n";
+
+/// regedit wraps lines of hex encoded data before they reach this length
+const MAX_LINE_LENGTH: usize = 80;
+
+const REG_NONE: u8 = 0x00;
+const REG_SZ: u8 = 0x01;
+const REG_EXPAND_SZ: u8 = 0x02;
+const REG_BINARY: u8 = 0x03;
+const REG_DWORD: u8 = 0x04;
+const REG_DWORD_BIG_ENDIAN: u8 = 0x05;
+const REG_LINK: u8 = 0x06;
+const REG_MULTI_SZ: u8 = 0x07;
+const REG_RESOURCE_LIST: u8 = 0x08;
+const REG_FULL_RESOURCE_DESCRIPTOR: u8 = 0x09;
+const REG_RESOURCE_REQUIREMENTS_LIST: u8 = 0x0a;
+const REG_QWORD: u8 = 0x0b;
+const REG_FILETIME: u8 = 0x10;
+
+/// replaces the root key of the hive in `path` by `root`, which should be
+/// a key like `HKEY_LOCAL_MACHINE\SOFTWARE`
+pub(crate) fn reg_path(root: &str, path: &str) -> String {
+    match path.split_once('\\') {
+        Some((_, path)) => format!("{root}\\{path}"),
+        None => root.to_owned(),
+    }
+}
+
+/// exports a key and its values in the format of regedit. `path` must be
+/// the full path of the key, including its root key like `HKEY_LOCAL_MACHINE`
+pub(crate) fn export_key<'a>(path: &str, values: impl Iterator<Item = KeyValueRef<'a>>) -> String {
+    let mut output = format!("\r\n[{path}]\r\n");
+    for value in values {
+        output.push_str(&export_value(value));
+    }
+    output
+}
+
+/// exports a single value. Strings and DWORDs are written in their readable
+/// form, all other types are hex encoded, together with their type number
+pub(crate) fn export_value(value: KeyValueRef) -> String {
+    let name = match value.name() {
+        "(Default)" => "@".to_owned(),
+        name => format!("\"{}\"", escape_string(name)),
+    };
+
+    let data = match value.value() {
+        // data of up to four bytes is stored in the value itself, and is
+        // always returned as DWORD. So, its original bytes are written
+        RegistryValue::RegDWord(n) if data_type(&value) != Some(REG_DWORD) => hex_data(
+            Some(data_type(&value).unwrap_or(REG_NONE)),
+            &n.to_le_bytes(),
+            &name,
+        ),
+        RegistryValue::RegSZ(s) => format!("\"{}\"", escape_string(s)),
+        RegistryValue::RegDWord(n) => format!("dword:{n:08x}"),
+        RegistryValue::RegExpandSZ(s) => hex_data(Some(REG_EXPAND_SZ), &utf16_string(s), &name),
+        RegistryValue::RegMultiSZ(lines) => {
+            hex_data(Some(REG_MULTI_SZ), &utf16_multi_string(lines), &name)
+        }
+        RegistryValue::RegBinary(data) => hex_data(None, data, &name),
+        RegistryValue::RegQWord(n) => hex_data(Some(REG_QWORD), &n.to_le_bytes(), &name),
+        RegistryValue::RegDWordBigEndian(n) => {
+            hex_data(Some(REG_DWORD_BIG_ENDIAN), &n.to_be_bytes(), &name)
+        }
+        RegistryValue::RegLink(s) => hex_data(Some(REG_LINK), &utf16_string(s), &name),
+        RegistryValue::RegResourceList(s) => {
+            hex_data(Some(REG_RESOURCE_LIST), &utf16_string(s), &name)
+        }
+        RegistryValue::RegFullResourceDescriptor(s) => {
+            hex_data(Some(REG_FULL_RESOURCE_DESCRIPTOR), &utf16_string(s), &name)
+        }
+        RegistryValue::RegResourceRequirementsList(s) => hex_data(
+            Some(REG_RESOURCE_REQUIREMENTS_LIST),
+            &utf16_string(s),
+            &name,
+        ),
+        RegistryValue::RegFileTime => hex_data(Some(REG_FILETIME), &[], &name),
+        RegistryValue::RegNone | RegistryValue::RegUnknown => hex_data(Some(REG_NONE), &[], &name),
+    };
+    format!("{name}={data}\r\n")
+}
+
+/// the number of the data type of a value
+fn data_type(value: &KeyValue) -> Option<u8> {
+    let data_type = value.data_type()?.to_string();
+    let number = match data_type.as_str() {
+        "RegNone" => REG_NONE,
+        "RegSZ" => REG_SZ,
+        "RegExpandSZ" => REG_EXPAND_SZ,
+        "RegBinary" => REG_BINARY,
+        "RegDWord" => REG_DWORD,
+        "RegDWordBigEndian" => REG_DWORD_BIG_ENDIAN,
+        "RegLink" => REG_LINK,
+        "RegMultiSZ" => REG_MULTI_SZ,
+        "RegResourceList" => REG_RESOURCE_LIST,
+        "RegFullResourceDescriptor" => REG_FULL_RESOURCE_DESCRIPTOR,
+        "RegResourceRequirementsList" => REG_RESOURCE_REQUIREMENTS_LIST,
+        "RegQWord" => REG_QWORD,
+        "RegFileTime" => REG_FILETIME,
+        _ => return None,
+    };
+    Some(number)
+}
+
+/// escapes backslashes and double quotes, like regedit does
+pub(crate) fn escape_string(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// encodes data as comma separated hex bytes, prefixed by `hex:` for binary
+/// data or `hex(n):` for other types. Long lines are wrapped like regedit
+/// does it, where `name` is the part of the first line before the `=`
+pub(crate) fn hex_data(data_type: Option<u8>, data: &[u8], name: &str) -> String {
+    let mut output = match data_type {
+        Some(data_type) => format!("hex({data_type:x}):"),
+        None => "hex:".to_owned(),
+    };
+    let mut line_length = name.len() + 1 + output.len();
+    for (i, b) in data.iter().enumerate() {
+        let is_last = i + 1 == data.len();
+        let _ = write!(output, "{b:02x}");
+        line_length += 2;
+        if !is_last {
+            output.push(',');
+            line_length += 1;
+
+            // the next byte and the trailing ',\' must fit into the line
+            if line_length + 4 >= MAX_LINE_LENGTH - 1 {
+                output.push_str("\\\r\n  ");
+                line_length = 2;
+            }
+        }
+    }
+    output
+}
+
+/// encodes a string as UTF-16LE, including the terminating null character
+pub(crate) fn utf16_string(s: &str) -> Vec<u8> {
+    s.encode_utf16()
+        .chain(std::iter::once(0))
+        .flat_map(u16::to_le_bytes)
+        .collect()
+}
+
+/// encodes every string as UTF-16LE with a terminating null character, and
+/// adds another null character at the end of the list
+pub(crate) fn utf16_multi_string(lines: &[String]) -> Vec<u8> {
+    let mut data: Vec<u8> = lines.iter().flat_map(|line| utf16_string(line)).collect();
+    data.extend([0, 0]);
+    data
+}
+
+/// encodes the output as UTF-16LE. The byte order mark must be written only
+/// once, at the beginning of the file
+pub(crate) fn utf16_output(s: &str, with_bom: bool) -> Vec<u8> {
+    let bom = with_bom.then_some(0xfeff_u16);
+    bom.into_iter()
+        .chain(s.encode_utf16())
+        .flat_map(u16::to_le_bytes)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        escape_string, hex_data, reg_path, utf16_multi_string, utf16_output, utf16_string,
+    };
+
+    /// decodes hex data like `reg.exe import` does, to verify that exported
+    /// data can be imported again
+    fn parse_hex_data(s: &str) -> (Option<u8>, Vec<u8>) {
+        let (prefix, bytes) = s.split_once(':').unwrap();
+        let data_type = prefix
+            .strip_prefix("hex(")
+            .map(|t| u8::from_str_radix(t.trim_end_matches(')'), 16).unwrap());
+        let bytes = bytes
+            .replace("\\\r\n", "")
+            .split(',')
+            .map(str::trim)
+            .filter(|b| !b.is_empty())
+            .map(|b| u8::from_str_radix(b, 16).unwrap())
+            .collect();
+        (data_type, bytes)
+    }
+
+    #[test]
+    fn replace_root_key() {
+        let root = "HKEY_LOCAL_MACHINE\\SOFTWARE";
+        assert_eq!(reg_path(root, "ROOT"), root);
+        assert_eq!(
+            reg_path(root, "ROOT\\Microsoft\\Windows"),
+            "HKEY_LOCAL_MACHINE\\SOFTWARE\\Microsoft\\Windows"
+        );
+    }
+
+    #[test]
+    fn escape_strings() {
+        assert_eq!(escape_string("C:\\Windows"), "C:\\\\Windows");
+        assert_eq!(escape_string("say \"hi\""), "say \\\"hi\\\"");
+        assert_eq!(escape_string("plain"), "plain");
+    }
+
+    #[test]
+    fn encode_strings_as_utf16() {
+        assert_eq!(utf16_string("ab"), vec![0x61, 0, 0x62, 0, 0, 0]);
+        assert_eq!(
+            utf16_multi_string(&["a".to_owned(), "b".to_owned()]),
+            vec![0x61, 0, 0, 0, 0x62, 0, 0, 0, 0, 0]
+        );
+        assert_eq!(utf16_output("a", true), vec![0xff, 0xfe, 0x61, 0]);
+        assert_eq!(utf16_output("a", false), vec![0x61, 0]);
+    }
+
+    #[test]
+    fn encode_hex_data() {
+        assert_eq!(hex_data(None, &[1, 2, 0xff], "\"x\""), "hex:01,02,ff");
+        assert_eq!(
+            hex_data(Some(0x0b), &42u64.to_le_bytes(), "\"x\""),
+            "hex(b):2a,00,00,00,00,00,00,00"
+        );
+        assert_eq!(hex_data(Some(0), &[], "@"), "hex(0):");
+    }
+
+    #[test]
+    fn wrap_long_hex_data() {
+        let data: Vec<u8> = (0..=255).collect();
+        let name = "\"a rather long value name\"";
+        let encoded = hex_data(None, &data, name);
+
+        let first_line = format!("{name}={}", encoded.lines().next().unwrap());
+        assert!(first_line.len() <= 80);
+        for line in encoded.split("\r\n") {
+            assert!(line.len() <= 80);
+        }
+        for line in encoded.split("\r\n").skip(1) {
+            assert!(line.starts_with("  "));
+        }
+        assert_eq!(parse_hex_data(&encoded), (None, data));
+    }
+
+    #[test]
+    fn round_trip_multi_strings() {
+        let lines = vec!["first".to_owned(), "zweite Zeile".to_owned()];
+        let encoded = hex_data(Some(7), &utf16_multi_string(&lines), "@");
+        let (data_type, bytes) = parse_hex_data(&encoded);
+        assert_eq!(data_type, Some(7));
+
+        let chars: Vec<u16> = bytes
+            .chunks_exact(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]))
+            .collect();
+        let decoded: Vec<_> = chars
+            .split(|c| *c == 0)
+            .filter(|s| !s.is_empty())
+            .map(String::from_utf16_lossy)
+            .collect();
+        assert_eq!(decoded, lines);
+    }
+}
@@ -0,0 +1,294 @@
+use std::io::{Read, Seek, Write};
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use dfir_toolkit::common::bodyfile::Bodyfile3Line;
+use dfir_toolkit::common::Timezone;
+use nt_hive2::{CleanHive, Hive, KeyNode, RegistryValue};
+use regex::Regex;
+use serde::Serialize;
+
+use crate::big_data::{KeyValueRef, KeyValues};
+use crate::subtree::Subtree;
+use crate::table::{timestamp_cell, write_table};
+
+const HEADERS: [&str; 6] = ["last written", "found in", "type", "value", "match", "path"];
+
+/// the part of a key or value which matches the search expression
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum MatchLocation {
+    KeyName,
+    ValueName,
+    ValueData,
+
+    /// the bytes of a binary value, interpreted as ASCII characters
+    BinaryAscii,
+
+    /// the bytes of a binary value, interpreted as UTF-16LE characters
+    BinaryUtf16,
+}
+
+impl MatchLocation {
+    fn as_str(&self) -> &'static str {
+        match self {
+            MatchLocation::KeyName => "key name",
+            MatchLocation::ValueName => "value name",
+            MatchLocation::ValueData => "value data",
+            MatchLocation::BinaryAscii => "binary (ascii)",
+            MatchLocation::BinaryUtf16 => "binary (utf-16le)",
+        }
+    }
+}
+
+/// a key name, value name or value data which matches the search expression.
+/// Only the first match of every key or value and location is reported.
+#[derive(Serialize, Debug)]
+pub(crate) struct SearchHit {
+    /// full path of the key
+    pub(crate) path: String,
+
+    pub(crate) last_written: DateTime<Utc>,
+    pub(crate) found_in: MatchLocation,
+
+    /// name of the value, if the match is not in the name of the key
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) value: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) value_type: Option<String>,
+
+    /// the text which matches the search expression
+    #[serde(rename = "match")]
+    pub(crate) matched: String,
+
+    /// offset of the match in the data of a binary value
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) offset: Option<usize>,
+}
+
+impl SearchHit {
+    pub(crate) fn to_bodyfile_line(&self) -> Bodyfile3Line {
+        let location = match &self.value {
+            Some(value) => format!("{}\\{value}", self.path),
+            None => self.path.clone(),
+        };
+        Bodyfile3Line::new()
+            .with_owned_name(format!(
+                "Search: '{}' in {} of '{location}'",
+                self.matched,
+                self.found_in.as_str()
+            ))
+            .with_mtime(self.last_written.into())
+    }
+}
+
+/// searches the names of all keys and values in the subtrees, and the
+/// contents of all string values, for `regex`. If `binary` is `true`, the
+/// data of binary values is searched, too.
+pub(crate) fn search<RS>(
+    hive: &mut Hive<RS, CleanHive>,
+    subtrees: &[Subtree],
+    regex: &Regex,
+    binary: bool,
+) -> Result<Vec<SearchHit>>
+where
+    RS: Read + Seek,
+{
+    let mut hits = Vec::new();
+    for subtree in subtrees {
+        let mut path = subtree.parents.clone();
+        search_key(
+            hive,
+            &subtree.key.borrow(),
+            &mut path,
+            regex,
+            binary,
+            &mut hits,
+        )?;
+    }
+    log::info!("found {} matches of '{regex}'", hits.len());
+    Ok(hits)
+}
+
+fn search_key<RS>(
+    hive: &mut Hive<RS, CleanHive>,
+    keynode: &KeyNode,
+    path: &mut Vec<String>,
+    regex: &Regex,
+    binary: bool,
+    hits: &mut Vec<SearchHit>,
+) -> Result<()>
+where
+    RS: Read + Seek,
+{
+    path.push(keynode.name().to_string());
+    let current_path = path.join("\\");
+
+    let hit = |found_in, value: Option<KeyValueRef>, matched: &str, offset| SearchHit {
+        path: current_path.clone(),
+        last_written: *keynode.timestamp(),
+        found_in,
+        value: value.map(|v| v.name().to_owned()),
+        value_type: value.and_then(|v| v.data_type().map(|t| t.to_string())),
+        matched: matched.to_owned(),
+        offset,
+    };
+
+    if let Some(m) = regex.find(keynode.name()) {
+        hits.push(hit(MatchLocation::KeyName, None, m.as_str(), None));
+    }
+    for value in KeyValues::read(hive, keynode).iter() {
+        if let Some(m) = regex.find(value.name()) {
+            hits.push(hit(MatchLocation::ValueName, Some(value), m.as_str(), None));
+        }
+        if let Some(m) = string_contents(value.value())
+            .into_iter()
+            .find_map(|s| regex.find(s))
+        {
+            hits.push(hit(MatchLocation::ValueData, Some(value), m.as_str(), None));
+        }
+        if let (true, RegistryValue::RegBinary(data)) = (binary, value.value()) {
+            for (found_in, matched, offset) in search_binary(data, regex) {
+                hits.push(hit(found_in, Some(value), &matched, Some(offset)));
+            }
+        }
+    }
+
+    for sk in keynode.subkeys(hive)?.iter() {
+        search_key(hive, &sk.borrow(), path, regex, binary, hits)?;
+    }
+    path.pop();
+    Ok(())
+}
+
+/// the strings which are stored in a value. Values of other types have no
+/// string contents
+fn string_contents(value: &RegistryValue) -> Vec<&str> {
+    match value {
+        RegistryValue::RegSZ(s)
+        | RegistryValue::RegExpandSZ(s)
+        | RegistryValue::RegLink(s)
+        | RegistryValue::RegResourceList(s)
+        | RegistryValue::RegFullResourceDescriptor(s)
+        | RegistryValue::RegResourceRequirementsList(s) => vec![s.as_str()],
+        RegistryValue::RegMultiSZ(lines) => lines.iter().map(String::as_str).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// searches binary data as ASCII and as UTF-16LE text. Because strings may
+/// begin at any offset, UTF-16LE is decoded beginning at an even and at an
+/// odd offset. Returns the location, the matching text and its offset in
+/// `data` of the first match of every interpretation.
+fn search_binary(data: &[u8], regex: &Regex) -> Vec<(MatchLocation, String, usize)> {
+    let mut hits = Vec::new();
+
+    // every byte becomes exactly one ASCII character, so that string
+    // offsets are byte offsets
+    let ascii: String = data
+        .iter()
+        .map(|b| {
+            if b.is_ascii_graphic() || *b == b' ' {
+                char::from(*b)
+            } else {
+                '.'
+            }
+        })
+        .collect();
+    if let Some(m) = regex.find(&ascii) {
+        hits.push((MatchLocation::BinaryAscii, m.as_str().to_owned(), m.start()));
+    }
+
+    let utf16_match = (0..2).find_map(|alignment| {
+        let text: String = data
+            .get(alignment..)
+            .unwrap_or_default()
+            .chunks_exact(2)
+            .map(|c| printable(char::from_u32(u32::from(u16::from_le_bytes([c[0], c[1]])))))
+            .collect();
+        regex.find(&text).map(|m| {
+            let index = text[..m.start()].chars().count();
+            (m.as_str().to_owned(), alignment + 2 * index)
+        })
+    });
+    if let Some((matched, offset)) = utf16_match {
+        hits.push((MatchLocation::BinaryUtf16, matched, offset));
+    }
+    hits
+}
+
+/// replaces control characters and surrogates, which cannot be decoded
+/// without their pair, by '.'
+fn printable(c: Option<char>) -> char {
+    match c {
+        Some(c) if !c.is_control() => c,
+        _ => '.',
+    }
+}
+
+/// writes the matches as a table with aligned columns
+pub(crate) fn write_hits_table<W: Write>(
+    w: W,
+    hits: &[SearchHit],
+    timezone: &Timezone,
+) -> std::io::Result<()> {
+    let rows: Vec<[String; 6]> = hits
+        .iter()
+        .map(|hit| {
+            [
+                timestamp_cell(&Some(hit.last_written), timezone),
+                match hit.offset {
+                    Some(offset) => format!("{} at 0x{offset:x}", hit.found_in.as_str()),
+                    None => hit.found_in.as_str().to_owned(),
+                },
+                hit.value_type.clone().unwrap_or_else(|| "-".to_owned()),
+                hit.value.clone().unwrap_or_else(|| "-".to_owned()),
+                hit.matched.chars().map(|c| printable(Some(c))).collect(),
+                hit.path.clone(),
+            ]
+        })
+        .collect();
+    write_table(w, HEADERS, &rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use regex::Regex;
+
+    use super::{search_binary, MatchLocation};
+
+    #[test]
+    fn search_binary_as_ascii() {
+        let regex = Regex::new("(?i)powershell").unwrap();
+        let data = b"\x00\x01PowerShell.exe\x00";
+        assert_eq!(
+            search_binary(data, &regex),
+            vec![(MatchLocation::BinaryAscii, "PowerShell".to_owned(), 2)]
+        );
+    }
+
+    #[test]
+    fn search_binary_as_utf16() {
+        let regex = Regex::new("cmd\\.exe").unwrap();
+        for prefix in [&b"\x01\x02"[..], &b"\x01"[..]] {
+            let mut data = prefix.to_vec();
+            data.extend("cmd.exe".encode_utf16().flat_map(u16::to_le_bytes));
+            assert_eq!(
+                search_binary(&data, &regex),
+                vec![(
+                    MatchLocation::BinaryUtf16,
+                    "cmd.exe".to_owned(),
+                    prefix.len()
+                )]
+            );
+        }
+    }
+
+    #[test]
+    fn replace_unprintable_characters() {
+        let regex = Regex::new("a.b").unwrap();
+        let hits = search_binary(b"a\x00b", &regex);
+        assert_eq!(hits[0].1, "a.b");
+    }
+}
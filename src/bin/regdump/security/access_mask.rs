@@ -0,0 +1,64 @@
+/// combinations of access rights, which are displayed instead of the
+/// single rights they consist of. Larger combinations come first.
+const COMBINED_RIGHTS: &[(u32, &str)] = &[
+    (0x000f_003f, "KEY_ALL_ACCESS"),
+    (0x0002_0019, "KEY_READ"),
+    (0x0002_0006, "KEY_WRITE"),
+];
+
+const RIGHTS: &[(u32, &str)] = &[
+    (0x0000_0001, "KEY_QUERY_VALUE"),
+    (0x0000_0002, "KEY_SET_VALUE"),
+    (0x0000_0004, "KEY_CREATE_SUB_KEY"),
+    (0x0000_0008, "KEY_ENUMERATE_SUB_KEYS"),
+    (0x0000_0010, "KEY_NOTIFY"),
+    (0x0000_0020, "KEY_CREATE_LINK"),
+    (0x0000_0100, "KEY_WOW64_64KEY"),
+    (0x0000_0200, "KEY_WOW64_32KEY"),
+    (0x0001_0000, "DELETE"),
+    (0x0002_0000, "READ_CONTROL"),
+    (0x0004_0000, "WRITE_DAC"),
+    (0x0008_0000, "WRITE_OWNER"),
+    (0x0010_0000, "SYNCHRONIZE"),
+    (0x0100_0000, "ACCESS_SYSTEM_SECURITY"),
+    (0x1000_0000, "GENERIC_ALL"),
+    (0x2000_0000, "GENERIC_EXECUTE"),
+    (0x4000_0000, "GENERIC_WRITE"),
+    (0x8000_0000, "GENERIC_READ"),
+];
+
+/// the access rights of a registry key in symbolic form, like
+/// `KEY_READ|WRITE_DAC`. Unknown bits are displayed in hex
+pub(crate) fn access_rights(mask: u32) -> String {
+    let mut remaining = mask;
+    let mut rights = Vec::new();
+    for (bits, name) in COMBINED_RIGHTS.iter().chain(RIGHTS.iter()) {
+        if remaining & bits == *bits {
+            rights.push((*name).to_owned());
+            remaining &= !bits;
+        }
+    }
+    if remaining != 0 {
+        rights.push(format!("0x{remaining:08x}"));
+    }
+    if rights.is_empty() {
+        "none".to_owned()
+    } else {
+        rights.join("|")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::access_rights;
+
+    #[test]
+    fn symbolic_access_rights() {
+        assert_eq!(access_rights(0x000f_003f), "KEY_ALL_ACCESS");
+        assert_eq!(access_rights(0x0002_0019), "KEY_READ");
+        assert_eq!(access_rights(0x0006_0019), "KEY_READ|WRITE_DAC");
+        assert_eq!(access_rights(0x8000_0000), "GENERIC_READ");
+        assert_eq!(access_rights(0x0000_0402), "KEY_SET_VALUE|0x00000400");
+        assert_eq!(access_rights(0), "none");
+    }
+}
@@ -0,0 +1,268 @@
+use std::fmt::Display;
+
+use serde::Serialize;
+
+use super::access_mask::access_rights;
+use super::sid::Trustee;
+
+const SE_DACL_PRESENT: u16 = 0x0004;
+const SE_DACL_PROTECTED: u16 = 0x1000;
+
+const DESCRIPTOR_HEADER_SIZE: usize = 20;
+const ACL_HEADER_SIZE: usize = 8;
+const ACE_HEADER_SIZE: usize = 4;
+
+const ACE_FLAGS: &[(u8, &str)] = &[
+    (0x01, "object inherit"),
+    (0x02, "container inherit"),
+    (0x04, "no propagate"),
+    (0x08, "inherit only"),
+    (0x10, "inherited"),
+];
+
+/// the owner, the group and the DACL of a key. Parts of a malformed
+/// descriptor which can be read are kept, and the errors which occurred
+/// while reading the other parts are reported.
+#[derive(Serialize, Debug, Default, Clone)]
+pub(crate) struct SecurityDescriptor {
+    pub(crate) owner: Option<Trustee>,
+    pub(crate) group: Option<Trustee>,
+
+    /// `None` if there is no DACL, which grants full access to everyone
+    pub(crate) dacl: Option<Vec<Ace>>,
+
+    /// `true` if the DACL does not inherit entries from the parent key
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) protected: bool,
+
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub(crate) errors: Vec<String>,
+}
+
+/// an entry of an access control list
+#[derive(Serialize, Debug, Clone)]
+pub(crate) struct Ace {
+    #[serde(rename = "type")]
+    pub(crate) ace_type: String,
+    pub(crate) trustee: Trustee,
+    pub(crate) mask: u32,
+
+    /// the access rights in symbolic form
+    pub(crate) access: String,
+
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub(crate) flags: Vec<&'static str>,
+}
+
+impl SecurityDescriptor {
+    /// parses a security descriptor in self-relative format
+    pub(crate) fn parse(data: &[u8]) -> Self {
+        let mut descriptor = Self::default();
+        if data.len() < DESCRIPTOR_HEADER_SIZE {
+            descriptor.errors.push(format!(
+                "the security descriptor is truncated ({} bytes)",
+                data.len()
+            ));
+            return descriptor;
+        }
+        let control = u16::from_le_bytes([data[2], data[3]]);
+        let offset_at = |pos: usize| {
+            u32::from_le_bytes([data[pos], data[pos + 1], data[pos + 2], data[pos + 3]]) as usize
+        };
+
+        descriptor.owner = descriptor.read_sid(data, offset_at(4), "owner");
+        descriptor.group = descriptor.read_sid(data, offset_at(8), "group");
+        descriptor.protected = control & SE_DACL_PROTECTED != 0;
+
+        let dacl_offset = offset_at(16);
+        if control & SE_DACL_PRESENT != 0 && dacl_offset != 0 {
+            match data.get(dacl_offset..) {
+                Some(acl) => descriptor.dacl = Some(descriptor.read_acl(acl)),
+                None => descriptor.errors.push(format!(
+                    "the offset of the DACL (0x{dacl_offset:x}) exceeds the security descriptor"
+                )),
+            }
+        }
+        descriptor
+    }
+
+    fn read_sid(&mut self, data: &[u8], offset: usize, name: &str) -> Option<Trustee> {
+        if offset == 0 {
+            return None;
+        }
+        match data
+            .get(offset..)
+            .ok_or_else(|| format!("offset 0x{offset:x} exceeds the security descriptor"))
+            .and_then(Trustee::parse)
+        {
+            Ok((trustee, _)) => Some(trustee),
+            Err(why) => {
+                self.errors.push(format!("invalid {name}: {why}"));
+                None
+            }
+        }
+    }
+
+    /// reads the entries of an ACL. Reading stops at the first malformed
+    /// entry, and the entries which have been read before are returned
+    fn read_acl(&mut self, acl: &[u8]) -> Vec<Ace> {
+        let mut aces = Vec::new();
+        if acl.len() < ACL_HEADER_SIZE {
+            self.errors.push("the DACL is truncated".to_owned());
+            return aces;
+        }
+        let count = u16::from_le_bytes([acl[4], acl[5]]) as usize;
+        let mut offset = ACL_HEADER_SIZE;
+        for index in 0..count {
+            match read_ace(&acl[offset.min(acl.len())..]) {
+                Ok((ace, size)) => {
+                    aces.push(ace);
+                    offset += size;
+                }
+                Err(why) => {
+                    self.errors.push(format!(
+                        "invalid entry {index} of {count} in the DACL: {why}"
+                    ));
+                    break;
+                }
+            }
+        }
+        aces
+    }
+}
+
+/// reads an ACE and returns it together with its size
+fn read_ace(data: &[u8]) -> Result<(Ace, usize), String> {
+    let header = data
+        .get(..ACE_HEADER_SIZE)
+        .ok_or_else(|| "the entry is truncated".to_owned())?;
+    let size = u16::from_le_bytes([header[2], header[3]]) as usize;
+    let ace = data
+        .get(..size)
+        .filter(|_| size >= ACE_HEADER_SIZE + 4)
+        .ok_or_else(|| format!("invalid size {size}"))?;
+
+    let ace_type = match header[0] {
+        0x00 => "allow",
+        0x01 => "deny",
+        0x02 => "audit",
+        0x11 => "mandatory label",
+        t => return Err(format!("unsupported type 0x{t:02x}")),
+    };
+    let mask = u32::from_le_bytes([ace[4], ace[5], ace[6], ace[7]]);
+    let (trustee, _) = Trustee::parse(&ace[8..])?;
+    let flags = ACE_FLAGS
+        .iter()
+        .filter(|(flag, _)| header[1] & flag != 0)
+        .map(|(_, name)| *name)
+        .collect();
+
+    Ok((
+        Ace {
+            ace_type: ace_type.to_owned(),
+            trustee,
+            mask,
+            access: access_rights(mask),
+            flags,
+        },
+        size,
+    ))
+}
+
+impl Display for Ace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}: {}", self.ace_type, self.trustee, self.access)?;
+        if !self.flags.is_empty() {
+            write!(f, " ({})", self.flags.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SecurityDescriptor;
+
+    fn sid(authority: u8, rids: &[u32]) -> Vec<u8> {
+        let mut data = vec![1, rids.len() as u8, 0, 0, 0, 0, 0, authority];
+        data.extend(rids.iter().flat_map(|rid| rid.to_le_bytes()));
+        data
+    }
+
+    fn ace(ace_type: u8, flags: u8, mask: u32, sid: &[u8]) -> Vec<u8> {
+        let size = (8 + sid.len()) as u16;
+        let mut data = vec![ace_type, flags];
+        data.extend(size.to_le_bytes());
+        data.extend(mask.to_le_bytes());
+        data.extend(sid);
+        data
+    }
+
+    fn descriptor(owner: &[u8], aces: &[Vec<u8>]) -> Vec<u8> {
+        let mut acl = vec![2, 0, 0, 0];
+        acl.extend((aces.len() as u16).to_le_bytes());
+        acl.extend([0, 0]);
+        for ace in aces {
+            acl.extend(ace);
+        }
+        let acl_size = acl.len() as u16;
+        acl[2..4].copy_from_slice(&acl_size.to_le_bytes());
+
+        let mut data = vec![1, 0, 0x04, 0x90];
+        data.extend(20u32.to_le_bytes());
+        data.extend(0u32.to_le_bytes());
+        data.extend(0u32.to_le_bytes());
+        data.extend(((20 + owner.len()) as u32).to_le_bytes());
+        data.extend(owner);
+        data.extend(acl);
+        data
+    }
+
+    #[test]
+    fn parse_descriptor() {
+        let data = descriptor(
+            &sid(5, &[32, 544]),
+            &[
+                ace(0, 0x02, 0x000f_003f, &sid(5, &[18])),
+                ace(1, 0x12, 0x0002_0006, &sid(1, &[0])),
+            ],
+        );
+        let descriptor = SecurityDescriptor::parse(&data);
+        assert!(descriptor.errors.is_empty());
+        assert!(descriptor.protected);
+        assert_eq!(descriptor.owner.unwrap().sid, "S-1-5-32-544");
+        assert!(descriptor.group.is_none());
+
+        let dacl = descriptor.dacl.unwrap();
+        assert_eq!(dacl.len(), 2);
+        assert_eq!(
+            dacl[0].to_string(),
+            "allow S-1-5-18 (NT AUTHORITY\\SYSTEM): KEY_ALL_ACCESS (container inherit)"
+        );
+        assert_eq!(
+            dacl[1].to_string(),
+            "deny S-1-1-0 (Everyone): KEY_WRITE (container inherit, inherited)"
+        );
+    }
+
+    #[test]
+    fn keep_valid_parts_of_malformed_descriptors() {
+        let mut broken_ace = ace(0, 0, 0x0002_0019, &sid(5, &[32, 545]));
+        broken_ace[2..4].copy_from_slice(&200u16.to_le_bytes());
+        let data = descriptor(
+            &sid(5, &[18]),
+            &[ace(0, 0, 0x0002_0019, &sid(5, &[11])), broken_ace],
+        );
+        let descriptor = SecurityDescriptor::parse(&data);
+        assert_eq!(descriptor.owner.unwrap().sid, "S-1-5-18");
+        assert_eq!(descriptor.dacl.unwrap().len(), 1);
+        assert_eq!(
+            descriptor.errors,
+            vec!["invalid entry 1 of 2 in the DACL: invalid size 200"]
+        );
+
+        let descriptor = SecurityDescriptor::parse(&[1, 0, 0, 0]);
+        assert!(descriptor.owner.is_none());
+        assert_eq!(descriptor.errors.len(), 1);
+    }
+}
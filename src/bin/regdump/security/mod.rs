@@ -0,0 +1,131 @@
+mod access_mask;
+mod descriptor;
+mod sid;
+
+pub(crate) use descriptor::*;
+
+use std::{
+    collections::HashMap,
+    io::{Read, Seek},
+};
+
+use anyhow::Result;
+use nt_hive2::{CleanHive, Hive};
+
+use crate::cell_scanner::{read_hive_bins, scan_cells, u32_at, RawKeyNode};
+
+/// parent chains which are longer than this are considered to be cyclic
+const MAX_DEPTH: usize = 512;
+
+/// offset of the security descriptor in a key security record, relative to
+/// the beginning of its cell
+const SK_HEADER_SIZE: usize = 4 + 20;
+
+/// the security descriptors of the existing keys of a hive. Descriptors are
+/// shared by many keys, so that every descriptor is parsed only once
+#[derive(Default)]
+pub(crate) struct SecurityDescriptors {
+    /// offsets of the key security records, by the paths of the keys
+    offsets: HashMap<String, u32>,
+
+    descriptors: HashMap<u32, SecurityDescriptor>,
+}
+
+impl SecurityDescriptors {
+    /// reads the key security records of all keys. `nt_hive2` does not
+    /// expose the offsets of these records, so the key nodes are read from
+    /// the cells of the hive. Keys are identified by their paths, which
+    /// begin with `root_name`, like the paths of the existing keys
+    pub(crate) fn read<RS>(hive: &mut Hive<RS, CleanHive>, root_name: &str) -> Result<Self>
+    where
+        RS: Read + Seek,
+    {
+        let root = hive.root_cell_offset().0;
+        let data = read_hive_bins(hive)?;
+        let cells = scan_cells(&data);
+        let nodes: HashMap<u32, &RawKeyNode> = cells
+            .key_nodes
+            .iter()
+            .filter(|node| !node.is_deleted)
+            .map(|node| (node.offset, node))
+            .collect();
+
+        let mut security = Self::default();
+        for node in nodes.values() {
+            let Some(path) = key_path(&nodes, node, root, root_name) else {
+                log::warn!(
+                    "unable to find the path of the key at offset 0x{:08x}",
+                    node.offset
+                );
+                continue;
+            };
+            security.offsets.insert(path.to_lowercase(), node.security);
+            security
+                .descriptors
+                .entry(node.security)
+                .or_insert_with(|| read_descriptor(&data, node.security));
+        }
+        log::info!(
+            "read {} security descriptors of {} keys",
+            security.descriptors.len(),
+            security.offsets.len()
+        );
+        Ok(security)
+    }
+
+    /// returns the security descriptor of the key at `path`, or `None` if
+    /// security descriptors have not been read. Paths are compared case
+    /// insensitive
+    pub(crate) fn of_key(&self, path: &str) -> Option<&SecurityDescriptor> {
+        self.offsets
+            .get(&path.to_lowercase())
+            .and_then(|offset| self.descriptors.get(offset))
+    }
+}
+
+/// builds the path of a key by following the offsets of the parent keys up
+/// to the root key at offset `root`. Returns `None` if the chain of parents
+/// is broken
+fn key_path(
+    nodes: &HashMap<u32, &RawKeyNode>,
+    node: &RawKeyNode,
+    root: u32,
+    root_name: &str,
+) -> Option<String> {
+    let mut names = Vec::new();
+    let mut current = node;
+    while current.offset != root {
+        if names.len() >= MAX_DEPTH {
+            return None;
+        }
+        names.push(current.name.clone()?);
+        current = nodes.get(&current.parent)?;
+    }
+    names.push(root_name.to_owned());
+    names.reverse();
+    Some(names.join("\\"))
+}
+
+/// reads the security descriptor from the key security record at `offset`.
+/// Errors are reported as part of the descriptor
+fn read_descriptor(data: &[u8], offset: u32) -> SecurityDescriptor {
+    let offset = offset as usize;
+    if data.get(offset + 4..offset + 6) != Some(b"sk") {
+        return SecurityDescriptor {
+            errors: vec![format!(
+                "there is no key security record at offset 0x{offset:08x}"
+            )],
+            ..Default::default()
+        };
+    }
+    let size = u32_at(data, offset + 20).unwrap_or_default() as usize;
+    match data.get(offset + SK_HEADER_SIZE..offset + SK_HEADER_SIZE + size) {
+        Some(descriptor) => SecurityDescriptor::parse(descriptor),
+        None => SecurityDescriptor {
+            errors: vec![format!(
+                "the security descriptor at offset 0x{offset:08x} with {size} bytes exceeds the hive"
+            )],
+            ..Default::default()
+        },
+    }
+}
@@ -0,0 +1,192 @@
+use std::fmt::Display;
+
+use serde::Serialize;
+
+/// SIDs which are the same on every system, and their names
+const WELL_KNOWN_SIDS: &[(&str, &str)] = &[
+    ("S-1-0-0", "NULL SID"),
+    ("S-1-1-0", "Everyone"),
+    ("S-1-2-0", "LOCAL"),
+    ("S-1-2-1", "CONSOLE LOGON"),
+    ("S-1-3-0", "CREATOR OWNER"),
+    ("S-1-3-1", "CREATOR GROUP"),
+    ("S-1-3-4", "OWNER RIGHTS"),
+    ("S-1-5-1", "NT AUTHORITY\\DIALUP"),
+    ("S-1-5-2", "NT AUTHORITY\\NETWORK"),
+    ("S-1-5-3", "NT AUTHORITY\\BATCH"),
+    ("S-1-5-4", "NT AUTHORITY\\INTERACTIVE"),
+    ("S-1-5-6", "NT AUTHORITY\\SERVICE"),
+    ("S-1-5-7", "NT AUTHORITY\\ANONYMOUS LOGON"),
+    ("S-1-5-9", "NT AUTHORITY\\ENTERPRISE DOMAIN CONTROLLERS"),
+    ("S-1-5-10", "NT AUTHORITY\\SELF"),
+    ("S-1-5-11", "NT AUTHORITY\\Authenticated Users"),
+    ("S-1-5-12", "NT AUTHORITY\\RESTRICTED"),
+    ("S-1-5-13", "NT AUTHORITY\\TERMINAL SERVER USER"),
+    ("S-1-5-14", "NT AUTHORITY\\REMOTE INTERACTIVE LOGON"),
+    ("S-1-5-18", "NT AUTHORITY\\SYSTEM"),
+    ("S-1-5-19", "NT AUTHORITY\\LOCAL SERVICE"),
+    ("S-1-5-20", "NT AUTHORITY\\NETWORK SERVICE"),
+    ("S-1-5-32-544", "BUILTIN\\Administrators"),
+    ("S-1-5-32-545", "BUILTIN\\Users"),
+    ("S-1-5-32-546", "BUILTIN\\Guests"),
+    ("S-1-5-32-547", "BUILTIN\\Power Users"),
+    ("S-1-5-32-548", "BUILTIN\\Account Operators"),
+    ("S-1-5-32-549", "BUILTIN\\Server Operators"),
+    ("S-1-5-32-550", "BUILTIN\\Print Operators"),
+    ("S-1-5-32-551", "BUILTIN\\Backup Operators"),
+    ("S-1-5-32-552", "BUILTIN\\Replicator"),
+    ("S-1-5-32-555", "BUILTIN\\Remote Desktop Users"),
+    ("S-1-5-32-556", "BUILTIN\\Network Configuration Operators"),
+    ("S-1-5-32-558", "BUILTIN\\Performance Monitor Users"),
+    ("S-1-5-32-559", "BUILTIN\\Performance Log Users"),
+    ("S-1-5-32-562", "BUILTIN\\Distributed COM Users"),
+    ("S-1-5-32-568", "BUILTIN\\IIS_IUSRS"),
+    ("S-1-5-32-573", "BUILTIN\\Event Log Readers"),
+    ("S-1-5-32-578", "BUILTIN\\Hyper-V Administrators"),
+    ("S-1-5-32-580", "BUILTIN\\Remote Management Users"),
+    ("S-1-5-80-0", "NT SERVICE\\ALL SERVICES"),
+    (
+        "S-1-5-80-956008885-3418522649-1831038044-1853292631-2271478464",
+        "NT SERVICE\\TrustedInstaller",
+    ),
+    (
+        "S-1-15-2-1",
+        "APPLICATION PACKAGE AUTHORITY\\ALL APPLICATION PACKAGES",
+    ),
+    (
+        "S-1-15-2-2",
+        "APPLICATION PACKAGE AUTHORITY\\ALL RESTRICTED APPLICATION PACKAGES",
+    ),
+    ("S-1-16-0", "Mandatory Label\\Untrusted Mandatory Level"),
+    ("S-1-16-4096", "Mandatory Label\\Low Mandatory Level"),
+    ("S-1-16-8192", "Mandatory Label\\Medium Mandatory Level"),
+    (
+        "S-1-16-8448",
+        "Mandatory Label\\Medium Plus Mandatory Level",
+    ),
+    ("S-1-16-12288", "Mandatory Label\\High Mandatory Level"),
+    ("S-1-16-16384", "Mandatory Label\\System Mandatory Level"),
+];
+
+/// relative identifiers of well-known accounts and groups of a domain or
+/// of the local computer, which follow a `S-1-5-21-x-y-z` prefix
+const WELL_KNOWN_RIDS: &[(u32, &str)] = &[
+    (500, "Administrator"),
+    (501, "Guest"),
+    (502, "krbtgt"),
+    (512, "Domain Admins"),
+    (513, "Domain Users"),
+    (514, "Domain Guests"),
+    (515, "Domain Computers"),
+    (516, "Domain Controllers"),
+    (518, "Schema Admins"),
+    (519, "Enterprise Admins"),
+    (520, "Group Policy Creator Owners"),
+];
+
+/// a security identifier together with its name, if it is a well-known SID
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Trustee {
+    pub(crate) sid: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) name: Option<String>,
+}
+
+impl Trustee {
+    /// parses a binary SID at the beginning of `data`. Returns the trustee
+    /// and the size of the SID
+    pub(crate) fn parse(data: &[u8]) -> Result<(Self, usize), String> {
+        let header = data
+            .get(..8)
+            .ok_or_else(|| "the SID is truncated".to_owned())?;
+        if header[0] != 1 {
+            return Err(format!("unsupported SID revision {}", header[0]));
+        }
+        let count = header[1] as usize;
+        let size = 8 + 4 * count;
+        let sub_authorities = data
+            .get(8..size)
+            .ok_or_else(|| format!("the SID with {count} sub authorities is truncated"))?;
+
+        let authority = header[2..8]
+            .iter()
+            .fold(0u64, |authority, b| (authority << 8) | u64::from(*b));
+        let mut sid = format!("S-1-{authority}");
+        let mut rids = Vec::with_capacity(count);
+        for rid in sub_authorities.chunks_exact(4) {
+            let rid = u32::from_le_bytes([rid[0], rid[1], rid[2], rid[3]]);
+            sid.push_str(&format!("-{rid}"));
+            rids.push(rid);
+        }
+
+        let name = well_known_name(&sid, authority, &rids);
+        Ok((Self { sid, name }, size))
+    }
+}
+
+impl Display for Trustee {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.name {
+            Some(name) => write!(f, "{} ({name})", self.sid),
+            None => write!(f, "{}", self.sid),
+        }
+    }
+}
+
+/// the names of service and capability SIDs are hashes of the names of the
+/// services and capabilities, so only their kind is known
+fn well_known_name(sid: &str, authority: u64, rids: &[u32]) -> Option<String> {
+    if let Some((_, name)) = WELL_KNOWN_SIDS.iter().find(|(s, _)| *s == sid) {
+        return Some((*name).to_owned());
+    }
+    match (authority, rids) {
+        (5, [21, _, _, _, rid]) => WELL_KNOWN_RIDS
+            .iter()
+            .find(|(r, _)| r == rid)
+            .map(|(_, name)| (*name).to_owned()),
+        (5, [80, ..]) => Some("NT SERVICE\\(service)".to_owned()),
+        (15, [3, ..]) => Some("APPLICATION PACKAGE AUTHORITY\\(capability)".to_owned()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Trustee;
+
+    fn sid(authority: u8, rids: &[u32]) -> Vec<u8> {
+        let mut data = vec![1, rids.len() as u8, 0, 0, 0, 0, 0, authority];
+        data.extend(rids.iter().flat_map(|rid| rid.to_le_bytes()));
+        data
+    }
+
+    #[test]
+    fn resolve_well_known_sids() {
+        let (trustee, size) = Trustee::parse(&sid(5, &[32, 544])).unwrap();
+        assert_eq!(size, 16);
+        assert_eq!(trustee.sid, "S-1-5-32-544");
+        assert_eq!(
+            trustee.to_string(),
+            "S-1-5-32-544 (BUILTIN\\Administrators)"
+        );
+
+        let (trustee, _) = Trustee::parse(&sid(1, &[0])).unwrap();
+        assert_eq!(trustee.name.as_deref(), Some("Everyone"));
+
+        let (trustee, _) = Trustee::parse(&sid(5, &[21, 1, 2, 3, 500])).unwrap();
+        assert_eq!(trustee.sid, "S-1-5-21-1-2-3-500");
+        assert_eq!(trustee.name.as_deref(), Some("Administrator"));
+
+        let (trustee, _) = Trustee::parse(&sid(5, &[21, 1, 2, 3, 1001])).unwrap();
+        assert_eq!(trustee.name, None);
+    }
+
+    #[test]
+    fn reject_truncated_sids() {
+        let data = sid(5, &[32, 544]);
+        assert!(Trustee::parse(&data[..12]).is_err());
+        assert!(Trustee::parse(&data[..4]).is_err());
+        assert!(Trustee::parse(&[2, 0, 0, 0, 0, 0, 0, 0]).is_err());
+    }
+}
@@ -0,0 +1,123 @@
+mod shell_item;
+mod shellbag;
+mod shellbags_table;
+
+pub(crate) use shell_item::*;
+pub(crate) use shellbag::*;
+pub(crate) use shellbags_table::*;
+
+use std::{
+    cell::RefCell,
+    io::{Read, Seek},
+    rc::Rc,
+};
+
+use anyhow::{bail, Result};
+use nt_hive2::{CleanHive, Hive, KeyNode, RegistryValue};
+
+use crate::big_data::KeyValues;
+use crate::subtree::find_by_path;
+
+/// keys which contain shellbags. The first two are found in NTUSER.DAT,
+/// the last one in UsrClass.dat
+const BAGMRU_KEYS: [&str; 3] = [
+    "Software\\Microsoft\\Windows\\Shell\\BagMRU",
+    "Software\\Microsoft\\Windows\\ShellNoRoam\\BagMRU",
+    "Local Settings\\Software\\Microsoft\\Windows\\Shell\\BagMRU",
+];
+
+/// reads the shellbags of a NTUSER.DAT or UsrClass.dat hive
+pub(crate) fn read_shellbags<RS>(
+    hive: &mut Hive<RS, CleanHive>,
+    root_key: &Rc<RefCell<KeyNode>>,
+) -> Result<Vec<ShellBag>>
+where
+    RS: Read + Seek,
+{
+    let mut shellbags = Vec::new();
+    let mut found = false;
+    for path in BAGMRU_KEYS {
+        if let Some(subtree) = find_by_path(hive, root_key, path)? {
+            log::info!("reading shellbags from '{path}'");
+            found = true;
+            let registry_path = subtree.path();
+            read_bagmru(
+                hive,
+                &subtree.key.borrow(),
+                &registry_path,
+                "",
+                &mut shellbags,
+            )?;
+        }
+    }
+    if !found {
+        bail!("there is no BagMRU key, is this a NTUSER.DAT or UsrClass.dat hive?");
+    }
+    Ok(shellbags)
+}
+
+/// reads the shell items of a BagMRU key and its subkeys. Every value with
+/// a numeric name contains a shell item, and the subkey with the same name
+/// contains the items of the subfolders
+fn read_bagmru<RS>(
+    hive: &mut Hive<RS, CleanHive>,
+    keynode: &KeyNode,
+    registry_path: &str,
+    parent_path: &str,
+    shellbags: &mut Vec<ShellBag>,
+) -> Result<()>
+where
+    RS: Read + Seek,
+{
+    let values = KeyValues::read(hive, keynode);
+    let mut items: Vec<_> = values
+        .iter()
+        .filter_map(|value| value.name().parse::<u32>().ok().map(|n| (n, value)))
+        .collect();
+    items.sort_by_key(|(n, _)| *n);
+
+    for (n, value) in items {
+        let item = match value.value() {
+            RegistryValue::RegBinary(data) => ShellItem::parse(data),
+            _ => ShellItem::Unknown { class_type: None },
+        };
+        let name = item.name();
+        let path =
+            if parent_path.is_empty() || parent_path.ends_with('\\') || name.starts_with('\\') {
+                format!("{parent_path}{name}")
+            } else {
+                format!("{parent_path}\\{name}")
+            };
+
+        let subkey = keynode.subkey(&n.to_string(), hive)?;
+        let (modified, accessed, created) = match &item {
+            ShellItem::FileEntry {
+                modified,
+                accessed,
+                created,
+                ..
+            } => (*modified, *accessed, *created),
+            _ => (None, None, None),
+        };
+        shellbags.push(ShellBag {
+            path: path.clone(),
+            registry_path: format!("{registry_path}\\{n}"),
+            item_type: item.item_type(),
+            last_written: subkey.as_ref().map(|sk| *sk.borrow().timestamp()),
+            modified,
+            accessed,
+            created,
+        });
+
+        if let Some(subkey) = subkey {
+            read_bagmru(
+                hive,
+                &subkey.borrow(),
+                &format!("{registry_path}\\{n}"),
+                &path,
+                shellbags,
+            )?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,262 @@
+use chrono::{DateTime, NaiveDate, Utc};
+
+/// signature of the extension block which contains the long name and
+/// further timestamps of a file entry
+const FILE_ENTRY_EXTENSION: [u8; 4] = 0xbeef0004u32.to_le_bytes();
+
+/// a single item of a shell item list, which is a part of a folder path
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum ShellItem {
+    /// a virtual folder like 'My Computer', identified by its GUID
+    RootFolder {
+        guid: String,
+    },
+
+    Volume {
+        name: String,
+    },
+
+    /// a directory or a file. The timestamps are stored with a resolution
+    /// of two seconds
+    FileEntry {
+        is_directory: bool,
+        name: String,
+        modified: Option<DateTime<Utc>>,
+        created: Option<DateTime<Utc>>,
+        accessed: Option<DateTime<Utc>>,
+    },
+
+    Network {
+        location: String,
+    },
+
+    /// an item whose class type is not supported, or which could not be parsed
+    Unknown {
+        class_type: Option<u8>,
+    },
+}
+
+impl ShellItem {
+    /// parses a shell item, beginning with its size. Items which cannot be
+    /// parsed are returned as [ShellItem::Unknown], so that the remaining
+    /// items of a path can still be displayed
+    pub(crate) fn parse(data: &[u8]) -> Self {
+        let size = match data.get(0..2) {
+            Some(size) => usize::from(u16::from_le_bytes([size[0], size[1]])),
+            None => return Self::Unknown { class_type: None },
+        };
+        let item = match data.get(..size) {
+            Some(item) if size >= 3 => item,
+            _ => return Self::Unknown { class_type: None },
+        };
+
+        let class_type = item[2];
+        let parsed = match class_type {
+            0x1f | 0x2e => parse_guid(item, 4).map(|guid| Self::RootFolder { guid }),
+            0x20..=0x2f => read_ascii(item, 3).map(|name| Self::Volume { name }),
+            0x30..=0x3f => parse_file_entry(item),
+            0x41..=0x4f => read_ascii(item, 5).map(|location| Self::Network { location }),
+            _ => None,
+        };
+        parsed.unwrap_or(Self::Unknown {
+            class_type: Some(class_type),
+        })
+    }
+
+    /// the name which is used as part of the folder path
+    pub(crate) fn name(&self) -> String {
+        match self {
+            Self::RootFolder { guid } => match known_folder(guid) {
+                Some(name) => name.to_owned(),
+                None => guid.clone(),
+            },
+            Self::Volume { name } => name.clone(),
+            Self::FileEntry { name, .. } => name.clone(),
+            Self::Network { location } => location.clone(),
+            Self::Unknown {
+                class_type: Some(class_type),
+            } => format!("<unknown shell item type 0x{class_type:02x}>"),
+            Self::Unknown { class_type: None } => "<invalid shell item>".to_owned(),
+        }
+    }
+
+    pub(crate) fn item_type(&self) -> &'static str {
+        match self {
+            Self::RootFolder { .. } => "root",
+            Self::Volume { .. } => "volume",
+            Self::FileEntry {
+                is_directory: true, ..
+            } => "directory",
+            Self::FileEntry { .. } => "file",
+            Self::Network { .. } => "network",
+            Self::Unknown { .. } => "unknown",
+        }
+    }
+}
+
+fn parse_file_entry(item: &[u8]) -> Option<ShellItem> {
+    let class_type = item[2];
+    let modified = fat_datetime(item, 8)?;
+
+    // the short name is stored in UTF-16 if this flag is set
+    let short_name = if class_type & 0x04 != 0 {
+        read_utf16(item, 14)?
+    } else {
+        read_ascii(item, 14)?
+    };
+
+    let extension = item
+        .windows(4)
+        .position(|w| w == FILE_ENTRY_EXTENSION)
+        .and_then(|pos| pos.checked_sub(4))
+        .and_then(|start| parse_file_entry_extension(&item[start..]));
+    let (name, created, accessed) = match extension {
+        Some((long_name, created, accessed)) => (long_name, created, accessed),
+        None => (short_name, None, None),
+    };
+
+    Some(ShellItem::FileEntry {
+        is_directory: class_type & 0x01 != 0,
+        name,
+        modified,
+        created,
+        accessed,
+    })
+}
+
+type FileEntryExtension = (String, Option<DateTime<Utc>>, Option<DateTime<Utc>>);
+
+/// parses the extension block `0xbeef0004` and returns the long name and
+/// the creation and access timestamps
+fn parse_file_entry_extension(block: &[u8]) -> Option<FileEntryExtension> {
+    let version = u16::from_le_bytes(block.get(2..4)?.try_into().ok()?);
+    let created = fat_datetime(block, 8)?;
+    let accessed = fat_datetime(block, 12)?;
+
+    let name_offset = match version {
+        0..=2 => return None,
+        3..=6 => 20,
+        7 => 38,
+        8 => 42,
+        _ => 46,
+    };
+    let long_name = read_utf16(block, name_offset)?;
+    Some((long_name, created, accessed))
+}
+
+/// converts a FAT date and time, which are stored in this order. Returns
+/// `Some(None)` if no timestamp has been stored, and `None` if there is no
+/// space for a timestamp
+fn fat_datetime(data: &[u8], offset: usize) -> Option<Option<DateTime<Utc>>> {
+    let bytes = data.get(offset..offset + 4)?;
+    let date = u16::from_le_bytes([bytes[0], bytes[1]]);
+    let time = u16::from_le_bytes([bytes[2], bytes[3]]);
+    if date == 0 && time == 0 {
+        return Some(None);
+    }
+
+    let year = 1980 + i32::from(date >> 9);
+    let month = u32::from((date >> 5) & 0x0f);
+    let day = u32::from(date & 0x1f);
+    let hour = u32::from(time >> 11);
+    let minute = u32::from((time >> 5) & 0x3f);
+    let second = u32::from(time & 0x1f) * 2;
+    Some(
+        NaiveDate::from_ymd_opt(year, month, day)
+            .and_then(|date| date.and_hms_opt(hour, minute, second))
+            .map(|ts| ts.and_utc()),
+    )
+}
+
+fn parse_guid(data: &[u8], offset: usize) -> Option<String> {
+    let b = data.get(offset..offset + 16)?;
+    Some(format!(
+        "{{{:08X}-{:04X}-{:04X}-{:02X}{:02X}-{:02X}{:02X}{:02X}{:02X}{:02X}{:02X}}}",
+        u32::from_le_bytes([b[0], b[1], b[2], b[3]]),
+        u16::from_le_bytes([b[4], b[5]]),
+        u16::from_le_bytes([b[6], b[7]]),
+        b[8],
+        b[9],
+        b[10],
+        b[11],
+        b[12],
+        b[13],
+        b[14],
+        b[15]
+    ))
+}
+
+/// reads a null terminated string, whose characters are decoded as Latin-1
+fn read_ascii(data: &[u8], offset: usize) -> Option<String> {
+    let bytes = data.get(offset..)?;
+    let end = bytes.iter().position(|b| *b == 0)?;
+    Some(bytes[..end].iter().map(|b| char::from(*b)).collect())
+}
+
+/// reads a null terminated UTF-16 string
+fn read_utf16(data: &[u8], offset: usize) -> Option<String> {
+    let chars: Vec<u16> = data
+        .get(offset..)?
+        .chunks_exact(2)
+        .map(|c| u16::from_le_bytes([c[0], c[1]]))
+        .take_while(|c| *c != 0)
+        .collect();
+    Some(String::from_utf16_lossy(&chars))
+}
+
+/// names of well-known folders
+fn known_folder(guid: &str) -> Option<&'static str> {
+    match guid {
+        "{20D04FE0-3AEA-1069-A2D8-08002B30309D}" => Some("My Computer"),
+        "{59031A47-3F72-44A7-89C5-5595FE6B30EE}" => Some("Users Files"),
+        "{450D8FBA-AD25-11D0-98A8-0800361B1103}" => Some("My Documents"),
+        "{208D2C60-3AEA-1069-A2D7-08002B30309D}" => Some("My Network Places"),
+        "{F02C1A0D-BE21-4350-88B0-7367FC96EF3C}" => Some("Network"),
+        "{645FF040-5081-101B-9F08-00AA002F954E}" => Some("Recycle Bin"),
+        "{26EE0668-A00A-44D7-9371-BEB064C98683}" => Some("Control Panel"),
+        "{031E4825-7B94-4DC3-B131-E946B44C8DD5}" => Some("Libraries"),
+        "{679F85CB-0220-4080-B29B-5540CC05AAB6}" => Some("Quick Access"),
+        "{F874310E-B6B7-47DC-BC84-B9E6B38F5903}" => Some("Home"),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fat_datetime, ShellItem};
+
+    #[test]
+    fn convert_fat_timestamps() {
+        // 2024-03-04 07:10:58
+        let data = [0x64, 0x58, 0x5d, 0x39];
+        assert_eq!(
+            fat_datetime(&data, 0).unwrap().unwrap().to_rfc3339(),
+            "2024-03-04T07:10:58+00:00"
+        );
+        assert_eq!(fat_datetime(&[0; 4], 0), Some(None));
+        assert_eq!(fat_datetime(&[0; 3], 0), None);
+    }
+
+    #[test]
+    fn parse_root_folders() {
+        let item = ShellItem::parse(&[
+            0x14, 0x00, 0x1f, 0x50, 0xe0, 0x4f, 0xd0, 0x20, 0xea, 0x3a, 0x69, 0x10, 0xa2, 0xd8,
+            0x08, 0x00, 0x2b, 0x30, 0x30, 0x9d,
+        ]);
+        assert_eq!(item.name(), "My Computer");
+        assert_eq!(item.item_type(), "root");
+    }
+
+    #[test]
+    fn parse_invalid_items() {
+        assert_eq!(ShellItem::parse(&[]).name(), "<invalid shell item>");
+        assert_eq!(
+            ShellItem::parse(&[0x20, 0x00, 0x1f]).name(),
+            "<invalid shell item>"
+        );
+        assert_eq!(
+            ShellItem::parse(&[0x05, 0x00, 0x31, 0x00, 0x00]).name(),
+            "<unknown shell item type 0x31>"
+        );
+    }
+}
@@ -0,0 +1,45 @@
+use chrono::{DateTime, Utc};
+use dfir_toolkit::common::bodyfile::Bodyfile3Line;
+use serde::Serialize;
+
+/// a folder which has been displayed by the Windows Explorer
+#[derive(Serialize, Debug)]
+pub(crate) struct ShellBag {
+    /// the reconstructed path of the folder
+    pub(crate) path: String,
+
+    /// the registry path of the value which contains the last shell item
+    pub(crate) registry_path: String,
+
+    #[serde(rename = "type")]
+    pub(crate) item_type: &'static str,
+
+    /// last written time of the key which contains the subfolders of this folder
+    pub(crate) last_written: Option<DateTime<Utc>>,
+
+    pub(crate) modified: Option<DateTime<Utc>>,
+    pub(crate) accessed: Option<DateTime<Utc>>,
+    pub(crate) created: Option<DateTime<Utc>>,
+}
+
+impl ShellBag {
+    /// the timestamps of the file entry become the corresponding bodyfile
+    /// timestamps, and the last written time of the key becomes ctime
+    pub(crate) fn to_bodyfile_line(&self) -> Bodyfile3Line {
+        let mut bf_line =
+            Bodyfile3Line::new().with_owned_name(format!("ShellBag: '{}'", self.path));
+        if let Some(ts) = &self.last_written {
+            bf_line = bf_line.with_ctime(ts.into());
+        }
+        if let Some(ts) = &self.modified {
+            bf_line = bf_line.with_mtime(ts.into());
+        }
+        if let Some(ts) = &self.accessed {
+            bf_line = bf_line.with_atime(ts.into());
+        }
+        if let Some(ts) = &self.created {
+            bf_line = bf_line.with_crtime(ts.into());
+        }
+        bf_line
+    }
+}
@@ -0,0 +1,37 @@
+use std::io::Write;
+
+use dfir_toolkit::common::Timezone;
+
+use super::ShellBag;
+use crate::table::{timestamp_cell, write_table};
+
+const HEADERS: [&str; 6] = [
+    "last written",
+    "modified",
+    "accessed",
+    "created",
+    "type",
+    "path",
+];
+
+/// writes the shellbags as a table with aligned columns
+pub(crate) fn write_shellbags_table<W: Write>(
+    w: W,
+    shellbags: &[ShellBag],
+    timezone: &Timezone,
+) -> std::io::Result<()> {
+    let rows: Vec<[String; 6]> = shellbags
+        .iter()
+        .map(|bag| {
+            [
+                timestamp_cell(&bag.last_written, timezone),
+                timestamp_cell(&bag.modified, timezone),
+                timestamp_cell(&bag.accessed, timezone),
+                timestamp_cell(&bag.created, timezone),
+                bag.item_type.to_owned(),
+                bag.path.clone(),
+            ]
+        })
+        .collect();
+    write_table(w, HEADERS, &rows)
+}
@@ -0,0 +1,32 @@
+use std::io::Write;
+
+use dfir_toolkit::common::Timezone;
+
+use super::ShimCacheEntry;
+use crate::table::{timestamp_cell, write_table};
+
+const HEADERS: [&str; 4] = ["position", "last modified", "executed", "path"];
+
+/// writes the entries as a table with aligned columns
+pub(crate) fn write_entries_table<W: Write>(
+    w: W,
+    entries: &[ShimCacheEntry],
+    timezone: &Timezone,
+) -> std::io::Result<()> {
+    let rows: Vec<[String; 4]> = entries
+        .iter()
+        .map(|entry| {
+            [
+                entry.position.to_string(),
+                timestamp_cell(&entry.last_modified, timezone),
+                match entry.executed {
+                    Some(true) => "yes".to_owned(),
+                    Some(false) => "no".to_owned(),
+                    None => "-".to_owned(),
+                },
+                entry.path.clone(),
+            ]
+        })
+        .collect();
+    write_table(w, HEADERS, &rows)
+}
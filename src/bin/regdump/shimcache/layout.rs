@@ -0,0 +1,251 @@
+use std::{
+    fmt::Display,
+    io::{Cursor, Read, Seek, SeekFrom},
+};
+
+use anyhow::{anyhow, bail, Result};
+use binread::BinReaderExt;
+use chrono::{DateTime, Utc};
+use winstructs::timestamp::WinTimestamp;
+
+use super::ShimCacheEntry;
+
+const WIN7_MAGIC: u32 = 0xbadc0fee;
+const WIN7_HEADER_SIZE: u64 = 0x80;
+const WIN8_HEADER_SIZE: u32 = 0x80;
+const WIN10_HEADER_SIZES: [u32; 2] = [0x30, 0x34];
+const WIN8_SIGNATURES: [&[u8; 4]; 2] = [b"00ts", b"10ts"];
+const WIN10_SIGNATURE: &[u8; 4] = b"10ts";
+
+/// this flag is set in the insert flags if the file has been executed
+const INSERT_FLAG_EXECUTED: u32 = 0x2;
+
+/// the different layouts of the AppCompatCache value
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Layout {
+    Windows7x86,
+    Windows7x64,
+    Windows8,
+    Windows10,
+}
+
+impl Display for Layout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Windows7x86 => write!(f, "Windows 7 (32 bit)"),
+            Self::Windows7x64 => write!(f, "Windows 7 (64 bit)"),
+            Self::Windows8 => write!(f, "Windows 8"),
+            Self::Windows10 => write!(f, "Windows 10/11"),
+        }
+    }
+}
+
+impl Layout {
+    /// detects the layout by looking at the header of the value
+    pub(crate) fn detect(data: &[u8]) -> Result<Self> {
+        let header = u32_at(data, 0)?;
+        if header == WIN7_MAGIC {
+            // in 64 bit systems, the path length is followed by 4 bytes padding,
+            // while 32 bit systems store the offset of the path here
+            if u32_at(data, 4)? > 0 && u32_at(data, WIN7_HEADER_SIZE as usize + 4)? != 0 {
+                Ok(Self::Windows7x86)
+            } else {
+                Ok(Self::Windows7x64)
+            }
+        } else if header == WIN8_HEADER_SIZE
+            && WIN8_SIGNATURES.contains(&signature_at(data, header as usize)?)
+        {
+            Ok(Self::Windows8)
+        } else if WIN10_HEADER_SIZES.contains(&header)
+            && signature_at(data, header as usize)? == WIN10_SIGNATURE
+        {
+            Ok(Self::Windows10)
+        } else {
+            bail!("unknown AppCompatCache format with header 0x{header:08x}")
+        }
+    }
+
+    /// parses all entries of the cache
+    pub(crate) fn parse(&self, data: &[u8]) -> Result<Vec<ShimCacheEntry>> {
+        let mut reader = Cursor::new(data);
+        match self {
+            Self::Windows7x86 | Self::Windows7x64 => self.parse_windows7(&mut reader),
+            Self::Windows8 | Self::Windows10 => self.parse_signed_entries(&mut reader),
+        }
+    }
+
+    fn parse_windows7(&self, reader: &mut Cursor<&[u8]>) -> Result<Vec<ShimCacheEntry>> {
+        reader.seek(SeekFrom::Start(4))?;
+        let count: u32 = reader.read_le()?;
+        reader.seek(SeekFrom::Start(WIN7_HEADER_SIZE))?;
+
+        let mut entries = Vec::new();
+        for position in 0..count as usize {
+            let path_length: u16 = reader.read_le()?;
+            let _max_path_length: u16 = reader.read_le()?;
+            let path_offset = if *self == Self::Windows7x64 {
+                let _padding: u32 = reader.read_le()?;
+                reader.read_le::<u64>()?
+            } else {
+                reader.read_le::<u32>()?.into()
+            };
+            let last_modified = read_filetime(reader)?;
+            let insert_flags: u32 = reader.read_le()?;
+            let _shim_flags: u32 = reader.read_le()?;
+            if *self == Self::Windows7x64 {
+                let _data: [u64; 2] = reader.read_le()?;
+            } else {
+                let _data: [u32; 2] = reader.read_le()?;
+            }
+
+            let entry_end = reader.position();
+            reader.seek(SeekFrom::Start(path_offset))?;
+            let path = read_path(reader, path_length)?;
+            reader.seek(SeekFrom::Start(entry_end))?;
+
+            entries.push(ShimCacheEntry {
+                position,
+                path,
+                last_modified,
+                executed: Some(insert_flags & INSERT_FLAG_EXECUTED != 0),
+            });
+        }
+        Ok(entries)
+    }
+
+    /// parses the entries of Windows 8 and later, which begin with a signature
+    fn parse_signed_entries(&self, reader: &mut Cursor<&[u8]>) -> Result<Vec<ShimCacheEntry>> {
+        let header_size: u32 = reader.read_le()?;
+        reader.seek(SeekFrom::Start(header_size.into()))?;
+
+        let mut entries = Vec::new();
+        while (reader.position() as usize) < reader.get_ref().len() {
+            let position = entries.len();
+            let signature: [u8; 4] = reader.read_le()?;
+            if !WIN8_SIGNATURES.contains(&&signature) {
+                bail!(
+                    "invalid signature {signature:02x?} of entry {position} at offset 0x{:x}",
+                    reader.position() - 4
+                );
+            }
+            let _unknown: u32 = reader.read_le()?;
+            let entry_size: u32 = reader.read_le()?;
+            let entry_end = reader.position() + u64::from(entry_size);
+
+            let path_length: u16 = reader.read_le()?;
+            let path = read_path(reader, path_length)?;
+
+            let (last_modified, executed) = if *self == Self::Windows8 {
+                let package_length: u16 = reader.read_le()?;
+                reader.seek(SeekFrom::Current(package_length.into()))?;
+                let insert_flags: u32 = reader.read_le()?;
+                let _shim_flags: u32 = reader.read_le()?;
+                (
+                    read_filetime(reader)?,
+                    Some(insert_flags & INSERT_FLAG_EXECUTED != 0),
+                )
+            } else {
+                (read_filetime(reader)?, None)
+            };
+
+            if entry_end > reader.get_ref().len() as u64 {
+                bail!("entry {position} exceeds the end of the AppCompatCache");
+            }
+            reader.seek(SeekFrom::Start(entry_end))?;
+
+            entries.push(ShimCacheEntry {
+                position,
+                path,
+                last_modified,
+                executed,
+            });
+        }
+        Ok(entries)
+    }
+}
+
+fn u32_at(data: &[u8], offset: usize) -> Result<u32> {
+    data.get(offset..offset + 4)
+        .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+        .ok_or_else(|| anyhow!("the AppCompatCache is too short"))
+}
+
+fn signature_at(data: &[u8], offset: usize) -> Result<&[u8; 4]> {
+    data.get(offset..offset + 4)
+        .map(|bytes| bytes.try_into().unwrap())
+        .ok_or_else(|| anyhow!("the AppCompatCache is too short"))
+}
+
+/// reads an UTF-16 encoded path, whose length is given in bytes. The
+/// `\??\` prefix of NT paths is removed
+fn read_path(reader: &mut Cursor<&[u8]>, length: u16) -> Result<String> {
+    let mut bytes = vec![0; length.into()];
+    reader.read_exact(&mut bytes)?;
+    let chars: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|c| u16::from_le_bytes([c[0], c[1]]))
+        .collect();
+    let path = String::from_utf16_lossy(&chars);
+    match path.strip_prefix("\\??\\") {
+        Some(path) => Ok(path.to_owned()),
+        None => Ok(path),
+    }
+}
+
+/// a FILETIME value of zero means that the timestamp is unknown
+fn read_filetime(reader: &mut Cursor<&[u8]>) -> Result<Option<DateTime<Utc>>> {
+    let filetime: [u8; 8] = reader.read_le()?;
+    if filetime == [0; 8] {
+        Ok(None)
+    } else {
+        Ok(Some(WinTimestamp::new(&filetime)?.to_datetime()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Layout;
+
+    fn windows7_x86_cache() -> Vec<u8> {
+        let path: Vec<u8> = "\\??\\C:\\x.exe"
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes)
+            .collect();
+        let mut data = vec![0; 0x80 + 32];
+        data[0..4].copy_from_slice(&0xbadc0feeu32.to_le_bytes());
+        data[4..8].copy_from_slice(&1u32.to_le_bytes());
+        data[0x80..0x82].copy_from_slice(&(path.len() as u16).to_le_bytes());
+        data[0x84..0x88].copy_from_slice(&(0xa0u32).to_le_bytes());
+        // 2024-03-04T07:05:00Z
+        data[0x88..0x90].copy_from_slice(&133_540_095_000_000_000u64.to_le_bytes());
+        data[0x90..0x94].copy_from_slice(&2u32.to_le_bytes());
+        data.extend(path);
+        data
+    }
+
+    #[test]
+    fn parse_windows7_x86() {
+        let data = windows7_x86_cache();
+        let layout = Layout::detect(&data).unwrap();
+        assert_eq!(layout, Layout::Windows7x86);
+
+        let entries = layout.parse(&data).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].path, "C:\\x.exe");
+        assert_eq!(entries[0].executed, Some(true));
+        assert_eq!(
+            entries[0].last_modified.unwrap().to_rfc3339(),
+            "2024-03-04T07:05:00+00:00"
+        );
+    }
+
+    #[test]
+    fn reject_invalid_caches() {
+        assert!(Layout::detect(&[]).is_err());
+        assert!(Layout::detect(&[0xff; 0x100]).is_err());
+
+        let mut data = windows7_x86_cache();
+        data.truncate(0x90);
+        assert!(Layout::Windows7x86.parse(&data).is_err());
+    }
+}
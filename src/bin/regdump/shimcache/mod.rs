@@ -0,0 +1,83 @@
+mod entries_table;
+mod layout;
+mod shimcache_entry;
+
+pub(crate) use entries_table::*;
+pub(crate) use layout::*;
+pub(crate) use shimcache_entry::*;
+
+use std::{
+    cell::RefCell,
+    io::{Read, Seek},
+    rc::Rc,
+};
+
+use anyhow::{anyhow, bail, Result};
+use nt_hive2::{CleanHive, Hive, KeyNode, RegistryValue};
+
+use crate::big_data::KeyValues;
+use crate::subtree::find_by_path;
+
+const APPCOMPATCACHE_KEY: &str = "Control\\Session Manager\\AppCompatCache";
+const APPCOMPATCACHE_VALUE: &str = "AppCompatCache";
+
+/// reads the entries of the AppCompatCache from a SYSTEM hive. If no
+/// `control_set` is given, the control set which is referenced by the
+/// `Current` value of the `Select` key is used.
+pub(crate) fn read_shimcache<RS>(
+    hive: &mut Hive<RS, CleanHive>,
+    root_key: &Rc<RefCell<KeyNode>>,
+    control_set: Option<u32>,
+) -> Result<Vec<ShimCacheEntry>>
+where
+    RS: Read + Seek,
+{
+    let control_set = match control_set {
+        Some(control_set) => control_set,
+        None => current_control_set(hive, root_key)?,
+    };
+    log::info!("reading the AppCompatCache of ControlSet{control_set:03}");
+
+    let path = format!("ControlSet{control_set:03}\\{APPCOMPATCACHE_KEY}");
+    let key = find_by_path(hive, root_key, &path)?
+        .ok_or_else(|| anyhow!("there is no key '{path}', is this a SYSTEM hive?"))?
+        .key;
+    let key = key.borrow();
+    let values = KeyValues::read(hive, &key);
+    let value = values
+        .iter()
+        .find(|v| v.name().eq_ignore_ascii_case(APPCOMPATCACHE_VALUE))
+        .ok_or_else(|| anyhow!("the key '{path}' has no value '{APPCOMPATCACHE_VALUE}'"))?;
+
+    match value.value() {
+        RegistryValue::RegBinary(data) => {
+            let layout = Layout::detect(data)?;
+            log::info!("the AppCompatCache has the layout of {layout}");
+            layout.parse(data)
+        }
+        _ => bail!("the value '{APPCOMPATCACHE_VALUE}' does not contain binary data"),
+    }
+}
+
+/// returns the number of the control set which is in use
+fn current_control_set<RS>(
+    hive: &mut Hive<RS, CleanHive>,
+    root_key: &Rc<RefCell<KeyNode>>,
+) -> Result<u32>
+where
+    RS: Read + Seek,
+{
+    let select = find_by_path(hive, root_key, "Select")?
+        .ok_or_else(|| anyhow!("there is no key 'Select', is this a SYSTEM hive?"))?
+        .key;
+    let select = select.borrow();
+    match select
+        .values()
+        .iter()
+        .find(|v| v.name().eq_ignore_ascii_case("Current"))
+        .map(|v| v.value())
+    {
+        Some(RegistryValue::RegDWord(current)) => Ok(*current),
+        _ => bail!("unable to find the current control set, please use '--control-set'"),
+    }
+}
@@ -0,0 +1,38 @@
+use chrono::{DateTime, Utc};
+use dfir_toolkit::common::bodyfile::Bodyfile3Line;
+use serde::Serialize;
+
+/// a single entry of the AppCompatCache. Entries are ordered from the most
+/// recently to the least recently inserted entry.
+#[derive(Serialize, Debug)]
+pub(crate) struct ShimCacheEntry {
+    /// position of the entry in the cache, beginning with 0
+    pub(crate) position: usize,
+
+    pub(crate) path: String,
+
+    /// last modification time of the file, not the time of execution
+    pub(crate) last_modified: Option<DateTime<Utc>>,
+
+    /// whether the file has been executed. This is only known for
+    /// Windows 7 and Windows 8.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) executed: Option<bool>,
+}
+
+impl ShimCacheEntry {
+    pub(crate) fn to_bodyfile_line(&self) -> Bodyfile3Line {
+        let executed = match self.executed {
+            Some(true) => ", executed",
+            _ => "",
+        };
+        let bf_line = Bodyfile3Line::new().with_owned_name(format!(
+            "ShimCache: '{}' (position {}{executed})",
+            self.path, self.position
+        ));
+        match &self.last_modified {
+            Some(ts) => bf_line.with_mtime(ts.into()),
+            None => bf_line,
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use std::{
+    cell::RefCell,
+    io::{Read, Seek},
+    rc::Rc,
+};
+
+use anyhow::Result;
+use nt_hive2::{CleanHive, Hive, KeyNode};
+use regex::Regex;
+
+/// a key which shall be dumped together with its descendants
+pub(crate) struct Subtree {
+    /// names of all ancestors of the key, beginning with the root key
+    pub(crate) parents: Vec<String>,
+    pub(crate) key: Rc<RefCell<KeyNode>>,
+}
+
+impl Subtree {
+    /// the whole hive, beginning with the root key
+    pub(crate) fn root(key: Rc<RefCell<KeyNode>>) -> Self {
+        Self {
+            parents: Vec::new(),
+            key,
+        }
+    }
+
+    /// the full path of the key, including the name of the root key
+    pub(crate) fn path(&self) -> String {
+        let mut path = self.parents.clone();
+        path.push(self.key.borrow().name().to_owned());
+        path.join("\\")
+    }
+}
+
+/// returns the key at `path`, which is relative to `root` and whose parts
+/// are separated by backslashes. Key names are compared case insensitive.
+pub(crate) fn find_by_path<RS>(
+    hive: &mut Hive<RS, CleanHive>,
+    root: &Rc<RefCell<KeyNode>>,
+    path: &str,
+) -> Result<Option<Subtree>>
+where
+    RS: Read + Seek,
+{
+    let mut parents = Vec::new();
+    let mut key = Rc::clone(root);
+    for name in path.split('\\').filter(|name| !name.is_empty()) {
+        let subkey = match key.borrow().subkey(name, hive)? {
+            Some(subkey) => subkey,
+            None => return Ok(None),
+        };
+        parents.push(key.borrow().name().to_owned());
+        key = subkey;
+    }
+    Ok(Some(Subtree { parents, key }))
+}
+
+/// returns all keys whose path relative to `root` matches `regex`.
+/// Descendants of a matching key are not returned, because they are part
+/// of its subtree.
+pub(crate) fn find_by_regex<RS>(
+    hive: &mut Hive<RS, CleanHive>,
+    root: &Rc<RefCell<KeyNode>>,
+    regex: &Regex,
+) -> Result<Vec<Subtree>>
+where
+    RS: Read + Seek,
+{
+    let mut subtrees = Vec::new();
+    let mut parents = vec![root.borrow().name().to_owned()];
+    find_by_regex_below(hive, &root.borrow(), &mut parents, regex, &mut subtrees)?;
+    Ok(subtrees)
+}
+
+fn find_by_regex_below<RS>(
+    hive: &mut Hive<RS, CleanHive>,
+    keynode: &KeyNode,
+    parents: &mut Vec<String>,
+    regex: &Regex,
+    subtrees: &mut Vec<Subtree>,
+) -> Result<()>
+where
+    RS: Read + Seek,
+{
+    for sk in keynode.subkeys(hive)?.iter() {
+        let name = sk.borrow().name().to_owned();
+        let relative_path = parents[1..]
+            .iter()
+            .chain(std::iter::once(&name))
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join("\\");
+
+        if regex.is_match(&relative_path) {
+            subtrees.push(Subtree {
+                parents: parents.clone(),
+                key: Rc::clone(sk),
+            });
+        } else {
+            parents.push(name);
+            find_by_regex_below(hive, &sk.borrow(), parents, regex, subtrees)?;
+            parents.pop();
+        }
+    }
+    Ok(())
+}
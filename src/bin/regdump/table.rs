@@ -0,0 +1,39 @@
+use std::io::Write;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use dfir_toolkit::common::Timezone;
+
+/// writes the rows as a table with aligned columns. The last column is not
+/// padded, because it usually contains paths of different lengths
+pub(crate) fn write_table<W: Write, const N: usize>(
+    mut w: W,
+    headers: [&str; N],
+    rows: &[[String; N]],
+) -> std::io::Result<()> {
+    let mut widths = headers.map(str::len);
+    for row in rows.iter() {
+        for (width, cell) in widths.iter_mut().zip(row.iter()) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let headers = headers.map(|h| h.to_uppercase());
+    for row in std::iter::once(&headers).chain(rows.iter()) {
+        let (last, cells) = row.split_last().unwrap();
+        for (cell, width) in cells.iter().zip(widths.iter()) {
+            write!(w, "{cell:width$}  ")?;
+        }
+        writeln!(w, "{last}")?;
+    }
+    Ok(())
+}
+
+/// formats an optional timestamp for a table cell
+pub(crate) fn timestamp_cell(ts: &Option<DateTime<Utc>>, timezone: &Timezone) -> String {
+    match ts {
+        Some(ts) => ts
+            .with_timezone(timezone)
+            .to_rfc3339_opts(SecondsFormat::Secs, true),
+        None => "-".to_owned(),
+    }
+}
@@ -0,0 +1,37 @@
+use chrono::{DateTime, Utc};
+use nt_hive2::KeyNode;
+
+/// selects keys by their last written timestamp. Both limits are inclusive.
+#[derive(Clone, Copy, Default)]
+pub(crate) struct TimestampFilter {
+    after: Option<DateTime<Utc>>,
+    before: Option<DateTime<Utc>>,
+}
+
+impl TimestampFilter {
+    pub(crate) fn new(after: Option<DateTime<Utc>>, before: Option<DateTime<Utc>>) -> Self {
+        Self { after, before }
+    }
+
+    pub(crate) fn is_active(&self) -> bool {
+        self.after.is_some() || self.before.is_some()
+    }
+
+    pub(crate) fn matches(&self, keynode: &KeyNode) -> bool {
+        self.matches_timestamp(Some(keynode.timestamp()))
+    }
+
+    /// keys without a timestamp, like recovered keys whose timestamp could
+    /// not be read, only match if the filter is not active
+    pub(crate) fn matches_timestamp(&self, timestamp: Option<&DateTime<Utc>>) -> bool {
+        let timestamp = match timestamp {
+            Some(timestamp) => timestamp,
+            None => return !self.is_active(),
+        };
+        self.after.map(|after| timestamp >= &after).unwrap_or(true)
+            && self
+                .before
+                .map(|before| timestamp <= &before)
+                .unwrap_or(true)
+    }
+}
@@ -0,0 +1,115 @@
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    path::PathBuf,
+};
+
+use anyhow::{bail, Context, Result};
+use binread::BinReaderExt;
+use nt_hive2::{
+    transactionlog::{ApplicationResult, TransactionLog},
+    BaseBlock, CleanHive, DirtyHive, Hive,
+};
+
+/// replays the log entries of the transaction logs into the hive, which is
+/// kept in memory. The hive file itself is not modified. Log entries are
+/// applied in the order of their sequence numbers, beginning with the entry
+/// which follows the secondary sequence number of the hive. A gap between
+/// the sequence numbers stops the replay, and a warning is printed
+pub(crate) fn apply_transaction_logs<B>(
+    mut hive: Hive<B, DirtyHive>,
+    logfiles: &[PathBuf],
+) -> Result<Hive<B, CleanHive>>
+where
+    B: BinReaderExt,
+{
+    if logfiles.len() > 2 {
+        bail!("more than two transaction log files are not supported")
+    }
+
+    let (primary, secondary) = match hive.base_block() {
+        Some(base_block) => (
+            *base_block.primary_sequence_number(),
+            *base_block.secondary_sequence_number(),
+        ),
+        None => {
+            log::warn!("the hive has no base block, treating it as if it was clean");
+            return Ok(hive.treat_hive_as_clean());
+        }
+    };
+
+    if primary == secondary {
+        log::info!("the hive is clean (sequence number {primary}), no transaction log is needed");
+        return Ok(hive.treat_hive_as_clean());
+    }
+
+    if logfiles.is_empty() {
+        log::warn!(
+            "the hive is dirty (sequence numbers {primary} and {secondary}), but no \
+             transaction logs have been found. Its contents may be stale or inconsistent"
+        );
+        return Ok(hive.treat_hive_as_clean());
+    }
+
+    if primary != secondary.wrapping_add(1) {
+        log::warn!(
+            "the sequence numbers of the hive differ by more than one ({primary} and \
+             {secondary}). Its contents may be inconsistent, even after applying the \
+             transaction logs"
+        );
+    }
+
+    let mut entries = BTreeMap::new();
+    for logfile in logfiles {
+        let log = TransactionLog::try_from(
+            File::open(logfile)
+                .with_context(|| format!("unable to open '{}'", logfile.display()))?,
+        )
+        .with_context(|| format!("unable to read the transaction log '{}'", logfile.display()))?;
+        for entry in log {
+            if *entry.sequence_number() > secondary {
+                entries.insert(*entry.sequence_number(), entry);
+            }
+        }
+    }
+
+    let mut expected = secondary.wrapping_add(1);
+    let mut applied = 0;
+    let mut dirty_pages = 0;
+    for (sequence_number, entry) in entries {
+        if sequence_number != expected {
+            log::warn!(
+                "expected a log entry with sequence number {expected}, but found \
+                 {sequence_number}. The remaining log entries are not applied, so the contents \
+                 of the hive may be stale"
+            );
+            break;
+        }
+        let pages = entry.dirty_pages().len();
+        if hive.apply_transaction_log(entry) != ApplicationResult::Applied {
+            log::warn!(
+                "unable to apply the log entry with sequence number {sequence_number}"
+            );
+            break;
+        }
+        applied += 1;
+        dirty_pages += pages;
+        expected = expected.wrapping_add(1);
+    }
+
+    if applied == 0 {
+        log::warn!(
+            "the hive is dirty (sequence numbers {primary} and {secondary}), but the \
+             transaction logs contain no entry which could be applied. Its contents may be stale"
+        );
+    } else {
+        // the output differs from the hive file, so this is always shown
+        log::warn!(
+            "applied {applied} log entries with sequence numbers {} to {} ({dirty_pages} dirty \
+             pages) to the hive, whose sequence numbers were {primary} and {secondary}",
+            secondary.wrapping_add(1),
+            expected.wrapping_sub(1)
+        );
+    }
+    Ok(hive.treat_hive_as_clean())
+}
@@ -0,0 +1,213 @@
+use std::fmt::Display;
+
+use anyhow::{bail, Result};
+use chrono::{DateTime, Duration, Utc};
+use dfir_toolkit::common::{FormattableDatetime, HumanDuration, Timezone};
+use lazy_static::lazy_static;
+use nt_hive2::RegistryValue;
+use regex::Regex;
+use serde::Serialize;
+use winstructs::timestamp::WinTimestamp;
+
+use crate::big_data::KeyValueRef;
+
+lazy_static! {
+    static ref USERASSIST_KEY: Regex =
+        Regex::new(r"(?i)\\Explorer\\UserAssist\\\{[^\\]+\}\\Count$").unwrap();
+}
+
+/// this value contains session information, not information about a program
+const SESSION_VALUE: &str = "UEME_CTLSESSION";
+
+/// size of an entry written by Windows XP
+const XP_ENTRY_SIZE: usize = 16;
+
+/// size of an entry written by Windows 7 and later
+const WIN7_ENTRY_SIZE: usize = 72;
+
+/// returns `true` if the values of the key at `path` are UserAssist entries
+pub(crate) fn is_userassist_key(path: &str) -> bool {
+    USERASSIST_KEY.is_match(path)
+}
+
+/// decodes the ROT13 encoded name of a UserAssist value
+pub(crate) fn rot13(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            'a'..='m' | 'A'..='M' => (c as u8 + 13) as char,
+            'n'..='z' | 'N'..='Z' => (c as u8 - 13) as char,
+            _ => c,
+        })
+        .collect()
+}
+
+/// information about a program which has been started by a user
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub(crate) struct UserAssistEntry {
+    run_count: u32,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    focus_count: Option<u32>,
+
+    /// time in milliseconds where the program had the focus
+    #[serde(skip_serializing_if = "Option::is_none")]
+    focus_time: Option<u32>,
+
+    last_executed: Option<DateTime<Utc>>,
+
+    /// timezone which is used to display `last_executed`
+    #[serde(skip)]
+    timezone: Timezone,
+}
+
+impl UserAssistEntry {
+    /// decodes a UserAssist value. Returns the decoded name of the program
+    /// together with the entry, or `None` if the value does not describe a
+    /// program. Malformed entries are reported as a warning and result in
+    /// `None`, so that their raw data can be displayed instead.
+    pub(crate) fn from_value(value: KeyValueRef) -> Option<(String, Self)> {
+        let program = rot13(value.name());
+        if program == SESSION_VALUE {
+            return None;
+        }
+        match value.value() {
+            RegistryValue::RegBinary(data) => match Self::decode(data) {
+                Ok(entry) => Some((program, entry)),
+                Err(why) => {
+                    log::warn!("unable to decode UserAssist entry of '{program}': {why}");
+                    None
+                }
+            },
+            _ => {
+                log::warn!("UserAssist entry of '{program}' does not contain binary data");
+                None
+            }
+        }
+    }
+
+    /// displays the time of the last execution in `timezone`, instead of UTC
+    pub(crate) fn with_timezone(mut self, timezone: Timezone) -> Self {
+        self.timezone = timezone;
+        self
+    }
+
+    fn decode(data: &[u8]) -> Result<Self> {
+        match data.len() {
+            XP_ENTRY_SIZE => Ok(Self {
+                // Windows XP starts counting at 5
+                run_count: u32_at(data, 4).saturating_sub(5),
+                focus_count: None,
+                focus_time: None,
+                last_executed: filetime_at(data, 8)?,
+                timezone: Timezone::default(),
+            }),
+            WIN7_ENTRY_SIZE => Ok(Self {
+                run_count: u32_at(data, 4),
+                focus_count: Some(u32_at(data, 8)),
+                focus_time: Some(u32_at(data, 12)),
+                last_executed: filetime_at(data, 60)?,
+                timezone: Timezone::default(),
+            }),
+            len => bail!("unexpected size of {len} bytes"),
+        }
+    }
+}
+
+impl Display for UserAssistEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "run count: {}", self.run_count)?;
+        if let Some(focus_count) = self.focus_count {
+            write!(f, ", focus count: {focus_count}")?;
+        }
+        if let Some(focus_time) = self.focus_time {
+            let focus_time = Duration::milliseconds(focus_time.into());
+            write!(f, ", focus time: {}", HumanDuration::from(focus_time))?;
+        }
+        match &self.last_executed {
+            Some(ts) => write!(
+                f,
+                ", last executed: {}",
+                FormattableDatetime::from(ts.with_timezone(&self.timezone))
+            ),
+            None => write!(f, ", last executed: never"),
+        }
+    }
+}
+
+fn u32_at(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+/// a FILETIME value of zero means that the program has never been executed
+fn filetime_at(data: &[u8], offset: usize) -> Result<Option<DateTime<Utc>>> {
+    let bytes = &data[offset..offset + 8];
+    if bytes.iter().all(|b| *b == 0) {
+        Ok(None)
+    } else {
+        Ok(Some(WinTimestamp::new(bytes)?.to_datetime()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_userassist_key, rot13, UserAssistEntry};
+
+    #[test]
+    fn decode_names() {
+        assert_eq!(
+            rot13("HRZR_EHACNGU:P:\\Jvaqbjf\\flfgrz32\\pzq.rkr"),
+            "UEME_RUNPATH:C:\\Windows\\system32\\cmd.exe"
+        );
+        assert_eq!(
+            rot13("{1NP14R77-02R7-4R5Q-O744-2RO1NR5198O7}\\abgrcnq.rkr"),
+            "{1AC14E77-02E7-4E5D-B744-2EB1AE5198B7}\\notepad.exe"
+        );
+    }
+
+    #[test]
+    fn recognize_userassist_keys() {
+        assert!(is_userassist_key(
+            "ROOT\\Software\\Microsoft\\Windows\\CurrentVersion\\Explorer\\UserAssist\\{CEBFF5CD-ACE2-4F4F-9178-9926F41749EA}\\Count"
+        ));
+        assert!(is_userassist_key(
+            "ROOT\\software\\microsoft\\windows\\currentversion\\explorer\\userassist\\{CEBFF5CD-ACE2-4F4F-9178-9926F41749EA}\\count"
+        ));
+        assert!(!is_userassist_key(
+            "ROOT\\Software\\Microsoft\\Windows\\CurrentVersion\\Explorer\\UserAssist\\{CEBFF5CD-ACE2-4F4F-9178-9926F41749EA}"
+        ));
+    }
+
+    #[test]
+    fn decode_win7_entries() {
+        let mut data = [0u8; 72];
+        data[4..8].copy_from_slice(&7u32.to_le_bytes());
+        data[8..12].copy_from_slice(&3u32.to_le_bytes());
+        data[12..16].copy_from_slice(&83_500u32.to_le_bytes());
+        // 2024-03-04T07:05:00Z
+        data[60..68].copy_from_slice(&133_540_095_000_000_000u64.to_le_bytes());
+
+        let entry = UserAssistEntry::decode(&data).unwrap();
+        assert_eq!(entry.run_count, 7);
+        assert_eq!(entry.focus_count, Some(3));
+        assert_eq!(
+            entry.to_string(),
+            "run count: 7, focus count: 3, focus time: 1m23s, last executed: 2024-03-04T07:05:00+00:00"
+        );
+    }
+
+    #[test]
+    fn decode_xp_entries() {
+        let mut data = [0u8; 16];
+        data[4..8].copy_from_slice(&6u32.to_le_bytes());
+
+        let entry = UserAssistEntry::decode(&data).unwrap();
+        assert_eq!(entry.run_count, 1);
+        assert_eq!(entry.to_string(), "run count: 1, last executed: never");
+    }
+
+    #[test]
+    fn reject_malformed_entries() {
+        assert!(UserAssistEntry::decode(&[]).is_err());
+        assert!(UserAssistEntry::decode(&[0u8; 71]).is_err());
+    }
+}
@@ -1,9 +1,13 @@
 use clap::{Parser, ValueHint};
 use log::LevelFilter;
+use regex::Regex;
 use clio::{Input,Output};
 use chrono_tz::Tz;
+use chrono::format::{Item, StrftimeItems};
 
-use dfir_toolkit::common::{HasVerboseFlag, TzArgument};
+use dfir_toolkit::common::{HasVerboseFlag, TzArgument, Verbosity};
+
+use crate::timestamp_format::{EpochUnit, TimestampFormat};
 
 /// replaces UNIX timestamps in a stream by a formatted date 
 #[derive(Parser, Debug)]
@@ -14,7 +18,7 @@ pub (crate) struct Cli {
     pub(crate) input_file: Input,
 
     #[clap(flatten)]
-    pub (crate) verbose: clap_verbosity_flag::Verbosity,
+    pub (crate) verbose: Verbosity,
 
     /// name of the file to write (default to stdout)
     #[clap(default_value="-", value_hint=ValueHint::FilePath, value_parser)]
@@ -24,9 +28,99 @@ pub (crate) struct Cli {
     #[clap(short('f'), long("from-timezone"), display_order(300), default_value_t=TzArgument::Tz(Tz::UTC))]
     pub(crate) src_zone: TzArgument,
 
-    /// name of offset of destination timezone (or 'list' to display all possible values
-    #[clap(short('t'), long("to-timezone"), display_order(400), default_value_t=TzArgument::Tz(Tz::UTC))]
+    /// name of offset of destination timezone (or 'list' to display all possible values. Every
+    /// timestamp is converted with the offset which is valid at this time, so that daylight
+    /// saving time is respected
+    #[clap(short('t'), long("to-timezone"), visible_alias("timezone"), display_order(400), default_value_t=TzArgument::Tz(Tz::UTC))]
     pub dst_zone: TzArgument,
+
+    /// format of the replaced timestamps, using the syntax of strftime (e.g. '%Y-%m-%d
+    /// %H:%M:%S %Z'). This overrides the format given by $DFIR_DATE. The default format is
+    /// RFC 3339
+    #[clap(long("output-format"), value_name("FORMAT"), value_parser(parse_strftime), display_order(410))]
+    pub(crate) output_format: Option<String>,
+
+    /// append the original value in parentheses to every replaced timestamp
+    #[clap(long("keep-original"), display_order(420))]
+    pub(crate) keep_original: bool,
+
+    /// replace only timestamps which are inside of the capture group of this regular expression
+    /// (e.g. 'time=(\d+)'), but all of them. The rest of every line is kept as it is
+    #[clap(long("only-matching"), value_name("REGEX"), value_parser(parse_pattern), display_order(450))]
+    pub(crate) only_matching: Option<Regex>,
+
+    /// format of the timestamps to replace. Can be specified multiple times, in which case the
+    /// format of every number is chosen by its number of digits (see below). If multiple
+    /// formats have the same number of digits, the first of them which results in a date
+    /// between 1970 and 2100 is used. Of every other number with at least 10 digits, the first
+    /// 10 digits are a UNIX timestamp. Numbers which would not result in a plausible date are
+    /// left untouched
+    #[clap(long("format"), value_enum, default_values_t=[TimestampFormat::Epoch], display_order(500))]
+    pub(crate) formats: Vec<TimestampFormat>,
+
+    /// also replace Windows FILETIME values (same as '--format filetime' in addition to the
+    /// other formats)
+    #[clap(long("filetime"), display_order(510))]
+    pub(crate) filetime: bool,
+
+    /// also replace WebKit timestamps, as used by Chrome (same as '--format webkit' in addition
+    /// to the other formats)
+    #[clap(long("webkit"), display_order(520))]
+    pub(crate) webkit: bool,
+
+    /// unit of UNIX timestamps. If a unit is given, every number is taken as a whole, and it is
+    /// only replaced if its date is between '--min-year' and '--max-year'. Otherwise, the first
+    /// 10 digits of every number with at least 10 digits are taken as seconds
+    #[clap(long("unit"), value_enum, display_order(530))]
+    pub(crate) unit: Option<EpochUnit>,
+
+    /// first year of the dates which are plausible for UNIX timestamps with '--unit'
+    #[clap(long("min-year"), value_name("YEAR"), value_parser(clap::value_parser!(i32).range(1970..10000)), default_value_t=1990, display_order(540))]
+    pub(crate) min_year: i32,
+
+    /// last year of the dates which are plausible for UNIX timestamps with '--unit'
+    #[clap(long("max-year"), value_name("YEAR"), value_parser(clap::value_parser!(i32).range(1970..10000)), default_value_t=2040, display_order(550))]
+    pub(crate) max_year: i32,
+}
+
+impl Cli {
+    /// the formats of the timestamps to replace. Because every number with at
+    /// least 10 digits could be a UNIX timestamp, this format comes last
+    pub(crate) fn formats(&self) -> Vec<TimestampFormat> {
+        let mut formats = self.formats.clone();
+        if self.filetime {
+            formats.push(TimestampFormat::Filetime);
+        }
+        if self.webkit {
+            formats.push(TimestampFormat::Webkit);
+        }
+        formats.sort_by_key(|format| *format == TimestampFormat::Epoch);
+        formats.dedup();
+        formats
+    }
+}
+
+fn parse_pattern(pattern: &str) -> Result<Regex, String> {
+    let regex = Regex::new(pattern)
+        .map_err(|why| format!("invalid regular expression '{pattern}': {why}"))?;
+    match regex.captures_len() {
+        2 => Ok(regex),
+        groups => Err(format!(
+            "the regular expression '{pattern}' must have exactly one capture group, but it has {}",
+            groups - 1
+        )),
+    }
+}
+
+fn parse_strftime(format: &str) -> Result<String, String> {
+    if StrftimeItems::new(format).any(|item| item == Item::Error) {
+        Err(format!(
+            "invalid date format '{format}', see \
+             <https://docs.rs/chrono/latest/chrono/format/strftime/index.html>"
+        ))
+    } else {
+        Ok(format.to_owned())
+    }
 }
 
 impl HasVerboseFlag for Cli {
@@ -1,14 +1,38 @@
-use std::io::{BufRead, Write};
-use regex::Regex;
+use std::io::{BufRead, ErrorKind, Write};
 use anyhow::{bail, Result};
-use dfir_toolkit::common::{ForensicsTimestamp,FancyParser,TzArgument};
+use dfir_toolkit::common::{FancyParser,TzArgument};
 use cli::Cli;
+use replacer::TimestampReplacer;
+use timestamp_format::TimestampParser;
 
 mod cli;
+mod replacer;
+mod timestamp_format;
 
 
+/// checks if writing failed because the reading end of the pipe has been
+/// closed, e.g. by `head`
+fn is_broken_pipe(why: &anyhow::Error) -> bool {
+    why.chain().any(|cause| {
+        cause
+            .downcast_ref::<std::io::Error>()
+            .is_some_and(|why| why.kind() == ErrorKind::BrokenPipe)
+    })
+}
+
 fn main() -> Result<()> {
-    let cli = Cli::parse_cli();
+    match run(Cli::parse_cli()) {
+        Err(why) if is_broken_pipe(&why) => Ok(()),
+        result => result,
+    }
+}
+
+fn run(cli: Cli) -> Result<()> {
+    if cli.min_year > cli.max_year {
+        bail!("'--min-year' must not be after '--max-year'");
+    }
+    let parser = TimestampParser::new(cli.formats())
+        .with_epoch_unit(cli.unit, cli.min_year, cli.max_year);
 
     let mut input = cli.input_file;
     let mut output = cli.output_file;
@@ -18,7 +42,9 @@ fn main() -> Result<()> {
         return Ok(());
     }
 
-    let re = Regex::new(r"^(?P<lhs>.*?)(?P<ts>\d{10})(?P<rhs>.*)$").unwrap();
+    let replacer = TimestampReplacer::new(parser, cli.dst_zone.into_tz().unwrap())
+        .with_output_format(cli.output_format)
+        .with_original(cli.keep_original);
     
     for line in input.lock().lines() {
         let content = match line {
@@ -26,15 +52,10 @@ fn main() -> Result<()> {
             Err(_) => bail!("content of input file need to be in UTF-8 (not in UTF-16)"),
         };
 
-        let out = match re.captures(&content) {
-            Some(caps) => {
-                let ts = ForensicsTimestamp::from(caps.name("ts").unwrap().as_str().parse::<i64>().unwrap()).with_timezone(cli.dst_zone.into_tz().unwrap());
-                format!("{}{}{}", caps.name("lhs").unwrap().as_str(),
-                                            ts,
-                                            caps.name("rhs").unwrap().as_str())
-
-            } 
-            None => content
+        // without a pattern, only the first timestamp of every line is replaced
+        let out = match &cli.only_matching {
+            Some(pattern) => replacer.replace_in_groups(&content, pattern),
+            None => replacer.replace_first(&content),
         };
 
         output.lock().write_all((out+ "\n").as_bytes())?;
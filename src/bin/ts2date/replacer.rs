@@ -0,0 +1,100 @@
+use chrono::DateTime;
+use chrono_tz::Tz;
+use dfir_toolkit::common::ForensicsTimestamp;
+use regex::Regex;
+
+use crate::timestamp_format::TimestampParser;
+
+/// replaces timestamps in a text by formatted dates
+pub(crate) struct TimestampReplacer {
+    parser: TimestampParser,
+    numbers: Regex,
+    dst_zone: Tz,
+    output_format: Option<String>,
+    keep_original: bool,
+}
+
+impl TimestampReplacer {
+    pub(crate) fn new(parser: TimestampParser, dst_zone: Tz) -> Self {
+        Self {
+            parser,
+            numbers: Regex::new(r"[0-9]+").unwrap(),
+            dst_zone,
+            output_format: None,
+            keep_original: false,
+        }
+    }
+
+    /// uses a strftime format instead of the default format
+    pub(crate) fn with_output_format(mut self, output_format: Option<String>) -> Self {
+        self.output_format = output_format;
+        self
+    }
+
+    /// appends the original value in parentheses to every formatted date
+    pub(crate) fn with_original(mut self, keep_original: bool) -> Self {
+        self.keep_original = keep_original;
+        self
+    }
+
+    /// replaces the first timestamp of `line`
+    pub(crate) fn replace_first(&self, line: &str) -> String {
+        self.replace(line, Some(1))
+    }
+
+    /// replaces all timestamps which are inside of the first capture group
+    /// of any match of `pattern`. The rest of `line` is kept as it is
+    pub(crate) fn replace_in_groups(&self, line: &str, pattern: &Regex) -> String {
+        let mut result = String::with_capacity(line.len());
+        let mut last_end = 0;
+        for group in pattern.captures_iter(line).filter_map(|c| c.get(1)) {
+            result.push_str(&line[last_end..group.start()]);
+            result.push_str(&self.replace(group.as_str(), None));
+            last_end = group.end();
+        }
+        result.push_str(&line[last_end..]);
+        result
+    }
+
+    /// replaces the timestamps of `text`, but not more than `limit`
+    fn replace(&self, text: &str, limit: Option<usize>) -> String {
+        let mut result = String::with_capacity(text.len());
+        let mut last_end = 0;
+        let mut replaced = 0;
+        for number in self.numbers.find_iter(text) {
+            if limit.is_some_and(|limit| replaced >= limit) {
+                break;
+            }
+            let Some((len, ts)) = self.parser.parse(number.as_str()) else {
+                continue;
+            };
+            let end = number.start() + len;
+            let Some(date) = self.format(ts, &text[number.start()..end]) else {
+                continue;
+            };
+            result.push_str(&text[last_end..number.start()]);
+            result.push_str(&date);
+            last_end = end;
+            replaced += 1;
+        }
+        result.push_str(&text[last_end..]);
+        result
+    }
+
+    fn format(&self, ts: i64, original: &str) -> Option<String> {
+        let date = match &self.output_format {
+            Some(format) => DateTime::from_timestamp(ts, 0)?
+                .with_timezone(&self.dst_zone)
+                .format(format)
+                .to_string(),
+            None => ForensicsTimestamp::from(ts)
+                .with_timezone(self.dst_zone)
+                .to_string(),
+        };
+        if self.keep_original {
+            Some(format!("{date} ({original})"))
+        } else {
+            Some(date)
+        }
+    }
+}
@@ -0,0 +1,269 @@
+use std::ops::RangeInclusive;
+
+use chrono::NaiveDate;
+use clap::ValueEnum;
+
+/// number of seconds between 1601-01-01 and 1970-01-01
+const SECONDS_FROM_1601_TO_1970: i64 = 11_644_473_600;
+
+/// number of seconds between 1904-01-01 and 1970-01-01
+const SECONDS_FROM_1904_TO_1970: i64 = 2_082_844_800;
+
+/// number of seconds between 1970-01-01 and 2001-01-01
+const SECONDS_FROM_1970_TO_2001: i64 = 978_307_200;
+
+/// 2100-01-01T00:00:00Z
+const END_OF_RANGE: i64 = 4_102_444_800;
+
+/// the formats of timestamps which can be replaced. The formats are
+/// distinguished by the number of digits of a value. If multiple formats have
+/// the same number of digits, the first of them which results in a plausible
+/// date is used
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum TimestampFormat {
+    /// UNIX timestamps, which are the first 10 digits of a number (see '--unit')
+    Epoch,
+
+    /// Windows FILETIME values (100 ns intervals since 1601-01-01), which are numbers of 18
+    /// digits
+    Filetime,
+
+    /// WebKit timestamps (microseconds since 1601-01-01), as used by Chrome and other
+    /// browsers, which are numbers of 17 digits
+    Webkit,
+
+    /// Mac absolute time (seconds since 2001-01-01), as used in property lists, which are
+    /// numbers of 9 or 10 digits
+    MacAbsolute,
+
+    /// HFS+ timestamps (seconds since 1904-01-01), which are numbers of 10 digits
+    Hfs,
+
+    /// APFS timestamps (nanoseconds since 1970-01-01), which are numbers of 19 digits
+    Apfs,
+}
+
+/// the unit of UNIX timestamps
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum EpochUnit {
+    /// infer the unit from the number of digits: 10 digits are seconds, 13 digits are
+    /// milliseconds, 16 digits are microseconds and 19 digits are nanoseconds
+    Auto,
+
+    /// seconds
+    S,
+
+    /// milliseconds
+    Ms,
+
+    /// microseconds
+    Us,
+
+    /// nanoseconds
+    Ns,
+}
+
+/// finds timestamps of the given formats in numbers
+pub(crate) struct TimestampParser {
+    formats: Vec<TimestampFormat>,
+
+    /// if no unit is given, the first 10 digits of every number with at
+    /// least 10 digits are taken as UNIX timestamp, regardless of its date
+    epoch_unit: Option<EpochUnit>,
+
+    /// UNIX timestamps which are considered plausible if a unit is given
+    epoch_window: RangeInclusive<i64>,
+}
+
+impl TimestampParser {
+    /// creates a parser for `formats`, which are tried in the given order
+    pub(crate) fn new(formats: Vec<TimestampFormat>) -> Self {
+        Self {
+            formats,
+            epoch_unit: None,
+            epoch_window: i64::MIN..=i64::MAX,
+        }
+    }
+
+    /// interprets every number as UNIX timestamp of `unit`, which is only
+    /// replaced if its date is between `min_year` and `max_year`
+    pub(crate) fn with_epoch_unit(
+        mut self,
+        epoch_unit: Option<EpochUnit>,
+        min_year: i32,
+        max_year: i32,
+    ) -> Self {
+        let start_of_year = |year| {
+            NaiveDate::from_ymd_opt(year, 1, 1)
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+                .map(|date| date.and_utc().timestamp())
+                .unwrap_or_else(|| panic!("invalid year: {year}"))
+        };
+        self.epoch_unit = epoch_unit;
+        self.epoch_window = start_of_year(min_year)..=start_of_year(max_year + 1) - 1;
+        self
+    }
+
+    /// finds the timestamp at the beginning of `number`, which must not be
+    /// preceded or followed by another digit, and returns the length of the
+    /// timestamp and its value as UNIX timestamp. Values which would result
+    /// in an implausible date are probably no timestamps at all, so `None`
+    /// is returned for them
+    pub(crate) fn parse(&self, number: &str) -> Option<(usize, i64)> {
+        self.formats
+            .iter()
+            .filter(|format| self.matches(**format, number))
+            .find_map(|format| self.convert(*format, number))
+    }
+
+    fn matches(&self, format: TimestampFormat, number: &str) -> bool {
+        match format {
+            TimestampFormat::Epoch => match self.epoch_unit {
+                // numbers which can have another format are never mangled
+                None => {
+                    number.len() >= 10
+                        && !self.formats.iter().any(|format| {
+                            *format != TimestampFormat::Epoch && self.matches(*format, number)
+                        })
+                }
+                Some(EpochUnit::Auto) => matches!(number.len(), 10 | 13 | 16 | 19),
+                Some(_) => true,
+            },
+            TimestampFormat::Filetime => number.len() == 18,
+            TimestampFormat::Webkit => number.len() == 17,
+            TimestampFormat::MacAbsolute => matches!(number.len(), 9 | 10),
+            TimestampFormat::Hfs => number.len() == 10,
+            TimestampFormat::Apfs => number.len() == 19,
+        }
+    }
+
+    /// converts a timestamp, which must be a date between 1970 and 2100 if
+    /// it is no UNIX timestamp
+    fn convert(&self, format: TimestampFormat, number: &str) -> Option<(usize, i64)> {
+        let (ticks_per_second, offset) = match format {
+            TimestampFormat::Epoch => return self.parse_epoch(number),
+            TimestampFormat::Filetime => (10_000_000, -SECONDS_FROM_1601_TO_1970),
+            TimestampFormat::Webkit => (1_000_000, -SECONDS_FROM_1601_TO_1970),
+            TimestampFormat::MacAbsolute => (1, SECONDS_FROM_1970_TO_2001),
+            TimestampFormat::Hfs => (1, -SECONDS_FROM_1904_TO_1970),
+            TimestampFormat::Apfs => (1_000_000_000, 0),
+        };
+        let unix_ts = number.parse::<i64>().ok()? / ticks_per_second + offset;
+        (0..END_OF_RANGE)
+            .contains(&unix_ts)
+            .then_some((number.len(), unix_ts))
+    }
+
+    fn parse_epoch(&self, number: &str) -> Option<(usize, i64)> {
+        let unit = match self.epoch_unit {
+            None => return Some((10, number[..10].parse().ok()?)),
+            Some(EpochUnit::Auto) => match number.len() {
+                10 => EpochUnit::S,
+                13 => EpochUnit::Ms,
+                16 => EpochUnit::Us,
+                _ => EpochUnit::Ns,
+            },
+            Some(unit) => unit,
+        };
+        let ticks_per_second = match unit {
+            EpochUnit::Auto | EpochUnit::S => 1,
+            EpochUnit::Ms => 1_000,
+            EpochUnit::Us => 1_000_000,
+            EpochUnit::Ns => 1_000_000_000,
+        };
+        let unix_ts = number.parse::<i64>().ok()?.div_euclid(ticks_per_second);
+        self.epoch_window
+            .contains(&unix_ts)
+            .then_some((number.len(), unix_ts))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EpochUnit, TimestampFormat, TimestampParser};
+
+    #[test]
+    fn convert_filetime() {
+        let parser = TimestampParser::new(vec![TimestampFormat::Filetime]);
+        assert_eq!(parser.parse("116444736000000000"), Some((18, 0)));
+        assert_eq!(parser.parse("133603191460000000"), Some((18, 1715845546)));
+        assert_eq!(parser.parse("999999999999999999"), None);
+        assert_eq!(parser.parse("100000000000000000"), None);
+    }
+
+    #[test]
+    fn convert_webkit() {
+        let parser = TimestampParser::new(vec![TimestampFormat::Webkit]);
+        assert_eq!(parser.parse("11644473600000000"), Some((17, 0)));
+        assert_eq!(parser.parse("13360319146123456"), Some((17, 1715845546)));
+        assert_eq!(parser.parse("99999999999999999"), None);
+        assert_eq!(parser.parse("133603191460000000"), None);
+    }
+
+    #[test]
+    fn convert_apple_timestamps() {
+        let parser = TimestampParser::new(vec![TimestampFormat::MacAbsolute]);
+        assert_eq!(parser.parse("737538346"), Some((9, 1715845546)));
+        assert_eq!(parser.parse("0000000000"), Some((10, 978307200)));
+        assert_eq!(parser.parse("9999999999"), None);
+        assert_eq!(parser.parse("73753834"), None);
+
+        let parser = TimestampParser::new(vec![TimestampFormat::Hfs]);
+        assert_eq!(parser.parse("3798690346"), Some((10, 1715845546)));
+        assert_eq!(parser.parse("2082844800"), Some((10, 0)));
+        assert_eq!(parser.parse("2082844799"), None);
+
+        let parser = TimestampParser::new(vec![TimestampFormat::Apfs]);
+        assert_eq!(parser.parse("1715845546123456789"), Some((19, 1715845546)));
+        assert_eq!(parser.parse("9215845546123456789"), None);
+    }
+
+    #[test]
+    fn choose_format_by_plausibility() {
+        let parser = TimestampParser::new(vec![
+            TimestampFormat::Hfs,
+            TimestampFormat::MacAbsolute,
+            TimestampFormat::Epoch,
+        ]);
+        // a plausible HFS+ timestamp
+        assert_eq!(parser.parse("3798690346"), Some((10, 1715845546)));
+        // too early for HFS+, but a plausible Mac absolute time
+        assert_eq!(parser.parse("1715845546"), Some((10, 2694152746)));
+        // UNIX timestamps are only used for numbers without another format
+        assert_eq!(parser.parse("17158455460"), Some((10, 1715845546)));
+        assert_eq!(parser.parse("9999999999"), None);
+    }
+
+    #[test]
+    fn detect_epoch_unit() {
+        let parser = TimestampParser::new(vec![TimestampFormat::Epoch]);
+        assert_eq!(parser.parse("1710252000000"), Some((10, 1710252000)));
+        assert_eq!(parser.parse("171025200"), None);
+
+        let parser = TimestampParser::new(vec![TimestampFormat::Epoch]).with_epoch_unit(
+            Some(EpochUnit::Auto),
+            1990,
+            2040,
+        );
+        for number in [
+            "1710252000",
+            "1710252000000",
+            "1710252000000000",
+            "1710252000000000000",
+        ] {
+            assert_eq!(parser.parse(number), Some((number.len(), 1710252000)));
+        }
+        assert_eq!(parser.parse("17102520000"), None);
+        assert_eq!(parser.parse("9999999999"), None);
+        assert_eq!(parser.parse("0000000000"), None);
+
+        let parser = TimestampParser::new(vec![TimestampFormat::Epoch]).with_epoch_unit(
+            Some(EpochUnit::Ms),
+            2024,
+            2024,
+        );
+        assert_eq!(parser.parse("1710252000123"), Some((13, 1710252000)));
+        assert_eq!(parser.parse("1610252000123"), None);
+        assert_eq!(parser.parse("1710252000"), None);
+    }
+}
@@ -1,16 +1,18 @@
 use clap::Parser;
 use clap::ValueHint;
 use clio::ClioPath;
-use dfir_toolkit::common::HasVerboseFlag;
+use dfir_toolkit::common::{HasVerboseFlag, Verbosity};
 use getset::Getters;
 use log::LevelFilter;
 
-/// creates bodyfile from ZIP Archives based on the contained files and folders
+/// creates bodyfile from ZIP, tar (optionally compressed with gzip or zstd) and 7z archives based on
+/// the contained files and folders
 #[derive(Parser, Getters)]
 #[clap(name=env!("CARGO_BIN_NAME"), author, version)]
 #[getset(get = "pub (crate)")]
 pub(crate) struct Cli {
-    /// names of the archive files (commonly files with 'zip' extension)
+    /// names of the archive files. The kind of an archive is detected by its magic or, if this is
+    /// unknown, by its extension
     #[clap(value_hint=ValueHint::FilePath)]
     zip_files: Vec<ClioPath>,
 
@@ -18,8 +20,25 @@ pub(crate) struct Cli {
     #[clap(long("show-archive-name"))]
     show_archive_name: bool,
 
+    /// append the CRC32 checksum and the compression method of every file to its name (ZIP
+    /// archives only)
+    #[clap(long("with-meta"))]
+    with_meta: bool,
+
+    /// also show the contents of ZIP archives (with 'zip' extension) which are contained in a ZIP
+    /// archive, up to the specified depth of nesting. The names of their contents are prefixed
+    /// with the path of the nested archive, e.g. 'inner.zip/file.txt'
+    #[clap(long("recurse-archives"), value_name = "DEPTH", default_value_t = 0)]
+    recurse_archives: u32,
+
+    /// maximum number of bytes (in MiB) which are decompressed to read the nested archives of
+    /// an archive. Nested archives which would exceed this limit are skipped, which prevents
+    /// zip bombs from exhausting the memory
+    #[clap(long("nested-size-limit"), value_name = "MIB", default_value_t = 1024)]
+    nested_size_limit: u64,
+
     #[clap(flatten)]
-    verbose: clap_verbosity_flag::Verbosity,
+    verbose: Verbosity,
 }
 
 impl HasVerboseFlag for Cli {
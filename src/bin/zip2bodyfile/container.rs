@@ -0,0 +1,184 @@
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+
+use dfir_toolkit::common::bodyfile::Bodyfile3Line;
+
+const ZIP_SIGNATURES: [&[u8]; 3] = [b"PK\x03\x04", b"PK\x05\x06", b"PK\x07\x08"];
+const SEVEN_ZIP_SIGNATURE: &[u8] = b"7z\xbc\xaf\x27\x1c";
+const GZIP_SIGNATURE: &[u8] = b"\x1f\x8b";
+const ZSTD_SIGNATURE: &[u8] = b"\x28\xb5\x2f\xfd";
+
+/// offset and value of the magic of a POSIX or GNU tar header
+const TAR_MAGIC_OFFSET: usize = 257;
+const TAR_MAGIC: &[u8] = b"ustar";
+
+/// the kinds of archives which are supported
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ContainerKind {
+    Zip,
+    Tar(Compression),
+    SevenZip,
+}
+
+/// compression of a tar archive as a whole
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    Gzip,
+    Zstd,
+}
+
+impl ContainerKind {
+    /// determines the kind of an archive by its magic, and by its extension if
+    /// the magic is unknown. Every other file is expected to be a zip archive
+    pub fn detect(path: &Path) -> std::io::Result<Self> {
+        let mut header = Vec::new();
+        File::open(path)?
+            .take((TAR_MAGIC_OFFSET + TAR_MAGIC.len()) as u64)
+            .read_to_end(&mut header)?;
+        Ok(Self::from_magic(&header).unwrap_or_else(|| Self::from_extension(path)))
+    }
+
+    fn from_magic(header: &[u8]) -> Option<Self> {
+        if ZIP_SIGNATURES.iter().any(|s| header.starts_with(s)) {
+            Some(Self::Zip)
+        } else if header.starts_with(SEVEN_ZIP_SIGNATURE) {
+            Some(Self::SevenZip)
+        } else if header.starts_with(GZIP_SIGNATURE) {
+            Some(Self::Tar(Compression::Gzip))
+        } else if header.starts_with(ZSTD_SIGNATURE) {
+            Some(Self::Tar(Compression::Zstd))
+        } else if header.get(TAR_MAGIC_OFFSET..) == Some(TAR_MAGIC) {
+            Some(Self::Tar(Compression::None))
+        } else {
+            None
+        }
+    }
+
+    fn from_extension(path: &Path) -> Self {
+        let name = path.to_string_lossy().to_lowercase();
+        if name.ends_with(".tar") {
+            Self::Tar(Compression::None)
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Self::Tar(Compression::Gzip)
+        } else if name.ends_with(".tar.zst") || name.ends_with(".tzst") {
+            Self::Tar(Compression::Zstd)
+        } else if name.ends_with(".7z") {
+            Self::SevenZip
+        } else {
+            Self::Zip
+        }
+    }
+}
+
+impl Compression {
+    /// opens a tar archive and decompresses it, if necessary
+    pub fn open(&self, path: &Path) -> anyhow::Result<Box<dyn Read>> {
+        let file = BufReader::new(File::open(path)?);
+        Ok(match self {
+            Self::None => Box::new(file),
+            Self::Gzip => Box::new(flate2::bufread::MultiGzDecoder::new(file)),
+            Self::Zstd => Box::new(zstd::stream::read::Decoder::with_buffer(file)?),
+        })
+    }
+}
+
+/// an entry of a tar or 7z archive, with all timestamps as UNIX epoch seconds
+#[derive(Default, Debug)]
+pub struct ArchiveEntry {
+    pub path: String,
+    pub size: u64,
+    pub modified: Option<i64>,
+    pub accessed: Option<i64>,
+    pub changed: Option<i64>,
+    pub created: Option<i64>,
+}
+
+impl ArchiveEntry {
+    pub fn has_timestamps(&self) -> bool {
+        self.modified.is_some()
+            || self.accessed.is_some()
+            || self.changed.is_some()
+            || self.created.is_some()
+    }
+
+    /// creates a bodyfile line. If the entry has no timestamps at all, zero
+    /// timestamps are used, so that it is visible in the timeline
+    pub fn to_bodyfile(&self, archive_name: &str, show_archive_name: bool) -> Bodyfile3Line {
+        let name = if show_archive_name {
+            format!("{} (in archive {archive_name})", self.path)
+        } else {
+            self.path.clone()
+        };
+        let mut bf_line = Bodyfile3Line::new()
+            .with_owned_name(name)
+            .with_size(self.size);
+
+        if !self.has_timestamps() {
+            return bf_line
+                .with_mtime(0.into())
+                .with_atime(0.into())
+                .with_ctime(0.into())
+                .with_crtime(0.into());
+        }
+        if let Some(mtime) = self.modified {
+            bf_line = bf_line.with_mtime(mtime.into());
+        }
+        if let Some(atime) = self.accessed {
+            bf_line = bf_line.with_atime(atime.into());
+        }
+        if let Some(ctime) = self.changed {
+            bf_line = bf_line.with_ctime(ctime.into());
+        }
+        if let Some(crtime) = self.created {
+            bf_line = bf_line.with_crtime(crtime.into());
+        }
+        bf_line
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Compression, ContainerKind};
+
+    #[test]
+    fn detect_by_magic() {
+        assert_eq!(
+            ContainerKind::from_magic(b"PK\x03\x04\x14\x00"),
+            Some(ContainerKind::Zip)
+        );
+        assert_eq!(
+            ContainerKind::from_magic(b"7z\xbc\xaf\x27\x1c\x00\x04"),
+            Some(ContainerKind::SevenZip)
+        );
+        assert_eq!(
+            ContainerKind::from_magic(b"\x1f\x8b\x08\x00"),
+            Some(ContainerKind::Tar(Compression::Gzip))
+        );
+
+        let mut tar_header = vec![0; 512];
+        tar_header[257..263].copy_from_slice(b"ustar\0");
+        assert_eq!(
+            ContainerKind::from_magic(&tar_header[..262]),
+            Some(ContainerKind::Tar(Compression::None))
+        );
+        assert_eq!(ContainerKind::from_magic(b"hello"), None);
+    }
+
+    #[test]
+    fn detect_by_extension() {
+        assert_eq!(
+            ContainerKind::from_extension("a/b.TGZ".as_ref()),
+            ContainerKind::Tar(Compression::Gzip)
+        );
+        assert_eq!(
+            ContainerKind::from_extension("collection.tar.zst".as_ref()),
+            ContainerKind::Tar(Compression::Zstd)
+        );
+        assert_eq!(
+            ContainerKind::from_extension("collection.zip".as_ref()),
+            ContainerKind::Zip
+        );
+    }
+}
@@ -0,0 +1,204 @@
+use std::io::{Read, Seek, SeekFrom};
+
+use anyhow::bail;
+
+const LOCAL_FILE_HEADER_SIGNATURE: u32 = 0x04034b50;
+const LOCAL_FILE_HEADER_SIZE: usize = 30;
+
+/// NTFS extra field, which contains the timestamps as FILETIME
+const NTFS: u16 = 0x000a;
+
+/// extended timestamp extra field (aka "UT"), which contains the timestamps
+/// as UNIX epoch seconds
+const EXTENDED_TIMESTAMP: u16 = 0x5455;
+
+/// attribute of the NTFS extra field which contains the timestamps
+const NTFS_TIMESTAMPS: u16 = 0x0001;
+
+/// difference between 1601-01-01 and 1970-01-01, in seconds
+const FILETIME_UNIX_EPOCH: i64 = 11_644_473_600;
+
+/// the timestamps of a zip entry which are stored in its extra fields, as
+/// UNIX epoch seconds. The MS-DOS timestamp of the entry has a precision of
+/// two seconds and no timezone, so these should be preferred
+#[derive(Default, Debug, PartialEq, Eq)]
+pub struct Timestamps {
+    pub modified: Option<i64>,
+    pub accessed: Option<i64>,
+    pub created: Option<i64>,
+}
+
+impl Timestamps {
+    /// parses the extra fields of a zip entry. If both an NTFS and an extended
+    /// timestamp extra field are found, the timestamps of the NTFS extra field
+    /// are used, because it contains all three timestamps even in the central
+    /// directory
+    pub fn from_extra_data(mut data: &[u8]) -> Self {
+        let mut ntfs = None;
+        let mut extended_timestamp = None;
+        while data.len() >= 4 {
+            let id = read_u16(data, 0);
+            let size = read_u16(data, 2) as usize;
+            let Some(field) = data.get(4..4 + size) else {
+                log::warn!("extra field 0x{id:04x} exceeds the extra data");
+                break;
+            };
+            match id {
+                NTFS => ntfs = ntfs.or_else(|| Self::from_ntfs(field)),
+                EXTENDED_TIMESTAMP => {
+                    extended_timestamp =
+                        extended_timestamp.or_else(|| Self::from_extended_timestamp(field))
+                }
+                _ => (),
+            }
+            data = &data[4 + size..];
+        }
+        ntfs.or(extended_timestamp).unwrap_or_default()
+    }
+
+    /// uses the timestamps of `other` for those timestamps which are missing
+    pub fn or(self, other: Self) -> Self {
+        Self {
+            modified: self.modified.or(other.modified),
+            accessed: self.accessed.or(other.accessed),
+            created: self.created.or(other.created),
+        }
+    }
+
+    /// the NTFS extra field consists of four reserved bytes, followed by
+    /// attributes with a tag and a size
+    fn from_ntfs(mut field: &[u8]) -> Option<Self> {
+        field = field.get(4..)?;
+        while field.len() >= 4 {
+            let tag = read_u16(field, 0);
+            let size = read_u16(field, 2) as usize;
+            let attribute = field.get(4..4 + size)?;
+            if tag == NTFS_TIMESTAMPS && size >= 24 {
+                return Some(Self {
+                    modified: filetime(read_u64(attribute, 0)),
+                    accessed: filetime(read_u64(attribute, 8)),
+                    created: filetime(read_u64(attribute, 16)),
+                });
+            }
+            field = &field[4 + size..];
+        }
+        None
+    }
+
+    /// the flags of the extended timestamp extra field specify which
+    /// timestamps are present in the local header. The central directory
+    /// usually contains only the modification time, but the same flags, so
+    /// that timestamps beyond the end of the field must be ignored
+    fn from_extended_timestamp(field: &[u8]) -> Option<Self> {
+        let (flags, mut times) = field.split_first()?;
+        let mut next_time = |flag: u8| {
+            if flags & flag == 0 || times.len() < 4 {
+                return None;
+            }
+            let time = u32::from_le_bytes(times[0..4].try_into().unwrap());
+            times = &times[4..];
+            Some(i64::from(time))
+        };
+        Some(Self {
+            modified: next_time(0b001),
+            accessed: next_time(0b010),
+            created: next_time(0b100),
+        })
+    }
+}
+
+/// reads the extra fields of the local file header which starts at
+/// `header_start`. They may differ from those in the central directory
+pub fn read_local_extra_data<R: Read + Seek>(
+    reader: &mut R,
+    header_start: u64,
+) -> anyhow::Result<Vec<u8>> {
+    let mut header = [0; LOCAL_FILE_HEADER_SIZE];
+    reader.seek(SeekFrom::Start(header_start))?;
+    reader.read_exact(&mut header)?;
+    if u32::from_le_bytes(header[0..4].try_into().unwrap()) != LOCAL_FILE_HEADER_SIGNATURE {
+        bail!("invalid signature of local file header at offset {header_start}");
+    }
+    let name_length = read_u16(&header, 26);
+    let extra_length = read_u16(&header, 28) as usize;
+
+    let mut extra_data = vec![0; extra_length];
+    reader.seek(SeekFrom::Current(i64::from(name_length)))?;
+    reader.read_exact(&mut extra_data)?;
+    Ok(extra_data)
+}
+
+/// converts a FILETIME to UNIX epoch seconds, or `None` if it is not set
+pub fn filetime(filetime: u64) -> Option<i64> {
+    (filetime != 0).then(|| (filetime / 10_000_000) as i64 - FILETIME_UNIX_EPOCH)
+}
+
+fn read_u16(data: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([data[offset], data[offset + 1]])
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    let mut bytes = [0; 8];
+    bytes.copy_from_slice(&data[offset..offset + 8]);
+    u64::from_le_bytes(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Timestamps;
+
+    const NTFS_FIELD: [u8; 36] = [
+        0x0a, 0x00, 0x20, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x18, 0x00, //
+        0x00, 0x40, 0x6d, 0x25, 0xeb, 0x53, 0xbf, 0x01, // 2000-01-01 00:00:00
+        0x80, 0x96, 0x98, 0x00, 0x00, 0x00, 0x00, 0x00, // 1601-01-01 00:00:01
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    ];
+
+    #[test]
+    fn parse_extended_timestamp() {
+        // the central directory contains only the modification time
+        let data = [0x55, 0x54, 0x05, 0x00, 0x07, 0x2e, 0x1f, 0xe0, 0x65];
+        assert_eq!(
+            Timestamps::from_extra_data(&data),
+            Timestamps {
+                modified: Some(1709186862),
+                accessed: None,
+                created: None
+            }
+        );
+
+        let data = [
+            0x55, 0x54, 0x09, 0x00, 0x05, 0x2e, 0x1f, 0xe0, 0x65, 0x2f, 0x1f, 0xe0, 0x65,
+        ];
+        assert_eq!(
+            Timestamps::from_extra_data(&data),
+            Timestamps {
+                modified: Some(1709186862),
+                accessed: None,
+                created: Some(1709186863)
+            }
+        );
+    }
+
+    #[test]
+    fn prefer_ntfs_timestamps() {
+        let mut data = vec![0x55, 0x54, 0x05, 0x00, 0x01, 0x2e, 0x1f, 0xe0, 0x65];
+        data.extend(NTFS_FIELD);
+        assert_eq!(
+            Timestamps::from_extra_data(&data),
+            Timestamps {
+                modified: Some(946684800),
+                accessed: Some(-11644473599),
+                created: None
+            }
+        );
+    }
+
+    #[test]
+    fn ignore_truncated_fields() {
+        assert_eq!(
+            Timestamps::from_extra_data(&NTFS_FIELD[..20]),
+            Timestamps::default()
+        );
+    }
+}
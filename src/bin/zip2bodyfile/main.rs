@@ -1,14 +1,24 @@
 mod cli;
+mod container;
+mod extra_fields;
+mod seven_zip;
+mod tar_archive;
 
-use std::io::{Read, Seek};
+use std::fs::File;
+use std::io::{BufReader, Cursor, Read, Seek};
+use std::rc::Rc;
 
 use chrono::FixedOffset;
 use cli::Cli;
+use container::{ArchiveEntry, ContainerKind};
 use dfir_toolkit::common::bodyfile::Bodyfile3Line;
 use dfir_toolkit::common::FancyParser;
+use extra_fields::Timestamps;
 use log::{error, warn};
+use seven_zip::SevenZipArchive;
+use tar_archive::TarArchive;
 use time::OffsetDateTime;
-use zip::{ExtraField, ZipArchive};
+use zip::ZipArchive;
 
 fn main() -> anyhow::Result<()> {
     let cli = Cli::parse_cli();
@@ -22,8 +32,40 @@ fn main() -> anyhow::Result<()> {
     for input in cli.zip_files().iter() {
         if let Some(zip_os_filename) = input.path().file_name() {
             if let Some(zip_filename) = zip_os_filename.to_str() {
-                let mut zip_archive = ZipArchive::new(input.clone().open()?)?;
-                zip_archive.display_zip_file(zip_filename, *cli.show_archive_name())?;
+                let display_options = DisplayOptions {
+                    show_archive_name: *cli.show_archive_name(),
+                    with_meta: *cli.with_meta(),
+                };
+                match ContainerKind::detect(input.path())? {
+                    ContainerKind::Zip => {
+                        let mut zip_archive = ZipArchive::new(input.clone().open()?)?;
+                        let mut local_headers = input.clone().open()?;
+                        let mut nested_archives = NestedArchives::new(
+                            *cli.recurse_archives(),
+                            cli.nested_size_limit() * 1024 * 1024,
+                        );
+                        zip_archive.display_zip_file(
+                            &mut local_headers,
+                            zip_filename,
+                            "",
+                            &display_options,
+                            &mut nested_archives,
+                        )?;
+                    }
+                    ContainerKind::Tar(compression) => display_entries(
+                        TarArchive::new(compression.open(input.path())?),
+                        zip_filename,
+                        &display_options,
+                    )?,
+                    ContainerKind::SevenZip => display_entries(
+                        SevenZipArchive::open(BufReader::new(File::open(input.path())?))?
+                            .entries()
+                            .into_iter()
+                            .map(Ok),
+                        zip_filename,
+                        &display_options,
+                    )?,
+                }
             } else {
                 error!("invalid Unicode characters in filename: '{zip_os_filename:?}'")
             }
@@ -34,44 +76,150 @@ fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
-trait DisplayZipFile {
+/// prints the entries of a tar or 7z archive
+fn display_entries(
+    entries: impl Iterator<Item = anyhow::Result<ArchiveEntry>>,
+    archive_name: &str,
+    options: &DisplayOptions,
+) -> anyhow::Result<()> {
+    let mut entries_without_timestamps = 0;
+    for entry in entries {
+        let entry = entry?;
+        if !entry.has_timestamps() {
+            entries_without_timestamps += 1;
+        }
+        println!(
+            "{}",
+            entry.to_bodyfile(archive_name, options.show_archive_name)
+        );
+    }
+    if entries_without_timestamps > 0 {
+        log::warn!(
+            "{entries_without_timestamps} entries of '{archive_name}' have no timestamps and are shown with zero timestamps"
+        );
+    }
+    Ok(())
+}
+
+struct DisplayOptions {
+    show_archive_name: bool,
+
+    /// append the CRC32 and the compression method to the name
+    with_meta: bool,
+}
+
+/// limits the recursion into archives which are contained in an archive
+struct NestedArchives {
+    max_depth: u32,
+    depth: u32,
+
+    /// number of bytes which may still be decompressed to read nested archives
+    remaining_size: u64,
+}
+
+impl NestedArchives {
+    fn new(max_depth: u32, size_limit: u64) -> Self {
+        Self {
+            max_depth,
+            depth: 0,
+            remaining_size: size_limit,
+        }
+    }
+
+    fn can_recurse_into(&self, name: &str) -> bool {
+        self.depth < self.max_depth && name.to_lowercase().ends_with(".zip")
+    }
+
+    /// decompresses a nested archive into memory. The size stored in the
+    /// archive is not trusted, so that reading stops as soon as the limit
+    /// is exceeded
+    fn read<R: Read + Seek>(
+        &mut self,
+        archive: &mut ZipArchive<R>,
+        index: usize,
+    ) -> anyhow::Result<Vec<u8>> {
+        let file = archive.by_index(index)?;
+        if file.size() > self.remaining_size {
+            anyhow::bail!("its size of {} bytes exceeds the size limit", file.size());
+        }
+        let mut data = Vec::with_capacity(file.size() as usize);
+        file.take(self.remaining_size + 1).read_to_end(&mut data)?;
+        if data.len() as u64 > self.remaining_size {
+            anyhow::bail!("it exceeds the size limit");
+        }
+        self.remaining_size -= data.len() as u64;
+        Ok(data)
+    }
+}
+
+trait DisplayZipFile<R> {
+    /// prints the contents of an archive. `prefix` is prepended to the names
+    /// of the contents of nested archives. `local_headers` is a second reader
+    /// of the archive, which is needed to read the local file headers
     fn display_zip_file(
         &mut self,
+        local_headers: &mut R,
+        zip_file_name: &str,
+        prefix: &str,
+        options: &DisplayOptions,
+        nested_archives: &mut NestedArchives,
+    ) -> anyhow::Result<()>;
+
+    fn display_nested_archive(
+        &mut self,
+        index: usize,
         zip_file_name: &str,
-        show_archive_name: bool,
+        path: &str,
+        options: &DisplayOptions,
+        nested_archives: &mut NestedArchives,
     ) -> anyhow::Result<()>;
 }
 
-impl<R> DisplayZipFile for ZipArchive<R>
+impl<R> DisplayZipFile<R> for ZipArchive<R>
 where
     R: Read + Seek,
 {
     fn display_zip_file(
         &mut self,
+        local_headers: &mut R,
         zip_file_name: &str,
-        show_archive_name: bool,
+        prefix: &str,
+        options: &DisplayOptions,
+        nested_archives: &mut NestedArchives,
     ) -> anyhow::Result<()> {
         for index in 0..self.len() {
-            let file = self.by_index(index)?;
+            // the file is not decompressed, so that the metadata of encrypted
+            // files can be read as well
+            let file = self.by_index_raw(index)?;
 
             let mut bf_line = Bodyfile3Line::new().with_size(file.size());
 
-            let mut utc_mtime = None;
-            for field in file.extra_data_fields() {
-                #[allow(irrefutable_let_patterns)]
-                if let ExtraField::ExtendedTimestamp(ts) = field {
-                    if let Some(mtime) = ts.mod_time() {
-                        bf_line = bf_line.with_mtime((mtime as i64).into());
-                        utc_mtime = Some(mtime as i64);
+            // the central directory often contains only a subset of the
+            // timestamps of the local file header
+            let central_timestamps =
+                Timestamps::from_extra_data(file.extra_data().unwrap_or_default());
+            let timestamps =
+                match extra_fields::read_local_extra_data(local_headers, file.header_start()) {
+                    Ok(extra_data) => {
+                        Timestamps::from_extra_data(&extra_data).or(central_timestamps)
                     }
-                    if let Some(atime) = ts.mod_time() {
-                        bf_line = bf_line.with_atime((atime as i64).into());
+                    Err(why) => {
+                        log::warn!(
+                            "unable to read the local header of '{}': {why}",
+                            file.name()
+                        );
+                        central_timestamps
                     }
-                    if let Some(crtime) = ts.mod_time() {
-                        bf_line = bf_line.with_crtime((crtime as i64).into());
-                    }
-                    break;
-                }
+                };
+            let utc_mtime = timestamps.modified;
+            if let Some(mtime) = timestamps.modified {
+                bf_line = bf_line.with_mtime(mtime.into());
+            }
+            if let Some(atime) = timestamps.accessed {
+                bf_line = bf_line.with_atime(atime.into());
+            }
+            if let Some(crtime) = timestamps.created {
+                bf_line = bf_line.with_crtime(crtime.into());
             }
 
             let tz_offset = utc_mtime.and_then(|utc_mtime| {
@@ -81,12 +229,19 @@ where
                             let local_ts = local_ts.unix_timestamp();
                             match i32::try_from(local_ts - utc_mtime) {
                                 Err(_) => {
-                                    log::warn!("illegal timezone offset: {}, ", local_ts - utc_mtime);
+                                    log::warn!(
+                                        "illegal timezone offset of '{}': {}",
+                                        file.name(),
+                                        local_ts - utc_mtime
+                                    );
                                     None
                                 }
                                 Ok(secs) => match FixedOffset::east_opt(secs) {
                                     None => {
-                                        log::warn!("timestamp offset (abs value) is too large: {secs} seconds");
+                                        log::warn!(
+                                            "timestamp offset (abs value) of '{}' is too large: {secs} seconds",
+                                            file.name()
+                                        );
                                         None
                                     }
                                     Some(offset) => Some(offset),
@@ -94,7 +249,7 @@ where
                             }
                         }
                         Err(why) => {
-                            log::warn!("unable to calculate timezone: {why}");
+                            log::warn!("unable to calculate timezone of '{}': {why}", file.name());
                             None
                         }
                     }
@@ -109,10 +264,17 @@ where
             if utc_mtime.is_none() {
                 match file.last_modified() {
                     None => {
-                        log::warn!("no extended timestamp header with modification time found");
+                        log::warn!(
+                            "'{}' has no extra field with a modification time",
+                            file.name()
+                        );
                     }
                     Some(last_modified) => {
-                        log::warn!("no extended timestamp header with modification time found, try using the MS-DOS timestamp instead");
+                        // this is common, e.g. for archives created by Windows
+                        log::info!(
+                            "'{}' has no extra field with a modification time, using the MS-DOS timestamp instead",
+                            file.name()
+                        );
                         match OffsetDateTime::try_from(last_modified) {
                             Err(why) => log::error!(
                                 "unable to convert {last_modified} into an OffsetDateTime: {why}"
@@ -123,19 +285,67 @@ where
                 }
             }
 
-            let name = if show_archive_name {
+            let meta_text = if options.with_meta {
                 format!(
-                    "{} (in archive {zip_file_name}){tz_offset_text}",
-                    file.name()
+                    ", [crc32: {:08x}, method: {}]",
+                    file.crc32(),
+                    file.compression()
                 )
             } else {
-                format!("{}{tz_offset_text}", file.name())
+                "".to_string()
+            };
+
+            let path = format!("{prefix}{}", file.name());
+            let name = if options.show_archive_name {
+                format!("{path} (in archive {zip_file_name}){tz_offset_text}{meta_text}")
+            } else {
+                format!("{path}{tz_offset_text}{meta_text}")
             };
 
             bf_line = bf_line.with_owned_name(name);
 
             println!("{bf_line}");
+
+            if file.is_file() && nested_archives.can_recurse_into(file.name()) {
+                drop(file);
+                self.display_nested_archive(index, zip_file_name, &path, options, nested_archives)?;
+            }
         }
         Ok(())
     }
+
+    /// prints the contents of a nested archive. If it cannot be read, e.g.
+    /// because it is encrypted, a warning is printed and its contents are
+    /// skipped
+    fn display_nested_archive(
+        &mut self,
+        index: usize,
+        zip_file_name: &str,
+        path: &str,
+        options: &DisplayOptions,
+        nested_archives: &mut NestedArchives,
+    ) -> anyhow::Result<()> {
+        let nested_archive = nested_archives.read(self, index).and_then(|data| {
+            let data: Rc<[u8]> = data.into();
+            Ok((ZipArchive::new(Cursor::new(Rc::clone(&data)))?, data))
+        });
+        let (mut nested_archive, data) = match nested_archive {
+            Ok(nested_archive) => nested_archive,
+            Err(why) => {
+                log::warn!("unable to read nested archive '{path}': {why}");
+                return Ok(());
+            }
+        };
+
+        nested_archives.depth += 1;
+        let result = nested_archive.display_zip_file(
+            &mut Cursor::new(data),
+            zip_file_name,
+            &format!("{path}/"),
+            options,
+            nested_archives,
+        );
+        nested_archives.depth -= 1;
+        result
+    }
 }
@@ -0,0 +1,592 @@
+use std::io::{Read, Seek, SeekFrom, Write};
+
+use anyhow::{anyhow, bail};
+use lzma_rs::decompress::{Options, UnpackedSize};
+
+use crate::container::ArchiveEntry;
+use crate::extra_fields::filetime;
+
+const SIGNATURE_HEADER_SIZE: u64 = 32;
+
+/// the header is read into memory, so its size is limited
+const MAX_HEADER_SIZE: u64 = 64 * 1024 * 1024;
+
+/// an encoded header may contain another encoded header, but there is no
+/// reason to do this more than once
+const MAX_HEADER_ENCODINGS: usize = 4;
+
+const K_END: u8 = 0x00;
+const K_HEADER: u8 = 0x01;
+const K_ARCHIVE_PROPERTIES: u8 = 0x02;
+const K_ADDITIONAL_STREAMS_INFO: u8 = 0x03;
+const K_MAIN_STREAMS_INFO: u8 = 0x04;
+const K_FILES_INFO: u8 = 0x05;
+const K_PACK_INFO: u8 = 0x06;
+const K_UNPACK_INFO: u8 = 0x07;
+const K_SUBSTREAMS_INFO: u8 = 0x08;
+const K_SIZE: u8 = 0x09;
+const K_CRC: u8 = 0x0a;
+const K_FOLDER: u8 = 0x0b;
+const K_CODERS_UNPACK_SIZE: u8 = 0x0c;
+const K_NUM_UNPACK_STREAM: u8 = 0x0d;
+const K_EMPTY_STREAM: u8 = 0x0e;
+const K_EMPTY_FILE: u8 = 0x0f;
+const K_NAME: u8 = 0x11;
+const K_CTIME: u8 = 0x12;
+const K_ATIME: u8 = 0x13;
+const K_MTIME: u8 = 0x14;
+const K_ENCODED_HEADER: u8 = 0x17;
+
+const CODER_COPY: &[u8] = &[0x00];
+const CODER_LZMA: &[u8] = &[0x03, 0x01, 0x01];
+const CODER_LZMA2: &[u8] = &[0x21];
+const CODER_AES: &[u8] = &[0x06, 0xf1, 0x07, 0x01];
+
+/// a minimal reader of 7z archives, which reads the names, sizes and
+/// timestamps of the entries from the header of the archive. The contents
+/// of the entries are not decompressed
+pub struct SevenZipArchive {
+    entries: Vec<ArchiveEntry>,
+}
+
+#[derive(Default)]
+struct StreamsInfo {
+    pack_position: u64,
+    pack_sizes: Vec<u64>,
+    folders: Vec<Folder>,
+
+    /// the sizes of the files which are stored in the folders
+    substream_sizes: Vec<u64>,
+}
+
+#[derive(Default)]
+struct Folder {
+    coders: Vec<Coder>,
+    unpack_sizes: Vec<u64>,
+    bound_out_streams: Vec<u64>,
+}
+
+struct Coder {
+    id: Vec<u8>,
+    properties: Vec<u8>,
+}
+
+impl Folder {
+    /// the size of the output of the folder, which is the size of the only
+    /// output stream which is not bound to the input of another coder
+    fn unpack_size(&self) -> u64 {
+        (0..self.unpack_sizes.len())
+            .find(|index| !self.bound_out_streams.contains(&(*index as u64)))
+            .map(|index| self.unpack_sizes[index])
+            .unwrap_or_default()
+    }
+}
+
+impl SevenZipArchive {
+    pub fn open<R: Read + Seek>(mut reader: R) -> anyhow::Result<Self> {
+        let mut signature_header = [0; SIGNATURE_HEADER_SIZE as usize];
+        reader.read_exact(&mut signature_header)?;
+        let mut header = Reader::new(&signature_header[12..28]);
+        let next_header_offset = header.read_u64()?;
+        let next_header_size = header.read_u64()?;
+        if next_header_size > MAX_HEADER_SIZE {
+            bail!("the header of {next_header_size} bytes is abnormally large");
+        }
+
+        let mut header = vec![0; next_header_size as usize];
+        reader.seek(SeekFrom::Start(header_position(next_header_offset)?))?;
+        reader.read_exact(&mut header)?;
+
+        for _ in 0..MAX_HEADER_ENCODINGS {
+            let mut header_reader = Reader::new(&header);
+            match header_reader.read_u8()? {
+                K_HEADER => {
+                    return Ok(Self {
+                        entries: header_reader.read_header()?,
+                    })
+                }
+                K_ENCODED_HEADER => {
+                    let streams_info = header_reader.read_streams_info()?;
+                    header = decode_header(&mut reader, &streams_info)?;
+                }
+                id => bail!("unexpected property 0x{id:02x} instead of the header"),
+            }
+        }
+        bail!("the header is encoded too many times")
+    }
+
+    pub fn entries(self) -> Vec<ArchiveEntry> {
+        self.entries
+    }
+}
+
+/// decompresses the first folder of an encoded header
+fn decode_header<R: Read + Seek>(
+    reader: &mut R,
+    streams_info: &StreamsInfo,
+) -> anyhow::Result<Vec<u8>> {
+    let (Some(folder), Some(pack_size)) = (
+        streams_info.folders.first(),
+        streams_info.pack_sizes.first(),
+    ) else {
+        bail!("the encoded header contains no data");
+    };
+    let unpack_size = folder.unpack_size();
+    if *pack_size > MAX_HEADER_SIZE || unpack_size > MAX_HEADER_SIZE {
+        bail!("the encoded header is abnormally large");
+    }
+    let [coder] = &folder.coders[..] else {
+        bail!("the header is encoded with an unsupported chain of coders");
+    };
+
+    let mut packed = vec![0; *pack_size as usize];
+    reader.seek(SeekFrom::Start(header_position(streams_info.pack_position)?))?;
+    reader.read_exact(&mut packed)?;
+
+    let mut header = LimitedWriter {
+        data: Vec::with_capacity(unpack_size as usize),
+        limit: unpack_size as usize,
+    };
+    match &coder.id[..] {
+        CODER_COPY => header.data = packed,
+        CODER_LZMA => {
+            // the LZMA properties are stored like the header of a '.lzma'
+            // file, but without the size
+            let mut input = coder.properties.clone();
+            input.extend(packed);
+            let options = Options {
+                unpacked_size: UnpackedSize::UseProvided(Some(unpack_size)),
+                ..Default::default()
+            };
+            lzma_rs::lzma_decompress_with_options(&mut &input[..], &mut header, &options)?
+        }
+        CODER_LZMA2 => lzma_rs::lzma2_decompress(&mut &packed[..], &mut header)?,
+        CODER_AES => bail!("the header is encrypted"),
+        id => bail!("the header is encoded with an unsupported coder {id:02x?}"),
+    }
+    header.data.truncate(unpack_size as usize);
+    Ok(header.data)
+}
+
+/// converts an offset which is relative to the end of the signature header
+/// into a position in the file
+fn header_position(offset: u64) -> anyhow::Result<u64> {
+    SIGNATURE_HEADER_SIZE
+        .checked_add(offset)
+        .ok_or_else(|| anyhow!("invalid offset 0x{offset:x} in the 7z archive"))
+}
+
+/// collects the decompressed header, and fails if it becomes larger than
+/// the size which has been announced in the archive
+struct LimitedWriter {
+    data: Vec<u8>,
+    limit: usize,
+}
+
+impl Write for LimitedWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if self.data.len() + buf.len() > self.limit {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "the decompressed header is larger than announced",
+            ));
+        }
+        self.data.write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// reads the structures of a 7z header
+struct Reader<'d> {
+    data: &'d [u8],
+}
+
+impl<'d> Reader<'d> {
+    fn new(data: &'d [u8]) -> Self {
+        Self { data }
+    }
+
+    fn read_bytes(&mut self, count: usize) -> anyhow::Result<&'d [u8]> {
+        if count > self.data.len() {
+            bail!("the header of the 7z archive is truncated");
+        }
+        let (bytes, data) = self.data.split_at(count);
+        self.data = data;
+        Ok(bytes)
+    }
+
+    fn read_u8(&mut self) -> anyhow::Result<u8> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    fn read_u32(&mut self) -> anyhow::Result<u32> {
+        Ok(u32::from_le_bytes(self.read_bytes(4)?.try_into()?))
+    }
+
+    fn read_u64(&mut self) -> anyhow::Result<u64> {
+        Ok(u64::from_le_bytes(self.read_bytes(8)?.try_into()?))
+    }
+
+    /// reads a number, whose first byte specifies how many bytes follow: the
+    /// number of leading 1 bits is the number of additional bytes, and the
+    /// remaining bits are the highest bits of the number
+    fn read_number(&mut self) -> anyhow::Result<u64> {
+        let first = self.read_u8()?;
+        let mut value = 0;
+        for index in 0..8 {
+            let mask = 0x80 >> index;
+            if first & mask == 0 {
+                let high = u64::from(first & (mask - 1));
+                return Ok(value | (high << (8 * index)));
+            }
+            value |= u64::from(self.read_u8()?) << (8 * index);
+        }
+        Ok(value)
+    }
+
+    /// reads a number which is used as the number of following items, which
+    /// cannot be larger than the remaining data
+    fn read_count(&mut self) -> anyhow::Result<usize> {
+        let count = self.read_number()?;
+        if count > self.data.len() as u64 * 8 {
+            bail!("invalid number of items in the header of the 7z archive");
+        }
+        Ok(count as usize)
+    }
+
+    fn read_bit_vector(&mut self, count: usize) -> anyhow::Result<Vec<bool>> {
+        let bytes = self.read_bytes(count.div_ceil(8))?;
+        Ok((0..count)
+            .map(|index| bytes[index / 8] & (0x80 >> (index % 8)) != 0)
+            .collect())
+    }
+
+    /// reads a bit vector, which is preceded by a flag which specifies if all
+    /// bits are set
+    fn read_optional_bit_vector(&mut self, count: usize) -> anyhow::Result<Vec<bool>> {
+        if self.read_u8()? != 0 {
+            Ok(vec![true; count])
+        } else {
+            self.read_bit_vector(count)
+        }
+    }
+
+    fn expect(&mut self, expected: u8) -> anyhow::Result<()> {
+        let id = self.read_u8()?;
+        if id != expected {
+            bail!("unexpected property 0x{id:02x} instead of 0x{expected:02x}");
+        }
+        Ok(())
+    }
+
+    fn read_header(&mut self) -> anyhow::Result<Vec<ArchiveEntry>> {
+        let mut streams_info = StreamsInfo::default();
+        let mut id = self.read_u8()?;
+        if id == K_ARCHIVE_PROPERTIES {
+            loop {
+                if self.read_u8()? == K_END {
+                    break;
+                }
+                let size = self.read_count()?;
+                self.read_bytes(size)?;
+            }
+            id = self.read_u8()?;
+        }
+        if id == K_ADDITIONAL_STREAMS_INFO {
+            self.read_streams_info()?;
+            id = self.read_u8()?;
+        }
+        if id == K_MAIN_STREAMS_INFO {
+            streams_info = self.read_streams_info()?;
+            id = self.read_u8()?;
+        }
+        match id {
+            K_FILES_INFO => self.read_files_info(&streams_info),
+            K_END => Ok(Vec::new()),
+            id => bail!("unexpected property 0x{id:02x} in the header"),
+        }
+    }
+
+    fn read_streams_info(&mut self) -> anyhow::Result<StreamsInfo> {
+        let mut streams_info = StreamsInfo::default();
+        loop {
+            match self.read_u8()? {
+                K_END => return Ok(streams_info),
+                K_PACK_INFO => self.read_pack_info(&mut streams_info)?,
+                K_UNPACK_INFO => self.read_unpack_info(&mut streams_info)?,
+                K_SUBSTREAMS_INFO => self.read_substreams_info(&mut streams_info)?,
+                id => bail!("unexpected property 0x{id:02x} in the streams info"),
+            }
+        }
+    }
+
+    fn read_pack_info(&mut self, streams_info: &mut StreamsInfo) -> anyhow::Result<()> {
+        streams_info.pack_position = self.read_number()?;
+        let count = self.read_count()?;
+        loop {
+            match self.read_u8()? {
+                K_END => return Ok(()),
+                K_SIZE => {
+                    streams_info.pack_sizes = (0..count)
+                        .map(|_| self.read_number())
+                        .collect::<anyhow::Result<_>>()?;
+                }
+                K_CRC => self.read_digests(count)?,
+                id => bail!("unexpected property 0x{id:02x} in the pack info"),
+            }
+        }
+    }
+
+    fn read_digests(&mut self, count: usize) -> anyhow::Result<()> {
+        for is_defined in self.read_optional_bit_vector(count)? {
+            if is_defined {
+                self.read_u32()?;
+            }
+        }
+        Ok(())
+    }
+
+    fn read_unpack_info(&mut self, streams_info: &mut StreamsInfo) -> anyhow::Result<()> {
+        self.expect(K_FOLDER)?;
+        let count = self.read_count()?;
+        if self.read_u8()? != 0 {
+            bail!("external folders are not supported");
+        }
+        streams_info.folders = (0..count)
+            .map(|_| self.read_folder())
+            .collect::<anyhow::Result<_>>()?;
+
+        self.expect(K_CODERS_UNPACK_SIZE)?;
+        for folder in streams_info.folders.iter_mut() {
+            for size in folder.unpack_sizes.iter_mut() {
+                *size = self.read_number()?;
+            }
+        }
+        loop {
+            match self.read_u8()? {
+                K_END => return Ok(()),
+                K_CRC => self.read_digests(count)?,
+                id => bail!("unexpected property 0x{id:02x} in the unpack info"),
+            }
+        }
+    }
+
+    fn read_folder(&mut self) -> anyhow::Result<Folder> {
+        let mut folder = Folder::default();
+        let mut in_streams = 0;
+        let mut out_streams = 0;
+        for _ in 0..self.read_count()? {
+            let flags = self.read_u8()?;
+            let id = self.read_bytes(usize::from(flags & 0x0f))?.to_vec();
+            if flags & 0x10 != 0 {
+                in_streams += self.read_number()?;
+                out_streams += self.read_number()?;
+            } else {
+                in_streams += 1;
+                out_streams += 1;
+            }
+            let properties = if flags & 0x20 != 0 {
+                let size = self.read_count()?;
+                self.read_bytes(size)?.to_vec()
+            } else {
+                Vec::new()
+            };
+            folder.coders.push(Coder { id, properties });
+        }
+        if out_streams == 0 || out_streams > self.data.len() as u64 {
+            bail!("invalid number of streams in folder");
+        }
+        folder.unpack_sizes = vec![0; out_streams as usize];
+
+        for _ in 0..out_streams - 1 {
+            self.read_number()?;
+            folder.bound_out_streams.push(self.read_number()?);
+        }
+        let packed_streams = in_streams.saturating_sub(out_streams - 1);
+        if packed_streams > 1 {
+            for _ in 0..packed_streams {
+                self.read_number()?;
+            }
+        }
+        Ok(folder)
+    }
+
+    fn read_substreams_info(&mut self, streams_info: &mut StreamsInfo) -> anyhow::Result<()> {
+        let mut substreams = vec![1; streams_info.folders.len()];
+        let mut id = self.read_u8()?;
+        if id == K_NUM_UNPACK_STREAM {
+            for count in substreams.iter_mut() {
+                *count = self.read_count()?;
+            }
+            id = self.read_u8()?;
+        }
+
+        // the size of the last substream of a folder is not stored, but is
+        // the remaining size of the folder
+        let has_sizes = id == K_SIZE;
+        for (folder, count) in streams_info.folders.iter().zip(&substreams) {
+            if *count == 0 {
+                continue;
+            }
+            let mut remaining = folder.unpack_size();
+            for _ in 1..*count {
+                let size = if has_sizes { self.read_number()? } else { 0 };
+                streams_info.substream_sizes.push(size);
+                remaining = remaining.saturating_sub(size);
+            }
+            streams_info.substream_sizes.push(remaining);
+        }
+        if has_sizes {
+            id = self.read_u8()?;
+        }
+
+        loop {
+            match id {
+                K_END => return Ok(()),
+                K_CRC => {
+                    // digests are stored for all substreams, except for those
+                    // of folders with a single substream, whose digest is the
+                    // digest of the folder
+                    let count = substreams.iter().filter(|count| **count != 1).sum();
+                    self.read_digests(count)?;
+                }
+                id => bail!("unexpected property 0x{id:02x} in the substreams info"),
+            }
+            id = self.read_u8()?;
+        }
+    }
+
+    fn read_files_info(&mut self, streams_info: &StreamsInfo) -> anyhow::Result<Vec<ArchiveEntry>> {
+        let count = self.read_count()?;
+        let mut entries: Vec<_> = (0..count).map(|_| ArchiveEntry::default()).collect();
+        let mut empty_streams = vec![false; count];
+        loop {
+            let id = self.read_u8()?;
+            if id == K_END {
+                break;
+            }
+            let size = self.read_count()?;
+            let mut property = Reader::new(self.read_bytes(size)?);
+            match id {
+                K_EMPTY_STREAM => empty_streams = property.read_bit_vector(count)?,
+                K_EMPTY_FILE => (),
+                K_NAME => {
+                    if property.read_u8()? != 0 {
+                        bail!("external file names are not supported");
+                    }
+                    for entry in entries.iter_mut() {
+                        let mut name = Vec::new();
+                        loop {
+                            match u16::from_le_bytes(property.read_bytes(2)?.try_into()?) {
+                                0 => break,
+                                c => name.push(c),
+                            }
+                        }
+                        entry.path = String::from_utf16_lossy(&name);
+                    }
+                }
+                K_CTIME | K_ATIME | K_MTIME => {
+                    let is_defined = property.read_optional_bit_vector(count)?;
+                    if property.read_u8()? != 0 {
+                        bail!("external timestamps are not supported");
+                    }
+                    for (entry, is_defined) in entries.iter_mut().zip(is_defined) {
+                        if !is_defined {
+                            continue;
+                        }
+                        let time = filetime(property.read_u64()?);
+                        match id {
+                            K_CTIME => entry.created = time,
+                            K_ATIME => entry.accessed = time,
+                            _ => entry.modified = time,
+                        }
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        // the files which are not empty are stored in the substreams, in the
+        // order of the files
+        let mut sizes = streams_info.substream_sizes.iter();
+        for (entry, is_empty) in entries.iter_mut().zip(empty_streams) {
+            if !is_empty {
+                entry.size = sizes.next().copied().unwrap_or_default();
+            }
+        }
+        Ok(entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[test]
+    fn read_numbers() {
+        let mut reader = Reader::new(&[
+            0x7f, // one byte
+            0x81, 0x23, // two bytes
+            0xc0, 0x34, 0x12, // three bytes
+            0xff, 1, 2, 3, 4, 5, 6, 7, 8, // nine bytes
+        ]);
+        assert_eq!(reader.read_number().unwrap(), 0x7f);
+        assert_eq!(reader.read_number().unwrap(), 0x123);
+        assert_eq!(reader.read_number().unwrap(), 0x1234);
+        assert_eq!(reader.read_number().unwrap(), 0x0807060504030201);
+        assert!(reader.read_number().is_err());
+    }
+
+    #[test]
+    fn read_bit_vectors() {
+        let mut reader = Reader::new(&[0b1010_0000, 0b1000_0000, 0x01]);
+        assert_eq!(
+            reader.read_bit_vector(9).unwrap(),
+            vec![true, false, true, false, false, false, false, false, true]
+        );
+        assert_eq!(reader.read_optional_bit_vector(3).unwrap(), vec![true; 3]);
+    }
+
+    #[test]
+    fn reject_invalid_header_offset() {
+        let mut file = b"7z\xbc\xaf\x27\x1c\x00\x04\x00\x00\x00\x00".to_vec();
+        file.extend(u64::MAX.to_le_bytes());
+        file.extend(4u64.to_le_bytes());
+        file.extend([0; 4]);
+        assert!(SevenZipArchive::open(Cursor::new(file)).is_err());
+    }
+
+    #[test]
+    fn reject_header_larger_than_announced() {
+        let mut packed = Vec::new();
+        lzma_rs::lzma2_compress(&mut &[0x01; 100][..], &mut packed).unwrap();
+        let mut file = vec![0; SIGNATURE_HEADER_SIZE as usize];
+        file.extend(&packed);
+
+        let mut streams_info = StreamsInfo {
+            pack_sizes: vec![packed.len() as u64],
+            folders: vec![Folder {
+                coders: vec![Coder {
+                    id: CODER_LZMA2.to_vec(),
+                    properties: Vec::new(),
+                }],
+                unpack_sizes: vec![100],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let header = decode_header(&mut Cursor::new(&file), &streams_info).unwrap();
+        assert_eq!(header, vec![0x01; 100]);
+
+        streams_info.folders[0].unpack_sizes = vec![4];
+        assert!(decode_header(&mut Cursor::new(&file), &streams_info).is_err());
+
+        streams_info.pack_position = u64::MAX;
+        assert!(decode_header(&mut Cursor::new(&file), &streams_info).is_err());
+    }
+}
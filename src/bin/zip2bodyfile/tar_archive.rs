@@ -0,0 +1,286 @@
+use std::io::Read;
+
+use anyhow::{anyhow, bail};
+
+use crate::container::ArchiveEntry;
+
+const BLOCK_SIZE: usize = 512;
+
+const REGULAR_FILE: u8 = b'0';
+const GNU_LONG_NAME: u8 = b'L';
+const GNU_LONG_LINK_NAME: u8 = b'K';
+const PAX_HEADER: u8 = b'x';
+const PAX_GLOBAL_HEADER: u8 = b'g';
+
+/// the magic and version of the headers written by GNU tar, which store the
+/// access and change times where POSIX headers store the name prefix
+const GNU_MAGIC: &[u8] = b"ustar  \0";
+
+/// extended headers and long names are read into memory, so their size is
+/// limited
+const MAX_EXTENSION_SIZE: u64 = 1024 * 1024;
+
+/// a minimal reader of tar archives (POSIX ustar, pax and GNU), which reads
+/// the headers of the entries and skips their contents
+pub struct TarArchive<R: Read> {
+    reader: R,
+    is_finished: bool,
+}
+
+/// values of extension headers, which override the values of the header of
+/// the next entry
+#[derive(Default)]
+struct Extensions {
+    path: Option<String>,
+    size: Option<u64>,
+    modified: Option<i64>,
+    accessed: Option<i64>,
+    changed: Option<i64>,
+}
+
+impl<R: Read> TarArchive<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            is_finished: false,
+        }
+    }
+
+    fn next_entry(&mut self) -> anyhow::Result<Option<ArchiveEntry>> {
+        let mut extensions = Extensions::default();
+        loop {
+            let mut header = [0; BLOCK_SIZE];
+            if !self.read_block(&mut header)? || header.iter().all(|b| *b == 0) {
+                return Ok(None);
+            }
+            verify_checksum(&header)?;
+
+            let size = parse_number(&header[124..136])?;
+            match header[156] {
+                GNU_LONG_NAME => {
+                    extensions.path = Some(c_string(&self.read_extension(size)?));
+                }
+                PAX_HEADER => {
+                    let records = self.read_extension(size)?;
+                    extensions.parse_pax_records(&records)?;
+                }
+                GNU_LONG_LINK_NAME | PAX_GLOBAL_HEADER => {
+                    self.skip(size)?;
+                }
+                type_flag => {
+                    let is_gnu = &header[257..265] == GNU_MAGIC;
+                    let path = extensions.path.take().unwrap_or_else(|| {
+                        let name = c_string(&header[0..100]);
+                        let prefix = c_string(&header[345..500]);
+                        if is_gnu || prefix.is_empty() {
+                            name
+                        } else {
+                            format!("{prefix}/{name}")
+                        }
+                    });
+                    let (accessed, changed) = if is_gnu {
+                        (
+                            parse_optional_time(&header[345..357])?,
+                            parse_optional_time(&header[357..369])?,
+                        )
+                    } else {
+                        (None, None)
+                    };
+                    let entry = ArchiveEntry {
+                        path,
+                        size: extensions.size.unwrap_or(size),
+                        modified: extensions
+                            .modified
+                            .or(Some(parse_number(&header[136..148])? as i64)),
+                        accessed: extensions.accessed.or(accessed),
+                        changed: extensions.changed.or(changed),
+                        created: None,
+                    };
+
+                    // only regular files have contents, whose size may be
+                    // overridden by a pax header
+                    if type_flag == REGULAR_FILE || type_flag == 0 {
+                        self.skip(entry.size)?;
+                    } else {
+                        self.skip(size)?;
+                    }
+                    return Ok(Some(entry));
+                }
+            }
+        }
+    }
+
+    /// reads a block, or returns `false` at the end of the archive
+    fn read_block(&mut self, block: &mut [u8; BLOCK_SIZE]) -> anyhow::Result<bool> {
+        let mut count = 0;
+        while count < BLOCK_SIZE {
+            match self.reader.read(&mut block[count..])? {
+                0 if count == 0 => return Ok(false),
+                0 => bail!("the tar archive is truncated"),
+                n => count += n,
+            }
+        }
+        Ok(true)
+    }
+
+    fn read_extension(&mut self, size: u64) -> anyhow::Result<Vec<u8>> {
+        if size > MAX_EXTENSION_SIZE {
+            bail!("extension header of {size} bytes is too large");
+        }
+        let mut data = vec![0; padded(size)? as usize];
+        self.reader.read_exact(&mut data)?;
+        data.truncate(size as usize);
+        Ok(data)
+    }
+
+    fn skip(&mut self, size: u64) -> anyhow::Result<()> {
+        let size = padded(size)?;
+        let skipped = std::io::copy(&mut (&mut self.reader).take(size), &mut std::io::sink())?;
+        if skipped != size {
+            bail!("the tar archive is truncated");
+        }
+        Ok(())
+    }
+}
+
+impl<R: Read> Iterator for TarArchive<R> {
+    type Item = anyhow::Result<ArchiveEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.is_finished {
+            return None;
+        }
+        let entry = self.next_entry();
+        if !matches!(entry, Ok(Some(_))) {
+            self.is_finished = true;
+        }
+        entry.transpose()
+    }
+}
+
+impl Extensions {
+    /// parses the records of a pax header, which have the form
+    /// `"<length> <key>=<value>\n"`
+    fn parse_pax_records(&mut self, mut records: &[u8]) -> anyhow::Result<()> {
+        while !records.is_empty() {
+            let Some(space) = records.iter().position(|b| *b == b' ') else {
+                bail!("invalid pax record");
+            };
+            let length: usize = String::from_utf8_lossy(&records[..space]).parse()?;
+            let Some(record) = records.get(space + 1..length) else {
+                bail!("invalid length of pax record");
+            };
+            let record = String::from_utf8_lossy(record);
+            if let Some((key, value)) = record.trim_end_matches('\n').split_once('=') {
+                match key {
+                    "path" => self.path = Some(value.to_owned()),
+                    "size" => self.size = Some(value.parse()?),
+                    "mtime" => self.modified = Some(parse_pax_time(value)?),
+                    "atime" => self.accessed = Some(parse_pax_time(value)?),
+                    "ctime" => self.changed = Some(parse_pax_time(value)?),
+                    _ => (),
+                }
+            }
+            records = &records[length..];
+        }
+        Ok(())
+    }
+}
+
+/// pax timestamps are decimal numbers of seconds, which may have a fraction
+fn parse_pax_time(value: &str) -> anyhow::Result<i64> {
+    Ok(value.parse::<f64>()?.floor() as i64)
+}
+
+/// numeric fields are octal numbers, or big-endian binary numbers if the
+/// highest bit of the first byte is set
+fn parse_number(field: &[u8]) -> anyhow::Result<u64> {
+    if field[0] & 0x80 != 0 {
+        return Ok(field[1..]
+            .iter()
+            .fold(u64::from(field[0] & 0x7f), |n, b| (n << 8) | u64::from(*b)));
+    }
+    let text = String::from_utf8_lossy(field);
+    let text = text.trim_matches(|c| c == '\0' || c == ' ');
+    if text.is_empty() {
+        return Ok(0);
+    }
+    match u64::from_str_radix(text, 8) {
+        Ok(n) => Ok(n),
+        Err(why) => bail!("invalid number '{text}' in tar header: {why}"),
+    }
+}
+
+fn parse_optional_time(field: &[u8]) -> anyhow::Result<Option<i64>> {
+    match parse_number(field)? {
+        0 => Ok(None),
+        time => Ok(Some(time as i64)),
+    }
+}
+
+/// the checksum is the sum of all bytes of the header, with the checksum
+/// field itself filled with spaces
+fn verify_checksum(header: &[u8; BLOCK_SIZE]) -> anyhow::Result<()> {
+    let expected = parse_number(&header[148..156])?;
+    let checksum: u64 = header
+        .iter()
+        .enumerate()
+        .map(|(index, b)| match index {
+            148..=155 => u64::from(b' '),
+            _ => u64::from(*b),
+        })
+        .sum();
+    if checksum != expected {
+        bail!("invalid checksum of tar header, this is probably not a tar archive");
+    }
+    Ok(())
+}
+
+fn c_string(data: &[u8]) -> String {
+    let end = data.iter().position(|b| *b == 0).unwrap_or(data.len());
+    String::from_utf8_lossy(&data[..end]).into_owned()
+}
+
+fn padded(size: u64) -> anyhow::Result<u64> {
+    size.div_ceil(BLOCK_SIZE as u64)
+        .checked_mul(BLOCK_SIZE as u64)
+        .ok_or_else(|| anyhow!("invalid size of {size} bytes in the tar archive"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{padded, parse_number, Extensions};
+
+    #[test]
+    fn pad_sizes() {
+        assert_eq!(padded(0).unwrap(), 0);
+        assert_eq!(padded(1).unwrap(), 512);
+        assert_eq!(padded(512).unwrap(), 512);
+        assert!(padded(u64::MAX).is_err());
+    }
+
+    #[test]
+    fn parse_numbers() {
+        assert_eq!(parse_number(b"00000001750\0").unwrap(), 1000);
+        assert_eq!(parse_number(b"     1750 \0").unwrap(), 1000);
+        assert_eq!(
+            parse_number(&[0x80, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x03, 0xe8]).unwrap(),
+            1000
+        );
+        assert!(parse_number(b"0000000017a0").is_err());
+    }
+
+    #[test]
+    fn parse_pax_records() {
+        let mut extensions = Extensions::default();
+        extensions
+            .parse_pax_records(
+                b"30 mtime=1709194030.123456789\n20 atime=1709194031\n25 path=a/very/long/name\n",
+            )
+            .unwrap();
+        assert_eq!(extensions.modified, Some(1709194030));
+        assert_eq!(extensions.accessed, Some(1709194031));
+        assert_eq!(extensions.path.as_deref(), Some("a/very/long/name"));
+        assert!(extensions.changed.is_none());
+    }
+}
@@ -0,0 +1,482 @@
+use std::fs::OpenOptions;
+use std::io::{Error, ErrorKind, Write};
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+
+use clap::ValueHint;
+use hmac::{Hmac, Mac};
+use serde_json::{Map, Value};
+use sha2::Sha256;
+
+/// size of a newly generated key, in bytes
+const KEY_SIZE: usize = 32;
+
+/// keys which are shorter than this are rejected
+const MIN_KEY_SIZE: usize = 16;
+
+/// number of bytes of the HMAC which are used in a pseudonym
+const PSEUDONYM_SIZE: usize = 6;
+
+/// values which disclose nothing about the investigated organisation, and
+/// which are useful for the analysis, e.g. to distinguish service logons
+const WELL_KNOWN_VALUES: &[&str] = &[
+    "",
+    "-",
+    "system",
+    "local service",
+    "network service",
+    "anonymous logon",
+    "nt authority",
+    "public",
+    "default",
+    "default user",
+    "all users",
+];
+
+/// directories which contain a directory per user, like `C:\Users\alice`
+const PROFILE_DIRECTORIES: &[&str] = &["users", "home", "documents and settings"];
+
+/// the option `--anonymize`, which lets a tool replace user names, host names
+/// and IP addresses by pseudonyms
+#[derive(clap::Args, Clone, Debug, Default)]
+pub struct AnonymizeArgs {
+    /// replace user names, host names and IP addresses by pseudonyms, which
+    /// are derived from the key in this file. The same key always yields the
+    /// same pseudonyms. If the file does not exist, it is created with a new
+    /// random key
+    #[clap(long("anonymize"), value_name("KEYFILE"), value_hint=ValueHint::FilePath, display_order(910))]
+    anonymize: Option<PathBuf>,
+}
+
+impl AnonymizeArgs {
+    /// reads the key file, or returns `None` if `--anonymize` has not been specified
+    pub fn anonymizer(&self) -> std::io::Result<Option<Anonymizer>> {
+        self.anonymize
+            .as_deref()
+            .map(Anonymizer::from_keyfile)
+            .transpose()
+    }
+}
+
+/// the kinds of values which are replaced by pseudonyms
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PseudonymKind {
+    User,
+    Host,
+    Ip,
+}
+
+impl PseudonymKind {
+    /// the kind of values which are stored in a field with this name, e.g.
+    /// `TargetUserName` of an EventData element. Returns `None` if the field
+    /// does not contain user names, host names or IP addresses
+    pub fn of_field(name: &str) -> Option<Self> {
+        let lower = name.to_lowercase();
+        let ends_with = |suffixes: &[&str]| suffixes.iter().any(|s| lower.ends_with(s));
+
+        if ends_with(&["username", "accountname"]) || lower.ends_with("user") {
+            Some(Self::User)
+        } else if ends_with(&[
+            "computer",
+            "computername",
+            "workstation",
+            "workstationname",
+            "machinename",
+            "hostname",
+            "servername",
+            "clientname",
+            "domainname",
+        ]) {
+            Some(Self::Host)
+        } else if (lower.ends_with("address") && !lower.ends_with("macaddress"))
+            || lower == "ip"
+            // 'ip' must be a word of its own, like in 'SourceIp', but not in 'GroupMembership'
+            || name.ends_with("Ip")
+            || name.ends_with("IP")
+        {
+            Some(Self::Ip)
+        } else {
+            None
+        }
+    }
+
+    fn prefix(&self) -> &'static str {
+        match self {
+            Self::User => "user",
+            Self::Host => "host",
+            Self::Ip => "ip",
+        }
+    }
+}
+
+/// replaces values by pseudonyms, which are derived from a secret key using
+/// HMAC-SHA256. The same value always yields the same pseudonym, so that
+/// events of the same user can still be correlated, e.g. `user-3f2a9c1b7d04`.
+///
+/// # Example
+/// ```
+/// use dfir_toolkit::common::{Anonymizer, PseudonymKind};
+///
+/// let anonymizer = Anonymizer::new(b"0123456789abcdef");
+/// let alice = anonymizer.pseudonym(PseudonymKind::User, "alice");
+/// assert!(alice.starts_with("user-"));
+/// assert_eq!(anonymizer.pseudonym(PseudonymKind::User, "ALICE"), alice);
+/// assert_eq!(anonymizer.pseudonym(PseudonymKind::User, "SYSTEM"), "SYSTEM");
+/// ```
+pub struct Anonymizer {
+    key: Vec<u8>,
+}
+
+impl Anonymizer {
+    pub fn new(key: &[u8]) -> Self {
+        Self { key: key.to_vec() }
+    }
+
+    /// reads the hex encoded key from `path`. If the file does not exist, it
+    /// is created with a new random key, and is only readable by its owner
+    pub fn from_keyfile(path: &Path) -> std::io::Result<Self> {
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(why) if why.kind() == ErrorKind::NotFound => {
+                return Self::create_keyfile(path);
+            }
+            Err(why) => {
+                return Err(Error::new(
+                    why.kind(),
+                    format!("unable to read key file '{}': {why}", path.display()),
+                ))
+            }
+        };
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            if std::fs::metadata(path)?.permissions().mode() & 0o077 != 0 {
+                log::warn!(
+                    "the key file '{}' can be read by other users",
+                    path.display()
+                );
+            }
+        }
+
+        match decode_hex(content.trim()) {
+            Some(key) if key.len() >= MIN_KEY_SIZE => Ok(Self::new(&key)),
+            _ => Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "invalid key file '{}': expected at least {MIN_KEY_SIZE} hex encoded bytes",
+                    path.display()
+                ),
+            )),
+        }
+    }
+
+    fn create_keyfile(path: &Path) -> std::io::Result<Self> {
+        let mut key = [0u8; KEY_SIZE];
+        getrandom::getrandom(&mut key).map_err(|why| Error::other(why.to_string()))?;
+
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options.open(path).map_err(|why| {
+            Error::new(
+                why.kind(),
+                format!("unable to create key file '{}': {why}", path.display()),
+            )
+        })?;
+        writeln!(file, "{}", encode_hex(&key))?;
+        log::info!("created the key file '{}'", path.display());
+        Ok(Self::new(&key))
+    }
+
+    /// the pseudonym of `value`. Case is ignored for user and host names,
+    /// because Windows ignores it as well. Empty values, loopback addresses
+    /// and well-known names like `SYSTEM` are not replaced
+    pub fn pseudonym(&self, kind: PseudonymKind, value: &str) -> String {
+        let normalized = match kind {
+            PseudonymKind::User | PseudonymKind::Host => value.trim().to_lowercase(),
+            PseudonymKind::Ip => match value.trim().parse::<IpAddr>() {
+                Ok(ip) if ip.is_loopback() || ip.is_unspecified() => return value.to_owned(),
+                Ok(ip) => ip.to_canonical().to_string(),
+                Err(_) => value.trim().to_lowercase(),
+            },
+        };
+        if WELL_KNOWN_VALUES.contains(&normalized.as_str()) {
+            return value.to_owned();
+        }
+
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&self.key).expect("HMAC can take a key of any size");
+        mac.update(kind.prefix().as_bytes());
+        mac.update(b":");
+        mac.update(normalized.as_bytes());
+        let hash = mac.finalize().into_bytes();
+        format!("{}-{}", kind.prefix(), encode_hex(&hash[..PSEUDONYM_SIZE]))
+    }
+
+    /// replaces the user names in profile directories, like `C:\Users\alice`
+    pub fn anonymize_path(&self, path: &str) -> String {
+        let mut result = String::with_capacity(path.len());
+        let mut in_profiles = false;
+        for segment in path.split_inclusive(['/', '\\']) {
+            let name = segment.trim_end_matches(['/', '\\']);
+            if in_profiles && !name.is_empty() {
+                result.push_str(&self.pseudonym(PseudonymKind::User, name));
+                result.push_str(&segment[name.len()..]);
+            } else {
+                result.push_str(segment);
+            }
+            in_profiles = PROFILE_DIRECTORIES.contains(&name.to_lowercase().as_str());
+        }
+        result
+    }
+
+    /// anonymizes a text, which is either a JSON object, like the names
+    /// written by `evtx2bodyfile`, or a path
+    pub fn anonymize_text(&self, text: &str) -> String {
+        if text.starts_with('{') {
+            if let Ok(Value::Object(mut map)) = serde_json::from_str(text) {
+                self.anonymize_map(&mut map);
+                return Value::Object(map).to_string();
+            }
+        }
+        self.anonymize_path(text)
+    }
+
+    /// anonymizes all fields of a JSON value, whose names indicate that they
+    /// contain user names, host names or IP addresses, and all paths
+    pub fn anonymize_value(&self, value: &mut Value) {
+        match value {
+            Value::Object(map) => self.anonymize_map(map),
+            Value::Array(values) => values.iter_mut().for_each(|v| self.anonymize_value(v)),
+            Value::String(s) => *s = self.anonymize_path(s),
+            _ => (),
+        }
+    }
+
+    pub fn anonymize_map(&self, map: &mut Map<String, Value>) {
+        // evtx writes <Data Name="IpAddress">10.0.0.5</Data> as
+        // {"#attributes": {"Name": "IpAddress"}, "#text": "10.0.0.5"}
+        let named_kind = map
+            .get("#attributes")
+            .and_then(|attributes| attributes.get("Name"))
+            .and_then(Value::as_str)
+            .and_then(PseudonymKind::of_field);
+
+        for (key, value) in map.iter_mut() {
+            let kind = if key == "#text" {
+                named_kind
+            } else {
+                PseudonymKind::of_field(key)
+            };
+            match kind {
+                Some(kind) => self.replace(kind, value),
+                None => self.anonymize_value(value),
+            }
+        }
+    }
+
+    fn replace(&self, kind: PseudonymKind, value: &mut Value) {
+        match value {
+            Value::String(s) => *s = self.pseudonym(kind, s),
+            Value::Number(n) => *value = Value::String(self.pseudonym(kind, &n.to_string())),
+            Value::Array(values) => values.iter_mut().for_each(|v| self.replace(kind, v)),
+            Value::Object(map) => self.anonymize_map(map),
+            _ => (),
+        }
+    }
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn decode_hex(text: &str) -> Option<Vec<u8>> {
+    if text.len() % 2 != 0 {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{Anonymizer, PseudonymKind};
+
+    fn anonymizer() -> Anonymizer {
+        Anonymizer::new(b"0123456789abcdef")
+    }
+
+    #[test]
+    fn detect_fields() {
+        for name in [
+            "SubjectUserName",
+            "TargetUserName",
+            "SamAccountName",
+            "User",
+            "TargetOutboundUserName",
+        ] {
+            assert_eq!(
+                PseudonymKind::of_field(name),
+                Some(PseudonymKind::User),
+                "{name}"
+            );
+        }
+        for name in [
+            "Computer",
+            "computer",
+            "WorkstationName",
+            "SubjectDomainName",
+            "DestinationHostname",
+            "TargetServerName",
+        ] {
+            assert_eq!(
+                PseudonymKind::of_field(name),
+                Some(PseudonymKind::Host),
+                "{name}"
+            );
+        }
+        for name in [
+            "IpAddress",
+            "SourceAddress",
+            "DestAddress",
+            "SourceIp",
+            "ClientIP",
+        ] {
+            assert_eq!(
+                PseudonymKind::of_field(name),
+                Some(PseudonymKind::Ip),
+                "{name}"
+            );
+        }
+        for name in [
+            "SubjectUserSid",
+            "LogonType",
+            "GroupMembership",
+            "MacAddress",
+            "ProcessName",
+            "EventID",
+        ] {
+            assert_eq!(PseudonymKind::of_field(name), None, "{name}");
+        }
+    }
+
+    #[test]
+    fn pseudonyms_depend_on_key() {
+        let value = anonymizer().pseudonym(PseudonymKind::Host, "WS01");
+        assert_eq!(value, anonymizer().pseudonym(PseudonymKind::Host, "ws01"));
+        assert_ne!(value, anonymizer().pseudonym(PseudonymKind::User, "WS01"));
+        assert_ne!(
+            value,
+            Anonymizer::new(b"fedcba9876543210").pseudonym(PseudonymKind::Host, "WS01")
+        );
+        assert_eq!(value.len(), "host-".len() + 12);
+    }
+
+    #[test]
+    fn keep_uninteresting_values() {
+        let anonymizer = anonymizer();
+        for value in ["-", "", "127.0.0.1", "::1", "0.0.0.0"] {
+            assert_eq!(anonymizer.pseudonym(PseudonymKind::Ip, value), value);
+        }
+        assert_eq!(
+            anonymizer.pseudonym(PseudonymKind::User, "LOCAL SERVICE"),
+            "LOCAL SERVICE"
+        );
+        assert_eq!(
+            anonymizer.pseudonym(PseudonymKind::Ip, "::ffff:10.0.0.5"),
+            anonymizer.pseudonym(PseudonymKind::Ip, "10.0.0.5")
+        );
+    }
+
+    #[test]
+    fn anonymize_paths() {
+        let anonymizer = anonymizer();
+        let alice = anonymizer.pseudonym(PseudonymKind::User, "alice");
+        assert_eq!(
+            anonymizer.anonymize_path(r"C:\Users\alice\NTUSER.DAT"),
+            format!(r"C:\Users\{alice}\NTUSER.DAT")
+        );
+        assert_eq!(
+            anonymizer.anonymize_path("/home/alice"),
+            format!("/home/{alice}")
+        );
+        assert_eq!(
+            anonymizer.anonymize_path(r"C:\Users\Public\Desktop"),
+            r"C:\Users\Public\Desktop"
+        );
+        assert_eq!(anonymizer.anonymize_path("/etc/passwd"), "/etc/passwd");
+    }
+
+    #[test]
+    fn anonymize_event() {
+        let anonymizer = anonymizer();
+        let mut event = json!({
+            "Event": {
+                "System": {"Computer": "WS01.corp.local", "EventID": 4624},
+                "EventData": {
+                    "TargetUserName": "alice",
+                    "IpAddress": "10.0.0.5",
+                    "LogonType": 10,
+                    "ProcessName": r"C:\Users\alice\evil.exe"
+                },
+                "UserData": {
+                    "Data": [{"#attributes": {"Name": "WorkstationName"}, "#text": "WS02"}]
+                }
+            }
+        });
+        anonymizer.anonymize_value(&mut event);
+
+        let alice = anonymizer.pseudonym(PseudonymKind::User, "alice");
+        assert_eq!(
+            event["Event"]["System"]["Computer"],
+            anonymizer.pseudonym(PseudonymKind::Host, "WS01.corp.local")
+        );
+        assert_eq!(event["Event"]["System"]["EventID"], 4624);
+        assert_eq!(
+            event["Event"]["EventData"]["TargetUserName"],
+            alice.as_str()
+        );
+        assert_eq!(
+            event["Event"]["EventData"]["IpAddress"],
+            anonymizer.pseudonym(PseudonymKind::Ip, "10.0.0.5")
+        );
+        assert_eq!(event["Event"]["EventData"]["LogonType"], 10);
+        assert_eq!(
+            event["Event"]["EventData"]["ProcessName"],
+            format!(r"C:\Users\{alice}\evil.exe")
+        );
+        assert_eq!(
+            event["Event"]["UserData"]["Data"][0]["#text"],
+            anonymizer.pseudonym(PseudonymKind::Host, "WS02")
+        );
+    }
+
+    #[test]
+    fn create_keyfile() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("anonymize.key");
+        let created = Anonymizer::from_keyfile(&path).unwrap();
+        let read = Anonymizer::from_keyfile(&path).unwrap();
+        assert_eq!(created.key, read.key);
+        assert_eq!(created.key.len(), super::KEY_SIZE);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        std::fs::write(&path, "0123").unwrap();
+        assert!(Anonymizer::from_keyfile(&path).is_err());
+    }
+}
@@ -1,20 +1,26 @@
 use duplicate::duplicate_item;
 use getset::{Getters, Setters};
+use serde::{Deserialize, Serialize};
 use std::convert::TryFrom;
 use std::error::Error;
 use std::fmt;
 
-use super::{Accessed, Modified, Changed, Created};
+use super::{Accessed, Changed, Created, Modified};
 
 ///
 /// This struct implements the bodyfile format generated by TSK 3.x
 ///
-#[derive(Debug, Getters, Setters)]
+/// If it is (de)serialized using `serde`, the fields have the names of the
+/// columns of a bodyfile, and missing fields get the values of
+/// [`Bodyfile3Line::new`]
+#[derive(Debug, Clone, PartialEq, Eq, Getters, Setters, Serialize, Deserialize)]
 #[getset(get = "pub with_prefix", set = "pub")]
+#[serde(default)]
 pub struct Bodyfile3Line {
     md5: String,
     name: String,
     inode: String,
+    #[serde(rename = "mode")]
     mode_as_string: String,
     uid: u64,
     gid: u64,
@@ -121,14 +127,28 @@ impl fmt::Display for Bodyfile3Line {
     /// let line = bf.to_string();
     /// assert_eq!(line, "4bad420da66571dac7f1ace995cc55c6|sample.txt|87915-128-1|r/rrwxrwxrwx|1003|500|126378|12341|12342|12343|12344")
     /// ```
+    ///
+    /// Line breaks would split the line, so they are written as `\n` and `\r`.
+    /// Pipes in the name are written as is, because the parser assigns all
+    /// surplus columns to the name. In all other columns, they are written
+    /// as `\x7c`:
+    ///
+    /// ```
+    /// use dfir_toolkit::common::bodyfile::Bodyfile3Line;
+    ///
+    /// let bf = Bodyfile3Line::new()
+    ///             .with_name("ls -l | wc\n")
+    ///             .with_inode("1|2");
+    /// assert_eq!(bf.to_string(), "0|ls -l | wc\\n|1\\x7c2||0|0|0|-1|-1|-1|-1")
+    /// ```
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
             "{}|{}|{}|{}|{}|{}|{}|{}|{}|{}|{}",
-            self.md5,
-            self.name,
-            self.inode,
-            self.mode_as_string,
+            Escaped(&self.md5, true),
+            Escaped(&self.name, false),
+            Escaped(&self.inode, true),
+            Escaped(&self.mode_as_string, true),
             self.uid,
             self.gid,
             self.size,
@@ -140,6 +160,26 @@ impl fmt::Display for Bodyfile3Line {
     }
 }
 
+/// a column of a bodyfile line, which is written without line breaks
+struct Escaped<'s>(&'s str, bool);
+
+impl fmt::Display for Escaped<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self(value, escape_pipes) = *self;
+        let mut rest = value;
+        while let Some(pos) = rest.find(|c| c == '\n' || c == '\r' || (escape_pipes && c == '|')) {
+            f.write_str(&rest[..pos])?;
+            f.write_str(match &rest[pos..pos + 1] {
+                "\n" => "\\n",
+                "\r" => "\\r",
+                _ => "\\x7c",
+            })?;
+            rest = &rest[pos + 1..];
+        }
+        f.write_str(rest)
+    }
+}
+
 #[derive(Debug)]
 pub enum Bodyfile3ParserError {
     /// indicates that number of columns is not valid
@@ -288,6 +328,62 @@ impl fmt::Display for Bodyfile3ParserError {
 
 impl Error for Bodyfile3ParserError {}
 
+impl Bodyfile3ParserError {
+    /// the name of the column which contains the invalid value
+    ///
+    /// # Example
+    /// ```
+    /// use dfir_toolkit::common::bodyfile::Bodyfile3ParserError;
+    ///
+    /// assert_eq!(Bodyfile3ParserError::IllegalATime.column(), Some("atime"));
+    /// assert_eq!(Bodyfile3ParserError::WrongNumberOfColumns.column(), None);
+    /// ```
+    pub fn column(&self) -> Option<&'static str> {
+        self.column_from_end().map(|(name, _)| name)
+    }
+
+    /// the name of the invalid column, and its position counted from the end
+    /// of the line, which is not affected by pipes in the name
+    fn column_from_end(&self) -> Option<(&'static str, usize)> {
+        match self {
+            Self::WrongNumberOfColumns => None,
+            Self::IllegalUid => Some(("uid", 6)),
+            Self::IllegalGid => Some(("gid", 5)),
+            Self::IllegalSize => Some(("size", 4)),
+            Self::IllegalATime => Some(("atime", 3)),
+            Self::IllegalMTime => Some(("mtime", 2)),
+            Self::IllegalCTime => Some(("ctime", 1)),
+            Self::IllegalCRTime => Some(("crtime", 0)),
+        }
+    }
+
+    /// describes the error, including the invalid value taken from `line`,
+    /// which is the line this error has been returned for
+    ///
+    /// # Example
+    /// ```
+    /// use dfir_toolkit::common::bodyfile::{Bodyfile3Line, Bodyfile3ParserError};
+    /// use std::convert::TryFrom;
+    ///
+    /// let line = "0|a|b|1|0|0|X|-1|-1|-1|-1";
+    /// let error = Bodyfile3Line::try_from(line).unwrap_err();
+    /// assert_eq!(error.describe(line), "invalid size 'X'");
+    ///
+    /// let line = "0|a|b|1|0|0";
+    /// let error = Bodyfile3Line::try_from(line).unwrap_err();
+    /// assert_eq!(error.describe(line), "expected 11 columns, but found 6");
+    /// ```
+    pub fn describe(&self, line: &str) -> String {
+        match self.column_from_end() {
+            None => format!("expected 11 columns, but found {}", line.split('|').count()),
+            Some((name, position)) => {
+                let value = line.rsplit('|').nth(position).unwrap_or_default();
+                format!("invalid {name} '{value}'")
+            }
+        }
+    }
+}
+
 impl TryFrom<&str> for Bodyfile3Line {
     type Error = Bodyfile3ParserError;
 
@@ -308,9 +404,8 @@ impl TryFrom<&str> for Bodyfile3Line {
     /// assert_eq!(*bf_line.get_atime(), Accessed::from(6));
     /// assert_eq!(*bf_line.get_mtime(), Modified::from(7));
     /// assert_eq!(*bf_line.get_ctime(), Changed::from(8));
-    /// assert_eq!(*bf_line.get_crtime(), Created::from(9)); 
+    /// assert_eq!(*bf_line.get_crtime(), Created::from(9));
     /// ```
-
     fn try_from(line: &str) -> Result<Self, Self::Error> {
         let parts: Vec<&str> = line.split('|').collect();
         if parts.len() < 11 {
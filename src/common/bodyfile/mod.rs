@@ -1,4 +1,3 @@
-
 //! Quote from <https://wiki.sleuthkit.org/index.php?title=Body_file>:
 //!
 //! > The body file is an intermediate file when creating a timeline of file
@@ -29,39 +28,166 @@
 //! ```
 //! use dfir_toolkit::common::bodyfile::Bodyfile3Line;
 //! use std::convert::TryFrom;
-//! 
+//!
 //! let str_line = "0|/Users/Administrator ($FILE_NAME)|93552-48-2|d/drwxrwxrwx|0|0|92|1577092511|1577092511|1577092511|-1";
 //! let bf_line = Bodyfile3Line::try_from(str_line).unwrap();
 //! assert_eq!(str_line, bf_line.to_string());
 //! ```
-//! 
+//!
 //! # Handling of pipes
-//! Normally, a filename should not contain a pipe symbol (|), but if 
-//! [bodyfile] is being used together with other sources, this may happen. So we 
+//! Normally, a filename should not contain a pipe symbol (|), but if
+//! [bodyfile] is being used together with other sources, this may happen. So we
 //! need to be able to handle this also:
-//! 
+//!
 //! ```
 //! use dfir_toolkit::common::bodyfile::Bodyfile3Line;
 //! use std::convert::TryFrom;
-//! 
+//!
 //! let str_line = "0|command was ls -l | wc |93552-48-2|d/drwxrwxrwx|0|0|92|1577092511|1577092511|1577092511|-1";
 //! let bf_line = Bodyfile3Line::try_from(str_line).unwrap();
 //! assert_eq!(str_line, bf_line.to_string());
 //! ```
-//! 
+//!
+//! Line breaks cannot be part of a bodyfile line, so they are written as `\n`
+//! and `\r`. To read a complete bodyfile, use [BodyfileReader], which reports
+//! the line number and the column of invalid values.
+//!
 pub mod bodyfile3;
 pub use bodyfile3::*;
 
+mod reader;
+pub use reader::*;
+
 mod times;
 pub use times::*;
 
 #[cfg(test)]
 mod tests {
-    use super::Bodyfile3Line;
+    use rand::distributions::{Distribution, Slice};
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::{Bodyfile3Line, BodyfileReader};
 
     #[test]
     fn sample1() {
         let bf = Bodyfile3Line::new();
         assert_eq!(bf.get_md5(), "0");
     }
+
+    /// creates a random number generator, whose seed is printed so that a
+    /// failed test can be reproduced with `DFIRTK_TEST_SEED`
+    fn rng() -> StdRng {
+        let seed = std::env::var("DFIRTK_TEST_SEED")
+            .ok()
+            .and_then(|seed| seed.parse().ok())
+            .unwrap_or_else(rand::random);
+        println!("DFIRTK_TEST_SEED={seed}");
+        StdRng::seed_from_u64(seed)
+    }
+
+    /// creates a string which contains all the characters which are special
+    /// in a bodyfile, except line breaks
+    fn random_string<R: Rng>(rng: &mut R, pipes: bool) -> String {
+        let mut chars = vec!['a', 'Z', '0', ' ', '\\', '/', '-', '#', 'ä', '€', '\t', '"'];
+        if pipes {
+            chars.push('|');
+        }
+        let chars = Slice::new(&chars).unwrap();
+        let len = rng.gen_range(0..20);
+        chars.sample_iter(rng).take(len).collect()
+    }
+
+    fn random_time<R: Rng>(rng: &mut R) -> i64 {
+        if rng.gen_bool(0.2) {
+            -1
+        } else {
+            rng.gen_range(0..i64::MAX)
+        }
+    }
+
+    fn random_line<R: Rng>(rng: &mut R) -> Bodyfile3Line {
+        Bodyfile3Line::new()
+            // a line beginning with `#` would be a comment
+            .with_owned_md5(format!("{:032x}", rng.gen::<u128>()))
+            .with_owned_name(random_string(rng, true))
+            .with_owned_inode(random_string(rng, false))
+            .with_owned_mode(random_string(rng, false))
+            .with_uid(rng.gen())
+            .with_gid(rng.gen())
+            .with_size(rng.gen())
+            .with_atime(random_time(rng).into())
+            .with_mtime(random_time(rng).into())
+            .with_ctime(random_time(rng).into())
+            .with_crtime(random_time(rng).into())
+    }
+
+    #[test]
+    fn read_written_lines() {
+        let mut rng = rng();
+        for _ in 0..1000 {
+            let line = random_line(&mut rng);
+            let written = line.to_string();
+            let read = Bodyfile3Line::try_from(written.as_str())
+                .unwrap_or_else(|why| panic!("{}: {written:?}", why.describe(&written)));
+            assert_eq!(read, line);
+        }
+    }
+
+    #[test]
+    fn read_written_bodyfile() {
+        let mut rng = rng();
+        let lines: Vec<_> = (0..1000).map(|_| random_line(&mut rng)).collect();
+        let bodyfile: String = lines.iter().map(|line| format!("{line}\n")).collect();
+        let read: Vec<_> = BodyfileReader::new(bodyfile.as_bytes())
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(read, lines);
+    }
+
+    #[test]
+    fn line_breaks_do_not_split_lines() {
+        let mut rng = rng();
+        for _ in 0..1000 {
+            let line = random_line(&mut rng)
+                .with_owned_name(format!(
+                    "{}\r\n{}",
+                    random_string(&mut rng, true),
+                    random_string(&mut rng, true)
+                ))
+                .with_owned_inode(format!("{}|\n", random_string(&mut rng, false)));
+            let written = line.to_string();
+            assert!(!written.contains('\n'));
+
+            // the line is read with the escaped columns, which are written
+            // in the same way again
+            let read = Bodyfile3Line::try_from(written.as_str()).unwrap();
+            assert_eq!(read.get_name(), &line.get_name().replace("\r\n", "\\r\\n"));
+            assert_eq!(
+                read.get_inode(),
+                &line.get_inode().replace("|\n", "\\x7c\\n")
+            );
+            assert_eq!(read.to_string(), written);
+            assert_eq!(read.get_atime(), line.get_atime());
+        }
+    }
+
+    #[cfg(feature = "serde_json")]
+    #[test]
+    fn serialize_to_json() {
+        let mut rng = rng();
+        for _ in 0..1000 {
+            let line = random_line(&mut rng);
+            let json = serde_json::to_string(&line).unwrap();
+            assert_eq!(serde_json::from_str::<Bodyfile3Line>(&json).unwrap(), line);
+        }
+
+        let line: Bodyfile3Line =
+            serde_json::from_str(r#"{"name": "a", "mode": "r/rrw-r--r--", "mtime": 1}"#).unwrap();
+        assert_eq!(line.to_string(), "0|a|0|r/rrw-r--r--|0|0|0|-1|1|-1|-1");
+        assert_eq!(
+            serde_json::to_value(&line).unwrap()["atime"],
+            serde_json::json!(-1)
+        );
+    }
 }
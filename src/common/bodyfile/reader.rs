@@ -0,0 +1,136 @@
+use std::error::Error;
+use std::fmt;
+use std::io::BufRead;
+
+use super::{Bodyfile3Line, Bodyfile3ParserError};
+
+/// reads a bodyfile line by line. Comments and empty lines are skipped.
+///
+/// # Example
+/// ```
+/// use dfir_toolkit::common::bodyfile::BodyfileReader;
+///
+/// let bodyfile = "# comment\n0|a|1|r/rrw-r--r--|0|0|0|-1|1|-1|-1\r\n0|b|1|r/rrw-r--r--|X|0|0|-1|1|-1|-1\n";
+/// let mut reader = BodyfileReader::new(bodyfile.as_bytes());
+/// assert_eq!(reader.next().unwrap().unwrap().get_name(), "a");
+///
+/// let error = reader.next().unwrap().unwrap_err();
+/// assert_eq!(error.line_number(), 3);
+/// assert_eq!(error.to_string(), "invalid bodyfile line 3: invalid uid 'X'");
+/// assert!(reader.next().is_none());
+/// ```
+pub struct BodyfileReader<R: BufRead> {
+    reader: R,
+    line_number: usize,
+}
+
+impl<R: BufRead> BodyfileReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            line_number: 0,
+        }
+    }
+
+    /// the number of the line which has been read last, starting with 1
+    pub fn line_number(&self) -> usize {
+        self.line_number
+    }
+
+    /// returns the next line without its line break and without parsing
+    /// it, e.g. for tools which also accept other formats than bodyfile
+    /// lines. Comments and empty lines are skipped.
+    pub fn next_line(&mut self) -> Option<Result<String, BodyfileReaderError>> {
+        let mut line = String::new();
+        loop {
+            line.clear();
+            self.line_number += 1;
+            match self.reader.read_line(&mut line) {
+                Ok(0) => return None,
+                Ok(_) => (),
+                Err(error) => {
+                    return Some(Err(BodyfileReaderError::Io {
+                        line_number: self.line_number,
+                        error,
+                    }))
+                }
+            }
+
+            let trimmed = line.strip_suffix('\n').unwrap_or(&line);
+            let trimmed = trimmed.strip_suffix('\r').unwrap_or(trimmed);
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+            line.truncate(trimmed.len());
+            return Some(Ok(line));
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for BodyfileReader<R> {
+    type Item = Result<Bodyfile3Line, BodyfileReaderError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let line = match self.next_line()? {
+            Ok(line) => line,
+            Err(why) => return Some(Err(why)),
+        };
+        Some(Bodyfile3Line::try_from(line.as_str()).map_err(|error| {
+            BodyfileReaderError::InvalidLine {
+                line_number: self.line_number,
+                reason: error.describe(&line),
+                error,
+            }
+        }))
+    }
+}
+
+#[derive(Debug)]
+pub enum BodyfileReaderError {
+    /// the line could not be read
+    Io {
+        line_number: usize,
+        error: std::io::Error,
+    },
+
+    /// the line is not a valid bodyfile line
+    InvalidLine {
+        line_number: usize,
+        reason: String,
+        error: Bodyfile3ParserError,
+    },
+}
+
+impl BodyfileReaderError {
+    /// the number of the line which caused the error, starting with 1
+    pub fn line_number(&self) -> usize {
+        match self {
+            Self::Io { line_number, .. } => *line_number,
+            Self::InvalidLine { line_number, .. } => *line_number,
+        }
+    }
+}
+
+impl fmt::Display for BodyfileReaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io { line_number, error } => {
+                write!(f, "unable to read bodyfile line {line_number}: {error}")
+            }
+            Self::InvalidLine {
+                line_number,
+                reason,
+                ..
+            } => write!(f, "invalid bodyfile line {line_number}: {reason}"),
+        }
+    }
+}
+
+impl Error for BodyfileReaderError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io { error, .. } => Some(error),
+            Self::InvalidLine { error, .. } => Some(error),
+        }
+    }
+}
@@ -1,14 +1,20 @@
 use crate::common::bodyfile::Bodyfile3ParserError;
-use std::fmt::Display;
 use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
 
-#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Ord, PartialOrd)]
+// timestamps are serialized like in a bodyfile, i.e. a missing timestamp is `-1`
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize)]
+#[serde(from = "i64", into = "i64")]
 pub struct Accessed(Option<i64>);
-#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Ord, PartialOrd)]
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize)]
+#[serde(from = "i64", into = "i64")]
 pub struct Modified(Option<i64>);
-#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Ord, PartialOrd)]
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize)]
+#[serde(from = "i64", into = "i64")]
 pub struct Changed(Option<i64>);
-#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Ord, PartialOrd)]
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize)]
+#[serde(from = "i64", into = "i64")]
 pub struct Created(Option<i64>);
 
 pub trait BehavesLikeI64: From<i64> + From<Option<i64>> {
@@ -41,6 +47,12 @@ macro_rules! behaves_like_i64 {
             }
         }
 
+        impl From<$t> for i64 {
+            fn from(v: $t) -> Self {
+                v.0.unwrap_or(-1)
+            }
+        }
+
         impl From<Option<i64>> for $t {
             fn from(v: Option<i64>) -> Self {
                 Self(v)
@@ -49,24 +61,28 @@ macro_rules! behaves_like_i64 {
 
         impl From<NaiveDateTime> for $t {
             fn from(v: NaiveDateTime) -> Self {
-                Self(Some(DateTime::<Utc>::from_naive_utc_and_offset(v, Utc).timestamp()))
+                Self(Some(
+                    DateTime::<Utc>::from_naive_utc_and_offset(v, Utc).timestamp(),
+                ))
             }
         }
 
         impl From<&NaiveDateTime> for $t {
             fn from(v: &NaiveDateTime) -> Self {
-                Self(Some(DateTime::<Utc>::from_naive_utc_and_offset(*v, Utc).timestamp()))
+                Self(Some(
+                    DateTime::<Utc>::from_naive_utc_and_offset(*v, Utc).timestamp(),
+                ))
             }
         }
 
-        impl From<DateTime::<Utc>> for $t {
-            fn from(v: DateTime::<Utc>) -> Self {
+        impl From<DateTime<Utc>> for $t {
+            fn from(v: DateTime<Utc>) -> Self {
                 Self(Some(v.timestamp()))
             }
         }
 
-        impl From<&DateTime::<Utc>> for $t {
-            fn from(v: &DateTime::<Utc>) -> Self {
+        impl From<&DateTime<Utc>> for $t {
+            fn from(v: &DateTime<Utc>) -> Self {
                 Self(Some(v.timestamp()))
             }
         }
@@ -0,0 +1,279 @@
+use std::ffi::OsString;
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Error, ErrorKind, IsTerminal, Stdout, Write};
+use std::path::{Path, PathBuf};
+
+use clap::ValueHint;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+
+/// the options `--output` and `--force`, which let a tool write into a file
+/// instead of stdout
+#[derive(clap::Args, Clone, Debug, Default)]
+pub struct OutputArgs {
+    /// write the output into this file instead of stdout. The file is only
+    /// created if all output has been written successfully. If its name ends
+    /// with '.gz', the output is compressed using gzip
+    #[clap(short('o'), long("output"), value_hint=ValueHint::FilePath, display_order(900))]
+    output: Option<PathBuf>,
+
+    /// replace the output file if it already exists
+    #[clap(long("force"), requires("output"), display_order(901))]
+    force: bool,
+}
+
+impl OutputArgs {
+    /// the file which has been specified with `--output`
+    pub fn path(&self) -> Option<&Path> {
+        self.output.as_deref()
+    }
+
+    /// opens the output, which must be committed using [FileOutput::commit]
+    pub fn create(&self) -> std::io::Result<FileOutput> {
+        match &self.output {
+            None => Ok(FileOutput::stdout()),
+            Some(path) => FileOutput::create(path, self.force),
+        }
+    }
+}
+
+/// writes either to stdout or atomically into a file: all data is written
+/// into a temporary file in the same directory, which replaces the output
+/// file in [FileOutput::commit]. If the output is dropped without being
+/// committed, e.g. because of an error, the temporary file is removed, so
+/// that an aborted run never leaves a truncated output file behind. The
+/// temporary file is only created when the first data is written, so that
+/// nothing is left behind if the process exits before.
+///
+/// # Example
+/// ```
+/// use dfir_toolkit::common::FileOutput;
+/// use std::io::Write;
+///
+/// let dir = tempfile::tempdir().unwrap();
+/// let path = dir.path().join("timeline.csv");
+///
+/// let mut output = FileOutput::create(&path, false).unwrap();
+/// writeln!(output, "some data").unwrap();
+/// assert!(!path.exists());
+///
+/// output.commit().unwrap();
+/// assert_eq!(std::fs::read_to_string(&path).unwrap(), "some data\n");
+///
+/// // an existing file is only replaced if this is forced
+/// assert!(FileOutput::create(&path, false).is_err());
+/// drop(FileOutput::create(&path, true).unwrap());
+/// assert_eq!(std::fs::read_to_string(&path).unwrap(), "some data\n");
+/// ```
+pub struct FileOutput {
+    /// `None` until the temporary file has been created
+    stream: Option<Stream>,
+    pending: Option<PendingFile>,
+}
+
+enum Stream {
+    Stdout(Stdout),
+    File(BufWriter<File>),
+    Gzip(GzEncoder<BufWriter<File>>),
+}
+
+/// a temporary file, which is removed unless it has been renamed
+struct PendingFile {
+    tmp_path: PathBuf,
+    path: PathBuf,
+    force: bool,
+    created: bool,
+    committed: bool,
+}
+
+impl PendingFile {
+    fn create(&mut self) -> std::io::Result<Stream> {
+        let file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&self.tmp_path)
+            .map_err(|why| {
+                Error::new(
+                    why.kind(),
+                    format!("unable to create '{}': {why}", self.tmp_path.display()),
+                )
+            })?;
+        self.created = true;
+
+        let writer = BufWriter::new(file);
+        if self.path.extension().is_some_and(|ext| ext == "gz") {
+            Ok(Stream::Gzip(GzEncoder::new(writer, Compression::default())))
+        } else {
+            Ok(Stream::File(writer))
+        }
+    }
+}
+
+impl Drop for PendingFile {
+    fn drop(&mut self) {
+        if self.created && !self.committed {
+            let _ = std::fs::remove_file(&self.tmp_path);
+        }
+    }
+}
+
+impl FileOutput {
+    pub fn stdout() -> Self {
+        Self {
+            stream: Some(Stream::Stdout(std::io::stdout())),
+            pending: None,
+        }
+    }
+
+    /// creates a temporary file for `path`. Fails if `path` already exists,
+    /// unless `force` is set. If the name of `path` ends with `.gz`, the
+    /// output is compressed using gzip
+    pub fn create(path: &Path, force: bool) -> std::io::Result<Self> {
+        if !force && path.exists() {
+            return Err(already_exists(path));
+        }
+
+        let file_name = path.file_name().ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("invalid output file name '{}'", path.display()),
+            )
+        })?;
+
+        // the temporary file must be in the same directory, because renaming
+        // is only atomic within the same file system
+        let mut tmp_name = OsString::from(".");
+        tmp_name.push(file_name);
+        tmp_name.push(format!(".{}.tmp", std::process::id()));
+        let pending = PendingFile {
+            tmp_path: path.with_file_name(tmp_name),
+            path: path.to_owned(),
+            force,
+            created: false,
+            committed: false,
+        };
+        Ok(Self {
+            stream: None,
+            pending: Some(pending),
+        })
+    }
+
+    fn stream(&mut self) -> std::io::Result<&mut Stream> {
+        if self.stream.is_none() {
+            let pending = self.pending.as_mut().expect("missing temporary file");
+            self.stream = Some(pending.create()?);
+        }
+        Ok(self.stream.as_mut().unwrap())
+    }
+
+    /// returns `true` if the output is written into a file
+    pub fn is_file(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// returns `true` if the output is written to a terminal, which means
+    /// that it may be colored
+    pub fn is_terminal(&self) -> bool {
+        matches!(&self.stream, Some(Stream::Stdout(stdout)) if stdout.is_terminal())
+    }
+
+    /// writes all remaining data and replaces the output file with the
+    /// temporary file
+    pub fn commit(mut self) -> std::io::Result<()> {
+        // an empty file is created even if nothing has been written
+        self.stream()?;
+        let Self { stream, pending } = self;
+        let file = match stream.unwrap() {
+            Stream::Stdout(mut stdout) => return stdout.flush(),
+            Stream::File(writer) => writer.into_inner().map_err(|why| why.into_error())?,
+            Stream::Gzip(encoder) => encoder
+                .finish()?
+                .into_inner()
+                .map_err(|why| why.into_error())?,
+        };
+        file.sync_all()?;
+        drop(file);
+
+        let mut pending = pending.expect("missing temporary file");
+        // the output file might have been created while we were writing
+        if !pending.force && pending.path.exists() {
+            return Err(already_exists(&pending.path));
+        }
+        std::fs::rename(&pending.tmp_path, &pending.path).map_err(|why| {
+            Error::new(
+                why.kind(),
+                format!("unable to create '{}': {why}", pending.path.display()),
+            )
+        })?;
+        pending.committed = true;
+        Ok(())
+    }
+}
+
+fn already_exists(path: &Path) -> Error {
+    Error::new(
+        ErrorKind::AlreadyExists,
+        format!(
+            "output file '{}' already exists, use '--force' to replace it",
+            path.display()
+        ),
+    )
+}
+
+impl Write for FileOutput {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self.stream()? {
+            Stream::Stdout(stdout) => stdout.write(buf),
+            Stream::File(writer) => writer.write(buf),
+            Stream::Gzip(encoder) => encoder.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self.stream.as_mut() {
+            None => Ok(()),
+            Some(Stream::Stdout(stdout)) => stdout.flush(),
+            Some(Stream::File(writer)) => writer.flush(),
+            Some(Stream::Gzip(encoder)) => encoder.flush(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+
+    use flate2::read::GzDecoder;
+
+    use super::FileOutput;
+
+    #[test]
+    fn remove_uncommitted_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("timeline.csv");
+        let output = FileOutput::create(&path, false).unwrap();
+        drop(output);
+
+        let mut output = FileOutput::create(&path, false).unwrap();
+        writeln!(output, "incomplete").unwrap();
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+        drop(output);
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn compress_gz_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("timeline.csv.gz");
+        let mut output = FileOutput::create(&path, false).unwrap();
+        writeln!(output, "compressed").unwrap();
+        output.commit().unwrap();
+
+        let mut content = String::new();
+        GzDecoder::new(std::fs::File::open(&path).unwrap())
+            .read_to_string(&mut content)
+            .unwrap();
+        assert_eq!(content, "compressed\n");
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+}
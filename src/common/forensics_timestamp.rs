@@ -3,10 +3,11 @@ use std::fmt::Display;
 use chrono::format::StrftimeItems;
 use chrono::offset::TimeZone;
 use chrono::{DateTime, FixedOffset, Utc};
-use chrono_tz::Tz;
 use lazy_static::lazy_static;
 use serde::Serialize;
 
+use super::Timezone;
+
 lazy_static! {
     static ref TIMESTAMP_FORMAT: Option<String> = {
         if let Ok(format) = std::env::var("DFIR_DATE") {
@@ -37,7 +38,7 @@ lazy_static! {
 
 pub struct ForensicsTimestamp {
     timestamp: DateTime<Utc>,
-    dst_zone: Tz,
+    dst_zone: Timezone,
 }
 
 impl From<i64> for ForensicsTimestamp {
@@ -48,25 +49,25 @@ impl From<i64> for ForensicsTimestamp {
         };
         Self {
             timestamp,
-            dst_zone: Tz::UTC,
+            dst_zone: Timezone::default(),
         }
     }
 }
 
 impl ForensicsTimestamp {
-    pub fn new(unix_ts: i64, dst_zone: Tz) -> Self {
+    pub fn new(unix_ts: i64, dst_zone: impl Into<Timezone>) -> Self {
         let timestamp = match DateTime::from_timestamp(unix_ts, 0) {
             Some(ts) => ts,
             None => panic!("unable to convert '{unix_ts}' into unix timestamp"),
         };
         Self {
             timestamp,
-            dst_zone,
+            dst_zone: dst_zone.into(),
         }
     }
 
-    pub fn with_timezone(mut self, dst_zone: Tz) -> Self {
-        self.dst_zone = dst_zone;
+    pub fn with_timezone(mut self, dst_zone: impl Into<Timezone>) -> Self {
+        self.dst_zone = dst_zone.into();
         self
     }
 
@@ -102,7 +103,7 @@ impl Serialize for ForensicsTimestamp {
 mod tests {
     use chrono_tz::{Europe, UTC};
 
-    use crate::common::ForensicsTimestamp;
+    use crate::common::{ForensicsTimestamp, Timezone};
 
     #[test]
     fn test_time_import() {
@@ -111,5 +112,9 @@ mod tests {
 
         let ts = ForensicsTimestamp::from(1715845546).with_timezone(UTC);
         assert_eq!(ts.to_string(), "2024-05-16T07:45:46+00:00");
+
+        let offset: Timezone = "-03:00".parse().unwrap();
+        let ts = ForensicsTimestamp::new(1715845546, offset);
+        assert_eq!(ts.to_string(), "2024-05-16T04:45:46-03:00");
     }
 }
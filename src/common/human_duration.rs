@@ -0,0 +1,165 @@
+use std::{fmt::Display, str::FromStr};
+
+use anyhow::{anyhow, bail};
+use chrono::Duration;
+
+const UNITS: [(&str, i64); 4] = [("d", 86400), ("h", 3600), ("m", 60), ("s", 1)];
+
+/// a duration which must be specified together with its unit, like `90s`,
+/// `30m`, `2h` or `1d`. Several values can be combined, e.g. `1h30m`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HumanDuration(Duration);
+
+impl HumanDuration {
+    pub fn duration(&self) -> Duration {
+        self.0
+    }
+}
+
+impl From<Duration> for HumanDuration {
+    fn from(duration: Duration) -> Self {
+        Self(duration)
+    }
+}
+
+impl FromStr for HumanDuration {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let value = s.trim();
+        if value.is_empty() {
+            bail!("empty duration");
+        }
+
+        let mut seconds: i64 = 0;
+        let mut rest = value;
+        while !rest.is_empty() {
+            let digits = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len());
+            if digits == 0 {
+                bail!("invalid duration '{value}': expected a number before '{rest}'");
+            }
+            let (number, tail) = rest.split_at(digits);
+            let unit_len = tail
+                .find(|c: char| c.is_ascii_digit())
+                .unwrap_or(tail.len());
+            let (unit, tail) = tail.split_at(unit_len);
+
+            let factor = UNITS
+                .iter()
+                .find(|(name, _)| *name == unit)
+                .map(|(_, factor)| *factor)
+                .ok_or_else(|| {
+                    if unit.is_empty() {
+                        anyhow!("invalid duration '{value}': missing unit after '{number}', use one of 's', 'm', 'h' or 'd'")
+                    } else {
+                        anyhow!("invalid duration '{value}': unknown unit '{unit}', use one of 's', 'm', 'h' or 'd'")
+                    }
+                })?;
+
+            seconds = number
+                .parse::<i64>()
+                .ok()
+                .and_then(|n| n.checked_mul(factor))
+                .and_then(|n| n.checked_add(seconds))
+                .ok_or_else(|| anyhow!("invalid duration '{value}': value is too large"))?;
+            rest = tail;
+        }
+
+        Duration::try_seconds(seconds)
+            .map(Self)
+            .ok_or_else(|| anyhow!("invalid duration '{value}': value is too large"))
+    }
+}
+
+impl Display for HumanDuration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut seconds = self.0.num_seconds();
+        if seconds < 0 {
+            write!(f, "-")?;
+            seconds = -seconds;
+        }
+        if seconds == 0 {
+            return write!(f, "0s");
+        }
+        for (name, factor) in UNITS {
+            if seconds >= factor {
+                write!(f, "{}{name}", seconds / factor)?;
+                seconds %= factor;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::HumanDuration;
+
+    fn parse(s: &str) -> Duration {
+        s.parse::<HumanDuration>().unwrap().duration()
+    }
+
+    #[test]
+    fn parse_single_units() {
+        assert_eq!(parse("90s"), Duration::seconds(90));
+        assert_eq!(parse("30m"), Duration::minutes(30));
+        assert_eq!(parse("2h"), Duration::hours(2));
+        assert_eq!(parse("1d"), Duration::days(1));
+        assert_eq!(parse("0s"), Duration::zero());
+        assert_eq!(parse(" 5m "), Duration::minutes(5));
+    }
+
+    #[test]
+    fn parse_combined_units() {
+        assert_eq!(parse("1h30m"), Duration::minutes(90));
+        assert_eq!(parse("1d2h3m4s"), Duration::seconds(93784));
+        assert_eq!(parse("30m30m"), Duration::hours(1));
+    }
+
+    #[test]
+    fn reject_invalid_durations() {
+        for value in [
+            "",
+            " ",
+            "30",
+            "1h30",
+            "h",
+            "2x",
+            "-5m",
+            "1.5h",
+            "m30",
+            "2 h",
+            "99999999999999999999s",
+        ] {
+            assert!(
+                value.parse::<HumanDuration>().is_err(),
+                "'{value}' should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn display_durations() {
+        assert_eq!(
+            HumanDuration::from(Duration::seconds(90)).to_string(),
+            "1m30s"
+        );
+        assert_eq!(HumanDuration::from(Duration::hours(26)).to_string(), "1d2h");
+        assert_eq!(HumanDuration::from(Duration::zero()).to_string(), "0s");
+        assert_eq!(
+            HumanDuration::from(Duration::seconds(-90)).to_string(),
+            "-1m30s"
+        );
+    }
+
+    #[test]
+    fn display_can_be_parsed() {
+        for value in ["1d2h3m4s", "45m", "7s"] {
+            assert_eq!(value.parse::<HumanDuration>().unwrap().to_string(), value);
+        }
+    }
+}
@@ -0,0 +1,50 @@
+use std::io::Write;
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+/// the `-v` and `-q` flags of all tools. Warnings, e.g. about skipped or
+/// corrupt records, are displayed by default; `-q` hides them, and every
+/// `-v` displays more details
+pub type Verbosity = clap_verbosity_flag::Verbosity<clap_verbosity_flag::WarnLevel>;
+
+/// writes log messages to stderr, in the same format which is used for
+/// other messages of the tools, e.g. `warning: unable to read 'a.pf'`.
+/// Debug and trace messages also contain the module which wrote them.
+pub struct StderrLogger {
+    level: LevelFilter,
+}
+
+impl StderrLogger {
+    /// installs the logger, unless a logger has already been installed
+    pub fn init(level: LevelFilter) {
+        if log::set_boxed_logger(Box::new(Self { level })).is_ok() {
+            log::set_max_level(level);
+        }
+    }
+}
+
+impl Log for StderrLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        // a failure to write to stderr cannot be reported anywhere
+        let mut stderr = std::io::stderr().lock();
+        let _ = match record.level() {
+            Level::Error => writeln!(stderr, "error: {}", record.args()),
+            Level::Warn => writeln!(stderr, "warning: {}", record.args()),
+            Level::Info => writeln!(stderr, "info: {}", record.args()),
+            Level::Debug => writeln!(stderr, "debug [{}]: {}", record.target(), record.args()),
+            Level::Trace => writeln!(stderr, "trace [{}]: {}", record.target(), record.args()),
+        };
+    }
+
+    fn flush(&self) {
+        let _ = std::io::stderr().flush();
+    }
+}
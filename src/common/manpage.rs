@@ -0,0 +1,364 @@
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use clap::{Arg, Command};
+
+/// renders man pages in roff format from the definition of a clap command.
+/// Every subcommand gets a man page of its own, which is named after the
+/// command and the subcommand, like `es4forensics-import(1)`.
+///
+/// # Example
+/// ```
+/// use clap::{Arg, Command};
+/// use dfir_toolkit::common::ManPage;
+///
+/// let cmd = Command::new("ts2date")
+///     .about("replaces UNIX timestamps in a stream by a formatted date")
+///     .arg(Arg::new("input").help("name of the file to read"));
+/// let roff = ManPage::new(cmd).render();
+/// assert!(roff.starts_with(".TH TS2DATE 1"));
+/// ```
+pub struct ManPage {
+    cmd: Command,
+    name: String,
+    version: String,
+}
+
+impl ManPage {
+    pub fn new(mut cmd: Command) -> Self {
+        // building the command adds `--help` and `--version` and sets the
+        // usage strings of the subcommands
+        cmd.build();
+        let name = cmd.get_name().to_owned();
+        let version = cmd.get_version().unwrap_or_default().to_owned();
+        Self { cmd, name, version }
+    }
+
+    /// the name of the man page, without the section number
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// the man pages of all visible subcommands, including their subcommands
+    pub fn subcommands(&self) -> Vec<ManPage> {
+        self.cmd
+            .get_subcommands()
+            .filter(|sub| !sub.is_hide_set() && sub.get_name() != "help")
+            .flat_map(|sub| {
+                let page = Self {
+                    cmd: sub.clone(),
+                    name: format!("{}-{}", self.name, sub.get_name()),
+                    // subcommands have the version of the binary
+                    version: sub
+                        .get_version()
+                        .map(str::to_owned)
+                        .unwrap_or_else(|| self.version.clone()),
+                };
+                let mut pages = page.subcommands();
+                pages.insert(0, page);
+                pages
+            })
+            .collect()
+    }
+
+    /// writes the man page of the command and of all of its subcommands
+    /// into `dir`, and returns the names of the files which have been written
+    pub fn write_to(&self, dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+        std::fs::create_dir_all(dir)?;
+        let mut files = Vec::new();
+        for page in std::iter::once(self).chain(self.subcommands().iter()) {
+            let path = dir.join(format!("{}.1", page.name()));
+            std::fs::write(&path, page.render())?;
+            files.push(path);
+        }
+        Ok(files)
+    }
+
+    pub fn render(&self) -> String {
+        let mut roff = String::new();
+        let source = format!("{} {}", self.name, self.version);
+        let _ = writeln!(
+            roff,
+            ".TH {} 1 \"\" \"{}\"",
+            escape(&self.name.to_uppercase()),
+            escape(source.trim())
+        );
+
+        roff.push_str(".SH NAME\n");
+        match self.cmd.get_about() {
+            Some(about) => {
+                let _ = writeln!(
+                    roff,
+                    "{} \\- {}",
+                    escape(&self.name),
+                    escape(&about.to_string())
+                );
+            }
+            None => {
+                let _ = writeln!(roff, "{}", escape(&self.name));
+            }
+        }
+
+        roff.push_str(".SH SYNOPSIS\n");
+        let usage = self.cmd.clone().render_usage().to_string();
+        let usage = usage.trim().trim_start_matches("Usage:").trim();
+        let _ = writeln!(roff, "{}", escape(usage));
+
+        if let Some(long_about) = self.cmd.get_long_about() {
+            roff.push_str(".SH DESCRIPTION\n");
+            push_text(&mut roff, &long_about.to_string());
+        }
+
+        let visible_args: Vec<_> = self
+            .cmd
+            .get_arguments()
+            .filter(|arg| !arg.is_hide_set())
+            .collect();
+        let (arguments, options): (Vec<_>, Vec<_>) = visible_args
+            .into_iter()
+            .partition(|arg| arg.is_positional());
+
+        if !arguments.is_empty() {
+            roff.push_str(".SH ARGUMENTS\n");
+            for arg in arguments {
+                push_arg(&mut roff, arg);
+            }
+        }
+
+        if !options.is_empty() {
+            roff.push_str(".SH OPTIONS\n");
+            for arg in options {
+                push_arg(&mut roff, arg);
+            }
+        }
+
+        let subcommands: Vec<_> = self
+            .cmd
+            .get_subcommands()
+            .filter(|sub| !sub.is_hide_set() && sub.get_name() != "help")
+            .collect();
+        if !subcommands.is_empty() {
+            roff.push_str(".SH SUBCOMMANDS\n");
+            for sub in subcommands {
+                let _ = writeln!(roff, ".TP\n\\fB{}\\fR", escape(sub.get_name()));
+                if let Some(about) = sub.get_about() {
+                    push_text(&mut roff, &about.to_string());
+                }
+                let _ = writeln!(
+                    roff,
+                    "See \\fB{}\\-{}\\fR(1).",
+                    escape(&self.name),
+                    escape(sub.get_name())
+                );
+            }
+        }
+
+        // the text after the help, which often contains examples, has been
+        // formatted for the terminal, so its line breaks are kept
+        if let Some(after_help) = self
+            .cmd
+            .get_after_long_help()
+            .or_else(|| self.cmd.get_after_help())
+        {
+            roff.push_str(".SH EXTRA\n.nf\n");
+            push_text(&mut roff, after_help.to_string().trim());
+            roff.push_str(".fi\n");
+        }
+
+        if let Some(author) = self.cmd.get_author() {
+            roff.push_str(".SH AUTHORS\n");
+            push_text(&mut roff, author);
+        }
+
+        roff
+    }
+}
+
+fn push_arg(roff: &mut String, arg: &Arg) {
+    let mut names = Vec::new();
+    if let Some(short) = arg.get_short() {
+        names.push(format!("\\fB\\-{}\\fR", escape(&short.to_string())));
+    }
+    if let Some(long) = arg.get_long() {
+        names.push(format!("\\fB\\-\\-{}\\fR", escape(long)));
+    }
+
+    let mut header = names.join(", ");
+    if arg.get_action().takes_values() {
+        let value_names = match arg.get_value_names() {
+            Some(names) => names.iter().map(|n| n.to_string()).collect(),
+            None => vec![arg.get_id().as_str().to_uppercase()],
+        };
+        for value_name in value_names {
+            if !header.is_empty() {
+                header.push(' ');
+            }
+            let _ = write!(header, "\\fI<{}>\\fR", escape(&value_name));
+        }
+    }
+    let _ = writeln!(roff, ".TP\n{header}");
+
+    if let Some(help) = arg.get_long_help().or_else(|| arg.get_help()) {
+        push_text(roff, &help.to_string());
+    }
+
+    if !arg.get_action().takes_values() {
+        return;
+    }
+
+    let possible_values: Vec<_> = arg
+        .get_possible_values()
+        .into_iter()
+        .filter(|value| !value.is_hide_set())
+        .collect();
+    if possible_values
+        .iter()
+        .any(|value| value.get_help().is_some())
+    {
+        roff.push_str(".RS\nPossible values:\n");
+        for value in possible_values {
+            let _ = writeln!(roff, ".IP \\(bu 2\n\\fB{}\\fR", escape(value.get_name()));
+            if let Some(help) = value.get_help() {
+                let _ = writeln!(roff, "\\- {}", escape(&help.to_string()));
+            }
+        }
+        roff.push_str(".RE\n");
+    } else if !possible_values.is_empty() {
+        let names: Vec<_> = possible_values
+            .iter()
+            .map(|value| value.get_name())
+            .collect();
+        let _ = writeln!(
+            roff,
+            ".br\n[possible values: {}]",
+            escape(&names.join(", "))
+        );
+    }
+
+    if !arg.is_hide_default_value_set() && !arg.get_default_values().is_empty() {
+        let defaults: Vec<_> = arg
+            .get_default_values()
+            .iter()
+            .map(|value| value.to_string_lossy())
+            .collect();
+        let _ = writeln!(roff, ".br\n[default: {}]", escape(&defaults.join(", ")));
+    }
+
+    if let Some(env) = arg.get_env() {
+        if !arg.is_hide_env_set() {
+            let _ = writeln!(roff, ".br\n[env: {}]", escape(&env.to_string_lossy()));
+        }
+    }
+}
+
+/// appends some text, where empty lines separate paragraphs
+fn push_text(roff: &mut String, text: &str) {
+    for line in text.lines() {
+        if line.trim().is_empty() {
+            roff.push_str(".sp\n");
+        } else {
+            roff.push_str(&escape(line));
+            roff.push('\n');
+        }
+    }
+}
+
+/// escapes characters which have a special meaning in roff
+fn escape(text: &str) -> String {
+    let escaped = text.replace('\\', "\\e").replace('-', "\\-");
+
+    // lines starting with a dot or an apostrophe would be control lines
+    if escaped.starts_with('.') || escaped.starts_with('\'') {
+        format!("\\&{escaped}")
+    } else {
+        escaped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
+
+    use super::ManPage;
+
+    #[derive(ValueEnum, Clone)]
+    enum Format {
+        /// one line per entry
+        Txt,
+        Csv,
+    }
+
+    #[derive(Subcommand)]
+    enum Action {
+        /// import timeline data
+        Import {
+            /// name of the file to import
+            input_file: String,
+        },
+    }
+
+    /// sample command with every kind of argument
+    #[derive(Parser)]
+    #[clap(
+        name = "sample",
+        version,
+        author,
+        after_help = "EXAMPLES\n\n  sample -F csv"
+    )]
+    struct Cli {
+        #[command(subcommand)]
+        action: Action,
+
+        /// output format
+        #[clap(short('F'), long("format"), value_enum, default_value_t = Format::Txt)]
+        format: Format,
+
+        /// replace the output file if it already exists
+        #[clap(long("force"))]
+        force: bool,
+
+        /// secret which is not documented
+        #[clap(long("secret"), hide(true))]
+        secret: Option<String>,
+    }
+
+    #[test]
+    fn contains_all_options() {
+        let page = ManPage::new(Cli::command());
+        let roff = page.render();
+        assert!(roff.starts_with(".TH SAMPLE 1"));
+
+        let mut cmd = Cli::command();
+        cmd.build();
+        for arg in cmd.get_arguments().filter(|arg| !arg.is_hide_set()) {
+            let long = arg.get_long().unwrap().replace('-', "\\-");
+            assert!(
+                roff.contains(&format!("\\fB\\-\\-{long}\\fR")),
+                "missing --{long}"
+            );
+        }
+        assert!(!roff.contains("secret"));
+        assert!(roff.contains("[default: txt]"));
+        assert!(roff.contains("\\fBtxt\\fR\n\\- one line per entry"));
+        assert!(roff.contains("sample \\-F csv"));
+        assert!(roff.contains("See \\fBsample\\-import\\fR(1)."));
+    }
+
+    #[test]
+    fn write_pages_of_subcommands() {
+        let dir = tempfile::tempdir().unwrap();
+        let files = ManPage::new(Cli::command()).write_to(dir.path()).unwrap();
+        assert_eq!(
+            files,
+            vec![
+                dir.path().join("sample.1"),
+                dir.path().join("sample-import.1")
+            ]
+        );
+
+        let import = std::fs::read_to_string(dir.path().join("sample-import.1")).unwrap();
+        assert!(import.starts_with(".TH SAMPLE\\-IMPORT 1"));
+        assert!(import.contains("\\fI<INPUT_FILE>\\fR"));
+        assert!(import.contains("name of the file to import"));
+    }
+}
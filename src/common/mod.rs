@@ -3,13 +3,29 @@ mod forensics_timestamp;
 mod parse_cli;
 mod rfc3339_datetime;
 mod tzargument;
+mod timezone;
 mod file_input;
+mod file_output;
 mod formattable_datetime;
+mod human_duration;
+mod time_bound;
+mod manpage;
+mod logger;
+#[cfg(feature = "anonymize")]
+mod anonymizer;
 
 pub use forensics_timestamp::*;
 pub use parse_cli::*;
 pub use rfc3339_datetime::*;
 pub use tzargument::*;
+pub use timezone::*;
 pub use formattable_datetime::*;
+pub use human_duration::*;
+pub use time_bound::*;
+pub use manpage::*;
+pub use logger::*;
+#[cfg(feature = "anonymize")]
+pub use anonymizer::*;
 
-pub use file_input::*;
\ No newline at end of file
+pub use file_input::*;
+pub use file_output::*;
\ No newline at end of file
@@ -1,9 +1,11 @@
+use std::path::PathBuf;
 use std::process::exit;
 
 use clap::{value_parser, Arg, ArgAction, Parser, Command};
 use clap_complete::{generate, Generator, Shell};
 use log::LevelFilter;
-use simplelog::{Config, TermLogger, TerminalMode, ColorChoice};
+
+use super::{ManPage, StderrLogger, TzArgument};
 
 pub trait HasVerboseFlag {
     fn log_level_filter(&self)-> LevelFilter;
@@ -14,6 +16,8 @@ pub trait FancyParser<P: Parser> {
 
     fn parse_markdown_help();
     fn parse_autocomplete();
+    fn parse_manpage();
+    fn parse_timezone_list();
 }
 
 impl<P> FancyParser<P> for P
@@ -23,13 +27,11 @@ where
     fn parse_cli() -> P {
         Self::parse_markdown_help();
         Self::parse_autocomplete();
+        Self::parse_manpage();
+        Self::parse_timezone_list();
         let cli = P::parse();
 
-        let _ = TermLogger::init(
-            cli.log_level_filter(),
-            Config::default(),
-            TerminalMode::Stderr,
-            ColorChoice::Auto);
+        StderrLogger::init(cli.log_level_filter());
         cli
     }
 
@@ -65,6 +67,65 @@ where
             exit(0);
         }
     }
+
+    /// writes man pages of the command and its subcommands into the
+    /// directory given by `--generate-manpage`
+    fn parse_manpage() {
+        let matches = P::command()
+            .ignore_errors(true)
+            .arg(
+                Arg::new("generate-manpage")
+                    .long("generate-manpage")
+                    .action(ArgAction::Set)
+                    .hide(true)
+                    .value_parser(value_parser!(PathBuf)),
+            )
+            .get_matches();
+
+        if let Some(dir) = matches.get_one::<PathBuf>("generate-manpage") {
+            match ManPage::new(P::command()).write_to(dir) {
+                Ok(files) => {
+                    for file in files {
+                        println!("{}", file.display());
+                    }
+                    exit(0);
+                }
+                Err(why) => {
+                    eprintln!("unable to write man pages into '{}': {why}", dir.display());
+                    exit(1);
+                }
+            }
+        }
+    }
+
+    /// displays the names of all timezones if `--timezone list` has been
+    /// given, even if other required arguments are missing
+    fn parse_timezone_list() {
+        let cmd = P::command();
+        let timezone_arg = cmd.get_arguments().find(|arg| {
+            arg.get_long() == Some("timezone")
+                || arg
+                    .get_all_aliases()
+                    .is_some_and(|aliases| aliases.contains(&"timezone"))
+        });
+        let Some(id) = timezone_arg.map(|arg| arg.get_id().clone()) else {
+            return;
+        };
+
+        // the timezone is read as string, because 'list' is no valid timezone
+        let matches = cmd
+            .ignore_errors(true)
+            .mut_arg(&id, |arg| arg.value_parser(value_parser!(String)))
+            .get_matches();
+
+        if matches
+            .get_one::<String>(id.as_str())
+            .is_some_and(|timezone| timezone == "list")
+        {
+            TzArgument::display_zones();
+            exit(0);
+        }
+    }
 }
 
 fn print_completions<G: Generator>(gen: G, cmd: &mut Command) {
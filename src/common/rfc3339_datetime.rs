@@ -1,4 +1,7 @@
 use std::ops::Deref;
+use std::str::FromStr;
+
+use anyhow::bail;
 
 use chrono::{DateTime, NaiveDateTime, Utc, NaiveDate, NaiveTime};
 
@@ -10,27 +13,29 @@ pub struct Rfc3339Datetime {
     timestamp: DateTime<Utc>
 }
 
-impl From<&str> for Rfc3339Datetime {
-    fn from(s: &str) -> Self {
+impl FromStr for Rfc3339Datetime {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
         if let Ok(timestamp) = DateTime::parse_from_rfc3339(s) {
-            return Self{timestamp: timestamp.with_timezone(&chrono::Utc)}
+            return Ok(Self{timestamp: timestamp.with_timezone(&chrono::Utc)})
         }
         
         if let Ok(timestamp) = NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S") {
-            return Self{timestamp: DateTime::<Utc>::from_naive_utc_and_offset(timestamp, Utc)}
+            return Ok(Self{timestamp: DateTime::<Utc>::from_naive_utc_and_offset(timestamp, Utc)})
         }
 
         if let Ok(timestamp) = NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S") {
-            return Self{timestamp: DateTime::<Utc>::from_naive_utc_and_offset(timestamp, Utc)}
+            return Ok(Self{timestamp: DateTime::<Utc>::from_naive_utc_and_offset(timestamp, Utc)})
         }
 
         if let Ok(timestamp) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
             let time = NaiveTime::from_hms_opt(0, 0, 0).unwrap();
             let timestamp = NaiveDateTime::new(timestamp, time);
-            return Self{timestamp: DateTime::<Utc>::from_naive_utc_and_offset(timestamp, Utc)}
+            return Ok(Self{timestamp: DateTime::<Utc>::from_naive_utc_and_offset(timestamp, Utc)})
         }
 
-        panic!("invalid timestamp: '{s}'");
+        bail!("invalid timestamp: '{s}'");
     }
 }
 
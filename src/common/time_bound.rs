@@ -0,0 +1,119 @@
+use std::str::FromStr;
+
+use super::{HumanDuration, Rfc3339Datetime};
+use anyhow::{anyhow, bail};
+use chrono::{DateTime, Duration, Utc};
+
+/// Lower or upper limit of a time window.
+///
+/// The limit can either be an absolute timestamp (using RFC 3339 syntax) or
+/// a negative offset like `-24h`, which is relative to the newest record.
+#[derive(Clone)]
+pub enum TimeBound {
+    Absolute(Rfc3339Datetime),
+    Relative(Duration),
+}
+
+impl TimeBound {
+    pub fn is_relative(&self) -> bool {
+        matches!(self, Self::Relative(_))
+    }
+
+    /// converts this value into an absolute timestamp. `newest` is required
+    /// for relative values only. Fails if the resulting timestamp cannot be
+    /// represented
+    pub fn resolve(&self, newest: Option<&DateTime<Utc>>) -> anyhow::Result<Option<DateTime<Utc>>> {
+        match self {
+            Self::Absolute(ts) => Ok(Some(**ts)),
+            Self::Relative(offset) => newest
+                .map(|newest| {
+                    newest.checked_sub_signed(*offset).ok_or_else(|| {
+                        anyhow!(
+                            "the relative time '-{}' reaches beyond the supported time range",
+                            HumanDuration::from(*offset)
+                        )
+                    })
+                })
+                .transpose(),
+        }
+    }
+}
+
+impl FromStr for TimeBound {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.strip_prefix('-') {
+            None => Ok(Self::Absolute(s.parse()?)),
+            Some(offset) => {
+                let unit_pos = offset.len()
+                    - offset
+                        .chars()
+                        .last()
+                        .map(char::len_utf8)
+                        .unwrap_or_default();
+                let (value, unit) = offset.split_at(unit_pos);
+                let value: i64 = value.parse().map_err(|_| {
+                    anyhow!("invalid relative time '{s}', expected something like '-24h'")
+                })?;
+                let duration = match unit {
+                    "s" => Duration::try_seconds(value),
+                    "m" => Duration::try_minutes(value),
+                    "h" => Duration::try_hours(value),
+                    "d" => Duration::try_days(value),
+                    "w" => Duration::try_weeks(value),
+                    _ => bail!("invalid unit in '{s}', use one of 's', 'm', 'h', 'd' or 'w'"),
+                };
+                duration
+                    .map(Self::Relative)
+                    .ok_or_else(|| anyhow!("invalid relative time '{s}': value is too large"))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Utc};
+
+    use super::TimeBound;
+
+    #[test]
+    fn parse_time_bounds() {
+        let newest: DateTime<Utc> = "2024-03-12T14:00:00Z".parse().unwrap();
+        let relative: TimeBound = "-90m".parse().unwrap();
+        assert!(relative.is_relative());
+        assert_eq!(
+            relative
+                .resolve(Some(&newest))
+                .unwrap()
+                .unwrap()
+                .to_rfc3339(),
+            "2024-03-12T12:30:00+00:00"
+        );
+
+        let absolute: TimeBound = "2024-03-12T08:00:00Z".parse().unwrap();
+        assert!(!absolute.is_relative());
+        assert_eq!(
+            absolute.resolve(None).unwrap().unwrap().to_rfc3339(),
+            "2024-03-12T08:00:00+00:00"
+        );
+
+        assert!("-24".parse::<TimeBound>().is_err());
+        assert!("-24y".parse::<TimeBound>().is_err());
+        assert!("-h".parse::<TimeBound>().is_err());
+        assert!("yesterday".parse::<TimeBound>().is_err());
+    }
+
+    #[test]
+    fn reject_large_relative_times() {
+        assert!("-99999999999w".parse::<TimeBound>().is_err());
+        assert!(format!("-{}s", i64::MAX).parse::<TimeBound>().is_err());
+
+        // the duration can be represented, but not the resulting timestamp
+        let newest: DateTime<Utc> = "2024-03-12T14:00:00Z".parse().unwrap();
+        let relative: TimeBound = "-99999999w".parse().unwrap();
+        assert!(relative.resolve(Some(&newest)).is_err());
+        assert!(relative.resolve(None).unwrap().is_none());
+    }
+}
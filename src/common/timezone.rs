@@ -0,0 +1,177 @@
+use std::{fmt::Display, str::FromStr};
+
+use anyhow::anyhow;
+use chrono::{
+    DateTime, FixedOffset, MappedLocalTime, NaiveDate, NaiveDateTime, Offset, TimeZone, Utc,
+};
+use chrono_tz::Tz;
+
+use super::FormattableDatetime;
+
+/// Timezone which should be used to display timestamps. This can either be
+/// an IANA timezone name like `Europe/Berlin`, which correctly handles
+/// daylight saving time, or a fixed offset like `+02:00`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Timezone {
+    Named(Tz),
+    Offset(FixedOffset),
+}
+
+impl Default for Timezone {
+    fn default() -> Self {
+        Self::Named(Tz::UTC)
+    }
+}
+
+impl Timezone {
+    pub fn is_utc(&self) -> bool {
+        match self {
+            Self::Named(tz) => *tz == Tz::UTC,
+            Self::Offset(offset) => offset.local_minus_utc() == 0,
+        }
+    }
+
+    /// converts a timestamp into this timezone
+    pub fn convert(&self, timestamp: &DateTime<Utc>) -> DateTime<FixedOffset> {
+        match self {
+            Self::Named(tz) => timestamp.with_timezone(tz).fixed_offset(),
+            Self::Offset(offset) => timestamp.with_timezone(offset),
+        }
+    }
+
+    /// converts a timestamp into this timezone, honoring the `DFIR_DATE`
+    /// environment variable
+    pub fn format(&self, timestamp: &DateTime<Utc>) -> String {
+        FormattableDatetime::from(self.convert(timestamp)).to_string()
+    }
+}
+
+impl From<Tz> for Timezone {
+    fn from(tz: Tz) -> Self {
+        Self::Named(tz)
+    }
+}
+
+/// allows to use a [`Timezone`] wherever chrono expects a timezone, e.g. in
+/// [`DateTime::with_timezone`]. The offset of a named timezone is determined
+/// for every timestamp individually, so that daylight saving time is respected
+impl TimeZone for Timezone {
+    type Offset = FixedOffset;
+
+    fn from_offset(offset: &FixedOffset) -> Self {
+        Self::Offset(*offset)
+    }
+
+    fn offset_from_local_date(&self, local: &NaiveDate) -> MappedLocalTime<FixedOffset> {
+        match self {
+            Self::Named(tz) => tz.offset_from_local_date(local).map(|o| o.fix()),
+            Self::Offset(offset) => MappedLocalTime::Single(*offset),
+        }
+    }
+
+    fn offset_from_local_datetime(&self, local: &NaiveDateTime) -> MappedLocalTime<FixedOffset> {
+        match self {
+            Self::Named(tz) => tz.offset_from_local_datetime(local).map(|o| o.fix()),
+            Self::Offset(offset) => MappedLocalTime::Single(*offset),
+        }
+    }
+
+    fn offset_from_utc_date(&self, utc: &NaiveDate) -> FixedOffset {
+        match self {
+            Self::Named(tz) => tz.offset_from_utc_date(utc).fix(),
+            Self::Offset(offset) => *offset,
+        }
+    }
+
+    fn offset_from_utc_datetime(&self, utc: &NaiveDateTime) -> FixedOffset {
+        match self {
+            Self::Named(tz) => tz.offset_from_utc_datetime(utc).fix(),
+            Self::Offset(offset) => *offset,
+        }
+    }
+}
+
+impl FromStr for Timezone {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.starts_with('+') || s.starts_with('-') {
+            let offset = DateTime::parse_from_str(
+                &format!("2000-01-01T00:00:00{s}"),
+                "%Y-%m-%dT%H:%M:%S%:z",
+            )
+            .map_err(|_| anyhow!("invalid timezone offset '{s}', expected something like '+02:00'"))?
+            .offset()
+            .to_owned();
+            Ok(Self::Offset(offset))
+        } else {
+            Ok(Self::Named(s.parse().map_err(|why| anyhow!("{why}"))?))
+        }
+    }
+}
+
+impl Display for Timezone {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Named(tz) => tz.fmt(f),
+            Self::Offset(offset) => offset.fmt(f),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, TimeZone, Utc};
+
+    use super::Timezone;
+
+    #[test]
+    fn convert_with_dst() {
+        let berlin: Timezone = "Europe/Berlin".parse().unwrap();
+        let winter: DateTime<Utc> = "2024-03-30T12:00:00Z".parse().unwrap();
+        let summer: DateTime<Utc> = "2024-03-31T12:00:00Z".parse().unwrap();
+        assert_eq!(berlin.convert(&winter).to_rfc3339(), "2024-03-30T13:00:00+01:00");
+        assert_eq!(berlin.convert(&summer).to_rfc3339(), "2024-03-31T14:00:00+02:00");
+        assert!(!berlin.is_utc());
+    }
+
+    #[test]
+    fn convert_with_offset() {
+        let offset: Timezone = "+02:00".parse().unwrap();
+        let ts: DateTime<Utc> = "2024-03-30T12:00:00Z".parse().unwrap();
+        assert_eq!(offset.convert(&ts).to_rfc3339(), "2024-03-30T14:00:00+02:00");
+
+        let offset: Timezone = "-05:30".parse().unwrap();
+        assert_eq!(offset.convert(&ts).to_rfc3339(), "2024-03-30T06:30:00-05:30");
+
+        assert!("+00:00".parse::<Timezone>().unwrap().is_utc());
+        assert!(Timezone::default().is_utc());
+    }
+
+    #[test]
+    fn use_as_chrono_timezone() {
+        let berlin: Timezone = "Europe/Berlin".parse().unwrap();
+        let summer: DateTime<Utc> = "2024-03-31T12:00:00Z".parse().unwrap();
+        assert_eq!(
+            summer.with_timezone(&berlin).to_rfc3339(),
+            "2024-03-31T14:00:00+02:00"
+        );
+
+        // 02:30 does not exist in Berlin on this day
+        let local = "2024-03-31T02:30:00".parse().unwrap();
+        assert!(berlin.from_local_datetime(&local).single().is_none());
+
+        let offset: Timezone = "-05:30".parse().unwrap();
+        assert_eq!(
+            summer.with_timezone(&offset).to_rfc3339(),
+            "2024-03-31T06:30:00-05:30"
+        );
+    }
+
+    #[test]
+    fn invalid_timezones() {
+        assert!("Europe/Nowhere".parse::<Timezone>().is_err());
+        assert!("+2".parse::<Timezone>().is_err());
+        assert!("+25:00".parse::<Timezone>().is_err());
+    }
+}
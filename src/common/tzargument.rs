@@ -1,7 +1,29 @@
+use std::ops::Deref;
 use std::{fmt::Display, str::FromStr};
 use chrono_tz::TZ_VARIANTS;
 use chrono_tz::Tz;
 
+use super::Timezone;
+
+/// the `--timezone` argument, which is shared by all tools which display
+/// timestamps. The value `list` is handled by [`super::FancyParser::parse_cli`]
+#[derive(clap::Args, Clone, Debug, Default)]
+pub struct TimezoneArgs {
+    /// display timestamps in this timezone. Use either a timezone name like
+    /// 'Europe/Berlin', which respects daylight saving time, or a fixed offset
+    /// like '+02:00'. Use 'list' to display all timezone names
+    #[clap(long("timezone"), default_value_t = Timezone::default(), allow_hyphen_values = true)]
+    timezone: Timezone,
+}
+
+impl Deref for TimezoneArgs {
+    type Target = Timezone;
+
+    fn deref(&self) -> &Self::Target {
+        &self.timezone
+    }
+}
+
 #[derive(Clone, Debug, Copy)]
 pub enum TzArgument {
     List,
@@ -0,0 +1,196 @@
+use std::{fmt::Display, time::Duration};
+
+use serde_json::Value;
+
+/// controls how often a bulk request is repeated if elasticsearch is
+/// temporarily unable to handle it, and how long to wait before doing so.
+/// The delay is doubled for every retry
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 5,
+            initial_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(60),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// the time to wait before the retry with the number `retry`, starting
+    /// with `0`
+    pub fn delay(&self, retry: u32) -> Duration {
+        self.initial_delay
+            .checked_mul(2u32.saturating_pow(retry))
+            .unwrap_or(self.max_delay)
+            .min(self.max_delay)
+    }
+}
+
+/// status codes which indicate that elasticsearch is temporarily unable to
+/// handle a request, e.g. because it is overloaded
+pub fn is_retryable_status(status: u16) -> bool {
+    matches!(status, 429 | 502 | 503 | 504)
+}
+
+/// the result of a single document of a bulk request
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BulkItemResult {
+    Indexed,
+
+    /// the document has been rejected, but might be accepted later
+    Retryable(String),
+
+    /// the document has been rejected and should not be sent again
+    Failed(String),
+}
+
+/// returns the result of every document of a bulk request, in the order in
+/// which the documents have been sent
+pub fn inspect_bulk_response(response: &Value, document_count: usize) -> Vec<BulkItemResult> {
+    if response["errors"].as_bool() == Some(false) {
+        return vec![BulkItemResult::Indexed; document_count];
+    }
+
+    let items = match response["items"].as_array() {
+        Some(items) if items.len() == document_count => items,
+        _ => {
+            return vec![
+                BulkItemResult::Failed(format!("invalid bulk response: {response}"));
+                document_count
+            ]
+        }
+    };
+
+    items.iter().map(inspect_bulk_item).collect()
+}
+
+fn inspect_bulk_item(item: &Value) -> BulkItemResult {
+    // every item consists of the name of the operation and its result
+    let result = match item.as_object().and_then(|item| item.values().next()) {
+        Some(result) => result,
+        None => return BulkItemResult::Failed(format!("invalid bulk item: {item}")),
+    };
+    let status = result["status"].as_u64().unwrap_or_default();
+    let reason = || {
+        format!(
+            "{} (status {status}): {}",
+            result["error"]["type"].as_str().unwrap_or("unknown error"),
+            result["error"]["reason"].as_str().unwrap_or_default()
+        )
+    };
+
+    match u16::try_from(status) {
+        Ok(200..=299) => BulkItemResult::Indexed,
+
+        // the ids of the documents are derived from their content, so a
+        // conflict means that this document has already been indexed
+        Ok(409) => BulkItemResult::Indexed,
+        Ok(status) if is_retryable_status(status) => BulkItemResult::Retryable(reason()),
+        _ => BulkItemResult::Failed(reason()),
+    }
+}
+
+/// the number of documents which have been handled by an [`crate::es4forensics::Index`]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BulkStatistics {
+    pub indexed: usize,
+    pub failed: usize,
+
+    /// the number of documents which have been sent again. A document which
+    /// has been sent three times is counted twice
+    pub retried: usize,
+}
+
+impl Display for BulkStatistics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "indexed: {}, failed: {}, retried: {}",
+            self.indexed, self.failed, self.retried
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use serde_json::json;
+
+    use super::{inspect_bulk_response, BulkItemResult, RetryPolicy};
+
+    #[test]
+    fn exponential_backoff() {
+        let policy = RetryPolicy {
+            max_retries: 10,
+            initial_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(5),
+        };
+        let delays: Vec<_> = (0..6).map(|retry| policy.delay(retry)).collect();
+        assert_eq!(
+            delays,
+            vec![
+                Duration::from_millis(500),
+                Duration::from_secs(1),
+                Duration::from_secs(2),
+                Duration::from_secs(4),
+                Duration::from_secs(5),
+                Duration::from_secs(5),
+            ]
+        );
+        assert_eq!(policy.delay(u32::MAX), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn inspect_successful_response() {
+        let response = json!({"took": 3, "errors": false, "items": []});
+        assert_eq!(
+            inspect_bulk_response(&response, 2),
+            vec![BulkItemResult::Indexed, BulkItemResult::Indexed]
+        );
+    }
+
+    #[test]
+    fn inspect_items() {
+        let response = json!({
+            "took": 3,
+            "errors": true,
+            "items": [
+                {"create": {"_id": "a", "status": 201}},
+                {"create": {"_id": "b", "status": 409, "error": {"type": "version_conflict_engine_exception"}}},
+                {"create": {"_id": "c", "status": 429, "error": {"type": "es_rejected_execution_exception", "reason": "queue is full"}}},
+                {"create": {"_id": "d", "status": 400, "error": {"type": "mapper_parsing_exception", "reason": "failed to parse field"}}},
+            ]
+        });
+        assert_eq!(
+            inspect_bulk_response(&response, 4),
+            vec![
+                BulkItemResult::Indexed,
+                BulkItemResult::Indexed,
+                BulkItemResult::Retryable(
+                    "es_rejected_execution_exception (status 429): queue is full".into()
+                ),
+                BulkItemResult::Failed(
+                    "mapper_parsing_exception (status 400): failed to parse field".into()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn inspect_invalid_response() {
+        let response = json!({"errors": true, "items": [{"create": {"status": 201}}]});
+        let results = inspect_bulk_response(&response, 2);
+        assert_eq!(results.len(), 2);
+        assert!(results
+            .iter()
+            .all(|result| matches!(result, BulkItemResult::Failed(_))));
+    }
+}
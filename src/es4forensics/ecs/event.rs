@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 use duplicate::duplicate_item;
 use serde::Serialize;
@@ -82,7 +82,7 @@ pub struct Event<'a> {
     module: Option<String>,
     provider: Option<String>,
     severity: Option<u8>,
-    custom_data: HashMap<&'a String, &'a Value>,
+    custom_data: BTreeMap<&'a String, &'a Value>,
 }
 
 impl<'a> Event<'a> {
@@ -117,7 +117,7 @@ impl<'a> EcsObject for Event<'a> {
 impl<'a> CustomizableField<'a> for Event<'a> {
     fn with_custom_data(
         mut self,
-        custom_data: &HashMap<&'a String, &'a serde_json::Value>,
+        custom_data: &BTreeMap<&'a String, &'a serde_json::Value>,
     ) -> Self {
         self.custom_data.extend(custom_data);
         self
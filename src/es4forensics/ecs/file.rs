@@ -5,7 +5,7 @@ use serde::Serialize;
 
 use crate::es4forensics::timestamp::Timestamp;
 
-use super::ecs_object::EcsObject;
+use super::{ecs_object::EcsObject, objects::Macb};
 
 #[derive(Serialize)]
 pub enum FileType {
@@ -50,6 +50,12 @@ pub struct File {
 
     #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
     file_type: Option<FileType>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    macb_short: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    macb_long: Option<Vec<&'static str>>,
 }
 
 impl From<String> for File {
@@ -92,6 +98,14 @@ impl File {
         self.attribute = ts;
         self
     }
+
+    /// stores which timestamps of the file are equal to the timestamp of the
+    /// document, e.g. as `m.c.` and `["modified", "changed"]`
+    pub fn with_macb(mut self, macb: &Macb) -> Self {
+        self.macb_short = Some(macb.into());
+        self.macb_long = Some(macb.into());
+        self
+    }
 }
 
 impl EcsObject for File {
@@ -12,7 +12,7 @@ pub use host::*;
 pub use file::*;
 pub use timeline_object::TimelineObject;
 
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 use serde_json::Value;
 
@@ -21,5 +21,5 @@ pub trait ECSFields {
 }
 
 pub trait CustomizableField<'a> {
-    fn with_custom_data(self, custom_data: &HashMap<&'a String, &'a Value>) -> Self;
+    fn with_custom_data(self, custom_data: &BTreeMap<&'a String, &'a Value>) -> Self;
 }
@@ -19,7 +19,7 @@ impl From<&Macb> for String {
     }
 }
 
-impl From<&Macb> for Vec<&str> {
+impl From<&Macb> for Vec<&'static str> {
     fn from(me: &Macb) -> Self {
         let mut res = Vec::new();
         if me.modified { res.push("modified"); }
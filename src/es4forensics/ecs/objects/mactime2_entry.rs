@@ -0,0 +1,97 @@
+use anyhow::Result;
+use chrono::DateTime;
+use serde::Deserialize;
+
+use crate::es4forensics::ecs::{ecs_builder::EcsBuilder, timeline_object::TimelineObject, File};
+use crate::es4forensics::timestamp::Timestamp;
+
+use super::Macb;
+
+#[derive(Deserialize)]
+struct Mactime2Flags {
+    m: bool,
+    a: bool,
+    c: bool,
+    b: bool,
+}
+
+/// a single line of the JSON output of `mactime2`, which already contains
+/// exactly one combination of timestamp and macb flags
+#[derive(Deserialize)]
+pub struct Mactime2Entry {
+    timestamp: String,
+    macb: Mactime2Flags,
+    size: u64,
+    uid: u64,
+    gid: u64,
+    mode: String,
+    inode: String,
+    name: String,
+}
+
+impl Mactime2Entry {
+    fn into_builder(self) -> Result<EcsBuilder> {
+        let timestamp: Timestamp = DateTime::parse_from_rfc3339(&self.timestamp)?.into();
+        let macb = Macb {
+            modified: self.macb.m,
+            accessed: self.macb.a,
+            changed: self.macb.c,
+            created: self.macb.b,
+        };
+        let file = File::from(self.name.clone())
+            .with_inode(self.inode)
+            .with_mode(self.mode)
+            .with_uid(self.uid)
+            .with_gid(self.gid)
+            .with_size(self.size)
+            .with_macb(&macb);
+
+        EcsBuilder::new(self.name, timestamp)
+            .with_additional_tag("bodyfile")
+            .with_file(file)
+    }
+}
+
+impl TryFrom<&str> for Mactime2Entry {
+    type Error = anyhow::Error;
+    fn try_from(line: &str) -> Result<Self> {
+        Ok(serde_json::from_str(line)?)
+    }
+}
+
+impl TimelineObject for Mactime2Entry {}
+
+impl IntoIterator for Mactime2Entry {
+    type Item = anyhow::Result<EcsBuilder>;
+    type IntoIter = std::vec::IntoIter<Self::Item>;
+    fn into_iter(self) -> Self::IntoIter {
+        vec![self.into_builder()].into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::Mactime2Entry;
+    use crate::es4forensics::TimelineObject;
+
+    #[test]
+    fn convert_mactime2_entry() {
+        let line = r#"{"timestamp":"2023-01-02T03:04:05+01:00","macb":{"m":true,"a":false,"c":true,"b":false},"size":42,"uid":0,"gid":0,"mode":"r/rrwxrwxrwx","inode":"93552-128-1","name":"/Windows/notepad.exe","source":null}"#;
+        let entry = Mactime2Entry::try_from(line).unwrap();
+        let documents: Vec<_> = entry.into_values().collect();
+        assert_eq!(documents.len(), 1);
+
+        let document = &documents[0];
+        assert_eq!(document["@timestamp"], json!(1672625045000i64));
+        assert_eq!(document["message"], "/Windows/notepad.exe");
+        assert_eq!(document["file"]["macb_short"], "m.c.");
+        assert_eq!(
+            document["file"]["macb_long"],
+            json!(["modified", "changed"])
+        );
+        assert_eq!(document["file"]["name"], "notepad.exe");
+        assert_eq!(document["file"]["size"], 42);
+    }
+}
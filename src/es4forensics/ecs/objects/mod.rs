@@ -5,6 +5,7 @@ mod posix_file;
 mod ntfs_file;
 mod simple_event;
 mod macb;
+mod mactime2_entry;
 
 pub use ad_object::*;
 pub use registry_key::*;
@@ -13,3 +14,4 @@ pub use posix_file::*;
 pub use ntfs_file::*;
 pub use simple_event::*;
 pub use macb::*;
+pub use mactime2_entry::*;
@@ -8,10 +8,13 @@ use serde::Serialize;
 use crate::es4forensics::{timestamp::Timestamp, ecs::{timeline_object::TimelineObject, ecs_builder::EcsBuilder}};
 use crate::es4forensics::ecs::File;
 
+use super::Macb;
+
 #[derive(Serialize)]
 pub struct PosixFile {
     name: String,
     inode: String,
+    mode: String,
     uid: u64,
     gid: u64,
     size: u64,
@@ -31,36 +34,30 @@ impl PosixFile {
         }
     }
 
-    // fn generate_macb(&self, reference_ts: &Timestamp) -> Macb {
-    //     let mut macb = Macb::default();
-    //     if let Some(t) = self.mtime.as_ref() {
-    //         macb.modified = t == reference_ts;
-    //     }
-    //     if let Some(t) = self.atime.as_ref() {
-    //         macb.accessed = t == reference_ts;
-    //     }
-    //     if let Some(t) = self.ctime.as_ref() {
-    //         macb.changed = t == reference_ts;
-    //     }
-    //     if let Some(t) = self.crtime.as_ref() {
-    //         macb.created = t == reference_ts;
-    //     }
-    //     macb
-    // }
+    fn generate_macb(&self, reference_ts: &Timestamp) -> Macb {
+        let is_reference = |ts: &Option<Timestamp>| ts.as_ref() == Some(reference_ts);
+        Macb {
+            modified: is_reference(&self.mtime),
+            accessed: is_reference(&self.atime),
+            changed: is_reference(&self.ctime),
+            created: is_reference(&self.crtime),
+        }
+    }
 
     fn add_builder_to(&self, docs: &mut HashMap<Timestamp, anyhow::Result<EcsBuilder>>, ts: &Option<Timestamp>) {
         if let Some(t) = ts.as_ref() {
-            //let macb = self.generate_macb(t);
             if ! docs.contains_key(t) {
                 let file = File::from(self.name.clone())
                     .with_inode(self.inode.clone())
+                    .with_mode(self.mode.clone())
                     .with_uid(self.uid)
                     .with_gid(self.gid)
                     .with_size(self.size)
                     .with_mtime(self.mtime.clone())
                     .with_accessed(self.atime.clone())
                     .with_ctime(self.ctime.clone())
-                    .with_created(self.crtime.clone());
+                    .with_created(self.crtime.clone())
+                    .with_macb(&self.generate_macb(t));
                 let builder = EcsBuilder::new(self.name.clone(), t.clone())
                     .with_additional_tag("bodyfile")
                     .with_file(file);
@@ -108,6 +105,7 @@ impl TryFrom<(&Bodyfile3Line, &Tz)> for PosixFile {
         Ok(Self {
             name: bfline.get_name().to_string(),
             inode: bfline.get_inode().to_string(),
+            mode: bfline.get_mode_as_string().to_string(),
             uid: *bfline.get_uid(),
             gid: *bfline.get_gid(),
             size: *bfline.get_size(),
@@ -1,9 +1,10 @@
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 use crate::es4forensics::{
     ecs::{ecs_builder::EcsBuilder, timeline_object::TimelineObject, *},
     ecs::log::{EventLevel, Log, Severity, Syslog},
 };
+use anyhow::bail;
 use chrono::{DateTime, Utc};
 use num_traits::ToPrimitive;
 use serde_json::Value;
@@ -20,7 +21,7 @@ pub struct WindowsEvent<'a> {
     provider_name: &'a Value,
     channel_name: &'a Value,
     _activity_id: Option<&'a Value>,
-    custom_data: HashMap<&'a String, &'a Value>,
+    custom_data: BTreeMap<&'a String, &'a Value>,
 }
 
 impl<'a> WindowsEvent<'a> {
@@ -35,7 +36,7 @@ impl<'a> WindowsEvent<'a> {
         provider_name: &'a Value,
         channel_name: &'a Value,
         _activity_id: Option<&'a Value>,
-        custom_data: HashMap<&'a String, &'a Value>,
+        custom_data: BTreeMap<&'a String, &'a Value>,
     ) -> Self {
         Self {
             event_record_id,
@@ -50,6 +51,56 @@ impl<'a> WindowsEvent<'a> {
         }
     }
 
+    /// creates an event from the JSON representation of an evtx record, as
+    /// it is created by the `evtx` crate. The content of `EventData` or
+    /// `UserData` is stored in the custom data of the event
+    pub fn from_record_data(
+        event_record_id: u64,
+        timestamp: DateTime<Utc>,
+        data: &'a Value,
+    ) -> anyhow::Result<Self> {
+        let event = &data["Event"];
+        let system = &event["System"];
+        let event_id = match &system["EventID"] {
+            Value::Object(event_id) => event_id.get("#text").unwrap_or(&Value::Null),
+            event_id => event_id,
+        };
+        let event_id = match event_id.as_u64() {
+            Some(event_id) => event_id,
+            None => bail!("invalid event id in record {event_record_id}: {event_id}"),
+        };
+
+        let provider_name = &system["Provider"]["#attributes"]["Name"];
+        let channel_name = &system["Channel"];
+        if !(provider_name.is_string() && channel_name.is_string()) {
+            bail!("missing provider or channel name in record {event_record_id}");
+        }
+
+        let custom_data = match event {
+            Value::Object(contents) => contents
+                .iter()
+                .filter(|(key, _)| *key != "System" && *key != "#attributes")
+                .collect(),
+            _ => BTreeMap::new(),
+        };
+
+        Ok(Self::new(
+            event_record_id,
+            timestamp,
+            event_id,
+            EventLevel::try_from(&system["Level"])?,
+            &system["Computer"],
+            provider_name,
+            channel_name,
+            system["Correlation"]["#attributes"].get("ActivityId"),
+            custom_data,
+        ))
+    }
+
+    pub fn event_id(&self) -> u64 {
+        self.event_id
+    }
+
     fn into_builder(self) -> anyhow::Result<EcsBuilder> {
         let event = Event::default()
             .with_kind(Kind::Event)
@@ -81,3 +132,45 @@ impl IntoIterator for WindowsEvent<'_> {
         vec![self.into_builder()].into_iter()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::WindowsEvent;
+    use crate::es4forensics::TimelineObject;
+
+    #[test]
+    fn convert_record_data() {
+        let data = json!({
+            "Event": {
+                "#attributes": {"xmlns": "http://schemas.microsoft.com/win/2004/08/events/event"},
+                "System": {
+                    "Provider": {"#attributes": {"Name": "Microsoft-Windows-Security-Auditing"}},
+                    "EventID": 4624,
+                    "Level": 0,
+                    "EventRecordID": 1234,
+                    "Channel": "Security",
+                    "Computer": "ws01.example.com"
+                },
+                "EventData": {"TargetUserName": "jsmith", "LogonType": 3}
+            }
+        });
+        let timestamp = "2023-01-02T03:04:05Z".parse().unwrap();
+        let event = WindowsEvent::from_record_data(1234, timestamp, &data).unwrap();
+        assert_eq!(event.event_id(), 4624);
+
+        let documents: Vec<_> = event.into_values().collect();
+        assert_eq!(documents.len(), 1);
+        assert_eq!(documents[0]["host"]["name"], "ws01.example.com");
+        assert_eq!(documents[0]["event"]["code"], 4624);
+        assert_eq!(documents[0]["event"]["sequence"], "1234");
+        assert_eq!(
+            documents[0]["event"]["custom_data"]["EventData"]["TargetUserName"],
+            "jsmith"
+        );
+
+        let invalid = json!({"Event": {"System": {"EventID": "abc"}}});
+        assert!(WindowsEvent::from_record_data(1, timestamp, &invalid).is_err());
+    }
+}
@@ -0,0 +1,57 @@
+use std::fmt::Display;
+
+use clap::ValueEnum;
+use serde_json::Value;
+
+/// the search engine which stores the index
+#[derive(ValueEnum, Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum Flavor {
+    #[default]
+    Elasticsearch,
+    Opensearch,
+}
+
+impl Flavor {
+    /// detects the flavor from the response to `GET /`. OpenSearch states
+    /// its name as `version.distribution`, which is missing in elasticsearch
+    pub fn from_info(info: &Value) -> Self {
+        match info["version"]["distribution"].as_str() {
+            Some(distribution) if distribution.eq_ignore_ascii_case("opensearch") => {
+                Self::Opensearch
+            }
+            _ => Self::Elasticsearch,
+        }
+    }
+}
+
+impl Display for Flavor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Flavor::Elasticsearch => write!(f, "elasticsearch"),
+            Flavor::Opensearch => write!(f, "opensearch"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::Flavor;
+
+    #[test]
+    fn detect_flavor() {
+        let opensearch = json!({
+            "name": "node-1",
+            "version": {"distribution": "opensearch", "number": "2.11.0"},
+            "tagline": "The OpenSearch Project: https://opensearch.org/"
+        });
+        let elasticsearch = json!({
+            "name": "node-1",
+            "version": {"number": "8.11.0", "build_flavor": "default"},
+            "tagline": "You Know, for Search"
+        });
+        assert_eq!(Flavor::from_info(&opensearch), Flavor::Opensearch);
+        assert_eq!(Flavor::from_info(&elasticsearch), Flavor::Elasticsearch);
+    }
+}
@@ -1,3 +1,9 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::{BufWriter, Write},
+    path::Path,
+};
+
 use anyhow::{bail, Result};
 use base64::{engine::general_purpose, Engine};
 use elasticsearch::{BulkOperation, BulkParts, Elasticsearch};
@@ -5,11 +11,17 @@ use serde_json::Value;
 use sha2::{Digest, Sha256};
 use tokio_async_drop::tokio_async_drop;
 
+use crate::es4forensics::bulk::{
+    inspect_bulk_response, is_retryable_status, BulkItemResult, BulkStatistics, RetryPolicy,
+};
 use crate::es4forensics::ecs::TimelineObject;
 
 struct ElasticDocument {
     id: String,
     content: Value,
+
+    /// size of the serialized content in bytes
+    size: usize,
 }
 
 impl From<ElasticDocument> for (String, Value) {
@@ -20,22 +32,44 @@ impl From<ElasticDocument> for (String, Value) {
 
 impl From<Value> for ElasticDocument {
     fn from(val: Value) -> Self {
-        let mut hasher: Sha256 = Sha256::new();
-        hasher.update(val.to_string());
-        let result = hasher.finalize();
+        let serialized = val.to_string();
         Self {
-            id: general_purpose::URL_SAFE_NO_PAD.encode(result),
+            id: document_id(&serialized),
             content: val,
+            size: serialized.len(),
         }
     }
 }
 
+impl ElasticDocument {
+    /// creates a document whose id is derived from `key` instead of its content
+    fn with_key(val: Value, key: &str) -> Self {
+        Self {
+            id: document_id(key),
+            size: val.to_string().len(),
+            content: val,
+        }
+    }
+}
+
+fn document_id(key: &str) -> String {
+    let mut hasher: Sha256 = Sha256::new();
+    hasher.update(key);
+    general_purpose::URL_SAFE_NO_PAD.encode(hasher.finalize())
+}
+
 pub struct Index {
     name: String,
     client: Elasticsearch,
 
     cache_size: usize,
+    max_payload_size: usize,
+    payload_size: usize,
     document_cache: Option<Vec<ElasticDocument>>,
+
+    retry_policy: RetryPolicy,
+    dead_letter_file: Option<BufWriter<File>>,
+    statistics: BulkStatistics,
 }
 
 impl Index {
@@ -44,7 +78,12 @@ impl Index {
             name,
             client,
             cache_size: 10000,
+            max_payload_size: 10 * 1024 * 1024,
+            payload_size: 0,
             document_cache: Some(Vec::new()),
+            retry_policy: RetryPolicy::default(),
+            dead_letter_file: None,
+            statistics: BulkStatistics::default(),
         }
     }
 
@@ -68,11 +107,28 @@ impl Index {
     }
 
     pub async fn add_bulk_document(&mut self, document: Value) -> Result<()> {
+        self.add_elastic_document(ElasticDocument::from(document))
+            .await
+    }
+
+    /// adds a document whose id is derived from `key`, e.g. the source and
+    /// the record id, instead of its content. This way, importing the same
+    /// record twice does not create a duplicate, even if the document
+    /// itself differs, e.g. because different labels have been added
+    pub async fn add_bulk_document_with_key(&mut self, document: Value, key: &str) -> Result<()> {
+        self.add_elastic_document(ElasticDocument::with_key(document, key))
+            .await
+    }
+
+    async fn add_elastic_document(&mut self, document: ElasticDocument) -> Result<()> {
         if let Some(c) = self.document_cache.as_mut() {
-            c.push(document.into())
+            self.payload_size += document.size;
+            c.push(document)
         }
 
-        if self.document_cache.as_ref().unwrap().len() >= self.cache_size {
+        if self.document_cache.as_ref().unwrap().len() >= self.cache_size
+            || self.payload_size >= self.max_payload_size
+        {
             self.flush().await
         } else {
             Ok(())
@@ -80,51 +136,115 @@ impl Index {
     }
 
     pub async fn flush(&mut self) -> Result<()> {
-        match self.document_cache.as_ref() {
-            None => log::trace!("There is no document cache"),
-
-            Some(document_cache) => {
-                log::info!(
-                    "flushing document cache with {} entries",
-                    document_cache.len()
-                );
-                if document_cache.is_empty() {
-                    log::trace!("Document cache is empty");
-                } else {
-                    let parts = BulkParts::Index(&self.name);
-
-                    let item_count = self.document_cache.as_ref().unwrap().len();
-                    let items: Vec<BulkOperation<Value>> = self
-                        .document_cache
-                        .replace(Vec::new())
-                        .unwrap()
-                        .into_iter()
-                        .map(|v| {
-                            let (id, val) = v.into();
-                            BulkOperation::create(id, val).into()
-                        })
-                        .collect();
-                    let bulk = self.client.bulk(parts).body(items);
-
-                    let response = bulk.send().await?;
-
-                    if !response.status_code().is_success() {
-                        log::error!(
-                            "error {} while sending bulk operation",
-                            response.status_code()
-                        );
-                        log::error!("{}", response.text().await?);
-                        bail!("error while sending bulk operation");
-                    } else {
-                        let json: Value = response.json().await?;
-                        if json["errors"].as_bool().unwrap() {
-                            log::error!("error while writing to elasticsearch: {json}");
-                        } else {
-                            log::trace!("successfully wrote {item_count} items");
-                        }
+        let mut documents = match self.document_cache.as_mut() {
+            None => {
+                log::trace!("There is no document cache");
+                return Ok(());
+            }
+            Some(document_cache) => std::mem::take(document_cache),
+        };
+        self.payload_size = 0;
+
+        log::info!("flushing document cache with {} entries", documents.len());
+        if documents.is_empty() {
+            log::trace!("Document cache is empty");
+            return Ok(());
+        }
+
+        let mut retry = 0;
+        loop {
+            let results = self.send_bulk(&documents).await?;
+
+            let mut rejected_documents = Vec::new();
+            for (document, result) in documents.into_iter().zip(results) {
+                match result {
+                    BulkItemResult::Indexed => self.statistics.indexed += 1,
+                    BulkItemResult::Retryable(_) if retry < self.retry_policy.max_retries => {
+                        rejected_documents.push(document)
+                    }
+                    BulkItemResult::Retryable(reason) | BulkItemResult::Failed(reason) => {
+                        self.reject(document, &reason)?
                     }
                 }
             }
+
+            if rejected_documents.is_empty() {
+                break;
+            }
+
+            let delay = self.retry_policy.delay(retry);
+            log::warn!(
+                "{} documents have been rejected, retrying in {} ms",
+                rejected_documents.len(),
+                delay.as_millis()
+            );
+            tokio::time::sleep(delay).await;
+
+            self.statistics.retried += rejected_documents.len();
+            documents = rejected_documents;
+            retry += 1;
+        }
+
+        if let Some(dead_letter_file) = self.dead_letter_file.as_mut() {
+            dead_letter_file.flush()?;
+        }
+        Ok(())
+    }
+
+    /// sends a bulk request and returns the result of every document. If the
+    /// whole request failed temporarily, every document is marked as retryable
+    async fn send_bulk(&self, documents: &[ElasticDocument]) -> Result<Vec<BulkItemResult>> {
+        let parts = BulkParts::Index(&self.name);
+        let items: Vec<BulkOperation<Value>> = documents
+            .iter()
+            .map(|d| BulkOperation::create(d.id.clone(), d.content.clone()).into())
+            .collect();
+
+        let response = match self.client.bulk(parts).body(items).send().await {
+            Ok(response) => response,
+            Err(why) => {
+                log::warn!("error while sending bulk operation: {why}");
+                return Ok(vec![
+                    BulkItemResult::Retryable(why.to_string());
+                    documents.len()
+                ]);
+            }
+        };
+
+        let status_code = response.status_code();
+        if is_retryable_status(status_code.as_u16()) {
+            log::warn!("error {status_code} while sending bulk operation");
+            Ok(vec![
+                BulkItemResult::Retryable(format!(
+                    "status {status_code}"
+                ));
+                documents.len()
+            ])
+        } else if !status_code.is_success() {
+            log::error!("error {status_code} while sending bulk operation");
+            log::error!("{}", response.text().await?);
+            bail!("error while sending bulk operation");
+        } else {
+            let json: Value = response.json().await?;
+            let results = inspect_bulk_response(&json, documents.len());
+            log::trace!(
+                "successfully wrote {} items",
+                results
+                    .iter()
+                    .filter(|r| **r == BulkItemResult::Indexed)
+                    .count()
+            );
+            Ok(results)
+        }
+    }
+
+    /// writes a document which could not be indexed to the dead letter file, if any
+    fn reject(&mut self, document: ElasticDocument, reason: &str) -> Result<()> {
+        self.statistics.failed += 1;
+        log::error!("document {} has been rejected: {reason}", document.id);
+        if let Some(dead_letter_file) = self.dead_letter_file.as_mut() {
+            serde_json::to_writer(&mut *dead_letter_file, &document.content)?;
+            writeln!(dead_letter_file)?;
         }
         Ok(())
     }
@@ -136,6 +256,40 @@ impl Index {
         self.cache_size = cache_size;
         Ok(())
     }
+
+    pub async fn set_max_payload_size(&mut self, max_payload_size: usize) -> Result<()> {
+        if self.max_payload_size > max_payload_size {
+            self.flush().await?;
+        }
+        self.max_payload_size = max_payload_size;
+        Ok(())
+    }
+
+    pub fn set_retry_policy(&mut self, retry_policy: RetryPolicy) {
+        self.retry_policy = retry_policy;
+    }
+
+    /// documents which have been rejected by elasticsearch are appended to
+    /// this file, one JSON document per line
+    pub fn set_dead_letter_file(&mut self, path: &Path) -> Result<()> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        self.dead_letter_file = Some(BufWriter::new(file));
+        Ok(())
+    }
+
+    /// the number of documents which have not been sent yet. If this is `0`,
+    /// every document which has been added has either been indexed or rejected
+    pub fn pending_documents(&self) -> usize {
+        self.document_cache
+            .as_ref()
+            .map(Vec::len)
+            .unwrap_or_default()
+    }
+
+    /// the number of documents which have been indexed, rejected or retried so far
+    pub fn statistics(&self) -> &BulkStatistics {
+        &self.statistics
+    }
 }
 
 impl Drop for Index {
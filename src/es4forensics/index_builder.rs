@@ -2,17 +2,22 @@ use anyhow::{anyhow, Result};
 use elasticsearch::{
     auth::Credentials,
     cat::CatIndicesParts,
-    cert::CertificateValidation,
+    cert::{Certificate, CertificateValidation},
     http::{
+        headers::HeaderMap,
         transport::{SingleNodeConnectionPool, TransportBuilder},
-        Url,
+        Method, Url,
     },
-    indices::IndicesCreateParts,
+    indices::{IndicesCreateParts, IndicesPutIndexTemplateParts},
     Elasticsearch,
 };
 use serde_json::{json, Value};
 
-use crate::es4forensics::{Protocol, index::Index};
+use crate::es4forensics::{
+    index::Index,
+    index_template::{index_mappings, index_template},
+    Flavor, Protocol,
+};
 
 pub struct IndexBuilder {
     host: Option<String>,
@@ -20,7 +25,9 @@ pub struct IndexBuilder {
     protocol: Protocol,
     index_name: String,
     do_certificate_validation: bool,
+    ca_certificate: Option<Vec<u8>>,
     credentials: Option<Credentials>,
+    flavor: Option<Flavor>,
 }
 
 const DEFAULT_HOST: &str = "localhost";
@@ -38,7 +45,9 @@ impl IndexBuilder {
             protocol: Protocol::default(),
             index_name,
             do_certificate_validation: true,
+            ca_certificate: None,
             credentials: None,
+            flavor: None,
         }
     }
 
@@ -57,11 +66,24 @@ impl IndexBuilder {
         self
     }
 
+    /// validates the certificate of the server using the PEM encoded CA
+    /// certificate(s), instead of the CAs trusted by the operating system
+    pub fn with_ca_certificate(mut self, pem: Vec<u8>) -> Self {
+        self.ca_certificate = Some(pem);
+        self
+    }
+
     pub fn with_credentials(mut self, credentials: Credentials) -> Self {
         self.credentials = Some(credentials);
         self
     }
 
+    /// skips the detection of the flavor, e.g. if `GET /` is not permitted
+    pub fn with_flavor(mut self, flavor: Flavor) -> Self {
+        self.flavor = Some(flavor);
+        self
+    }
+
     pub fn host(&self) -> &str {
         match self.host.as_ref() {
             Some(h) => h,
@@ -81,6 +103,133 @@ impl IndexBuilder {
         self.client_has_index(&client).await
     }
 
+    /// the flavor of the server, which is [`Flavor::Elasticsearch`] unless it
+    /// has been set or detected otherwise
+    pub fn flavor(&self) -> Flavor {
+        self.flavor.unwrap_or_default()
+    }
+
+    /// determines whether the server runs elasticsearch or OpenSearch, using
+    /// the response to `GET /`. Nothing is sent if the flavor has already been set
+    pub async fn detect_flavor(&mut self) -> Result<Flavor> {
+        if let Some(flavor) = self.flavor {
+            return Ok(flavor);
+        }
+
+        let client = self.create_client()?;
+        let response = client
+            .info()
+            .send()
+            .await
+            .map_err(|why| self.connection_error(why))?;
+
+        let flavor = match response.status_code().as_u16() {
+            401 | 403 => return Err(self.authentication_error()),
+            _ => Flavor::from_info(&response.error_for_status_code()?.json::<Value>().await?),
+        };
+        log::info!("detected {flavor} at {}:{}", self.host(), self.port());
+        self.flavor = Some(flavor);
+        Ok(flavor)
+    }
+
+    /// sends a cheap authenticated request to elasticsearch, to make sure that
+    /// the credentials are accepted before any data is sent
+    pub async fn check_credentials(&self) -> Result<()> {
+        let client = self.create_client()?;
+        let response = match self.flavor() {
+            Flavor::Elasticsearch => client.security().authenticate().send().await,
+            Flavor::Opensearch => {
+                client
+                    .send(
+                        Method::Get,
+                        "/_plugins/_security/authinfo",
+                        HeaderMap::new(),
+                        Option::<&()>::None,
+                        Option::<()>::None,
+                        None,
+                    )
+                    .await
+            }
+        }
+        .map_err(|why| self.connection_error(why))?;
+
+        match response.status_code().as_u16() {
+            401 | 403 => Err(self.authentication_error()),
+
+            // OpenSearch does not know this endpoint if its security plugin is disabled
+            400 | 404 if self.flavor() == Flavor::Opensearch => {
+                log::warn!("the security plugin of OpenSearch seems to be disabled");
+                Ok(())
+            }
+            _ => {
+                response.error_for_status_code_ref()?;
+                let body = response.json::<Value>().await?;
+                let username = match self.flavor() {
+                    Flavor::Elasticsearch => &body["username"],
+                    Flavor::Opensearch => &body["user_name"],
+                };
+                log::info!(
+                    "authenticated as '{}' using {}",
+                    username.as_str().unwrap_or_default(),
+                    self.authentication_method()
+                );
+                Ok(())
+            }
+        }
+    }
+
+    fn authentication_error(&self) -> anyhow::Error {
+        anyhow!(
+            "authentication at {} failed using {}",
+            self.flavor(),
+            self.authentication_method()
+        )
+    }
+
+    /// a description of the credentials, which does not contain any secrets
+    fn authentication_method(&self) -> &'static str {
+        match self.credentials {
+            None => "no credentials",
+            Some(Credentials::Basic(_, _)) => "basic authentication",
+            Some(Credentials::ApiKey(_, _)) => "an API key",
+            Some(Credentials::Bearer(_)) => "a bearer token",
+            Some(_) => "a client certificate",
+        }
+    }
+
+    /// creates or updates an index template, which makes sure that the index
+    /// gets the correct field mappings if it is created implicitly while
+    /// importing documents. Existing indices are not changed by this
+    pub async fn put_index_template(&self) -> Result<()> {
+        let client = self.create_client()?;
+        let template_name = format!("{}_template", self.index_name);
+        log::info!("create or update index template '{template_name}'");
+
+        let response = client
+            .indices()
+            .put_index_template(IndicesPutIndexTemplateParts::Name(&template_name))
+            .body(index_template(&self.index_name))
+            .send()
+            .await
+            .map_err(|why| self.connection_error(why))?;
+
+        if let Err(why) = response.error_for_status_code_ref() {
+            log::error!(
+                "error while creating index template: {}",
+                response.text().await?
+            );
+            return Err(anyhow!(why));
+        }
+
+        if self.client_has_index(&client).await? {
+            log::warn!(
+                "index '{}' exists already, the index template will not change its mappings",
+                self.index_name
+            );
+        }
+        Ok(())
+    }
+
     pub async fn connect(self) -> Result<Index> {
         let client = self.create_client()?;
         Ok(Index::new(self.index_name, client))
@@ -91,45 +240,7 @@ impl IndexBuilder {
 
         if !self.client_has_index(&client).await? {
             log::info!("create index with mappings");
-            let index_body = json!({
-                "mappings": {
-                    "properties": {
-                        "@timestamp": {
-                            "type": "date",
-                            "format": "epoch_millis"
-                        },
-                        "tags": {
-                            "type": "keyword"
-                        },
-                        "file": {
-                            "properties": {
-                                "accessed": {
-                                    "type": "date",
-                                    "format": "epoch_millis"
-                                },
-                                "created": {
-                                    "type": "date",
-                                    "format": "epoch_millis"
-                                },
-                                "ctime": {
-                                    "type": "date",
-                                    "format": "epoch_millis"
-                                },
-                                "mtime": {
-                                    "type": "date",
-                                    "format": "epoch_millis"
-                                },
-                                "macb_short": {
-                                    "type": "keyword"
-                                },
-                                "macb_long": {
-                                    "type": "keyword"
-                                }
-                            }
-                        }
-                    }
-                }
-            });
+            let index_body = json!({ "mappings": index_mappings() });
             let parts = IndicesCreateParts::Index(&self.index_name);
             let response = client
                 .indices()
@@ -194,12 +305,20 @@ impl IndexBuilder {
     fn create_client(&self) -> Result<Elasticsearch> {
         let url = Url::parse(&format!("{}://{}:{}", self.protocol, self.host(), self.port()))?;
         let conn_pool = SingleNodeConnectionPool::new(url);
+        let cert_validation = match (self.do_certificate_validation, &self.ca_certificate) {
+            (false, _) => CertificateValidation::None,
+            (true, None) => CertificateValidation::Default,
+            (true, Some(pem)) => CertificateValidation::Full(
+                Certificate::from_pem(pem)
+                    .map_err(|why| anyhow!("invalid CA certificate: {why}"))?,
+            ),
+        };
+        // OpenSearch does not expect the meta header, which describes the
+        // client to elasticsearch, so it is only sent if we know that we
+        // are talking to elasticsearch
         let mut transport_builder = TransportBuilder::new(conn_pool)
-            .cert_validation(if self.do_certificate_validation {
-                CertificateValidation::Default
-            } else {
-                CertificateValidation::None
-            })
+            .cert_validation(cert_validation)
+            .enable_meta_header(self.flavor == Some(Flavor::Elasticsearch))
             .disable_proxy();
 
         if let Some(credentials) = &self.credentials {
@@ -209,6 +328,31 @@ impl IndexBuilder {
         Ok(Elasticsearch::new(transport))
     }
 
+    /// converts an error which occurred while sending a request into a
+    /// readable message, which names the server
+    fn connection_error(&self, why: elasticsearch::Error) -> anyhow::Error {
+        let mut reason = why.to_string();
+        let mut source = std::error::Error::source(&why);
+        while let Some(inner) = source {
+            reason = inner.to_string();
+            source = inner.source();
+        }
+
+        if reason.to_lowercase().contains("certificate") {
+            anyhow!(
+                "the TLS certificate of {}:{} could not be verified: {reason}",
+                self.host(),
+                self.port()
+            )
+        } else {
+            anyhow!(
+                "unable to connect to {}:{}: {reason}",
+                self.host(),
+                self.port()
+            )
+        }
+    }
+
     async fn client_has_index(&self, client: &Elasticsearch) -> Result<bool> {
         log::info!("test if index '{}' exists", self.index_name);
 
@@ -217,7 +361,8 @@ impl IndexBuilder {
             .indices(CatIndicesParts::Index(&["*"]))
             .format("json")
             .send()
-            .await?;
+            .await
+            .map_err(|why| self.connection_error(why))?;
         response.error_for_status_code_ref()?;
 
         if response.content_length().unwrap_or(0) == 0 {
@@ -0,0 +1,134 @@
+use serde_json::{json, Value};
+
+/// timestamps are written as milliseconds since the epoch by
+/// [`crate::es4forensics::EcsBuilder`], but as RFC 3339 strings by `mactime2`
+const DATE_FORMAT: &str = "strict_date_optional_time||epoch_millis";
+
+/// the mappings of all fields which are written by the tools of this crate.
+/// Fields which are not mentioned here are mapped dynamically, with the
+/// exception of record ids, SIDs and hashes, which are recognized by their name
+pub fn index_mappings() -> Value {
+    let date = json!({"type": "date", "format": DATE_FORMAT});
+    json!({
+        "dynamic_templates": [
+            {
+                "record_ids": {
+                    "match_pattern": "regex",
+                    "match": "(?i).*record_?id",
+                    "mapping": {"type": "long"}
+                }
+            },
+            {
+                "sids": {
+                    "match_pattern": "regex",
+                    "match": "(?i).*sid",
+                    "mapping": {"type": "keyword"}
+                }
+            },
+            {
+                "hashes": {
+                    "match_pattern": "regex",
+                    "match": "(?i)md5|sha1|sha256|imphash|.*hash(es)?",
+                    "mapping": {"type": "keyword"}
+                }
+            }
+        ],
+        "properties": {
+            "@timestamp": date,
+            "message": {"type": "text"},
+            "tags": {"type": "keyword"},
+            "labels": {
+                "properties": {
+                    "case_id": {"type": "keyword"},
+                    "host": {"type": "keyword"}
+                }
+            },
+            "ecs": {
+                "properties": {
+                    "version": {"type": "keyword"}
+                }
+            },
+            "event": {
+                "properties": {
+                    "action": {"type": "keyword"},
+                    "code": {"type": "long"},
+                    "module": {"type": "keyword"},
+                    "provider": {"type": "keyword"},
+                    "sequence": {"type": "long"},
+                    "severity": {"type": "long"}
+                }
+            },
+            "host": {
+                "properties": {
+                    "name": {"type": "keyword"}
+                }
+            },
+            "file": {
+                "properties": {
+                    "accessed": date,
+                    "created": date,
+                    "ctime": date,
+                    "mtime": date,
+                    "directory": {"type": "keyword"},
+                    "extension": {"type": "keyword"},
+                    "gid": {"type": "long"},
+                    "uid": {"type": "long"},
+                    "inode": {"type": "keyword"},
+                    "mode": {"type": "keyword"},
+                    "name": {"type": "keyword"},
+                    "path": {"type": "keyword"},
+                    "size": {"type": "long"},
+                    "target_path": {"type": "keyword"},
+                    "type": {"type": "keyword"},
+                    "macb_short": {"type": "keyword"},
+                    "macb_long": {"type": "keyword"}
+                }
+            },
+            "macb": {
+                "properties": {
+                    "m": {"type": "boolean"},
+                    "a": {"type": "boolean"},
+                    "c": {"type": "boolean"},
+                    "b": {"type": "boolean"}
+                }
+            }
+        }
+    })
+}
+
+/// an index template which applies [`index_mappings`] to the index
+/// `index_name`, if the index is created implicitly by importing documents
+pub fn index_template(index_name: &str) -> Value {
+    json!({
+        "index_patterns": [index_name],
+        "priority": 200,
+        "template": {
+            "mappings": index_mappings()
+        },
+        "_meta": {
+            "created_by": env!("CARGO_PKG_NAME"),
+            "version": env!("CARGO_PKG_VERSION")
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::index_template;
+
+    #[test]
+    fn field_types() {
+        let template = index_template("timeline");
+        let properties = &template["template"]["mappings"]["properties"];
+
+        assert_eq!(template["index_patterns"][0], "timeline");
+        assert_eq!(properties["@timestamp"]["type"], "date");
+        assert_eq!(properties["file"]["properties"]["mtime"]["type"], "date");
+        assert_eq!(properties["message"]["type"], "text");
+        assert_eq!(
+            properties["event"]["properties"]["sequence"]["type"],
+            "long"
+        );
+        assert_eq!(properties["macb"]["properties"]["b"]["type"], "boolean");
+    }
+}
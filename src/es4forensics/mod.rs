@@ -3,7 +3,8 @@
 //! an elasticsearch index.
 //! 
 //! # Creating Indices
-//! ```
+#![cfg_attr(feature = "elasticsearch", doc = "```")]
+#![cfg_attr(not(feature = "elasticsearch"), doc = "```ignore")]
 //! use dfir_toolkit::es4forensics::IndexBuilder;
 //! use dfir_toolkit::es4forensics::WithHost;
 //! use elasticsearch::auth::Credentials;
@@ -28,7 +29,8 @@
 //! For example, consider we have a line from a bodyfile. We need to convert this
 //! into a [`ecs::objects::PosixFile`]-Object, which can then be added to an Index:
 //! 
-//! ```
+#![cfg_attr(feature = "elasticsearch", doc = "```")]
+#![cfg_attr(not(feature = "elasticsearch"), doc = "```ignore")]
 //! use dfir_toolkit::es4forensics::objects::PosixFile;
 //!# use dfir_toolkit::es4forensics::Index;
 //! 
@@ -54,16 +56,13 @@
 //! use dfir_toolkit::es4forensics::Timestamp;
 //! use dfir_toolkit::es4forensics::TimelineObject;
 //! use serde_json::Value;
-//!# use dfir_toolkit::es4forensics::Index;
 //! 
-//!# fn foo(mut index: Index) {
 //! let str_line = "0|/Users/Administrator ($FILE_NAME)|93552-48-2|d/drwxrwxrwx|0|0|92|1577092511|1577092511|1577092511|-1";
 //! let posix_file: PosixFile = str_line.try_into().unwrap();
 //! 
 //! for json_value in posix_file.into_values() {
 //!     println!("{json_value}");
 //! }
-//!# }
 //! ```
 
 #[cfg(feature="elasticsearch")]
@@ -72,11 +71,17 @@ mod index;
 #[cfg(feature="elasticsearch")]
 mod index_builder;
 
+#[cfg(feature="elasticsearch")]
+mod bulk;
+
+mod index_template;
+
 mod timestamp;
 mod utils;
 mod ecs;
 
 mod protocol;
+mod flavor;
 mod stream_source;
 
 #[cfg(feature="elasticsearch")]
@@ -84,7 +89,12 @@ pub use index::*;
 
 #[cfg(feature="elasticsearch")]
 pub use index_builder::*;
+
+#[cfg(feature="elasticsearch")]
+pub use bulk::*;
+pub use index_template::*;
 pub use timestamp::*;
 pub use ecs::*;
 pub use protocol::*;
+pub use flavor::*;
 pub use stream_source::*;
\ No newline at end of file
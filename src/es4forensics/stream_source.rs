@@ -4,7 +4,6 @@ use std::{
     io::{BufRead, BufReader, Read},
 };
 
-#[cfg(feature = "gzip")]
 use flate2::read::GzDecoder;
 
 pub enum StreamSource {
@@ -18,18 +17,12 @@ impl StreamSource {
             Ok(StreamSource::Stdin)
         } else {
             let file = BufReader::new(File::open(filename)?);
-
-            #[cfg(not(feature = "gzip"))]
-            let reader: Box<dyn BufRead + Send> = Box::new(file);
-
-            #[cfg(feature = "gzip")]
             let reader = Self::open_gzip(filename, file);
 
             Ok(StreamSource::File(reader))
         }
     }
 
-    #[cfg(feature = "gzip")]
     fn open_gzip<R: Read + Send + 'static>(filename: &str, file: R) -> Box<dyn BufRead + Send> {
 
         if filename.ends_with(".gz") {
@@ -0,0 +1,210 @@
+use std::io::Cursor;
+use std::sync::Arc;
+
+use evtx::binxml::deserializer::BinXmlDeserializer;
+use evtx::err::{EvtxError, Result};
+use evtx::{EvtxChunk, EvtxChunkHeader, EvtxRecord, EvtxRecordHeader};
+
+/// size of the header of a record
+const RECORD_HEADER_SIZE: u64 = 24;
+
+/// size of the header of a chunk, where the first record starts
+pub(crate) const CHUNK_HEADER_SIZE: u64 = 512;
+
+/// an entry of a chunk, which is either a record or a range of bytes
+/// which could not be parsed. All offsets are relative to the begin of the
+/// chunk.
+pub enum ChunkEntry<'c> {
+    Record {
+        record: EvtxRecord<'c>,
+        begin: u64,
+        end: u64,
+    },
+
+    /// a record with a valid header, whose contents could not be parsed
+    InvalidRecord {
+        record_id: u64,
+        begin: u64,
+        end: u64,
+        why: EvtxError,
+    },
+
+    /// a range of bytes without a valid record header
+    Damaged { begin: u64, end: u64 },
+}
+
+/// a record header or a damaged range in the raw data of a chunk
+pub(crate) enum RecordSlot {
+    Record {
+        header: EvtxRecordHeader,
+        begin: u64,
+        end: u64,
+    },
+    Damaged {
+        begin: u64,
+        end: u64,
+    },
+}
+
+/// iterates over the record headers in the raw data of a chunk, without
+/// parsing the records themselves. The iteration does not stop at an invalid
+/// record header. Instead, the chunk is searched for the next valid record
+/// header, and the damaged range is returned.
+pub(crate) struct RecordSlots<'d> {
+    data: &'d [u8],
+    offset: u64,
+    end: u64,
+    last_record_id: u64,
+    exhausted: bool,
+}
+
+impl<'d> RecordSlots<'d> {
+    pub(crate) fn new(data: &'d [u8], header: &EvtxChunkHeader) -> Self {
+        Self {
+            data,
+            offset: CHUNK_HEADER_SIZE,
+            end: u64::from(header.free_space_offset).min(data.len() as u64),
+            last_record_id: header.last_event_record_id,
+            exhausted: false,
+        }
+    }
+}
+
+impl Iterator for RecordSlots<'_> {
+    type Item = RecordSlot;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.exhausted || self.offset + RECORD_HEADER_SIZE > self.end {
+            return None;
+        }
+
+        let begin = self.offset;
+        match header_at(self.data, begin, self.end) {
+            Some(header) => {
+                self.offset += u64::from(header.data_size);
+                if header.event_record_id == self.last_record_id {
+                    self.exhausted = true;
+                }
+                Some(RecordSlot::Record {
+                    header,
+                    begin,
+                    end: self.offset,
+                })
+            }
+            None => {
+                self.offset = ((begin + 1)..self.end)
+                    .find(|offset| header_at(self.data, *offset, self.end).is_some())
+                    .unwrap_or(self.end);
+                Some(RecordSlot::Damaged {
+                    begin,
+                    end: self.offset,
+                })
+            }
+        }
+    }
+}
+
+/// returns the header of the record at `offset`, if there is a record
+/// which fits completely into the chunk before `end`
+fn header_at(data: &[u8], offset: u64, end: u64) -> Option<EvtxRecordHeader> {
+    if offset + RECORD_HEADER_SIZE > end {
+        return None;
+    }
+    let mut cursor = Cursor::new(&data[offset as usize..]);
+    let header = EvtxRecordHeader::from_reader(&mut cursor).ok()?;
+    let size = u64::from(header.data_size);
+    if size < RECORD_HEADER_SIZE + 4 || offset + size > end {
+        return None;
+    }
+
+    // the size is repeated at the end of the record
+    let copy_offset = (offset + size - 4) as usize;
+    let size_copy = u32::from_le_bytes(data[copy_offset..copy_offset + 4].try_into().ok()?);
+    (size_copy == header.data_size).then_some(header)
+}
+
+/// Windows updates the header of a chunk only from time to time, so the
+/// header of the last chunk of a file which has not been closed properly
+/// does not contain the most recent records. This extends `header` by all
+/// valid records which directly follow the last known record, and returns
+/// the number of records which have been recovered.
+pub(crate) fn recover_records(data: &[u8], header: &mut EvtxChunkHeader) -> u64 {
+    let mut offset = u64::from(header.free_space_offset);
+    let mut recovered = 0;
+    while let Some(record) = header_at(data, offset, data.len() as u64) {
+        // a record which is older than the last known record is a leftover
+        // of a previous use of the chunk
+        if record.event_record_id <= header.last_event_record_id {
+            break;
+        }
+        header.last_event_record_id = record.event_record_id;
+        header.last_event_record_data_offset = offset as u32;
+        offset += u64::from(record.data_size);
+        recovered += 1;
+    }
+
+    if recovered > 0 {
+        header.free_space_offset = offset as u32;
+        header.last_event_record_number += recovered;
+    }
+    recovered
+}
+
+/// iterates over the records of a chunk. In contrast to the iterator of the
+/// `evtx` crate, the iteration does not stop at an invalid record header.
+/// Instead, the chunk is searched for the next valid record header, and
+/// the damaged range is returned.
+pub struct ChunkRecords<'c> {
+    chunk: &'c EvtxChunk<'c>,
+    slots: RecordSlots<'c>,
+}
+
+impl<'c> ChunkRecords<'c> {
+    pub fn new(chunk: &'c EvtxChunk<'c>) -> Self {
+        Self {
+            chunk,
+            slots: RecordSlots::new(chunk.data, chunk.header),
+        }
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn parse_record(&self, header: &EvtxRecordHeader, offset: u64) -> Result<EvtxRecord<'c>> {
+        let deserializer = BinXmlDeserializer::init(
+            self.chunk.data,
+            offset + RECORD_HEADER_SIZE,
+            Some(self.chunk),
+            false,
+            self.chunk.settings.get_ansi_codec(),
+        );
+        let tokens = deserializer
+            .iter_tokens(Some(header.record_data_size()))?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(EvtxRecord {
+            chunk: self.chunk,
+            event_record_id: header.event_record_id,
+            timestamp: header.timestamp,
+            tokens,
+            settings: Arc::clone(&self.chunk.settings),
+        })
+    }
+}
+
+impl<'c> Iterator for ChunkRecords<'c> {
+    type Item = ChunkEntry<'c>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(match self.slots.next()? {
+            RecordSlot::Record { header, begin, end } => match self.parse_record(&header, begin) {
+                Ok(record) => ChunkEntry::Record { record, begin, end },
+                Err(why) => ChunkEntry::InvalidRecord {
+                    record_id: header.event_record_id,
+                    begin,
+                    end,
+                    why,
+                },
+            },
+            RecordSlot::Damaged { begin, end } => ChunkEntry::Damaged { begin, end },
+        })
+    }
+}
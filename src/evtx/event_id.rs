@@ -1,4 +1,5 @@
 use chrono::{DateTime, Utc, Duration};
+#[cfg(feature = "evtx")]
 use evtx::SerializedEvtxRecord;
 
 
@@ -37,6 +38,7 @@ impl PartialOrd for EventId {
 }
 
 impl EventId {
+    #[cfg(feature = "evtx")]
     #[allow(dead_code)]
     pub fn from<T>(record: &SerializedEvtxRecord<T>) -> Self {
         Self {
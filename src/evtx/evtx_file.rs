@@ -0,0 +1,466 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use evtx::err::{DeserializationError, EvtxError, InputError, Result};
+use evtx::{
+    EvtxChunkData, EvtxChunkHeader, EvtxRecord, EvtxRecordHeader, ParserSettings,
+    SerializedEvtxRecord,
+};
+
+use super::chunk_records::{recover_records, ChunkEntry, ChunkRecords, RecordSlot, RecordSlots};
+use super::mapped_file::MappedFile;
+
+/// size of the file header, after which the first chunk starts
+const FILE_HEADER_SIZE: u64 = 4096;
+
+const CHUNK_SIZE: u64 = 65536;
+
+const FILE_MAGIC: &[u8; 8] = b"ElfFile\0";
+
+/// offset of the flags in the file header
+const FLAGS_OFFSET: usize = 120;
+
+/// the file has not been closed properly
+const FLAG_DIRTY: u32 = 0x1;
+
+/// an evtx file, which is read by all tools which process evtx files. The
+/// file is mapped into memory, so that chunks can be accessed without
+/// copying them. The data of a chunk is only copied when its records are
+/// being parsed, and records are only deserialized on demand.
+///
+/// In contrast to [evtx::EvtxParser], damaged records don't stop the
+/// parsing of their chunk, and records which are missing in the chunk
+/// headers of a file which has not been closed properly (a "dirty" file)
+/// are recovered.
+///
+/// # Example
+/// ```
+/// use dfir_toolkit::evtx::EvtxFile;
+///
+/// let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/data/evtx/security.evtx");
+/// let file = EvtxFile::open(&path).unwrap();
+/// assert!(!file.is_dirty());
+///
+/// // reading the record headers does not parse the records
+/// let newest = file.record_headers().map(|header| header.timestamp).max();
+/// assert!(newest.is_some());
+///
+/// for record in file.records_json_value() {
+///     assert!(record.unwrap().data.is_object());
+/// }
+/// ```
+pub struct EvtxFile {
+    path: PathBuf,
+    data: MappedFile,
+    flags: u32,
+    settings: Arc<ParserSettings>,
+    progress: Option<Arc<ProgressFn>>,
+}
+
+type ProgressFn = dyn Fn(u64) + Send + Sync;
+
+impl EvtxFile {
+    /// maps the file at `path` into memory. Fails if the file cannot be
+    /// opened or if it has no valid evtx header
+    #[allow(clippy::result_large_err)]
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let data =
+            MappedFile::open(path).map_err(|why| InputError::failed_to_open_file(why, path))?;
+        Self::new(path.to_owned(), data)
+    }
+
+    /// uses the content of a file which has already been read, e.g. from
+    /// stdin. `path` is only used for messages.
+    #[allow(clippy::result_large_err)]
+    pub fn from_buffer(path: impl Into<PathBuf>, data: Vec<u8>) -> Result<Self> {
+        Self::new(path.into(), MappedFile::from(data))
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn new(path: PathBuf, data: MappedFile) -> Result<Self> {
+        if !data.starts_with(FILE_MAGIC) {
+            let mut magic = [0; 8];
+            let len = data.len().min(magic.len());
+            magic[..len].copy_from_slice(&data[..len]);
+            return Err(DeserializationError::InvalidEvtxFileHeaderMagic { magic }.into());
+        }
+        if (data.len() as u64) < FILE_HEADER_SIZE {
+            return Err(EvtxError::calculation_error(format!(
+                "the file header of '{}' is incomplete",
+                path.display()
+            )));
+        }
+
+        let flags = u32::from_le_bytes(
+            data[FLAGS_OFFSET..FLAGS_OFFSET + 4]
+                .try_into()
+                .expect("invalid size of the flags"),
+        );
+        let file = Self {
+            path,
+            data,
+            flags,
+            settings: Arc::new(ParserSettings::default()),
+            progress: None,
+        };
+        if file.is_dirty() {
+            log::info!(
+                "'{}' has not been closed properly, trying to recover records which are missing in the chunk headers",
+                file.path.display()
+            );
+        }
+        Ok(file)
+    }
+
+    pub fn with_configuration(mut self, settings: ParserSettings) -> Self {
+        self.settings = Arc::new(settings);
+        self
+    }
+
+    /// registers a function which is called with the number of bytes which
+    /// have been processed, whenever a chunk is read
+    pub fn with_progress(mut self, progress: impl Fn(u64) + Send + Sync + 'static) -> Self {
+        self.progress = Some(Arc::new(progress));
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// size of the file in bytes
+    pub fn size(&self) -> u64 {
+        self.data.len() as u64
+    }
+
+    /// returns `true` if the file is mapped into memory, and `false` if it
+    /// has been read completely
+    pub fn is_mapped(&self) -> bool {
+        self.data.is_mapped()
+    }
+
+    /// returns `true` if the file has not been closed properly
+    pub fn is_dirty(&self) -> bool {
+        self.flags & FLAG_DIRTY != 0
+    }
+
+    /// the number of chunks in the file, which is calculated from the size
+    /// of the file, because the number in the file header is not reliable
+    pub fn chunk_count(&self) -> u64 {
+        (self.size() - FILE_HEADER_SIZE) / CHUNK_SIZE
+    }
+
+    fn chunk_range(chunk_number: u64) -> std::ops::Range<usize> {
+        let begin = (FILE_HEADER_SIZE + chunk_number * CHUNK_SIZE) as usize;
+        begin..begin + CHUNK_SIZE as usize
+    }
+
+    fn chunk_bytes(&self, chunk_number: u64) -> &[u8] {
+        &self.data[Self::chunk_range(chunk_number)]
+    }
+
+    /// iterates over all chunks which are not empty. The data of every
+    /// chunk is copied only when the iteration reaches it.
+    pub fn chunks(&self) -> EvtxChunks<'_> {
+        EvtxChunks {
+            file: self,
+            next_chunk: 0,
+        }
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn read_chunk(&self, chunk_number: u64, data: &[u8]) -> Result<EvtxChunkData> {
+        let mut chunk =
+            EvtxChunkData::new(data.to_vec(), self.settings.should_validate_checksums()).map_err(
+                |why| EvtxError::FailedToParseChunk {
+                    chunk_id: chunk_number,
+                    source: why,
+                },
+            )?;
+        if self.is_dirty() {
+            self.recover_records(chunk_number, &chunk.data, &mut chunk.header);
+        }
+        Ok(chunk)
+    }
+
+    fn recover_records(&self, chunk_number: u64, data: &[u8], header: &mut EvtxChunkHeader) {
+        let recovered = recover_records(data, header);
+        if recovered > 0 {
+            log::info!(
+                "recovered {recovered} records of chunk {chunk_number} of '{}'",
+                self.path.display()
+            );
+        }
+    }
+
+    /// iterates over the headers of all records, which contain the id and
+    /// the timestamp of the records. This is much faster than parsing the
+    /// records. Chunks with an invalid header are skipped.
+    pub fn record_headers(&self) -> impl Iterator<Item = EvtxRecordHeader> + '_ {
+        (0..self.chunk_count()).flat_map(|chunk_number| {
+            let data = self.chunk_bytes(chunk_number);
+            let headers: Vec<_> = EvtxChunkHeader::from_reader(&mut std::io::Cursor::new(data))
+                .map(|mut header| {
+                    if self.is_dirty() {
+                        recover_records(data, &mut header);
+                    }
+                    RecordSlots::new(data, &header)
+                        .filter_map(|slot| match slot {
+                            RecordSlot::Record { header, .. } => Some(header),
+                            RecordSlot::Damaged { .. } => None,
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            self.data.release(Self::chunk_range(chunk_number));
+            headers
+        })
+    }
+
+    /// iterates over all records, which are converted by `f`. Multiple
+    /// chunks are converted in parallel, depending on the number of threads
+    /// in the parser settings.
+    pub fn serialized_records<'a, U: Send>(
+        &'a self,
+        f: impl FnMut(Result<EvtxRecord<'_>>) -> Result<U> + Send + Sync + Clone + 'a,
+    ) -> impl Iterator<Item = Result<U>> + 'a {
+        let num_threads = (*self.settings.get_num_threads()).max(1);
+        let mut chunks = self.chunks();
+
+        std::iter::from_fn(move || {
+            let batch: Vec<_> = chunks.by_ref().take(num_threads).collect();
+            if batch.is_empty() {
+                return None;
+            }
+
+            let results: Vec<Vec<Result<U>>> = if batch.len() == 1 {
+                batch
+                    .into_iter()
+                    .map(|(chunk, chunk_number)| self.convert_chunk(chunk, chunk_number, f.clone()))
+                    .collect()
+            } else {
+                std::thread::scope(|scope| {
+                    let workers: Vec<_> = batch
+                        .into_iter()
+                        .map(|(chunk, chunk_number)| {
+                            let f = f.clone();
+                            scope.spawn(move || self.convert_chunk(chunk, chunk_number, f))
+                        })
+                        .collect();
+                    workers
+                        .into_iter()
+                        .map(|worker| {
+                            worker
+                                .join()
+                                .unwrap_or_else(|why| std::panic::resume_unwind(why))
+                        })
+                        .collect()
+                })
+            };
+            Some(results.into_iter().flatten())
+        })
+        .flatten()
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn convert_chunk<U>(
+        &self,
+        chunk: Result<EvtxChunkData>,
+        chunk_number: u64,
+        mut f: impl FnMut(Result<EvtxRecord<'_>>) -> Result<U>,
+    ) -> Vec<Result<U>> {
+        let mut chunk = match chunk {
+            Ok(chunk) => chunk,
+            Err(why) => return vec![Err(why)],
+        };
+        let chunk = match chunk.parse(Arc::clone(&self.settings)) {
+            Ok(chunk) => chunk,
+            Err(why) => {
+                return vec![Err(EvtxError::FailedToParseChunk {
+                    chunk_id: chunk_number,
+                    source: why,
+                })]
+            }
+        };
+
+        ChunkRecords::new(&chunk)
+            .map(|entry| match entry {
+                ChunkEntry::Record { record, .. } => f(Ok(record)),
+                ChunkEntry::InvalidRecord { record_id, why, .. } => {
+                    f(Err(EvtxError::FailedToParseRecord {
+                        record_id,
+                        source: Box::new(why),
+                    }))
+                }
+                ChunkEntry::Damaged { begin, end } => f(Err(EvtxError::calculation_error(
+                    format!("chunk {chunk_number} is damaged between offset {begin} and {end}"),
+                ))),
+            })
+            .collect()
+    }
+
+    /// iterates over all records, which are formatted as XML
+    #[allow(clippy::result_large_err)]
+    pub fn records(&self) -> impl Iterator<Item = Result<SerializedEvtxRecord<String>>> + '_ {
+        self.serialized_records(|record| record.and_then(|record| record.into_xml()))
+    }
+
+    /// iterates over all records, which are formatted as JSON
+    #[allow(clippy::result_large_err)]
+    pub fn records_json(&self) -> impl Iterator<Item = Result<SerializedEvtxRecord<String>>> + '_ {
+        self.serialized_records(|record| record.and_then(|record| record.into_json()))
+    }
+
+    /// iterates over all records, whose data is a [serde_json::Value]
+    #[cfg(feature = "serde_json")]
+    #[allow(clippy::result_large_err)]
+    pub fn records_json_value(
+        &self,
+    ) -> impl Iterator<Item = Result<SerializedEvtxRecord<serde_json::Value>>> + '_ {
+        self.serialized_records(|record| record.and_then(|record| record.into_json_value()))
+    }
+}
+
+/// iterates over the chunks of an [EvtxFile], together with their numbers.
+/// Empty chunks, which might occur in the middle of a dirty file, are
+/// skipped.
+pub struct EvtxChunks<'f> {
+    file: &'f EvtxFile,
+    next_chunk: u64,
+}
+
+impl Iterator for EvtxChunks<'_> {
+    type Item = (Result<EvtxChunkData>, u64);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.next_chunk < self.file.chunk_count() {
+            let chunk_number = self.next_chunk;
+            self.next_chunk += 1;
+
+            let data = self.file.chunk_bytes(chunk_number);
+            if let Some(progress) = self.file.progress.as_ref() {
+                progress(FILE_HEADER_SIZE + self.next_chunk * CHUNK_SIZE);
+            }
+            let chunk = if data.iter().all(|b| *b == 0) {
+                None
+            } else {
+                Some(self.file.read_chunk(chunk_number, data))
+            };
+
+            // the data of the chunk has been copied
+            self.file.data.release(EvtxFile::chunk_range(chunk_number));
+            if let Some(chunk) = chunk {
+                return Some((chunk, chunk_number));
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use evtx::EvtxParser;
+
+    use super::{EvtxFile, CHUNK_SIZE, FILE_HEADER_SIZE, FLAGS_OFFSET, FLAG_DIRTY};
+
+    fn sample_file(name: &str) -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("data")
+            .join("evtx")
+            .join(name)
+    }
+
+    fn record_ids(file: &EvtxFile) -> Vec<u64> {
+        file.records_json_value()
+            .map(|record| record.unwrap().event_record_id)
+            .collect()
+    }
+
+    #[test]
+    fn read_like_evtx_parser() {
+        for name in ["security.evtx", "system.evtx", "application.evtx"] {
+            let expected: Vec<_> = EvtxParser::from_path(sample_file(name))
+                .unwrap()
+                .records_json_value()
+                .map(|record| record.unwrap())
+                .collect();
+
+            let file = EvtxFile::open(sample_file(name)).unwrap();
+            let records: Vec<_> = file.records_json_value().map(|r| r.unwrap()).collect();
+            assert_eq!(records, expected);
+
+            let headers: Vec<_> = file
+                .record_headers()
+                .map(|header| (header.event_record_id, header.timestamp))
+                .collect();
+            let expected: Vec<_> = expected
+                .iter()
+                .map(|record| (record.event_record_id, record.timestamp))
+                .collect();
+            assert_eq!(headers, expected);
+        }
+    }
+
+    #[test]
+    fn read_multiple_chunks_in_parallel() {
+        let mut data = std::fs::read(sample_file("security.evtx")).unwrap();
+        let chunk = data[FILE_HEADER_SIZE as usize..].to_vec();
+        for _ in 0..4 {
+            data.extend_from_slice(&chunk);
+        }
+        // empty chunks are skipped
+        data.extend(std::iter::repeat(0).take(CHUNK_SIZE as usize));
+
+        let single = EvtxFile::from_buffer("security.evtx", data.clone()).unwrap();
+        let parallel = EvtxFile::from_buffer("security.evtx", data)
+            .unwrap()
+            .with_configuration(evtx::ParserSettings::default().num_threads(3));
+        assert_eq!(single.chunk_count(), 6);
+        assert_eq!(single.chunks().count(), 5);
+        assert_eq!(record_ids(&parallel), record_ids(&single));
+        assert_eq!(
+            record_ids(&single).len(),
+            5 * record_ids(&EvtxFile::open(sample_file("security.evtx")).unwrap()).len()
+        );
+    }
+
+    #[test]
+    fn recover_records_of_dirty_file() {
+        let clean = EvtxFile::open(sample_file("security.evtx")).unwrap();
+        let headers: Vec<_> = clean.record_headers().collect();
+        let expected = record_ids(&clean);
+
+        // let the chunk header end before the last record, like Windows
+        // does before the header is updated
+        let mut data = std::fs::read(sample_file("security.evtx")).unwrap();
+        let last_record: u64 = 512
+            + headers[..headers.len() - 1]
+                .iter()
+                .map(|header| u64::from(header.data_size))
+                .sum::<u64>();
+        let chunk = FILE_HEADER_SIZE as usize;
+        let previous_id = headers[headers.len() - 2].event_record_id;
+        data[chunk + 32..chunk + 40].copy_from_slice(&previous_id.to_le_bytes());
+        data[chunk + 48..chunk + 52].copy_from_slice(&(last_record as u32).to_le_bytes());
+
+        let truncated = EvtxFile::from_buffer("security.evtx", data.clone()).unwrap();
+        assert_eq!(record_ids(&truncated), expected[..expected.len() - 1]);
+
+        data[FLAGS_OFFSET] |= FLAG_DIRTY as u8;
+        let dirty = EvtxFile::from_buffer("security.evtx", data).unwrap();
+        assert!(dirty.is_dirty());
+        assert_eq!(record_ids(&dirty), expected);
+        assert_eq!(dirty.record_headers().count(), expected.len());
+    }
+
+    #[test]
+    fn reject_invalid_files() {
+        assert!(EvtxFile::from_buffer("empty.evtx", Vec::new()).is_err());
+        assert!(EvtxFile::from_buffer("short.evtx", b"ElfFile\0".to_vec()).is_err());
+        assert!(EvtxFile::open(sample_file("missing.evtx")).is_err());
+    }
+}
@@ -0,0 +1,145 @@
+use std::fs::File;
+use std::ops::Deref;
+use std::path::Path;
+
+#[cfg(feature = "memmap2")]
+use memmap2::Mmap;
+
+/// the content of a file, which is mapped into memory if possible. Pages of
+/// a mapped file are loaded on demand and can be dropped by the kernel at any
+/// time, so that even files with multiple gigabytes don't increase the
+/// memory usage of the process permanently. If the file cannot be mapped,
+/// e.g. because it is a pipe, or if memory mapping is not supported, the
+/// file is read into memory instead.
+///
+/// A mapped file must not be truncated by another process while it is
+/// mapped: accessing a page behind the new end of the file raises `SIGBUS`
+/// on unix systems, which terminates the process. This is not expected for
+/// evidence files, which should not be modified anyway.
+pub(crate) struct MappedFile {
+    data: Data,
+}
+
+enum Data {
+    #[cfg(feature = "memmap2")]
+    Mapped(Mmap),
+    Owned(Vec<u8>),
+}
+
+impl MappedFile {
+    pub(crate) fn open(path: &Path) -> std::io::Result<Self> {
+        let file = File::open(path)?;
+
+        #[cfg(feature = "memmap2")]
+        match Self::map(&file) {
+            Ok(mapped) => return Ok(mapped),
+            Err(why) => log::debug!(
+                "unable to map '{}' into memory, reading it instead: {why}",
+                path.display()
+            ),
+        }
+
+        Self::read(file)
+    }
+
+    #[cfg(feature = "memmap2")]
+    fn map(file: &File) -> std::io::Result<Self> {
+        // SAFETY: the mapping is read only. It is only invalidated if the
+        // file is truncated while it is mapped, see above
+        let mmap = unsafe { Mmap::map(file)? };
+
+        // the chunks of evtx files are usually read from the beginning to
+        // the end, so the kernel should read ahead. Failing to do so is not
+        // an error.
+        #[cfg(unix)]
+        let _ = mmap.advise(memmap2::Advice::Sequential);
+
+        Ok(Self {
+            data: Data::Mapped(mmap),
+        })
+    }
+
+    fn read(mut file: File) -> std::io::Result<Self> {
+        use std::io::Read;
+
+        let mut data = Vec::new();
+        file.read_to_end(&mut data)?;
+        Ok(Self::from(data))
+    }
+
+    /// returns `true` if the file is mapped into memory instead of being
+    /// read completely
+    pub(crate) fn is_mapped(&self) -> bool {
+        !matches!(self.data, Data::Owned(_))
+    }
+
+    /// tells the kernel that the pages of `range` are not needed anymore,
+    /// so that they don't count to the memory usage of the process. They
+    /// are read from the file again if they are accessed later.
+    pub(crate) fn release(&self, range: std::ops::Range<usize>) {
+        #[cfg(all(unix, feature = "memmap2"))]
+        if let Data::Mapped(mmap) = &self.data {
+            let end = range.end.min(mmap.len());
+            if range.start < end {
+                // SAFETY: the mapping is private and has never been
+                // modified, so the pages are read from the file again.
+                // Failing to release them is not an error
+                let _ = unsafe {
+                    mmap.unchecked_advise_range(
+                        memmap2::UncheckedAdvice::DontNeed,
+                        range.start,
+                        end - range.start,
+                    )
+                };
+            }
+        }
+
+        #[cfg(not(all(unix, feature = "memmap2")))]
+        let _ = range;
+    }
+}
+
+impl From<Vec<u8>> for MappedFile {
+    fn from(data: Vec<u8>) -> Self {
+        Self {
+            data: Data::Owned(data),
+        }
+    }
+}
+
+impl Deref for MappedFile {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        match &self.data {
+            #[cfg(feature = "memmap2")]
+            Data::Mapped(mmap) => mmap,
+            Data::Owned(data) => data,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::MappedFile;
+
+    #[test]
+    fn map_file() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(b"ElfFile\0").unwrap();
+        file.flush().unwrap();
+
+        let mapped = MappedFile::open(file.path()).unwrap();
+        assert_eq!(&mapped[..], b"ElfFile\0");
+        assert_eq!(mapped.is_mapped(), cfg!(feature = "memmap2"));
+    }
+
+    #[test]
+    fn map_empty_file() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let mapped = MappedFile::open(file.path()).unwrap();
+        assert!(mapped.is_empty());
+    }
+}
@@ -1,5 +1,25 @@
+#[cfg(feature = "evtx")]
+mod chunk_records;
 mod event_id;
+#[cfg(feature = "evtx")]
+mod evtx_file;
+#[cfg(feature = "evtx")]
+mod mapped_file;
 mod range;
+#[cfg(all(feature = "evtx", feature = "serde_json"))]
+mod record_fields;
+mod record_id_gap;
+mod record_id_ranges;
+mod time_gap;
 
+#[cfg(feature = "evtx")]
+pub use chunk_records::*;
 pub use event_id::*;
-pub use range::*;
\ No newline at end of file
+#[cfg(feature = "evtx")]
+pub use evtx_file::*;
+pub use range::*;
+#[cfg(all(feature = "evtx", feature = "serde_json"))]
+pub use record_fields::*;
+pub use record_id_gap::*;
+pub use record_id_ranges::*;
+pub use time_gap::*;
@@ -0,0 +1,289 @@
+use std::io::Cursor;
+
+use evtx::binxml::name::{BinXmlName, BinXmlNameRef};
+use evtx::binxml::value_variant::BinXmlValue;
+use evtx::model::deserialized::BinXMLDeserializedTokens;
+use evtx::{EvtxChunk, EvtxRecord};
+use serde_json::Value;
+
+/// size of the link to the next name, which precedes every name in a chunk
+const NAME_LINK_SIZE: u64 = 6;
+
+/// fields of a record, which are read from the tokens of the parsed record,
+/// without serializing the record. This allows to filter records before
+/// they are converted into JSON.
+#[derive(Default, Debug)]
+pub struct RecordFields {
+    event_id: Option<String>,
+    channel: Option<String>,
+
+    /// fields of `EventData`, or of the first element inside of `UserData`,
+    /// with their values like in the JSON representation of the record
+    data: Vec<(String, Value)>,
+}
+
+impl RecordFields {
+    /// reads only the fields of the `System` element, the remaining tokens
+    /// are not visited.
+    ///
+    /// Returns `None` if the tokens of the record cannot be interpreted,
+    /// e.g. because a template is missing in the template table of the chunk.
+    /// In this case, the record must be serialized to read its fields
+    pub fn read_system(record: &EvtxRecord) -> Option<Self> {
+        FieldReader::new(record.chunk, false).read(record)
+    }
+
+    /// reads the fields of the `System` element and the fields of
+    /// `EventData` or `UserData`. Returns `None` in the same cases as
+    /// [RecordFields::read_system]
+    pub fn read(record: &EvtxRecord) -> Option<Self> {
+        FieldReader::new(record.chunk, true).read(record)
+    }
+
+    pub fn event_id(&self) -> Option<u16> {
+        self.event_id.as_ref()?.trim().parse().ok()
+    }
+
+    pub fn channel(&self) -> Option<&str> {
+        self.channel.as_deref()
+    }
+
+    /// returns the value of a field in `EventData` or `UserData`, which is
+    /// the same value like in the JSON representation of the record
+    pub fn data(&self, name: &str) -> Option<&Value> {
+        self.data
+            .iter()
+            .find_map(|(n, value)| (n == name).then_some(value))
+    }
+}
+
+struct FieldReader<'c> {
+    chunk: &'c EvtxChunk<'c>,
+
+    /// names of the currently open elements
+    path: Vec<String>,
+
+    /// `true` while the attributes of an element are being read
+    in_start_element: bool,
+
+    /// name of the attribute, whose value is read next
+    attribute: Option<String>,
+
+    /// `false` if the reader stops after the `System` element
+    with_data: bool,
+
+    /// `true` if no more tokens need to be visited
+    done: bool,
+    fields: RecordFields,
+}
+
+impl<'c> FieldReader<'c> {
+    fn new(chunk: &'c EvtxChunk<'c>, with_data: bool) -> Self {
+        Self {
+            chunk,
+            path: Vec::new(),
+            in_start_element: false,
+            attribute: None,
+            with_data,
+            done: false,
+            fields: RecordFields::default(),
+        }
+    }
+
+    fn read(mut self, record: &EvtxRecord) -> Option<RecordFields> {
+        for token in record.tokens.iter() {
+            self.visit(token)?;
+        }
+        Some(self.fields)
+    }
+
+    fn visit(&mut self, token: &BinXMLDeserializedTokens) -> Option<()> {
+        if self.done {
+            return Some(());
+        }
+        match token {
+            BinXMLDeserializedTokens::TemplateInstance(template) => {
+                let definition = self
+                    .chunk
+                    .template_table
+                    .get_template(template.template_def_offset)?;
+                for token in definition.tokens.iter() {
+                    match token {
+                        BinXMLDeserializedTokens::Substitution(descriptor) => {
+                            if !descriptor.ignore {
+                                if let Some(value) = template
+                                    .substitution_array
+                                    .get(usize::from(descriptor.substitution_index))
+                                {
+                                    self.visit(value)?;
+                                }
+                            }
+                        }
+                        _ => self.visit(token)?,
+                    }
+                }
+            }
+            BinXMLDeserializedTokens::Value(BinXmlValue::BinXmlType(tokens)) => {
+                for token in tokens.iter() {
+                    self.visit(token)?;
+                }
+            }
+            BinXMLDeserializedTokens::OpenStartElement(element) => {
+                let name = self.name(&element.name)?;
+                self.path.push(name);
+                self.in_start_element = true;
+                if self.is_data_field() {
+                    let name = self.path.last().cloned().unwrap_or_default();
+                    self.fields.data.push((name, Value::Null));
+                }
+            }
+            BinXMLDeserializedTokens::Attribute(attribute) => {
+                self.attribute = Some(self.name(&attribute.name)?);
+            }
+            BinXMLDeserializedTokens::CloseStartElement => self.close_start_element(),
+            BinXMLDeserializedTokens::CloseEmptyElement => {
+                self.close_start_element();
+                self.close_element();
+            }
+            BinXMLDeserializedTokens::CloseElement => self.close_element(),
+            BinXMLDeserializedTokens::Value(value) if self.in_start_element => {
+                self.attribute_value(value)
+            }
+            BinXMLDeserializedTokens::Value(value) => self.characters(value),
+            _ => (),
+        }
+        Some(())
+    }
+
+    fn name(&self, name: &BinXmlNameRef) -> Option<String> {
+        match self.chunk.string_cache.get_cached_string(name.offset) {
+            Some(name) => Some(name.as_str().to_owned()),
+            None => {
+                let mut cursor = Cursor::new(self.chunk.data);
+                cursor.set_position(u64::from(name.offset) + NAME_LINK_SIZE);
+                let name = BinXmlName::from_stream(&mut cursor).ok()?;
+                Some(name.as_str().to_owned())
+            }
+        }
+    }
+
+    /// `true` if the current element is a field in `EventData`, or in the
+    /// first element inside of `UserData`
+    fn is_data_field(&self) -> bool {
+        match &self.path[..] {
+            [event, data, _] => event == "Event" && data == "EventData",
+            [event, data, _, _] => event == "Event" && data == "UserData",
+            _ => false,
+        }
+    }
+
+    fn close_start_element(&mut self) {
+        self.in_start_element = false;
+        self.attribute = None;
+    }
+
+    fn close_element(&mut self) {
+        match &self.path[..] {
+            [_, system] if system == "System" && !self.with_data => self.done = true,
+            [_, data] if data == "EventData" || data == "UserData" => self.done = true,
+            _ => (),
+        }
+        self.path.pop();
+    }
+
+    /// uses the value of the 'Name' attribute of a `Data` element as the
+    /// name of the field, like the JSON representation does
+    fn attribute_value(&mut self, value: &BinXmlValue) {
+        if self.attribute.as_deref() != Some("Name") || !self.is_data_field() {
+            return;
+        }
+        if self.path.last().map(String::as_str) != Some("Data") {
+            return;
+        }
+        if let Some((name, _)) = self.fields.data.last_mut() {
+            *name = value.as_cow_str().into_owned();
+        }
+    }
+
+    fn characters(&mut self, value: &BinXmlValue) {
+        if self.is_data_field() {
+            if let Some((_, data)) = self.fields.data.last_mut() {
+                if data.is_null() {
+                    *data = Value::from(value.clone());
+                }
+            }
+            return;
+        }
+
+        let field = match &self.path[..] {
+            [event, system, field] if event == "Event" && system == "System" => field,
+            _ => return,
+        };
+        let target = match field.as_str() {
+            "EventID" => &mut self.fields.event_id,
+            "Channel" => &mut self.fields.channel,
+            _ => return,
+        };
+        target
+            .get_or_insert_with(String::new)
+            .push_str(&value.as_cow_str());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::evtx::EvtxFile;
+
+    use super::RecordFields;
+
+    fn sample_file(name: &str) -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("data")
+            .join("evtx")
+            .join(name)
+    }
+
+    #[test]
+    #[allow(clippy::result_large_err)]
+    fn read_like_json() {
+        for name in ["security.evtx", "system.evtx", "application.evtx"] {
+            let file = EvtxFile::open(sample_file(name)).unwrap();
+            let records = file.serialized_records(|record| {
+                record.and_then(|record| {
+                    let fields = RecordFields::read(&record);
+                    record.into_json_value().map(|value| (fields, value))
+                })
+            });
+            for record in records {
+                let (fields, value) = record.unwrap();
+                let fields = fields.expect("unable to read the record fields");
+                let system = &value.data["Event"]["System"];
+                let event_id = system["EventID"].get("#text").unwrap_or(&system["EventID"]);
+                assert_eq!(fields.event_id().map(u64::from), event_id.as_u64());
+                assert_eq!(fields.channel(), system["Channel"].as_str());
+
+                let data = match value.data["Event"].get("EventData") {
+                    Some(data) => data,
+                    None => match value.data["Event"].get("UserData") {
+                        Some(user_data) => user_data
+                            .as_object()
+                            .unwrap()
+                            .iter()
+                            .find(|(k, _)| *k != "#attributes")
+                            .map(|(_, v)| v)
+                            .unwrap(),
+                        None => continue,
+                    },
+                };
+                for (name, value) in data.as_object().unwrap() {
+                    if name != "#attributes" {
+                        assert_eq!(fields.data(name), Some(value), "field '{name}'");
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,40 @@
+use chrono::Duration;
+use super::EventId;
+
+/// a jump in the event record ids between two records which are
+/// consecutive in record order
+pub struct RecordIdGap<'a> {
+    pub before: &'a EventId,
+    pub after: &'a EventId,
+}
+
+impl<'a> RecordIdGap<'a> {
+    /// number of record ids between both records which have no record
+    pub fn missing_ids(&self) -> u64 {
+        self.after.event_record_id() - self.before.event_record_id() - 1
+    }
+
+    pub fn duration(&self) -> Duration {
+        *self.after.timestamp() - *self.before.timestamp()
+    }
+
+    /// records which have been deleted without any time passing by are a
+    /// strong indicator of selective deletion
+    pub fn has_time_gap(&self, threshold: Duration) -> bool {
+        self.duration() > threshold
+    }
+}
+
+/// returns all jumps in the record ids of `ids`, which must be sorted by
+/// the event record id. Only ids between two existing records are
+/// considered, so that rotated logs whose first id is large are not
+/// reported
+pub fn find_record_id_gaps(ids: &[EventId]) -> Vec<RecordIdGap<'_>> {
+    ids.windows(2)
+        .filter(|pair| pair[1].event_record_id() > pair[0].event_record_id() + 1)
+        .map(|pair| RecordIdGap {
+            before: &pair[0],
+            after: &pair[1],
+        })
+        .collect()
+}
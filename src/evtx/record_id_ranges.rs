@@ -0,0 +1,190 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail};
+
+/// An inclusive range of event record ids, which may be open at either end
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RecordIdRange {
+    begin: Option<u64>,
+    end: Option<u64>,
+}
+
+impl RecordIdRange {
+    pub fn begin(&self) -> Option<u64> {
+        self.begin
+    }
+
+    pub fn end(&self) -> Option<u64> {
+        self.end
+    }
+
+    pub fn contains(&self, id: u64) -> bool {
+        self.begin.map_or(true, |b| b <= id) && self.end.map_or(true, |e| id <= e)
+    }
+
+    fn lower(&self) -> u64 {
+        self.begin.unwrap_or(u64::MIN)
+    }
+
+    fn upper(&self) -> u64 {
+        self.end.unwrap_or(u64::MAX)
+    }
+}
+
+impl FromStr for RecordIdRange {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse_id = |v: &str| -> anyhow::Result<Option<u64>> {
+            let v = v.trim();
+            if v.is_empty() {
+                Ok(None)
+            } else {
+                Ok(Some(v.parse().map_err(|why| {
+                    anyhow!("invalid record id '{v}': {why}")
+                })?))
+            }
+        };
+
+        let (begin, end) = match s.split_once("..") {
+            Some((begin, end)) => (parse_id(begin)?, parse_id(end)?),
+            None => {
+                let id = parse_id(s)?.ok_or_else(|| anyhow!("empty record id range"))?;
+                (Some(id), Some(id))
+            }
+        };
+
+        if let (Some(b), Some(e)) = (begin, end) {
+            if b > e {
+                bail!("invalid record id range '{s}': {b} is larger than {e}");
+            }
+        }
+        Ok(Self { begin, end })
+    }
+}
+
+impl Display for RecordIdRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.begin, self.end) {
+            (Some(b), Some(e)) if b == e => write!(f, "{b}"),
+            (b, e) => write!(
+                f,
+                "{}..{}",
+                b.map(|b| b.to_string()).unwrap_or_default(),
+                e.map(|e| e.to_string()).unwrap_or_default()
+            ),
+        }
+    }
+}
+
+/// A list of record id ranges, like `1000..2000,5000,7000..`.
+///
+/// Overlapping and adjacent ranges are merged while parsing, so that the
+/// ranges are always sorted and disjoint.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RecordIdRanges {
+    ranges: Vec<RecordIdRange>,
+}
+
+impl RecordIdRanges {
+    pub fn contains(&self, id: u64) -> bool {
+        self.ranges.iter().any(|r| r.contains(id))
+    }
+
+    /// returns `true` if any id in `begin..=end` is contained in one of the ranges
+    pub fn overlaps(&self, begin: u64, end: u64) -> bool {
+        self.ranges
+            .iter()
+            .any(|r| r.lower() <= end && begin <= r.upper())
+    }
+
+    pub fn ranges(&self) -> impl Iterator<Item = &RecordIdRange> {
+        self.ranges.iter()
+    }
+
+    fn merge(mut ranges: Vec<RecordIdRange>) -> Vec<RecordIdRange> {
+        ranges.sort_by_key(|r| r.lower());
+        let mut merged: Vec<RecordIdRange> = Vec::with_capacity(ranges.len());
+        for range in ranges {
+            match merged.last_mut() {
+                Some(last) if range.lower() <= last.upper().saturating_add(1) => {
+                    if range.upper() > last.upper() {
+                        last.end = range.end;
+                    }
+                }
+                _ => merged.push(range),
+            }
+        }
+        merged
+    }
+}
+
+impl FromStr for RecordIdRanges {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let ranges = s
+            .split(',')
+            .map(RecordIdRange::from_str)
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Ok(Self {
+            ranges: Self::merge(ranges),
+        })
+    }
+}
+
+impl From<Vec<RecordIdRange>> for RecordIdRanges {
+    fn from(ranges: Vec<RecordIdRange>) -> Self {
+        Self {
+            ranges: Self::merge(ranges),
+        }
+    }
+}
+
+impl Display for RecordIdRanges {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let ranges: Vec<_> = self.ranges.iter().map(|r| r.to_string()).collect();
+        write!(f, "{}", ranges.join(","))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RecordIdRanges;
+
+    #[test]
+    fn parse_ranges() {
+        let ranges: RecordIdRanges = "1000..2000,5000,7000..".parse().unwrap();
+        assert!(!ranges.contains(999));
+        assert!(ranges.contains(1000));
+        assert!(ranges.contains(2000));
+        assert!(!ranges.contains(2001));
+        assert!(ranges.contains(5000));
+        assert!(!ranges.contains(5001));
+        assert!(ranges.contains(u64::MAX));
+
+        let ranges: RecordIdRanges = "..10".parse().unwrap();
+        assert!(ranges.contains(0));
+        assert!(!ranges.contains(11));
+    }
+
+    #[test]
+    fn merge_ranges() {
+        let ranges: RecordIdRanges = "5..10,1..3,4,8..20,30..".parse().unwrap();
+        assert_eq!(ranges.to_string(), "1..20,30..");
+        assert!(ranges.overlaps(25, 35));
+        assert!(!ranges.overlaps(21, 29));
+
+        let ranges: RecordIdRanges = "..5,3..".parse().unwrap();
+        assert_eq!(ranges.to_string(), "..");
+    }
+
+    #[test]
+    fn invalid_ranges() {
+        assert!("".parse::<RecordIdRanges>().is_err());
+        assert!("10..5".parse::<RecordIdRanges>().is_err());
+        assert!("a..5".parse::<RecordIdRanges>().is_err());
+        assert!("1,,2".parse::<RecordIdRanges>().is_err());
+    }
+}
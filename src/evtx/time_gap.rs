@@ -0,0 +1,55 @@
+use chrono::Duration;
+use super::EventId;
+
+/// the threshold is at least this factor times the median interval between
+/// two consecutive records
+pub const ADAPTIVE_FACTOR: i32 = 100;
+
+/// lower limit for the threshold which adapts to the cadence of the log
+pub const MIN_ADAPTIVE_THRESHOLD_MINUTES: i64 = 10;
+
+/// a period of time without any records, between two records which are
+/// consecutive in record order
+pub struct TimeGap<'a> {
+    pub before: &'a EventId,
+    pub after: &'a EventId,
+}
+
+impl<'a> TimeGap<'a> {
+    pub fn duration(&self) -> Duration {
+        *self.after.timestamp() - *self.before.timestamp()
+    }
+}
+
+/// returns all gaps between consecutive records of `ids` which are longer
+/// than `threshold`. `ids` must be sorted by the event record id
+pub fn find_time_gaps(ids: &[EventId], threshold: Duration) -> Vec<TimeGap<'_>> {
+    ids.windows(2)
+        .map(|pair| TimeGap {
+            before: &pair[0],
+            after: &pair[1],
+        })
+        .filter(|gap| gap.duration() > threshold)
+        .collect()
+}
+
+/// computes a threshold which fits to the cadence of the log: `ADAPTIVE_FACTOR`
+/// times the median interval between two consecutive records, but at least
+/// `MIN_ADAPTIVE_THRESHOLD_MINUTES`. Records which go back in time are
+/// treated as if they had no distance to their predecessor
+pub fn adaptive_threshold(ids: &[EventId]) -> Duration {
+    let mut intervals: Vec<Duration> = ids
+        .windows(2)
+        .map(|pair| (*pair[1].timestamp() - *pair[0].timestamp()).max(Duration::zero()))
+        .collect();
+    intervals.sort_unstable();
+
+    let minimum = Duration::minutes(MIN_ADAPTIVE_THRESHOLD_MINUTES);
+    match intervals.get(intervals.len() / 2) {
+        None => minimum,
+        Some(median) => median
+            .checked_mul(ADAPTIVE_FACTOR)
+            .unwrap_or(Duration::max_value())
+            .max(minimum),
+    }
+}
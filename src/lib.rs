@@ -2,5 +2,5 @@ pub mod registry;
 pub mod common;
 pub mod evtx;
 
-#[cfg(feature="elastic")]
+#[cfg(feature = "ecs")]
 pub mod es4forensics;
\ No newline at end of file
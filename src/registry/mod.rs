@@ -1,2 +1,10 @@
 
-pub mod types;
\ No newline at end of file
+pub mod types;
+
+#[cfg(feature = "nt_hive2")]
+mod resident_data;
+mod transaction_logs;
+
+#[cfg(feature = "nt_hive2")]
+pub use resident_data::*;
+pub use transaction_logs::*;
@@ -0,0 +1,98 @@
+use nt_hive2::RegistryValue;
+
+use super::types::parse_reg_sz_raw;
+
+/// the most significant bit of the data size marks data which is stored in
+/// the data offset field of the value record
+const RESIDENT_DATA: u32 = 0x8000_0000;
+
+const REG_NONE: u32 = 0;
+const REG_SZ: u32 = 1;
+const REG_EXPAND_SZ: u32 = 2;
+const REG_DWORD: u32 = 4;
+const REG_DWORD_BIG_ENDIAN: u32 = 5;
+const REG_LINK: u32 = 6;
+const REG_MULTI_SZ: u32 = 7;
+
+/// decodes the data of a value record which stores up to four bytes of data
+/// in its data offset field. `nt_hive2` returns such data as `RegDWord`,
+/// regardless of the type of the value. `cell` begins with the cell size of
+/// the value record. Returns `None` if the data is not resident.
+pub fn read_resident_data(cell: &[u8]) -> Option<RegistryValue> {
+    if cell.get(4..6) != Some(b"vk") {
+        return None;
+    }
+    let data_size = u32::from_le_bytes(cell.get(8..12)?.try_into().ok()?);
+    if data_size & RESIDENT_DATA == 0 {
+        return None;
+    }
+    let size = ((data_size & !RESIDENT_DATA) as usize).min(4);
+    let data = cell.get(12..12 + size)?;
+    let data_type = u32::from_le_bytes(cell.get(16..20)?.try_into().ok()?);
+
+    let mut padded = [0; 4];
+    padded[..size].copy_from_slice(data);
+    Some(match data_type {
+        REG_NONE if size == 0 => RegistryValue::RegNone,
+        REG_SZ => RegistryValue::RegSZ(string(data)),
+        REG_EXPAND_SZ => RegistryValue::RegExpandSZ(string(data)),
+        REG_LINK => RegistryValue::RegLink(string(data)),
+        REG_MULTI_SZ => RegistryValue::RegMultiSZ(
+            string(data)
+                .split('\0')
+                .filter(|s| !s.is_empty())
+                .map(str::to_owned)
+                .collect(),
+        ),
+        REG_DWORD => RegistryValue::RegDWord(u32::from_le_bytes(padded)),
+        REG_DWORD_BIG_ENDIAN => RegistryValue::RegDWordBigEndian(u32::from_be_bytes(padded)),
+        _ => RegistryValue::RegBinary(data.to_vec()),
+    })
+}
+
+fn string(data: &[u8]) -> String {
+    let s = parse_reg_sz_raw(data)
+        .unwrap_or_else(|_| String::from_utf8_lossy(data).into_owned());
+    s.trim_end_matches('\0').to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use nt_hive2::RegistryValue;
+
+    use super::read_resident_data;
+
+    fn value_cell(data_size: u32, data: [u8; 4], data_type: u32) -> Vec<u8> {
+        let mut cell = (-32i32).to_le_bytes().to_vec();
+        cell.extend(b"vk");
+        cell.extend(1u16.to_le_bytes());
+        cell.extend(data_size.to_le_bytes());
+        cell.extend(data);
+        cell.extend(data_type.to_le_bytes());
+        cell.extend([1, 0, 0, 0, b'B']);
+        cell
+    }
+
+    #[test]
+    fn decode_by_type() {
+        let string = read_resident_data(&value_cell(0x8000_0004, [b'b', 0, 0, 0], 1));
+        assert!(matches!(string, Some(RegistryValue::RegSZ(s)) if s == "b"));
+
+        let string = read_resident_data(&value_cell(0x8000_0004, [b'a', 0, b'b', 0], 2));
+        assert!(matches!(string, Some(RegistryValue::RegExpandSZ(s)) if s == "ab"));
+
+        let dword = read_resident_data(&value_cell(0x8000_0004, [3, 0, 0, 0], 4));
+        assert!(matches!(dword, Some(RegistryValue::RegDWord(3))));
+
+        let dword = read_resident_data(&value_cell(0x8000_0004, [0, 0, 0, 3], 5));
+        assert!(matches!(dword, Some(RegistryValue::RegDWordBigEndian(3))));
+
+        let binary = read_resident_data(&value_cell(0x8000_0002, [1, 2, 3, 4], 3));
+        assert!(matches!(binary, Some(RegistryValue::RegBinary(b)) if b == [1, 2]));
+    }
+
+    #[test]
+    fn ignore_data_in_other_cells() {
+        assert!(read_resident_data(&value_cell(4, [0x20, 0, 0, 0], 1)).is_none());
+    }
+}
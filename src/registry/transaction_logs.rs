@@ -0,0 +1,46 @@
+use std::path::{Path, PathBuf};
+
+/// extensions of the transaction log files, which are searched next to the
+/// hive file if no log file has been specified
+const LOG_EXTENSIONS: [&str; 2] = ["LOG1", "LOG2"];
+
+/// returns the transaction log files which are stored next to `hive_file`
+/// and have the same name with the extension `.LOG1` or `.LOG2`. Names
+/// are compared case insensitive
+pub fn find_transaction_logs(hive_file: &Path) -> Vec<PathBuf> {
+    let (Some(dir), Some(hive_name)) = (hive_file.parent(), hive_file.file_name()) else {
+        return Vec::new();
+    };
+    let dir = if dir.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        dir
+    };
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(why) => {
+            log::warn!(
+                "unable to search for transaction logs in '{}': {why}",
+                dir.display()
+            );
+            return Vec::new();
+        }
+    };
+
+    let mut logfiles: Vec<_> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .filter(|path| {
+            let (Some(stem), Some(extension)) = (path.file_stem(), path.extension()) else {
+                return false;
+            };
+            stem.eq_ignore_ascii_case(hive_name)
+                && LOG_EXTENSIONS
+                    .iter()
+                    .any(|ext| extension.eq_ignore_ascii_case(ext))
+        })
+        .collect();
+    logfiles.sort();
+    logfiles
+}
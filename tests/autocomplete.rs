@@ -0,0 +1,47 @@
+use assert_cmd::Command;
+
+/// every binary, and if it has been built with the enabled features
+const BINARIES: &[(&str, bool)] = &[
+    ("mactime2", cfg!(feature = "mactime2")),
+    ("evtxscan", cfg!(feature = "evtxscan")),
+    ("evtxcat", cfg!(feature = "evtxcat")),
+    ("evtxls", cfg!(feature = "evtxls")),
+    ("evtxanalyze", cfg!(feature = "evtxanalyze")),
+    ("evtx2bodyfile", cfg!(feature = "evtx2bodyfile")),
+    ("pol_export", cfg!(feature = "pol_export")),
+    ("es4forensics", cfg!(feature = "es4forensics")),
+    ("regdump", cfg!(feature = "regdump")),
+    ("hivescan", cfg!(feature = "hivescan")),
+    ("cleanhive", cfg!(feature = "cleanhive")),
+    ("ipgrep", cfg!(feature = "ipgrep")),
+    ("ts2date", cfg!(feature = "ts2date")),
+    ("lnk2bodyfile", cfg!(feature = "lnk2bodyfile")),
+    ("pf2bodyfile", cfg!(feature = "pf2bodyfile")),
+    ("zip2bodyfile", cfg!(feature = "zip2bodyfile")),
+];
+
+const SHELLS: &[&str] = &["bash", "zsh", "fish", "powershell", "elvish"];
+
+#[test]
+fn every_binary_supports_every_shell() {
+    for (binary, _) in BINARIES.iter().filter(|(_, enabled)| *enabled) {
+        for shell in SHELLS {
+            let output = Command::cargo_bin(binary)
+                .unwrap()
+                .arg("--autocomplete")
+                .arg(shell)
+                .output()
+                .unwrap();
+            assert!(
+                output.status.success(),
+                "{binary} --autocomplete {shell} failed: {}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+            let script = String::from_utf8(output.stdout).unwrap();
+            assert!(
+                script.contains(binary),
+                "the {shell} script of {binary} does not mention its name"
+            );
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use std::path::Path;
+
+use assert_cmd::Command;
+
+use super::{cleanhive, data_dir, foreign_log};
+
+fn check(hive: &Path) -> (Option<i32>, String) {
+    let output = Command::cargo_bin("cleanhive")
+        .unwrap()
+        .arg("--check")
+        .arg(hive)
+        .output()
+        .unwrap();
+    (
+        output.status.code(),
+        String::from_utf8(output.stdout).unwrap(),
+    )
+}
+
+#[test]
+fn check_dirty_hive() {
+    let (code, stdout) = check(&data_dir().join("NewDirtyHive"));
+    assert_eq!(code, Some(3));
+    assert!(stdout.starts_with(
+        "hive:\n  signature:        valid\n  checksum:         valid (0xce22827f)\n  \
+         sequence numbers: 3 and 2 (different)\n"
+    ));
+    assert!(stdout.contains("  log entries:      1 (sequence numbers 2 to 2)\n"));
+    assert!(stdout.contains("  log entries:      3 (sequence numbers 3 to 5)\n"));
+    assert!(stdout.contains("recovery: apply 3 log entries (3 dirty pages) from '"));
+    assert!(stdout.ends_with("state: dirty, but recoverable\n"));
+}
+
+#[test]
+fn check_clean_hive() {
+    let dir = tempfile::tempdir().unwrap();
+    let hive = dir.path().join("cleaned");
+    assert!(cleanhive(&[], &data_dir().join("NewDirtyHive"), &hive)
+        .status
+        .success());
+    let (code, stdout) = check(&hive);
+
+    assert_eq!(code, Some(0));
+    assert!(stdout.contains("  sequence numbers: 5 and 5 (equal)\n"));
+    assert!(stdout.contains("recovery: not needed\n"));
+    assert!(stdout.ends_with("state: clean\n"));
+}
+
+#[test]
+fn check_corrupted_hives() {
+    let dir = foreign_log();
+    let (foreign_code, foreign) = check(&dir.path().join("NewDirtyHive"));
+
+    let mut data = std::fs::read(data_dir().join("NewDirtyHive")).unwrap();
+    data[4] = 7;
+    let hive = dir.path().join("InvalidChecksum");
+    std::fs::write(&hive, data).unwrap();
+    let (invalid_code, invalid) = check(&hive);
+
+    assert_eq!(foreign_code, Some(4));
+    assert!(foreign.contains("  belongs to hive:  no, it belongs to the hive 'Xrs\\user"));
+    assert!(foreign.contains("NewDirtyHive.LOG2' seems to belong to a different hive\n"));
+    assert!(foreign.ends_with("state: corrupted\n"));
+
+    assert_eq!(invalid_code, Some(4));
+    assert!(invalid.contains("  checksum:         invalid (stored 0xce22827f, calculated "));
+    assert!(
+        invalid.contains("recovery: impossible, because the base block of the hive is invalid\n")
+    );
+}
@@ -0,0 +1,47 @@
+use std::path::{Path, PathBuf};
+
+use assert_cmd::Command;
+use tempfile::TempDir;
+
+mod check;
+mod transaction_logs;
+
+/// a dirty hive, whose changes are stored in `NewDirtyHive.LOG2`
+pub(crate) fn data_dir() -> PathBuf {
+    let mut data_path = PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap());
+    data_path.push("tests");
+    data_path.push("data");
+    data_path.push("hivescan");
+    data_path.push("NewDirtyHive1");
+    data_path
+}
+
+pub(crate) fn cleanhive(args: &[&str], hive: &Path, output: &Path) -> std::process::Output {
+    Command::cargo_bin("cleanhive")
+        .unwrap()
+        .args(args)
+        .arg("-O")
+        .arg(output)
+        .arg(hive)
+        .output()
+        .unwrap()
+}
+
+/// copies the hive and its logs into a new temporary directory, and modifies
+/// the file name in the base block of `NewDirtyHive.LOG2`
+pub(crate) fn foreign_log() -> TempDir {
+    let tempdir = tempfile::tempdir().unwrap();
+    let dir = tempdir.path();
+    for name in ["NewDirtyHive", "NewDirtyHive.LOG1"] {
+        std::fs::copy(data_dir().join(name), dir.join(name)).unwrap();
+    }
+    let mut log = std::fs::read(data_dir().join("NewDirtyHive.LOG2")).unwrap();
+    log[0x30] = b'X';
+    let checksum = log[..0x1fc]
+        .chunks_exact(4)
+        .map(|dword| u32::from_le_bytes(dword.try_into().unwrap()))
+        .fold(0, |checksum, dword| checksum ^ dword);
+    log[0x1fc..0x200].copy_from_slice(&checksum.to_le_bytes());
+    std::fs::write(dir.join("NewDirtyHive.LOG2"), log).unwrap();
+    tempdir
+}
@@ -0,0 +1,39 @@
+use super::{cleanhive, data_dir, foreign_log};
+
+#[test]
+fn apply_discovered_logs() {
+    let dir = tempfile::tempdir().unwrap();
+    let output = dir.path().join("cleaned");
+    let result = cleanhive(&[], &data_dir().join("NewDirtyHive"), &output);
+    let cleaned = std::fs::read(&output).unwrap();
+    assert!(result.status.success());
+
+    let stderr = String::from_utf8(result.stderr).unwrap();
+    assert!(stderr.contains("NewDirtyHive.LOG1' contains no entries which are newer than the hive"));
+    assert!(stderr.contains("warning: applied 3 log entries (3 dirty pages) from '"));
+    assert!(stderr.contains("NewDirtyHive.LOG2'"));
+
+    let expected = std::fs::read(data_dir().join("NewDirtyHive.clean1")).unwrap();
+    assert_eq!(cleaned.len(), expected.len());
+    assert!(cleaned[0x1000..] == expected[0x1000..]);
+}
+
+#[test]
+fn refuse_logs_of_another_hive() {
+    let dir = foreign_log();
+    let output = dir.path().join("cleaned");
+
+    let refused = cleanhive(&[], &dir.path().join("NewDirtyHive"), &output);
+    let forced = cleanhive(&["--force"], &dir.path().join("NewDirtyHive"), &output);
+
+    assert!(!refused.status.success());
+    let stderr = String::from_utf8(refused.stderr).unwrap();
+    assert!(stderr.contains("seems to belong to a different hive"));
+    assert!(stderr.contains("it belongs to the hive 'Xrs\\user\\Desktop\\1\\NewDirtyHive'"));
+    assert!(stderr.contains("Use '--force' to apply it anyway"));
+
+    assert!(forced.status.success());
+    let stderr = String::from_utf8(forced.stderr).unwrap();
+    assert!(stderr.contains("warning: the transaction log '"));
+    assert!(stderr.contains("applied 3 log entries (3 dirty pages) from '"));
+}
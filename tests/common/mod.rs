@@ -0,0 +1,20 @@
+//! helpers which are shared by the integration tests. Not every test crate
+//! uses all of them.
+#![allow(dead_code)]
+
+use std::path::PathBuf;
+
+/// returns the path of one of the sample files in `tests/data/evtx`
+pub fn sample_file(name: &str) -> PathBuf {
+    let mut data_path = PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap());
+    data_path.push("tests");
+    data_path.push("data");
+    data_path.push("evtx");
+    data_path.push(name);
+    data_path
+}
+
+/// writes `value` as little endian at `offset`
+pub fn put_u32(data: &mut [u8], offset: usize, value: u32) {
+    data[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+}
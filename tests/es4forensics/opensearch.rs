@@ -0,0 +1,59 @@
+//! These tests require a running OpenSearch instance and are ignored by
+//! default. Start a container without the security plugin using
+//!
+//! ```shell
+//! docker run --rm -p 9200:9200 -e discovery.type=single-node \
+//!     -e DISABLE_SECURITY_PLUGIN=true opensearchproject/opensearch:2
+//! ```
+//!
+//! and run them with `cargo test opensearch -- --ignored`. The port can be
+//! changed using the environment variable `ES4FORENSICS_TEST_OPENSEARCH_PORT`.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use assert_cmd::Command;
+
+use crate::common::sample_file;
+
+/// runs `es4forensics` against the OpenSearch container, using an index
+/// which has not been used by an earlier test run
+fn es4forensics(action: &str) -> Command {
+    let port =
+        std::env::var("ES4FORENSICS_TEST_OPENSEARCH_PORT").unwrap_or_else(|_| "9200".to_owned());
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis();
+
+    let mut cmd = Command::cargo_bin("es4forensics").unwrap();
+    cmd.args(["--strict", "-vv", "--proto", "http", "-W", "admin"])
+        .args(["-P", &port])
+        .args(["-I", &format!("es4forensics_test_{action}_{timestamp}")])
+        .arg(action);
+    cmd
+}
+
+#[test]
+#[ignore]
+fn opensearch_import() {
+    let result = es4forensics("import")
+        .write_stdin("{\"message\": \"foo\"}\n{\"message\": \"bar\"}\n")
+        .ok();
+    assert!(result.is_ok());
+
+    let stderr = String::from_utf8(result.unwrap().stderr).unwrap();
+    assert!(stderr.contains("detected opensearch"));
+    assert!(stderr.contains("indexed: 2, failed: 0"));
+}
+
+#[test]
+#[ignore]
+fn opensearch_import_evtx() {
+    let result = es4forensics("import-evtx")
+        .arg(sample_file("security.evtx"))
+        .ok();
+    assert!(result.is_ok());
+
+    let stderr = String::from_utf8(result.unwrap().stderr).unwrap();
+    assert!(stderr.contains("failed: 0"));
+}
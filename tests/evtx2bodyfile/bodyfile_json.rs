@@ -0,0 +1,53 @@
+use serde_json::{json, Value};
+
+use super::{convert, expected_bodyfile};
+
+fn bodyfile_json(file: &str) -> Vec<Value> {
+    convert(&[file], &["--format", "bodyfile-json"])
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect()
+}
+
+#[test]
+fn same_values_as_bodyfile() {
+    let expected = expected_bodyfile("security");
+    let values = bodyfile_json("security.evtx");
+    assert_eq!(values.len(), expected.lines().count());
+
+    for (value, line) in values.iter().zip(expected.lines()) {
+        let columns: Vec<_> = line.rsplitn(10, '|').collect();
+        // the name field may contain '|', so it is the last of the reversed columns
+        let name = columns[9].split_once('|').unwrap().1;
+        assert_eq!(value["name"], json!(name));
+        assert_eq!(value["atime"].to_string(), columns[3]);
+        assert_eq!(value["mtime"].to_string(), columns[2]);
+        assert_eq!(value["ctime"].to_string(), columns[1]);
+        assert_eq!(value["crtime"].to_string(), columns[0]);
+    }
+}
+
+#[test]
+fn structured_event_data() {
+    let values = bodyfile_json("security.evtx");
+    let logon = &values[0];
+    assert_eq!(logon["event_record_id"], json!(1));
+    assert_eq!(logon["event_id"], json!(4624));
+    assert_eq!(logon["channel_name"], json!("Security"));
+    assert_eq!(logon["mtime"], json!(1710230400));
+    assert_eq!(logon["atime"], json!(-1));
+    assert_eq!(
+        logon["custom_data"]["EventData"]["TargetUserName"],
+        json!("jsmith")
+    );
+
+    // events with UserData instead of EventData
+    let cleared = values
+        .iter()
+        .find(|v| v["event_id"] == json!(1102))
+        .unwrap();
+    assert_eq!(
+        cleared["custom_data"]["UserData"]["LogFileCleared"]["SubjectUserName"],
+        json!("Administrator")
+    );
+}
@@ -0,0 +1,183 @@
+use std::path::PathBuf;
+
+use assert_cmd::Command;
+use serde_json::Value;
+
+use crate::common::sample_file;
+
+use super::expected_bodyfile;
+
+/// offset of the first chunk in a sample file
+const CHUNK: usize = 4096;
+
+/// offset of the second record in `security.evtx`
+const SECOND_RECORD: usize = 6811;
+
+/// creates a copy of `security.evtx`, which is modified by `damage`
+fn damaged_file<D: FnOnce(&mut [u8])>(name: &str, damage: D) -> PathBuf {
+    let mut data = std::fs::read(sample_file("security.evtx")).unwrap();
+    damage(&mut data);
+
+    let path = tmp_path(name);
+    std::fs::write(&path, data).unwrap();
+    path
+}
+
+/// creates a copy of a sample file whose first chunk has an invalid magic
+fn dirty_file(name: &str) -> PathBuf {
+    damaged_file(name, |data| {
+        data[CHUNK..CHUNK + 8].copy_from_slice(b"XXXXXXXX")
+    })
+}
+
+fn tmp_path(name: &str) -> PathBuf {
+    let mut path = PathBuf::from(env!("CARGO_TARGET_TMPDIR"));
+    path.push("evtx2bodyfile");
+    std::fs::create_dir_all(&path).unwrap();
+    path.push(name);
+    path
+}
+
+#[test]
+fn lenient_mode_skips_invalid_chunks() {
+    let dirty = dirty_file("lenient_mode_skips_invalid_chunks.evtx");
+    let result = Command::cargo_bin("evtx2bodyfile")
+        .unwrap()
+        .arg(&dirty)
+        .arg(sample_file("system.evtx"))
+        .assert()
+        .success();
+    let output = result.get_output();
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        expected_bodyfile("system")
+    );
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains(&format!(
+        "{}: converted 0 records, skipped 1 chunks, 0 records and 0 damaged ranges",
+        dirty.display()
+    )));
+}
+
+#[test]
+fn strict_mode_fails_on_invalid_chunks() {
+    let dirty = dirty_file("strict_mode_fails_on_invalid_chunks.evtx");
+    Command::cargo_bin("evtx2bodyfile")
+        .unwrap()
+        .arg("--strict")
+        .arg(&dirty)
+        .assert()
+        .failure();
+}
+
+#[test]
+fn summary_as_json() {
+    let dirty = dirty_file("summary_as_json.evtx");
+    let summary_file = tmp_path("summary_as_json.json");
+    Command::cargo_bin("evtx2bodyfile")
+        .unwrap()
+        .arg("--summary-json")
+        .arg(&summary_file)
+        .arg(sample_file("security.evtx"))
+        .arg(&dirty)
+        .assert()
+        .success();
+
+    let summary: Value =
+        serde_json::from_str(&std::fs::read_to_string(summary_file).unwrap()).unwrap();
+    let summary = summary.as_array().unwrap();
+    assert_eq!(summary.len(), 2);
+
+    assert_eq!(summary[0]["converted_records"], 8);
+    assert_eq!(summary[0]["skipped_chunks"], 0);
+    assert_eq!(summary[0]["skipped_records"], 0);
+
+    assert_eq!(summary[1]["file"], dirty.display().to_string());
+    assert_eq!(summary[1]["converted_records"], 0);
+    assert_eq!(summary[1]["skipped_chunks"], 1);
+    let reasons = summary[1]["reasons"].as_object().unwrap();
+    assert_eq!(reasons.values().collect::<Vec<_>>(), vec![1]);
+    assert!(summary[1]["error"].is_null());
+}
+
+#[test]
+fn records_after_a_damaged_record_are_recovered() {
+    let damaged = damaged_file(
+        "records_after_a_damaged_record_are_recovered.evtx",
+        |data| data[SECOND_RECORD..SECOND_RECORD + 4].copy_from_slice(b"XXXX"),
+    );
+    let report = tmp_path("records_after_a_damaged_record_are_recovered.txt");
+    let result = Command::cargo_bin("evtx2bodyfile")
+        .unwrap()
+        .arg("--report")
+        .arg(&report)
+        .arg(&damaged)
+        .assert()
+        .success();
+
+    let expected: String = expected_bodyfile("security")
+        .lines()
+        .enumerate()
+        .filter(|(index, _)| *index != 1)
+        .map(|(_, line)| format!("{line}\n"))
+        .collect();
+    assert_eq!(
+        String::from_utf8_lossy(&result.get_output().stdout),
+        expected
+    );
+
+    let report = std::fs::read_to_string(report).unwrap();
+    assert_eq!(
+        report,
+        format!(
+            "{}: converted 7 records, skipped 0 chunks, 0 records and 1 damaged ranges\n    chunk 0 (bytes 0x1a9b..0x2286): damaged record header\n",
+            damaged.display()
+        )
+    );
+}
+
+#[test]
+fn chunks_with_invalid_header_checksum_are_skipped() {
+    let damaged = damaged_file(
+        "chunks_with_invalid_header_checksum_are_skipped.evtx",
+        |data| {
+            // header_chunk_checksum
+            data[CHUNK + 0x7c] ^= 0xff
+        },
+    );
+    let summary_file = tmp_path("chunks_with_invalid_header_checksum_are_skipped.json");
+    let result = Command::cargo_bin("evtx2bodyfile")
+        .unwrap()
+        .arg("--summary-json")
+        .arg(&summary_file)
+        .arg(&damaged)
+        .assert()
+        .success();
+    assert!(result.get_output().stdout.is_empty());
+
+    let summary: Value =
+        serde_json::from_str(&std::fs::read_to_string(summary_file).unwrap()).unwrap();
+    let ranges = summary[0]["skipped_ranges"].as_array().unwrap();
+    assert_eq!(ranges.len(), 1);
+    assert_eq!(ranges[0]["chunk"], 0);
+    assert_eq!(ranges[0]["begin"], 0x1000);
+    assert_eq!(ranges[0]["end"], 0x11000);
+    assert_eq!(
+        ranges[0]["reason"],
+        "invalid chunk: invalid header checksum"
+    );
+}
+
+#[test]
+fn strict_mode_fails_on_damaged_records() {
+    let damaged = damaged_file("strict_mode_fails_on_damaged_records.evtx", |data| {
+        data[SECOND_RECORD..SECOND_RECORD + 4].copy_from_slice(b"XXXX")
+    });
+    Command::cargo_bin("evtx2bodyfile")
+        .unwrap()
+        .arg("--strict")
+        .arg(&damaged)
+        .assert()
+        .failure();
+}
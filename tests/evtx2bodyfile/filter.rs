@@ -0,0 +1,63 @@
+use super::{convert, expected_bodyfile};
+
+/// returns the lines of the expected bodyfile which satisfy `predicate`
+fn expected_lines_where<P: Fn(&str) -> bool>(name: &str, predicate: P) -> String {
+    expected_bodyfile(name)
+        .lines()
+        .filter(|line| predicate(line))
+        .map(|line| format!("{line}\n"))
+        .collect()
+}
+
+fn has_event_id(line: &str, event_id: u16) -> bool {
+    line.contains(&format!(r#""event_id":{event_id},"#))
+}
+
+#[test]
+fn include_event_ids() {
+    let output = convert(&["system.evtx"], &["--include-event-ids", "7000,7045"]);
+    let expected = expected_lines_where("system", |line| {
+        has_event_id(line, 7000) || has_event_id(line, 7045)
+    });
+    assert!(!expected.is_empty());
+    assert_eq!(output, expected);
+}
+
+#[test]
+fn exclude_event_ids() {
+    let output = convert(&["security.evtx"], &["--exclude-event-ids", "4624"]);
+    let expected = expected_lines_where("security", |line| !has_event_id(line, 4624));
+    assert_ne!(expected, expected_bodyfile("security"));
+    assert_eq!(output, expected);
+}
+
+#[test]
+fn include_channel_is_case_insensitive() {
+    let output = convert(
+        &["system.evtx"],
+        &["--include-channel", "microsoft-windows-sysmon/operational"],
+    );
+    let expected = expected_lines_where("system", |line| {
+        line.contains(r#""channel_name":"Microsoft-Windows-Sysmon/Operational""#)
+    });
+    assert_eq!(expected.lines().count(), 1);
+    assert_eq!(output, expected);
+}
+
+#[test]
+fn filters_are_combined() {
+    let output = convert(
+        &["security.evtx", "system.evtx"],
+        &[
+            "--include-channel",
+            "System",
+            "--exclude-event-ids",
+            "7000",
+        ],
+    );
+    let expected = expected_lines_where("system", |line| {
+        line.contains(r#""channel_name":"System""#) && !has_event_id(line, 7000)
+    });
+    assert!(!expected.is_empty());
+    assert_eq!(output, expected);
+}
@@ -0,0 +1,44 @@
+use std::path::PathBuf;
+
+use assert_cmd::Command;
+
+use crate::common::sample_file;
+
+mod bodyfile_json;
+mod error_handling;
+mod filter;
+mod name_fields;
+mod output_dir;
+mod parallel;
+mod progress;
+
+/// returns the expected bodyfile of one of the sample files
+pub(crate) fn expected_bodyfile(name: &str) -> String {
+    let mut data_path = PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap());
+    data_path.push("tests");
+    data_path.push("data");
+    data_path.push("evtx2bodyfile");
+    data_path.push(name);
+    std::fs::read_to_string(data_path.with_extension("bodyfile")).unwrap()
+}
+
+/// runs `evtx2bodyfile` and returns its output
+pub(crate) fn convert(files: &[&str], args: &[&str]) -> String {
+    let mut cmd = Command::cargo_bin("evtx2bodyfile").unwrap();
+    let result = cmd
+        .args(args)
+        .args(files.iter().map(|f| sample_file(f)))
+        .ok();
+    assert!(result.is_ok());
+    String::from_utf8(result.unwrap().stdout).unwrap()
+}
+
+#[test]
+fn bodyfile_is_unchanged() {
+    for name in ["security", "system", "application"] {
+        assert_eq!(
+            convert(&[&format!("{name}.evtx")], &[]),
+            expected_bodyfile(name)
+        );
+    }
+}
@@ -0,0 +1,66 @@
+use super::{convert, expected_bodyfile};
+
+/// returns the name column of every line
+fn names(bodyfile: &str) -> Vec<String> {
+    bodyfile
+        .lines()
+        .map(|line| {
+            let parts: Vec<_> = line.split('|').collect();
+            parts[1..parts.len() - 9].join("|")
+        })
+        .collect()
+}
+
+#[test]
+fn selected_fields() {
+    let output = convert(
+        &["security.evtx"],
+        &[
+            "--name-fields",
+            "eventid,provider,data.TargetUserName,data.IpAddress",
+        ],
+    );
+    let names = names(&output);
+    assert_eq!(names.len(), 8);
+    assert_eq!(
+        names[0],
+        "4624;Microsoft-Windows-Security-Auditing;jsmith;10.0.0.5"
+    );
+
+    // missing fields are empty
+    assert_eq!(names[1], "4688;Microsoft-Windows-Security-Auditing;;");
+}
+
+#[test]
+fn user_data_and_separator() {
+    let output = convert(
+        &["security.evtx"],
+        &[
+            "--include-event-ids",
+            "1102",
+            "--name-fields",
+            "recordid,time,channel,data.SubjectUserName",
+            "--name-separator",
+            " | ",
+        ],
+    );
+    let names = names(&output);
+    assert_eq!(names.len(), 1);
+    assert!(names[0].ends_with(" | Security | Administrator"));
+
+    // the timestamp is formatted in the same way as in the JSON object
+    let expected = expected_bodyfile("security");
+    let line = expected.lines().nth(3).unwrap();
+    let timestamp = names[0].split(" | ").nth(1).unwrap();
+    assert!(line.contains(&format!(r#""timestamp":"{timestamp}""#)));
+}
+
+#[test]
+fn invalid_field_is_rejected() {
+    assert_cmd::Command::cargo_bin("evtx2bodyfile")
+        .unwrap()
+        .args(["--name-fields", "eventid,unknown"])
+        .arg(super::sample_file("security.evtx"))
+        .assert()
+        .failure();
+}
@@ -0,0 +1,102 @@
+use std::path::{Path, PathBuf};
+
+use assert_cmd::Command;
+
+use crate::common::sample_file;
+
+use super::expected_bodyfile;
+
+/// creates an empty directory for the output of a test
+fn output_dir(name: &str) -> PathBuf {
+    let mut path = PathBuf::from(env!("CARGO_TARGET_TMPDIR"));
+    path.push("evtx2bodyfile");
+    path.push(name);
+    if path.exists() {
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+    path
+}
+
+/// copies a sample file into a subdirectory of the output directory, to
+/// create input files with the same name
+fn copy_sample(dir: &Path, subdir: &str, sample: &str, name: &str) -> PathBuf {
+    let mut path = dir.join("input");
+    path.push(subdir);
+    std::fs::create_dir_all(&path).unwrap();
+    path.push(name);
+    std::fs::copy(sample_file(sample), &path).unwrap();
+    path
+}
+
+#[test]
+fn one_file_per_input() {
+    let dir = output_dir("one_file_per_input");
+    let out = dir.join("out");
+    Command::cargo_bin("evtx2bodyfile")
+        .unwrap()
+        .arg("--output-dir")
+        .arg(&out)
+        .arg(sample_file("security.evtx"))
+        .arg(sample_file("system.evtx"))
+        .assert()
+        .success()
+        .stdout("");
+
+    for name in ["security", "system"] {
+        let output = std::fs::read_to_string(out.join(format!("{name}.bodyfile"))).unwrap();
+        assert_eq!(output, expected_bodyfile(name));
+    }
+}
+
+#[test]
+fn name_collisions_are_resolved_in_input_order() {
+    let dir = output_dir("name_collisions_are_resolved_in_input_order");
+    let first = copy_sample(&dir, "a", "system.evtx", "Security.evtx");
+    let second = copy_sample(&dir, "b", "security.evtx", "Security.evtx");
+    let out = dir.join("out");
+
+    Command::cargo_bin("evtx2bodyfile")
+        .unwrap()
+        .args(["--threads", "2", "--output-dir"])
+        .arg(&out)
+        .arg(&first)
+        .arg(&second)
+        .assert()
+        .success();
+
+    let read = |name: &str| std::fs::read_to_string(out.join(name)).unwrap();
+    assert_eq!(read("Security.bodyfile"), expected_bodyfile("system"));
+    assert_eq!(read("Security_2.bodyfile"), expected_bodyfile("security"));
+}
+
+#[test]
+fn existing_files_require_overwrite() {
+    let dir = output_dir("existing_files_require_overwrite");
+    let out = dir.join("out");
+    std::fs::create_dir_all(&out).unwrap();
+    std::fs::write(out.join("security.bodyfile"), "old content").unwrap();
+
+    Command::cargo_bin("evtx2bodyfile")
+        .unwrap()
+        .arg("--output-dir")
+        .arg(&out)
+        .arg(sample_file("security.evtx"))
+        .assert()
+        .failure();
+    assert_eq!(
+        std::fs::read_to_string(out.join("security.bodyfile")).unwrap(),
+        "old content"
+    );
+
+    Command::cargo_bin("evtx2bodyfile")
+        .unwrap()
+        .args(["--overwrite", "--output-dir"])
+        .arg(&out)
+        .arg(sample_file("security.evtx"))
+        .assert()
+        .success();
+    assert_eq!(
+        std::fs::read_to_string(out.join("security.bodyfile")).unwrap(),
+        expected_bodyfile("security")
+    );
+}
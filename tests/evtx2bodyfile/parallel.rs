@@ -0,0 +1,89 @@
+use std::path::PathBuf;
+
+use assert_cmd::Command;
+
+use crate::common::sample_file;
+
+use super::{convert, expected_bodyfile};
+
+const SAMPLES: [&str; 3] = ["security.evtx", "system.evtx", "application.evtx"];
+
+fn expected_lines() -> String {
+    ["security", "system", "application"]
+        .into_iter()
+        .map(expected_bodyfile)
+        .collect()
+}
+
+fn sorted_lines(output: &str) -> Vec<&str> {
+    let mut lines: Vec<_> = output.lines().collect();
+    lines.sort_unstable();
+    lines
+}
+
+/// creates a file which cannot be parsed as evtx file
+fn broken_file(name: &str) -> PathBuf {
+    let mut path = PathBuf::from(env!("CARGO_TARGET_TMPDIR"));
+    path.push("evtx2bodyfile");
+    std::fs::create_dir_all(&path).unwrap();
+    path.push(name);
+    std::fs::write(&path, "no evtx file").unwrap();
+    path
+}
+
+#[test]
+fn files_are_written_in_order() {
+    for threads in ["1", "2", "3"] {
+        let output = convert(&SAMPLES, &["--threads", threads]);
+        assert_eq!(output, expected_lines());
+    }
+}
+
+#[test]
+fn interleaved_output_contains_all_lines() {
+    let output = convert(&SAMPLES, &["--interleave", "--threads", "3"]);
+    let expected = expected_lines();
+    assert_eq!(sorted_lines(&output), sorted_lines(&expected));
+}
+
+#[test]
+fn errors_are_attributed_to_the_file() {
+    let broken = broken_file("errors_are_attributed_to_the_file.evtx");
+    let result = Command::cargo_bin("evtx2bodyfile")
+        .unwrap()
+        .arg(sample_file("security.evtx"))
+        .arg(&broken)
+        .arg(sample_file("system.evtx"))
+        .assert()
+        .failure();
+    let output = result.get_output();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains(&format!("'{}'", broken.display())));
+
+    // the other files have been converted nevertheless
+    let expected = expected_bodyfile("security") + &expected_bodyfile("system");
+    assert_eq!(String::from_utf8_lossy(&output.stdout), expected);
+}
+
+#[test]
+fn strict_mode_does_not_start_new_files() {
+    let broken = broken_file("strict_mode_does_not_start_new_files.evtx");
+    let result = Command::cargo_bin("evtx2bodyfile")
+        .unwrap()
+        .args(["--strict", "--threads", "1"])
+        .arg(&broken)
+        .arg(sample_file("security.evtx"))
+        .assert()
+        .failure();
+    assert!(result.get_output().stdout.is_empty());
+}
+
+#[test]
+fn zero_threads_are_rejected() {
+    Command::cargo_bin("evtx2bodyfile")
+        .unwrap()
+        .args(["--threads", "0"])
+        .arg(sample_file("security.evtx"))
+        .assert()
+        .failure();
+}
@@ -0,0 +1,14 @@
+use assert_cmd::Command;
+
+use crate::common::sample_file;
+
+#[test]
+fn no_progress_without_terminal() {
+    let result = Command::cargo_bin("evtx2bodyfile")
+        .unwrap()
+        .arg(sample_file("security.evtx"))
+        .arg(sample_file("system.evtx"))
+        .assert()
+        .success();
+    assert!(result.get_output().stderr.is_empty());
+}
@@ -0,0 +1,187 @@
+use std::path::PathBuf;
+
+use assert_cmd::Command;
+use serde_json::Value;
+
+fn anomalies_file(name: &str) -> PathBuf {
+    let mut data_path = PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap());
+    data_path.push("tests");
+    data_path.push("data");
+    data_path.push("anomalies");
+    data_path.push(name);
+    data_path
+}
+
+/// runs `evtxanalyze anomalies` with JSON output for the Security and the
+/// System log and returns the findings
+fn findings_with(args: &[&str]) -> Vec<Value> {
+    let mut cmd = Command::cargo_bin("evtxanalyze").unwrap();
+    let result = cmd
+        .arg("anomalies")
+        .args(["--format", "json"])
+        .args(args)
+        .arg(anomalies_file("Security.evtx"))
+        .arg(anomalies_file("System.evtx"))
+        .ok();
+    assert!(result.is_ok());
+
+    let stdout = result.unwrap().stdout;
+    match serde_json::from_slice(&stdout).unwrap() {
+        Value::Array(findings) => findings,
+        _ => panic!("findings must be an array"),
+    }
+}
+
+fn of_heuristic<'a>(findings: &'a [Value], heuristic: &str) -> Vec<&'a Value> {
+    findings
+        .iter()
+        .filter(|f| f["heuristic"] == heuristic)
+        .collect()
+}
+
+fn record_ids(finding: &Value) -> Vec<u64> {
+    finding["evidence"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["record_id"].as_u64().unwrap())
+        .collect()
+}
+
+#[test]
+fn all_heuristics_are_enabled_by_default() {
+    let findings = findings_with(&[]);
+    assert_eq!(findings.len(), 9);
+
+    let timestamps: Vec<_> = findings
+        .iter()
+        .map(|f| f["first_timestamp"].as_str().unwrap())
+        .collect();
+    let mut sorted = timestamps.clone();
+    sorted.sort();
+    assert_eq!(timestamps, sorted);
+}
+
+#[test]
+fn cleared_logs() {
+    let findings = findings_with(&[]);
+    let cleared = of_heuristic(&findings, "log-cleared");
+    assert_eq!(cleared.len(), 2);
+    assert!(cleared.iter().all(|f| f["severity"] == "high"));
+    assert!(cleared[0]["description"]
+        .as_str()
+        .unwrap()
+        .contains("security log"));
+    assert!(cleared[1]["description"]
+        .as_str()
+        .unwrap()
+        .contains("'Application'"));
+}
+
+#[test]
+fn audit_policy_changes() {
+    let findings = findings_with(&[]);
+    let changes = of_heuristic(&findings, "audit-policy");
+    assert_eq!(changes.len(), 1);
+    assert_eq!(record_ids(changes[0]), vec![8]);
+    assert_eq!(changes[0]["first_timestamp"], "2024-03-04T07:05:00Z");
+}
+
+#[test]
+fn service_installs_outside_business_hours() {
+    let findings = findings_with(&[]);
+    let installs = of_heuristic(&findings, "service-install");
+
+    // 'updater' has been installed on a monday morning
+    assert_eq!(installs.len(), 2);
+    assert!(installs[0]["description"]
+        .as_str()
+        .unwrap()
+        .contains("'evilsvc'"));
+    assert!(installs[1]["description"]
+        .as_str()
+        .unwrap()
+        .contains("'backdoor'"));
+}
+
+#[test]
+fn business_hours_respect_the_timezone() {
+    // 'evilsvc' has been installed at 08:30 local time, but 'backdoor' has
+    // been installed on a saturday
+    let findings = findings_with(&["--timezone", "+02:00"]);
+    let installs = of_heuristic(&findings, "service-install");
+    assert_eq!(installs.len(), 1);
+    assert!(installs[0]["description"]
+        .as_str()
+        .unwrap()
+        .contains("'backdoor'"));
+
+    let findings = findings_with(&["--business-hours", "6-19"]);
+    assert_eq!(of_heuristic(&findings, "service-install").len(), 1);
+}
+
+#[test]
+fn short_lived_accounts() {
+    let findings = findings_with(&[]);
+    let accounts = of_heuristic(&findings, "short-lived-account");
+    assert_eq!(accounts.len(), 1);
+    assert_eq!(record_ids(accounts[0]), vec![7, 13]);
+    assert_eq!(accounts[0]["first_timestamp"], "2024-03-04T06:55:00Z");
+    assert_eq!(accounts[0]["last_timestamp"], "2024-03-04T07:40:00Z");
+}
+
+#[test]
+fn implausible_logons() {
+    let findings = findings_with(&[]);
+    let logons = of_heuristic(&findings, "implausible-logon");
+
+    // neither the window manager nor the repeated logons of bob from the
+    // same client are reported
+    assert_eq!(logons.len(), 1);
+    assert_eq!(record_ids(logons[0]), vec![2, 3]);
+    assert_eq!(logons[0]["severity"], "high");
+
+    let findings = findings_with(&["--logon-interval", "3m"]);
+    assert!(of_heuristic(&findings, "implausible-logon").is_empty());
+}
+
+#[test]
+fn gaps() {
+    let findings = findings_with(&[]);
+    let gaps = of_heuristic(&findings, "gaps");
+    assert_eq!(gaps.len(), 2);
+
+    assert_eq!(record_ids(gaps[0]), vec![8, 12]);
+    assert_eq!(gaps[0]["severity"], "high");
+    assert!(gaps[0]["evidence"][0]["file"]
+        .as_str()
+        .unwrap()
+        .ends_with("Security.evtx"));
+
+    assert_eq!(record_ids(gaps[1]), vec![4, 5]);
+    assert_eq!(gaps[1]["severity"], "low");
+}
+
+#[test]
+fn heuristics_can_be_disabled() {
+    let findings = findings_with(&["--disable", "gaps,log-cleared", "-D", "audit-policy"]);
+    assert_eq!(findings.len(), 4);
+    for heuristic in ["gaps", "log-cleared", "audit-policy"] {
+        assert!(of_heuristic(&findings, heuristic).is_empty());
+    }
+}
+
+#[test]
+fn table_output() {
+    let mut cmd = Command::cargo_bin("evtxanalyze").unwrap();
+    let result = cmd.arg("anomalies").arg(anomalies_file("System.evtx")).ok();
+    assert!(result.is_ok());
+
+    let stdout = String::from_utf8(result.unwrap().stdout).unwrap();
+    let lines: Vec<_> = stdout.lines().collect();
+    assert_eq!(lines.len(), 4);
+    assert!(lines[0].starts_with("SEVERITY"));
+    assert!(lines[1].starts_with("medium    service-install"));
+    assert!(lines[1].contains("System.evtx:1"));
+    assert!(lines[3].contains("System.evtx:4,System.evtx:5"));
+}
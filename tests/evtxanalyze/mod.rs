@@ -0,0 +1,36 @@
+use std::path::PathBuf;
+
+use assert_cmd::Command;
+
+use crate::common::sample_file;
+
+mod anomalies;
+mod pstree;
+mod sessions;
+
+/// runs `evtxanalyze pstree` and returns its output
+pub(crate) fn pstree(file: &str, args: &[&str]) -> String {
+    let mut cmd = Command::cargo_bin("evtxanalyze").unwrap();
+    let result = cmd.arg("pstree").args(args).arg(sample_file(file)).ok();
+    assert!(result.is_ok());
+    String::from_utf8(result.unwrap().stdout).unwrap()
+}
+
+/// directory which contains the logs of a terminal server
+pub(crate) fn sessions_dir() -> PathBuf {
+    let mut data_path = PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap());
+    data_path.push("tests");
+    data_path.push("data");
+    data_path.push("sessions");
+    data_path
+}
+
+/// directory which contains the logs of a terminal server, with additional
+/// logons which are used to test the timeline output
+pub(crate) fn sessions_timeline_dir() -> PathBuf {
+    let mut data_path = PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap());
+    data_path.push("tests");
+    data_path.push("data");
+    data_path.push("sessions_timeline");
+    data_path
+}
@@ -0,0 +1,126 @@
+use assert_cmd::Command;
+use serde_json::{json, Value};
+
+use crate::common::sample_file;
+
+use super::pstree;
+
+#[test]
+fn json_is_nested() {
+    let tree: Value =
+        serde_json::from_str(&pstree("security.evtx", &["--format", "json"])).unwrap();
+    let roots = tree.as_array().unwrap();
+    assert_eq!(roots.len(), 1);
+
+    let cmd = &roots[0];
+    assert_eq!(cmd["pid"], json!(0x1f40));
+    assert_eq!(cmd["image"], json!("C:\\Windows\\System32\\cmd.exe"));
+    assert_eq!(cmd["command_line"], json!("cmd.exe /c \"echo a,b;c\""));
+    assert_eq!(cmd["user"], json!("jsmith"));
+    assert_eq!(cmd["start"], json!("2024-03-12T08:05:00Z"));
+    assert_eq!(cmd["end"], Value::Null);
+
+    let children = cmd["children"].as_array().unwrap();
+    assert_eq!(children.len(), 1);
+    assert_eq!(children[0]["pid"], json!(0x2000));
+    assert_eq!(children[0]["parent_pid"], json!(0x1f40));
+    assert_eq!(children[0]["user"], json!("Administrator"));
+    assert_eq!(children[0]["children"], json!([]));
+}
+
+#[test]
+fn dot_has_one_node_per_process() {
+    let dot = pstree("security.evtx", &["--format", "dot"]);
+    assert!(dot.starts_with("digraph {"));
+    assert!(dot.trim_end().ends_with('}'));
+
+    let nodes: Vec<_> = dot.lines().filter(|l| l.contains("[label=")).collect();
+    assert_eq!(nodes.len(), 2);
+    let edges: Vec<_> = dot.lines().filter(|l| l.contains("->")).collect();
+    assert_eq!(edges, vec!["p2 -> p10;"]);
+}
+
+#[test]
+fn dot_labels_are_escaped() {
+    let dot = pstree("security.evtx", &["--format", "dot"]);
+    let cmd = dot.lines().find(|l| l.starts_with("p2 [")).unwrap();
+    assert!(cmd.contains(r#"cmd.exe /c \"echo a,b;c\""#));
+
+    // every quote inside of the label must be escaped
+    let label = cmd
+        .strip_prefix("p2 [label=\"")
+        .unwrap()
+        .split_once("\", shape=")
+        .unwrap()
+        .0;
+    let unescaped = label.replace("\\\\", "").replace("\\\"", "").contains('"');
+    assert!(!unescaped);
+}
+
+fn json_tree(args: &[&str]) -> Value {
+    let mut args = args.to_vec();
+    args.extend(["--format", "json"]);
+    serde_json::from_str(&pstree("security.evtx", &args)).unwrap()
+}
+
+#[test]
+fn root_by_image() {
+    let tree = json_tree(&["--root", "POWERSHELL"]);
+    let roots = tree.as_array().unwrap();
+    assert_eq!(roots.len(), 1);
+    assert_eq!(roots[0]["pid"], json!(0x2000));
+    assert_eq!(roots[0]["context_only"], json!(false));
+}
+
+#[test]
+fn root_by_pid() {
+    for pid in ["0x1f40", "8000"] {
+        let tree = json_tree(&["--root", pid]);
+        let roots = tree.as_array().unwrap();
+        assert_eq!(roots.len(), 1);
+        assert_eq!(roots[0]["pid"], json!(0x1f40));
+        assert_eq!(roots[0]["children"].as_array().unwrap().len(), 1);
+    }
+}
+
+#[test]
+fn ancestors_are_context() {
+    let tree = json_tree(&["--from", "2024-03-12T10:00:00"]);
+    let cmd = &tree[0];
+    assert_eq!(cmd["pid"], json!(0x1f40));
+    assert_eq!(cmd["context_only"], json!(true));
+    assert_eq!(cmd["children"][0]["context_only"], json!(false));
+
+    let markdown = pstree(
+        "security.evtx",
+        &["--from", "2024-03-12T10:00:00", "--format", "markdown"],
+    );
+    let lines: Vec<_> = markdown.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].ends_with("*(context only)*"));
+    assert!(!lines[1].ends_with("*(context only)*"));
+}
+
+#[test]
+fn processes_after_window_are_hidden() {
+    let tree = json_tree(&["--to", "2024-03-12T10:00:00"]);
+    assert_eq!(tree.as_array().unwrap().len(), 1);
+    assert_eq!(tree[0]["children"], json!([]));
+
+    let csv = pstree("security.evtx", &["--to", "2024-03-12T10:00:00"]);
+    assert_eq!(csv.lines().count(), 2);
+}
+
+#[test]
+fn reject_invalid_timestamp() {
+    for option in ["--from", "--to"] {
+        let mut cmd = Command::cargo_bin("evtxanalyze").unwrap();
+        let assert = cmd
+            .args(["pstree", option, "yesterday"])
+            .arg(sample_file("security.evtx"))
+            .assert();
+        let stderr = String::from_utf8_lossy(&assert.get_output().stderr).to_string();
+        assert.code(2);
+        assert!(stderr.contains("invalid timestamp: 'yesterday'"));
+    }
+}
@@ -0,0 +1,103 @@
+use std::{collections::HashMap, path::Path};
+
+use assert_cmd::Command;
+
+use super::{sessions_dir, sessions_timeline_dir};
+
+/// runs `evtxanalyze sessions` and returns the sessions as maps of column
+/// names to values
+fn sessions_with(dir: &Path, args: &[&str]) -> Vec<HashMap<String, String>> {
+    let mut cmd = Command::cargo_bin("evtxanalyze").unwrap();
+    let result = cmd.arg("sessions").args(args).arg(dir).ok();
+    assert!(result.is_ok());
+
+    let stdout = result.unwrap().stdout;
+    let mut reader = csv::Reader::from_reader(&stdout[..]);
+    reader.deserialize().map(|r| r.unwrap()).collect()
+}
+
+fn sessions() -> Vec<HashMap<String, String>> {
+    sessions_with(&sessions_dir(), &[])
+}
+
+fn session_of(sessions: &[HashMap<String, String>], user: &str) -> HashMap<String, String> {
+    let mut matching = sessions
+        .iter()
+        .filter(|s| s.get("usernames").or_else(|| s.get("user")).unwrap() == user);
+    let session = matching.next().unwrap().clone();
+    assert!(matching.next().is_none(), "multiple sessions of {user}");
+    session
+}
+
+#[test]
+fn terminal_services_events_are_merged() {
+    let sessions = sessions();
+    assert_eq!(sessions.len(), 3);
+
+    let session = session_of(&sessions, "jsmith");
+    assert_eq!(session["terminal_services_session_id"], "2");
+    assert_eq!(session["partially_reconstructed"], "false");
+    assert!(session["clients"].contains("10.0.0.5"));
+
+    // 2 authentications, 2 logons, 2 logoffs, 1 user initiated logoff and
+    // 5 events of the LocalSessionManager
+    assert_eq!(session["events"], "12");
+    assert_eq!(session["begin"], "2024-03-12T09:00:00");
+    assert_eq!(session["end"], "2024-03-12T12:00:01");
+}
+
+#[test]
+fn reconnects_create_activity_intervals() {
+    let session = session_of(&sessions(), "jsmith");
+    assert_eq!(
+        session["activity"],
+        "2024-03-12T09:00:02/2024-03-12T10:00:00, 2024-03-12T11:00:01/2024-03-12T11:59:59"
+    );
+}
+
+#[test]
+fn sessions_without_security_events_are_partial() {
+    let sessions = sessions();
+    let session = session_of(&sessions, "admin");
+    assert_eq!(session["terminal_services_session_id"], "3");
+    assert_eq!(session["partially_reconstructed"], "true");
+    assert_eq!(session["clients"], "10.0.0.9");
+    assert_eq!(
+        session["activity"],
+        "2024-03-11T15:00:00/2024-03-11T16:00:00"
+    );
+
+    let local = session_of(&sessions, "alice");
+    assert_eq!(local["terminal_services_session_id"], "");
+    assert_eq!(local["partially_reconstructed"], "false");
+}
+
+#[test]
+fn timeline_csv() {
+    let sessions = sessions_with(&sessions_timeline_dir(), &["--format", "csv"]);
+    assert_eq!(sessions.len(), 4);
+
+    let session = session_of(&sessions, "jsmith");
+    assert_eq!(session["domain"], "CORP");
+    assert_eq!(session["logon_type"], "10");
+    assert_eq!(session["logon"], "2024-03-12T09:00:02Z");
+    assert_eq!(session["logoff"], "2024-03-12T11:59:59Z");
+    assert_eq!(session["duration"], "02:59:57");
+
+    let evidence: Vec<_> = session["evidence"].split(';').collect();
+    assert_eq!(evidence.len(), 12);
+    assert!(evidence.contains(&"Security:3"));
+    assert!(
+        evidence.contains(&"Microsoft-Windows-TerminalServices-LocalSessionManager/Operational:4")
+    );
+}
+
+#[test]
+fn timeline_csv_of_open_session() {
+    let sessions = sessions_with(&sessions_timeline_dir(), &["--format", "csv"]);
+    let session = session_of(&sessions, "bob");
+    assert_eq!(session["logon"], "2024-03-12T13:00:00Z");
+    assert_eq!(session["logoff"], "");
+    assert_eq!(session["duration"], "");
+    assert_eq!(session["source"], "WKS07");
+}
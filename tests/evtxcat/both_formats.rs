@@ -0,0 +1,52 @@
+use assert_cmd::Command;
+
+use crate::common::sample_file;
+
+/// splits the output at the separator lines and checks every section
+#[test]
+fn xml_and_json() {
+    let mut cmd = Command::cargo_bin("evtxcat").unwrap();
+    let result = cmd
+        .args(["-F", "both", "--min", "3", "--max", "5"])
+        .arg(sample_file("security.evtx"))
+        .ok();
+    assert!(result.is_ok());
+    let stdout = String::from_utf8(result.unwrap().stdout).unwrap();
+
+    let mut sections: Vec<(String, Vec<&str>)> = Vec::new();
+    for line in stdout.lines() {
+        match line
+            .strip_prefix("<!-- ")
+            .and_then(|l| l.strip_suffix(" -->"))
+        {
+            Some(separator) => sections.push((separator.to_owned(), Vec::new())),
+            None => sections.last_mut().unwrap().1.push(line),
+        }
+    }
+
+    let separators: Vec<_> = sections.iter().map(|(s, _)| &s[..]).collect();
+    assert_eq!(
+        separators,
+        vec![
+            "record 3: xml",
+            "record 3: json",
+            "end of record 3",
+            "record 4: xml",
+            "record 4: json",
+            "end of record 4",
+            "record 5: xml",
+            "record 5: json",
+            "end of record 5",
+        ]
+    );
+
+    for (id, chunk) in [3, 4, 5].iter().zip(sections.chunks(3)) {
+        let xml = chunk[0].1.join("\n");
+        assert!(xml.contains(&format!("<EventRecordID>{id}</EventRecordID>")));
+
+        let json: serde_json::Value = serde_json::from_str(&chunk[1].1.join("\n")).unwrap();
+        assert_eq!(json["Event"]["System"]["EventRecordID"], *id);
+
+        assert!(chunk[2].1.is_empty());
+    }
+}
@@ -0,0 +1,33 @@
+use assert_cmd::Command;
+
+use crate::common::sample_file;
+
+fn evtxcat(args: &[&str]) -> String {
+    let mut cmd = Command::cargo_bin("evtxcat").unwrap();
+    let result = cmd
+        .args(["-i", "9"])
+        .args(args)
+        .arg(sample_file("security.evtx"))
+        .ok();
+    assert!(result.is_ok());
+    String::from_utf8(result.unwrap().stdout).unwrap()
+}
+
+#[test]
+fn highlight_xml() {
+    let stdout = evtxcat(&["--highlight", "sedebug", "--highlight", "ADMINISTRATOR"]);
+    assert!(stdout.contains(r#"<Data Name="PrivilegeList">>>>SeDebug<<<Privilege</Data>"#));
+    assert!(stdout.contains(r#"<Data Name="SubjectUserName">>>>Administrator<<<</Data>"#));
+}
+
+#[test]
+fn highlight_json() {
+    let stdout = evtxcat(&["-F", "json", "--highlight", "SeDebug"]);
+    assert!(stdout.contains(r#""PrivilegeList": ">>>SeDebug<<<Privilege""#));
+}
+
+#[test]
+fn highlight_case_sensitive() {
+    let stdout = evtxcat(&["--highlight", "sedebug", "--case-sensitive"]);
+    assert!(!stdout.contains(">>>"));
+}
@@ -0,0 +1,24 @@
+use assert_cmd::Command;
+
+use crate::common::sample_file;
+
+mod both_formats;
+mod highlight;
+mod multiple_files;
+mod output;
+mod ranges;
+mod time_window;
+
+/// runs `evtxcat` with XML output and returns the record ids of all
+/// displayed records
+pub(crate) fn record_ids(file: &str, args: &[&str]) -> Vec<u64> {
+    let mut cmd = Command::cargo_bin("evtxcat").unwrap();
+    let result = cmd.args(args).arg(sample_file(file)).ok();
+    assert!(result.is_ok());
+    String::from_utf8(result.unwrap().stdout)
+        .unwrap()
+        .lines()
+        .filter_map(|l| l.trim().strip_prefix("<EventRecordID>"))
+        .map(|l| l.strip_suffix("</EventRecordID>").unwrap().parse().unwrap())
+        .collect()
+}
@@ -0,0 +1,76 @@
+use assert_cmd::Command;
+
+use crate::common::sample_file;
+
+/// returns the name of the source file and the record id of every record
+fn sourced_record_ids(files: &[&str], args: &[&str]) -> Vec<(String, u64)> {
+    let mut cmd = Command::cargo_bin("evtxcat").unwrap();
+    let result = cmd
+        .args(args)
+        .args(files.iter().map(|f| sample_file(f)))
+        .ok();
+    assert!(result.is_ok());
+
+    let mut source = None;
+    let mut records = Vec::new();
+    for line in String::from_utf8(result.unwrap().stdout).unwrap().lines() {
+        if let Some(s) = line
+            .strip_prefix("<!-- source: ")
+            .and_then(|l| l.strip_suffix(" -->"))
+        {
+            source = Some(s.rsplit('/').next().unwrap().to_owned());
+        } else if let Some(id) = line.trim().strip_prefix("<EventRecordID>") {
+            let id = id
+                .strip_suffix("</EventRecordID>")
+                .unwrap()
+                .parse()
+                .unwrap();
+            records.push((source.take().expect("missing source"), id));
+        }
+    }
+    records
+}
+
+fn ids(records: &[(&str, u64)]) -> Vec<(String, u64)> {
+    records.iter().map(|(s, id)| (s.to_string(), *id)).collect()
+}
+
+#[test]
+fn records_in_file_order() {
+    assert_eq!(
+        sourced_record_ids(
+            &["security.evtx", "system.evtx"],
+            &["--min", "9", "--max", "101"]
+        ),
+        ids(&[
+            ("security.evtx", 9),
+            ("security.evtx", 10),
+            ("system.evtx", 100),
+            ("system.evtx", 101)
+        ])
+    );
+}
+
+#[test]
+fn record_ids_per_file() {
+    assert_eq!(
+        sourced_record_ids(&["security.evtx", "security.evtx"], &["-i", "3"]),
+        ids(&[("security.evtx", 3), ("security.evtx", 3)])
+    );
+}
+
+#[test]
+fn sort_records_by_time() {
+    assert_eq!(
+        sourced_record_ids(
+            &["security.evtx", "system.evtx"],
+            &["--to", "2024-03-12T08:07:00Z", "--sort"]
+        ),
+        ids(&[
+            ("security.evtx", 1),
+            ("system.evtx", 100),
+            ("security.evtx", 2),
+            ("system.evtx", 101)
+        ])
+    );
+}
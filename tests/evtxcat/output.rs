@@ -0,0 +1,89 @@
+use std::path::PathBuf;
+
+use assert_cmd::Command;
+
+use crate::common::sample_file;
+
+/// returns an empty directory for the files written by a single test
+fn output_dir(name: &str) -> PathBuf {
+    let mut dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR"));
+    dir.push("evtxcat");
+    dir.push(name);
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn evtxcat(args: &[&str], output: &PathBuf) -> assert_cmd::assert::Assert {
+    Command::cargo_bin("evtxcat")
+        .unwrap()
+        .args(args)
+        .arg("--output")
+        .arg(output)
+        .arg(sample_file("security.evtx"))
+        .assert()
+}
+
+#[test]
+fn write_records_to_file() {
+    let output = output_dir("write_records_to_file").join("records.xml");
+    let result = evtxcat(&["--id", "3"], &output).success();
+    assert!(result.get_output().stdout.is_empty());
+
+    let contents = std::fs::read_to_string(&output).unwrap();
+    assert!(contents.contains("<EventRecordID>3</EventRecordID>"));
+
+    // no temporary files are left behind
+    assert_eq!(
+        std::fs::read_dir(output.parent().unwrap()).unwrap().count(),
+        1
+    );
+}
+
+#[test]
+fn json_in_file_is_not_colored() {
+    let output = output_dir("json_in_file_is_not_colored").join("records.json");
+    evtxcat(&["--id", "3", "-F", "json"], &output).success();
+
+    let contents = std::fs::read_to_string(&output).unwrap();
+    assert!(!contents.contains('\x1b'));
+    let value: serde_json::Value = serde_json::from_str(&contents).unwrap();
+    assert_eq!(value["Event"]["System"]["EventRecordID"], 3);
+}
+
+#[test]
+fn refuse_to_overwrite() {
+    let output = output_dir("refuse_to_overwrite").join("records.xml");
+    std::fs::write(&output, "evidence").unwrap();
+
+    evtxcat(&["--id", "3"], &output).code(exitcode::CANTCREAT);
+    assert_eq!(std::fs::read_to_string(&output).unwrap(), "evidence");
+
+    evtxcat(&["--id", "3", "--force"], &output).success();
+    let contents = std::fs::read_to_string(&output).unwrap();
+    assert!(contents.contains("<EventRecordID>3</EventRecordID>"));
+}
+
+#[test]
+fn exit_code_if_no_records_matched() {
+    let output = output_dir("exit_code_if_no_records_matched").join("records.xml");
+    evtxcat(&["--id", "4711"], &output).code(1);
+    assert!(std::fs::read_to_string(&output).unwrap().is_empty());
+
+    Command::cargo_bin("evtxcat")
+        .unwrap()
+        .args(["--id", "4711"])
+        .arg(sample_file("security.evtx"))
+        .assert()
+        .code(1);
+}
+
+#[test]
+fn force_requires_output() {
+    Command::cargo_bin("evtxcat")
+        .unwrap()
+        .arg("--force")
+        .arg(sample_file("security.evtx"))
+        .assert()
+        .failure();
+}
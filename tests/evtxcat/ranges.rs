@@ -0,0 +1,80 @@
+use std::io::Write;
+
+use assert_cmd::Command;
+
+use crate::common::sample_file;
+
+use super::record_ids;
+
+/// returns the messages written to stderr
+fn stderr_of(args: &[&str]) -> String {
+    let mut cmd = Command::cargo_bin("evtxcat").unwrap();
+    let result = cmd.args(args).arg(sample_file("security.evtx")).ok();
+    assert!(result.is_ok());
+    String::from_utf8(result.unwrap().stderr).unwrap()
+}
+
+#[test]
+fn open_ended_ranges() {
+    assert_eq!(
+        record_ids("security.evtx", &["--range", "..3"]),
+        vec![1, 2, 3]
+    );
+    assert_eq!(
+        record_ids("security.evtx", &["--range", "8.."]),
+        vec![8, 9, 10]
+    );
+}
+
+#[test]
+fn list_of_ranges() {
+    assert_eq!(
+        record_ids("security.evtx", &["--range", "..2,9..,4"]),
+        vec![1, 2, 4, 9, 10]
+    );
+    assert_eq!(
+        record_ids("security.evtx", &["--range", "2..4,3..6"]),
+        vec![2, 3, 4, 5]
+    );
+}
+
+#[test]
+fn report_missing_ids() {
+    assert!(
+        stderr_of(&["--range", "2..4,3..6"]).contains("1 of the requested record ids are missing")
+    );
+    assert!(stderr_of(&["--range", "1..20"]).contains("12 of the requested record ids are missing"));
+
+    // open ends are limited to the ids which are contained in the file
+    assert!(stderr_of(&["--range", "..5"]).is_empty());
+    assert!(stderr_of(&["--range", "5.."]).contains("2 of the requested record ids are missing"));
+}
+
+#[test]
+fn unreadable_records_are_not_missing() {
+    // the record 5 contains an invalid token at the beginning of its data
+    let mut data = std::fs::read(sample_file("security.evtx")).unwrap();
+    let record = (0..data.len() - 16)
+        .find(|&i| data[i..i + 4] == *b"**\0\0" && data[i + 8..i + 16] == 5u64.to_le_bytes())
+        .unwrap();
+    data[record + 24] = 0xff;
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    file.write_all(&data).unwrap();
+
+    let mut cmd = Command::cargo_bin("evtxcat").unwrap();
+    let result = cmd.args(["--range", "1..10"]).arg(file.path()).ok().unwrap();
+    let stderr = String::from_utf8(result.stderr).unwrap();
+    assert!(stderr.contains("unable to read the record 5"));
+    assert!(stderr.contains("1 records of '"));
+    assert!(stderr.contains("2 of the requested record ids are missing"));
+}
+
+#[test]
+fn range_conflicts_with_id() {
+    Command::cargo_bin("evtxcat")
+        .unwrap()
+        .args(["--range", "1..3", "--id", "2"])
+        .arg(sample_file("security.evtx"))
+        .assert()
+        .failure();
+}
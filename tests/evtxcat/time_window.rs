@@ -0,0 +1,77 @@
+use assert_cmd::Command;
+
+use crate::common::sample_file;
+
+use super::record_ids;
+
+#[test]
+fn select_by_time() {
+    assert_eq!(
+        record_ids(
+            "security.evtx",
+            &[
+                "--from",
+                "2024-03-12T13:59:00Z",
+                "--to",
+                "2024-03-12T14:00:30Z"
+            ]
+        ),
+        vec![5, 9]
+    );
+    assert_eq!(
+        record_ids("security.evtx", &["--from", "2024-03-12 14:00:30"]),
+        vec![8, 10]
+    );
+}
+
+#[test]
+fn select_by_time_and_id() {
+    assert_eq!(
+        record_ids(
+            "security.evtx",
+            &["--min", "2", "--max", "9", "--from", "2024-03-12T08:06:00Z"]
+        ),
+        vec![3, 4, 5, 8, 9]
+    );
+    Command::cargo_bin("evtxcat")
+        .unwrap()
+        .args(["-i", "3", "--from", "2024-03-12T08:11:00Z"])
+        .arg(sample_file("security.evtx"))
+        .assert()
+        .code(1)
+        .stdout("");
+}
+
+#[test]
+fn select_by_time_as_json() {
+    let mut cmd = Command::cargo_bin("evtxcat").unwrap();
+    let result = cmd
+        .args(["-F", "json", "--to", "2024-03-12T08:06:00Z"])
+        .arg(sample_file("security.evtx"))
+        .ok();
+    assert!(result.is_ok());
+    let stdout = String::from_utf8(result.unwrap().stdout).unwrap();
+    assert!(stdout.contains(r#""EventRecordID": 1,"#));
+    assert!(stdout.contains(r#""EventRecordID": 2,"#));
+    assert!(!stdout.contains(r#""EventRecordID": 3,"#));
+}
+
+#[test]
+fn stop_reading_sorted_files() {
+    assert_eq!(
+        record_ids(
+            "system.evtx",
+            &["--to", "2024-03-12T09:00:00Z", "--assume-sorted"]
+        ),
+        vec![100, 101]
+    );
+
+    // the time window ends before record 8, which is newer than record 9
+    assert_eq!(
+        record_ids(
+            "security.evtx",
+            &["--to", "2024-03-12T14:00:30Z", "--assume-sorted"]
+        ),
+        vec![1, 2, 3, 4, 5]
+    );
+}
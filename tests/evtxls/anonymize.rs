@@ -0,0 +1,64 @@
+use assert_cmd::Command;
+
+use crate::common::sample_file;
+
+fn anonymized(keyfile: &std::path::Path) -> String {
+    let mut cmd = Command::cargo_bin("evtxls").unwrap();
+    let result = cmd
+        .arg("--anonymize")
+        .arg(keyfile)
+        .arg(sample_file("security.evtx"))
+        .ok();
+    String::from_utf8(result.unwrap().stdout).unwrap()
+}
+
+#[test]
+fn replace_names_by_pseudonyms() {
+    let dir = tempfile::tempdir().unwrap();
+    let keyfile = dir.path().join("key");
+    let output = anonymized(&keyfile);
+
+    for name in ["jsmith", "Administrator", "CORP", "WKS07", "10.0.0.5"] {
+        assert!(!output.contains(name), "'{name}' has not been replaced");
+    }
+    assert!(output.contains("\"TargetUserName\":\"user-"));
+    assert!(output.contains("\"IpAddress\":\"ip-"));
+
+    // SIDs and well known values are kept
+    assert!(output.contains("S-1-5-21-1-2-3-1104"));
+    assert!(output.contains("\"SubjectUserName\":\"-\""));
+
+    // the same key always yields the same pseudonyms
+    assert!(keyfile.exists());
+    assert_eq!(anonymized(&keyfile), output);
+
+    let other_key = dir.path().join("other_key");
+    assert_ne!(anonymized(&other_key), output);
+}
+
+#[cfg(unix)]
+#[test]
+fn create_private_keyfile() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempfile::tempdir().unwrap();
+    let keyfile = dir.path().join("key");
+    anonymized(&keyfile);
+    let mode = std::fs::metadata(&keyfile).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o600);
+}
+
+#[test]
+fn reject_invalid_keyfile() {
+    let dir = tempfile::tempdir().unwrap();
+    let keyfile = dir.path().join("key");
+    std::fs::write(&keyfile, "not a key").unwrap();
+
+    let mut cmd = Command::cargo_bin("evtxls").unwrap();
+    cmd.arg("--anonymize")
+        .arg(&keyfile)
+        .arg(sample_file("security.evtx"))
+        .assert()
+        .failure()
+        .stdout("");
+}
@@ -0,0 +1,49 @@
+use std::{
+    io::Read,
+    process::{Command, Stdio},
+};
+
+use crate::common::sample_file;
+
+/// the consumer closes its end of the pipe after reading the first bytes,
+/// like `head` does
+fn close_stdout_early(format: &str) {
+    let mut data_dir = sample_file("security.evtx");
+    data_dir.pop();
+
+    let mut child = Command::new(assert_cmd::cargo::cargo_bin("evtxls"))
+        .arg("-F")
+        .arg(format)
+        // generate enough output to fill the pipe buffer
+        .args(std::iter::repeat(&data_dir).take(100))
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    let mut buffer = [0; 16];
+    child.stdout.take().unwrap().read_exact(&mut buffer).unwrap();
+
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success(), "{format}: {:?}", output.status);
+    assert!(
+        output.stderr.is_empty(),
+        "{format}: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn broken_pipe_plain() {
+    close_stdout_early("plain");
+}
+
+#[test]
+fn broken_pipe_jsonl() {
+    close_stdout_early("jsonl");
+}
+
+#[test]
+fn broken_pipe_csv() {
+    close_stdout_early("csv");
+}
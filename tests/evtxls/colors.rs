@@ -0,0 +1,31 @@
+use assert_cmd::Command;
+
+use crate::common::sample_file;
+
+fn run_with_colors(color: &str) -> String {
+    let mut cmd = Command::cargo_bin("evtxls").unwrap();
+    let result = cmd
+        .arg("-C")
+        .arg(color)
+        .arg(sample_file("system.evtx"))
+        .ok();
+    assert!(result.is_ok());
+    String::from_utf8(result.unwrap().stdout).unwrap()
+}
+
+#[test]
+fn no_colors_when_piping() {
+    assert!(!run_with_colors("auto").contains('\u{1b}'));
+    assert!(!run_with_colors("never").contains('\u{1b}'));
+}
+
+#[test]
+fn colors_by_level() {
+    let output = run_with_colors("always");
+    let lines: Vec<_> = output.lines().collect();
+
+    // record 102 is an error, record 103 is a warning
+    assert!(lines[2].starts_with("\u{1b}[31m2024-03-12T09:30:00+00:00"));
+    assert!(lines[3].starts_with("\u{1b}[33m2024-03-12T15:00:00+00:00"));
+    assert!(lines[0].starts_with("2024-03-12T08:01:00+00:00"));
+}
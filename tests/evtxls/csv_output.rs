@@ -0,0 +1,44 @@
+use std::io::{BufReader, Cursor};
+
+use assert_cmd::Command;
+
+use crate::common::sample_file;
+
+/// tests if the csv output has a header and correctly quotes the event data
+#[test]
+fn csv_output() {
+    let mut cmd = Command::cargo_bin("evtxls").unwrap();
+    let result = cmd.arg("-F").arg("csv").arg(sample_file("security.evtx")).ok();
+    assert!(result.is_ok());
+
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(true)
+        .from_reader(BufReader::new(Cursor::new(result.unwrap().stdout)));
+    assert_eq!(
+        reader.headers().unwrap(),
+        vec!["timestamp", "event_id", "event_record_id", "event_data"]
+    );
+
+    let records: Vec<_> = reader.records().map(|r| r.unwrap()).collect();
+    assert_eq!(records.len(), 8);
+
+    let second = &records[1];
+    assert_eq!(second.get(0).unwrap(), "2024-03-12T08:05:00+00:00");
+    assert_eq!(second.get(1).unwrap(), "4688");
+    assert_eq!(second.get(2).unwrap(), "2");
+    let event_data: serde_json::Value = serde_json::from_str(second.get(3).unwrap()).unwrap();
+    assert_eq!(event_data["CommandLine"], r#"cmd.exe /c "echo a,b;c""#);
+}
+
+/// tests if tsv output uses tabs as delimiter
+#[test]
+fn tsv_output() {
+    let mut cmd = Command::cargo_bin("evtxls").unwrap();
+    let result = cmd.arg("-F").arg("tsv").arg("-B").arg(sample_file("security.evtx")).ok();
+    assert!(result.is_ok());
+
+    let stdout = String::from_utf8(result.unwrap().stdout).unwrap();
+    let mut lines = stdout.lines();
+    assert_eq!(lines.next().unwrap(), "timestamp\tevent_data");
+    assert!(lines.next().unwrap().starts_with("2024-03-12T08:00:00.123456+00:00\t"));
+}
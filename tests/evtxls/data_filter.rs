@@ -0,0 +1,27 @@
+use super::record_ids;
+
+#[test]
+fn all_data_filters_must_match() {
+    assert_eq!(
+        record_ids(&["--data", "TargetUserName=Administrator", "--data", "LogonType=10"]),
+        vec!["3", "8"]
+    );
+}
+
+#[test]
+fn any_data_filter_may_match() {
+    assert_eq!(
+        record_ids(&[
+            "--data-any",
+            "IpAddress~=^10\\.",
+            "--data-any",
+            "SubjectUserName=jsmith"
+        ]),
+        vec!["1", "2", "8"]
+    );
+}
+
+#[test]
+fn missing_fields_dont_match() {
+    assert!(record_ids(&["--data", "NoSuchField~=.*"]).is_empty());
+}
@@ -0,0 +1,73 @@
+use assert_cmd::Command;
+
+use crate::common::sample_file;
+
+fn evtxls(args: &[&str]) -> Vec<String> {
+    let mut cmd = Command::cargo_bin("evtxls").unwrap();
+    let result = cmd
+        .args(["-C", "never"])
+        .args(args)
+        .arg(sample_file("security.evtx"))
+        .ok();
+    assert!(result.is_ok());
+    String::from_utf8(result.unwrap().stdout)
+        .unwrap()
+        .lines()
+        .map(|l| l.to_owned())
+        .collect()
+}
+
+#[test]
+fn select_fields_plain() {
+    let lines = evtxls(&["--fields", "recordid,eventid,provider", "-d", "|"]);
+    assert_eq!(lines[0], "1|4624|Microsoft-Windows-Security-Auditing");
+
+    let lines = evtxls(&["--fields", "eventid,data"]);
+    assert!(lines[3].starts_with(r#"1102 {"LogFileCleared":{"#));
+}
+
+#[test]
+fn select_fields_csv() {
+    let lines = evtxls(&["--fields", "time,recordid,level", "-F", "csv"]);
+    assert_eq!(lines[0], "time,recordid,level");
+    assert_eq!(lines[1], "2024-03-12T08:00:00.123456+00:00,1,0");
+}
+
+#[test]
+fn select_fields_jsonl() {
+    let lines = evtxls(&["--fields", "recordid,eventid,data", "-F", "jsonl"]);
+    assert!(lines[0].starts_with(r#"{"recordid":1,"eventid":4624,"data":{"#));
+}
+
+#[test]
+fn hide_data() {
+    assert_eq!(
+        evtxls(&["--hide-data", "-d", "|"])[0],
+        "2024-03-12T08:00:00.123456+00:00|4624|1"
+    );
+    assert_eq!(
+        evtxls(&["--hide-data", "-F", "csv"])[0],
+        "timestamp,event_id,event_record_id"
+    );
+    assert_eq!(
+        evtxls(&["--hide-data", "--fields", "recordid,data", "-F", "csv"])[1],
+        "1"
+    );
+
+    let lines = evtxls(&["--hide-data", "-F", "jsonl"]);
+    let record: serde_json::Value = serde_json::from_str(&lines[0]).unwrap();
+    assert!(record.get("custom_data").is_none());
+    assert_eq!(record["event_record_id"], 1);
+}
+
+#[test]
+fn invalid_field() {
+    let mut cmd = Command::cargo_bin("evtxls").unwrap();
+    let assert = cmd
+        .args(["--fields", "recordid,foo"])
+        .arg(sample_file("security.evtx"))
+        .assert()
+        .failure();
+    let stderr = String::from_utf8_lossy(&assert.get_output().stderr).to_string();
+    assert!(stderr.contains("possible values: time, recordid, eventid"));
+}
@@ -0,0 +1,31 @@
+use super::{record_ids, record_ids_in};
+
+#[test]
+fn grep_records() {
+    assert_eq!(record_ids(&["--grep", "SeDebugPrivilege"]), vec!["9"]);
+    assert_eq!(
+        record_ids(&["--grep", r#""TargetUserName":"jsmith""#]),
+        vec!["1", "5"]
+    );
+}
+
+#[test]
+fn grep_inverted() {
+    assert_eq!(
+        record_ids(&["--grep", "jsmith", "--grep-v", "4688"]),
+        vec!["1", "5"]
+    );
+}
+
+#[test]
+fn grep_ignore_case() {
+    assert!(record_ids_in(&["system.evtx"], &["--grep", "EVIL"]).is_empty());
+    assert_eq!(
+        record_ids_in(&["system.evtx"], &["--grep", "EVIL", "--ignore-case"]),
+        vec!["100", "102"]
+    );
+    assert_eq!(
+        record_ids_in(&["system.evtx"], &["--grep", "EVIL", "-I"]),
+        vec!["100", "102"]
+    );
+}
@@ -0,0 +1,10 @@
+use super::record_ids_in;
+
+#[test]
+fn filter_by_min_level() {
+    let files = &["security.evtx", "system.evtx"];
+    assert_eq!(record_ids_in(files, &["--min-level", "warning"]), vec!["102", "103"]);
+    assert_eq!(record_ids_in(files, &["--min-level", "Error"]), vec!["102"]);
+    assert_eq!(record_ids_in(files, &["--min-level", "critical"]), Vec::<String>::new());
+    assert_eq!(record_ids_in(files, &["--min-level", "verbose"]).len(), 12);
+}
@@ -0,0 +1,43 @@
+use assert_cmd::Command;
+
+use crate::common::sample_file;
+
+mod anonymize;
+mod broken_pipe;
+mod colors;
+mod csv_output;
+mod data_filter;
+mod fields;
+mod grep;
+mod min_level;
+mod progress;
+mod record_ranges;
+mod sort_merge;
+mod source_filter;
+mod template;
+mod time_window;
+mod timezone;
+mod user_filter;
+
+/// runs `evtxls` on `security.evtx` and returns the record ids of all
+/// displayed records
+pub(crate) fn record_ids(args: &[&str]) -> Vec<String> {
+    record_ids_in(&["security.evtx"], args)
+}
+
+/// runs `evtxls` on the specified sample files and returns the record ids
+/// of all displayed records
+pub(crate) fn record_ids_in(files: &[&str], args: &[&str]) -> Vec<String> {
+    let mut cmd = Command::cargo_bin("evtxls").unwrap();
+    let result = cmd
+        .args(["-d", "|", "-b", "event-record-id"])
+        .args(args)
+        .args(files.iter().map(|f| sample_file(f)))
+        .ok();
+    assert!(result.is_ok());
+    String::from_utf8(result.unwrap().stdout)
+        .unwrap()
+        .lines()
+        .map(|l| l.split('|').nth(1).unwrap().to_owned())
+        .collect()
+}
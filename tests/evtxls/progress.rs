@@ -0,0 +1,29 @@
+use assert_cmd::Command;
+
+use crate::common::sample_file;
+
+use super::{record_ids, record_ids_in};
+
+#[test]
+fn progress_does_not_change_output() {
+    assert_eq!(record_ids(&["--progress"]), record_ids(&[]));
+    assert_eq!(
+        record_ids_in(
+            &["system.evtx", "application.evtx"],
+            &["--progress", "--sort-merge"]
+        ),
+        record_ids_in(&["system.evtx", "application.evtx"], &["--sort-merge"])
+    );
+}
+
+#[test]
+fn no_progress_without_terminal() {
+    let mut cmd = Command::cargo_bin("evtxls").unwrap();
+    let assert = cmd
+        .arg("--progress")
+        .arg(sample_file("security.evtx"))
+        .arg(sample_file("system.evtx"))
+        .assert()
+        .success();
+    assert!(assert.get_output().stderr.is_empty());
+}
@@ -0,0 +1,15 @@
+use super::record_ids;
+
+#[test]
+fn select_record_ranges() {
+    assert_eq!(record_ids(&["--range", "..2,8..9"]), vec!["1", "2", "8", "9"]);
+    assert_eq!(record_ids(&["--range", "4,9.."]), vec!["4", "9", "10"]);
+}
+
+#[test]
+fn ranges_are_combined_with_other_filters() {
+    assert_eq!(
+        record_ids(&["--range", "3..", "--include", "4624"]),
+        vec!["8"]
+    );
+}
@@ -0,0 +1,70 @@
+use std::io::{BufReader, Cursor};
+
+use assert_cmd::Command;
+
+use crate::common::sample_file;
+
+/// returns (source, record id) of all records
+fn merge(files: &[&str]) -> Vec<(String, String)> {
+    let mut cmd = Command::cargo_bin("evtxls").unwrap();
+    let result = cmd
+        .args(["--sort-merge", "-F", "csv", "-b", "event-record-id"])
+        .args(files.iter().map(|f| sample_file(f)))
+        .ok();
+    assert!(result.is_ok());
+
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(true)
+        .from_reader(BufReader::new(Cursor::new(result.unwrap().stdout)));
+    assert_eq!(
+        reader.headers().unwrap(),
+        vec!["timestamp", "source", "event_record_id", "event_data"]
+    );
+    reader
+        .records()
+        .map(|r| r.unwrap())
+        .map(|r| {
+            let source = std::path::Path::new(r.get(1).unwrap())
+                .file_name()
+                .unwrap()
+                .to_string_lossy()
+                .to_string();
+            (source, r.get(2).unwrap().to_owned())
+        })
+        .collect()
+}
+
+fn ids(records: &[(String, String)]) -> Vec<&str> {
+    records.iter().map(|(_, id)| &id[..]).collect()
+}
+
+#[test]
+fn merge_sorted_files() {
+    let records = merge(&["application.evtx", "system.evtx"]);
+    assert_eq!(ids(&records), vec!["50", "100", "101", "51", "102", "103", "52"]);
+    assert_eq!(records[0].0, "application.evtx");
+    assert_eq!(records[1].0, "system.evtx");
+}
+
+#[test]
+fn merge_unsorted_files() {
+    // security.evtx contains a record which is older than its predecessor
+    let records = merge(&["security.evtx", "system.evtx"]);
+    assert_eq!(
+        ids(&records),
+        vec!["1", "100", "2", "101", "3", "4", "102", "9", "5", "8", "10", "103"]
+    );
+}
+
+#[test]
+fn merge_directory() {
+    let mut cmd = Command::cargo_bin("evtxls").unwrap();
+    let mut data_path = sample_file("security.evtx");
+    data_path.pop();
+    let result = cmd.arg("--sort-merge").arg(data_path).ok();
+    assert!(result.is_ok());
+    assert_eq!(
+        String::from_utf8(result.unwrap().stdout).unwrap().lines().count(),
+        15
+    );
+}
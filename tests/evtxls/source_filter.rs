@@ -0,0 +1,31 @@
+use super::record_ids_in;
+
+const ALL_FILES: &[&str] = &["security.evtx", "system.evtx"];
+
+#[test]
+fn include_channels() {
+    assert_eq!(
+        record_ids_in(ALL_FILES, &["--include-channel", "SYSMON"]),
+        vec!["101"]
+    );
+    assert_eq!(
+        record_ids_in(ALL_FILES, &["--include-channel", "=system", "--include-channel", "sysmon"]),
+        vec!["100", "101", "102", "103"]
+    );
+}
+
+#[test]
+fn exclude_providers() {
+    assert_eq!(
+        record_ids_in(
+            ALL_FILES,
+            &[
+                "--include-channel",
+                "=Security",
+                "--exclude-provider",
+                "security-auditing"
+            ]
+        ),
+        vec!["4"]
+    );
+}
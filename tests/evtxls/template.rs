@@ -0,0 +1,39 @@
+use assert_cmd::Command;
+
+use crate::common::sample_file;
+
+#[test]
+fn render_template() {
+    let mut cmd = Command::cargo_bin("evtxls").unwrap();
+    let result = cmd
+        .arg("--template")
+        .arg("{time} {{{eventid}}} {data.TargetUserName} -> {data.IpAddress}")
+        .arg("--missing")
+        .arg("-")
+        .arg("--include")
+        .arg("4624,4634")
+        .arg(sample_file("security.evtx"))
+        .ok();
+    assert!(result.is_ok());
+
+    assert_eq!(
+        String::from_utf8(result.unwrap().stdout).unwrap(),
+        r#"2024-03-12T08:00:00.123456+00:00 {4624} jsmith -> 10.0.0.5
+2024-03-12T14:00:00+00:00 {4634} jsmith -> -
+2024-03-12T14:01:00+00:00 {4624} Administrator -> 10.0.0.9
+"#
+    );
+}
+
+#[test]
+fn unknown_placeholder_fails() {
+    let mut cmd = Command::cargo_bin("evtxls").unwrap();
+    let assert = cmd
+        .arg("--template")
+        .arg("{time} {foo}")
+        .arg(sample_file("security.evtx"))
+        .assert()
+        .failure();
+    let stderr = String::from_utf8(assert.get_output().stderr.clone()).unwrap();
+    assert!(stderr.contains("valid placeholders are"));
+}
@@ -0,0 +1,40 @@
+use assert_cmd::Command;
+
+use crate::common::sample_file;
+
+use super::record_ids;
+
+#[test]
+fn absolute_time_window() {
+    assert_eq!(
+        record_ids(&["--from", "2024-03-12T08:05:00Z", "--to", "2024-03-12T08:12:00Z"]),
+        vec!["2", "3", "4"]
+    );
+}
+
+#[test]
+fn relative_time_window() {
+    // the newest record has been created at 14:02
+    assert_eq!(record_ids(&["--from", "-2m"]), vec!["5", "8", "10"]);
+    assert_eq!(
+        record_ids(&["--from", "-3m", "--include", "4624,4672"]),
+        vec!["8", "9"]
+    );
+}
+
+/// tests if relative times which are too large are rejected instead of
+/// crashing evtxls
+#[test]
+fn reject_large_relative_times() {
+    for (from, code) in [("-99999999999w", 2), ("-99999999w", 1)] {
+        let mut cmd = Command::cargo_bin("evtxls").unwrap();
+        let result = cmd
+            .args(["--from", from])
+            .arg(sample_file("security.evtx"))
+            .env("RUST_BACKTRACE", "0")
+            .assert()
+            .code(code);
+        let stderr = String::from_utf8_lossy(&result.get_output().stderr).into_owned();
+        assert!(!stderr.contains("panicked"), "{stderr}");
+    }
+}
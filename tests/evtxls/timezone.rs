@@ -0,0 +1,32 @@
+use assert_cmd::Command;
+
+use crate::common::sample_file;
+
+fn run(args: &[&str]) -> String {
+    let mut cmd = Command::cargo_bin("evtxls").unwrap();
+    let result = cmd.args(args).arg(sample_file("application.evtx")).ok();
+    assert!(result.is_ok());
+    String::from_utf8(result.unwrap().stdout).unwrap()
+}
+
+#[test]
+fn convert_to_named_timezone() {
+    // daylight saving time started in the US on 2024-03-10
+    let output = run(&["--timezone", "America/New_York", "--template", "{time} {recordid}"]);
+    assert_eq!(output.lines().next().unwrap(), "2024-03-12T04:00:30-04:00 50");
+}
+
+#[test]
+fn convert_to_fixed_offset() {
+    let output = run(&["--timezone", "-05:00", "-F", "csv", "-B"]);
+    let second_line = output.lines().nth(1).unwrap();
+    assert!(second_line.starts_with("2024-03-12T03:00:30-05:00,"));
+}
+
+#[test]
+fn jsonl_keeps_utc_timestamp() {
+    let output = run(&["--timezone", "Europe/Berlin", "-F", "jsonl"]);
+    let record: serde_json::Value = serde_json::from_str(output.lines().next().unwrap()).unwrap();
+    assert_eq!(record["timestamp"], "2024-03-12T09:00:30+01:00");
+    assert_eq!(record["timestamp_utc"], "2024-03-12T08:00:30Z");
+}
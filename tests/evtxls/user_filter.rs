@@ -0,0 +1,39 @@
+use super::{record_ids, record_ids_in};
+
+#[test]
+fn include_user() {
+    assert_eq!(
+        record_ids(&["--include-user", "jsmith"]),
+        vec!["1", "2", "5"]
+    );
+    assert_eq!(
+        record_ids(&["--include-user", "CORP\\JSmith"]),
+        vec!["1", "2", "5"]
+    );
+    assert!(record_ids(&["--include-user", "OTHER\\jsmith"]).is_empty());
+}
+
+#[test]
+fn include_user_from_sysmon() {
+    assert_eq!(
+        record_ids_in(&["system.evtx"], &["--include-user", "corp\\jsmith"]),
+        vec!["101"]
+    );
+}
+
+#[test]
+fn exclude_user() {
+    assert_eq!(
+        record_ids(&["--exclude-user", "administrator"]),
+        vec!["1", "2", "5"]
+    );
+    assert_eq!(
+        record_ids(&[
+            "--include-user",
+            "administrator",
+            "--exclude-user",
+            "CORP\\Administrator"
+        ]),
+        Vec::<String>::new()
+    );
+}
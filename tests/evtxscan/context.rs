@@ -0,0 +1,98 @@
+use assert_cmd::Command;
+use serde_json::{json, Value};
+
+use crate::common::sample_file;
+
+use super::scan;
+
+fn context_ids(records: &Value) -> Vec<u64> {
+    records
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["event_record_id"].as_u64().unwrap())
+        .collect()
+}
+
+#[test]
+fn context_in_json_report() {
+    let report: Value = serde_json::from_str(&scan(
+        "security.evtx",
+        &["--format", "json", "--context", "2"],
+    ))
+    .unwrap();
+    let file = &report["files"][0];
+
+    let time_gap = &file["time_gaps"][0];
+    assert_eq!(context_ids(&time_gap["records_before"]), vec![3, 4]);
+    assert_eq!(context_ids(&time_gap["records_after"]), vec![5, 8]);
+    assert_eq!(
+        time_gap["records_before"][1],
+        json!({
+            "event_record_id": 4,
+            "timestamp": "2024-03-12T08:12:00Z",
+            "event_id": 1102,
+            "provider": "Microsoft-Windows-Eventlog",
+            "summary": "SubjectDomainName=CORP, SubjectLogonId=0x4d5e6f, SubjectUserName=Administrator, SubjectUserSid=S-1-5-21-1-2-3-500"
+        })
+    );
+
+    let id_gap = &file["record_id_gaps"][0];
+    assert_eq!(context_ids(&id_gap["records_before"]), vec![4, 5]);
+    assert_eq!(context_ids(&id_gap["records_after"]), vec![8, 9]);
+}
+
+#[test]
+fn context_at_the_end_of_the_file() {
+    let report: Value = serde_json::from_str(&scan(
+        "system.evtx",
+        &["--format", "json", "--context", "3", "--threshold", "2h"],
+    ))
+    .unwrap();
+    let time_gap = &report["files"][0]["time_gaps"][0];
+    assert_eq!(
+        context_ids(&time_gap["records_before"]),
+        vec![100, 101, 102]
+    );
+    assert_eq!(context_ids(&time_gap["records_after"]), vec![103]);
+}
+
+#[test]
+fn no_context_by_default() {
+    let report: Value =
+        serde_json::from_str(&scan("security.evtx", &["--format", "json"])).unwrap();
+    assert!(report["files"][0]["time_gaps"][0]
+        .get("records_before")
+        .is_none());
+    assert!(!scan("security.evtx", &[]).contains("records before the gap"));
+}
+
+#[test]
+fn context_in_text_report() {
+    let output = scan("security.evtx", &["--context", "1"]);
+    let context: Vec<_> = output
+        .split("no events for 5h48m:")
+        .nth(1)
+        .unwrap()
+        .lines()
+        .skip(1)
+        .take_while(|l| !l.is_empty())
+        .map(str::trim)
+        .collect();
+    assert_eq!(context[2], "records before the gap:");
+    assert!(
+        context[3].starts_with("2024-03-12T08:12:00        4  1102 Microsoft-Windows-Eventlog: ")
+    );
+    assert_eq!(context[4], "records after the gap:");
+    assert!(context[5].starts_with("2024-03-12T14:00:00        5  4634 "));
+}
+
+#[test]
+fn context_must_not_be_zero() {
+    Command::cargo_bin("evtxscan")
+        .unwrap()
+        .args(["--context", "0"])
+        .arg(sample_file("security.evtx"))
+        .assert()
+        .code(2);
+}
@@ -0,0 +1,66 @@
+use assert_cmd::Command;
+
+use crate::common::sample_file;
+
+use super::scan;
+
+const HEADER: &str = "file,finding,start,end,duration_seconds,first_record_id,last_record_id";
+
+/// returns the lines of the CSV report, without the file name
+fn csv_lines(file: &str, args: &[&str]) -> Vec<String> {
+    let mut args = args.to_vec();
+    args.extend(["--format", "csv"]);
+    let output = scan(file, &args);
+    let mut lines = output.lines();
+    assert_eq!(lines.next(), Some(HEADER));
+    lines
+        .map(|l| l.split_once(',').unwrap().1.to_owned())
+        .collect()
+}
+
+#[test]
+fn one_line_per_finding() {
+    assert_eq!(
+        csv_lines("security.evtx", &[]),
+        vec![
+            "time_gap,2024-03-12T08:12:00Z,2024-03-12T14:00:00Z,20880,4,5",
+            "record_id_gap_without_time_gap,2024-03-12T14:00:00Z,2024-03-12T14:01:00Z,60,5,8",
+            "time_skew,2024-03-12T14:01:00Z,2024-03-12T13:59:30Z,-90,8,9",
+        ]
+    );
+}
+
+#[test]
+fn header_without_findings() {
+    assert!(csv_lines("application.evtx", &[]).is_empty());
+}
+
+#[test]
+fn file_name_in_every_line() {
+    let result = Command::cargo_bin("evtxscan")
+        .unwrap()
+        .args(["--format", "csv", "--threshold", "1h"])
+        .arg(sample_file("security.evtx"))
+        .arg(sample_file("system.evtx"))
+        .assert()
+        .code(1);
+
+    let mut reader = csv::Reader::from_reader(&result.get_output().stdout[..]);
+    let files: Vec<_> = reader
+        .records()
+        .map(|r| {
+            let record = r.unwrap();
+            record[0].rsplit('/').next().unwrap().to_owned()
+        })
+        .collect();
+    assert_eq!(
+        files,
+        vec![
+            "security.evtx",
+            "security.evtx",
+            "security.evtx",
+            "system.evtx",
+            "system.evtx"
+        ]
+    );
+}
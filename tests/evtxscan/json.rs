@@ -0,0 +1,60 @@
+use serde_json::{json, Value};
+
+use super::scan;
+
+fn json_report(file: &str, args: &[&str]) -> Value {
+    let mut args = args.to_vec();
+    args.extend(["--format", "json"]);
+    serde_json::from_str(&scan(file, &args)).unwrap()
+}
+
+#[test]
+fn file_summary() {
+    let report = json_report("security.evtx", &[]);
+    let files = report["files"].as_array().unwrap();
+    assert_eq!(files.len(), 1);
+    assert!(files[0]["file"]
+        .as_str()
+        .unwrap()
+        .ends_with("security.evtx"));
+    assert_eq!(
+        files[0]["summary"],
+        json!({
+            "first_record": {"event_record_id": 1, "timestamp": "2024-03-12T08:00:00.123456Z"},
+            "last_record": {"event_record_id": 10, "timestamp": "2024-03-12T14:02:00Z"},
+            "total_records": 8,
+            "unreadable_records": 0,
+            "time_skews": 1
+        })
+    );
+}
+
+#[test]
+fn time_gaps() {
+    let report = json_report("system.evtx", &["--threshold", "1h"]);
+    assert_eq!(
+        report["files"][0]["time_gaps"],
+        json!([
+            {
+                "start": "2024-03-12T08:06:00Z",
+                "end": "2024-03-12T09:30:00Z",
+                "duration_seconds": 5040,
+                "record_id_before": 101,
+                "record_id_after": 102
+            },
+            {
+                "start": "2024-03-12T09:30:00Z",
+                "end": "2024-03-12T15:00:00Z",
+                "duration_seconds": 19800,
+                "record_id_before": 102,
+                "record_id_after": 103
+            }
+        ])
+    );
+}
+
+#[test]
+fn no_time_gaps() {
+    let report = json_report("system.evtx", &["--threshold", "1d"]);
+    assert_eq!(report["files"][0]["time_gaps"], json!([]));
+}
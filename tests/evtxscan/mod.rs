@@ -0,0 +1,21 @@
+use assert_cmd::Command;
+
+use crate::common::sample_file;
+
+mod context;
+mod csv;
+mod json;
+mod multiple_files;
+mod record_id_gaps;
+mod threshold;
+mod time_skews;
+mod unreadable_records;
+
+/// runs `evtxscan` and returns its output. The exit code is 1 if
+/// anything has been found
+pub(crate) fn scan(file: &str, args: &[&str]) -> String {
+    let mut cmd = Command::cargo_bin("evtxscan").unwrap();
+    let output = cmd.args(args).arg(sample_file(file)).output().unwrap();
+    assert!(matches!(output.status.code(), Some(0 | 1)));
+    String::from_utf8(output.stdout).unwrap()
+}
@@ -0,0 +1,119 @@
+use std::path::PathBuf;
+
+use assert_cmd::Command;
+use serde_json::{json, Value};
+
+use crate::common::sample_file;
+
+/// creates a directory containing `system.evtx`, and `security.evtx`
+/// together with an invalid evtx file in a subdirectory
+fn evidence_dir(name: &str) -> PathBuf {
+    let mut dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR"));
+    dir.push("evtxscan");
+    dir.push(name);
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(dir.join("sub")).unwrap();
+    std::fs::copy(sample_file("system.evtx"), dir.join("system.evtx")).unwrap();
+    std::fs::copy(
+        sample_file("security.evtx"),
+        dir.join("sub").join("security.evtx"),
+    )
+    .unwrap();
+    std::fs::write(dir.join("sub").join("broken.evtx"), "no evtx file").unwrap();
+    std::fs::write(dir.join("sub").join("notes.txt"), "no evtx file").unwrap();
+    dir
+}
+
+#[test]
+fn scan_directory_recursively() {
+    let dir = evidence_dir("scan_directory_recursively");
+    let result = Command::cargo_bin("evtxscan")
+        .unwrap()
+        .args(["--format", "json"])
+        .arg(&dir)
+        .assert()
+        .code(1);
+    let report: Value = serde_json::from_slice(&result.get_output().stdout).unwrap();
+
+    let files: Vec<_> = report["files"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|f| f["file"].as_str().unwrap().rsplit('/').next().unwrap())
+        .collect();
+    assert_eq!(files, vec!["security.evtx", "system.evtx"]);
+
+    let unreadable = report["unreadable_files"].as_array().unwrap();
+    assert_eq!(unreadable.len(), 1);
+    assert!(unreadable[0]["file"]
+        .as_str()
+        .unwrap()
+        .ends_with("broken.evtx"));
+    assert!(!unreadable[0]["error"].as_str().unwrap().is_empty());
+
+    let summary = &report["summary"];
+    assert_eq!(summary["scanned_files"], json!(2));
+    assert_eq!(summary["unreadable_files"], json!(1));
+    assert_eq!(summary["time_gaps"], json!(1));
+    assert_eq!(summary["record_id_gaps"], json!(1));
+    assert_eq!(summary["time_skews"], json!(1));
+    assert!(summary["largest_time_gap"]["file"]
+        .as_str()
+        .unwrap()
+        .ends_with("security.evtx"));
+    assert_eq!(
+        summary["largest_time_gap"]["duration_seconds"],
+        json!(20880)
+    );
+}
+
+#[test]
+fn text_report_grouped_by_file() {
+    let dir = evidence_dir("text_report_grouped_by_file");
+    let result = Command::cargo_bin("evtxscan")
+        .unwrap()
+        .arg("-q")
+        .arg(&dir)
+        .arg(sample_file("application.evtx"))
+        .assert()
+        .code(1);
+    let output = String::from_utf8(result.get_output().stdout.clone()).unwrap();
+
+    let headers: Vec<_> = output
+        .lines()
+        .filter_map(|l| l.strip_prefix("FILE: "))
+        .map(|l| l.rsplit('/').next().unwrap())
+        .collect();
+    assert_eq!(
+        headers,
+        vec!["security.evtx", "system.evtx", "application.evtx"]
+    );
+    assert!(output.contains("SUMMARY:"));
+    assert!(output.contains("  3 files scanned"));
+    assert!(output.contains("UNREADABLE FILES:"));
+}
+
+#[test]
+fn exit_codes() {
+    // nothing suspicious in this file
+    Command::cargo_bin("evtxscan")
+        .unwrap()
+        .arg(sample_file("application.evtx"))
+        .assert()
+        .success();
+
+    Command::cargo_bin("evtxscan")
+        .unwrap()
+        .arg(sample_file("security.evtx"))
+        .assert()
+        .code(1);
+
+    let dir = evidence_dir("exit_codes");
+    Command::cargo_bin("evtxscan")
+        .unwrap()
+        .arg("-q")
+        .arg(dir.join("sub").join("broken.evtx"))
+        .arg(sample_file("application.evtx"))
+        .assert()
+        .code(exitcode::DATAERR);
+}
@@ -0,0 +1,52 @@
+use serde_json::{json, Value};
+
+use super::scan;
+
+fn record_id_gaps(file: &str, args: &[&str]) -> Value {
+    let mut args = args.to_vec();
+    args.extend(["--format", "json"]);
+    let report: Value = serde_json::from_str(&scan(file, &args)).unwrap();
+    report["files"][0]["record_id_gaps"].clone()
+}
+
+#[test]
+fn gap_without_time_gap() {
+    assert_eq!(
+        record_id_gaps("security.evtx", &[]),
+        json!([{
+            "record_id_before": 5,
+            "record_id_after": 8,
+            "missing_ids": 2,
+            "start": "2024-03-12T14:00:00Z",
+            "end": "2024-03-12T14:01:00Z",
+            "duration_seconds": 60,
+            "with_time_gap": false
+        }])
+    );
+
+    let output = scan("security.evtx", &[]);
+    let section = output
+        .split("RECORD ID GAPS WITHOUT TIME GAP")
+        .nth(1)
+        .expect("missing section for record id gaps without time gap");
+    assert!(section.contains("2 missing records between event 5 and event 8"));
+}
+
+#[test]
+fn gap_with_time_gap() {
+    assert_eq!(
+        record_id_gaps("security.evtx", &["--threshold", "30s"])[0]["with_time_gap"],
+        json!(true)
+    );
+
+    let output = scan("security.evtx", &["--threshold", "30s"]);
+    assert!(!output.contains("RECORD ID GAPS WITHOUT TIME GAP"));
+    assert!(output.contains("RECORD ID GAPS:"));
+}
+
+#[test]
+fn no_gap_at_the_beginning_of_rotated_logs() {
+    // the ids of this file start at 100
+    assert_eq!(record_id_gaps("system.evtx", &[]), json!([]));
+    assert!(!scan("system.evtx", &[]).contains("RECORD ID GAPS"));
+}
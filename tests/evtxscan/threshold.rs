@@ -0,0 +1,50 @@
+use assert_cmd::Command;
+
+use crate::common::sample_file;
+
+use super::scan;
+
+/// returns the durations of all reported time gaps
+fn gaps(file: &str, args: &[&str]) -> Vec<String> {
+    scan(file, args)
+        .lines()
+        .filter_map(|l| l.trim().strip_prefix("no events for "))
+        .map(|l| l.strip_suffix(':').unwrap().to_owned())
+        .collect()
+}
+
+#[test]
+fn explicit_threshold() {
+    assert_eq!(
+        gaps("system.evtx", &["--threshold", "1h"]),
+        vec!["1h24m", "5h30m"]
+    );
+    assert_eq!(gaps("system.evtx", &["--threshold", "2h"]), vec!["5h30m"]);
+    assert_eq!(
+        gaps("system.evtx", &["--threshold", "1h24m"]),
+        vec!["5h30m"]
+    );
+    assert_eq!(gaps("system.evtx", &["--threshold", "90s"]).len(), 3);
+    assert!(gaps("system.evtx", &["--threshold", "1d"]).is_empty());
+}
+
+#[test]
+fn adaptive_threshold() {
+    // the median interval is 2m30s, so gaps longer than 4h10m are reported
+    assert_eq!(gaps("security.evtx", &[]), vec!["5h48m"]);
+
+    // the median interval is 1h24m
+    assert!(gaps("system.evtx", &[]).is_empty());
+}
+
+#[test]
+fn threshold_requires_unit() {
+    for threshold in ["30", "2x", "1h30", ""] {
+        Command::cargo_bin("evtxscan")
+            .unwrap()
+            .args(["--threshold", threshold])
+            .arg(sample_file("system.evtx"))
+            .assert()
+            .failure();
+    }
+}
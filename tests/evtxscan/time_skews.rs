@@ -0,0 +1,45 @@
+use serde_json::{json, Value};
+
+use super::scan;
+
+fn json_report(file: &str, args: &[&str]) -> Value {
+    let mut args = args.to_vec();
+    args.extend(["--format", "json"]);
+    let report: Value = serde_json::from_str(&scan(file, &args)).unwrap();
+    report["files"][0].clone()
+}
+
+#[test]
+fn detect_time_skew() {
+    let report = json_report("security.evtx", &[]);
+    assert_eq!(report["summary"]["time_skews"], json!(1));
+    assert_eq!(
+        report["time_skews"],
+        json!([{
+            "previous_record": {"event_record_id": 8, "timestamp": "2024-03-12T14:01:00Z"},
+            "record": {"event_record_id": 9, "timestamp": "2024-03-12T13:59:30Z"},
+            "duration_seconds": -90
+        }])
+    );
+
+    let output = scan("security.evtx", &[]);
+    assert!(output.contains("but the current event 9 occurred at 2024-03-12T13:59:30"));
+    assert!(output.contains("TIME SKEWS: 1 events are older than their predecessor"));
+}
+
+#[test]
+fn skew_within_tolerance() {
+    let report = json_report("security.evtx", &["--negative-tolerance", "90"]);
+    assert_eq!(report["summary"]["time_skews"], json!(0));
+    assert_eq!(report["time_skews"], json!([]));
+
+    assert!(scan("security.evtx", &["-N", "90"]).contains("TIME SKEWS: 0 events"));
+}
+
+#[test]
+fn no_time_skews_in_sorted_file() {
+    assert_eq!(
+        json_report("system.evtx", &[])["summary"]["time_skews"],
+        json!(0)
+    );
+}
@@ -0,0 +1,63 @@
+use std::io::Write;
+
+use assert_cmd::Command;
+use serde_json::{json, Value};
+use tempfile::NamedTempFile;
+
+use crate::common::sample_file;
+
+/// returns a copy of `security.evtx`, in which the record 4 contains an
+/// invalid token at the beginning of its data
+fn file_with_unreadable_record() -> NamedTempFile {
+    let mut data = std::fs::read(sample_file("security.evtx")).unwrap();
+    let record = (0..data.len() - 16)
+        .find(|&i| data[i..i + 4] == *b"**\0\0" && data[i + 8..i + 16] == 4u64.to_le_bytes())
+        .unwrap();
+    data[record + 24] = 0xff;
+    let mut file = NamedTempFile::new().unwrap();
+    file.write_all(&data).unwrap();
+    file
+}
+
+fn scan(file: &NamedTempFile, args: &[&str]) -> (String, String) {
+    let output = Command::cargo_bin("evtxscan")
+        .unwrap()
+        .args(args)
+        .arg(file.path())
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    (
+        String::from_utf8(output.stdout).unwrap(),
+        String::from_utf8(output.stderr).unwrap(),
+    )
+}
+
+#[test]
+fn unreadable_records_are_not_missing() {
+    let file = file_with_unreadable_record();
+    let (stdout, _) = scan(&file, &["--format", "json"]);
+    let report: Value = serde_json::from_str(&stdout).unwrap();
+    let file_report = &report["files"][0];
+    assert_eq!(file_report["summary"]["total_records"], json!(7));
+    assert_eq!(file_report["summary"]["unreadable_records"], json!(1));
+    assert_eq!(report["summary"]["unreadable_records"], json!(1));
+
+    // only the records 6 and 7 are missing
+    let gaps = file_report["record_id_gaps"].as_array().unwrap();
+    assert_eq!(gaps.len(), 1);
+    assert_eq!(gaps[0]["record_id_before"], json!(5));
+    assert_eq!(gaps[0]["record_id_after"], json!(8));
+}
+
+#[test]
+fn report_unreadable_records() {
+    let file = file_with_unreadable_record();
+    let (stdout, stderr) = scan(&file, &[]);
+    assert!(stderr.contains("warning: unable to read the record 4 of '"));
+    assert!(stdout.contains("UNREADABLE RECORDS:\n  1 records unreadable\n  record ids: 4\n"));
+    assert!(!stdout.contains("between event 3 and event 5"));
+
+    let (stdout, _) = scan(&file, &["--format", "csv"]);
+    assert!(stdout.contains(",unreadable_record,,,,4,4\n"));
+}
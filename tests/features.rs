@@ -0,0 +1,58 @@
+use std::path::PathBuf;
+use std::process::Command;
+
+/// every feature which enables a binary, or only the library
+const FEATURES: &[&str] = &[
+    "lib-only",
+    "mactime2",
+    "evtxscan",
+    "evtxcat",
+    "evtxls",
+    "evtxanalyze",
+    "evtx2bodyfile",
+    "pol_export",
+    "es4forensics",
+    "regdump",
+    "hivescan",
+    "cleanhive",
+    "ipgrep",
+    "ts2date",
+    "lnk2bodyfile",
+    "pf2bodyfile",
+    "zip2bodyfile",
+];
+
+/// the library must compile without any feature, and every binary must
+/// compile together with its tests if only its own feature is enabled. CI
+/// additionally runs the tests of every feature on its own
+#[test]
+fn every_feature_compiles_on_its_own() {
+    cargo_check(None);
+    for feature in FEATURES {
+        cargo_check(Some(feature));
+    }
+}
+
+fn cargo_check(feature: Option<&str>) {
+    // use a target directory of its own, to not invalidate the artifacts
+    // which have been built with the default features
+    let target_dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("features");
+    let cargo = std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_owned());
+
+    let mut command = Command::new(cargo);
+    command
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .env("CARGO_TARGET_DIR", target_dir)
+        .args(["check", "--quiet", "--all-targets", "--no-default-features"]);
+    if let Some(feature) = feature {
+        command.args(["--features", feature]);
+    }
+
+    let output = command.output().unwrap();
+    assert!(
+        output.status.success(),
+        "unable to compile with feature '{}':\n{}",
+        feature.unwrap_or("none"),
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
@@ -0,0 +1,47 @@
+use dfir_toolkit::common::bodyfile::{Bodyfile3Line, Modified};
+
+use super::scan;
+
+#[test]
+fn bodyfile_of_carved_keys() {
+    let output = scan(&["--format", "bodyfile"]);
+    let lines: Vec<_> = output
+        .lines()
+        .map(|line| Bodyfile3Line::try_from(line).unwrap())
+        .collect();
+    let line = |name: &str| lines.iter().find(|l| l.get_name() == name).unwrap();
+
+    assert_eq!(lines.len(), 7);
+    assert_eq!(
+        line("ROOT\\Software\\Uninstalled (deleted)").get_mtime(),
+        &Modified::from(1709632800)
+    );
+    assert_eq!(
+        line("ROOT\\Software\\Uninstalled\\Settings (deleted)").get_inode(),
+        "2e0"
+    );
+    assert_eq!(
+        line("ROOT\\Software\\Vendor").get_mtime(),
+        &Modified::from(1706778000)
+    );
+
+    // the parent of this key has been overwritten
+    assert_eq!(
+        line("[unknown-parent]\\Lost (deleted)").get_mtime(),
+        &Modified::from(1709798400)
+    );
+}
+
+#[test]
+fn bodyfile_shortcut() {
+    // the order of keys whose parent is unknown is not defined
+    let sorted = |output: String| {
+        let mut lines: Vec<_> = output.lines().map(str::to_owned).collect();
+        lines.sort();
+        lines
+    };
+    assert_eq!(
+        sorted(scan(&["-b"])),
+        sorted(scan(&["--format", "bodyfile"]))
+    );
+}
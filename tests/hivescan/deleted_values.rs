@@ -0,0 +1,33 @@
+use super::scan;
+
+#[test]
+fn deleted_value_in_slack_of_values_list() {
+    let output = scan(&[]);
+    assert!(output.contains(
+        "[ROOT\\Software\\Vendor]; last change at 2024-02-01T09:00:00+00:00, found at offset 0x1398\n\
+         \"Version\" = \"1.0\"\n\
+         \"InstallPath\" = \"C:\\\\Program Files\\\\Vendor\"; deleted, found at offset 0x1458\n"
+    ));
+}
+
+#[test]
+fn orphaned_deleted_values() {
+    let output = scan(&[]);
+    let (_, orphaned) = output
+        .split_once("[[unknown-parent]]; deleted values whose key could not be found\n")
+        .unwrap();
+    assert_eq!(
+        orphaned.trim_end(),
+        "\"B\" = \"b\"; deleted, found at offset 0x11e0"
+    );
+}
+
+#[test]
+fn values_of_deleted_keys() {
+    let output = scan(&[]);
+    assert!(output.contains(
+        "[ROOT\\Software\\Uninstalled]; last change at 2024-03-05T10:00:00+00:00, found at offset 0x1210\n\
+         \"DisplayName\" = \"Evil Tool\"\n\
+         \"Count\" = 0x00000003\n"
+    ));
+}
@@ -0,0 +1,52 @@
+use serde_json::Value;
+
+use super::{hive_file, scan};
+
+#[test]
+fn filter_by_name_regex() {
+    let output = scan(&["--name-regex", "^(settings|lost)$"]);
+    assert!(output.contains("[ROOT\\Software\\Uninstalled\\Settings]"));
+    assert!(output.contains("[[unknown-parent]\\Lost]"));
+    assert!(!output.contains("[ROOT\\Software\\Uninstalled]"));
+    assert!(!output.contains("[[unknown-parent]];"));
+}
+
+#[test]
+fn filter_by_path_in_all_formats() {
+    let output = scan(&["--path-contains", "root/software/UNINSTALLED"]);
+    assert!(output.contains("[ROOT\\Software\\Uninstalled]"));
+    assert!(output.contains("[ROOT\\Software\\Uninstalled\\Settings]"));
+    assert!(!output.contains("[ROOT\\Software\\Broken]"));
+
+    let output = scan(&["--path-contains", "Software\\Uninstalled", "-F", "bodyfile"]);
+    let names: Vec<_> = output
+        .lines()
+        .map(|l| l.split('|').nth(1).unwrap())
+        .collect();
+    assert_eq!(
+        names,
+        [
+            "ROOT\\Software\\Uninstalled (deleted)",
+            "ROOT\\Software\\Uninstalled\\Settings (deleted)"
+        ]
+    );
+
+    let output = scan(&["--path-contains", "uninstalled\\settings", "-F", "json"]);
+    let findings: Vec<Value> = serde_json::from_str(&output).unwrap();
+    assert_eq!(findings.len(), 1);
+    assert_eq!(findings[0]["path"], "ROOT\\Software\\Uninstalled\\Settings");
+}
+
+#[test]
+fn report_suppressed_findings() {
+    let mut cmd = assert_cmd::Command::cargo_bin("hivescan").unwrap();
+    let result = cmd
+        .args(["--name-regex", "^vendor$", "-F", "bodyfile"])
+        .arg(hive_file())
+        .ok()
+        .unwrap();
+    assert_eq!(String::from_utf8(result.stdout).unwrap().lines().count(), 1);
+    assert!(String::from_utf8(result.stderr)
+        .unwrap()
+        .ends_with("warning: 6 findings have been suppressed by the filters\n"));
+}
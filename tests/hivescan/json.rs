@@ -0,0 +1,23 @@
+use serde_json::Value;
+
+use super::scan;
+
+#[test]
+fn json_with_cell_offsets() {
+    let output = scan(&["--format", "json"]);
+    let findings: Vec<Value> = serde_json::from_str(&output).unwrap();
+    assert_eq!(findings.len(), 7);
+
+    let finding = |path: &str| findings.iter().find(|f| f["path"] == path).unwrap();
+    let settings = finding("ROOT\\Software\\Uninstalled\\Settings");
+    assert_eq!(settings["name"], "Settings");
+    assert_eq!(settings["last_written"], "2024-03-05T10:01:00Z");
+    assert_eq!(settings["allocated"], false);
+    assert_eq!(settings["offset"], 0x12e0);
+
+    let vendor = finding("ROOT\\Software\\Vendor");
+    assert_eq!(vendor["allocated"], true);
+    assert_eq!(vendor["offset"], 0x1398);
+
+    assert_eq!(finding("[unknown-parent]\\Lost")["allocated"], false);
+}
@@ -1,2 +1,28 @@
+use std::path::PathBuf;
+
+use assert_cmd::Command;
+
 mod testhive;
-mod new_dirty_hive1;
\ No newline at end of file
+mod new_dirty_hive1;
+mod bodyfile;
+mod deleted_values;
+mod filter;
+mod json;
+
+/// a hive which contains deleted keys and values, and a deleted key and a
+/// deleted value whose parent does not exist anymore
+pub(crate) fn hive_file() -> PathBuf {
+    let mut data_path = PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap());
+    data_path.push("tests");
+    data_path.push("data");
+    data_path.push("hivescan");
+    data_path.push("SOFTWARE_deleted_values");
+    data_path
+}
+
+pub(crate) fn scan(args: &[&str]) -> String {
+    let mut cmd = Command::cargo_bin("hivescan").unwrap();
+    let result = cmd.args(args).arg(hive_file()).ok();
+    assert!(result.is_ok());
+    String::from_utf8(result.unwrap().stdout).unwrap()
+}
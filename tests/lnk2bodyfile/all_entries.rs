@@ -0,0 +1,52 @@
+use std::path::PathBuf;
+
+use assert_cmd::Command;
+use dfir_toolkit::common::bodyfile::{BehavesLikeI64, Bodyfile3Line};
+
+#[test]
+fn test_all_entries() {
+    let mut cmd = Command::cargo_bin("lnk2bodyfile").unwrap();
+    let mut data_path = PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap());
+    data_path.push("tests");
+    data_path.push("data");
+    data_path.push("lnk2bodyfile");
+    data_path.push("x64dbg.lnk");
+
+    let result = cmd.arg("--all-entries").arg(&data_path).ok();
+    assert!(result.is_ok());
+
+    let stdout = String::from_utf8(result.unwrap().stdout).unwrap();
+    let lines: Vec<_> = stdout
+        .lines()
+        .map(|line| Bodyfile3Line::try_from(line).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 3);
+
+    let header = &lines[0];
+    assert_eq!(
+        header.get_name(),
+        r#"C:\Program Files\x64dbg\release\x64\x64dbg.exe - (referred to by "x64dbg.lnk") [shell link header]"#
+    );
+    assert_eq!(header.get_mtime().as_ref(), Some(&1695724422));
+    assert_eq!(header.get_atime().as_ref(), Some(&1695724808));
+    assert_eq!(header.get_crtime().as_ref(), Some(&1695250410));
+
+    // the shell item stores FAT timestamps, which differ from the header
+    let shell_item = &lines[1];
+    assert_eq!(
+        shell_item.get_name(),
+        r#"C:\Program Files\x64dbg\release\x64\x64dbg.exe - (referred to by "x64dbg.lnk") [shell item extension block]"#
+    );
+    assert_eq!(shell_item.get_size(), header.get_size());
+    assert_eq!(shell_item.get_mtime().as_ref(), Some(&1695724424));
+    assert_eq!(shell_item.get_atime().as_ref(), Some(&1695724810));
+    assert_eq!(shell_item.get_crtime().as_ref(), Some(&1695250410));
+
+    let lnk_file = &lines[2];
+    assert_eq!(
+        lnk_file.get_name(),
+        &format!("{} [file system]", data_path.display())
+    );
+    assert_eq!(*lnk_file.get_size(), 1981);
+    assert!(lnk_file.get_mtime().is_some());
+}
@@ -0,0 +1,47 @@
+use std::path::PathBuf;
+
+use assert_cmd::Command;
+use serde_json::Value;
+
+#[test]
+fn test_json_metadata() {
+    let mut cmd = Command::cargo_bin("lnk2bodyfile").unwrap();
+    let mut data_path = PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap());
+    data_path.push("tests");
+    data_path.push("data");
+    data_path.push("lnk2bodyfile");
+    data_path.push("x64dbg.lnk");
+
+    let result = cmd.args(["--format", "json"]).arg(data_path).ok();
+    assert!(result.is_ok());
+
+    let stdout = String::from_utf8(result.unwrap().stdout).unwrap();
+    let mut lines = stdout.lines();
+    let metadata: Value = serde_json::from_str(lines.next().unwrap()).unwrap();
+    assert!(lines.next().is_none());
+
+    assert_eq!(metadata["lnk_file"], "x64dbg.lnk");
+    assert_eq!(
+        metadata["target_path"],
+        r"C:\Program Files\x64dbg\release\x64\x64dbg.exe"
+    );
+    assert_eq!(
+        metadata["working_dir"],
+        r"C:\Program Files\x64dbg\release\x64"
+    );
+    assert_eq!(metadata["description"], "A Debugger for the future!");
+    assert!(metadata["link_flags"]
+        .as_array()
+        .unwrap()
+        .contains(&Value::from("HAS_LINK_INFO")));
+    assert_eq!(metadata["target_size"], 172768);
+    assert_eq!(metadata["creation_time"], "2023-09-20T22:53:30Z");
+    assert_eq!(metadata["drive_type"], "fixed");
+    assert_eq!(metadata["volume_serial_number"], "6AE4-9C36");
+    assert_eq!(metadata["machine_id"], "desktop-ogjlo6b");
+    assert_eq!(metadata["mac_address"], "08:00:27:ec:f5:e1");
+
+    // absent fields are null, not empty strings
+    assert!(metadata["arguments"].is_null());
+    assert!(metadata["volume_label"].is_null());
+}
@@ -0,0 +1,173 @@
+use std::path::PathBuf;
+
+use assert_cmd::Command;
+
+use crate::common::put_u32;
+
+const ENDOFCHAIN: u32 = 0xfffffffe;
+const FREESECT: u32 = 0xffffffff;
+const FATSECT: u32 = 0xfffffffd;
+
+fn data_path(file_name: &str) -> PathBuf {
+    let mut data_path = PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap());
+    data_path.push("tests");
+    data_path.push("data");
+    data_path.push("lnk2bodyfile");
+    data_path.push(file_name);
+    data_path
+}
+
+fn lnk_data(file_name: &str) -> Vec<u8> {
+    std::fs::read(data_path(file_name)).unwrap()
+}
+
+fn directory_entry(name: &str, object_type: u8, start: u32, size: usize) -> Vec<u8> {
+    let mut entry = vec![0; 128];
+    let name: Vec<u16> = name.encode_utf16().chain([0]).collect();
+    for (index, c) in name.iter().enumerate() {
+        entry[index * 2..index * 2 + 2].copy_from_slice(&c.to_le_bytes());
+    }
+    entry[0x40..0x42].copy_from_slice(&((name.len() * 2) as u16).to_le_bytes());
+    entry[0x42] = object_type;
+    put_u32(&mut entry, 0x44, FREESECT);
+    put_u32(&mut entry, 0x48, FREESECT);
+    put_u32(&mut entry, 0x4c, FREESECT);
+    put_u32(&mut entry, 0x74, start);
+    put_u32(&mut entry, 0x78, size as u32);
+    entry
+}
+
+/// creates a compound file (version 3) which stores all streams in its mini
+/// stream, like a small automatic destinations jump list does
+fn compound_file(streams: &[(&str, Vec<u8>)]) -> Vec<u8> {
+    let mut directory = Vec::new();
+    let mut mini_stream = Vec::new();
+    let mut mini_fat = Vec::new();
+    for (name, data) in streams {
+        let start = mini_fat.len();
+        let sectors = data.len().div_ceil(64);
+        for index in 0..sectors {
+            mini_fat.push(if index + 1 == sectors {
+                ENDOFCHAIN
+            } else {
+                (start + index + 1) as u32
+            });
+        }
+        mini_stream.extend_from_slice(data);
+        mini_stream.resize(mini_fat.len() * 64, 0);
+        directory.extend(directory_entry(name, 2, start as u32, data.len()));
+    }
+    mini_stream.resize(mini_stream.len().div_ceil(512) * 512, 0);
+    let mini_stream_sectors = mini_stream.len() / 512;
+    let root = directory_entry("Root Entry", 5, 3, mini_fat.len() * 64);
+    directory.splice(0..0, root);
+    assert!(directory.len() <= 512 && mini_fat.len() <= 128);
+    directory.resize(512, 0);
+
+    // sector 0 is the FAT, sector 1 the directory, sector 2 the mini FAT
+    // and all other sectors contain the mini stream
+    let mut fat = vec![FATSECT, ENDOFCHAIN, ENDOFCHAIN];
+    for index in 0..mini_stream_sectors {
+        fat.push(if index + 1 == mini_stream_sectors {
+            ENDOFCHAIN
+        } else {
+            (index + 4) as u32
+        });
+    }
+    fat.resize(128, FREESECT);
+    mini_fat.resize(128, FREESECT);
+
+    let mut header = vec![0; 512];
+    header[0..8].copy_from_slice(&[0xd0, 0xcf, 0x11, 0xe0, 0xa1, 0xb1, 0x1a, 0xe1]);
+    header[0x18..0x20].copy_from_slice(&[0x3e, 0, 3, 0, 0xfe, 0xff, 9, 0]);
+    header[0x20] = 6;
+    put_u32(&mut header, 0x2c, 1);
+    put_u32(&mut header, 0x30, 1);
+    put_u32(&mut header, 0x38, 4096);
+    put_u32(&mut header, 0x3c, 2);
+    put_u32(&mut header, 0x40, 1);
+    put_u32(&mut header, 0x44, ENDOFCHAIN);
+    for index in 0..109 {
+        put_u32(&mut header, 0x4c + index * 4, FREESECT);
+    }
+    put_u32(&mut header, 0x4c, 0);
+
+    let mut file = header;
+    file.extend(fat.iter().flat_map(|sector| sector.to_le_bytes()));
+    file.extend(directory);
+    file.extend(mini_fat.iter().flat_map(|sector| sector.to_le_bytes()));
+    file.extend(mini_stream);
+    file
+}
+
+#[test]
+fn test_automatic_destinations() {
+    let dir = tempfile::tempdir().unwrap();
+    let jump_list = dir.path().join("5f7b5f1e01b83767.automaticDestinations-ms");
+    std::fs::write(
+        &jump_list,
+        compound_file(&[
+            ("1", lnk_data("x64dbg.lnk")[..120].to_vec()),
+            ("DestList", vec![1; 32]),
+            ("a", lnk_data("Obsidian.lnk")),
+        ]),
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("lnk2bodyfile").unwrap();
+    let result = cmd.arg(&jump_list).ok();
+    assert!(result.is_ok());
+    let output = result.unwrap();
+
+    // the malformed link is skipped, but the others are printed
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<_> = stdout.lines().collect();
+    assert_eq!(lines.len(), 1);
+    assert!(lines[0].contains(
+        r#"Obsidian.exe - (referred to by "5f7b5f1e01b83767.automaticDestinations-ms:a")"#
+    ));
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("processed 1 files with 1 links, 1 failures"));
+}
+
+#[test]
+fn test_recursive_directory() {
+    let dir = tempfile::tempdir().unwrap();
+    let recent = dir.path().join("Users/admin/AppData/Roaming/Microsoft/Windows/Recent");
+    let custom = recent.join("CustomDestinations");
+    std::fs::create_dir_all(&custom).unwrap();
+    std::fs::copy(data_path("x64dbg.lnk"), recent.join("x64dbg.lnk")).unwrap();
+    std::fs::write(recent.join("broken.lnk"), b"not a link").unwrap();
+    std::fs::write(recent.join("notes.txt"), b"not a link").unwrap();
+
+    // a custom destinations jump list contains the links with some
+    // additional data between them
+    let mut jump_list = vec![2, 0, 0, 0, 1, 0, 0, 0];
+    jump_list.extend(lnk_data("Obsidian.lnk"));
+    jump_list.extend([0xab, 0xfb, 0xbf, 0xba]);
+    jump_list.extend(lnk_data("x64dbg.lnk"));
+    jump_list.extend([0xab, 0xfb, 0xbf, 0xba]);
+    std::fs::write(
+        custom.join("590aee7bdd69b59b.customDestinations-ms"),
+        jump_list,
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("lnk2bodyfile").unwrap();
+    let result = cmd.arg(dir.path().join("Users")).ok();
+    assert!(result.is_ok());
+    let output = result.unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<_> = stdout.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0]
+        .contains(r#"Obsidian.exe - (referred to by "590aee7bdd69b59b.customDestinations-ms:1")"#));
+    assert!(lines[1]
+        .contains(r#"x64dbg.exe - (referred to by "590aee7bdd69b59b.customDestinations-ms:2")"#));
+    assert!(lines[2].contains(r#"x64dbg.exe - (referred to by "x64dbg.lnk")"#));
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("processed 2 files with 3 links, 1 failures"));
+}
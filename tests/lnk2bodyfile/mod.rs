@@ -1,2 +1,5 @@
 mod x64dbg;
-mod obsidian;
\ No newline at end of file
+mod obsidian;
+mod json;
+mod all_entries;
+mod jump_lists;
\ No newline at end of file
@@ -0,0 +1,23 @@
+use assert_cmd::Command;
+
+const BODYFILE: &str = r"0|C:\Users\jsmith\Desktop\notes.txt|1|r/rrw-r--r--|0|0|0|-1|1715845546|-1|-1
+0|C:\Windows\System32\cmd.exe|2|r/rrw-r--r--|0|0|0|-1|1715845547|-1|-1
+";
+
+#[test]
+fn anonymize_user_directories() {
+    let dir = tempfile::tempdir().unwrap();
+    let keyfile = dir.path().join("key");
+
+    let mut cmd = Command::cargo_bin("mactime2").unwrap();
+    let result = cmd
+        .args(["-d", "-b", "-", "--anonymize"])
+        .arg(&keyfile)
+        .write_stdin(BODYFILE)
+        .ok();
+    let output = String::from_utf8(result.unwrap().stdout).unwrap();
+
+    assert!(!output.contains("jsmith"));
+    assert!(output.contains(r"C:\Users\user-"));
+    assert!(output.contains(r"C:\Windows\System32\cmd.exe"));
+}
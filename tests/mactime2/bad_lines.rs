@@ -0,0 +1,54 @@
+use assert_cmd::Command;
+
+const BODYFILE: &str = "\
+0|/a|1|r/rrw-r--r--|0|0|10|-1|100|-1|-1
+0|/b|2|r/rrw-r--r--|0|0|10|-1|1\r00|-1|-1
+# comment
+0|/c
+0|/d|3|r/rrw-r--r--|0|0|10|-1|300|-1|-1\r\n";
+
+/// tests if malformed lines are skipped, counted and written to `--bad-lines`
+#[test]
+fn report_bad_lines() {
+    let dir = tempfile::tempdir().unwrap();
+    let bad_lines = dir.path().join("bad-lines.txt");
+
+    let mut cmd = Command::cargo_bin("mactime2").unwrap();
+    let result = cmd
+        .args(["-d", "-b", "-", "--bad-lines"])
+        .arg(&bad_lines)
+        .write_stdin(BODYFILE)
+        .ok()
+        .unwrap();
+    let stdout = String::from_utf8(result.stdout).unwrap();
+    let names: Vec<_> = stdout
+        .lines()
+        .map(|line| line.split(',').nth(7).unwrap())
+        .collect();
+    assert_eq!(names, vec!["/a", "/d"]);
+
+    let stderr = String::from_utf8(result.stderr).unwrap();
+    assert!(stderr.contains("skipped 2 malformed lines in 'stdin'"));
+
+    assert_eq!(
+        std::fs::read_to_string(&bad_lines).unwrap(),
+        "0|/b|2|r/rrw-r--r--|0|0|10|-1|1\r00|-1|-1\n0|/c\n"
+    );
+}
+
+/// tests if the strict mode aborts at the first malformed line, without
+/// writing a partial timeline
+#[test]
+fn abort_at_first_bad_line() {
+    let mut cmd = Command::cargo_bin("mactime2").unwrap();
+    let result = cmd
+        .args(["-d", "-b", "-", "--strict"])
+        .write_stdin(BODYFILE)
+        .assert()
+        .failure()
+        .get_output()
+        .clone();
+    assert!(result.stdout.is_empty());
+    let stderr = String::from_utf8(result.stderr).unwrap();
+    assert!(stderr.contains("malformed bodyfile line 2 in 'stdin'"));
+}
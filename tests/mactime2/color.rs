@@ -0,0 +1,30 @@
+use assert_cmd::Command;
+
+const BODYFILE: &str = "\
+0|/Windows/System32/cmd.exe|1|r/rrwxrwxrwx|0|0|0|1715845546|1715845546|1715845546|1715845546
+0|Prefetch: run 'CMD.EXE' (run 3 times)|0|r/rrwxrwxrwx|0|0|0|-1|1715845546|-1|-1
+";
+
+fn lines(args: &[&str]) -> Vec<String> {
+    let mut cmd = Command::cargo_bin("mactime2").unwrap();
+    let result = cmd.args(["-b", "-"]).args(args).write_stdin(BODYFILE).ok();
+    String::from_utf8(result.unwrap().stdout)
+        .unwrap()
+        .lines()
+        .map(ToOwned::to_owned)
+        .collect()
+}
+
+/// tests if only tagged rows are colored, and only if colors are enabled
+#[test]
+fn color_by_artifact_source() {
+    let colored = lines(&["--color", "always"]);
+    assert_eq!(colored.len(), 2);
+    assert!(!colored[0].contains('\x1b'));
+    assert!(colored[1].starts_with("\x1b[32m"));
+    assert!(colored[1].ends_with("\x1b[0m"));
+
+    for args in [&["--color", "never"][..], &[]] {
+        assert!(lines(args).iter().all(|line| !line.contains('\x1b')));
+    }
+}
@@ -0,0 +1,95 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use assert_cmd::Command;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+
+const PASSWD: &str = "0|/etc/passwd|1|r/rrw-r--r--|0|0|0|-1|1715845546|-1|-1\n";
+const SHADOW: &str = "0|/etc/shadow|2|r/rrw-------|0|0|0|-1|1715845547|-1|-1\n";
+const GROUP: &str = "0|/etc/group|3|r/rrw-r--r--|0|0|0|-1|1715845548|-1|-1\n";
+
+fn gzip(content: &str) -> Vec<u8> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(content.as_bytes()).unwrap();
+    encoder.finish().unwrap()
+}
+
+fn zstd(content: &str) -> Vec<u8> {
+    zstd::encode_all(content.as_bytes(), 0).unwrap()
+}
+
+fn write_bodyfile(dir: &Path, name: &str, content: &[u8]) -> PathBuf {
+    let path = dir.join(name);
+    std::fs::write(&path, content).unwrap();
+    path
+}
+
+fn names(csv: &[u8]) -> Vec<String> {
+    csv::ReaderBuilder::new()
+        .has_headers(false)
+        .from_reader(csv)
+        .records()
+        .map(|record| record.unwrap()[7].to_owned())
+        .collect()
+}
+
+/// tests if compressed bodyfiles are detected by their extension or by their
+/// content, and if they can be merged with plain bodyfiles
+#[test]
+fn read_compressed_inputs() {
+    let dir = tempfile::tempdir().unwrap();
+    let plain = write_bodyfile(dir.path(), "plain.bodyfile", GROUP.as_bytes());
+    let gz = write_bodyfile(dir.path(), "fs.bodyfile.gz", &gzip(PASSWD));
+    let zst = write_bodyfile(dir.path(), "fs.bodyfile.zst", &zstd(SHADOW));
+    let gz_without_extension = write_bodyfile(dir.path(), "gz.bodyfile", &gzip(PASSWD));
+    let zst_without_extension = write_bodyfile(dir.path(), "zst.bodyfile", &zstd(SHADOW));
+
+    let mactime2 = |inputs: &[&PathBuf]| -> Vec<String> {
+        let mut cmd = Command::cargo_bin("mactime2").unwrap();
+        for input in inputs {
+            cmd.arg("-b").arg(input);
+        }
+        names(&cmd.arg("-d").ok().unwrap().stdout)
+    };
+
+    assert_eq!(
+        mactime2(&[&plain, &gz, &zst]),
+        ["/etc/passwd", "/etc/shadow", "/etc/group"]
+    );
+    assert_eq!(
+        mactime2(&[&gz_without_extension, &zst_without_extension]),
+        ["/etc/passwd", "/etc/shadow"]
+    );
+}
+
+/// tests if compressed input is detected if it is read from stdin
+#[test]
+fn read_compressed_stdin() {
+    for input in [gzip(PASSWD), zstd(PASSWD)] {
+        let mut cmd = Command::cargo_bin("mactime2").unwrap();
+        let result = cmd.arg("-d").write_stdin(input).ok();
+        assert_eq!(names(&result.unwrap().stdout), ["/etc/passwd"]);
+    }
+}
+
+/// tests if a corrupted stream aborts mactime2 with the name of the file
+#[test]
+fn reject_corrupted_stream() {
+    let dir = tempfile::tempdir().unwrap();
+    let content: String = (0..1000).map(|_| PASSWD).collect();
+    for (name, mut compressed) in [
+        ("fs.bodyfile.gz", gzip(&content)),
+        ("fs.bodyfile.zst", zstd(&content)),
+    ] {
+        compressed.truncate(compressed.len() / 2);
+        let path = write_bodyfile(dir.path(), name, &compressed);
+
+        let mut cmd = Command::cargo_bin("mactime2").unwrap();
+        let result = cmd.arg("-d").arg("-b").arg(&path).assert().failure();
+        let output = result.get_output();
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains(&path.display().to_string()), "{stderr}");
+        assert!(output.stdout.is_empty());
+    }
+}
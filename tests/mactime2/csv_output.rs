@@ -25,3 +25,34 @@ fn csv_output() {
 
     assert_eq!(first_line.get(7).unwrap(), r##"{"activity_id":null,"channel_name":"Microsoft-Windows-WER-PayloadHealth/Operational","computer":"WIN-J56D9ENVG6H","custom_data":{"EventData":{"#attributes":{"Name":"WER_PAYLOAD_HEALTH_FAIL"},"BytesUploaded":0,"HttpExchangeResult":2147954402,"PayloadSize":4569,"Protocol":"Watson","RequestStatusCode":0,"ServerName":"umwatson.events.data.microsoft.com","Stage":"s1event","TransportHr":2147954402,"UploadDuration":21094}},"event_id":2,"event_record_id":1,"level":4,"provider_name":"Microsoft-Windows-WER-PayloadHealth","timestamp":"2022-11-16T08:26:43.409044Z"}"##);
 }
+
+/// tests if names containing the delimiter are quoted, and if the default
+/// output is not changed by the CSV options
+#[test]
+fn csv_delimiter_and_bom() {
+    let bodyfile = "0|/tmp/a;b,c|1234|r/rrw-r--r--|0|0|42|-1|0|-1|-1\n";
+    let csv = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("mactime2").unwrap();
+        let result = cmd.args(args).arg("-b").arg("-").write_stdin(bodyfile).ok();
+        String::from_utf8(result.unwrap().stdout).unwrap()
+    };
+
+    assert_eq!(
+        csv(&["-d"]),
+        "1970-01-01T00:00:00+00:00,42,m...,r/rrw-r--r--,0,0,1234,\"/tmp/a;b,c\"\n"
+    );
+    assert_eq!(
+        csv(&["--delimiter", ";", "--bom"]),
+        "\u{feff}1970-01-01T00:00:00+00:00;42;m...;r/rrw-r--r--;0;0;1234;\"/tmp/a;b,c\"\n"
+    );
+    assert_eq!(
+        csv(&["-F", "csv", "--delimiter", "\\t"]),
+        "1970-01-01T00:00:00+00:00\t42\tm...\tr/rrw-r--r--\t0\t0\t1234\t/tmp/a;b,c\n"
+    );
+
+    let mut cmd = Command::cargo_bin("mactime2").unwrap();
+    cmd.args(["-F", "txt", "--bom", "-b", "-"])
+        .write_stdin(bodyfile)
+        .assert()
+        .failure();
+}
@@ -0,0 +1,51 @@
+use assert_cmd::Command;
+
+const BODYFILE: &str = "\
+0|/a|1|r/rrw-r--r--|0|0|10|-1|100|-1|-1
+0|/b|2|r/rrw-r--r--|0|0|10|-1|100|-1|-1
+0|/a|1|r/rrw-r--r--|0|0|10|-1|100|-1|-1
+0|/a|1|r/rrw-r--r--|0|0|20|-1|100|-1|-1
+0|/a|1|r/rrw-r--r--|0|0|10|-1|200|-1|-1
+";
+
+fn mactime2(args: &[&str]) -> (Vec<String>, String) {
+    let mut cmd = Command::cargo_bin("mactime2").unwrap();
+    let result = cmd
+        .args(["-v", "-d", "-b", "-"])
+        .args(args)
+        .write_stdin(BODYFILE)
+        .ok()
+        .unwrap();
+    let entries = String::from_utf8(result.stdout)
+        .unwrap()
+        .lines()
+        .map(|line| {
+            let columns: Vec<_> = line.split(',').collect();
+            format!("{}@{}:{}", columns[7], &columns[0][17..19], columns[1])
+        })
+        .collect();
+    (entries, String::from_utf8(result.stderr).unwrap())
+}
+
+#[test]
+fn dedup_identical_entries() {
+    let (entries, _) = mactime2(&[]);
+    assert_eq!(entries.len(), 5);
+
+    let (entries, stderr) = mactime2(&["--dedup"]);
+    assert_eq!(
+        entries,
+        vec!["/a@40:10", "/b@40:10", "/a@40:20", "/a@20:10"]
+    );
+    assert!(stderr.contains("removed 1 duplicate entries"));
+}
+
+#[test]
+fn dedup_by_fields() {
+    let (entries, stderr) = mactime2(&["--dedup-by", "name,timestamp"]);
+    assert_eq!(entries, vec!["/a@40:10", "/b@40:10", "/a@20:10"]);
+    assert!(stderr.contains("removed 2 duplicate entries"));
+
+    let (entries, _) = mactime2(&["--dedup-by", "name"]);
+    assert_eq!(entries, vec!["/a@40:10", "/b@40:10"]);
+}
@@ -0,0 +1,57 @@
+use assert_cmd::Command;
+use serde_json::{json, Value};
+
+const BODYFILE: &str = r#"0|/etc/passwd|1|r/rrw-r--r--|0|0|0|-1|1715845546|-1|-1
+{"name": "logon of 'admin'", "mtime": 1715845545, "event_id": 4624, "data": {"user": "admin"}}
+# comment
+{"name": "created by 'admin'", "inode": "2", "size": 42, "crtime": 1715845547, "atime": 1715845547}
+"#;
+
+fn mactime2(args: &[&str]) -> String {
+    let mut cmd = Command::cargo_bin("mactime2").unwrap();
+    let result = cmd.args(["-b", "-"]).args(args).write_stdin(BODYFILE).ok();
+    String::from_utf8(result.unwrap().stdout).unwrap()
+}
+
+/// tests if classic and extended lines are sorted together, and if the
+/// attributes of extended lines are written by the JSON output
+#[test]
+fn mix_classic_and_extended_lines() {
+    let entries: Vec<Value> = mactime2(&["-F", "jsonl"])
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(entries.len(), 3);
+    assert_eq!(entries[0]["name"], "logon of 'admin'");
+    assert_eq!(
+        entries[0]["attributes"],
+        json!({"event_id": 4624, "data": {"user": "admin"}})
+    );
+    assert_eq!(entries[1]["name"], "/etc/passwd");
+    assert!(entries[1].get("attributes").is_none());
+    assert_eq!(entries[2]["name"], "created by 'admin'");
+    assert_eq!(
+        entries[2]["macb"],
+        json!({"m": false, "a": true, "c": false, "b": true})
+    );
+    assert_eq!(entries[2]["size"], 42);
+    assert_eq!(entries[2]["attributes"], json!({}));
+
+    let csv = mactime2(&["-d"]);
+    let names: Vec<_> = csv
+        .lines()
+        .map(|line| line.split(',').nth(7).unwrap())
+        .collect();
+    assert_eq!(
+        names,
+        ["logon of 'admin'", "/etc/passwd", "created by 'admin'"]
+    );
+}
+
+#[test]
+fn sort_extended_lines_with_temporary_files() {
+    assert_eq!(
+        mactime2(&["-F", "jsonl"]),
+        mactime2(&["-F", "jsonl", "--max-memory", "0"])
+    );
+}
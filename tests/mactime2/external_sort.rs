@@ -0,0 +1,50 @@
+use assert_cmd::Command;
+
+/// a bodyfile with many entries which share only a few timestamps, so that
+/// stable sorting can be checked
+fn bodyfile() -> String {
+    (0..2000)
+        .map(|i| {
+            let ts = 1661774613 + (i * 7919) % 13;
+            format!(
+                "0|/dir/file {i}|{i}|r/rrw-r--r--|0|0|{i}|{ts}|{}|{ts}|-1\n",
+                ts + i % 3
+            )
+        })
+        .collect()
+}
+
+fn mactime2(args: &[&str]) -> Vec<u8> {
+    let mut cmd = Command::cargo_bin("mactime2").unwrap();
+    let result = cmd
+        .args(["-d", "-b", "-"])
+        .args(args)
+        .write_stdin(bodyfile())
+        .ok();
+    result.unwrap().stdout
+}
+
+/// tests if sorting with temporary files results in the same timeline as
+/// sorting in memory, and if all temporary files are removed
+#[test]
+fn sort_with_temporary_files() {
+    let tmpdir = tempfile::tempdir().unwrap();
+
+    let in_memory = mactime2(&[]);
+    let external = mactime2(&["--max-memory", "16K", "--tmpdir", tmpdir.path().to_str().unwrap()]);
+    assert_eq!(in_memory.len(), external.len());
+    assert!(in_memory == external);
+    assert_eq!(std::fs::read_dir(tmpdir.path()).unwrap().count(), 0);
+}
+
+#[test]
+fn reject_missing_tmpdir() {
+    let dir = tempfile::tempdir().unwrap();
+    let tmpdir = dir.path().join("missing");
+    let mut cmd = Command::cargo_bin("mactime2").unwrap();
+    cmd.args(["-d", "-b", "-", "--max-memory", "0", "--tmpdir"])
+        .arg(tmpdir)
+        .write_stdin(bodyfile())
+        .assert()
+        .failure();
+}
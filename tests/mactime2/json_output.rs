@@ -0,0 +1,155 @@
+use std::{
+    io::{BufReader, Cursor},
+    path::PathBuf,
+};
+
+use assert_cmd::Command;
+use serde_json::Value;
+
+fn mactime2(args: &[&str]) -> Vec<u8> {
+    let mut data_path = PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap());
+    data_path.push("tests");
+    data_path.push("data");
+    data_path.push("mactime2");
+    data_path.push("sample.bodyfile");
+
+    let mut cmd = Command::cargo_bin("mactime2").unwrap();
+    let result = cmd.args(args).arg("-b").arg(data_path).ok();
+    assert!(result.is_ok());
+    result.unwrap().stdout
+}
+
+/// tests if the JSONL output contains the same entries in the same order as
+/// the CSV output
+#[test]
+fn jsonl_output() {
+    let entries: Vec<Value> = String::from_utf8(mactime2(&["-F", "jsonl"]))
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .from_reader(BufReader::new(Cursor::new(mactime2(&["-F", "csv"]))));
+    let records: Vec<_> = reader.records().map(|record| record.unwrap()).collect();
+    assert_eq!(entries.len(), records.len());
+
+    for (entry, record) in entries.iter().zip(records.iter()) {
+        assert_eq!(entry["timestamp"], record[0]);
+        assert_eq!(entry["size"].as_u64().unwrap().to_string(), &record[1]);
+        let flags: String = ["m", "a", "c", "b"]
+            .iter()
+            .map(|flag| {
+                if entry["macb"][flag].as_bool().unwrap() {
+                    flag.chars().next().unwrap()
+                } else {
+                    '.'
+                }
+            })
+            .collect();
+        assert_eq!(flags, &record[2]);
+        assert_eq!(entry["mode"], record[3]);
+        assert_eq!(entry["uid"].as_u64().unwrap().to_string(), &record[4]);
+        assert_eq!(entry["gid"].as_u64().unwrap().to_string(), &record[5]);
+        assert_eq!(entry["inode"], record[6]);
+        assert_eq!(entry["name"], record[7]);
+        assert!(entry["source"]
+            .as_str()
+            .unwrap()
+            .ends_with("sample.bodyfile"));
+    }
+}
+
+/// tests if every line of the JSONL output with ECS field names is a
+/// complete ECS document, which corresponds to the entry of the JSONL output
+/// in the same line
+#[test]
+fn jsonl_ecs_output() {
+    let json = String::from_utf8(mactime2(&["-F", "jsonl"])).unwrap();
+    let jsonl = String::from_utf8(mactime2(&["-F", "jsonl", "--ecs"])).unwrap();
+    assert_eq!(json.lines().count(), jsonl.lines().count());
+
+    for (json_line, jsonl_line) in json.lines().zip(jsonl.lines()) {
+        assert!(jsonl_line.starts_with('{') && jsonl_line.ends_with('}'));
+        let entry: Value = serde_json::from_str(json_line).unwrap();
+        let document: Value = serde_json::from_str(jsonl_line).unwrap();
+        assert_eq!(document["@timestamp"], entry["timestamp"]);
+        assert_eq!(document["file"]["path"], entry["name"]);
+        assert_eq!(document["file"]["size"], entry["size"]);
+        assert_eq!(document["log"]["file"]["path"], entry["source"]);
+
+        let actions: Vec<_> = document["event"]["action"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|action| action.as_str().unwrap())
+            .collect();
+        for (flag, action) in [
+            ("m", "modified"),
+            ("a", "accessed"),
+            ("c", "changed"),
+            ("b", "created"),
+        ] {
+            assert_eq!(
+                entry["macb"][flag].as_bool().unwrap(),
+                actions.contains(&action)
+            );
+        }
+    }
+}
+
+#[test]
+fn ecs_requires_json_format() {
+    let mut cmd = Command::cargo_bin("mactime2").unwrap();
+    cmd.args(["-F", "txt", "--ecs", "-b", "-"])
+        .write_stdin("")
+        .assert()
+        .failure();
+}
+
+/// tests if `json` writes the entries of `jsonl` as a JSON array
+#[test]
+fn json_writes_array() {
+    let entries: Vec<Value> = serde_json::from_slice(&mactime2(&["-F", "json"])).unwrap();
+    let lines: Vec<Value> = String::from_utf8(mactime2(&["-F", "jsonl"]))
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert!(!entries.is_empty());
+    assert_eq!(entries, lines);
+    assert_eq!(mactime2(&["-j"]), mactime2(&["-F", "json"]));
+}
+
+/// tests if the deprecated format `elastic` still writes the ECS documents of
+/// every timestamp of a file, like earlier releases did
+#[test]
+fn elastic_writes_ecs_documents() {
+    let documents: Vec<Value> = String::from_utf8(mactime2(&["-F", "elastic"]))
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert!(!documents.is_empty());
+    for document in &documents {
+        assert!(document["@timestamp"].is_number());
+        assert!(document["file"]["path"].is_string());
+        assert!(document.get("macb").is_none());
+    }
+}
+
+/// tests if 'json' and 'jsonl' are different formats, and if the 'jsonl'
+/// output has neither a wrapping array nor separating commas
+#[test]
+fn jsonl_differs_from_json() {
+    let json = mactime2(&["-F", "json"]);
+    let jsonl = String::from_utf8(mactime2(&["-F", "jsonl"])).unwrap();
+    assert_ne!(json, jsonl.as_bytes());
+
+    assert!(serde_json::from_str::<Value>(&jsonl).is_err());
+    for line in jsonl.lines() {
+        assert!(line.starts_with('{') && line.ends_with('}'));
+        assert!(serde_json::from_str::<Value>(line).unwrap().is_object());
+    }
+}
@@ -3,4 +3,19 @@ mod autocomplete;
 mod is_sorted;
 mod is_stable_sorting;
 
-mod csv_output;
\ No newline at end of file
+mod csv_output;
+mod json_output;
+mod time_window;
+mod timezone;
+mod external_sort;
+mod dedup;
+mod bad_lines;
+mod color;
+mod name_filter;
+mod split_output;
+mod extended_input;
+mod multiple_inputs;
+mod compressed_input;
+mod stats;
+mod output_file;
+mod anonymize;
@@ -0,0 +1,84 @@
+use std::path::{Path, PathBuf};
+
+use assert_cmd::Command;
+use serde_json::Value;
+
+fn write_bodyfile(dir: &Path, name: &str, content: &str) -> PathBuf {
+    let path = dir.join(name);
+    std::fs::write(&path, content).unwrap();
+    path
+}
+
+/// tests if multiple bodyfiles are merged, and if every entry keeps the label
+/// of its bodyfile in every output format
+#[test]
+fn merge_labeled_inputs() {
+    let dir = tempfile::tempdir().unwrap();
+    let fs = write_bodyfile(
+        dir.path(),
+        "fs.bodyfile",
+        "0|/etc/passwd|1|r/rrw-r--r--|0|0|0|-1|1715845546|-1|-1\n",
+    );
+    let evtx = write_bodyfile(
+        dir.path(),
+        "evtx.bodyfile",
+        "0|logon of 'admin'|0||0|0|0|-1|1715845545|-1|-1\n",
+    );
+    let host1_fs = format!("host1:{}", fs.display());
+    let host1_evtx = format!("host,\"1\":{}", evtx.display());
+
+    let mactime2 = |args: &[&str]| -> String {
+        let mut cmd = Command::cargo_bin("mactime2").unwrap();
+        let result = cmd
+            .args(["-b", &host1_fs, "-b", &host1_evtx])
+            .args(args)
+            .ok();
+        String::from_utf8(result.unwrap().stdout).unwrap()
+    };
+
+    let csv = mactime2(&["-d", "--show-headers"]);
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(true)
+        .from_reader(csv.as_bytes());
+    assert_eq!(&reader.headers().unwrap()[8], "source");
+    let sources: Vec<_> = reader
+        .records()
+        .map(|record| record.unwrap()[8].to_owned())
+        .collect();
+    assert_eq!(sources, ["host,\"1\"", "host1"]);
+
+    let sources: Vec<_> = mactime2(&["-F", "jsonl"])
+        .lines()
+        .map(|line| serde_json::from_str::<Value>(line).unwrap()["source"].clone())
+        .collect();
+    assert_eq!(sources, ["host,\"1\"", "host1"]);
+
+    let txt = mactime2(&[]);
+    assert!(txt
+        .lines()
+        .next()
+        .unwrap()
+        .ends_with(" [host,\"1\"] logon of 'admin'"));
+
+    // without a label, the path is used
+    let mut cmd = Command::cargo_bin("mactime2").unwrap();
+    let result = cmd.args(["-d", "-b", &host1_fs, "-b"]).arg(&evtx).ok();
+    let csv = String::from_utf8(result.unwrap().stdout).unwrap();
+    assert!(csv
+        .lines()
+        .next()
+        .unwrap()
+        .ends_with(&format!(",{}", evtx.display())));
+}
+
+/// a single bodyfile without label gets no additional column
+#[test]
+fn single_input_without_source() {
+    let mut cmd = Command::cargo_bin("mactime2").unwrap();
+    let result = cmd
+        .args(["-d", "-b", "-"])
+        .write_stdin("0|/etc/passwd|1|r/rrw-r--r--|0|0|0|-1|1715845546|-1|-1\n")
+        .ok();
+    let csv = String::from_utf8(result.unwrap().stdout).unwrap();
+    assert_eq!(csv.trim_end().split(',').count(), 8);
+}
@@ -0,0 +1,47 @@
+use assert_cmd::Command;
+
+const BODYFILE: &str = r"0|C:\Users\admin\Desktop\notes.txt|1|r/rrw-r--r--|0|0|0|-1|1715845546|-1|-1
+0|C:\USERS\guest\ntuser.dat|2|r/rrw-r--r--|0|0|0|-1|1715845547|-1|-1
+0|C:\Users\admin\AppData\Local\Temp\x.tmp|3|r/rrw-r--r--|0|0|0|-1|1715845548|-1|-1
+0|C:\Windows\winsxs\amd64\cmd.exe|4|r/rrw-r--r--|0|0|0|-1|1715845549|-1|-1
+0|C:\Windows\System32\cmd.exe|5|r/rrw-r--r--|0|0|0|-1|1715845550|-1|-1
+";
+
+fn inodes(args: &[&str]) -> Vec<String> {
+    let mut cmd = Command::cargo_bin("mactime2").unwrap();
+    let result = cmd
+        .args(["-d", "-b", "-"])
+        .args(args)
+        .write_stdin(BODYFILE)
+        .ok();
+    String::from_utf8(result.unwrap().stdout)
+        .unwrap()
+        .lines()
+        .map(|line| line.split(',').nth(6).unwrap().to_owned())
+        .collect()
+}
+
+/// tests if `--include` is applied before `--exclude`
+#[test]
+fn filter_by_name() {
+    assert_eq!(inodes(&[]).len(), 5);
+    assert_eq!(inodes(&["--include", r"(?i)\\users\\"]), ["1", "2", "3"]);
+    assert_eq!(inodes(&["--include", r"\\Users\\"]), ["1", "3"]);
+    assert_eq!(
+        inodes(&["--include", r"(?i)\\users\\", "--exclude", r"\\AppData\\"]),
+        ["1", "2"]
+    );
+    assert_eq!(
+        inodes(&["--exclude", r"\\winsxs\\", "--exclude", r"\\AppData\\"]),
+        ["1", "2", "5"]
+    );
+}
+
+#[test]
+fn reject_invalid_regex() {
+    let mut cmd = Command::cargo_bin("mactime2").unwrap();
+    cmd.args(["-b", "-", "--include", "(unclosed"])
+        .write_stdin(BODYFILE)
+        .assert()
+        .failure();
+}
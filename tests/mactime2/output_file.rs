@@ -0,0 +1,85 @@
+use std::io::Read;
+
+use assert_cmd::Command;
+use flate2::read::GzDecoder;
+
+const BODYFILE: &str = "0|/etc/passwd|1|r/rrw-r--r--|0|0|0|-1|1715845546|-1|-1\n";
+
+/// tests if `--output` writes the timeline into a file instead of stdout,
+/// and if an existing file is only replaced with `--force`
+#[test]
+fn write_into_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("timeline.csv");
+
+    let mut cmd = Command::cargo_bin("mactime2").unwrap();
+    let result = cmd
+        .args(["-d", "-b", "-", "--output"])
+        .arg(&path)
+        .write_stdin(BODYFILE)
+        .ok()
+        .unwrap();
+    assert!(result.stdout.is_empty());
+    let timeline = std::fs::read_to_string(&path).unwrap();
+    assert!(timeline.contains("/etc/passwd"));
+
+    let mut cmd = Command::cargo_bin("mactime2").unwrap();
+    let result = cmd
+        .args(["-d", "-b", "-", "--output"])
+        .arg(&path)
+        .write_stdin("")
+        .assert()
+        .failure()
+        .get_output()
+        .clone();
+    let stderr = String::from_utf8(result.stderr).unwrap();
+    assert!(stderr.contains("already exists, use '--force' to replace it"));
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), timeline);
+
+    let mut cmd = Command::cargo_bin("mactime2").unwrap();
+    cmd.args(["-d", "-b", "-", "--force", "--output"])
+        .arg(&path)
+        .write_stdin("")
+        .ok()
+        .unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "");
+
+    // no temporary files are left behind
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+}
+
+/// tests if the output is compressed if the file name ends with '.gz'
+#[test]
+fn write_gzipped_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("timeline.json.gz");
+
+    let mut cmd = Command::cargo_bin("mactime2").unwrap();
+    cmd.args(["-b", "-", "-F", "jsonl", "-o"])
+        .arg(&path)
+        .write_stdin(BODYFILE)
+        .ok()
+        .unwrap();
+
+    let mut timeline = String::new();
+    GzDecoder::new(std::fs::File::open(&path).unwrap())
+        .read_to_string(&mut timeline)
+        .unwrap();
+    let entry: serde_json::Value = serde_json::from_str(timeline.trim_end()).unwrap();
+    assert_eq!(entry["name"], "/etc/passwd");
+}
+
+/// tests if the timeline file is not created if mactime2 fails
+#[test]
+fn no_file_after_failure() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("timeline.csv");
+
+    let mut cmd = Command::cargo_bin("mactime2").unwrap();
+    cmd.args(["-d", "-b", "-", "--strict", "-o"])
+        .arg(&path)
+        .write_stdin(format!("{BODYFILE}0|/b\n"))
+        .assert()
+        .failure();
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+}
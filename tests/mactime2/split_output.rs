@@ -0,0 +1,60 @@
+use assert_cmd::Command;
+
+const BODYFILE: &str = "\
+0|/a|1|r/rrw-r--r--|0|0|0|-1|1710201600|-1|-1
+0|/b|2|r/rrw-r--r--|0|0|0|0|1710284400|1710288001|-1
+0|/c|3|r/rrw-r--r--|0|0|0|-1|-1|-1|-1
+";
+
+/// tests if every entry is written into the file of its day in the
+/// destination timezone, and if every file has a header
+#[test]
+fn split_by_day() {
+    let dir = tempfile::tempdir().unwrap();
+    let output_dir = dir.path().join("timeline");
+    let mut cmd = Command::cargo_bin("mactime2").unwrap();
+    let result = cmd
+        .args(["-d", "-b", "-", "-t", "Europe/Berlin", "--split-by", "day"])
+        .arg("--output-dir")
+        .arg(&output_dir)
+        .write_stdin(BODYFILE)
+        .ok()
+        .unwrap();
+    assert!(result.stdout.is_empty());
+
+    let mut files: Vec<_> = std::fs::read_dir(&output_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect();
+    files.sort();
+    assert_eq!(
+        files,
+        [
+            "timeline-2024-03-12.csv",
+            "timeline-2024-03-13.csv",
+            "timeline-epoch.csv"
+        ]
+    );
+
+    let inodes = |file: &str| -> Vec<String> {
+        let content = std::fs::read_to_string(output_dir.join(file)).unwrap();
+        let mut lines = content.lines();
+        assert!(lines.next().unwrap().starts_with("timestamp,"));
+        lines
+            .map(|line| line.split(',').nth(6).unwrap().to_owned())
+            .collect()
+    };
+    // 2024-03-12T23:00:00Z is already the 13th in Berlin
+    assert_eq!(inodes("timeline-2024-03-12.csv"), ["1"]);
+    assert_eq!(inodes("timeline-2024-03-13.csv"), ["2", "2"]);
+    assert_eq!(inodes("timeline-epoch.csv"), ["3", "2"]);
+}
+
+#[test]
+fn require_output_dir() {
+    let mut cmd = Command::cargo_bin("mactime2").unwrap();
+    cmd.args(["-d", "-b", "-", "--split-by", "day"])
+        .write_stdin(BODYFILE)
+        .assert()
+        .failure();
+}
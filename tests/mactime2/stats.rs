@@ -0,0 +1,77 @@
+use assert_cmd::Command;
+
+const BODYFILE: &str = "0|/etc/passwd|1|r/rrw-r--r--|0|0|0|-1|1715845546|-1|-1
+0|/etc/shadow|2|r/rrw-------|0|0|0|-1|1715845547|1715932800|-1
+0|/etc/group|3|r/rrw-r--r--|0|0|0|-1|0|-1|-1
+";
+
+fn value<'s>(stats: &'s str, key: &str) -> Option<&'s str> {
+    stats
+        .lines()
+        .find_map(|line| line.trim_start().strip_prefix(key))
+        .map(str::trim_start)
+}
+
+/// tests if the statistics are written to stderr after the timeline, without
+/// changing the timeline
+#[test]
+fn print_stats() {
+    let mut cmd = Command::cargo_bin("mactime2").unwrap();
+    let timeline = cmd.arg("-d").write_stdin(BODYFILE).ok().unwrap().stdout;
+
+    let mut cmd = Command::cargo_bin("mactime2").unwrap();
+    let result = cmd
+        .args(["-d", "--stats"])
+        .write_stdin(BODYFILE)
+        .ok()
+        .unwrap();
+    assert_eq!(result.stdout, timeline);
+
+    let stats = String::from_utf8(result.stderr).unwrap();
+    assert_eq!(value(&stats, "entries:"), Some("4"));
+    assert_eq!(value(&stats, "stdin:"), Some("4"));
+    assert_eq!(
+        value(&stats, "first timestamp:"),
+        Some("2024-05-16T07:45:46+00:00")
+    );
+    assert_eq!(
+        value(&stats, "last timestamp:"),
+        Some("2024-05-17T08:00:00+00:00")
+    );
+    assert_eq!(
+        value(&stats, "busiest day:"),
+        Some("2024-05-16 (2 entries)")
+    );
+    assert_eq!(
+        value(&stats, "busiest hour:"),
+        Some("2024-05-16 07:00 (2 entries)")
+    );
+    assert_eq!(value(&stats, "zero or no timestamp:"), Some("1"));
+}
+
+/// tests if the statistics are written into a file, counted per source
+#[test]
+fn write_stats_into_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let bodyfile = dir.path().join("fs.bodyfile");
+    std::fs::write(&bodyfile, BODYFILE).unwrap();
+    let stats_file = dir.path().join("stats.txt");
+
+    let mut cmd = Command::cargo_bin("mactime2").unwrap();
+    let result = cmd
+        .arg("-b")
+        .arg(format!("host1:{}", bodyfile.display()))
+        .arg("-b")
+        .arg(format!("host2:{}", bodyfile.display()))
+        .args(["-d", "--from", "2024-05-17T00:00:00Z", "--stats"])
+        .arg(&stats_file)
+        .ok()
+        .unwrap();
+    assert!(result.stderr.is_empty());
+
+    let stats = std::fs::read_to_string(&stats_file).unwrap();
+    assert_eq!(value(&stats, "entries:"), Some("2"));
+    assert_eq!(value(&stats, "host1:"), Some("1"));
+    assert_eq!(value(&stats, "host2:"), Some("1"));
+    assert_eq!(value(&stats, "zero or no timestamp:"), Some("0"));
+}
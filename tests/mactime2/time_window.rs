@@ -0,0 +1,72 @@
+use assert_cmd::Command;
+
+const BODYFILE: &str = "\
+0|/zero|1|r/rrw-r--r--|0|0|0|0|0|0|0
+0|/before|2|r/rrw-r--r--|0|0|0|-1|1715817600|-1|-1
+0|/inside|3|r/rrw-r--r--|0|0|0|1715904000|1715904000|1715990400|-1
+0|/after|4|r/rrw-r--r--|0|0|0|-1|1717200000|-1|-1
+";
+
+fn names(args: &[&str]) -> Vec<String> {
+    let mut cmd = Command::cargo_bin("mactime2").unwrap();
+    let result = cmd
+        .args(["-d", "-b", "-"])
+        .args(args)
+        .write_stdin(BODYFILE)
+        .ok();
+    String::from_utf8(result.unwrap().stdout)
+        .unwrap()
+        .lines()
+        .map(|line| {
+            let mut columns = line.split(',');
+            format!("{}@{}", columns.nth(7).unwrap(), &line[..10])
+        })
+        .collect()
+}
+
+/// tests if only the entries inside of the time window are displayed, and if
+/// zero timestamps are hidden by the window
+#[test]
+fn filter_time_window() {
+    assert_eq!(names(&[]).len(), 5);
+    assert_eq!(
+        names(&["--from", "2024-05-17", "--to", "2024-05-17T23:59:59Z"]),
+        vec!["/inside@2024-05-17"]
+    );
+    assert_eq!(
+        names(&["--to", "2024-05-17"]),
+        vec!["/before@2024-05-16", "/inside@2024-05-17"]
+    );
+    assert_eq!(
+        names(&["--to", "2024-05-17", "--include-zero"]),
+        vec![
+            "/zero@1970-01-01",
+            "/before@2024-05-16",
+            "/inside@2024-05-17"
+        ]
+    );
+    assert_eq!(
+        names(&["--from", "2024-05-18T00:00:00+02:00"]),
+        vec!["/inside@2024-05-18", "/after@2024-06-01"]
+    );
+}
+
+#[test]
+fn reject_empty_time_window() {
+    let mut cmd = Command::cargo_bin("mactime2").unwrap();
+    cmd.args(["--from", "2024-06-01", "--to", "2024-05-01", "-b", "-"])
+        .write_stdin(BODYFILE)
+        .assert()
+        .failure();
+}
+
+#[test]
+fn reject_invalid_timestamp() {
+    for option in ["--from", "--to"] {
+        let mut cmd = Command::cargo_bin("mactime2").unwrap();
+        let assert = cmd.args([option, "yesterday", "-b", "-"]).write_stdin(BODYFILE).assert();
+        let stderr = String::from_utf8_lossy(&assert.get_output().stderr).to_string();
+        assert.code(2);
+        assert!(stderr.contains("invalid timestamp: 'yesterday'"));
+    }
+}
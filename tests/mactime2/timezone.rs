@@ -0,0 +1,49 @@
+use assert_cmd::Command;
+use serde_json::Value;
+
+/// 2023-10-29T00:30:00Z and 2023-10-29T01:30:00Z, daylight saving time ends
+/// at 01:00 UTC in Europe/Berlin
+const BODYFILE: &str = "\
+0|/before|1|r/rrw-r--r--|0|0|0|-1|1698539400|-1|-1
+0|/after|2|r/rrw-r--r--|0|0|0|-1|1698543000|-1|-1
+";
+
+fn mactime2(args: &[&str]) -> String {
+    let mut cmd = Command::cargo_bin("mactime2").unwrap();
+    let result = cmd
+        .args(["--timezone", "Europe/Berlin", "--show-utc", "-b", "-"])
+        .args(args)
+        .write_stdin(BODYFILE)
+        .ok();
+    String::from_utf8(result.unwrap().stdout).unwrap()
+}
+
+/// tests if every timestamp is converted with the offset which is valid at
+/// its time, on both sides of a change of the daylight saving time
+#[test]
+fn convert_around_dst_transition() {
+    assert_eq!(
+        mactime2(&["-d", "-H"]),
+        "timestamp,size,flags,mode,uid,gid,inode,name,utc_timestamp\n\
+         2023-10-29T02:30:00+02:00,0,m...,r/rrw-r--r--,0,0,1,/before,2023-10-29T00:30:00+00:00\n\
+         2023-10-29T02:30:00+01:00,0,m...,r/rrw-r--r--,0,0,2,/after,2023-10-29T01:30:00+00:00\n"
+    );
+
+    let entries: Vec<Value> = mactime2(&["-F", "jsonl"])
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(entries[0]["timestamp"], "2023-10-29T02:30:00+02:00");
+    assert_eq!(entries[0]["utc_timestamp"], "2023-10-29T00:30:00+00:00");
+    assert_eq!(entries[1]["timestamp"], "2023-10-29T02:30:00+01:00");
+    assert_eq!(entries[1]["utc_timestamp"], "2023-10-29T01:30:00+00:00");
+}
+
+#[test]
+fn show_utc_requires_csv_or_json() {
+    let mut cmd = Command::cargo_bin("mactime2").unwrap();
+    cmd.args(["-F", "txt", "--show-utc", "-b", "-"])
+        .write_stdin(BODYFILE)
+        .assert()
+        .failure();
+}
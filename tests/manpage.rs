@@ -0,0 +1,64 @@
+use assert_cmd::Command;
+
+/// every binary, and if it has been built with the enabled features
+const BINARIES: &[(&str, bool)] = &[
+    ("mactime2", cfg!(feature = "mactime2")),
+    ("evtxscan", cfg!(feature = "evtxscan")),
+    ("evtxcat", cfg!(feature = "evtxcat")),
+    ("evtxls", cfg!(feature = "evtxls")),
+    ("evtxanalyze", cfg!(feature = "evtxanalyze")),
+    ("evtx2bodyfile", cfg!(feature = "evtx2bodyfile")),
+    ("pol_export", cfg!(feature = "pol_export")),
+    ("es4forensics", cfg!(feature = "es4forensics")),
+    ("regdump", cfg!(feature = "regdump")),
+    ("hivescan", cfg!(feature = "hivescan")),
+    ("cleanhive", cfg!(feature = "cleanhive")),
+    ("ipgrep", cfg!(feature = "ipgrep")),
+    ("ts2date", cfg!(feature = "ts2date")),
+    ("lnk2bodyfile", cfg!(feature = "lnk2bodyfile")),
+    ("pf2bodyfile", cfg!(feature = "pf2bodyfile")),
+    ("zip2bodyfile", cfg!(feature = "zip2bodyfile")),
+];
+
+/// every option which is listed by `--help` must be described in the man page
+#[test]
+fn every_binary_has_a_manpage_with_all_options() {
+    for (binary, _) in BINARIES.iter().filter(|(_, enabled)| *enabled) {
+        let dir = tempfile::tempdir().unwrap();
+        Command::cargo_bin(binary)
+            .unwrap()
+            .arg("--generate-manpage")
+            .arg(dir.path())
+            .assert()
+            .success();
+
+        let roff = std::fs::read_to_string(dir.path().join(format!("{binary}.1"))).unwrap();
+        assert!(roff.starts_with(".TH "), "invalid man page of {binary}");
+
+        let help = Command::cargo_bin(binary)
+            .unwrap()
+            .arg("--help")
+            .output()
+            .unwrap();
+        let help = String::from_utf8(help.stdout).unwrap();
+        for option in help.lines().filter_map(long_option) {
+            assert!(
+                roff.contains(&format!("\\fB\\-\\-{}\\fR", option.replace('-', "\\-"))),
+                "the man page of {binary} does not contain '--{option}'"
+            );
+        }
+    }
+}
+
+/// the name of the long option which is described in this line of `--help`
+fn long_option(line: &str) -> Option<&str> {
+    let line = line.trim_start();
+    if !line.starts_with('-') {
+        return None;
+    }
+    let option = line
+        .split_whitespace()
+        .find(|word| word.starts_with("--"))?;
+    let option = option.trim_start_matches("--").trim_end_matches([',', '.']);
+    option.split('=').next()
+}
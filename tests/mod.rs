@@ -1,5 +1,33 @@
+mod common;
+mod autocomplete;
+mod manpage;
+#[cfg(feature = "mactime2")]
 mod mactime2;
+#[cfg(feature = "ts2date")]
 mod ts2date;
+#[cfg(feature = "lnk2bodyfile")]
 mod lnk2bodyfile;
+#[cfg(feature = "zip2bodyfile")]
 mod zip2bodyfile;
+#[cfg(feature = "pf2bodyfile")]
+mod pf2bodyfile;
+#[cfg(feature = "hivescan")]
 mod hivescan;
+#[cfg(feature = "evtxls")]
+mod evtxls;
+#[cfg(feature = "evtxcat")]
+mod evtxcat;
+#[cfg(feature = "evtxscan")]
+mod evtxscan;
+#[cfg(feature = "evtx2bodyfile")]
+mod evtx2bodyfile;
+#[cfg(feature = "evtxanalyze")]
+mod evtxanalyze;
+#[cfg(feature = "regdump")]
+mod regdump;
+#[cfg(feature = "cleanhive")]
+mod cleanhive;
+#[cfg(feature = "pol_export")]
+mod pol_export;
+#[cfg(feature = "es4forensics")]
+mod es4forensics;
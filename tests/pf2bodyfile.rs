@@ -0,0 +1,351 @@
+#![cfg(feature = "pf2bodyfile")]
+
+use std::path::PathBuf;
+
+use assert_cmd::Command;
+use dfir_toolkit::common::bodyfile::{BehavesLikeI64, Bodyfile3Line};
+use serde_json::Value;
+
+// this file is also a module of `tests/mod.rs`, which loads `common` as well
+#[path = "common/mod.rs"]
+#[allow(clippy::duplicate_mod)]
+mod common;
+use common::put_u32;
+
+fn run_pf2bodyfile(args: &[&str]) -> String {
+    let mut cmd = Command::cargo_bin("pf2bodyfile").unwrap();
+    let mut data_path = PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap());
+    data_path.push("tests");
+    data_path.push("data");
+    data_path.push("scca");
+    data_path.push("WMIADAP.EXE-F8DFDFA2.pf");
+
+    let result = cmd.args(args).arg(data_path).ok();
+    assert!(result.is_ok());
+
+    String::from_utf8(result.unwrap().stdout).unwrap()
+}
+
+fn bodyfile_lines(args: &[&str]) -> Vec<Bodyfile3Line> {
+    run_pf2bodyfile(args)
+        .lines()
+        .map(|line| Bodyfile3Line::try_from(line).unwrap())
+        .collect()
+}
+
+#[test]
+fn test_executable_only() {
+    let lines = bodyfile_lines(&[]);
+    assert_eq!(lines.len(), 1);
+    assert_eq!(
+        lines[0].get_name(),
+        "Prefetch: run 'WMIADAP.EXE' (run 48 times, hash F8DFDFA2, read from 'WMIADAP.EXE-F8DFDFA2.pf')"
+    );
+    assert_eq!(lines[0].get_atime().as_ref(), Some(&1392907079));
+}
+
+#[test]
+fn test_include_references() {
+    let lines = bodyfile_lines(&["--include-references"]);
+    assert_eq!(
+        lines[0].get_name(),
+        "Prefetch: run 'WMIADAP.EXE' (run 48 times, hash F8DFDFA2, read from 'WMIADAP.EXE-F8DFDFA2.pf')"
+    );
+
+    let references = &lines[1..];
+    assert_eq!(references.len(), 40);
+    assert!(references
+        .iter()
+        .all(|line| line.get_name().contains(" referenced by 'WMIADAP.EXE' ")));
+    assert!(references
+        .iter()
+        .all(|line| line.get_atime().as_ref() == Some(&1392907079)));
+    assert!(references.iter().any(|line| line.get_name()
+        == r"Prefetch: file '\DEVICE\HARDDISKVOLUME2\WINDOWS\SYSTEM32\NTDLL.DLL' referenced by 'WMIADAP.EXE' (read from 'WMIADAP.EXE-F8DFDFA2.pf')"));
+    assert!(references.iter().any(|line| line.get_name()
+        == r"Prefetch: directory '\DEVICE\HARDDISKVOLUME2\WINDOWS\SYSTEM32\WBEM' referenced by 'WMIADAP.EXE' (read from 'WMIADAP.EXE-F8DFDFA2.pf')"));
+}
+
+#[test]
+fn test_json() {
+    let stdout = run_pf2bodyfile(&["--format", "json"]);
+    let mut lines = stdout.lines();
+    let info: Value = serde_json::from_str(lines.next().unwrap()).unwrap();
+    assert!(lines.next().is_none());
+
+    assert_eq!(info["prefetch_file"], "WMIADAP.EXE-F8DFDFA2.pf");
+    assert_eq!(info["executable"], "WMIADAP.EXE");
+    assert_eq!(info["prefetch_hash"], "F8DFDFA2");
+    assert_eq!(info["version"], 23);
+    assert_eq!(info["run_count"], 48);
+    assert_eq!(
+        info["last_run_times"],
+        Value::from(vec!["2014-02-20T14:37:59.678286Z"])
+    );
+    assert!(info.get("referenced_files").is_none());
+
+    let stdout = run_pf2bodyfile(&["--format", "json", "--include-references"]);
+    let info: Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(info["referenced_files"].as_array().unwrap().len(), 35);
+    assert_eq!(info["referenced_directories"].as_array().unwrap().len(), 5);
+}
+
+fn utf16(text: &str) -> Vec<u8> {
+    text.encode_utf16().flat_map(u16::to_le_bytes).collect()
+}
+
+/// creates a prefetch file with the layout of version 30 (Windows 10 and 11)
+/// for 'TEST.EXE', which references one file and one directory
+fn prefetch_file(version: u32, last_run_times: &[u64]) -> Vec<u8> {
+    const METRICS_OFFSET: usize = 296;
+    const STRINGS_OFFSET: usize = METRICS_OFFSET + 32;
+
+    let mut filename_strings = utf16(r"\VOLUME{01d5a0a0a0a0a0a0-0123abcd}\TEST\TEST.EXE");
+    let filename_length = filename_strings.len() / 2;
+    filename_strings.extend([0; 2]);
+    filename_strings.resize(filename_strings.len().div_ceil(8) * 8, 0);
+
+    let device_path = utf16(r"\VOLUME{01d5a0a0a0a0a0a0-0123abcd}");
+    let directory = r"\VOLUME{01d5a0a0a0a0a0a0-0123abcd}\TEST";
+    let mut volume = vec![0; 96];
+    put_u32(&mut volume, 0, 96);
+    put_u32(&mut volume, 4, (device_path.len() / 2) as u32);
+    volume.extend(device_path);
+    volume.extend([0; 2]);
+    let file_references_offset = volume.len() as u32;
+    put_u32(&mut volume, 20, file_references_offset);
+    put_u32(&mut volume, 24, 16);
+    volume.extend([0; 16]);
+    let directory_strings_offset = volume.len() as u32;
+    put_u32(&mut volume, 28, directory_strings_offset);
+    put_u32(&mut volume, 32, 1);
+    volume.extend((directory.len() as u16).to_le_bytes());
+    volume.extend(utf16(directory));
+    volume.extend([0; 4]);
+
+    let volume_offset = STRINGS_OFFSET + filename_strings.len();
+    let mut data = vec![0; METRICS_OFFSET + 32];
+    put_u32(&mut data, 0, version);
+    data[4..8].copy_from_slice(b"SCCA");
+    put_u32(&mut data, 8, 17);
+    put_u32(&mut data, 12, (volume_offset + volume.len()) as u32);
+    let name = utf16("TEST.EXE");
+    data[16..16 + name.len()].copy_from_slice(&name);
+    put_u32(&mut data, 76, 0x0123abcd);
+
+    put_u32(&mut data, 84, METRICS_OFFSET as u32);
+    put_u32(&mut data, 88, 1);
+    put_u32(&mut data, 92, STRINGS_OFFSET as u32);
+    put_u32(&mut data, 100, STRINGS_OFFSET as u32);
+    put_u32(&mut data, 104, filename_strings.len() as u32);
+    put_u32(&mut data, 108, volume_offset as u32);
+    put_u32(&mut data, 112, 1);
+    put_u32(&mut data, 116, volume.len() as u32);
+    for (index, time) in last_run_times.iter().enumerate() {
+        let offset = 128 + index * 8;
+        data[offset..offset + 8].copy_from_slice(&time.to_le_bytes());
+    }
+    put_u32(&mut data, 200, 12);
+
+    put_u32(&mut data, METRICS_OFFSET + 16, filename_length as u32);
+    data.extend(filename_strings);
+    data.extend(volume);
+    data
+}
+
+/// compresses a file like Windows 10 and 11 do, using the Xpress Huffman
+/// algorithm. All symbols have a code length of 9, so that the code of every
+/// symbol is the symbol itself. Repeated sequences of up to 17 bytes are
+/// encoded as matches, longer ones would require additional length bytes
+fn mam_compressed(data: &[u8]) -> Vec<u8> {
+    assert!(data.len() <= 65536);
+    let mut compressed = b"MAM\x04".to_vec();
+    compressed.extend((data.len() as u32).to_le_bytes());
+    compressed.extend([0x99; 256]);
+
+    let mut writer = BitWriter::default();
+    let mut position = 0;
+    while position < data.len() {
+        match longest_match(data, position) {
+            Some((offset, length)) => {
+                let offset_bits = offset.ilog2();
+                writer.write(256 + (offset_bits << 4) + (length as u32 - 3), 9);
+                writer.write(offset as u32 - (1 << offset_bits), offset_bits);
+                position += length;
+            }
+            None => {
+                writer.write(data[position].into(), 9);
+                position += 1;
+            }
+        }
+    }
+    compressed.extend(writer.finish());
+    compressed
+}
+
+/// returns the offset and the length of the longest previous occurrence of
+/// the bytes at `position`, if it is at least 3 bytes long
+fn longest_match(data: &[u8], position: usize) -> Option<(usize, usize)> {
+    (1..=position.min(8192))
+        .map(|offset| {
+            let length = (0..17.min(data.len() - position))
+                .take_while(|&i| data[position + i] == data[position + i - offset])
+                .count();
+            (offset, length)
+        })
+        .max_by_key(|&(offset, length)| (length, std::cmp::Reverse(offset)))
+        .filter(|&(_, length)| length >= 3)
+}
+
+/// writes the bits of an Xpress Huffman stream, which are read in 16 bit
+/// little endian words, beginning with the most significant bit
+#[derive(Default)]
+struct BitWriter {
+    data: Vec<u8>,
+    word: u16,
+    bits: u32,
+}
+
+impl BitWriter {
+    fn write(&mut self, value: u32, bits: u32) {
+        for bit in (0..bits).rev() {
+            self.word = (self.word << 1) | ((value >> bit) & 1) as u16;
+            self.bits += 1;
+            if self.bits == 16 {
+                self.data.extend(self.word.to_le_bytes());
+                self.word = 0;
+                self.bits = 0;
+            }
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.bits > 0 {
+            self.write(0, 16 - self.bits);
+        }
+        self.data.extend([0; 8]);
+        self.data
+    }
+}
+
+fn run_pf2bodyfile_on(files: &[(&str, Vec<u8>)], args: &[&str]) -> (String, String) {
+    let dir = tempfile::tempdir().unwrap();
+    let mut cmd = Command::cargo_bin("pf2bodyfile").unwrap();
+    cmd.args(args);
+    for (name, data) in files {
+        std::fs::write(dir.path().join(name), data).unwrap();
+        cmd.arg(dir.path().join(name));
+    }
+
+    let output = cmd.ok().unwrap();
+    (
+        String::from_utf8(output.stdout).unwrap(),
+        String::from_utf8(output.stderr).unwrap(),
+    )
+}
+
+#[test]
+fn test_version_30() {
+    // FILETIMEs of 2024-01-17T21:20:00Z and 2023-09-24T03:33:20Z
+    let run_times = [
+        133_500_000_000_000_000,
+        133_500_000_000_000_000,
+        133_400_000_000_000_000,
+    ];
+    let (stdout, _) = run_pf2bodyfile_on(
+        &[("TEST.EXE-0123ABCD.pf", prefetch_file(30, &run_times))],
+        &["--include-references"],
+    );
+    let lines: Vec<_> = stdout
+        .lines()
+        .map(|line| Bodyfile3Line::try_from(line).unwrap())
+        .collect();
+
+    // duplicate and unused run times are omitted
+    assert_eq!(lines.len(), 4);
+    assert_eq!(
+        lines[0].get_name(),
+        "Prefetch: run 'TEST.EXE' (run 12 times, hash 0123ABCD, read from 'TEST.EXE-0123ABCD.pf')"
+    );
+    assert_eq!(lines[0].get_atime().as_ref(), Some(&1705526400));
+    assert_eq!(lines[1].get_atime().as_ref(), Some(&1695526400));
+    assert_eq!(
+        lines[2].get_name(),
+        r"Prefetch: file '\VOLUME{01d5a0a0a0a0a0a0-0123abcd}\TEST\TEST.EXE' referenced by 'TEST.EXE' (read from 'TEST.EXE-0123ABCD.pf')"
+    );
+    assert_eq!(lines[2].get_atime().as_ref(), Some(&1705526400));
+    assert_eq!(
+        lines[3].get_name(),
+        r"Prefetch: directory '\VOLUME{01d5a0a0a0a0a0a0-0123abcd}\TEST' referenced by 'TEST.EXE' (read from 'TEST.EXE-0123ABCD.pf')"
+    );
+}
+
+#[test]
+fn test_compressed_version_31() {
+    let run_times = [133_500_000_000_000_000];
+    let data = prefetch_file(31, &run_times);
+    let compressed = mam_compressed(&data);
+
+    // the repeated volume names and zeros are encoded as matches, without
+    // which every byte would need 9 bits
+    assert!(compressed.len() < data.len());
+
+    let (stdout, stderr) = run_pf2bodyfile_on(
+        &[("TEST.EXE-0123ABCD.pf", compressed)],
+        &["--format", "json", "--include-references"],
+    );
+    assert!(stderr.is_empty());
+
+    let info: Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(info["executable"], "TEST.EXE");
+    assert_eq!(info["version"], 31);
+    assert_eq!(info["run_count"], 12);
+    assert_eq!(
+        info["last_run_times"],
+        Value::from(vec!["2024-01-17T21:20:00Z"])
+    );
+    assert_eq!(
+        info["referenced_directories"],
+        Value::from(vec![r"\VOLUME{01d5a0a0a0a0a0a0-0123abcd}\TEST"])
+    );
+}
+
+#[test]
+fn test_unsupported_version() {
+    let run_times = [133_500_000_000_000_000];
+    let (stdout, stderr) = run_pf2bodyfile_on(
+        &[
+            (
+                "NEW.EXE-0123ABCD.pf",
+                mam_compressed(&prefetch_file(32, &run_times)),
+            ),
+            ("TEST.EXE-0123ABCD.pf", prefetch_file(30, &run_times)),
+        ],
+        &[],
+    );
+
+    // the file with the unknown version is reported, but does not prevent
+    // the other file from being read
+    assert!(stderr.contains(
+        "warning: unable to read 'NEW.EXE-0123ABCD.pf': unsupported prefetch version 32"
+    ));
+    assert_eq!(stdout.lines().count(), 1);
+    assert!(stdout.contains("Prefetch: run 'TEST.EXE'"));
+}
+
+#[test]
+fn test_quiet_hides_warnings() {
+    let run_times = [133_500_000_000_000_000];
+    let (stdout, stderr) = run_pf2bodyfile_on(
+        &[
+            (
+                "NEW.EXE-0123ABCD.pf",
+                mam_compressed(&prefetch_file(32, &run_times)),
+            ),
+            ("TEST.EXE-0123ABCD.pf", prefetch_file(30, &run_times)),
+        ],
+        &["-q"],
+    );
+    assert!(stderr.is_empty());
+    assert_eq!(stdout.lines().count(), 1);
+}
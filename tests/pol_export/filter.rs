@@ -0,0 +1,76 @@
+use serde_json::Value;
+
+use super::{export, export_json};
+
+const DEFENDER: &str = "Software\\Policies\\Microsoft\\Windows Defender";
+
+fn value_names(entries: &[Value]) -> Vec<&str> {
+    entries
+        .iter()
+        .map(|entry| entry["value_name"].as_str().unwrap())
+        .collect()
+}
+
+#[test]
+fn filter_by_key_prefix() {
+    let expected = [
+        "DisableAntiSpyware",
+        "ServiceKeepAlive",
+        "DisableRealtimeMonitoring",
+        "DisableBlockAtFirstSeen",
+        "C:\\Temp",
+    ];
+
+    // the keys of 'Windows Defender Security Center' are not subkeys of
+    // 'Windows Defender', and keys are compared case insensitive
+    let entries = export_json("Defender.pol", &["--key-prefix", DEFENDER]);
+    assert_eq!(value_names(&entries), expected);
+
+    let entries = export_json(
+        "Defender.pol",
+        &[
+            "--key-prefix",
+            "software/policies/microsoft/windows defender/",
+        ],
+    );
+    assert_eq!(value_names(&entries), expected);
+
+    let csv = export("Defender.pol", &["--key-prefix", DEFENDER]);
+    let lines: Vec<_> = csv.lines().skip(1).collect();
+    assert_eq!(lines.len(), expected.len());
+    assert!(lines
+        .iter()
+        .zip(expected)
+        .all(|(line, name)| line.contains(&format!(",{name},"))));
+}
+
+#[test]
+fn filter_by_value_name() {
+    let entries = export_json(
+        "Defender.pol",
+        &["--value-name-regex", "^disable.*(monitoring|seen)$"],
+    );
+    assert_eq!(
+        value_names(&entries),
+        ["DisableRealtimeMonitoring", "DisableBlockAtFirstSeen"]
+    );
+
+    let csv = export(
+        "Defender.pol",
+        &["--value-name-regex", "^DISABLE", "--key-prefix", DEFENDER],
+    );
+    assert_eq!(
+        csv,
+        "key,value_name,value_type,value_data\n\
+         Software\\Policies\\Microsoft\\Windows Defender,DisableAntiSpyware,RegDWord,1\n\
+         Software\\Policies\\Microsoft\\Windows Defender\\Real-Time Protection,DisableRealtimeMonitoring,RegDWord,1\n\
+         SOFTWARE\\POLICIES\\MICROSOFT\\WINDOWS DEFENDER\\SPYNET,DisableBlockAtFirstSeen,RegDWord,1\n"
+    );
+}
+
+#[test]
+fn omit_unreadable_entries_when_filtering() {
+    let entries = export_json("Registry.pol", &["--value-name-regex", "."]);
+    assert_eq!(entries.len(), 10);
+    assert!(entries.iter().all(|entry| entry.get("offset").is_none()));
+}
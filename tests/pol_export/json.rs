@@ -0,0 +1,59 @@
+use serde_json::{json, Value};
+
+use super::export_json;
+
+fn entry<'e>(entries: &'e [Value], value_name: &str) -> &'e Value {
+    entries
+        .iter()
+        .find(|entry| entry["value_name"] == value_name)
+        .unwrap()
+}
+
+#[test]
+fn export_typed_values() {
+    let entries = export_json("Registry.pol", &[]);
+    assert_eq!(entries.len(), 11);
+    assert_eq!(
+        entries[0],
+        json!({
+            "key": "Software\\Policies\\Microsoft\\Windows\\WindowsUpdate\\AU",
+            "value_name": "NoAutoUpdate",
+            "type": "REG_DWORD",
+            "data": 1
+        })
+    );
+    assert_eq!(
+        entry(&entries, "WUServer")["data"],
+        "http://wsus.example.com:8530"
+    );
+    assert_eq!(entry(&entries, "LogonScript")["type"], "REG_EXPAND_SZ");
+    assert_eq!(
+        entry(&entries, "TrustedHosts")["data"],
+        json!(["host1", "host2"])
+    );
+    assert_eq!(entry(&entries, "Blob")["data"], "3q2+7w==");
+    assert_eq!(entry(&entries, "Quota")["data"], 0x1_0000_0000u64);
+}
+
+#[test]
+fn include_malformed_entries() {
+    let entries = export_json("Registry.pol", &[]);
+
+    let broken = entry(&entries, "Broken");
+    assert_eq!(broken["type"], "REG_DWORD");
+    assert!(broken.get("data").is_none());
+    assert!(broken["error"]
+        .as_str()
+        .unwrap()
+        .contains("RegDWord must be exactly 4 bytes large"));
+
+    let unknown = entry(&entries, "Unknown");
+    assert_eq!(unknown["type"], "0x00000042");
+    assert_eq!(unknown["error"], "unknown registry type 0x00000042");
+
+    // the entry without separator cannot be read, but the next entry can
+    assert_eq!(entries[9]["offset"], 1336);
+    assert!(entries[9].get("key").is_none());
+    assert!(entries[9]["error"].is_string());
+    assert_eq!(entries[10]["value_name"], "**del.Legacy");
+}
@@ -0,0 +1,35 @@
+use std::path::PathBuf;
+
+use assert_cmd::Command;
+use serde_json::Value;
+
+mod filter;
+mod json;
+
+/// `Registry.pol` contains an entry of every common type, an entry whose
+/// data does not match its type, an entry of an unknown type and an entry
+/// with a missing separator. `Defender.pol` contains policies of Windows
+/// Defender and of similarly named keys
+pub(crate) fn polfile(name: &str) -> PathBuf {
+    let mut data_path = PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap());
+    data_path.push("tests");
+    data_path.push("data");
+    data_path.push("pol_export");
+    data_path.push(name);
+    data_path
+}
+
+pub(crate) fn export(name: &str, args: &[&str]) -> String {
+    let result = Command::cargo_bin("pol_export")
+        .unwrap()
+        .args(args)
+        .arg(polfile(name))
+        .ok()
+        .unwrap();
+    String::from_utf8(result.stdout).unwrap()
+}
+
+pub(crate) fn export_json(name: &str, args: &[&str]) -> Vec<Value> {
+    let output = export(name, &[&["--format", "json"], args].concat());
+    serde_json::from_str(&output).unwrap()
+}
@@ -0,0 +1,79 @@
+use base64::{engine::general_purpose, Engine};
+use serde_json::Value;
+
+use super::{data_file, regdump_file, value};
+
+/// a SYSTEM hive whose AppCompatCache and the values of the key `BigData`
+/// are stored in big data records
+fn bigdata(args: &[&str]) -> String {
+    regdump_file(args, data_file("regdump", "SYSTEM_bigdata"))
+}
+
+fn bigdata_key() -> Value {
+    let output = bigdata(&["--format", "jsonl"]);
+    let line = output
+        .lines()
+        .find(|line| line.contains("\"path\":\"ROOT\\\\BigData\""))
+        .unwrap();
+    serde_json::from_str(line).unwrap()
+}
+
+#[test]
+fn reassemble_big_data_as_json() {
+    let key = bigdata_key();
+
+    let binary = general_purpose::STANDARD
+        .decode(value(&key, "Binary")["data"].as_str().unwrap())
+        .unwrap();
+    assert_eq!(binary.len(), 70000);
+    assert!(binary
+        .iter()
+        .enumerate()
+        .all(|(i, b)| *b as usize == i % 251));
+
+    assert_eq!(value(&key, "String")["type"], "RegSZ");
+    assert_eq!(value(&key, "String")["data"], "0123456789".repeat(2000));
+
+    // the data fills one segment and a single byte of a second segment
+    let one_byte_more = general_purpose::STANDARD
+        .decode(value(&key, "OneByteMore")["data"].as_str().unwrap())
+        .unwrap();
+    assert_eq!(one_byte_more, vec![b'x'; 16345]);
+}
+
+#[test]
+fn reassemble_big_data_as_text() {
+    let output = bigdata(&["--path", "BigData", "--hide-timestamps"]);
+    let expected = format!("\"String\" = RegSZ:\"{}\"\n", "0123456789".repeat(2000));
+    assert!(output.contains(&expected));
+}
+
+#[test]
+fn reassemble_big_data_as_reg_file() {
+    let output = bigdata(&["--format", "reg", "--path", "BigData"]);
+    let bytes = output
+        .split("\"OneByteMore\"=hex:")
+        .nth(1)
+        .unwrap()
+        .split("\r\n\r\n")
+        .next()
+        .unwrap()
+        .matches("78")
+        .count();
+    assert_eq!(bytes, 16345);
+}
+
+#[test]
+fn decode_big_shimcache() {
+    let output = bigdata(&["--decode", "shimcache", "--format", "jsonl"]);
+    let entries: Vec<Value> = output
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(entries.len(), 800);
+    assert_eq!(
+        entries[799]["path"],
+        "C:\\Windows\\System32\\program_0799.exe"
+    );
+    assert_eq!(entries[799]["last_modified"], "2024-03-04T06:59:00Z");
+}
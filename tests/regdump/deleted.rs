@@ -0,0 +1,150 @@
+use assert_cmd::Command;
+use dfir_toolkit::common::bodyfile::{Bodyfile3Line, Modified};
+use serde_json::Value;
+
+use super::data_file;
+
+/// runs `regdump --deleted` for a hive which contains deleted keys and values
+fn deleted(args: &[&str]) -> String {
+    let mut cmd = Command::cargo_bin("regdump").unwrap();
+    let result = cmd
+        .arg("--deleted")
+        .args(args)
+        .arg(data_file("regdump", "SOFTWARE_deleted"))
+        .ok();
+    assert!(result.is_ok());
+    String::from_utf8(result.unwrap().stdout).unwrap()
+}
+
+fn subkey<'a>(key: &'a Value, name: &str) -> &'a Value {
+    key["subkeys"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|k| k["name"] == name)
+        .unwrap()
+}
+
+#[test]
+fn deleted_keys_as_text() {
+    let output = deleted(&[]);
+    assert!(output.contains(
+        "\n[ROOT\\Software\\Uninstalled]; 2024-03-05T10:00:00+00:00; deleted\n\
+         ; recovered from a free cell at offset 0x00000210, its contents may be inconsistent\n\
+         \"DisplayName\" = RegSZ:\"Evil Tool\"\n\
+         \"Count\" = RegDWord:0x00000003\n"
+    ));
+    assert!(output.contains(
+        "\n[ROOT\\Software\\Uninstalled\\Settings]; 2024-03-05T10:01:00+00:00; deleted\n"
+    ));
+    assert!(
+        output.contains("; 1 of 2 values could not be recovered\n\"A\" = RegDWord:0x00000001\n")
+    );
+
+    // existing keys are not marked
+    assert!(output.contains("\n[ROOT\\Software\\Vendor]; 2024-02-01T09:00:00+00:00\n"));
+}
+
+#[test]
+fn no_deleted_keys_by_default() {
+    let mut cmd = Command::cargo_bin("regdump").unwrap();
+    let result = cmd.arg(data_file("regdump", "SOFTWARE_deleted")).ok();
+    let output = String::from_utf8(result.unwrap().stdout).unwrap();
+    assert!(!output.contains("Uninstalled"));
+    assert!(!output.contains("deleted"));
+}
+
+#[test]
+fn deleted_keys_in_json_tree() {
+    let root: Value = serde_json::from_str(&deleted(&["--format", "json"])).unwrap();
+    let software = subkey(&root, "Software");
+    assert!(subkey(software, "Vendor").get("deleted").is_none());
+
+    let uninstalled = subkey(software, "Uninstalled");
+    assert_eq!(uninstalled["deleted"], true);
+    assert_eq!(uninstalled["last_written"], "2024-03-05T10:00:00Z");
+    assert_eq!(uninstalled["values"][0]["data"], "Evil Tool");
+    assert_eq!(subkey(uninstalled, "Settings")["deleted"], true);
+
+    let broken = subkey(software, "Broken");
+    assert_eq!(broken["values"].as_array().unwrap().len(), 1);
+    assert_eq!(broken["notes"][1], "1 of 2 values could not be recovered");
+}
+
+#[test]
+fn orphaned_keys_and_values() {
+    let root: Value = serde_json::from_str(&deleted(&["--format", "json"])).unwrap();
+    let orphaned = subkey(&root, "$Orphaned");
+    assert_eq!(orphaned["deleted"], true);
+    assert!(orphaned["last_written"].is_null());
+
+    let values: Vec<_> = orphaned["values"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|v| v["name"].as_str().unwrap())
+        .collect();
+    assert!(values.contains(&"InstallPath"));
+    assert_eq!(subkey(orphaned, "Lost")["values"][0]["data"], "orphan");
+}
+
+#[test]
+fn deleted_keys_as_bodyfile() {
+    let output = deleted(&["--format", "bodyfile"]);
+    let line = output
+        .lines()
+        .find(|line| line.contains("Uninstalled (deleted)"))
+        .unwrap();
+    let bf_line = Bodyfile3Line::try_from(line).unwrap();
+    assert_eq!(bf_line.get_name(), "ROOT\\Software\\Uninstalled (deleted)");
+    assert_eq!(*bf_line.get_mtime(), Modified::from(1709632800));
+}
+
+#[test]
+fn filter_deleted_keys() {
+    let output = deleted(&[
+        "--format",
+        "jsonl",
+        "--path",
+        "Software",
+        "--modified-after",
+        "2024-03-05T10:00:30Z",
+    ]);
+    let keys: Vec<Value> = output
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    let paths: Vec<_> = keys.iter().map(|k| k["path"].as_str().unwrap()).collect();
+    assert_eq!(
+        paths,
+        [
+            "ROOT\\Software\\Broken",
+            "ROOT\\Software\\Uninstalled\\Settings"
+        ]
+    );
+    assert!(keys.iter().all(|k| k["deleted"] == true));
+}
+
+#[test]
+fn decode_resident_data_of_deleted_values() {
+    let mut cmd = Command::cargo_bin("regdump").unwrap();
+    let result = cmd
+        .args(["--deleted", "--format", "jsonl"])
+        .arg(data_file("hivescan", "SOFTWARE_deleted_values"))
+        .ok();
+    assert!(result.is_ok());
+    let output = String::from_utf8(result.unwrap().stdout).unwrap();
+    let orphaned: Value = output
+        .lines()
+        .map(|line| serde_json::from_str::<Value>(line).unwrap())
+        .find(|k| k["name"] == "$Orphaned")
+        .unwrap();
+    let value = orphaned["values"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|v| v["name"] == "B")
+        .unwrap();
+    assert_eq!(value["type"], "RegSZ");
+    assert_eq!(value["data"], "b");
+}
@@ -0,0 +1,401 @@
+use std::path::PathBuf;
+
+use assert_cmd::Command;
+use dfir_toolkit::common::bodyfile::{Bodyfile3Line, Changed, Modified};
+use serde_json::Value;
+
+mod big_data;
+mod deleted;
+mod reg_export;
+mod search;
+mod security;
+mod shellbags;
+mod shimcache;
+mod transaction_logs;
+
+pub(crate) fn data_file(dir: &str, name: &str) -> PathBuf {
+    let mut data_path = PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap());
+    data_path.push("tests");
+    data_path.push("data");
+    data_path.push(dir);
+    data_path.push(name);
+    data_path
+}
+
+fn testhive() -> PathBuf {
+    data_file("hivescan", "testhive")
+}
+
+/// a small user hive which contains UserAssist entries
+fn ntuser() -> PathBuf {
+    data_file("regdump", "NTUSER.DAT")
+}
+
+fn regdump_file(args: &[&str], hive: PathBuf) -> String {
+    let mut cmd = Command::cargo_bin("regdump").unwrap();
+    let result = cmd.args(args).arg(hive).ok();
+    assert!(result.is_ok());
+    String::from_utf8(result.unwrap().stdout).unwrap()
+}
+
+fn regdump(args: &[&str]) -> String {
+    regdump_file(args, testhive())
+}
+
+fn value<'a>(key: &'a Value, name: &str) -> &'a Value {
+    key["values"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|v| v["name"] == name)
+        .unwrap()
+}
+
+#[test]
+fn json_tree() {
+    let root: Value = serde_json::from_str(&regdump(&["--format", "json"])).unwrap();
+    assert_eq!(root["name"], "ROOT");
+    assert_eq!(root["last_written"], "2021-07-28T16:33:34.361728Z");
+
+    let data_test = root["subkeys"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|k| k["name"] == "data-test")
+        .unwrap();
+    assert!(data_test["subkeys"].as_array().unwrap().is_empty());
+
+    let multi_sz = value(data_test, "reg-multi-sz");
+    assert_eq!(multi_sz["type"], "RegMultiSZ");
+    assert_eq!(multi_sz["data"][0], "multi-sz-test");
+    assert_eq!(multi_sz["data"][1], "line2");
+
+    let binary = value(data_test, "binary");
+    assert_eq!(binary["type"], "RegBinary");
+    assert_eq!(binary["data"], "AQIDBAU=");
+
+    assert_eq!(value(data_test, "dword")["data"], 42);
+    assert_eq!(value(data_test, "qword")["data"], u64::MAX);
+    assert_eq!(value(data_test, "reg-expand-sz")["data"], "sz-test");
+}
+
+#[test]
+fn json_lines() {
+    let output = regdump(&["--format", "jsonl"]);
+    let keys: Vec<Value> = output
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(keys.len(), regdump(&[]).matches("\n[").count());
+    assert_eq!(keys[0]["path"], "ROOT");
+
+    let data_test = keys
+        .iter()
+        .find(|k| k["path"] == "ROOT\\data-test")
+        .unwrap();
+    assert_eq!(data_test["name"], "data-test");
+    assert!(data_test.get("subkeys").is_none());
+    assert_eq!(value(data_test, "reg-sz")["data"], "sz-test");
+}
+
+#[test]
+fn bodyfile_conflicts_with_format() {
+    let mut cmd = Command::cargo_bin("regdump").unwrap();
+    cmd.args(["--bodyfile", "--format", "json"])
+        .arg(testhive())
+        .assert()
+        .failure();
+}
+
+#[test]
+fn bodyfile() {
+    let output = regdump(&["--format", "bodyfile"]);
+    assert_eq!(output, regdump(&["--bodyfile"]));
+
+    let lines: Vec<_> = output
+        .lines()
+        .map(|line| Bodyfile3Line::try_from(line).unwrap())
+        .collect();
+    assert_eq!(lines.len(), regdump(&[]).matches("\n[").count());
+    assert_eq!(lines[0].get_name(), "ROOT");
+    assert_eq!(*lines[0].get_mtime(), Modified::from(1627490014));
+    assert_eq!(*lines[0].get_ctime(), Changed::default());
+
+    let output = regdump(&["-F", "bodyfile", "--value-count", "-p", "data-test"]);
+    let line = Bodyfile3Line::try_from(output.trim_end()).unwrap();
+    assert_eq!(line.get_name(), "ROOT\\data-test (8 values)");
+}
+
+#[test]
+fn dump_subtrees_by_path() {
+    let output = regdump(&[
+        "--path",
+        "DATA-test",
+        "-p",
+        "\\subpath-test\\WITH-single-level-subkey",
+    ]);
+    let keys: Vec<_> = output.lines().filter(|l| l.starts_with('[')).collect();
+    assert_eq!(keys.len(), 3);
+    assert!(keys[0].starts_with("[ROOT\\data-test];"));
+    assert!(keys[1].starts_with("[ROOT\\subpath-test\\with-single-level-subkey];"));
+    assert!(keys[2].starts_with("[ROOT\\subpath-test\\with-single-level-subkey\\subkey];"));
+    assert!(output.contains("\"dword\" = RegDWord:0x0000002a"));
+}
+
+#[test]
+fn dump_subtrees_by_regex() {
+    let output = regdump(&[
+        "--path-regex",
+        "^SUBKEY-test\\\\key10[0-2]$",
+        "--format",
+        "jsonl",
+    ]);
+    let paths: Vec<_> = output
+        .lines()
+        .map(|line| serde_json::from_str::<Value>(line).unwrap()["path"].clone())
+        .collect();
+    assert_eq!(
+        paths,
+        vec![
+            "ROOT\\subkey-test\\Key100",
+            "ROOT\\subkey-test\\key101",
+            "ROOT\\subkey-test\\Key102"
+        ]
+    );
+}
+
+#[test]
+fn subtrees_as_json() {
+    let output = regdump(&[
+        "--path",
+        "subpath-test\\with-two-levels-of-subkeys",
+        "-F",
+        "json",
+    ]);
+    let subtrees: Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(subtrees.as_array().unwrap().len(), 1);
+    assert_eq!(
+        subtrees[0]["path"],
+        "ROOT\\subpath-test\\with-two-levels-of-subkeys"
+    );
+    assert_eq!(subtrees[0]["subkeys"][0]["name"], "subkey1");
+    assert_eq!(subtrees[0]["subkeys"][0]["subkeys"][0]["name"], "subkey2");
+}
+
+#[test]
+fn missing_paths() {
+    let mut cmd = Command::cargo_bin("regdump").unwrap();
+    let output = cmd
+        .args(["--path", "data-test", "--path", "no-such-key"])
+        .arg(testhive())
+        .assert()
+        .code(3)
+        .get_output()
+        .clone();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("error: there is no key 'no-such-key'"));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("[ROOT\\data-test]"));
+
+    let mut cmd = Command::cargo_bin("regdump").unwrap();
+    cmd.args(["--path-regex", "^no-such-key$"])
+        .arg(testhive())
+        .assert()
+        .code(3);
+}
+
+#[test]
+fn filter_by_timestamp() {
+    // the root key has been written after most of its subkeys
+    let output = regdump(&["--modified-before", "2021-07-28T16:33:34.3598Z"]);
+    let keys: Vec<_> = output.lines().filter(|l| l.starts_with('[')).collect();
+    assert!(!keys.iter().any(|k| k.starts_with("[ROOT];")));
+    assert!(keys.iter().any(|k| k.starts_with("[ROOT\\data-test];")));
+
+    let output = regdump(&[
+        "--modified-before",
+        "2021-07-28T16:33:34.3598Z",
+        "--with-ancestors",
+    ]);
+    let keys: Vec<_> = output.lines().filter(|l| l.starts_with('[')).collect();
+    assert!(keys[0].starts_with("[ROOT];"));
+    assert!(keys[1..].iter().all(|k| !k.starts_with("[ROOT];")));
+
+    let output = regdump(&["--modified-after", "2021-07-28T16:33:34.3598Z"]);
+    assert!(!output.contains("[ROOT\\data-test]"));
+}
+
+#[test]
+fn reject_invalid_timestamp() {
+    for option in ["--modified-after", "--modified-before"] {
+        let mut cmd = Command::cargo_bin("regdump").unwrap();
+        let assert = cmd.args([option, "yesterday"]).arg(testhive()).assert();
+        let stderr = String::from_utf8_lossy(&assert.get_output().stderr).to_string();
+        assert.code(2);
+        assert!(stderr.contains("invalid timestamp: 'yesterday'"));
+    }
+}
+
+#[test]
+fn filter_by_timestamp_as_json() {
+    let args = ["--modified-before", "2021-07-28T16:33:34.3598Z", "-F"];
+    let without_ancestors = regdump(&[&args[..], &["jsonl"]].concat());
+    let with_ancestors = regdump(&[&args[..], &["jsonl", "--with-ancestors"]].concat());
+    assert_eq!(
+        without_ancestors.lines().count() + 2,
+        with_ancestors.lines().count()
+    );
+
+    let keys: Value = serde_json::from_str(&regdump(&[&args[..], &["json"]].concat())).unwrap();
+    let keys = keys.as_array().unwrap();
+    assert_eq!(keys.len(), without_ancestors.lines().count());
+    assert!(keys.iter().any(|k| k["path"] == "ROOT\\data-test"));
+    assert!(keys.iter().all(|k| k.get("subkeys").is_none()));
+
+    let trees: Value = serde_json::from_str(&regdump(
+        &[&args[..], &["json", "--with-ancestors"]].concat(),
+    ))
+    .unwrap();
+    assert_eq!(trees.as_array().unwrap().len(), 1);
+    assert_eq!(trees[0]["path"], "ROOT");
+}
+
+#[test]
+fn filter_subtrees_by_timestamp() {
+    let output = regdump(&[
+        "--path",
+        "subkey-test",
+        "--path",
+        "data-test",
+        "--modified-after",
+        "2021-07-28T16:33:34.3610Z",
+    ]);
+    let keys: Vec<_> = output.lines().filter(|l| l.starts_with('[')).collect();
+    assert!(keys[0].starts_with("[ROOT\\subkey-test];"));
+    assert!(keys.iter().all(|k| k.starts_with("[ROOT\\subkey-test")));
+    assert!(keys
+        .iter()
+        .any(|k| k.starts_with("[ROOT\\subkey-test\\key479];")));
+}
+
+const USERASSIST_KEY: &str = "Software\\Microsoft\\Windows\\CurrentVersion\\Explorer\\UserAssist";
+const CMD_ENTRY: &str = "\"{1AC14E77-02E7-4E5D-B744-2EB1AE5198B7}\\cmd.exe\" = UserAssist:run count: 7, focus count: 3, focus time: 1m23s, last executed: 2024-03-04T07:05:00+00:00";
+
+#[test]
+fn decode_userassist_of_selected_keys() {
+    let output = regdump_file(&["--path", USERASSIST_KEY], ntuser());
+    assert!(output.contains(CMD_ENTRY));
+    assert!(output.contains("\"Microsoft.Windows.Explorer\" = UserAssist:run count: 2, focus count: 14, focus time: 10m, last executed: never"));
+
+    // neither the session information nor malformed entries are decoded
+    assert!(output.contains("\"HRZR_PGYFRFFVBA\" = RegBinary:"));
+    assert!(output.contains(
+        "\"P:\\Hfref\\obo\\Qrfxgbc\\rivy.rkr\" = RegBinary:[0, 0, 0, 0, 0, 0, 0, 0, 0, 0]"
+    ));
+
+    let output = regdump_file(&["--path", USERASSIST_KEY, "--raw"], ntuser());
+    assert!(!output.contains("UserAssist:"));
+    assert!(output.contains("\"{1NP14R77-02R7-4R5Q-O744-2RO1NR5198O7}\\pzq.rkr\" = RegBinary:"));
+}
+
+#[test]
+fn decode_userassist_of_whole_hive() {
+    assert!(!regdump_file(&[], ntuser()).contains("UserAssist:"));
+    assert!(regdump_file(&["--decode", "userassist"], ntuser()).contains(CMD_ENTRY));
+
+    let mut cmd = Command::cargo_bin("regdump").unwrap();
+    cmd.args(["--decode", "userassist", "--raw"])
+        .arg(ntuser())
+        .assert()
+        .failure();
+}
+
+#[test]
+fn display_in_timezone() {
+    let output = regdump_file(
+        &["--path", USERASSIST_KEY, "--timezone", "Europe/Berlin"],
+        ntuser(),
+    );
+    assert!(output.contains("last executed: 2024-03-04T08:05:00+01:00"));
+
+    let output = regdump(&["--timezone", "+05:30"]);
+    assert!(output.contains("[ROOT]; 2021-07-28T22:03:34.361728+05:30"));
+}
+
+#[test]
+fn decode_userassist_as_json() {
+    let output = regdump_file(&["--decode", "userassist", "-F", "jsonl"], ntuser());
+    let count = output
+        .lines()
+        .map(|line| serde_json::from_str::<Value>(line).unwrap())
+        .find(|k| k["name"] == "Count")
+        .unwrap();
+    let cmd = value(&count, "{1AC14E77-02E7-4E5D-B744-2EB1AE5198B7}\\cmd.exe");
+    assert_eq!(cmd["type"], "UserAssist");
+    assert_eq!(cmd["data"]["run_count"], 7);
+    assert_eq!(cmd["data"]["focus_time"], 83500);
+    assert_eq!(cmd["data"]["last_executed"], "2024-03-04T07:05:00Z");
+    assert_eq!(
+        value(&count, "P:\\Hfref\\obo\\Qrfxgbc\\rivy.rkr")["type"],
+        "RegBinary"
+    );
+
+    let subtrees: Value = serde_json::from_str(&regdump_file(
+        &["-p", USERASSIST_KEY, "-F", "json"],
+        ntuser(),
+    ))
+    .unwrap();
+    let count = &subtrees[0]["subkeys"][0]["subkeys"][0];
+    assert_eq!(count["name"], "Count");
+    assert_eq!(
+        value(count, "Microsoft.Windows.Explorer")["data"]["run_count"],
+        2
+    );
+}
+
+#[test]
+fn warn_about_malformed_userassist_entries() {
+    let mut cmd = Command::cargo_bin("regdump").unwrap();
+    let output = cmd
+        .args(["-v", "--path", USERASSIST_KEY])
+        .arg(ntuser())
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("unable to decode UserAssist entry of 'C:\\Users\\bob\\Desktop\\evil.exe'")
+    );
+}
+
+#[test]
+fn hexdump_of_binary_values() {
+    let output = regdump(&["--hexdump", "-p", "data-test"]);
+    assert!(output.contains(
+        "\"binary\" = RegBinary:\n    00000000  01 02 03 04 05                                    |.....|\n"
+    ));
+    assert!(output.contains("\"dword\" = RegDWord:0x0000002a"));
+
+    let output = regdump(&["--hexdump", "--hexdump-limit", "32", "-p", "big-data-test"]);
+    let lines: Vec<_> = output
+        .lines()
+        .skip_while(|l| !l.starts_with("\"A\" = RegBinary:"))
+        .skip(1)
+        .take_while(|l| l.starts_with("    "))
+        .collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[1].starts_with("    00000010  41 41"));
+    assert!(lines[2].starts_with("    \u{2026} ") && lines[2].ends_with(" more bytes"));
+
+    // JSON is not affected
+    let output = regdump(&["--hexdump", "-p", "data-test", "-F", "json"]);
+    let subtrees: Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(value(&subtrees[0], "binary")["data"], "AQIDBAU=");
+
+    let mut cmd = Command::cargo_bin("regdump").unwrap();
+    cmd.args(["--hexdump-limit", "32"])
+        .arg(testhive())
+        .assert()
+        .failure();
+}
@@ -0,0 +1,47 @@
+use assert_cmd::Command;
+
+use super::{regdump, testhive};
+
+#[test]
+fn export_as_reg_file() {
+    let output = regdump(&["--format", "reg", "--path", "data-test"]);
+    assert!(output.starts_with("Windows Registry Editor Version 5.00\r\n"));
+    assert!(output.contains("\r\n[HKEY_LOCAL_MACHINE\\TESTHIVE\\data-test]\r\n"));
+    assert!(output.contains("\r\n\"dword\"=dword:0000002a\r\n"));
+    assert!(output.contains("\r\n\"binary\"=hex:01,02,03,04,05\r\n"));
+    assert!(output.contains("\r\n\"qword\"=hex(b):ff,ff,ff,ff,ff,ff,ff,ff\r\n"));
+    assert!(!output.contains("\r\n[HKEY_LOCAL_MACHINE\\TESTHIVE]\r\n"));
+}
+
+#[test]
+fn replace_root_key() {
+    let output = regdump(&[
+        "--format",
+        "reg",
+        "--reg-root",
+        "HKEY_CURRENT_USER\\Imported",
+    ]);
+    assert!(output.contains("\r\n[HKEY_CURRENT_USER\\Imported]\r\n"));
+    assert!(output.contains("\r\n[HKEY_CURRENT_USER\\Imported\\data-test]\r\n"));
+    assert!(!output.contains("TESTHIVE"));
+}
+
+#[test]
+fn export_as_utf16() {
+    let mut cmd = Command::cargo_bin("regdump").unwrap();
+    let output = cmd
+        .args(["--format", "reg", "--utf16"])
+        .arg(testhive())
+        .ok()
+        .unwrap()
+        .stdout;
+    assert_eq!(&output[..2], &[0xff, 0xfe]);
+
+    let chars: Vec<u16> = output[2..]
+        .chunks_exact(2)
+        .map(|c| u16::from_le_bytes([c[0], c[1]]))
+        .collect();
+    let text = String::from_utf16(&chars).unwrap();
+    assert!(text.starts_with("Windows Registry Editor Version 5.00\r\n"));
+    assert!(text.contains("[HKEY_LOCAL_MACHINE\\TESTHIVE\\data-test]"));
+}
@@ -0,0 +1,78 @@
+use serde_json::Value;
+
+use super::{data_file, regdump, regdump_file};
+
+fn search_jsonl(output: &str) -> Vec<Value> {
+    output
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+#[test]
+fn search_names_and_strings() {
+    let hits = search_jsonl(&regdump(&["--search", "(?i)^REG-SZ$|line2", "-F", "jsonl"]));
+    assert_eq!(hits.len(), 2);
+
+    assert_eq!(hits[0]["path"], "ROOT\\data-test");
+    assert_eq!(hits[0]["found_in"], "value_name");
+    assert_eq!(hits[0]["value"], "reg-sz");
+    assert_eq!(hits[0]["value_type"], "RegSZ");
+    assert_eq!(hits[0]["last_written"], "2021-07-28T16:33:34.359783Z");
+
+    assert_eq!(hits[1]["found_in"], "value_data");
+    assert_eq!(hits[1]["value"], "reg-multi-sz");
+    assert_eq!(hits[1]["match"], "line2");
+}
+
+#[test]
+fn search_case_sensitive() {
+    let output = regdump(&["--search", "DATA-TEST", "-F", "jsonl"]);
+    assert!(output.is_empty());
+}
+
+#[test]
+fn search_selected_keys() {
+    let hits = search_jsonl(&regdump(&[
+        "--search",
+        "test",
+        "--path",
+        "subkey-test",
+        "-F",
+        "jsonl",
+    ]));
+    assert!(!hits.is_empty());
+    assert!(hits.iter().all(|hit| hit["path"]
+        .as_str()
+        .unwrap()
+        .starts_with("ROOT\\subkey-test")));
+}
+
+#[test]
+fn search_binary_values() {
+    let hive = data_file("regdump", "UsrClass.dat");
+    assert!(regdump_file(&["--search", "Users", "-F", "jsonl"], hive.clone()).is_empty());
+
+    let hits = search_jsonl(&regdump_file(
+        &["--search", "Users", "--search-binary", "-F", "jsonl"],
+        hive,
+    ));
+    let utf16 = hits
+        .iter()
+        .find(|hit| hit["found_in"] == "binary_utf16")
+        .unwrap();
+    assert_eq!(utf16["value_type"], "RegBinary");
+    assert_eq!(utf16["match"], "Users");
+    assert!(utf16["offset"].is_u64());
+    assert!(hits.iter().any(|hit| hit["found_in"] == "binary_ascii"));
+}
+
+#[test]
+fn search_as_text() {
+    let output = regdump(&["--search", "line2"]);
+    let lines: Vec<_> = output.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with("LAST WRITTEN"));
+    assert!(lines[1].contains("value data"));
+    assert!(lines[1].ends_with("ROOT\\data-test"));
+}
@@ -0,0 +1,66 @@
+use serde_json::Value;
+
+use super::{regdump, regdump_file, testhive};
+
+#[test]
+fn display_security_descriptors() {
+    let output = regdump(&["--security", "--path", "data-test"]);
+    let lines: Vec<_> = output.lines().collect();
+    assert_eq!(
+        lines[1],
+        "[ROOT\\data-test]; 2021-07-28T16:33:34.359783+00:00"
+    );
+    assert_eq!(lines[2], "; owner: S-1-5-32-544 (BUILTIN\\Administrators)");
+    assert_eq!(lines[3], "; group: S-1-5-32-544 (BUILTIN\\Administrators)");
+    assert_eq!(
+        lines[4],
+        "; allow S-1-5-18 (NT AUTHORITY\\SYSTEM): KEY_ALL_ACCESS (container inherit)"
+    );
+    assert!(lines.contains(&"; allow S-1-1-0 (Everyone): KEY_READ (container inherit)"));
+
+    let output = regdump(&["--path", "data-test"]);
+    assert!(!output.contains("; owner:"));
+}
+
+#[test]
+fn security_descriptors_as_json() {
+    let output = regdump(&["--security", "--format", "jsonl"]);
+    let keys: Vec<Value> = output
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert!(keys.iter().all(|key| key["security"].is_object()));
+
+    let security = &keys[0]["security"];
+    assert_eq!(security["owner"]["sid"], "S-1-5-32-544");
+    assert_eq!(security["owner"]["name"], "BUILTIN\\Administrators");
+    let dacl = security["dacl"].as_array().unwrap();
+    assert_eq!(dacl[0]["type"], "allow");
+    assert_eq!(dacl[0]["trustee"]["sid"], "S-1-5-18");
+    assert_eq!(dacl[0]["mask"], 0xf003f);
+    assert_eq!(dacl[0]["access"], "KEY_ALL_ACCESS");
+    assert_eq!(dacl[0]["flags"][0], "container inherit");
+
+    let root: Value = serde_json::from_str(&regdump(&["--security", "--format", "json"])).unwrap();
+    assert_eq!(root["security"], keys[0]["security"]);
+}
+
+#[test]
+fn tolerate_malformed_descriptors() {
+    // the only key security record of the test hive is at offset 0x78, and
+    // the offset of the owner is stored at offset 4 of its descriptor
+    let mut data = std::fs::read(testhive()).unwrap();
+    data[0x1000 + 0x78 + 24 + 4..][..4].copy_from_slice(&0xfff0u32.to_le_bytes());
+    let hive = tempfile::NamedTempFile::new().unwrap();
+    std::fs::write(hive.path(), data).unwrap();
+
+    let output = regdump_file(&["--security", "--path", "data-test"], hive.path().to_path_buf());
+
+    assert!(output.contains("; owner: -\n"));
+    assert!(output.contains(
+        "; invalid security descriptor: invalid owner: offset 0xfff0 exceeds the security \
+         descriptor\n"
+    ));
+    assert!(output.contains("; allow S-1-1-0 (Everyone): KEY_READ (container inherit)\n"));
+    assert!(output.contains("\"dword\" = RegDWord:0x0000002a\n"));
+}
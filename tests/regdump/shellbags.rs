@@ -0,0 +1,112 @@
+use assert_cmd::Command;
+use dfir_toolkit::common::bodyfile::{Accessed, Bodyfile3Line, Changed, Created, Modified};
+use serde_json::Value;
+
+use super::data_file;
+
+/// runs `regdump --decode shellbags` for one of the user hives
+fn shellbags(hive: &str, args: &[&str]) -> String {
+    let mut cmd = Command::cargo_bin("regdump").unwrap();
+    let result = cmd
+        .args(["--decode", "shellbags"])
+        .args(args)
+        .arg(data_file("regdump", hive))
+        .ok();
+    assert!(result.is_ok());
+    String::from_utf8(result.unwrap().stdout).unwrap()
+}
+
+fn shellbags_json(hive: &str) -> Vec<Value> {
+    match serde_json::from_str(&shellbags(hive, &["--format", "json"])).unwrap() {
+        Value::Array(shellbags) => shellbags,
+        _ => panic!("shellbags must be an array"),
+    }
+}
+
+fn by_path<'a>(shellbags: &'a [Value], path: &str) -> &'a Value {
+    shellbags.iter().find(|bag| bag["path"] == path).unwrap()
+}
+
+#[test]
+fn usrclass() {
+    let shellbags = shellbags_json("UsrClass.dat");
+    assert_eq!(shellbags.len(), 7);
+    assert_eq!(shellbags[0]["path"], "My Computer");
+    assert_eq!(shellbags[0]["type"], "root");
+    assert_eq!(shellbags[1]["path"], "My Computer\\C:\\");
+    assert_eq!(shellbags[1]["type"], "volume");
+
+    let users = by_path(&shellbags, "My Computer\\C:\\Users");
+    assert_eq!(users["type"], "directory");
+    assert_eq!(users["last_written"], "2024-03-04T07:05:00Z");
+    assert_eq!(users["modified"], "2024-01-10T09:00:00Z");
+    assert_eq!(users["accessed"], "2024-03-04T07:00:00Z");
+    assert_eq!(users["created"], "2019-12-07T09:03:44Z");
+    assert_eq!(
+        users["registry_path"],
+        "ROOT\\Local Settings\\Software\\Microsoft\\Windows\\Shell\\BagMRU\\0\\0\\0"
+    );
+
+    // different versions of the extension block
+    assert_eq!(
+        by_path(&shellbags, "My Computer\\C:\\old")["created"],
+        "2010-01-01T00:00:00Z"
+    );
+}
+
+#[test]
+fn unknown_items_do_not_break_the_path() {
+    let shellbags = shellbags_json("UsrClass.dat");
+    let unknown = by_path(
+        &shellbags,
+        "My Computer\\C:\\Users\\bob\\<unknown shell item type 0x74>",
+    );
+    assert_eq!(unknown["type"], "unknown");
+
+    let loot = by_path(
+        &shellbags,
+        "My Computer\\C:\\Users\\bob\\<unknown shell item type 0x74>\\Loot Folder",
+    );
+    assert_eq!(loot["modified"], "2024-03-04T07:10:00Z");
+    assert_eq!(loot["last_written"], Value::Null);
+}
+
+#[test]
+fn ntuser() {
+    let shellbags = shellbags_json("NTUSER.DAT");
+    assert_eq!(shellbags.len(), 2);
+    assert_eq!(shellbags[1]["path"], "Network\\\\fileserver\\finance");
+    assert_eq!(shellbags[1]["type"], "network");
+}
+
+#[test]
+fn table_output() {
+    let output = shellbags("UsrClass.dat", &[]);
+    let lines: Vec<_> = output.lines().collect();
+    assert_eq!(lines.len(), 8);
+    assert!(lines[0].starts_with("LAST WRITTEN"));
+    assert!(lines[3].ends_with("directory  My Computer\\C:\\Users"));
+}
+
+#[test]
+fn bodyfile_output() {
+    let output = shellbags("UsrClass.dat", &["--format", "bodyfile"]);
+    let line = output
+        .lines()
+        .map(|line| Bodyfile3Line::try_from(line).unwrap())
+        .find(|line| line.get_name() == "ShellBag: 'My Computer\\C:\\Users'")
+        .unwrap();
+    assert_eq!(*line.get_ctime(), Changed::from(1709535900));
+    assert_eq!(*line.get_mtime(), Modified::from(1704877200));
+    assert_eq!(*line.get_atime(), Accessed::from(1709535600));
+    assert_eq!(*line.get_crtime(), Created::from(1575709424));
+}
+
+#[test]
+fn no_shellbags() {
+    let mut cmd = Command::cargo_bin("regdump").unwrap();
+    cmd.args(["--decode", "shellbags"])
+        .arg(data_file("regdump", "SYSTEM_win10"))
+        .assert()
+        .failure();
+}
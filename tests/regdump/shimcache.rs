@@ -0,0 +1,114 @@
+use assert_cmd::Command;
+use dfir_toolkit::common::bodyfile::{Bodyfile3Line, Modified};
+use serde_json::Value;
+
+use super::data_file;
+
+/// runs `regdump --decode shimcache` for one of the SYSTEM hives
+fn shimcache(hive: &str, args: &[&str]) -> String {
+    let mut cmd = Command::cargo_bin("regdump").unwrap();
+    let result = cmd
+        .args(["--decode", "shimcache"])
+        .args(args)
+        .arg(data_file("regdump", hive))
+        .ok();
+    assert!(result.is_ok());
+    String::from_utf8(result.unwrap().stdout).unwrap()
+}
+
+fn shimcache_json(hive: &str, args: &[&str]) -> Vec<Value> {
+    let output = shimcache(hive, &[args, &["--format", "json"]].concat());
+    match serde_json::from_str(&output).unwrap() {
+        Value::Array(entries) => entries,
+        _ => panic!("entries must be an array"),
+    }
+}
+
+#[test]
+fn windows7() {
+    // the hive uses ControlSet002
+    let entries = shimcache_json("SYSTEM_win7", &[]);
+    assert_eq!(entries.len(), 3);
+    assert_eq!(entries[0]["position"], 0);
+    assert_eq!(entries[0]["path"], "C:\\Users\\bob\\Desktop\\evil.exe");
+    assert_eq!(entries[0]["last_modified"], "2024-03-04T07:01:02Z");
+    assert_eq!(entries[0]["executed"], true);
+    assert_eq!(entries[1]["executed"], false);
+    assert_eq!(entries[2]["last_modified"], Value::Null);
+}
+
+#[test]
+fn override_control_set() {
+    let entries = shimcache_json("SYSTEM_win7", &["--control-set", "1"]);
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0]["path"], "C:\\Windows\\system32\\old.exe");
+
+    let mut cmd = Command::cargo_bin("regdump").unwrap();
+    cmd.args(["--decode", "shimcache", "--control-set", "3"])
+        .arg(data_file("regdump", "SYSTEM_win7"))
+        .assert()
+        .failure();
+}
+
+#[test]
+fn windows81() {
+    let entries = shimcache_json("SYSTEM_win81", &[]);
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[1]["path"], "C:\\Temp\\dropper.exe");
+    assert_eq!(entries[1]["last_modified"], "2024-03-02T22:15:00Z");
+    assert_eq!(entries[0]["executed"], true);
+    assert_eq!(entries[1]["executed"], false);
+}
+
+#[test]
+fn windows10() {
+    let entries = shimcache_json("SYSTEM_win10", &[]);
+    assert_eq!(entries.len(), 3);
+    assert_eq!(
+        entries[0]["path"],
+        "C:\\Users\\bob\\AppData\\Local\\Temp\\payload.exe"
+    );
+    assert_eq!(entries[2]["path"], "SYSVOL\\Windows\\System32\\notepad.exe");
+    assert!(entries.iter().all(|e| e.get("executed").is_none()));
+}
+
+#[test]
+fn table_output() {
+    let output = shimcache("SYSTEM_win7", &[]);
+    let lines: Vec<_> = output.lines().collect();
+    assert_eq!(lines.len(), 4);
+    assert!(lines[0].starts_with("POSITION"));
+    assert_eq!(
+        lines[1],
+        "0         2024-03-04T07:01:02Z  yes       C:\\Users\\bob\\Desktop\\evil.exe"
+    );
+}
+
+#[test]
+fn bodyfile_output() {
+    let output = shimcache("SYSTEM_win10", &["--format", "bodyfile"]);
+    let lines: Vec<_> = output
+        .lines()
+        .map(|line| Bodyfile3Line::try_from(line).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 3);
+    assert_eq!(
+        lines[0].get_name(),
+        "ShimCache: 'C:\\Users\\bob\\AppData\\Local\\Temp\\payload.exe' (position 0)"
+    );
+    assert_eq!(*lines[0].get_mtime(), Modified::from(1709535540));
+
+    let output = shimcache("SYSTEM_win7", &["-b"]);
+    assert!(
+        output.contains("ShimCache: 'C:\\Users\\bob\\Desktop\\evil.exe' (position 0, executed)")
+    );
+}
+
+#[test]
+fn no_system_hive() {
+    let mut cmd = Command::cargo_bin("regdump").unwrap();
+    cmd.args(["--decode", "shimcache"])
+        .arg(data_file("regdump", "NTUSER.DAT"))
+        .assert()
+        .failure();
+}
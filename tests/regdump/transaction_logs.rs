@@ -0,0 +1,70 @@
+use std::path::PathBuf;
+
+use assert_cmd::Command;
+
+use super::data_file;
+
+/// a dirty hive, whose transaction logs contain the entries with the
+/// sequence numbers 3 to 5
+fn dirty_hive(name: &str) -> PathBuf {
+    data_file("hivescan", "NewDirtyHive1").join(name)
+}
+
+/// runs regdump for the dirty hive and returns stdout and stderr
+fn regdump_dirty(args: &[&str]) -> (String, String) {
+    let mut cmd = Command::cargo_bin("regdump").unwrap();
+    let output = cmd
+        .args(args)
+        .arg(dirty_hive("NewDirtyHive"))
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    (
+        String::from_utf8(output.stdout).unwrap(),
+        String::from_utf8(output.stderr).unwrap(),
+    )
+}
+
+#[test]
+fn find_transaction_logs() {
+    let (stdout, stderr) = regdump_dirty(&[]);
+    assert!(stdout.contains("\\Key3]"));
+    assert!(!stdout.contains("\\Key1]"));
+    assert!(stderr.contains(
+        "warning: applied 3 log entries with sequence numbers 3 to 5 (3 dirty pages) to the hive, \
+         whose sequence numbers were 3 and 2"
+    ));
+}
+
+#[test]
+fn specify_transaction_logs() {
+    let log1 = dirty_hive("NewDirtyHive.LOG1");
+    let log2 = dirty_hive("NewDirtyHive.LOG2");
+    let (stdout, _) = regdump_dirty(&[
+        "--log",
+        log1.to_str().unwrap(),
+        "--log",
+        log2.to_str().unwrap(),
+    ]);
+    assert!(stdout.contains("\\Key3]"));
+}
+
+#[test]
+fn ignore_transaction_logs() {
+    let (stdout, stderr) = regdump_dirty(&["--no-logs"]);
+    assert!(stdout.contains("\\Key1]"));
+    assert!(!stdout.contains("\\Key3]"));
+    assert!(stderr.is_empty());
+}
+
+#[test]
+fn warn_about_missing_log_entries() {
+    // this log contains only an entry which has already been written to the hive
+    let log1 = dirty_hive("NewDirtyHive.LOG1");
+    let (stdout, stderr) = regdump_dirty(&["--log", log1.to_str().unwrap()]);
+    assert!(stdout.contains("\\Key1]"));
+    assert!(stderr.contains(
+        "warning: the hive is dirty (sequence numbers 3 and 2), but the transaction logs \
+         contain no entry which could be applied"
+    ));
+}
@@ -1,3 +1,5 @@
+#![cfg(feature = "ts2date")]
+
 use std::io::{Cursor, BufReader, BufRead};
 
 use assert_cmd::Command;
@@ -68,4 +70,187 @@ fn ts2date_list2() {
 
     let reader = BufReader::new(Cursor::new(result.unwrap().stdout));
     assert!(reader.lines().map_while(Result::ok).any(|f| f == "Europe/Berlin"));
-}
\ No newline at end of file
+}
+#[test]
+fn ts2date_filetime() {
+    const REGISTRY_OUTPUT: &str = r#"LastWriteTime: 133603191460000000 HKLM\SYSTEM\ControlSet001\Services\bam
+LastWriteTime: 999999999999999999 HKLM\SYSTEM\ControlSet001\Services\bam\State
+Created: 1715845546, LastWriteTime: 133603191460000000
+"#;
+    const REGISTRY_OUTPUT_OUT: &str = r#"LastWriteTime: 2024-05-16T07:45:46+00:00 HKLM\SYSTEM\ControlSet001\Services\bam
+LastWriteTime: 999999999999999999 HKLM\SYSTEM\ControlSet001\Services\bam\State
+Created: 2024-05-16T07:45:46+00:00, LastWriteTime: 133603191460000000
+"#;
+
+    let mut cmd = Command::cargo_bin("ts2date").unwrap();
+    let result = cmd.arg("--filetime").write_stdin(REGISTRY_OUTPUT).ok();
+    assert!(result.is_ok());
+
+    assert_eq!(
+        REGISTRY_OUTPUT_OUT,
+        String::from_utf8(result.unwrap().stdout).unwrap()
+    );
+
+    // without '--filetime', FILETIMEs are not recognized
+    let mut cmd = Command::cargo_bin("ts2date").unwrap();
+    let result = cmd.write_stdin(REGISTRY_OUTPUT).ok();
+    assert!(!String::from_utf8(result.unwrap().stdout)
+        .unwrap()
+        .contains("2024-05-16T07:45:46+00:00 HKLM"));
+}
+
+#[test]
+fn ts2date_webkit() {
+    const HISTORY: &str = r#"13360319146123456|https://www.example.com/|Example Domain
+1715845546|https://www.example.org/|Example Domain
+99999999999999999|https://www.example.net/|Example Domain
+133603191460000000|https://www.example.com/login|Login
+"#;
+    const HISTORY_OUT: &str = r#"2024-05-16T07:45:46+00:00|https://www.example.com/|Example Domain
+2024-05-16T07:45:46+00:00|https://www.example.org/|Example Domain
+99999999999999999|https://www.example.net/|Example Domain
+2024-05-16T07:45:46+00:00|https://www.example.com/login|Login
+"#;
+
+    let mut cmd = Command::cargo_bin("ts2date").unwrap();
+    let result = cmd
+        .args([
+            "--format", "epoch", "--format", "webkit", "--format", "filetime",
+        ])
+        .write_stdin(HISTORY)
+        .ok();
+    assert!(result.is_ok());
+
+    assert_eq!(
+        HISTORY_OUT,
+        String::from_utf8(result.unwrap().stdout).unwrap()
+    );
+
+    // '--webkit' keeps the default format, but FILETIMEs are not recognized
+    let mut cmd = Command::cargo_bin("ts2date").unwrap();
+    let result = cmd.arg("--webkit").write_stdin(HISTORY).ok();
+    let lines: Vec<_> = String::from_utf8(result.unwrap().stdout)
+        .unwrap()
+        .lines()
+        .map(|line| line.split('|').next().unwrap().to_owned())
+        .collect();
+    assert_eq!(
+        lines[..3],
+        [
+            "2024-05-16T07:45:46+00:00",
+            "2024-05-16T07:45:46+00:00",
+            "99999999999999999",
+        ]
+    );
+    assert!(!lines[3].starts_with("2024-05-16T07:45:46+00:00"));
+}
+
+#[test]
+fn ts2date_unit() {
+    const LOG: &str = r#"ts=1710252000 level=info msg="service started"
+ts=1710252000000 level=info msg="service started"
+ts=1710252000000000 level=info msg="service started"
+ts=1710252000000000000 level=info msg="service started"
+ts=4102444800 level=info msg="expires"
+pid=12345678901 level=info msg="no timestamp"
+"#;
+    const LOG_OUT: &str = r#"ts=2024-03-12T15:00:00+01:00 level=info msg="service started"
+ts=2024-03-12T15:00:00+01:00 level=info msg="service started"
+ts=2024-03-12T15:00:00+01:00 level=info msg="service started"
+ts=2024-03-12T15:00:00+01:00 level=info msg="service started"
+ts=4102444800 level=info msg="expires"
+pid=12345678901 level=info msg="no timestamp"
+"#;
+
+    let mut cmd = Command::cargo_bin("ts2date").unwrap();
+    let result = cmd
+        .args(["--unit", "auto", "-t", "Europe/Berlin"])
+        .write_stdin(LOG)
+        .ok();
+    assert!(result.is_ok());
+
+    assert_eq!(LOG_OUT, String::from_utf8(result.unwrap().stdout).unwrap());
+
+    // the plausible window can be extended
+    let mut cmd = Command::cargo_bin("ts2date").unwrap();
+    let result = cmd
+        .args(["--unit", "s", "--max-year", "2100"])
+        .write_stdin("ts=4102444800\n")
+        .ok();
+    assert_eq!(
+        "ts=2100-01-01T00:00:00+00:00\n",
+        String::from_utf8(result.unwrap().stdout).unwrap()
+    );
+
+    let mut cmd = Command::cargo_bin("ts2date").unwrap();
+    cmd.args(["--min-year", "2040", "--max-year", "1990"])
+        .write_stdin("ts=1710252000\n")
+        .assert()
+        .failure();
+}
+
+#[test]
+fn ts2date_apple() {
+    const PLIST: &str = r#"<key>LastUsedDate</key><real>737538346</real>
+<key>CreationDate</key><integer>3798690346</integer>
+"#;
+    const PLIST_OUT: &str = r#"<key>LastUsedDate</key><real>2024-05-16T07:45:46+00:00</real>
+<key>CreationDate</key><integer>2024-05-16T07:45:46+00:00</integer>
+"#;
+
+    let mut cmd = Command::cargo_bin("ts2date").unwrap();
+    let result = cmd
+        .args(["--format", "hfs", "--format", "mac-absolute"])
+        .write_stdin(PLIST)
+        .ok();
+    assert!(result.is_ok());
+
+    assert_eq!(PLIST_OUT, String::from_utf8(result.unwrap().stdout).unwrap());
+}
+
+#[test]
+fn ts2date_output_format() {
+    const EVENTS: &str = "1710252000|logon\n1721988000|logoff\n";
+    const EVENTS_OUT: &str = "12.03.2024 15:00:00 CET (1710252000)|logon
+26.07.2024 12:00:00 CEST (1721988000)|logoff
+";
+
+    let mut cmd = Command::cargo_bin("ts2date").unwrap();
+    let result = cmd
+        .args(["--timezone", "Europe/Berlin", "--keep-original"])
+        .args(["--output-format", "%d.%m.%Y %H:%M:%S %Z"])
+        .write_stdin(EVENTS)
+        .ok();
+    assert!(result.is_ok());
+
+    assert_eq!(EVENTS_OUT, String::from_utf8(result.unwrap().stdout).unwrap());
+
+    let mut cmd = Command::cargo_bin("ts2date").unwrap();
+    cmd.args(["--output-format", "%Q"])
+        .write_stdin(EVENTS)
+        .assert()
+        .failure();
+}
+
+#[test]
+fn ts2date_only_matching() {
+    const LOG: &str = "size=1715845546 start=1710252000 end=1710252060 count=1234567890\n";
+    const LOG_OUT: &str = "size=1715845546 start=2024-03-12T14:00:00+00:00 end=2024-03-12T14:01:00+00:00 count=1234567890\n";
+
+    let mut cmd = Command::cargo_bin("ts2date").unwrap();
+    let result = cmd
+        .args(["--only-matching", r"(?:start|end)=(\d+)"])
+        .write_stdin(LOG)
+        .ok();
+    assert!(result.is_ok());
+
+    assert_eq!(LOG_OUT, String::from_utf8(result.unwrap().stdout).unwrap());
+
+    for pattern in [r"start=\d+", r"(start)=(\d+)", r"start=(\d+"] {
+        let mut cmd = Command::cargo_bin("ts2date").unwrap();
+        cmd.args(["--only-matching", pattern])
+            .write_stdin(LOG)
+            .assert()
+            .failure();
+    }
+}
@@ -1,10 +1,14 @@
+#![cfg(feature = "zip2bodyfile")]
+
 use std::{
-    io::{BufRead, BufReader, Cursor},
-    path::PathBuf,
+    io::{BufRead, BufReader, Cursor, Write},
+    path::{Path, PathBuf},
 };
 
 use assert_cmd::Command;
 use dfir_toolkit::common::bodyfile::{Bodyfile3Line, Accessed, Modified, Changed, Created};
+use tempfile::NamedTempFile;
+use zip::{write::SimpleFileOptions, AesMode, ZipWriter};
 
 #[test]
 fn test_hello() {
@@ -16,6 +20,11 @@ fn test_hello_with_archive_name() {
     do_test_hello(r#"hello.txt (in archive hello.zip), [offset: +01:00]"#, vec!["--show-archive-name"].into_iter());
 }
 
+#[test]
+fn test_hello_with_meta() {
+    do_test_hello(r#"hello.txt, [offset: +01:00], [crc32: af083b2d, method: Stored]"#, vec!["--with-meta"].into_iter());
+}
+
 fn do_test_hello(expected_name: &str, args: impl Iterator<Item = &'static str>) {
     let mut cmd = Command::cargo_bin("zip2bodyfile").unwrap();
     let mut data_path = PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap());
@@ -43,7 +52,8 @@ fn do_test_hello(expected_name: &str, args: impl Iterator<Item = &'static str>)
     assert_eq!(*bfline.get_atime(), Accessed::from(1709194030));
     assert_eq!(*bfline.get_mtime(), Modified::from(1709194030));
     assert_eq!(*bfline.get_ctime(), Changed::default());
-    assert_eq!(*bfline.get_crtime(), Created::from(1709194030));
+    // the extended timestamp of the local header contains no creation time
+    assert_eq!(*bfline.get_crtime(), Created::default());
 
     assert!(lines_iterator.next().is_none());
 }
@@ -105,3 +115,295 @@ fn do_test_hello2(args: impl Iterator<Item = &'static str>) {
 
     assert!(lines_iterator.next().is_none());
 }
+
+fn zip_data(files: &[(&str, &[u8])], password: Option<&str>) -> Vec<u8> {
+    let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+    for (name, data) in files {
+        let options = SimpleFileOptions::default();
+        let options = match password {
+            Some(password) => options.with_aes_encryption(AesMode::Aes256, password),
+            None => options,
+        };
+        writer.start_file(*name, options).unwrap();
+        writer.write_all(data).unwrap();
+    }
+    writer.finish().unwrap().into_inner()
+}
+
+/// writes `outer.zip`, which contains `readme.txt` and `inner.zip`, which
+/// contains `file.txt` and `innermost.zip`
+fn nested_zip_file(inner_password: Option<&str>) -> NamedTempFile {
+    let innermost = zip_data(&[("secret.txt", b"secret")], None);
+    let inner = zip_data(
+        &[("file.txt", b"hello"), ("innermost.zip", &innermost)],
+        inner_password,
+    );
+    let outer = zip_data(&[("readme.txt", b"readme"), ("inner.zip", &inner)], None);
+
+    let mut file = tempfile::Builder::new().suffix(".zip").tempfile().unwrap();
+    file.write_all(&outer).unwrap();
+    file
+}
+
+fn run_nested(inner_password: Option<&str>, args: &[&str]) -> (Vec<String>, String) {
+    let zip_file = nested_zip_file(inner_password);
+    let mut cmd = Command::cargo_bin("zip2bodyfile").unwrap();
+    let result = cmd.arg(zip_file.path()).args(args).ok();
+    assert!(result.is_ok());
+    let output = result.unwrap();
+
+    let names = String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(|line| {
+            let bfline = Bodyfile3Line::try_from(line).unwrap();
+            let name = bfline.get_name();
+            name[..name.find(", [offset").unwrap_or(name.len())].to_owned()
+        })
+        .collect();
+    (names, String::from_utf8(output.stderr).unwrap())
+}
+
+#[test]
+fn test_nested_archives_not_recursed_by_default() {
+    let (names, _) = run_nested(None, &[]);
+    assert_eq!(names, vec!["readme.txt", "inner.zip"]);
+}
+
+#[test]
+fn test_nested_archives() {
+    let (names, stderr) = run_nested(None, &["--recurse-archives", "2"]);
+    assert_eq!(
+        names,
+        vec![
+            "readme.txt",
+            "inner.zip",
+            "inner.zip/file.txt",
+            "inner.zip/innermost.zip",
+            "inner.zip/innermost.zip/secret.txt"
+        ]
+    );
+    assert!(stderr.is_empty());
+}
+
+#[test]
+fn test_nested_archives_depth() {
+    let (names, _) = run_nested(None, &["--recurse-archives", "1"]);
+    assert_eq!(
+        names,
+        vec![
+            "readme.txt",
+            "inner.zip",
+            "inner.zip/file.txt",
+            "inner.zip/innermost.zip"
+        ]
+    );
+}
+
+#[test]
+fn test_encrypted_nested_archive() {
+    let (names, stderr) = run_nested(Some("infected"), &["--recurse-archives", "2"]);
+
+    // the encrypted inner archive is a regular member of the outer archive,
+    // but its members are encrypted, so that the innermost archive cannot be
+    // read
+    assert_eq!(
+        names,
+        vec![
+            "readme.txt",
+            "inner.zip",
+            "inner.zip/file.txt",
+            "inner.zip/innermost.zip"
+        ]
+    );
+    assert!(stderr.contains("warning: unable to read nested archive 'inner.zip/innermost.zip'"));
+}
+
+#[test]
+fn test_nested_size_limit() {
+    let (names, stderr) = run_nested(
+        None,
+        &["--recurse-archives", "2", "--nested-size-limit", "0"],
+    );
+    assert_eq!(names, vec!["readme.txt", "inner.zip"]);
+    assert!(stderr.contains("warning: unable to read nested archive 'inner.zip'"));
+    assert!(stderr.contains("size limit"));
+}
+
+fn data_path(file_name: &str) -> PathBuf {
+    let mut data_path = PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap());
+    data_path.push("tests");
+    data_path.push("data");
+    data_path.push("zip2bodyfile");
+    data_path.push(file_name);
+    data_path
+}
+
+fn run_zip2bodyfile(archive: &Path, args: &[&str]) -> (Vec<Bodyfile3Line>, String) {
+    let mut cmd = Command::cargo_bin("zip2bodyfile").unwrap();
+    let result = cmd.arg(archive).args(args).ok();
+    assert!(result.is_ok());
+    let output = result.unwrap();
+
+    let lines = String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(|line| Bodyfile3Line::try_from(line).unwrap())
+        .collect();
+    (lines, String::from_utf8(output.stderr).unwrap())
+}
+
+#[test]
+fn test_tar_archives() {
+    let long_name = format!(
+        "collection/{}hello.txt",
+        "C/Users/admin/AppData/Roaming/Microsoft/Windows/".repeat(3)
+    );
+
+    // a pax archive, a GNU archive compressed with gzip and a pax archive
+    // compressed with zstd
+    for (file_name, has_pax_times) in [
+        ("hello.tar", true),
+        ("hello.tar.gz", false),
+        ("hello.tar.zst", true),
+    ] {
+        let (lines, stderr) = run_zip2bodyfile(&data_path(file_name), &[]);
+        assert_eq!(lines.len(), 3);
+        assert!(stderr.is_empty());
+
+        assert_eq!(lines[0].get_name(), "collection/");
+        assert_eq!(*lines[0].get_mtime(), Modified::from(1709194000));
+
+        assert_eq!(lines[1].get_name(), "collection/hello.txt");
+        assert_eq!(*lines[1].get_size(), 12);
+        assert_eq!(*lines[1].get_mtime(), Modified::from(1709194030));
+        if has_pax_times {
+            assert_eq!(*lines[1].get_atime(), Accessed::from(1709194031));
+            assert_eq!(*lines[1].get_ctime(), Changed::from(1709194032));
+        } else {
+            assert_eq!(*lines[1].get_atime(), Accessed::default());
+            assert_eq!(*lines[1].get_ctime(), Changed::default());
+        }
+        assert_eq!(*lines[1].get_crtime(), Created::default());
+
+        assert_eq!(lines[2].get_name(), &long_name);
+    }
+}
+
+#[test]
+fn test_tar_archive_with_archive_name() {
+    let (lines, _) = run_zip2bodyfile(&data_path("hello.tar.gz"), &["--show-archive-name"]);
+    assert_eq!(
+        lines[1].get_name(),
+        "collection/hello.txt (in archive hello.tar.gz)"
+    );
+}
+
+fn seven_zip_number(value: u64) -> Vec<u8> {
+    if value < 0x80 {
+        vec![value as u8]
+    } else {
+        assert!(value < 0x4000);
+        vec![0x80 | (value >> 8) as u8, value as u8]
+    }
+}
+
+fn seven_zip_times(times: &[Option<u64>]) -> Vec<u8> {
+    let mut property = vec![0, 0, 0];
+    for (index, time) in times.iter().enumerate() {
+        if let Some(time) = time {
+            property[1] |= 0x80 >> index;
+            property.extend(((time + 11_644_473_600) * 10_000_000).to_le_bytes());
+        }
+    }
+    property
+}
+
+/// creates a 7z archive with a directory and two files, which are stored
+/// uncompressed. If `encode_header` is set, the header is compressed with
+/// LZMA, as 7-Zip does by default
+fn seven_zip_file(encode_header: bool) -> NamedTempFile {
+    let contents = b"helloworld!";
+
+    let mut header = vec![0x01, 0x04];
+    header.extend([0x06, 0x00, 0x01, 0x09, contents.len() as u8, 0x00]);
+    header.extend([0x07, 0x0b, 0x01, 0x00, 0x01, 0x01, 0x00]);
+    header.extend([0x0c, contents.len() as u8, 0x00]);
+    header.extend([0x08, 0x0d, 0x02, 0x09, 0x05, 0x00, 0x00]);
+
+    let mut names = vec![0];
+    for name in ["dir", "dir/a.txt", "dir/b.txt"] {
+        names.extend(name.encode_utf16().chain([0]).flat_map(u16::to_le_bytes));
+    }
+    let mtimes = seven_zip_times(&[Some(1709194000), Some(1709194030), None]);
+    let ctimes = seven_zip_times(&[None, Some(1709190000), None]);
+    header.extend([0x05, 0x03, 0x0e, 0x01, 0b1000_0000, 0x0f, 0x01, 0x00]);
+    for (id, property) in [(0x11, names), (0x14, mtimes), (0x12, ctimes)] {
+        header.push(id);
+        header.extend(seven_zip_number(property.len() as u64));
+        header.extend(property);
+    }
+    header.extend([0x00, 0x00]);
+
+    let mut data = contents.to_vec();
+    if encode_header {
+        let mut compressed = Vec::new();
+        lzma_rs::lzma_compress(&mut &header[..], &mut compressed).unwrap();
+        let (properties, packed) = (&compressed[..5], &compressed[13..]);
+
+        let mut encoded_header = vec![0x17, 0x06];
+        encoded_header.extend(seven_zip_number(data.len() as u64));
+        encoded_header.extend([0x01, 0x09]);
+        encoded_header.extend(seven_zip_number(packed.len() as u64));
+        encoded_header.extend([
+            0x00, 0x07, 0x0b, 0x01, 0x00, 0x01, 0x23, 0x03, 0x01, 0x01, 0x05,
+        ]);
+        encoded_header.extend(properties);
+        encoded_header.push(0x0c);
+        encoded_header.extend(seven_zip_number(header.len() as u64));
+        encoded_header.extend([0x00, 0x00]);
+
+        data.extend(packed);
+        header = encoded_header;
+    }
+
+    let mut file = b"7z\xbc\xaf\x27\x1c\x00\x04\x00\x00\x00\x00".to_vec();
+    file.extend((data.len() as u64).to_le_bytes());
+    file.extend((header.len() as u64).to_le_bytes());
+    file.extend([0; 4]);
+    file.extend(data);
+    file.extend(header);
+
+    let mut archive = tempfile::Builder::new().suffix(".7z").tempfile().unwrap();
+    archive.write_all(&file).unwrap();
+    archive
+}
+
+#[test]
+fn test_seven_zip_archives() {
+    for encode_header in [false, true] {
+        let archive = seven_zip_file(encode_header);
+        let (lines, stderr) = run_zip2bodyfile(archive.path(), &[]);
+
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0].get_name(), "dir");
+        assert_eq!(*lines[0].get_size(), 0);
+        assert_eq!(*lines[0].get_mtime(), Modified::from(1709194000));
+        assert_eq!(*lines[0].get_crtime(), Created::default());
+
+        assert_eq!(lines[1].get_name(), "dir/a.txt");
+        assert_eq!(*lines[1].get_size(), 5);
+        assert_eq!(*lines[1].get_mtime(), Modified::from(1709194030));
+        assert_eq!(*lines[1].get_crtime(), Created::from(1709190000));
+
+        // entries without timestamps are shown with zero timestamps
+        assert_eq!(lines[2].get_name(), "dir/b.txt");
+        assert_eq!(*lines[2].get_size(), 6);
+        assert_eq!(*lines[2].get_mtime(), Modified::from(0));
+        assert_eq!(*lines[2].get_atime(), Accessed::from(0));
+        assert_eq!(*lines[2].get_ctime(), Changed::from(0));
+        assert_eq!(*lines[2].get_crtime(), Created::from(0));
+        assert!(stderr.contains("1 entries of"));
+        assert!(stderr.contains("have no timestamps"));
+    }
+}